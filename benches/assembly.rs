@@ -1,17 +1,22 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use fenris::assembly::global::{CsrAssembler, CsrParAssembler};
-use fenris::assembly::local::{ElementEllipticAssemblerBuilder, QuadratureTable};
-use fenris::assembly::operators::LaplaceOperator;
-use fenris::element::ElementConnectivity;
-use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::assembly::color_elements;
+use fenris::assembly::global::{color_nodes, CsrAssembler, CsrParAssembler, VectorAssembler, VectorParAssembler};
+use fenris::assembly::local::{
+    CachedBasisQuadrature, ElementEllipticAssemblerBuilder, ElementSourceAssemblerBuilder, QuadratureTable,
+    SourceFunction,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
+use fenris::element::{ElementConnectivity, Hex8Element};
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d};
 use fenris::mesh::Mesh;
 use fenris::quadrature::CanonicalStiffnessQuadrature;
 use fenris::SmallDim;
+use fenris_paradis::DisjointSubsets;
 use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
 use fenris_solid::MaterialEllipticOperator;
 use fenris_traits::allocators::DimAllocator;
 use nalgebra::allocator::Allocator;
-use nalgebra::{DVector, DVectorView, DefaultAllocator};
+use nalgebra::{DVector, DVectorView, DefaultAllocator, Point3, Vector1, U1, U3};
 use nalgebra_sparse::pattern::SparsityPattern;
 use nalgebra_sparse::CsrMatrix;
 use std::hint::black_box;
@@ -37,6 +42,29 @@ where
     assembler.assemble_into_csr(matrix, &element_assembler)
 }
 
+fn assemble_poisson_into_par<D, C>(
+    matrix: &mut CsrMatrix<f64>,
+    assembler: &CsrParAssembler<f64>,
+    colors: &[DisjointSubsets],
+    u: DVectorView<f64>,
+    qtable: &(impl QuadratureTable<f64, D, Data = ()> + Sync),
+    mesh: &Mesh<f64, D, C>,
+) -> eyre::Result<()>
+where
+    D: SmallDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D> + Sync,
+    DefaultAllocator: DimAllocator<f64, D>,
+    <DefaultAllocator as Allocator<f64, D>>::Buffer: Sync,
+{
+    let element_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(u)
+        .with_finite_element_space(mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(qtable)
+        .build();
+    assembler.assemble_into_csr(matrix, colors, &element_assembler)
+}
+
 fn assemble_poisson_pattern_serial<D, C>(
     assembler: &CsrAssembler<f64>,
     u: DVectorView<f64>,
@@ -144,6 +172,126 @@ pub fn poisson_assembly_serial(c: &mut Criterion) {
     }
 }
 
+/// Compares serial and parallel assembly of the Poisson stiffness matrix on hex meshes with
+/// up to 1M elements. The number of threads can be controlled with `RAYON_NUM_THREADS`.
+pub fn poisson_hex8_assembly_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("poisson stiffness matrix assembly hex8");
+    group.sample_size(10);
+    let serial_assembler = CsrAssembler::default();
+    let par_assembler = CsrParAssembler::default();
+    for res in [25, 50, 100] {
+        let hex8_mesh = create_unit_box_uniform_hex_mesh_3d(res);
+        let pattern = par_assembler.assemble_pattern(&hex8_mesh);
+        let nnz = pattern.nnz();
+        let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern, vec![0.0; nnz]).unwrap();
+        let u = DVector::repeat(matrix.nrows(), 0.0);
+        let qtable = hex8_mesh.canonical_stiffness_quadrature();
+        let colors: Vec<DisjointSubsets> = color_elements(&hex8_mesh).into();
+        let num_elements = hex8_mesh.connectivity().len();
+        group.bench_function(format!("serial ({num_elements} elements)"), |b| {
+            b.iter(|| {
+                assemble_poisson_into_serial(
+                    &mut matrix,
+                    &serial_assembler,
+                    DVectorView::from(&u),
+                    &qtable,
+                    &hex8_mesh,
+                )
+            })
+        });
+        group.bench_function(format!("parallel ({num_elements} elements)"), |b| {
+            b.iter(|| {
+                assemble_poisson_into_par(
+                    &mut matrix,
+                    &par_assembler,
+                    &colors,
+                    DVectorView::from(&u),
+                    &qtable,
+                    &hex8_mesh,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+struct SinusoidalSource;
+
+impl Operator<f64, U3> for SinusoidalSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U3> for SinusoidalSource {
+    fn evaluate(&self, x: &Point3<f64>, _: &()) -> Vector1<f64> {
+        Vector1::new(x.x.sin() * x.y.cos() * x.z.sin())
+    }
+}
+
+/// Compares serial assembly of a source vector on hex meshes with parallel assembly based on
+/// coloring and on per-thread accumulation. The number of threads can be controlled with `RAYON_NUM_THREADS`.
+pub fn source_vector_hex8_assembly_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("source vector assembly hex8");
+    group.sample_size(10);
+    let serial_assembler = VectorAssembler::default();
+    let par_assembler = VectorParAssembler::default();
+    for res in [25, 50, 100] {
+        let hex8_mesh = create_unit_box_uniform_hex_mesh_3d(res);
+        let qtable = hex8_mesh.canonical_stiffness_quadrature();
+        let colors = color_nodes(&hex8_mesh);
+        let source_assembler = ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(&hex8_mesh)
+            .with_quadrature_table(&qtable)
+            .with_source(&SinusoidalSource)
+            .build();
+        let mut vector = DVector::zeros(hex8_mesh.vertices().len());
+        let num_elements = hex8_mesh.connectivity().len();
+        group.bench_function(format!("serial ({num_elements} elements)"), |b| {
+            b.iter(|| serial_assembler.assemble_vector_into(&mut vector, &source_assembler))
+        });
+        group.bench_function(format!("parallel colored ({num_elements} elements)"), |b| {
+            b.iter(|| par_assembler.assemble_vector_into(&mut vector, &colors, &source_assembler))
+        });
+        group.bench_function(format!("parallel reduction ({num_elements} elements)"), |b| {
+            b.iter(|| par_assembler.assemble_vector_into_by_reduction(&mut vector, &source_assembler))
+        });
+    }
+    group.finish();
+}
+
+/// Compares serial assembly of the Poisson stiffness matrix on hex meshes with and without
+/// precomputed basis gradients.
+pub fn poisson_hex8_assembly_cached_basis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("poisson stiffness matrix assembly hex8 cached basis");
+    group.sample_size(10);
+    let assembler = CsrAssembler::default();
+    for res in [25, 50] {
+        let hex8_mesh = create_unit_box_uniform_hex_mesh_3d(res);
+        let pattern = assembler.assemble_pattern(&hex8_mesh);
+        let nnz = pattern.nnz();
+        let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern, vec![0.0; nnz]).unwrap();
+        let u = DVector::repeat(matrix.nrows(), 0.0);
+        let qtable = hex8_mesh.canonical_stiffness_quadrature();
+        let cached_qtable = CachedBasisQuadrature::new(qtable.clone(), &Hex8Element::reference());
+        let num_elements = hex8_mesh.connectivity().len();
+        group.bench_function(format!("uncached ({num_elements} elements)"), |b| {
+            b.iter(|| assemble_poisson_into_serial(&mut matrix, &assembler, DVectorView::from(&u), &qtable, &hex8_mesh))
+        });
+        group.bench_function(format!("cached ({num_elements} elements)"), |b| {
+            b.iter(|| {
+                assemble_poisson_into_serial(
+                    &mut matrix,
+                    &assembler,
+                    DVectorView::from(&u),
+                    &cached_qtable,
+                    &hex8_mesh,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
 pub fn poisson_pattern_assembly_serial(c: &mut Criterion) {
     let resolutions = vec![5, 10, 20];
     let assembler = CsrAssembler::default();
@@ -243,12 +391,15 @@ pub fn elasticity_3d_pattern_assembly_parallel(c: &mut Criterion) {
 criterion_group!(
     serial_assembly,
     poisson_assembly_serial,
+    poisson_hex8_assembly_cached_basis,
     poisson_pattern_assembly_serial,
     elasticity_3d_pattern_assembly_serial,
 );
 
 criterion_group!(
     parallel_assembly,
+    poisson_hex8_assembly_scaling,
+    source_vector_hex8_assembly_scaling,
     poisson_pattern_assembly_parallel,
     elasticity_3d_pattern_assembly_parallel
 );
@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::space::{
+    interpolate_at_points, interpolate_at_points_par, interpolate_gradient_at_points,
+    interpolate_gradient_at_points_par, SpatiallyIndexed,
+};
+use fenris::util::global_vector_from_point_fn;
+use nalgebra::{DVectorView, Matrix3x1, Point3, Vector1};
+use std::hint::black_box;
+
+/// Points on a regular grid in $[-0.1, 1.1]^3$, so that some of the points lie outside the mesh.
+fn grid_points(points_per_dim: usize) -> Vec<Point3<f64>> {
+    let h = 1.2 / (points_per_dim - 1) as f64;
+    let coord = |i: usize| -0.1 + i as f64 * h;
+    let mut points = Vec::with_capacity(points_per_dim.pow(3));
+    for k in 0..points_per_dim {
+        for j in 0..points_per_dim {
+            for i in 0..points_per_dim {
+                points.push(Point3::new(coord(i), coord(j), coord(k)));
+            }
+        }
+    }
+    points
+}
+
+pub fn hex8_interpolation_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interpolation hex8");
+    group.sample_size(10);
+    let space = SpatiallyIndexed::from_space(create_unit_box_uniform_hex_mesh_3d(20));
+    let u = global_vector_from_point_fn(space.space().vertices(), |p: &Point3<f64>| {
+        Vector1::new(p.x.sin() * p.y.cos() + p.z)
+    });
+    for points_per_dim in [20, 50] {
+        let points = grid_points(points_per_dim);
+        let num_points = points.len();
+        let mut values = vec![Vector1::zeros(); num_points];
+        let mut gradients = vec![Matrix3x1::zeros(); num_points];
+
+        group.bench_function(format!("serial values ({num_points} points)"), |b| {
+            b.iter(|| interpolate_at_points(&space, &points, DVectorView::from(&u), black_box(&mut values)))
+        });
+        group.bench_function(format!("parallel values ({num_points} points)"), |b| {
+            b.iter(|| interpolate_at_points_par(&space, &points, DVectorView::from(&u), black_box(&mut values)))
+        });
+        group.bench_function(format!("serial gradients ({num_points} points)"), |b| {
+            b.iter(|| interpolate_gradient_at_points(&space, &points, DVectorView::from(&u), black_box(&mut gradients)))
+        });
+        group.bench_function(format!("parallel gradients ({num_points} points)"), |b| {
+            b.iter(|| {
+                interpolate_gradient_at_points_par(&space, &points, DVectorView::from(&u), black_box(&mut gradients))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(interpolation, hex8_interpolation_scaling);
+criterion_main!(interpolation);
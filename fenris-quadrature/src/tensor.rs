@@ -1,10 +1,20 @@
 //! 2D and 3D quadrature rules formed by tensor product formulations.
 //!
 //! For quadrilaterals and hexahedra, quadrature rules can be constructed as tensor products
-//! of 1D rules. This module provides rules constructed in this fashion.
+//! of 1D rules. This module provides rules constructed in this fashion. Similarly, rules for
+//! the reference prism can be constructed as the tensor product of a triangle rule and a 1D rule.
+//!
+//! # Point ordering
+//!
+//! The points of a tensor product rule are ordered lexicographically by the indices of the
+//! points in the 1D rules, with the *last* coordinate varying fastest. For example, the points of
+//! a quadrilateral rule with `nx` and `ny` points along the `x` and `y` axes are given by
+//! `(x_i, y_j)` with index `i * ny + j`, and the weight of each point is the product `wx_i * wy_j`
+//! of the 1D weights.
 
-use crate::univariate::gauss;
-use crate::Rule;
+use crate::polyquad;
+use crate::univariate::{gauss, try_gauss_lobatto};
+use crate::{Error, Rule};
 
 /// A Gauss quadrature rule for the reference quadrilateral.
 ///
@@ -12,14 +22,98 @@ use crate::Rule;
 /// points per dimension.
 pub fn quadrilateral_gauss(num_points_per_dim: usize) -> Rule<2> {
     let n = num_points_per_dim;
-    let (weights1d, points1d) = gauss(n);
-    let mut weights2d = Vec::with_capacity(n * n);
-    let mut points2d = Vec::with_capacity(n * n);
+    quadrilateral_gauss_anisotropic(n, n)
+}
 
-    let rule1d_iter = || weights1d.iter().zip(&points1d);
+/// A Gauss quadrature rule for the reference quadrilateral with a different number of points along each axis.
+///
+/// The rule is constructed as a tensor product of 1D Gauss rules with `nx` and `ny` points,
+/// and integrates exactly all monomials `x^p y^q` with `p <= 2 nx - 1` and `q <= 2 ny - 1`.
+/// See the [module-level documentation](self) for the ordering of the points.
+pub fn quadrilateral_gauss_anisotropic(nx: usize, ny: usize) -> Rule<2> {
+    tensor_product_2d(&gauss(nx), &gauss(ny))
+}
 
-    for (&wx, &[x]) in rule1d_iter() {
-        for (&wy, &[y]) in rule1d_iter() {
+/// A Gauss quadrature rule for the reference hexahedron.
+///
+/// The rule is constructed as a tensor product from 1D rules, with the provided number of
+/// points per dimension.
+pub fn hexahedron_gauss(num_points_per_dim: usize) -> Rule<3> {
+    let n = num_points_per_dim;
+    hexahedron_gauss_anisotropic(n, n, n)
+}
+
+/// A Gauss quadrature rule for the reference hexahedron with a different number of points along each axis.
+///
+/// The rule is constructed as a tensor product of 1D Gauss rules with `nx`, `ny` and `nz` points,
+/// and integrates exactly all monomials `x^p y^q z^r` with `p <= 2 nx - 1`, `q <= 2 ny - 1`
+/// and `r <= 2 nz - 1`. See the [module-level documentation](self) for the ordering of the points.
+pub fn hexahedron_gauss_anisotropic(nx: usize, ny: usize, nz: usize) -> Rule<3> {
+    tensor_product_3d(&gauss(nx), &gauss(ny), &gauss(nz))
+}
+
+/// A Gauss-Lobatto quadrature rule for the reference quadrilateral.
+///
+/// The rule is the tensor product of the 1D rule [`try_gauss_lobatto(n)`](try_gauss_lobatto)
+/// with itself. Its points coincide with the nodes of the tensor-product Lagrange element of
+/// degree `n - 1`, i.e. the `Quad4` element for `n = 2` and the `Quad9` element for `n = 3`,
+/// so that mass matrices computed with the matching rule are diagonal. See the
+/// [module-level documentation](self) for the ordering of the points.
+///
+/// Returns `None` if the 1D rule is not available.
+pub fn try_quadrilateral_gauss_lobatto(n: usize) -> Option<Rule<2>> {
+    let rule1d = try_gauss_lobatto(n)?;
+    Some(tensor_product_2d(&rule1d, &rule1d))
+}
+
+/// A Gauss-Lobatto quadrature rule for the reference hexahedron.
+///
+/// The rule is the tensor product of the 1D rule [`try_gauss_lobatto(n)`](try_gauss_lobatto)
+/// with itself. Its points coincide with the nodes of the tensor-product Lagrange element of
+/// degree `n - 1`, i.e. the `Hex8` element for `n = 2` and the `Hex27` element for `n = 3`,
+/// so that mass matrices computed with the matching rule are diagonal. See the
+/// [module-level documentation](self) for the ordering of the points.
+///
+/// Returns `None` if the 1D rule is not available.
+pub fn try_hexahedron_gauss_lobatto(n: usize) -> Option<Rule<3>> {
+    let rule1d = try_gauss_lobatto(n)?;
+    Some(tensor_product_3d(&rule1d, &rule1d, &rule1d))
+}
+
+/// A quadrature rule for the reference prism.
+///
+/// The rule is constructed as the tensor product of the triangle rule with the given strength
+/// (see [`polyquad::triangle`]) and a 1D Gauss rule with `nz` points along the `z` axis. It integrates
+/// exactly all polynomials of the form `p(x, y) z^r` with `p` of total degree at most `triangle_strength`
+/// and `r <= 2 nz - 1`. The points are ordered as described in the
+/// [module-level documentation](self), with the index of the triangle point varying slowest.
+///
+/// # Errors
+///
+/// Returns an error if there is no triangle rule available with sufficient strength.
+pub fn prism_gauss(triangle_strength: usize, nz: usize) -> Result<Rule<3>, Error> {
+    let (weights_xy, points_xy) = polyquad::triangle(triangle_strength)?;
+    let (weights_z, points_z) = gauss(nz);
+    let mut weights3d = Vec::with_capacity(weights_xy.len() * nz);
+    let mut points3d = Vec::with_capacity(weights_xy.len() * nz);
+
+    for (&w_xy, &[x, y]) in weights_xy.iter().zip(&points_xy) {
+        for (&wz, &[z]) in weights_z.iter().zip(&points_z) {
+            weights3d.push(w_xy * wz);
+            points3d.push([x, y, z]);
+        }
+    }
+
+    Ok((weights3d, points3d))
+}
+
+fn tensor_product_2d((weights_x, points_x): &Rule<1>, (weights_y, points_y): &Rule<1>) -> Rule<2> {
+    let n = weights_x.len() * weights_y.len();
+    let mut weights2d = Vec::with_capacity(n);
+    let mut points2d = Vec::with_capacity(n);
+
+    for (&wx, &[x]) in weights_x.iter().zip(points_x) {
+        for (&wy, &[y]) in weights_y.iter().zip(points_y) {
             let w = wx * wy;
             weights2d.push(w);
             points2d.push([x, y]);
@@ -29,21 +123,18 @@ pub fn quadrilateral_gauss(num_points_per_dim: usize) -> Rule<2> {
     (weights2d, points2d)
 }
 
-/// A Gauss quadrature rule for the reference hexahedron.
-///
-/// The rule is constructed as a tensor product from 1D rules, with the provided number of
-/// points per dimension.
-pub fn hexahedron_gauss(num_points_per_dim: usize) -> Rule<3> {
-    let n = num_points_per_dim;
-    let (weights1d, points1d) = gauss(n);
-    let mut weights3d = Vec::with_capacity(n * n * n);
-    let mut points3d = Vec::with_capacity(n * n * n);
-
-    let rule1d_iter = || weights1d.iter().zip(&points1d);
+fn tensor_product_3d(
+    (weights_x, points_x): &Rule<1>,
+    (weights_y, points_y): &Rule<1>,
+    (weights_z, points_z): &Rule<1>,
+) -> Rule<3> {
+    let n = weights_x.len() * weights_y.len() * weights_z.len();
+    let mut weights3d = Vec::with_capacity(n);
+    let mut points3d = Vec::with_capacity(n);
 
-    for (&wx, &[x]) in rule1d_iter() {
-        for (&wy, &[y]) in rule1d_iter() {
-            for (&wz, &[z]) in rule1d_iter() {
+    for (&wx, &[x]) in weights_x.iter().zip(points_x) {
+        for (&wy, &[y]) in weights_y.iter().zip(points_y) {
+            for (&wz, &[z]) in weights_z.iter().zip(points_z) {
                 let w = wx * wy * wz;
                 weights3d.push(w);
                 points3d.push([x, y, z]);
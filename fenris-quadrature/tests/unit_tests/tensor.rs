@@ -1,5 +1,10 @@
 use fenris_quadrature::integrate;
-use fenris_quadrature::tensor::{hexahedron_gauss, quadrilateral_gauss};
+use fenris_quadrature::polyquad::triangle;
+use fenris_quadrature::tensor::{
+    hexahedron_gauss, hexahedron_gauss_anisotropic, prism_gauss, quadrilateral_gauss, quadrilateral_gauss_anisotropic,
+    try_hexahedron_gauss_lobatto, try_quadrilateral_gauss_lobatto,
+};
+use fenris_quadrature::univariate::{gauss, try_gauss_lobatto};
 use matrixcompare::assert_scalar_eq;
 
 #[test]
@@ -56,3 +61,149 @@ fn hexahedral_gauss_rules_satisfy_expected_accuracy() {
         }
     }
 }
+
+/// The exact integral of x^alpha over the reference interval [-1, 1].
+fn monomial_integral_1d(alpha: i32) -> f64 {
+    (1.0 - (-1.0f64).powi(alpha + 1)) / (alpha as f64 + 1.0)
+}
+
+fn factorial(n: i32) -> f64 {
+    (1..=n).map(|i| i as f64).product()
+}
+
+fn binomial(n: i32, k: i32) -> f64 {
+    factorial(n) / (factorial(k) * factorial(n - k))
+}
+
+/// The exact integral of x^alpha y^beta over the reference triangle.
+fn monomial_integral_triangle(alpha: i32, beta: i32) -> f64 {
+    // With x = 2u - 1 and y = 2v - 1, the integral becomes 4 times the integral of
+    // (2u - 1)^alpha (2v - 1)^beta over the unit triangle, on which the integral of
+    // u^i v^j is i! j! / (i + j + 2)!
+    let mut integral = 0.0;
+    for i in 0..=alpha {
+        for j in 0..=beta {
+            let coeff =
+                binomial(alpha, i) * binomial(beta, j) * 2.0f64.powi(i + j) * (-1.0f64).powi(alpha - i + beta - j);
+            integral += coeff * factorial(i) * factorial(j) / factorial(i + j + 2);
+        }
+    }
+    4.0 * integral
+}
+
+#[test]
+fn anisotropic_quadrilateral_gauss_rules_satisfy_expected_accuracy() {
+    for nx in 1..=6 {
+        for ny in 1..=6 {
+            let rule = quadrilateral_gauss_anisotropic(nx, ny);
+            assert_eq!(rule.0.len(), nx * ny);
+            assert!(rule.0.iter().all(|&w| w > 0.0));
+            assert_scalar_eq!(rule.0.iter().sum::<f64>(), 4.0, comp = abs, tol = 1e-14);
+
+            for alpha in 0..=(2 * nx - 1) as i32 {
+                for beta in 0..=(2 * ny - 1) as i32 {
+                    let expected = monomial_integral_1d(alpha) * monomial_integral_1d(beta);
+                    let estimated = integrate(&rule, |&[x, y]| x.powi(alpha) * y.powi(beta));
+                    assert_scalar_eq!(estimated, expected, comp = abs, tol = 1e-14);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn anisotropic_hexahedron_gauss_rules_satisfy_expected_accuracy() {
+    for (nx, ny, nz) in [(1, 2, 3), (3, 3, 2), (2, 1, 4), (4, 3, 1)] {
+        let rule = hexahedron_gauss_anisotropic(nx, ny, nz);
+        assert_eq!(rule.0.len(), nx * ny * nz);
+        assert!(rule.0.iter().all(|&w| w > 0.0));
+        assert_scalar_eq!(rule.0.iter().sum::<f64>(), 8.0, comp = abs, tol = 1e-14);
+
+        for alpha in 0..=(2 * nx - 1) as i32 {
+            for beta in 0..=(2 * ny - 1) as i32 {
+                for gamma in 0..=(2 * nz - 1) as i32 {
+                    let expected =
+                        monomial_integral_1d(alpha) * monomial_integral_1d(beta) * monomial_integral_1d(gamma);
+                    let estimated = integrate(&rule, |&[x, y, z]| x.powi(alpha) * y.powi(beta) * z.powi(gamma));
+                    assert_scalar_eq!(estimated, expected, comp = abs, tol = 1e-13);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn anisotropic_rules_have_documented_point_ordering() {
+    let (weights_x, points_x) = gauss(3);
+    let (weights_y, points_y) = gauss(2);
+    let (weights, points) = quadrilateral_gauss_anisotropic(3, 2);
+    for i in 0..3 {
+        for j in 0..2 {
+            assert_eq!(points[i * 2 + j], [points_x[i][0], points_y[j][0]]);
+            assert_eq!(weights[i * 2 + j], weights_x[i] * weights_y[j]);
+        }
+    }
+
+    // The isotropic rules coincide with the anisotropic rules
+    assert_eq!(quadrilateral_gauss(3), quadrilateral_gauss_anisotropic(3, 3));
+    assert_eq!(hexahedron_gauss(3), hexahedron_gauss_anisotropic(3, 3, 3));
+}
+
+#[test]
+fn prism_gauss_rules_satisfy_expected_accuracy() {
+    for triangle_strength in 0..=8 {
+        for nz in 1..=4 {
+            let rule = prism_gauss(triangle_strength, nz).unwrap();
+            assert_eq!(rule.0.len(), triangle(triangle_strength).unwrap().0.len() * nz);
+            // The reference prism has volume 2 * 2 = 4
+            assert_scalar_eq!(rule.0.iter().sum::<f64>(), 4.0, comp = abs, tol = 1e-13);
+
+            for alpha in 0..=triangle_strength as i32 {
+                for beta in 0..=(triangle_strength as i32 - alpha) {
+                    for gamma in 0..=(2 * nz - 1) as i32 {
+                        let expected = monomial_integral_triangle(alpha, beta) * monomial_integral_1d(gamma);
+                        let estimated = integrate(&rule, |&[x, y, z]| x.powi(alpha) * y.powi(beta) * z.powi(gamma));
+                        assert_scalar_eq!(estimated, expected, comp = abs, tol = 1e-13);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn gauss_lobatto_tensor_rules_satisfy_expected_accuracy() {
+    assert!(try_quadrilateral_gauss_lobatto(1).is_none());
+    assert!(try_hexahedron_gauss_lobatto(1).is_none());
+
+    for n in 2..=6 {
+        // Gauss-Lobatto rules with n points integrate polynomials of degree 2n - 3 exactly
+        let degree = (2 * n - 3) as i32;
+        let (weights_1d, points_1d) = try_gauss_lobatto(n).unwrap();
+
+        let rule = try_quadrilateral_gauss_lobatto(n).unwrap();
+        assert_eq!(rule.0.len(), n * n);
+        assert_eq!(rule.1[0], [points_1d[0][0], points_1d[0][0]]);
+        assert_eq!(rule.0[n + 1], weights_1d[1] * weights_1d[1]);
+        for alpha in 0..=degree {
+            for beta in 0..=degree {
+                let expected = monomial_integral_1d(alpha) * monomial_integral_1d(beta);
+                let estimated = integrate(&rule, |&[x, y]| x.powi(alpha) * y.powi(beta));
+                assert_scalar_eq!(estimated, expected, comp = abs, tol = 1e-13);
+            }
+        }
+
+        let rule = try_hexahedron_gauss_lobatto(n).unwrap();
+        assert_eq!(rule.0.len(), n * n * n);
+        for alpha in 0..=degree {
+            for beta in 0..=degree {
+                for gamma in 0..=degree {
+                    let expected =
+                        monomial_integral_1d(alpha) * monomial_integral_1d(beta) * monomial_integral_1d(gamma);
+                    let estimated = integrate(&rule, |&[x, y, z]| x.powi(alpha) * y.powi(beta) * z.powi(gamma));
+                    assert_scalar_eq!(estimated, expected, comp = abs, tol = 1e-13);
+                }
+            }
+        }
+    }
+}
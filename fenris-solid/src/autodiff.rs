@@ -0,0 +1,195 @@
+//! Automatic differentiation of hyperelastic materials.
+//!
+//! Implementing a [`HyperelasticMaterial`] requires analytic expressions for the stress tensor
+//! and the stress contraction, which are tedious and error-prone to derive. [`AutodiffMaterial`]
+//! instead only requires the energy density, and derives the stress tensor and the stress
+//! contraction by forward-mode automatic differentiation with [`HyperDual`] numbers.
+//!
+//! The derivatives are exact up to round-off errors, but considerably more expensive to
+//! compute than hand-written analytic expressions. This makes automatic differentiation
+//! mostly useful for prototyping new constitutive models and for verifying analytic
+//! implementations.
+//!
+//! Since [`HyperDual`] implements [`Real`], existing material models that are generic over
+//! the scalar type can be reused for the energy density:
+//!
+//! ```
+//! use fenris::nalgebra::{Matrix3, Vector3};
+//! use fenris_solid::autodiff::{AutodiffMaterial, HyperDual};
+//! use fenris_solid::materials::{LameParameters, NeoHookeanMaterial};
+//! use fenris_solid::HyperelasticMaterial;
+//!
+//! let material = AutodiffMaterial::from_energy_fn(
+//!     |deformation_gradient: &Matrix3<HyperDual<f64>>, parameters: &LameParameters<f64>| {
+//!         let parameters = LameParameters {
+//!             mu: HyperDual::constant(parameters.mu),
+//!             lambda: HyperDual::constant(parameters.lambda),
+//!         };
+//!         NeoHookeanMaterial.compute_energy_density(deformation_gradient, &parameters)
+//!     },
+//! );
+//!
+//! let parameters = LameParameters { mu: 1.0, lambda: 10.0 };
+//! let f = Matrix3::new(1.1, 0.1, 0.0, 0.0, 0.9, 0.2, 0.0, 0.0, 1.0);
+//! let stress = material.compute_stress_tensor(&f, &parameters);
+//! let contraction = material.compute_stress_contraction(&f, &Vector3::x(), &Vector3::y(), &parameters);
+//! ```
+use crate::HyperelasticMaterial;
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DefaultAllocator, DimName, OMatrix, OVector};
+use fenris::Real;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+mod hyper_dual;
+
+pub use hyper_dual::HyperDual;
+
+/// An energy density $\psi(\vec F)$ that can be evaluated with [`HyperDual`] numbers.
+pub trait EnergyDensity<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: DimName,
+    DefaultAllocator: DimAllocator<HyperDual<T>, GeometryDim>,
+{
+    type Parameters: Clone + Default + 'static;
+
+    /// Compute the energy density $\psi = \psi(\vec F)$.
+    fn compute_energy_density(
+        &self,
+        deformation_gradient: &OMatrix<HyperDual<T>, GeometryDim, GeometryDim>,
+        parameters: &Self::Parameters,
+    ) -> HyperDual<T>;
+}
+
+/// An energy density given by a closure.
+///
+/// See [`AutodiffMaterial::from_energy_fn`].
+pub struct EnergyDensityFn<F, Parameters> {
+    f: F,
+    marker: PhantomData<fn(&Parameters)>,
+}
+
+impl<F, Parameters> EnergyDensityFn<F, Parameters> {
+    pub fn new(f: F) -> Self {
+        Self { f, marker: PhantomData }
+    }
+}
+
+impl<F: Clone, Parameters> Clone for EnergyDensityFn<F, Parameters> {
+    fn clone(&self) -> Self {
+        Self::new(self.f.clone())
+    }
+}
+
+impl<F, Parameters> Debug for EnergyDensityFn<F, Parameters> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnergyDensityFn").finish_non_exhaustive()
+    }
+}
+
+impl<T, GeometryDim, F, Parameters> EnergyDensity<T, GeometryDim> for EnergyDensityFn<F, Parameters>
+where
+    T: Real,
+    GeometryDim: DimName,
+    F: Fn(&OMatrix<HyperDual<T>, GeometryDim, GeometryDim>, &Parameters) -> HyperDual<T>,
+    Parameters: Clone + Default + 'static,
+    DefaultAllocator: DimAllocator<HyperDual<T>, GeometryDim>,
+{
+    type Parameters = Parameters;
+
+    fn compute_energy_density(
+        &self,
+        deformation_gradient: &OMatrix<HyperDual<T>, GeometryDim, GeometryDim>,
+        parameters: &Self::Parameters,
+    ) -> HyperDual<T> {
+        (self.f)(deformation_gradient, parameters)
+    }
+}
+
+/// A hyperelastic material defined only by its energy density, with stress and stress
+/// contraction computed by automatic differentiation.
+///
+/// The stress tensor is computed with one evaluation of the energy density for each component
+/// of $\vec P$, in which the $\epsilon_1$ part of the corresponding component of $\vec F$ is seeded.
+/// Each entry $(i, j)$ of the stress contraction is the second directional derivative
+/// $$
+/// \mathcal{C}\_{\vec P}(\vec F, \vec a, \vec b)\_{ij}
+///     = \frac{\partial^2 \psi}{\partial F_{ik} \partial F_{jm}} a_k b_m,
+/// $$
+/// which is obtained from a single evaluation of the energy density by seeding the $\epsilon_1$ part
+/// with $\vec e_i \otimes \vec a$ and the $\epsilon_2$ part with $\vec e_j \otimes \vec b$.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct AutodiffMaterial<E> {
+    energy: E,
+}
+
+impl<E> AutodiffMaterial<E> {
+    pub fn new(energy: E) -> Self {
+        Self { energy }
+    }
+
+    pub fn energy(&self) -> &E {
+        &self.energy
+    }
+}
+
+impl<F, Parameters> AutodiffMaterial<EnergyDensityFn<F, Parameters>> {
+    /// Constructs a material from a closure that computes the energy density.
+    pub fn from_energy_fn(f: F) -> Self {
+        Self::new(EnergyDensityFn::new(f))
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, D, E> HyperelasticMaterial<T, D> for AutodiffMaterial<E>
+where
+    T: Real,
+    D: DimName,
+    E: EnergyDensity<T, D>,
+    DefaultAllocator: DimAllocator<T, D> + DimAllocator<HyperDual<T>, D>,
+{
+    type Parameters = E::Parameters;
+
+    fn compute_energy_density(&self, deformation_gradient: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let F = deformation_gradient.map(HyperDual::constant);
+        self.energy.compute_energy_density(&F, parameters).re
+    }
+
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let mut F = deformation_gradient.map(HyperDual::constant);
+        OMatrix::<T, D, D>::from_fn(|i, j| {
+            F[(i, j)].e1 = T::one();
+            let P_ij = self.energy.compute_energy_density(&F, parameters).e1;
+            F[(i, j)].e1 = T::zero();
+            P_ij
+        })
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let F = deformation_gradient.map(HyperDual::constant);
+        OMatrix::<T, D, D>::from_fn(|i, j| {
+            let mut F_seeded = F.clone();
+            for k in 0..D::dim() {
+                F_seeded[(i, k)].e1 = a[k];
+                F_seeded[(j, k)].e2 = b[k];
+            }
+            self.energy
+                .compute_energy_density(&F_seeded, parameters)
+                .e12
+        })
+    }
+}
@@ -0,0 +1,686 @@
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+use fenris::nalgebra::{ComplexField, Field, RealField, SimdValue};
+use fenris::Real;
+use num_traits::{FromPrimitive, Num, One, Signed, Zero};
+use simba::scalar::{SubsetOf, SupersetOf};
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign};
+
+/// A hyper-dual number $x + x_1 \epsilon_1 + x_2 \epsilon_2 + x_{12} \epsilon_1 \epsilon_2$ for forward-mode
+/// automatic differentiation.
+///
+/// The infinitesimal parts satisfy $\epsilon_1^2 = \epsilon_2^2 = 0$, but $\epsilon_1 \epsilon_2 \neq 0$.
+/// Evaluating a function $f$ at $\vec x + \vec u \epsilon_1 + \vec v \epsilon_2$ therefore gives
+/// $$
+/// f(\vec x) + \nabla f(\vec x) \cdot \vec u \\, \epsilon_1 + \nabla f(\vec x) \cdot \vec v \\, \epsilon_2
+///     + \vec u^T \nabla^2 f(\vec x) \\, \vec v \\, \epsilon_1 \epsilon_2,
+/// $$
+/// so that a single evaluation yields first and second directional derivatives exactly, without
+/// any truncation error.
+///
+/// `HyperDual<T>` implements [`RealField`], so any function that is generic over
+/// [`Real`](fenris::Real) can be differentiated with it.
+///
+/// Comparisons only take into account the real part. Consequently, branches in the
+/// differentiated function are resolved according to the real part, and the derivatives are those of
+/// the taken branch.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HyperDual<T> {
+    /// The real part.
+    pub re: T,
+    /// The coefficient of $\epsilon_1$.
+    pub e1: T,
+    /// The coefficient of $\epsilon_2$.
+    pub e2: T,
+    /// The coefficient of $\epsilon_1 \epsilon_2$.
+    pub e12: T,
+}
+
+impl<T: Real> HyperDual<T> {
+    pub fn new(re: T, e1: T, e2: T, e12: T) -> Self {
+        Self { re, e1, e2, e12 }
+    }
+
+    /// A hyper-dual number with vanishing infinitesimal parts.
+    pub fn constant(re: T) -> Self {
+        Self::new(re, T::zero(), T::zero(), T::zero())
+    }
+
+    /// Applies a scalar function $f$, given $f(x)$, $f'(x)$ and $f''(x)$ at the real part $x$.
+    fn chain(&self, f: T, df: T, d2f: T) -> Self {
+        Self {
+            re: f,
+            e1: df * self.e1,
+            e2: df * self.e2,
+            e12: df * self.e12 + d2f * self.e1 * self.e2,
+        }
+    }
+
+    fn scale_by(&self, s: T) -> Self {
+        Self::new(self.re * s, self.e1 * s, self.e2 * s, self.e12 * s)
+    }
+
+    fn has_infinitesimal_part(&self) -> bool {
+        !(self.e1.is_zero() && self.e2.is_zero() && self.e12.is_zero())
+    }
+}
+
+impl<T: Real> From<T> for HyperDual<T> {
+    fn from(re: T) -> Self {
+        Self::constant(re)
+    }
+}
+
+impl<T: Real> Display for HyperDual<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {}ε1 + {}ε2 + {}ε1ε2", self.re, self.e1, self.e2, self.e12)
+    }
+}
+
+impl<T: Real> PartialEq for HyperDual<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.re == other.re
+    }
+}
+
+impl<T: Real> PartialOrd for HyperDual<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.re.partial_cmp(&other.re)
+    }
+}
+
+impl<T: Real> Neg for HyperDual<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.e1, -self.e2, -self.e12)
+    }
+}
+
+impl<T: Real> Add for HyperDual<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.e1 + rhs.e1, self.e2 + rhs.e2, self.e12 + rhs.e12)
+    }
+}
+
+impl<T: Real> Sub for HyperDual<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.e1 - rhs.e1, self.e2 - rhs.e2, self.e12 - rhs.e12)
+    }
+}
+
+impl<T: Real> Mul for HyperDual<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re,
+            self.re * rhs.e1 + self.e1 * rhs.re,
+            self.re * rhs.e2 + self.e2 * rhs.re,
+            self.re * rhs.e12 + self.e1 * rhs.e2 + self.e2 * rhs.e1 + self.e12 * rhs.re,
+        )
+    }
+}
+
+impl<T: Real> Div for HyperDual<T> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.recip()
+    }
+}
+
+impl<T: Real> Rem for HyperDual<T> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        // x % y = x - trunc(x / y) * y, where the truncated quotient is piecewise constant
+        let quotient = (self.re / rhs.re).trunc();
+        let mut result = self - rhs.scale_by(quotient);
+        result.re = self.re % rhs.re;
+        result
+    }
+}
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl<T: Real> $trait for HyperDual<T> {
+            fn $method(&mut self, rhs: Self) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+impl_assign_op!(AddAssign, add_assign, +);
+impl_assign_op!(SubAssign, sub_assign, -);
+impl_assign_op!(MulAssign, mul_assign, *);
+impl_assign_op!(DivAssign, div_assign, /);
+impl_assign_op!(RemAssign, rem_assign, %);
+
+impl<T: Real> Zero for HyperDual<T> {
+    fn zero() -> Self {
+        Self::constant(T::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.re.is_zero()
+    }
+}
+
+impl<T: Real> One for HyperDual<T> {
+    fn one() -> Self {
+        Self::constant(T::one())
+    }
+}
+
+impl<T: Real> Num for HyperDual<T> {
+    type FromStrRadixErr = T::FromStrRadixErr;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        T::from_str_radix(str, radix).map(Self::constant)
+    }
+}
+
+impl<T: Real> Signed for HyperDual<T> {
+    fn abs(&self) -> Self {
+        if self.re.is_sign_negative() {
+            -*self
+        } else {
+            *self
+        }
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self <= other {
+            Self::zero()
+        } else {
+            *self - *other
+        }
+    }
+
+    fn signum(&self) -> Self {
+        Self::constant(Signed::signum(&self.re))
+    }
+
+    fn is_positive(&self) -> bool {
+        self.re.is_positive()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.re.is_negative()
+    }
+}
+
+impl<T: Real> FromPrimitive for HyperDual<T> {
+    fn from_i64(n: i64) -> Option<Self> {
+        T::from_i64(n).map(Self::constant)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        T::from_u64(n).map(Self::constant)
+    }
+
+    fn from_f32(n: f32) -> Option<Self> {
+        T::from_f32(n).map(Self::constant)
+    }
+
+    fn from_f64(n: f64) -> Option<Self> {
+        T::from_f64(n).map(Self::constant)
+    }
+}
+
+impl<T: Real> AbsDiffEq for HyperDual<T> {
+    type Epsilon = Self;
+
+    fn default_epsilon() -> Self {
+        Self::constant(T::default_epsilon())
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self) -> bool {
+        self.re.abs_diff_eq(&other.re, epsilon.re)
+    }
+}
+
+impl<T: Real> RelativeEq for HyperDual<T> {
+    fn default_max_relative() -> Self {
+        Self::constant(T::default_max_relative())
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Self, max_relative: Self) -> bool {
+        self.re.relative_eq(&other.re, epsilon.re, max_relative.re)
+    }
+}
+
+impl<T: Real> UlpsEq for HyperDual<T> {
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Self, max_ulps: u32) -> bool {
+        self.re.ulps_eq(&other.re, epsilon.re, max_ulps)
+    }
+}
+
+impl<T: Real> SimdValue for HyperDual<T> {
+    type Element = Self;
+    type SimdBool = bool;
+
+    fn lanes() -> usize {
+        1
+    }
+
+    fn splat(val: Self) -> Self {
+        val
+    }
+
+    fn extract(&self, _: usize) -> Self {
+        *self
+    }
+
+    unsafe fn extract_unchecked(&self, _: usize) -> Self {
+        *self
+    }
+
+    fn replace(&mut self, _: usize, val: Self) {
+        *self = val
+    }
+
+    unsafe fn replace_unchecked(&mut self, _: usize, val: Self) {
+        *self = val
+    }
+
+    fn select(self, cond: bool, other: Self) -> Self {
+        if cond {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+impl<T: Real> SubsetOf<HyperDual<T>> for HyperDual<T> {
+    fn to_superset(&self) -> Self {
+        *self
+    }
+
+    fn from_superset_unchecked(element: &Self) -> Self {
+        *element
+    }
+
+    fn is_in_subset(_: &Self) -> bool {
+        true
+    }
+}
+
+impl<T: Real> SubsetOf<HyperDual<T>> for f64 {
+    fn to_superset(&self) -> HyperDual<T> {
+        HyperDual::constant(SupersetOf::<f64>::from_subset(self))
+    }
+
+    fn from_superset_unchecked(element: &HyperDual<T>) -> Self {
+        SupersetOf::<f64>::to_subset_unchecked(&element.re)
+    }
+
+    fn is_in_subset(element: &HyperDual<T>) -> bool {
+        !element.has_infinitesimal_part() && SupersetOf::<f64>::is_in_subset(&element.re)
+    }
+}
+
+impl<T: Real> Field for HyperDual<T> {}
+
+impl<T: Real> ComplexField for HyperDual<T> {
+    type RealField = Self;
+
+    fn from_real(re: Self) -> Self {
+        re
+    }
+
+    fn real(self) -> Self {
+        self
+    }
+
+    fn imaginary(self) -> Self {
+        Self::zero()
+    }
+
+    fn modulus(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn modulus_squared(self) -> Self {
+        self * self
+    }
+
+    fn argument(self) -> Self {
+        if self.re >= T::zero() {
+            Self::zero()
+        } else {
+            Self::pi()
+        }
+    }
+
+    fn norm1(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn scale(self, factor: Self) -> Self {
+        self * factor
+    }
+
+    fn unscale(self, factor: Self) -> Self {
+        self / factor
+    }
+
+    fn signum(self) -> Self {
+        Signed::signum(&self)
+    }
+
+    fn floor(self) -> Self {
+        Self::constant(self.re.floor())
+    }
+
+    fn ceil(self) -> Self {
+        Self::constant(self.re.ceil())
+    }
+
+    fn round(self) -> Self {
+        Self::constant(self.re.round())
+    }
+
+    fn trunc(self) -> Self {
+        Self::constant(self.re.trunc())
+    }
+
+    fn fract(self) -> Self {
+        Self {
+            re: self.re.fract(),
+            ..self
+        }
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn abs(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        (self * self + other * other).sqrt()
+    }
+
+    fn recip(self) -> Self {
+        let r = self.re.recip();
+        self.chain(r, -r * r, (r + r) * r * r)
+    }
+
+    fn conjugate(self) -> Self {
+        self
+    }
+
+    fn sin(self) -> Self {
+        let (s, c) = self.re.sin_cos();
+        self.chain(s, c, -s)
+    }
+
+    fn cos(self) -> Self {
+        let (s, c) = self.re.sin_cos();
+        self.chain(c, -s, -c)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    fn tan(self) -> Self {
+        let t = self.re.tan();
+        let dt = T::one() + t * t;
+        self.chain(t, dt, (t + t) * dt)
+    }
+
+    fn asin(self) -> Self {
+        let x = self.re;
+        let s = T::one() - x * x;
+        let df = s.sqrt().recip();
+        self.chain(x.asin(), df, x * df / s)
+    }
+
+    fn acos(self) -> Self {
+        let x = self.re;
+        let s = T::one() - x * x;
+        let df = s.sqrt().recip();
+        self.chain(x.acos(), -df, -x * df / s)
+    }
+
+    fn atan(self) -> Self {
+        let x = self.re;
+        let df = (T::one() + x * x).recip();
+        self.chain(x.atan(), df, -(x + x) * df * df)
+    }
+
+    fn sinh(self) -> Self {
+        let (s, c) = (self.re.sinh(), self.re.cosh());
+        self.chain(s, c, s)
+    }
+
+    fn cosh(self) -> Self {
+        let (s, c) = (self.re.sinh(), self.re.cosh());
+        self.chain(c, s, c)
+    }
+
+    fn tanh(self) -> Self {
+        let t = self.re.tanh();
+        let dt = T::one() - t * t;
+        self.chain(t, dt, -(t + t) * dt)
+    }
+
+    fn asinh(self) -> Self {
+        let x = self.re;
+        let s = x * x + T::one();
+        let df = s.sqrt().recip();
+        self.chain(x.asinh(), df, -x * df / s)
+    }
+
+    fn acosh(self) -> Self {
+        let x = self.re;
+        let s = x * x - T::one();
+        let df = s.sqrt().recip();
+        self.chain(x.acosh(), df, -x * df / s)
+    }
+
+    fn atanh(self) -> Self {
+        let x = self.re;
+        let df = (T::one() - x * x).recip();
+        self.chain(x.atanh(), df, (x + x) * df * df)
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.ln().scale_by(T::ln_2().recip())
+    }
+
+    fn log10(self) -> Self {
+        self.ln().scale_by(T::ln_10().recip())
+    }
+
+    fn ln(self) -> Self {
+        let r = self.re.recip();
+        self.chain(self.re.ln(), r, -r * r)
+    }
+
+    fn ln_1p(self) -> Self {
+        let r = (T::one() + self.re).recip();
+        self.chain(self.re.ln_1p(), r, -r * r)
+    }
+
+    fn sqrt(self) -> Self {
+        let s = self.re.sqrt();
+        let df = (s + s).recip();
+        self.chain(s, df, -df / (s + s) / s)
+    }
+
+    fn exp(self) -> Self {
+        let e = self.re.exp();
+        self.chain(e, e, e)
+    }
+
+    fn exp2(self) -> Self {
+        let e = self.re.exp2();
+        let ln_2 = T::ln_2();
+        self.chain(e, ln_2 * e, ln_2 * ln_2 * e)
+    }
+
+    fn exp_m1(self) -> Self {
+        let e = self.re.exp();
+        self.chain(self.re.exp_m1(), e, e)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        match n {
+            0 => Self::one(),
+            1 => self,
+            _ => {
+                let x = self.re;
+                let n_t = T::from_i32(n).unwrap();
+                let x_n2 = x.powi(n - 2);
+                self.chain(x_n2 * x * x, n_t * x_n2 * x, n_t * (n_t - T::one()) * x_n2)
+            }
+        }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        if n.has_infinitesimal_part() {
+            (self.ln() * n).exp()
+        } else if n.re.is_zero() {
+            Self::one()
+        } else {
+            let x = self.re;
+            let n = n.re;
+            let x_n2 = x.powf(n - T::one() - T::one());
+            self.chain(x.powf(n), n * x_n2 * x, n * (n - T::one()) * x_n2)
+        }
+    }
+
+    fn powc(self, n: Self) -> Self {
+        self.powf(n)
+    }
+
+    fn cbrt(self) -> Self {
+        let c = self.re.cbrt();
+        let three = T::from_i32(3).unwrap();
+        let df = (three * c * c).recip();
+        self.chain(c, df, -(df + df) / (three * self.re))
+    }
+
+    fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.e1.is_finite() && self.e2.is_finite() && self.e12.is_finite()
+    }
+
+    fn try_sqrt(self) -> Option<Self> {
+        (self.re >= T::zero()).then(|| self.sqrt())
+    }
+}
+
+macro_rules! real_constants {
+    ($($name:ident),*) => {
+        $(
+            fn $name() -> Self {
+                Self::constant(T::$name())
+            }
+        )*
+    };
+}
+
+impl<T: Real> RealField for HyperDual<T> {
+    fn is_sign_positive(&self) -> bool {
+        self.re.is_sign_positive()
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        self.re.is_sign_negative()
+    }
+
+    fn copysign(self, sign: Self) -> Self {
+        if self.re.is_sign_negative() == sign.re.is_sign_negative() {
+            self
+        } else {
+            -self
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self.re >= other.re {
+            self
+        } else {
+            other
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if self.re <= other.re {
+            self
+        } else {
+            other
+        }
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        if self < min {
+            min
+        } else if self > max {
+            max
+        } else {
+            self
+        }
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        // atan2(y, x) differs from atan(y / x) (or -atan(x / y)) only by a piecewise constant,
+        // so the derivatives agree. We pick the better conditioned quotient
+        let mut result = if ComplexField::abs(other.re) >= ComplexField::abs(self.re) {
+            (self / other).atan()
+        } else {
+            -(other / self).atan()
+        };
+        result.re = self.re.atan2(other.re);
+        result
+    }
+
+    fn min_value() -> Option<Self> {
+        T::min_value().map(Self::constant)
+    }
+
+    fn max_value() -> Option<Self> {
+        T::max_value().map(Self::constant)
+    }
+
+    real_constants!(
+        pi,
+        two_pi,
+        frac_pi_2,
+        frac_pi_3,
+        frac_pi_4,
+        frac_pi_6,
+        frac_pi_8,
+        frac_1_pi,
+        frac_2_pi,
+        frac_2_sqrt_pi,
+        e,
+        log2_e,
+        log10_e,
+        ln_2,
+        ln_10
+    );
+}
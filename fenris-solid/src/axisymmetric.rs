@@ -0,0 +1,150 @@
+use crate::materials::LameParameters;
+use eyre::bail;
+use fenris::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use fenris::error::AssemblyError;
+use fenris::nalgebra::{DMatrixViewMut, Dyn, Matrix4, OMatrix, Point2, U2, U4};
+use fenris::space::{FiniteElementConnectivity, FiniteElementSpace};
+use fenris::{Real, Symmetry};
+use numeric_literals::replace_float_literals;
+
+/// An element assembler for the stiffness matrix of axisymmetric linear elasticity.
+///
+/// The elements of the space are given in the $(r, z)$ half-plane, where the first coordinate is
+/// the radial coordinate $r \geq 0$, and the displacement $(u_r, u_z)$ does not depend on the angle
+/// $\theta$. In addition to the in-plane strains, the strain has the hoop component
+/// $\varepsilon_{\theta \theta} = u_r / r$, and with the strain vector
+/// $(\varepsilon_{rr}, \varepsilon_{zz}, \varepsilon_{\theta \theta}, \gamma_{rz})$ the element stiffness
+/// matrix is
+/// <div>$$
+///   \vec K^K = \int_K \vec B^T \vec D \vec B \\, 2 \pi r \\, \mathrm{d}r \\, \mathrm{d}z,
+/// $$</div>
+/// where $\vec D$ is the isotropic elasticity matrix given by the Lamé parameters associated with
+/// each quadrature point. The quadrature table must therefore provide the *unweighted*
+/// two-dimensional quadrature, without the factor $2 \pi r$
+/// (see [`AxisymmetricQuadratureTable`](fenris::assembly::local::AxisymmetricQuadratureTable)).
+///
+/// The hoop strain involves division by $r$, so the radial coordinate must be positive at all
+/// quadrature points, and an error is returned otherwise. Elements may touch the axis $r = 0$,
+/// since quadrature points are usually located in the interior of the element. In this case, the
+/// radial displacement must be constrained to zero on the axis, as required by symmetry.
+#[derive(Debug)]
+pub struct AxisymmetricElasticityAssembler<'a, Space, QTable: ?Sized> {
+    space: &'a Space,
+    qtable: &'a QTable,
+}
+
+impl<'a, Space, QTable: ?Sized> AxisymmetricElasticityAssembler<'a, Space, QTable> {
+    pub fn new(space: &'a Space, qtable: &'a QTable) -> Self {
+        Self { space, qtable }
+    }
+}
+
+impl<'a, Space, QTable> ElementConnectivityAssembler for AxisymmetricElasticityAssembler<'a, Space, QTable>
+where
+    Space: FiniteElementConnectivity,
+    QTable: ?Sized,
+{
+    fn solution_dim(&self) -> usize {
+        2
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+/// The elasticity matrix relating the strains $(\varepsilon_{rr}, \varepsilon_{zz}, \varepsilon_{\theta \theta},
+/// \gamma_{rz})$ to the stresses $(\sigma_{rr}, \sigma_{zz}, \sigma_{\theta \theta}, \sigma_{rz})$.
+#[rustfmt::skip]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+fn axisymmetric_elasticity_matrix<T: Real>(parameters: &LameParameters<T>) -> Matrix4<T> {
+    let &LameParameters { mu, lambda } = parameters;
+    let a = lambda + 2.0 * mu;
+    Matrix4::new(
+        a,      lambda, lambda, 0.0,
+        lambda, a,      lambda, 0.0,
+        lambda, lambda, a,      0.0,
+        0.0,    0.0,    0.0,    mu,
+    )
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for AxisymmetricElasticityAssembler<'a, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T, GeometryDim = U2, ReferenceDim = U2>,
+    QTable: ?Sized + QuadratureTable<T, U2, Data = LameParameters<T>>,
+{
+    #[allow(non_snake_case)]
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let n = self.space.element_node_count(element_index);
+        assert_eq!(output.nrows(), 2 * n, "Output matrix dimension mismatch");
+        assert_eq!(output.ncols(), 2 * n, "Output matrix dimension mismatch");
+
+        let num_points = self.qtable.element_quadrature_size(element_index);
+        let mut points = vec![Point2::origin(); num_points];
+        let mut weights = vec![T::zero(); num_points];
+        let mut parameters = vec![LameParameters::default(); num_points];
+        self.qtable
+            .populate_element_quadrature_and_data(element_index, &mut points, &mut weights, &mut parameters);
+
+        let mut basis_values = vec![T::zero(); n];
+        let mut ref_gradients = OMatrix::<T, U2, Dyn>::zeros(n);
+        let mut B = OMatrix::<T, U4, Dyn>::zeros(2 * n);
+        output.fill(T::zero());
+
+        for (q, ((xi, &w), lame)) in points.iter().zip(&weights).zip(&parameters).enumerate() {
+            self.space
+                .populate_element_basis(element_index, &mut basis_values, xi);
+            self.space
+                .populate_element_gradients(element_index, ref_gradients.as_view_mut(), xi);
+            let J = self.space.element_reference_jacobian(element_index, xi);
+            let J_det = J.determinant();
+            let J_inv_t = J
+                .try_inverse()
+                .ok_or(AssemblyError::SingularJacobian { element_index })?
+                .transpose();
+            let r = self.space.map_element_reference_coords(element_index, xi).x;
+            if r <= T::zero() {
+                bail!(
+                    "Quadrature point {} of element {} has non-positive radial coordinate {:?}",
+                    q,
+                    element_index,
+                    r
+                );
+            }
+
+            // Gradients with respect to (r, z)
+            let G = J_inv_t * &ref_gradients;
+            for (i, phi_i) in basis_values.iter().enumerate() {
+                let (dphi_dr, dphi_dz) = (G[(0, i)], G[(1, i)]);
+                B[(0, 2 * i)] = dphi_dr;
+                B[(1, 2 * i + 1)] = dphi_dz;
+                B[(2, 2 * i)] = *phi_i / r;
+                B[(3, 2 * i)] = dphi_dz;
+                B[(3, 2 * i + 1)] = dphi_dr;
+            }
+
+            let D = axisymmetric_elasticity_matrix(lame);
+            let scale = w * J_det.abs() * T::two_pi() * r;
+            output += B.transpose() * (D * &B) * scale;
+        }
+
+        Ok(())
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
@@ -0,0 +1,454 @@
+//! Calibration of material parameters from homogeneous stress-strain experiments.
+//!
+//! Given stress-strain data from uniaxial or biaxial tension/compression tests, the functionality
+//! in this module fits the parameters of a [`HyperelasticMaterial`] by nonlinear least squares.
+//! Each sample is assumed to correspond to a *homogeneous* deformation of the specimen, so that
+//! the stress predicted by the material model can be obtained by directly evaluating the
+//! stress tensor for the deformation gradient associated with the sample.
+//!
+//! All stresses are nominal stresses, i.e. components of the First Piola-Kirchhoff stress
+//! tensor $\vec P$, which corresponds to the force per unit *undeformed* area commonly reported
+//! in experiments. The material is assumed to be isotropic.
+use crate::materials::LameParameters;
+use crate::HyperelasticMaterial;
+use fenris::nalgebra::{DMatrix, DVector, Matrix3, Vector3, U3};
+use fenris::Real;
+use numeric_literals::replace_float_literals;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+
+/// Material parameters that can be calibrated.
+///
+/// Calibration works with the parameters as a flat vector of real numbers. Parameters that
+/// should not be calibrated can simply be omitted from the vector, in which case they retain
+/// their initial values.
+pub trait CalibrationParameters<T>: Clone {
+    /// Names of the parameters, in the same order as in the parameter vector.
+    fn parameter_names(&self) -> Vec<&'static str>;
+
+    /// Returns the calibrated parameters as a vector.
+    fn to_parameter_vector(&self) -> DVector<T>;
+
+    /// Updates the calibrated parameters from the given vector.
+    ///
+    /// # Panics
+    ///
+    /// Implementations may panic if the length of the vector is not equal to the number of
+    /// calibrated parameters.
+    fn update_from_parameter_vector(&mut self, values: &[T]);
+}
+
+impl<T: Real> CalibrationParameters<T> for LameParameters<T> {
+    fn parameter_names(&self) -> Vec<&'static str> {
+        vec!["mu", "lambda"]
+    }
+
+    fn to_parameter_vector(&self) -> DVector<T> {
+        DVector::from_column_slice(&[self.mu, self.lambda])
+    }
+
+    fn update_from_parameter_vector(&mut self, values: &[T]) {
+        assert_eq!(values.len(), 2, "Lamé parameters consist of exactly two parameters");
+        self.mu = values[0];
+        self.lambda = values[1];
+    }
+}
+
+/// A sample from a uniaxial test.
+///
+/// The specimen is stretched along the first coordinate axis, and the lateral surfaces
+/// are traction-free.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniaxialSample<T> {
+    /// The stretch $\lambda$ along the loading direction.
+    pub stretch: T,
+    /// The nominal stress along the loading direction.
+    pub stress: T,
+}
+
+/// A sample from a biaxial test.
+///
+/// The specimen is stretched along the first and second coordinate axes, and the surfaces
+/// normal to the third coordinate axis are traction-free.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BiaxialSample<T> {
+    /// The stretches $\lambda_1, \lambda_2$ along the two loading directions.
+    pub stretches: [T; 2],
+    /// The nominal stresses along the two loading directions.
+    pub stresses: [T; 2],
+}
+
+/// A sample from a homogeneous stress-strain experiment.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StressStrainSample<T> {
+    Uniaxial(UniaxialSample<T>),
+    Biaxial(BiaxialSample<T>),
+}
+
+impl<T> From<UniaxialSample<T>> for StressStrainSample<T> {
+    fn from(sample: UniaxialSample<T>) -> Self {
+        Self::Uniaxial(sample)
+    }
+}
+
+impl<T> From<BiaxialSample<T>> for StressStrainSample<T> {
+    fn from(sample: BiaxialSample<T>) -> Self {
+        Self::Biaxial(sample)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// No samples were provided.
+    NoSamples,
+    /// The inner Newton solve for the unknown lateral stretch of a sample did not converge.
+    LateralStretchNotConverged,
+    /// The material produced a non-finite stress for the initial parameters.
+    InvalidInitialParameters,
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationError::NoSamples => write!(f, "No samples were provided for calibration."),
+            CalibrationError::LateralStretchNotConverged => {
+                write!(f, "Failed to determine lateral stretch for zero lateral stress.")
+            }
+            CalibrationError::InvalidInitialParameters => {
+                write!(
+                    f,
+                    "The material produced non-finite stresses for the initial parameters."
+                )
+            }
+        }
+    }
+}
+
+impl Error for CalibrationError {}
+
+/// Maximum number of iterations for the inner Newton solve for the lateral stretch.
+const MAX_LATERAL_STRETCH_ITERATIONS: usize = 100;
+
+/// Solves for the stretch along the given lateral directions such that the lateral stress vanishes.
+///
+/// The deformation gradient is diagonal, with the given diagonal entries along the loading
+/// directions. By isotropy, the stretches along all lateral directions are equal.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn solve_for_lateral_stretch<T, M>(
+    material: &M,
+    parameters: &M::Parameters,
+    mut diagonal: Vector3<T>,
+    lateral_directions: &[usize],
+    initial_stretch: T,
+) -> Result<Matrix3<T>, CalibrationError>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    let k = lateral_directions[0];
+    let e = |i| Vector3::ith(i, T::one());
+    let mut stretch = initial_stretch;
+    for _ in 0..MAX_LATERAL_STRETCH_ITERATIONS {
+        for &l in lateral_directions {
+            diagonal[l] = stretch;
+        }
+        let F = Matrix3::from_diagonal(&diagonal);
+        let residual = material.compute_stress_tensor(&F, parameters)[(k, k)];
+        // d P_kk / d stretch = sum_l d P_kk / d F_ll, and the stress contraction gives
+        // C(F, e_k, e_l)_kl = d P_kk / d F_ll
+        let derivative = lateral_directions
+            .iter()
+            .map(|&l| material.compute_stress_contraction(&F, &e(k), &e(l), parameters)[(k, l)])
+            .fold(T::zero(), |sum, d| sum + d);
+
+        if residual == T::zero() {
+            return Ok(F);
+        }
+        if !residual.is_finite() || !derivative.is_finite() || derivative == T::zero() {
+            return Err(CalibrationError::LateralStretchNotConverged);
+        }
+
+        // Damp the step so that the stretch remains positive
+        let mut step = -residual / derivative;
+        while stretch + step <= T::zero() {
+            step *= 0.5;
+        }
+        stretch += step;
+
+        if step.abs() <= 1e-14 * stretch {
+            for &l in lateral_directions {
+                diagonal[l] = stretch;
+            }
+            return Ok(Matrix3::from_diagonal(&diagonal));
+        }
+    }
+    Err(CalibrationError::LateralStretchNotConverged)
+}
+
+/// Computes the deformation gradient for a uniaxial test with the given stretch.
+///
+/// The deformation gradient is $\vec F = \operatorname{diag}(\lambda, \lambda_t, \lambda_t)$,
+/// where the lateral stretch $\lambda_t$ is determined by a Newton solve such that the lateral
+/// stress vanishes. The initial guess $\lambda_t = 1 / \sqrt{\lambda}$ corresponds to an
+/// incompressible material, which makes the solve robust also for nearly incompressible materials.
+///
+/// # Errors
+///
+/// Returns an error if the Newton solve for the lateral stretch does not converge.
+pub fn uniaxial_deformation_gradient<T, M>(
+    material: &M,
+    parameters: &M::Parameters,
+    stretch: T,
+) -> Result<Matrix3<T>, CalibrationError>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    let diagonal = Vector3::new(stretch, T::one(), T::one());
+    let initial_stretch = T::one() / stretch.sqrt();
+    solve_for_lateral_stretch(material, parameters, diagonal, &[1, 2], initial_stretch)
+}
+
+/// Computes the deformation gradient for a biaxial test with the given stretches.
+///
+/// The deformation gradient is $\vec F = \operatorname{diag}(\lambda_1, \lambda_2, \lambda_3)$,
+/// where the stretch $\lambda_3$ is determined by a Newton solve such that the stress normal
+/// to the loading plane vanishes. The initial guess corresponds to an incompressible material.
+///
+/// # Errors
+///
+/// Returns an error if the Newton solve for the out-of-plane stretch does not converge.
+pub fn biaxial_deformation_gradient<T, M>(
+    material: &M,
+    parameters: &M::Parameters,
+    stretches: [T; 2],
+) -> Result<Matrix3<T>, CalibrationError>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    let [lambda1, lambda2] = stretches;
+    let diagonal = Vector3::new(lambda1, lambda2, T::one());
+    let initial_stretch = T::one() / (lambda1 * lambda2);
+    solve_for_lateral_stretch(material, parameters, diagonal, &[2], initial_stretch)
+}
+
+/// Computes the residuals (predicted minus measured stresses) for all samples.
+fn compute_residuals<T, M>(
+    material: &M,
+    parameters: &M::Parameters,
+    samples: &[StressStrainSample<T>],
+) -> Result<DVector<T>, CalibrationError>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    let mut residuals = Vec::new();
+    for sample in samples {
+        match sample {
+            StressStrainSample::Uniaxial(sample) => {
+                let f = uniaxial_deformation_gradient(material, parameters, sample.stretch)?;
+                let p = material.compute_stress_tensor(&f, parameters);
+                residuals.push(p[(0, 0)] - sample.stress);
+            }
+            StressStrainSample::Biaxial(sample) => {
+                let f = biaxial_deformation_gradient(material, parameters, sample.stretches)?;
+                let p = material.compute_stress_tensor(&f, parameters);
+                residuals.push(p[(0, 0)] - sample.stresses[0]);
+                residuals.push(p[(1, 1)] - sample.stresses[1]);
+            }
+        }
+    }
+    Ok(DVector::from_vec(residuals))
+}
+
+/// Settings for the Levenberg-Marquardt algorithm used for calibration.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LevenbergMarquardtSettings<T> {
+    pub max_iterations: usize,
+    /// Relative tolerance for the parameter update.
+    ///
+    /// The iteration terminates when $\\| \delta \vec p \\| \leq \epsilon (\\| \vec p \\| + \epsilon)$.
+    pub tolerance: T,
+    /// The initial damping parameter $\mu$ in the damped normal equations
+    /// $(\vec J^T \vec J + \mu \operatorname{diag}(\vec J^T \vec J)) \delta \vec p = - \vec J^T \vec r$.
+    pub initial_damping: T,
+}
+
+impl<T: Real> Default for LevenbergMarquardtSettings<T> {
+    fn default() -> Self {
+        Self {
+            max_iterations: 200,
+            tolerance: T::from_f64(1e-12).unwrap(),
+            initial_damping: T::from_f64(1e-3).unwrap(),
+        }
+    }
+}
+
+/// The result of a material calibration.
+#[derive(Debug, Clone)]
+pub struct CalibrationReport<T: Real, Parameters> {
+    /// The fitted material parameters.
+    pub parameters: Parameters,
+    /// Names of the calibrated parameters.
+    pub parameter_names: Vec<&'static str>,
+    /// Values of the calibrated parameters, in the same order as `parameter_names`.
+    pub parameter_values: Vec<T>,
+    /// Residuals (predicted minus measured stress) for each measured stress component.
+    pub residuals: DVector<T>,
+    /// The Euclidean norm of the residual vector.
+    pub residual_norm: T,
+    /// The number of Levenberg-Marquardt iterations performed.
+    pub iterations: usize,
+    /// Whether the iteration converged within the maximum number of iterations.
+    pub converged: bool,
+}
+
+/// Fits the parameters of a material to the given stress-strain samples.
+///
+/// The parameters are determined by minimizing the sum of squared differences between the
+/// stresses predicted by the material and the measured stresses with the Levenberg-Marquardt
+/// algorithm. Derivatives with respect to the parameters are approximated by central finite
+/// differences, so that no additional functionality is required of the material.
+///
+/// Note that a single kind of experiment may not be sufficient to determine all parameters.
+/// For example, the uniaxial response of a linear elastic material depends only on Young's modulus,
+/// so that the Lamé parameters can only be identified if biaxial samples are also provided.
+///
+/// # Errors
+///
+/// Returns an error if no samples are given, or if the predicted stresses cannot be
+/// computed for the initial parameters.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn calibrate_material_parameters<T, M>(
+    material: &M,
+    initial_parameters: &M::Parameters,
+    samples: &[StressStrainSample<T>],
+    settings: &LevenbergMarquardtSettings<T>,
+) -> Result<CalibrationReport<T, M::Parameters>, CalibrationError>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+    M::Parameters: CalibrationParameters<T>,
+{
+    if samples.is_empty() {
+        return Err(CalibrationError::NoSamples);
+    }
+
+    let mut parameters = initial_parameters.clone();
+    let mut p = parameters.to_parameter_vector();
+    let evaluate_residuals = |parameters: &mut M::Parameters, p: &DVector<T>| {
+        parameters.update_from_parameter_vector(p.as_slice());
+        compute_residuals(material, parameters, samples)
+            .ok()
+            .filter(|r| r.iter().all(|r_i| r_i.is_finite()))
+    };
+
+    let mut r = evaluate_residuals(&mut parameters, &p).ok_or(CalibrationError::InvalidInitialParameters)?;
+    let mut cost = 0.5 * r.norm_squared();
+    let num_params = p.len();
+    let fd_scale = T::default_epsilon().powf(1.0 / 3.0);
+
+    let mut damping: Option<T> = None;
+    let mut damping_growth = 2.0;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < settings.max_iterations {
+        iterations += 1;
+
+        // Approximate the Jacobian of the residuals with central differences
+        let mut jacobian = DMatrix::zeros(r.len(), num_params);
+        for i in 0..num_params {
+            let h = fd_scale * T::max(p[i].abs(), 1.0);
+            let mut p_plus = p.clone();
+            let mut p_minus = p.clone();
+            p_plus[i] += h;
+            p_minus[i] -= h;
+            match (
+                evaluate_residuals(&mut parameters, &p_plus),
+                evaluate_residuals(&mut parameters, &p_minus),
+            ) {
+                (Some(r_plus), Some(r_minus)) => jacobian.set_column(i, &((r_plus - r_minus) / (2.0 * h))),
+                (Some(r_plus), None) => jacobian.set_column(i, &((r_plus - &r) / h)),
+                (None, Some(r_minus)) => jacobian.set_column(i, &((&r - r_minus) / h)),
+                (None, None) => {}
+            }
+        }
+
+        let jtj = jacobian.transpose() * &jacobian;
+        let gradient = jacobian.transpose() * &r;
+        if cost == T::zero() || gradient.amax() == T::zero() {
+            converged = true;
+            break;
+        }
+
+        // Marquardt's scaling of the damping term, with a floor on the scaling to handle
+        // parameters that (locally) do not influence the residuals
+        let max_diag = jtj.diagonal().amax();
+        let scaling = jtj
+            .diagonal()
+            .map(|d| T::max(d, T::default_epsilon() * max_diag));
+        let mu = *damping.get_or_insert(settings.initial_damping);
+
+        let mut system = jtj.clone();
+        for i in 0..num_params {
+            system[(i, i)] += mu * scaling[i];
+        }
+        let delta = match system.cholesky() {
+            Some(cholesky) => -cholesky.solve(&gradient),
+            None => {
+                damping = Some(mu * damping_growth);
+                damping_growth *= 2.0;
+                continue;
+            }
+        };
+
+        let p_new = &p + &delta;
+        let step_accepted = match evaluate_residuals(&mut parameters, &p_new) {
+            Some(r_new) => {
+                let cost_new = 0.5 * r_new.norm_squared();
+                // The reduction predicted by the linearized model
+                let predicted_reduction = -(delta.dot(&gradient) + 0.5 * delta.dot(&(&jtj * &delta)));
+                let rho = (cost - cost_new) / predicted_reduction;
+                if cost_new < cost && rho > 0.0 {
+                    // Nielsen's update strategy for the damping parameter
+                    let factor = T::max(1.0 / 3.0, 1.0 - (2.0 * rho - 1.0).powi(3));
+                    damping = Some(mu * factor);
+                    damping_growth = 2.0;
+                    p = p_new;
+                    r = r_new;
+                    cost = cost_new;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        };
+
+        if !step_accepted {
+            damping = Some(mu * damping_growth);
+            damping_growth *= 2.0;
+        }
+
+        if delta.norm() <= settings.tolerance * (p.norm() + settings.tolerance) {
+            converged = true;
+            break;
+        }
+    }
+
+    parameters.update_from_parameter_vector(p.as_slice());
+    Ok(CalibrationReport {
+        parameter_names: parameters.parameter_names(),
+        parameter_values: p.iter().copied().collect(),
+        parameters,
+        residual_norm: r.norm(),
+        residuals: r,
+        iterations,
+        converged,
+    })
+}
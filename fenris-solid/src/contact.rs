@@ -0,0 +1,371 @@
+//! Penalty-based contact against rigid obstacles.
+//!
+//! A rigid obstacle is described by a signed distance function $\phi$, which is negative inside
+//! the obstacle. A node with reference position $\vec X$ and displacement $\vec u$ has the
+//! *gap* $g = \phi(\vec X + \vec u)$, and is in contact with the obstacle if $g \leq 0$.
+//! The penalty method adds the energy
+//! <div>$$
+//!   E_c = \sum_{i : g_i \leq 0} \frac{\kappa}{2} g_i^2
+//! $$</div>
+//! to the total potential energy of the body, where $\kappa$ is the penalty stiffness.
+//! The associated contact force on a penetrating node is $-\kappa g \nabla \phi$, which pushes the
+//! node out of the obstacle. At equilibrium, the penetration depth is therefore inversely
+//! proportional to the penalty stiffness.
+//!
+//! [`PenaltyContactAssembler`] implements the element assembler traits over one-node "elements",
+//! so that contact contributions can be combined with the elastic contributions by summation,
+//! or with an [`AggregateElementAssembler`](fenris::assembly::local::AggregateElementAssembler).
+use eyre::eyre;
+use fenris::allocators::DimAllocator;
+use fenris::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, ElementVectorAssembler};
+use fenris::geometry::sdf::SignedDistanceFunction2d;
+use fenris::geometry::SignedDistance;
+use fenris::nalgebra::{
+    DMatrixViewMut, DVectorView, DVectorViewMut, DefaultAllocator, DimName, OPoint, OVector, Point2, Unit, U2,
+};
+use fenris::Real;
+
+/// A rigid obstacle described by a signed distance function $\phi$.
+pub trait ContactObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Computes the signed distance $\phi(\vec x)$, which is negative inside the obstacle.
+    fn signed_distance(&self, x: &OPoint<T, D>) -> T;
+
+    /// Computes the gradient $\nabla \phi(\vec x)$ of the signed distance.
+    ///
+    /// Returns `None` if the gradient is not defined at $\vec x$.
+    fn signed_distance_gradient(&self, x: &OPoint<T, D>) -> Option<OVector<T, D>>;
+}
+
+/// An obstacle occupying the half-space behind a plane.
+///
+/// The normal points out of the obstacle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub point: OPoint<T, D>,
+    pub normal: Unit<OVector<T, D>>,
+}
+
+impl<T, D> PlaneObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn from_point_and_normal(point: OPoint<T, D>, normal: Unit<OVector<T, D>>) -> Self {
+        Self { point, normal }
+    }
+}
+
+impl<T, D> ContactObstacle<T, D> for PlaneObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn signed_distance(&self, x: &OPoint<T, D>) -> T {
+        self.normal.dot(&(x - &self.point))
+    }
+
+    fn signed_distance_gradient(&self, _x: &OPoint<T, D>) -> Option<OVector<T, D>> {
+        Some(self.normal.clone_owned())
+    }
+}
+
+/// A solid ball (a disk in 2D, a sphere in 3D).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BallObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub center: OPoint<T, D>,
+    pub radius: T,
+}
+
+impl<T, D> ContactObstacle<T, D> for BallObstacle<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn signed_distance(&self, x: &OPoint<T, D>) -> T {
+        (x - &self.center).norm() - self.radius
+    }
+
+    fn signed_distance_gradient(&self, x: &OPoint<T, D>) -> Option<OVector<T, D>> {
+        let y = x - &self.center;
+        let y_norm = y.norm();
+        (y_norm > T::zero()).then(|| y / y_norm)
+    }
+}
+
+/// An obstacle given by a [`SignedDistanceFunction2d`], such as
+/// [`SdfCircle`](fenris::geometry::sdf::SdfCircle) or unions of such functions.
+#[derive(Debug, Clone)]
+pub struct SdfObstacle<Sdf>(pub Sdf);
+
+impl<T, Sdf> ContactObstacle<T, U2> for SdfObstacle<Sdf>
+where
+    T: Real,
+    Sdf: SignedDistanceFunction2d<T>,
+{
+    fn signed_distance(&self, x: &Point2<T>) -> T {
+        self.0.eval(x)
+    }
+
+    fn signed_distance_gradient(&self, x: &Point2<T>) -> Option<OVector<T, U2>> {
+        self.0.gradient(x)
+    }
+}
+
+/// An obstacle given by a geometry that supports closest-point [`SignedDistance`] queries,
+/// such as polygons, polyhedra and surface meshes.
+///
+/// The gradient of the signed distance is computed from the direction to the closest point.
+#[derive(Debug, Clone)]
+pub struct ClosestPointObstacle<Geometry>(pub Geometry);
+
+impl<T, D, Geometry> ContactObstacle<T, D> for ClosestPointObstacle<Geometry>
+where
+    T: Real,
+    D: DimName,
+    Geometry: SignedDistance<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn signed_distance(&self, x: &OPoint<T, D>) -> T {
+        self.0
+            .query_signed_distance(x)
+            .map(|result| result.signed_distance)
+            .unwrap_or_else(|| T::max_value().unwrap())
+    }
+
+    fn signed_distance_gradient(&self, x: &OPoint<T, D>) -> Option<OVector<T, D>> {
+        let result = self.0.query_signed_distance(x)?;
+        let d = x - &result.point;
+        let d_norm = d.norm();
+        (d_norm > T::zero()).then(|| d * (result.signed_distance.signum() / d_norm))
+    }
+}
+
+/// Per-node contact quantities, suitable for export as point fields.
+///
+/// All vectors have one entry per node. Only the candidate contact nodes of the assembler can be
+/// in contact, but the gap is computed for all nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactState<T> {
+    /// The gap $g = \phi(\vec X + \vec u)$.
+    pub gap: Vec<T>,
+    /// The penetration depth $\max(-g, 0)$ of candidate contact nodes, or zero.
+    pub penetration: Vec<T>,
+    /// Whether the node is a candidate contact node in contact with the obstacle, i.e. $g \leq 0$.
+    pub in_contact: Vec<bool>,
+}
+
+impl<T: Real> ContactState<T> {
+    /// Returns the contact status as a field with values `1` (in contact) and `0` (not in contact).
+    pub fn contact_indicator(&self) -> Vec<T> {
+        self.in_contact
+            .iter()
+            .map(|&in_contact| if in_contact { T::one() } else { T::zero() })
+            .collect()
+    }
+}
+
+/// Assembles penalty contact forces and stiffnesses for nodes penetrating a rigid obstacle.
+///
+/// Each candidate contact node (typically the boundary nodes of the body) is treated as a
+/// separate one-node element. The vector contribution is the gradient of the penalty energy,
+/// $\kappa g \nabla \phi$, which is consistent with the sign convention of
+/// [`ElementEllipticAssembler`](fenris::assembly::local::ElementEllipticAssembler), and the
+/// matrix contribution is the approximate Hessian $\kappa \nabla \phi \otimes \nabla \phi$,
+/// which neglects the curvature of the obstacle. Nodes that are not in contact with the obstacle
+/// contribute zero.
+///
+/// Nodes that touch the obstacle ($g = 0$) are considered to be in contact. They do not
+/// contribute a force, but do contribute stiffness, so that the Jacobian of a body resting on an
+/// obstacle is not singular.
+#[derive(Debug, Clone)]
+pub struct PenaltyContactAssembler<'a, T, D, Obstacle>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    vertices: &'a [OPoint<T, D>],
+    contact_nodes: &'a [usize],
+    obstacle: &'a Obstacle,
+    penalty: T,
+    u: DVectorView<'a, T>,
+}
+
+impl<'a, T, D, Obstacle> PenaltyContactAssembler<'a, T, D, Obstacle>
+where
+    T: Real,
+    D: DimName,
+    Obstacle: ContactObstacle<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Constructs a contact assembler for the given candidate contact nodes.
+    ///
+    /// `vertices` are the reference positions of all nodes in the mesh, and `u` is the
+    /// current displacement of all nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of `u` is incompatible with the number of vertices, or if a
+    /// contact node index is out of bounds.
+    pub fn new(
+        vertices: &'a [OPoint<T, D>],
+        contact_nodes: &'a [usize],
+        obstacle: &'a Obstacle,
+        penalty: T,
+        u: impl Into<DVectorView<'a, T>>,
+    ) -> Self {
+        let u = u.into();
+        assert_eq!(u.len(), D::dim() * vertices.len(), "Displacement dimension mismatch");
+        assert!(
+            contact_nodes.iter().all(|&node| node < vertices.len()),
+            "Contact node index out of bounds"
+        );
+        Self {
+            vertices,
+            contact_nodes,
+            obstacle,
+            penalty,
+            u,
+        }
+    }
+
+    pub fn penalty(&self) -> T {
+        self.penalty
+    }
+
+    fn deformed_position(&self, node: usize) -> OPoint<T, D> {
+        let d = D::dim();
+        let u_node = self.u.rows_generic(d * node, D::name());
+        &self.vertices[node] + u_node
+    }
+
+    /// Computes the gap and the signed distance gradient for a candidate contact node, if the
+    /// node is in contact with the obstacle.
+    fn compute_active_contact(&self, node: usize) -> eyre::Result<Option<(T, OVector<T, D>)>> {
+        let x = self.deformed_position(node);
+        let gap = self.obstacle.signed_distance(&x);
+        if gap <= T::zero() {
+            let gradient = self
+                .obstacle
+                .signed_distance_gradient(&x)
+                .ok_or_else(|| eyre!("signed distance gradient is undefined at contact node {}", node))?;
+            Ok(Some((gap, gradient)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Computes the gap, penetration depth and contact status of all nodes.
+    pub fn compute_contact_state(&self) -> ContactState<T> {
+        let num_nodes = self.vertices.len();
+        let gap: Vec<_> = (0..num_nodes)
+            .map(|node| self.obstacle.signed_distance(&self.deformed_position(node)))
+            .collect();
+        let mut penetration = vec![T::zero(); num_nodes];
+        let mut in_contact = vec![false; num_nodes];
+        for &node in self.contact_nodes {
+            if gap[node] <= T::zero() {
+                penetration[node] = -gap[node];
+                in_contact[node] = true;
+            }
+        }
+        ContactState {
+            gap,
+            penetration,
+            in_contact,
+        }
+    }
+
+    /// Computes the total contact force $\sum_i -\kappa g_i \nabla \phi_i$ acting on the body.
+    pub fn compute_total_contact_force(&self) -> eyre::Result<OVector<T, D>> {
+        let mut total = OVector::<T, D>::zeros();
+        for &node in self.contact_nodes {
+            if let Some((gap, gradient)) = self.compute_active_contact(node)? {
+                total -= gradient * (self.penalty * gap);
+            }
+        }
+        Ok(total)
+    }
+}
+
+impl<'a, T, D, Obstacle> ElementConnectivityAssembler for PenaltyContactAssembler<'a, T, D, Obstacle>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn solution_dim(&self) -> usize {
+        D::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.contact_nodes.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn element_node_count(&self, _element_index: usize) -> usize {
+        1
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        output[0] = self.contact_nodes[element_index];
+    }
+}
+
+impl<'a, T, D, Obstacle> ElementVectorAssembler<T> for PenaltyContactAssembler<'a, T, D, Obstacle>
+where
+    T: Real,
+    D: DimName,
+    Obstacle: ContactObstacle<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        assert_eq!(output.len(), D::dim(), "Output vector dimension mismatch");
+        let node = self.contact_nodes[element_index];
+        match self.compute_active_contact(node)? {
+            Some((gap, gradient)) => output.copy_from(&(gradient * (self.penalty * gap))),
+            None => output.fill(T::zero()),
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, D, Obstacle> ElementMatrixAssembler<T> for PenaltyContactAssembler<'a, T, D, Obstacle>
+where
+    T: Real,
+    D: DimName,
+    Obstacle: ContactObstacle<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        assert_eq!(output.nrows(), D::dim(), "Output matrix dimension mismatch");
+        assert_eq!(output.ncols(), D::dim(), "Output matrix dimension mismatch");
+        let node = self.contact_nodes[element_index];
+        match self.compute_active_contact(node)? {
+            Some((_, gradient)) => output.copy_from(&(&gradient * gradient.transpose() * self.penalty)),
+            None => output.fill(T::zero()),
+        }
+        Ok(())
+    }
+}
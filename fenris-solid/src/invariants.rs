@@ -0,0 +1,191 @@
+//! Invariants of the deformation gradient and their derivatives.
+//!
+//! Isotropic hyperelastic materials are commonly formulated in terms of the invariants
+//! <div>$$
+//! I_1 = \tr{\vec C}, \qquad I_2 = \frac{1}{2} \left( I_1^2 - \tr{\vec C^2} \right), \qquad J = \det \vec F,
+//! $$</div>
+//! where $\vec C = \vec F^T \vec F$ is the right Cauchy-Green tensor. For each invariant $I$, this module
+//! provides a function for the invariant itself, for its gradient $\pd{I}{\vec F}$ and for the contraction
+//! of its second derivative with vectors $\vec a$ and $\vec b$, given by
+//! <div>$$
+//! \left[ \mathcal{C}_I(\vec F, \vec a, \vec b) \right]_{ij}
+//!     = a_k \frac{\partial^2 I}{\partial F_{ik} \partial F_{jm}} b_m.
+//! $$</div>
+//! The contractions follow the same convention as
+//! [`HyperelasticMaterial::compute_stress_contraction`](crate::HyperelasticMaterial::compute_stress_contraction),
+//! so that the stress contraction of a material with energy density $\psi(I_1, I_2, J)$ follows from
+//! the chain rule as a combination of the gradients and contractions of the invariants.
+use crate::{dispatch_physical_dim, PhysicalDim};
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DefaultAllocator, DimName, Matrix2, Matrix3, OMatrix, OVector};
+use fenris::Real;
+use numeric_literals::replace_float_literals;
+
+/// Computes the first invariant $I_1 = \tr{\vec F^T \vec F} = \| \vec F \|_F^2$.
+#[allow(non_snake_case)]
+pub fn first_invariant<T, D>(F: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    F.norm_squared()
+}
+
+/// Computes the gradient $\pd{I_1}{\vec F} = 2 \vec F$ of the first invariant.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+pub fn first_invariant_gradient<T, D>(F: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    F * 2.0
+}
+
+/// Computes the contraction $\mathcal{C}_{I_1}(\vec F, \vec a, \vec b) = 2 (\vec a \cdot \vec b) \vec I$
+/// of the second derivative of the first invariant.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+pub fn first_invariant_contraction<T, D>(
+    _F: &OMatrix<T, D, D>,
+    a: &OVector<T, D>,
+    b: &OVector<T, D>,
+) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    OMatrix::<T, D, D>::identity() * (2.0 * a.dot(b))
+}
+
+/// Computes the second invariant $I_2 = \frac{1}{2} \left( I_1^2 - \tr{\vec C^2} \right)$.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+pub fn second_invariant<T, D>(F: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let C = F.transpose() * F;
+    let I1 = C.trace();
+    0.5 * (I1 * I1 - C.norm_squared())
+}
+
+/// Computes the gradient $\pd{I_2}{\vec F} = 2 (I_1 \vec F - \vec F \vec C)$ of the second invariant.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+pub fn second_invariant_gradient<T, D>(F: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let I1 = first_invariant(F);
+    let F_C = F * (F.transpose() * F);
+    (F * I1 - F_C) * 2.0
+}
+
+/// Computes the contraction of the second derivative of the second invariant.
+///
+/// The contraction is given by
+/// <div>$$
+/// \mathcal{C}_{I_2}(\vec F, \vec a, \vec b)
+///     = 2 \left( I_1 (\vec a \cdot \vec b) - \vec b \cdot \vec C \vec a \right) \vec I
+///     - 2 (\vec a \cdot \vec b) \vec F \vec F^T
+///     + 4 (\vec F \vec a) \otimes (\vec F \vec b)
+///     - 2 (\vec F \vec b) \otimes (\vec F \vec a).
+/// $$</div>
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+pub fn second_invariant_contraction<T, D>(
+    F: &OMatrix<T, D, D>,
+    a: &OVector<T, D>,
+    b: &OVector<T, D>,
+) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let I1 = first_invariant(F);
+    let a_dot_b = a.dot(b);
+    let ref F_a = F * a;
+    let ref F_b = F * b;
+    let ref I = OMatrix::<T, D, D>::identity();
+    I * (2.0 * (I1 * a_dot_b - F_b.dot(F_a))) - (F * F.transpose()) * (2.0 * a_dot_b) + F_a * F_b.transpose() * 4.0
+        - F_b * F_a.transpose() * 2.0
+}
+
+/// Computes the determinant $J = \det \vec F$.
+#[allow(non_snake_case)]
+pub fn determinant<T, D>(F: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    F.determinant()
+}
+
+/// Computes the gradient $\pd{J}{\vec F} = \operatorname{cof} \vec F$ of the determinant.
+///
+/// Unlike $J \vec F^{-T}$, the cofactor matrix is also defined for singular $\vec F$.
+#[allow(non_snake_case)]
+pub fn determinant_gradient<T, D>(F: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |_| OMatrix::<T, D, D>::identity(),
+        |dim| {
+            let F = dim.matrix(F);
+            let cof = Matrix2::new(F[(1, 1)], -F[(1, 0)], -F[(0, 1)], F[(0, 0)]);
+            dim.generic_matrix(cof)
+        },
+        |dim| {
+            let F = dim.matrix(F);
+            // Each column of the cofactor matrix is the cross product of the two other columns of F
+            let cof = Matrix3::from_columns(&[
+                F.column(1).cross(&F.column(2)),
+                F.column(2).cross(&F.column(0)),
+                F.column(0).cross(&F.column(1)),
+            ]);
+            dim.generic_matrix(cof)
+        },
+    )
+}
+
+/// Computes the contraction of the second derivative of the determinant.
+///
+/// In 1D, the contraction vanishes. In 2D, it is given by
+/// $\mathcal{C}_J = (a_1 b_2 - a_2 b_1) \begin{pmatrix} 0 & 1 \newline -1 & 0 \end{pmatrix}$,
+/// and in 3D by $\mathcal{C}_J = - [\vec F (\vec a \times \vec b)]_\times$, where $[\vec v]_\times$ is the
+/// cross product matrix of $\vec v$.
+#[allow(non_snake_case)]
+pub fn determinant_contraction<T, D>(F: &OMatrix<T, D, D>, a: &OVector<T, D>, b: &OVector<T, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |_| OMatrix::<T, D, D>::zeros(),
+        |dim| {
+            let c = dim.vector(a).perp(dim.vector(b));
+            let K = Matrix2::new(T::zero(), c, -c, T::zero());
+            dim.generic_matrix(K)
+        },
+        |dim| {
+            let (F, a, b) = (dim.matrix(F), dim.vector(a), dim.vector(b));
+            // K_ij = eps_ijl (F (a x b))_l
+            let K = -(F * a.cross(b)).cross_matrix();
+            dim.generic_matrix(K)
+        },
+    )
+}
@@ -4,17 +4,34 @@ use fenris::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticO
 use fenris::nalgebra::{
     DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector, RealField, U1, U2, U3,
 };
+use fenris::util::{with_const_dim, ConstDim};
 use fenris::{Real, SmallDim, Symmetry};
 use std::cmp::min;
 
+pub mod autodiff;
+pub mod calibration;
+pub mod contact;
+pub mod invariants;
 pub mod materials;
+pub mod model;
+pub mod post_processing;
+pub mod verification;
+
+#[cfg(feature = "proptest-support")]
+pub mod proptest;
 
 mod logdet;
-pub use logdet::log_det_F;
+pub use logdet::{log_det_F, log_det_F_contraction, log_det_F_gradient};
+
+mod axisymmetric;
+pub use axisymmetric::AxisymmetricElasticityAssembler;
 
 mod gravity_source;
 pub use gravity_source::GravitySource;
 
+mod multi_material;
+pub use multi_material::{MaterialRegistry, MultiMaterialAssembler};
+
 /// Compute the deformation gradient $\vec F$ given the displacement gradient $\nabla \vec u$.
 #[allow(non_snake_case)]
 pub fn deformation_gradient<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
@@ -409,9 +426,9 @@ pub fn compute_batch_contraction<T, GeometryDim>(
 /// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) &= \mathcal{C}_g(\nabla \vec u, \vec a, \vec b). \\\\
 /// \end{aligned}
 /// $$
-pub struct MaterialEllipticOperator<'a, Material>(&'a Material);
+pub struct MaterialEllipticOperator<'a, Material: ?Sized>(&'a Material);
 
-impl<'a, Material> MaterialEllipticOperator<'a, Material> {
+impl<'a, Material: ?Sized> MaterialEllipticOperator<'a, Material> {
     pub fn new(material: &'a Material) -> Self {
         Self(material)
     }
@@ -421,7 +438,7 @@ impl<'a, T, GeometryDim, Material> Operator<T, GeometryDim> for MaterialElliptic
 where
     T: Real,
     GeometryDim: SmallDim,
-    Material: HyperelasticMaterial<T, GeometryDim>,
+    Material: ?Sized + HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
     type SolutionDim = GeometryDim;
@@ -432,7 +449,7 @@ impl<'a, T, GeometryDim, Material> EllipticEnergy<T, GeometryDim> for MaterialEl
 where
     T: Real,
     GeometryDim: SmallDim,
-    Material: HyperelasticMaterial<T, GeometryDim>,
+    Material: ?Sized + HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
     fn compute_energy(&self, u_grad: &OMatrix<T, GeometryDim, GeometryDim>, parameters: &Self::Parameters) -> T {
@@ -444,7 +461,7 @@ impl<'a, T, GeometryDim, Material> EllipticOperator<T, GeometryDim> for Material
 where
     T: Real,
     GeometryDim: SmallDim,
-    Material: HyperelasticMaterial<T, GeometryDim>,
+    Material: ?Sized + HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
     fn compute_elliptic_operator(
@@ -474,7 +491,7 @@ impl<'a, T, GeometryDim, Material> EllipticContraction<T, GeometryDim> for Mater
 where
     T: Real,
     GeometryDim: SmallDim,
-    Material: HyperelasticMaterial<T, GeometryDim>,
+    Material: ?Sized + HyperelasticMaterial<T, GeometryDim>,
     DefaultAllocator: DimAllocator<T, GeometryDim>,
 {
     fn contract(
@@ -528,3 +545,19 @@ pub trait PhysicalDim: internal::Sealed + SmallDim {}
 impl PhysicalDim for U1 {}
 impl PhysicalDim for U2 {}
 impl PhysicalDim for U3 {}
+
+/// Calls the function corresponding to the physical dimension `D`.
+///
+/// This is the infallible counterpart of [`with_const_dim`] for physical dimensions: since
+/// [`PhysicalDim`] is only implemented for the dimensions $1$, $2$ and $3$, the dispatch always
+/// succeeds.
+pub fn dispatch_physical_dim<D, R>(
+    f1: impl FnOnce(ConstDim<D, 1>) -> R,
+    f2: impl FnOnce(ConstDim<D, 2>) -> R,
+    f3: impl FnOnce(ConstDim<D, 3>) -> R,
+) -> R
+where
+    D: PhysicalDim,
+{
+    with_const_dim(f1, f2, f3).expect("Physical dimensions are always 1, 2 or 3")
+}
@@ -1,7 +1,6 @@
-use crate::PhysicalDim;
+use crate::{dispatch_physical_dim, PhysicalDim};
 use fenris::allocators::DimAllocator;
-use fenris::nalgebra::{DefaultAllocator, Matrix1, Matrix2, Matrix3, OMatrix};
-use fenris::util::try_transmute_ref;
+use fenris::nalgebra::{DefaultAllocator, Matrix2, Matrix3, OMatrix, OVector};
 use fenris::Real;
 use numeric_literals::replace_float_literals;
 
@@ -20,21 +19,33 @@ where
     D: PhysicalDim,
     DefaultAllocator: DimAllocator<T, D>,
 {
-    match D::USIZE {
-        1 => {
-            let du_dX: &Matrix1<T> = try_transmute_ref(du_dX).unwrap();
-            let det = du_dX[(0, 0)];
-            (det > 0.0).then(|| det.ln())
-        }
-        2 => log_det_F_2d(try_transmute_ref(du_dX).unwrap()),
-        3 => log_det_F_3d(try_transmute_ref(du_dX).unwrap()),
-        _ => unreachable!("Physical dimensions do not extend past 3 dimensions"),
-    }
+    dispatch_physical_dim::<D, _>(
+        |dim| {
+            let gamma = dim.matrix(du_dX)[(0, 0)];
+            (gamma > -1.0).then(|| T::ln_1p(gamma))
+        },
+        |dim| log_det_F_2d(dim.matrix(du_dX)),
+        |dim| log_det_F_3d(dim.matrix(du_dX)),
+    )
 }
 
 #[allow(non_snake_case)]
 #[replace_float_literals(T::from_f64(literal).unwrap())]
 fn log_det_F_2d<T: Real>(du_dX: &Matrix2<T>) -> Option<T> {
+    let gamma = det_F_minus_one_2d(du_dX);
+    (gamma > -1.0).then(|| T::ln_1p(gamma))
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn log_det_F_3d<T: Real>(du_dX: &Matrix3<T>) -> Option<T> {
+    let gamma = det_F_minus_one_3d(du_dX);
+    (gamma > -1.0).then(|| T::ln_1p(gamma))
+}
+
+/// Computes $\gamma = \det \vec F - 1$ from $\pd{\vec u}{\vec X}$ in 2D.
+#[allow(non_snake_case)]
+fn det_F_minus_one_2d<T: Real>(du_dX: &Matrix2<T>) -> T {
     // See comments in 3D impl for more elaborate explanation
     // Given a matrix A = [a, b; c, d] the determinant is
     //  det(A) = ad - bc
@@ -48,13 +59,13 @@ fn log_det_F_2d<T: Real>(du_dX: &Matrix2<T>) -> Option<T> {
     let u22 = du_dX[(1, 1)];
     let b = du_dX[(0, 1)];
     let c = du_dX[(1, 0)];
-    let gamma = u11 * u22 + u11 + u22 - b * c;
-    (gamma > -1.0).then(|| T::ln_1p(gamma))
+    u11 * u22 + u11 + u22 - b * c
 }
 
+/// Computes $\gamma = \det \vec F - 1$ from $\pd{\vec u}{\vec X}$ in 3D.
 #[allow(non_snake_case)]
 #[replace_float_literals(T::from_f64(literal).unwrap())]
-fn log_det_F_3d<T: Real>(du_dX: &Matrix3<T>) -> Option<T> {
+fn det_F_minus_one_3d<T: Real>(du_dX: &Matrix3<T>) -> T {
     // Given a matrix A = [a, b, c; d, e, f; g, h, i] the determinant is
     //  det(A) = aei + bfg + cdh - ceg - bdi - afh
     // The first term is the product of the diagonals. Since F = I + du_dX,
@@ -78,9 +89,115 @@ fn log_det_F_3d<T: Real>(du_dX: &Matrix3<T>) -> Option<T> {
     let f = du_dX[(1, 2)];
     let g = du_dX[(2, 0)];
     let h = du_dX[(2, 1)];
-    let gamma = u11 * u22 * u33 + u11 * u22 + u11 * u33 + u22 * u33 + u11 + u22 + u33 + b * f * g + c * d * h
+    u11 * u22 * u33 + u11 * u22 + u11 * u33 + u22 * u33 + u11 + u22 + u33 + b * f * g + c * d * h
         - c * e * g
         - b * d * i
-        - a * f * h;
-    (gamma > -1.0).then(|| T::ln_1p(gamma))
+        - a * f * h
+}
+
+/// Compute the deviation $\vec F^{-T} - \vec I$ of the gradient $\pd{}{\vec F} \log(\det \vec F) = \vec F^{-T}$
+/// from the identity, given $\pd{\vec u}{\vec X}$.
+///
+/// Returns `None` if $\det \vec F \leq 0$.
+///
+/// For small deformations, $\vec F^{-T}$ is close to the identity, and the deviation can not be recovered
+/// accurately from $\vec F^{-T}$ once it has been formed. Instead, the deviation is computed as
+/// <div>$$
+/// \vec F^{-T} - \vec I = - (\vec F^{-1} \vec U)^T = - \frac{1}{1 + \gamma} (\operatorname{adj}(\vec F) \vec U)^T,
+/// $$</div>
+/// where $\vec U = \pd{\vec u}{\vec X}$ and $\gamma = \det \vec F - 1$ is computed directly from
+/// $\vec U$ in the same way as in [`log_det_F`]. The gradient itself is obtained by adding the identity.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn log_det_F_gradient<T, D>(du_dX: &OMatrix<T, D, D>) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |dim| {
+            let u = dim.matrix(du_dX)[(0, 0)];
+            (u > -1.0).then(|| OMatrix::<T, D, D>::from_element(-u / (1.0 + u)))
+        },
+        |dim| log_det_F_gradient_2d(dim.matrix(du_dX)).map(|grad| dim.generic_matrix(grad)),
+        |dim| log_det_F_gradient_3d(dim.matrix(du_dX)).map(|grad| dim.generic_matrix(grad)),
+    )
+}
+
+/// Compute the contraction of the second derivative of $\log(\det \vec F)$ with the vectors
+/// $\vec a$ and $\vec b$, given $\pd{\vec u}{\vec X}$.
+///
+/// The contraction is defined by
+/// <div>$$
+/// \left[ \mathcal{C}(\vec F, \vec a, \vec b) \right]_{ij}
+///     = \frac{\partial^2 \log(\det \vec F)}{\partial F_{ik} \partial F_{jm}} a_k b_m
+///     = - \left[ (\vec F^{-T} \vec b) \otimes (\vec F^{-T} \vec a) \right]_{ij},
+/// $$</div>
+/// in line with the stress contraction of
+/// [`HyperelasticMaterial`](crate::HyperelasticMaterial). The products $\vec F^{-T} \vec a$ and
+/// $\vec F^{-T} \vec b$ are computed with [`log_det_F_gradient`], without forming $\vec F$.
+///
+/// Returns `None` if $\det \vec F \leq 0$.
+#[allow(non_snake_case)]
+pub fn log_det_F_contraction<T, D>(
+    du_dX: &OMatrix<T, D, D>,
+    a: &OVector<T, D>,
+    b: &OVector<T, D>,
+) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let G = log_det_F_gradient(du_dX)?;
+    let F_inv_T_a = a + &G * a;
+    let F_inv_T_b = b + &G * b;
+    Some(-F_inv_T_b * F_inv_T_a.transpose())
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn log_det_F_gradient_2d<T: Real>(du_dX: &Matrix2<T>) -> Option<Matrix2<T>> {
+    let gamma = det_F_minus_one_2d(du_dX);
+    if gamma > -1.0 {
+        let u11 = du_dX[(0, 0)];
+        let u22 = du_dX[(1, 1)];
+        let b = du_dX[(0, 1)];
+        let c = du_dX[(1, 0)];
+        // The adjugate only involves individual entries of F = I + U,
+        // so it can be formed without significant loss of accuracy
+        let adj_F = Matrix2::new(1.0 + u22, -b, -c, 1.0 + u11);
+        Some(-(adj_F * du_dX).transpose() / (1.0 + gamma))
+    } else {
+        None
+    }
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn log_det_F_gradient_3d<T: Real>(du_dX: &Matrix3<T>) -> Option<Matrix3<T>> {
+    let gamma = det_F_minus_one_3d(du_dX);
+    if gamma > -1.0 {
+        // The cofactors of F = I + U are products of at most two entries of F, and so
+        // (contrary to the determinant) they can be formed without significant loss of accuracy
+        let a = 1.0 + du_dX[(0, 0)];
+        let e = 1.0 + du_dX[(1, 1)];
+        let i = 1.0 + du_dX[(2, 2)];
+        let b = du_dX[(0, 1)];
+        let c = du_dX[(0, 2)];
+        let d = du_dX[(1, 0)];
+        let f = du_dX[(1, 2)];
+        let g = du_dX[(2, 0)];
+        let h = du_dX[(2, 1)];
+        #[rustfmt::skip]
+        let adj_F = Matrix3::new(
+            e * i - f * h, c * h - b * i, b * f - c * e,
+            f * g - d * i, a * i - c * g, c * d - a * f,
+            d * h - e * g, b * g - a * h, a * e - b * d,
+        );
+        Some(-(adj_F * du_dX).transpose() / (1.0 + gamma))
+    } else {
+        None
+    }
 }
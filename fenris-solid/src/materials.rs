@@ -1,6 +1,17 @@
-use crate::{compute_batch_contraction, log_det_F, u_grad_from_F, HyperelasticMaterial, PhysicalDim};
+use crate::invariants::{
+    determinant, determinant_contraction, determinant_gradient, first_invariant, first_invariant_contraction,
+    first_invariant_gradient,
+};
+use crate::{
+    compute_batch_contraction, dispatch_physical_dim, log_det_F, log_det_F_gradient, u_grad_from_F,
+    HyperelasticMaterial, PhysicalDim,
+};
+use eyre::{eyre, WrapErr};
 use fenris::allocators::DimAllocator;
-use fenris::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector};
+use fenris::nalgebra::{
+    DMatrixViewMut, DVectorView, DefaultAllocator, DimName, Matrix2, Matrix3, OMatrix, OVector, Vector2, Vector3, U2,
+    U3,
+};
 use fenris::Real;
 use numeric_literals::replace_float_literals;
 use serde::{Deserialize, Serialize};
@@ -22,23 +33,185 @@ where
     }
 }
 
+impl<T> LameParameters<T>
+where
+    T: Real,
+{
+    /// Returns the effective Lamé parameters for plane stress.
+    ///
+    /// Two-dimensional simulations with the Lamé parameters $\mu$ and $\lambda$ correspond to
+    /// plane strain. For a thin plate in plane stress, the out-of-plane stress vanishes instead, and
+    /// the in-plane behavior is described by the same $\mu$ and the effective parameter
+    /// <div>$$
+    /// \lambda^* = \frac{2 \mu \lambda}{\lambda + 2 \mu}.
+    /// $$</div>
+    /// For nonlinear materials, see [`PlaneStress`].
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    pub fn to_plane_stress(&self) -> Self {
+        let &Self { mu, lambda } = self;
+        Self {
+            mu,
+            lambda: 2.0 * mu * lambda / (lambda + 2.0 * mu),
+        }
+    }
+}
+
+/// Young's modulus $E$ and Poisson's ratio $\nu$.
+///
+/// Conversions to and from the other representations of the elastic parameters require
+/// $E > 0$ and $-1 < \nu < 0.5$, which excludes the incompressible limit $\nu = 0.5$.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct YoungPoisson<T> {
     pub young: T,
     pub poisson: T,
 }
 
-impl<T> From<YoungPoisson<T>> for LameParameters<T>
+/// The bulk modulus $\kappa$ and the shear modulus $\mu$.
+///
+/// The bulk modulus is defined for three dimensions, so that $\kappa = \lambda + \frac{2}{3} \mu$.
+/// Conversions to and from the other representations of the elastic parameters require
+/// $\kappa > 0$ and $\mu > 0$.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkShearParameters<T> {
+    pub kappa: T,
+    pub mu: T,
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+fn check_young_poisson<T: Real>(params: &YoungPoisson<T>) -> eyre::Result<()> {
+    let &YoungPoisson { young, poisson } = params;
+    if !(young.is_finite() && young > 0.0) {
+        Err(eyre!("Young's modulus must be positive and finite, got {young}"))
+    } else if !(poisson > -1.0 && poisson < 0.5) {
+        Err(eyre!(
+            "Poisson's ratio must be in the open interval (-1, 0.5), got {poisson}"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_bulk_shear<T: Real>(params: &BulkShearParameters<T>) -> eyre::Result<()> {
+    let &BulkShearParameters { kappa, mu } = params;
+    if !(kappa.is_finite() && kappa > T::zero()) {
+        Err(eyre!("Bulk modulus must be positive and finite, got {kappa}"))
+    } else if !(mu.is_finite() && mu > T::zero()) {
+        Err(eyre!("Shear modulus must be positive and finite, got {mu}"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the Lamé parameters, which are valid if the corresponding bulk and shear moduli are valid.
+fn check_lame<T: Real>(params: &LameParameters<T>) -> eyre::Result<()> {
+    if !params.lambda.is_finite() {
+        return Err(eyre!("Lamé parameter lambda must be finite, got {}", params.lambda));
+    }
+    check_bulk_shear(&lame_to_bulk_shear(params)).wrap_err("Invalid Lamé parameters")
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+fn lame_to_bulk_shear<T: Real>(params: &LameParameters<T>) -> BulkShearParameters<T> {
+    let &LameParameters { mu, lambda } = params;
+    BulkShearParameters {
+        kappa: lambda + 2.0 * mu / 3.0,
+        mu,
+    }
+}
+
+impl<T> TryFrom<YoungPoisson<T>> for LameParameters<T>
 where
     T: Real,
 {
+    type Error = eyre::Report;
+
     #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
-    fn from(params: YoungPoisson<T>) -> Self {
-        // TODO: Test this!
+    fn try_from(params: YoungPoisson<T>) -> eyre::Result<Self> {
+        check_young_poisson(&params)?;
         let YoungPoisson { young, poisson } = params;
         let mu = 0.5 * young / (1.0 + poisson);
         let lambda = 2.0 * mu * poisson / (1.0 - 2.0 * poisson);
-        Self { mu, lambda }
+        Ok(Self { mu, lambda })
+    }
+}
+
+impl<T> TryFrom<LameParameters<T>> for YoungPoisson<T>
+where
+    T: Real,
+{
+    type Error = eyre::Report;
+
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    fn try_from(params: LameParameters<T>) -> eyre::Result<Self> {
+        check_lame(&params)?;
+        let LameParameters { mu, lambda } = params;
+        Ok(Self {
+            young: mu * (3.0 * lambda + 2.0 * mu) / (lambda + mu),
+            poisson: 0.5 * lambda / (lambda + mu),
+        })
+    }
+}
+
+impl<T> TryFrom<LameParameters<T>> for BulkShearParameters<T>
+where
+    T: Real,
+{
+    type Error = eyre::Report;
+
+    fn try_from(params: LameParameters<T>) -> eyre::Result<Self> {
+        check_lame(&params)?;
+        Ok(lame_to_bulk_shear(&params))
+    }
+}
+
+impl<T> TryFrom<BulkShearParameters<T>> for LameParameters<T>
+where
+    T: Real,
+{
+    type Error = eyre::Report;
+
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    fn try_from(params: BulkShearParameters<T>) -> eyre::Result<Self> {
+        check_bulk_shear(&params)?;
+        let BulkShearParameters { kappa, mu } = params;
+        Ok(Self {
+            mu,
+            lambda: kappa - 2.0 * mu / 3.0,
+        })
+    }
+}
+
+impl<T> TryFrom<YoungPoisson<T>> for BulkShearParameters<T>
+where
+    T: Real,
+{
+    type Error = eyre::Report;
+
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    fn try_from(params: YoungPoisson<T>) -> eyre::Result<Self> {
+        check_young_poisson(&params)?;
+        let YoungPoisson { young, poisson } = params;
+        Ok(Self {
+            kappa: young / (3.0 * (1.0 - 2.0 * poisson)),
+            mu: 0.5 * young / (1.0 + poisson),
+        })
+    }
+}
+
+impl<T> TryFrom<BulkShearParameters<T>> for YoungPoisson<T>
+where
+    T: Real,
+{
+    type Error = eyre::Report;
+
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    fn try_from(params: BulkShearParameters<T>) -> eyre::Result<Self> {
+        check_bulk_shear(&params)?;
+        let BulkShearParameters { kappa, mu } = params;
+        Ok(Self {
+            young: 9.0 * kappa * mu / (3.0 * kappa + mu),
+            poisson: (3.0 * kappa - 2.0 * mu) / (2.0 * (3.0 * kappa + mu)),
+        })
     }
 }
 
@@ -126,12 +299,17 @@ where
 ///
 /// The strain energy density is given by
 /// $$
-/// \psi(\vec F) = \frac{\mu}{2}(I_C - 3) - \mu \log J + \frac{\lambda}{2}(\log J)^2,
+/// \psi(\vec F) = \frac{\mu}{2}(I_C - d) - \mu \log J + \frac{\lambda}{2}(\log J)^2,
 /// $$
-/// where $J = \det \vec F$ and $I_C = \tr{\vec C} = \tr{\vec F^T \vec F}$ is the first right Cauchy-Green invariant.
+/// where $d$ is the dimension, $J = \det \vec F$ and $I_C = \tr{\vec C} = \tr{\vec F^T \vec F}$ is the first
+/// right Cauchy-Green invariant.
 ///
 /// Note that the energy is only well-defined when $J > 0$. We explicitly return infinity in this case, so that
-/// it may be used e.g. as a barrier in optimization.
+/// it may be used e.g. as a barrier in optimization. The stress tensor and the stress contraction are undefined
+/// for $J \leq 0$, and all of their entries are NaN in this case.
+///
+/// All quantities are computed from the displacement gradient with [`log_det_F`] and [`log_det_F_gradient`]
+/// without forming $\vec F - \vec F^{-T}$ explicitly, so that they remain accurate for small strains.
 ///
 /// The Piola-Kirchhoff stress tensor is given by
 /// $$
@@ -231,6 +409,18 @@ where
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NeoHookeanMaterial;
 
+/// Computes $\log J$ and $\vec F^{-T} - \vec I$ from the displacement Jacobian $\pd{\vec u}{\vec X}$,
+/// or `None` if $J \leq 0$.
+#[allow(non_snake_case)]
+fn log_det_and_gradient<T, D>(du_dX: &OMatrix<T, D, D>) -> Option<(T, OMatrix<T, D, D>)>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    Some((log_det_F(du_dX)?, log_det_F_gradient(du_dX)?))
+}
+
 #[allow(non_snake_case)]
 #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
 impl<T, D> HyperelasticMaterial<T, D> for NeoHookeanMaterial
@@ -266,21 +456,25 @@ where
         deformation_gradient: &OMatrix<T, D, D>,
         parameters: &Self::Parameters,
     ) -> OMatrix<T, D, D> {
-        let LameParameters { mu, lambda } = parameters.clone();
-        let F = deformation_gradient;
-        let J = F.determinant();
+        let u_grad = u_grad_from_F(deformation_gradient);
+        self.compute_stress_tensor_du(&u_grad, parameters)
+    }
 
-        if J <= T::zero() {
+    fn compute_stress_tensor_du(&self, u_grad: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> OMatrix<T, D, D> {
+        let LameParameters { mu, lambda } = parameters.clone();
+        let du_dX = u_grad.transpose();
+        if let Some((logJ, G)) = log_det_and_gradient(&du_dX) {
+            // Original expression
+            //  P = mu * (F - F^{-T}) + lambda * logJ * F^{-T}
+            // The difference F - F^{-T} suffers from cancellation for small du_dX. Instead we use
+            //  F - F^{-T} = U - G
+            // with U = du_dX and G = F^{-T} - I computed without forming F.
+            let U = &du_dX;
+            let F_inv_T = OMatrix::<T, D, D>::identity() + &G;
+            (U - G) * mu + F_inv_T * (lambda * logJ)
+        } else {
             // TODO: How to address this? Might have to address in the API itself?
             OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap())
-        } else {
-            let logJ = J.ln();
-            let F_inv = F
-                .clone()
-                .try_inverse()
-                .expect("F is guaranteed to be invertible here");
-            let F_inv_T = F_inv.transpose();
-            F_inv_T * (-mu + lambda * logJ) + F * mu
         }
     }
 
@@ -291,26 +485,29 @@ where
         b: &OVector<T, D>,
         parameters: &Self::Parameters,
     ) -> OMatrix<T, D, D> {
-        let LameParameters { mu, lambda } = parameters.clone();
-        let F = deformation_gradient;
-        let J = F.determinant();
+        let u_grad = u_grad_from_F(deformation_gradient);
+        self.compute_stress_contraction_du(&u_grad, a, b, parameters)
+    }
 
-        if J <= T::zero() {
-            // TODO: How to address this? Might have to address in the API itself?
-            OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap())
-        } else {
-            let logJ = J.ln();
-            let F_inv = F
-                .clone()
-                .try_inverse()
-                .expect("F is guaranteed to be invertible here");
-            let F_inv_T = F_inv.transpose();
+    fn compute_stress_contraction_du(
+        &self,
+        u_grad: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let LameParameters { mu, lambda } = parameters.clone();
+        if let Some((logJ, G)) = log_det_and_gradient(&u_grad.transpose()) {
+            let F_inv_T = OMatrix::<T, D, D>::identity() + G;
             let ref F_inv_T_a = &F_inv_T * a;
             let ref F_inv_T_b = &F_inv_T * b;
             let ref I = OMatrix::<_, D, D>::identity();
             let alpha = -mu + lambda * logJ;
             (F_inv_T_a) * (F_inv_T_b.transpose() * lambda) - F_inv_T_b * (F_inv_T_a.transpose() * alpha)
                 + I * (mu * a.dot(&b))
+        } else {
+            // TODO: How to address this? Might have to address in the API itself?
+            OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap())
         }
     }
 
@@ -323,20 +520,23 @@ where
         b: DVectorView<T>,
         parameters: &Self::Parameters,
     ) {
-        let LameParameters { mu, lambda } = parameters.clone();
-        let F = deformation_gradient;
-        let J = F.determinant();
+        let u_grad = u_grad_from_F(deformation_gradient);
+        self.accumulate_stress_contractions_du_into(output, alpha, &u_grad, a, b, parameters)
+    }
 
-        if J <= T::zero() {
-            todo!("How to address non-positive J? (J = {})", J);
-        } else {
+    fn accumulate_stress_contractions_du_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        u_grad: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let LameParameters { mu, lambda } = parameters.clone();
+        if let Some((logJ, G)) = log_det_and_gradient(&u_grad.transpose()) {
+            let F_inv_T = OMatrix::<T, D, D>::identity() + G;
             // Precompute all the quantities that are independent of a and b
-            let logJ = J.ln();
-            let F_inv = F
-                .clone()
-                .try_inverse()
-                .expect("F is guaranteed to be invertible here");
-            let F_inv_T = F_inv.transpose();
             let ref I = OMatrix::<_, D, D>::identity();
             // Note: This alpha is from the formula, not from the alpha contraction parameter!
             // TODO: Use different formula in derivation?
@@ -348,6 +548,344 @@ where
                 (F_inv_T_a) * (F_inv_T_b.transpose() * lambda) - F_inv_T_b * (F_inv_T_a.transpose() * alpha_nh)
                     + I * (mu * a.dot(&b))
             })
+        } else {
+            // Consistent with compute_stress_contraction, the contractions are undefined
+            compute_batch_contraction(output, alpha, a, b, |_, _| {
+                OMatrix::<T, D, D>::repeat(T::from_f64(f64::NAN).unwrap())
+            })
+        }
+    }
+}
+
+/// The Stable Neo-Hookean material model by Smith et al.
+///
+/// Unlike [`NeoHookeanMaterial`], the energy density remains finite and smooth for degenerate or inverted
+/// deformation gradients ($J \leq 0$), which makes it well suited for simulations with large deformations
+/// in which elements may invert. The strain energy density is given by
+/// $$
+/// \psi(\vec F) = \frac{\mu}{2}(I_C - d) + \frac{\hat \lambda}{2}(J - \alpha)^2 - \frac{\mu^2}{2 \hat \lambda},
+/// $$
+/// where $d$ is the dimension, $J = \det \vec F$ and $I_C = \tr{\vec F^T \vec F}$. The last term is a constant
+/// that ensures that the energy vanishes in the rest state. Given the Lamé parameters $\mu$ and $\lambda$,
+/// the reparameterization
+/// $$
+/// \hat \lambda = \lambda + \mu, \qquad \alpha = 1 + \frac{\mu}{\hat \lambda}
+/// $$
+/// ensures that the rest state is stress-free and that the model is consistent with linear elasticity
+/// with the same Lamé parameters for small strains. We require $\lambda + \mu > 0$.
+///
+/// With $\operatorname{cof} \vec F = \pd{J}{\vec F}$ the cofactor matrix, the Piola-Kirchhoff stress tensor is
+/// $$
+///  \vec P = \mu \vec F + \hat \lambda (J - \alpha) \operatorname{cof} \vec F,
+/// $$
+/// and the stress contraction for arbitrary vectors $\vec a, \vec b \in \mathbb{R}^d$ is given by
+/// <div>$$
+///   \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b)
+///   = \mu (\vec a \cdot \vec b) \vec I
+///    + \hat \lambda (\operatorname{cof} \vec F \, \vec a) \otimes (\operatorname{cof} \vec F \, \vec b)
+///    + \hat \lambda (J - \alpha) \vec K(\vec F, \vec a, \vec b),
+/// $$</div>
+/// where $K_{ij} = a_k \frac{\partial^2 J}{\partial F_{ik} \partial F_{jm}} b_m$ is the contraction of the
+/// Hessian of the determinant. In 2D, $\vec K = (a_1 b_2 - a_2 b_1) \begin{pmatrix} 0 & 1 \newline -1 & 0
+/// \end{pmatrix}$, and in 3D, $\vec K = - [\vec F (\vec a \times \vec b)]_\times$, where $[\vec v]_\times$ is the
+/// cross product matrix of $\vec v$. None of these expressions involve $\vec F^{-1}$, so they are all
+/// well-defined for any $\vec F$.
+///
+/// Note that we omit the additional logarithmic term $-\frac{\mu}{2} \log(I_C + 1)$ that is used in the original
+/// paper to guarantee a stable rest state under complete collapse.
+///
+/// Smith, B., De Goes, F., & Kim, T. (2018). Stable Neo-Hookean flesh simulation.
+/// ACM Transactions on Graphics, 37(2), 1-15.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StableNeoHookeanMaterial;
+
+/// Returns the parameters $(\mu, \hat \lambda, \alpha)$ of the Stable Neo-Hookean model.
+fn stable_neo_hookean_parameters<T: Real>(parameters: &LameParameters<T>) -> (T, T, T) {
+    let &LameParameters { mu, lambda } = parameters;
+    let lambda_hat = lambda + mu;
+    let alpha = T::one() + mu / lambda_hat;
+    (mu, lambda_hat, alpha)
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+impl<T, D> HyperelasticMaterial<T, D> for StableNeoHookeanMaterial
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type Parameters = LameParameters<T>;
+
+    fn compute_energy_density(&self, deformation_gradient: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let (mu, lambda_hat, alpha) = stable_neo_hookean_parameters(parameters);
+        let F = deformation_gradient;
+        let J = determinant(F);
+        let d = T::from_usize(D::dim()).unwrap();
+        0.5 * mu * (first_invariant(F) - d) + 0.5 * lambda_hat * (J - alpha).powi(2) - 0.5 * mu * mu / lambda_hat
+    }
+
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let (mu, lambda_hat, alpha) = stable_neo_hookean_parameters(parameters);
+        let F = deformation_gradient;
+        let J = determinant(F);
+        first_invariant_gradient(F) * (0.5 * mu) + determinant_gradient(F) * (lambda_hat * (J - alpha))
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let (mu, lambda_hat, alpha) = stable_neo_hookean_parameters(parameters);
+        let F = deformation_gradient;
+        let J = determinant(F);
+        let cof_F = determinant_gradient(F);
+        let ref cof_F_a = &cof_F * a;
+        let ref cof_F_b = &cof_F * b;
+        first_invariant_contraction(F, a, b) * (0.5 * mu)
+            + cof_F_a * (cof_F_b.transpose() * lambda_hat)
+            + determinant_contraction(F, a, b) * (lambda_hat * (J - alpha))
+    }
+
+    fn accumulate_stress_contractions_into(
+        &self,
+        output: DMatrixViewMut<T>,
+        alpha: T,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        // Precompute all the quantities that are independent of a and b
+        let (mu, lambda_hat, alpha_snh) = stable_neo_hookean_parameters(parameters);
+        let F = deformation_gradient;
+        let J = determinant(F);
+        let cof_F = determinant_gradient(F);
+
+        compute_batch_contraction(output, alpha, a, b, |a, b| {
+            let ref cof_F_a = &cof_F * a;
+            let ref cof_F_b = &cof_F * b;
+            first_invariant_contraction(F, a, b) * (0.5 * mu)
+                + cof_F_a * (cof_F_b.transpose() * lambda_hat)
+                + determinant_contraction(F, a, b) * (lambda_hat * (J - alpha_snh))
+        })
+    }
+}
+
+/// The corotated linear elastic material model.
+///
+/// Given the polar decomposition $\vec F = \vec R \vec S$ of the deformation gradient into a rotation $\vec R$ and
+/// a symmetric stretch $\vec S = \vec R^T \vec F$, the model evaluates linear elasticity in the rotated frame.
+/// With Lamé parameters $\mu$ and $\lambda$, the strain energy density is
+/// <div>$$
+/// \psi(\vec F) = \mu \| \vec F - \vec R \|^2 + \frac{\lambda}{2} \operatorname{tr}^2(\vec S - \vec I)
+///     = \mu \| \vec S - \vec I \|^2 + \frac{\lambda}{2} \operatorname{tr}^2(\vec S - \vec I).
+/// $$</div>
+/// The energy is invariant under superposed rotations, and the model coincides with [`LinearElasticMaterial`]
+/// for small strains. The rotation is always chosen to be a proper rotation ($\det \vec R = 1$), so that
+/// $\vec S$ has a negative eigenvalue for inverted deformation gradients. The associated stress tensor is
+/// <div>$$
+/// \vec P(\vec F) = 2 \mu (\vec F - \vec R) + \lambda \operatorname{tr}(\vec S - \vec I) \vec R.
+/// $$</div>
+///
+/// # Stress contraction
+///
+/// Treating the rotation as constant yields the *fixed corotated* approximation
+/// <div>$$
+/// \mathcal{C}^{\text{fixed}}_{\vec P}(\vec F, \vec a, \vec b) =
+///     2 \mu (\vec a \cdot \vec b) \vec I + \lambda (\vec R \vec a) \otimes (\vec R \vec b),
+/// $$</div>
+/// which is always positive semi-definite, but not consistent with the stress tensor. If rotation derivatives
+/// are included, which is the default, the contraction is exact and given by
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) = \mathcal{C}^{\text{fixed}}_{\vec P}(\vec F, \vec a, \vec b)
+///     + \left[ \lambda \operatorname{tr}(\vec S - \vec I) - 2 \mu \right] \vec K(\vec F, \vec a, \vec b),
+/// $$</div>
+/// where $K_{ij} = a_k \pd{R_{ik}}{F_{jm}} b_m$. In 3D,
+/// $\vec K = - \vec R [\vec a]_\times (\operatorname{tr}(\vec S) \vec I - \vec S)^{-1} [\vec b]_\times \vec R^T$,
+/// where $[\vec v]_\times$ is the cross product matrix of $\vec v$, and in 2D,
+/// $\vec K = (\vec R \vec a^\perp) \otimes (\vec R \vec b^\perp) / \operatorname{tr}(\vec S)$ with
+/// $\vec v^\perp = (-v_2, v_1)$. The exact contraction is not necessarily positive semi-definite. Where the
+/// rotation is not differentiable, which may only happen for degenerate or inverted deformation gradients,
+/// the fixed corotated approximation is used instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorotatedLinearElasticMaterial {
+    /// Whether the stress contraction includes derivatives of the rotation.
+    pub include_rotation_derivatives: bool,
+}
+
+impl Default for CorotatedLinearElasticMaterial {
+    fn default() -> Self {
+        Self {
+            include_rotation_derivatives: true,
+        }
+    }
+}
+
+impl CorotatedLinearElasticMaterial {
+    /// Constructs a new corotated material whose stress contraction includes rotation derivatives.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the stress contraction includes rotation derivatives.
+    ///
+    /// Without rotation derivatives, the stress contraction is the fixed corotated approximation.
+    pub fn with_rotation_derivatives(self, include_rotation_derivatives: bool) -> Self {
+        Self {
+            include_rotation_derivatives,
+        }
+    }
+}
+
+/// Computes the polar decomposition $\vec F = \vec R \vec S$ with a proper rotation $\vec R$ and
+/// a symmetric matrix $\vec S$.
+#[allow(non_snake_case)]
+fn polar_decomposition<T, D>(F: &OMatrix<T, D, D>) -> (OMatrix<T, D, D>, OMatrix<T, D, D>)
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |_| (OMatrix::<T, D, D>::identity(), F.clone()),
+        |dim| {
+            let F = dim.matrix(F);
+            // The rotation maximizes tr(R^T F) = c (F_11 + F_22) + s (F_21 - F_12) over all
+            // rotations R = [c, -s; s, c]
+            let (c, s) = (F[(0, 0)] + F[(1, 1)], F[(1, 0)] - F[(0, 1)]);
+            let norm = c.hypot(s);
+            let R = if norm > T::zero() {
+                Matrix2::new(c / norm, -s / norm, s / norm, c / norm)
+            } else {
+                Matrix2::identity()
+            };
+            let S = R.transpose() * F;
+            (dim.generic_matrix(R), dim.generic_matrix(S))
+        },
+        |dim| {
+            let F = dim.matrix(F);
+            let svd = F.svd(true, true);
+            let mut u = svd.u.expect("U is requested");
+            let v_t = svd.v_t.expect("V^T is requested");
+            let mut sigma = svd.singular_values;
+            if (u * v_t).determinant() < T::zero() {
+                // Flip the sign of the smallest singular value so that R is a proper rotation
+                let i = sigma.imin();
+                sigma[i] = -sigma[i];
+                u.column_mut(i).neg_mut();
+            }
+            let R = u * v_t;
+            // S = V Sigma V^T, where Sigma V^T is obtained by scaling the rows of V^T
+            let mut sigma_v_t = v_t;
+            for i in 0..3 {
+                sigma_v_t.row_mut(i).scale_mut(sigma[i]);
+            }
+            let S = v_t.transpose() * sigma_v_t;
+            (dim.generic_matrix(R), dim.generic_matrix(S))
+        },
+    )
+}
+
+/// Computes the contraction $a_k \pd{R_{ik}}{F_{jm}} b_m \vec e_i \otimes \vec e_j$ of the derivative of the
+/// rotation in the polar decomposition, or `None` if the rotation is not differentiable.
+#[allow(non_snake_case)]
+fn rotation_derivative_contraction<T, D>(
+    R: &OMatrix<T, D, D>,
+    S: &OMatrix<T, D, D>,
+    a: &OVector<T, D>,
+    b: &OVector<T, D>,
+) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |_| Some(OMatrix::<T, D, D>::zeros()),
+        |dim| {
+            let (R, a, b) = (dim.matrix(R), dim.vector(a), dim.vector(b));
+            let tr_S = S.trace();
+            if tr_S <= T::zero() {
+                return None;
+            }
+            let R_a_perp = R * Vector2::new(-a.y, a.x);
+            let R_b_perp = R * Vector2::new(-b.y, b.x);
+            let K = R_a_perp * R_b_perp.transpose() / tr_S;
+            Some(dim.generic_matrix(K))
+        },
+        |dim| {
+            let (R, S, a, b) = (dim.matrix(R), dim.matrix(S), dim.vector(a), dim.vector(b));
+            let G = (Matrix3::identity() * S.trace() - S).try_inverse()?;
+            let K = -R * a.cross_matrix() * G * b.cross_matrix() * R.transpose();
+            Some(dim.generic_matrix(K))
+        },
+    )
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+impl<T, D> HyperelasticMaterial<T, D> for CorotatedLinearElasticMaterial
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type Parameters = LameParameters<T>;
+
+    fn compute_energy_density(&self, deformation_gradient: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient;
+        let (R, _) = polar_decomposition(F);
+        // Since R is orthogonal, |S - I|^2 = |F|^2 - 2 tr(S) + d with tr(S) = tr(R^T F). The rotation
+        // maximizes tr(R^T F), so this formulation is insensitive to small errors in R
+        let d = T::from_usize(D::dim()).unwrap();
+        let tr_S = R.dot(F);
+        mu * (F.norm_squared() - 2.0 * tr_S + d) + 0.5 * lambda * (tr_S - d).powi(2)
+    }
+
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let F = deformation_gradient;
+        let (R, S) = polar_decomposition(F);
+        let tr_eps = S.trace() - T::from_usize(D::dim()).unwrap();
+        (F - &R) * (2.0 * mu) + R * (lambda * tr_eps)
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let &LameParameters { mu, lambda } = parameters;
+        let (R, S) = polar_decomposition(deformation_gradient);
+        let I = OMatrix::<T, D, D>::identity();
+        let ref R_a = &R * a;
+        let ref R_b = &R * b;
+        let fixed_contraction = I * (2.0 * mu * a.dot(b)) + R_a * (R_b.transpose() * lambda);
+
+        let rotation_contraction = self
+            .include_rotation_derivatives
+            .then(|| rotation_derivative_contraction(&R, &S, a, b))
+            .flatten();
+        if let Some(K) = rotation_contraction {
+            let tr_eps = S.trace() - T::from_usize(D::dim()).unwrap();
+            fixed_contraction + K * (lambda * tr_eps - 2.0 * mu)
+        } else {
+            fixed_contraction
         }
     }
 }
@@ -467,3 +1005,333 @@ where
         })
     }
 }
+
+/// Parameters for [`TransverselyIsotropicMaterial`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransverselyIsotropicParameters<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Lamé parameters of the isotropic Neo-Hookean base material.
+    pub lame: LameParameters<T>,
+    /// The fiber direction $\vec n$ in the reference configuration. Must have unit length.
+    pub fiber_direction: OVector<T, D>,
+    /// The fiber stiffness $k_1$.
+    pub fiber_stiffness: T,
+    /// The dimensionless fiber nonlinearity $k_2 \geq 0$. With $k_2 = 0$ the fiber term is quadratic.
+    pub fiber_nonlinearity: T,
+}
+
+impl<T, D> Default for TransverselyIsotropicParameters<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            lame: LameParameters::default(),
+            fiber_direction: OVector::<T, D>::zeros(),
+            fiber_stiffness: T::zero(),
+            fiber_nonlinearity: T::zero(),
+        }
+    }
+}
+
+/// A transversely isotropic, fiber-reinforced material model.
+///
+/// The material adds an exponential fiber term of Holzapfel-Gasser-Ogden type to the isotropic
+/// [`NeoHookeanMaterial`]. With fiber direction $\vec n$ and the invariant
+/// $I_4 = \vec n^T \vec C \vec n = | \vec F \vec n |^2$, which measures the squared fiber stretch,
+/// the strain energy density is
+/// <div>$$
+/// \psi(\vec F) = \psi_{\text{NH}}(\vec F)
+///     + \frac{k_1}{2 k_2} \left( \exp \left( k_2 (I_4 - 1)^2 \right) - 1 \right),
+/// $$</div>
+/// where $k_1$ is the fiber stiffness and $k_2$ is the fiber nonlinearity. In the limit $k_2 \rightarrow 0$,
+/// the fiber term becomes the quadratic term $\frac{k_1}{2} (I_4 - 1)^2$. The fiber term is active in both
+/// tension and compression. The parameters are given by [`TransverselyIsotropicParameters`], so that the
+/// fiber direction may vary from quadrature point to quadrature point.
+///
+/// Writing $\psi_f'$ and $\psi_f''$ for the first and second derivatives of the fiber term with respect to $I_4$,
+/// the stress tensor is
+/// <div>$$
+/// \vec P(\vec F) = \vec P_{\text{NH}}(\vec F) + 2 \psi_f' (\vec F \vec n) \otimes \vec n
+/// $$</div>
+/// and the contraction operator is
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) = \mathcal{C}_{\vec P, \text{NH}}(\vec F, \vec a, \vec b)
+///     + (\vec n \cdot \vec a) (\vec n \cdot \vec b)
+///     \left[ 4 \psi_f'' (\vec F \vec n) \otimes (\vec F \vec n) + 2 \psi_f' \vec I \right].
+/// $$</div>
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransverselyIsotropicMaterial;
+
+/// Computes the fiber energy $\psi_f$ and its first and second derivatives with respect to $I_4$.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+fn fiber_energy_and_derivatives<T: Real>(I4: T, k1: T, k2: T) -> (T, T, T) {
+    if k1 == 0.0 {
+        // Avoid 0 * inf = NaN when the exponential overflows for large fiber stretches
+        return (0.0, 0.0, 0.0);
+    }
+    let x = I4 - 1.0;
+    let y = k2 * x * x;
+    // (exp(y) - 1) / y, which is 1 for y = 0, computed without cancellation
+    let exp_m1_over_y = if y == 0.0 { 1.0 } else { y.exp_m1() / y };
+    let exp_y = y.exp();
+    let psi = 0.5 * k1 * x * x * exp_m1_over_y;
+    let dpsi = k1 * x * exp_y;
+    let d2psi = k1 * exp_y * (1.0 + 2.0 * y);
+    (psi, dpsi, d2psi)
+}
+
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+impl<T, D> HyperelasticMaterial<T, D> for TransverselyIsotropicMaterial
+where
+    T: Real,
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type Parameters = TransverselyIsotropicParameters<T, D>;
+
+    fn compute_energy_density(&self, deformation_gradient: &OMatrix<T, D, D>, parameters: &Self::Parameters) -> T {
+        let F = deformation_gradient;
+        let n = &parameters.fiber_direction;
+        let I4 = (F * n).norm_squared();
+        let (psi_f, _, _) = fiber_energy_and_derivatives(I4, parameters.fiber_stiffness, parameters.fiber_nonlinearity);
+        NeoHookeanMaterial.compute_energy_density(F, &parameters.lame) + psi_f
+    }
+
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let F = deformation_gradient;
+        let n = &parameters.fiber_direction;
+        let F_n = F * n;
+        let (_, dpsi_f, _) = fiber_energy_and_derivatives(
+            F_n.norm_squared(),
+            parameters.fiber_stiffness,
+            parameters.fiber_nonlinearity,
+        );
+        let P_f = F_n * (n.transpose() * (2.0 * dpsi_f));
+        NeoHookeanMaterial.compute_stress_tensor(F, &parameters.lame) + P_f
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &OMatrix<T, D, D>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, D> {
+        let F = deformation_gradient;
+        let n = &parameters.fiber_direction;
+        let ref F_n = F * n;
+        let (_, dpsi_f, d2psi_f) = fiber_energy_and_derivatives(
+            F_n.norm_squared(),
+            parameters.fiber_stiffness,
+            parameters.fiber_nonlinearity,
+        );
+        let I = OMatrix::<T, D, D>::identity();
+        let C_f = (F_n * (F_n.transpose() * (4.0 * d2psi_f)) + I * (2.0 * dpsi_f)) * (n.dot(a) * n.dot(b));
+        NeoHookeanMaterial.compute_stress_contraction(F, a, b, &parameters.lame) + C_f
+    }
+}
+
+/// Embeds the 2D matrix `M` in the upper-left block of a 3D matrix with the given $(3, 3)$ entry.
+#[allow(non_snake_case)]
+fn embed_in_3d<T: Real>(M: &Matrix2<T>, M_33: T) -> Matrix3<T> {
+    let mut M_3d = Matrix3::zeros();
+    M_3d.fixed_view_mut::<2, 2>(0, 0).copy_from(M);
+    M_3d[(2, 2)] = M_33;
+    M_3d
+}
+
+fn embed_vector_in_3d<T: Real>(v: &Vector2<T>) -> Vector3<T> {
+    Vector3::new(v.x, v.y, T::zero())
+}
+
+/// Returns the upper-left (in-plane) $2 \times 2$ block of a 3D matrix.
+#[allow(non_snake_case)]
+fn in_plane_block<T: Real>(M: &Matrix3<T>) -> Matrix2<T> {
+    M.fixed_view::<2, 2>(0, 0).into_owned()
+}
+
+/// Adapts a 3D material to 2D under the plane strain assumption.
+///
+/// The 2D deformation gradient $\vec F$ is embedded in the 3D deformation gradient
+/// <div>$$
+/// \vec F_{3D} = \begin{pmatrix} \vec F & \vec 0 \newline \vec 0^T & 1 \end{pmatrix},
+/// $$</div>
+/// so that the out-of-plane strain vanishes. The energy density is the energy density of the 3D material
+/// evaluated at $\vec F_{3D}$, and the stress tensor and stress contraction are the in-plane blocks of
+/// the corresponding 3D quantities. For isotropic materials, the out-of-plane shear stresses vanish,
+/// and the result is exact.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaneStrain<M> {
+    pub material: M,
+}
+
+impl<M> PlaneStrain<M> {
+    pub fn new(material: M) -> Self {
+        Self { material }
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, M> HyperelasticMaterial<T, U2> for PlaneStrain<M>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    type Parameters = M::Parameters;
+
+    fn compute_energy_density(&self, deformation_gradient: &Matrix2<T>, parameters: &Self::Parameters) -> T {
+        let F = embed_in_3d(deformation_gradient, T::one());
+        self.material.compute_energy_density(&F, parameters)
+    }
+
+    fn compute_stress_tensor(&self, deformation_gradient: &Matrix2<T>, parameters: &Self::Parameters) -> Matrix2<T> {
+        let F = embed_in_3d(deformation_gradient, T::one());
+        in_plane_block(&self.material.compute_stress_tensor(&F, parameters))
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &Matrix2<T>,
+        a: &Vector2<T>,
+        b: &Vector2<T>,
+        parameters: &Self::Parameters,
+    ) -> Matrix2<T> {
+        let F = embed_in_3d(deformation_gradient, T::one());
+        let C =
+            self.material
+                .compute_stress_contraction(&F, &embed_vector_in_3d(a), &embed_vector_in_3d(b), parameters);
+        in_plane_block(&C)
+    }
+}
+
+/// Adapts a 3D material to 2D under the plane stress assumption.
+///
+/// The 2D deformation gradient $\vec F$ is embedded in the 3D deformation gradient
+/// <div>$$
+/// \vec F_{3D} = \begin{pmatrix} \vec F & \vec 0 \newline \vec 0^T & \lambda_3 \end{pmatrix},
+/// $$</div>
+/// where the out-of-plane stretch $\lambda_3 = \lambda_3(\vec F)$ is determined such that the out-of-plane
+/// stress vanishes, $P_{33}(\vec F_{3D}) = 0$. The stretch is computed by Newton's method, starting from the
+/// rest state $\lambda_3 = 1$. For linear elasticity, $P_{33}$ is affine in $\lambda_3$ and the first Newton
+/// step gives the analytic solution. For isotropic materials, the out-of-plane shear stresses vanish.
+///
+/// The energy density is the energy density of the 3D material evaluated at $\vec F_{3D}$. Since $P_{33} = 0$,
+/// the stress tensor is the in-plane block of the 3D stress tensor. The stress contraction accounts for the
+/// dependence of $\lambda_3$ on $\vec F$ by static condensation, i.e.
+/// <div>$$
+/// \mathcal{C}_{\vec P}(\vec F, \vec a, \vec b) = \left[ \mathcal{C}_{3D}(\vec a, \vec b) \right]_{\alpha \beta}
+///     - \frac{\left[ \mathcal{C}_{3D}(\vec a, \vec e_3) \right]_{\alpha 3}
+///         \left[ \mathcal{C}_{3D}(\vec e_3, \vec b) \right]_{3 \beta}}
+///         {\left[ \mathcal{C}_{3D}(\vec e_3, \vec e_3) \right]_{33}},
+/// $$</div>
+/// where $\mathcal{C}_{3D}$ is the stress contraction of the 3D material at $\vec F_{3D}$, the vectors
+/// $\vec a, \vec b$ are embedded in 3D and $\alpha, \beta \in \\{ 1, 2 \\}$.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaneStress<M> {
+    pub material: M,
+}
+
+impl<M> PlaneStress<M> {
+    pub fn new(material: M) -> Self {
+        Self { material }
+    }
+
+    /// Computes the out-of-plane stretch $\lambda_3$ for the given 2D deformation gradient.
+    ///
+    /// If Newton's method fails to make progress, for example because the material is not
+    /// stable in the out-of-plane direction, the last iterate is returned.
+    #[allow(non_snake_case)]
+    #[replace_float_literals(T::from_f64(literal).expect("literal must fit in T"))]
+    pub fn compute_out_of_plane_stretch<T>(&self, deformation_gradient: &Matrix2<T>, parameters: &M::Parameters) -> T
+    where
+        T: Real,
+        M: HyperelasticMaterial<T, U3>,
+    {
+        // Newton's method converges quadratically, so once the step is of the order of sqrt(eps),
+        // the error after the step is of the order of eps
+        let tolerance = T::default_epsilon().sqrt();
+        let max_iterations = 50;
+        let ref e_3 = Vector3::z();
+        let mut lambda_3 = 1.0;
+        for _ in 0..max_iterations {
+            let F = embed_in_3d(deformation_gradient, lambda_3);
+            let P_33 = self.material.compute_stress_tensor(&F, parameters)[(2, 2)];
+            let dP33_dF33 = self
+                .material
+                .compute_stress_contraction(&F, e_3, e_3, parameters)[(2, 2)];
+            if dP33_dF33 <= 0.0 || !dP33_dF33.is_finite() {
+                break;
+            }
+            let step = -P_33 / dP33_dF33;
+            let next = lambda_3 + step;
+            // Do not let the iterate cross into inverted configurations
+            lambda_3 = if next > 0.0 { next } else { 0.5 * lambda_3 };
+            if step.abs() <= tolerance * lambda_3 {
+                break;
+            }
+        }
+        lambda_3
+    }
+}
+
+#[allow(non_snake_case)]
+impl<T, M> HyperelasticMaterial<T, U2> for PlaneStress<M>
+where
+    T: Real,
+    M: HyperelasticMaterial<T, U3>,
+{
+    type Parameters = M::Parameters;
+
+    fn compute_energy_density(&self, deformation_gradient: &Matrix2<T>, parameters: &Self::Parameters) -> T {
+        let lambda_3 = self.compute_out_of_plane_stretch(deformation_gradient, parameters);
+        let F = embed_in_3d(deformation_gradient, lambda_3);
+        self.material.compute_energy_density(&F, parameters)
+    }
+
+    fn compute_stress_tensor(&self, deformation_gradient: &Matrix2<T>, parameters: &Self::Parameters) -> Matrix2<T> {
+        let lambda_3 = self.compute_out_of_plane_stretch(deformation_gradient, parameters);
+        let F = embed_in_3d(deformation_gradient, lambda_3);
+        in_plane_block(&self.material.compute_stress_tensor(&F, parameters))
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        deformation_gradient: &Matrix2<T>,
+        a: &Vector2<T>,
+        b: &Vector2<T>,
+        parameters: &Self::Parameters,
+    ) -> Matrix2<T> {
+        let lambda_3 = self.compute_out_of_plane_stretch(deformation_gradient, parameters);
+        let F = embed_in_3d(deformation_gradient, lambda_3);
+        let ref a = embed_vector_in_3d(a);
+        let ref b = embed_vector_in_3d(b);
+        let ref e_3 = Vector3::z();
+        let C_ab = self
+            .material
+            .compute_stress_contraction(&F, a, b, parameters);
+        let C_a3 = self
+            .material
+            .compute_stress_contraction(&F, a, e_3, parameters);
+        let C_3b = self
+            .material
+            .compute_stress_contraction(&F, e_3, b, parameters);
+        let C_33 = self
+            .material
+            .compute_stress_contraction(&F, e_3, e_3, parameters)[(2, 2)];
+        let coupling = C_a3.fixed_view::<2, 1>(0, 2) * C_3b.fixed_view::<1, 2>(2, 0);
+        in_plane_block(&C_ab) - coupling / C_33
+    }
+}
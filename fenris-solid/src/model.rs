@@ -0,0 +1,66 @@
+//! Higher-level functionality for setting up and solving solid mechanics problems.
+//!
+//! # Modal analysis
+//!
+//! The natural frequencies and mode shapes of an elastic body are given by the generalized
+//! eigenvalue problem
+//! $$ \vec K \vec u = \omega^2 \vec M \vec u, $$
+//! where $\vec K$ is the stiffness matrix and $\vec M$ is the mass matrix. [`ModalProblem`]
+//! assembles the stiffness and mass matrices, and eliminates Dirichlet-constrained degrees of
+//! freedom by *reduction*, i.e. by removing the associated rows and columns. The reduced
+//! matrices may be passed to an external eigensolver, or the lowest modes can be computed with
+//! [`ModalProblem::compute_lowest_modes`].
+//!
+//! # Initial conditions
+//!
+//! Initial conditions given as functions must be transferred to the finite element space.
+//! [`project_initial_condition`] computes the consistent $L^2$ projection, which conserves the
+//! integral of the field and is more accurate than nodal interpolation for fields that are not
+//! smooth on the scale of the mesh. [`project_initial_condition_lumped`] is a cheaper variant
+//! based on the lumped mass matrix.
+//!
+//! # Problem descriptions
+//!
+//! A [`ProblemDescription`] is a serializable description of a complete problem setup: the mesh,
+//! the physics, materials, named node sets, boundary conditions, loads and optionally the time
+//! stepping parameters. Storing problem setups as data files (for example JSON) makes experiments
+//! reproducible and easy to share. A [`ProblemBuilder`] validates a description and turns it into
+//! a [`Problem`], which solves the static problem or simulates the dynamic problem with one of
+//! the integrators in [`dynamics`].
+//!
+//! # Quasi-static hyperelasticity
+//!
+//! [`QuasiStaticModel`] assembles the energy, residual and tangent of a hyperelastic body
+//! subject to Dirichlet boundary conditions and external loads, and solves for equilibrium with
+//! Newton's method in [`QuasiStaticModel::solve_load_step`]. Large loads are typically applied
+//! in several load steps, each starting from the solution of the previous step.
+//!
+//! # Elastodynamics
+//!
+//! The [`dynamics`] module provides integrators for the equations of motion of elastic bodies,
+//! using the quasi-static model to evaluate internal forces.
+//!
+//! # Reaction-diffusion systems
+//!
+//! [`ImexReactionDiffusion`] integrates multi-species reaction-diffusion systems in time with an
+//! implicit-explicit scheme: diffusion is treated implicitly and reactions explicitly at the nodes.
+//!
+//! # Recording diagnostics
+//!
+//! A [`Recorder`] collects time series of scalar diagnostics, such as the total energy or the
+//! maximum displacement, from time-dependent simulations.
+pub mod dynamics;
+
+mod description;
+mod modal;
+mod projection;
+mod quasi_static;
+mod reaction_diffusion;
+mod recorder;
+
+pub use description::*;
+pub use modal::*;
+pub use projection::*;
+pub use quasi_static::*;
+pub use reaction_diffusion::*;
+pub use recorder::*;
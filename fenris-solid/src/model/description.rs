@@ -0,0 +1,933 @@
+use crate::materials::{
+    CorotatedLinearElasticMaterial, LameParameters, LinearElasticMaterial, NeoHookeanMaterial, StVKMaterial,
+    StableNeoHookeanMaterial, YoungPoisson,
+};
+use crate::model::dynamics::{DynamicState, NewmarkIntegrator, SymplecticEuler, TimeIntegrator};
+use crate::model::{LoadStepSettings, QuasiStaticModel, Recorder};
+use crate::{HyperelasticMaterial, MaterialEllipticOperator, PhysicalDim};
+use eyre::{eyre, WrapErr};
+use fenris::allocators::{DimAllocator, ElementConnectivityAllocator, TriDimAllocator};
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, VectorAssembler,
+};
+use fenris::assembly::local::{
+    Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, ElementSourceAssemblerBuilder, SourceFunction,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
+use fenris::assembly::DirichletBcs;
+use fenris::element::ElementConnectivity;
+use fenris::io::msh::{load_msh_from_file, MshConnectivity};
+use fenris::mesh::Mesh;
+use fenris::nalgebra::{DVector, DefaultAllocator, DimName, OPoint, OVector, U1};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature::{CanonicalMassQuadrature, CanonicalStiffnessQuadrature};
+use fenris::SmallDim;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Evaluates `$body` with `$material` bound to a reference to the material of the given model.
+macro_rules! with_material {
+    ($model:expr, |$material:ident| $body:expr) => {
+        match $model {
+            MaterialModel::LinearElastic => {
+                let $material = &LinearElasticMaterial;
+                $body
+            }
+            MaterialModel::NeoHookean => {
+                let $material = &NeoHookeanMaterial;
+                $body
+            }
+            MaterialModel::StVK => {
+                let $material = &StVKMaterial;
+                $body
+            }
+            MaterialModel::StableNeoHookean => {
+                let $material = &StableNeoHookeanMaterial;
+                $body
+            }
+            MaterialModel::Corotated => {
+                let $material = &CorotatedLinearElasticMaterial::default();
+                $body
+            }
+        }
+    };
+}
+
+/// A serializable description of a complete problem setup.
+///
+/// Entities such as materials and node sets are given names, which are referenced by other
+/// entries in the description. The description is only a collection of data: use a
+/// [`ProblemBuilder`] to validate the description and construct the corresponding [`Problem`].
+///
+/// All types that make up a description reject unknown fields upon deserialization, so that
+/// misspelled entries in a problem file are not silently ignored.
+///
+/// An example of a description in JSON format:
+///
+/// ```json
+/// {
+///   "mesh": { "file": "cantilever.msh" },
+///   "physics": { "type": "elasticity", "material": "steel" },
+///   "materials": [
+///     {
+///       "name": "steel",
+///       "model": "linear_elastic",
+///       "parameters": { "young_poisson": { "young": 200e9, "poisson": 0.3 } },
+///       "density": 7850.0
+///     }
+///   ],
+///   "node_sets": [
+///     { "name": "clamped", "selection": { "bounding_box": { "min": [0.0, 0.0], "max": [0.0, 0.1] } } },
+///     { "name": "tip", "selection": { "indices": [40] } }
+///   ],
+///   "dirichlet": [ { "node_set": "clamped" } ],
+///   "loads": [ { "type": "nodal_force", "node_set": "tip", "value": [0.0, -1000.0] } ],
+///   "time_stepping": { "time_step": 1e-4, "num_steps": 100, "integrator": { "type": "newmark" } }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProblemDescription {
+    pub mesh: MeshDescription,
+    pub physics: PhysicsDescription,
+    #[serde(default)]
+    pub materials: Vec<MaterialDescription>,
+    #[serde(default)]
+    pub node_sets: Vec<NodeSetDescription>,
+    #[serde(default)]
+    pub dirichlet: Vec<DirichletDescription>,
+    #[serde(default)]
+    pub loads: Vec<LoadDescription>,
+    /// Parameters for the simulation of the dynamic problem, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_stepping: Option<TimeSteppingDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MeshDescription {
+    /// Path to a Gmsh MSH file.
+    ///
+    /// Relative paths are resolved against the base directory of the [`ProblemBuilder`].
+    pub file: PathBuf,
+}
+
+/// The physics that the problem models.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PhysicsDescription {
+    /// Elasticity with the named material.
+    ///
+    /// For the [`LinearElastic`](MaterialModel::LinearElastic) model the static problem is a
+    /// linear system. All other models are nonlinear, and the static problem is solved with
+    /// Newton's method (see [`Problem::solve_static`]).
+    Elasticity { material: String },
+    /// The Poisson equation $- \Delta u = f$ for a scalar unknown $u$.
+    Poisson {},
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaterialDescription {
+    pub name: String,
+    pub model: MaterialModel,
+    pub parameters: ElasticParameters,
+    /// The mass density, which defaults to `1`.
+    #[serde(default = "default_density")]
+    pub density: f64,
+}
+
+fn default_density() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialModel {
+    /// See [`LinearElasticMaterial`].
+    LinearElastic,
+    /// See [`NeoHookeanMaterial`].
+    NeoHookean,
+    /// See [`StVKMaterial`].
+    #[serde(rename = "stvk")]
+    StVK,
+    /// See [`StableNeoHookeanMaterial`].
+    StableNeoHookean,
+    /// See [`CorotatedLinearElasticMaterial`].
+    Corotated,
+}
+
+impl MaterialModel {
+    /// Whether the internal forces of the material model are linear in the displacement.
+    pub fn is_linear(&self) -> bool {
+        matches!(self, Self::LinearElastic)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ElasticParameters {
+    Lame { mu: f64, lambda: f64 },
+    YoungPoisson { young: f64, poisson: f64 },
+}
+
+impl ElasticParameters {
+    pub fn to_lame_parameters(&self) -> eyre::Result<LameParameters<f64>> {
+        match *self {
+            Self::Lame { mu, lambda } => Ok(LameParameters { mu, lambda }),
+            Self::YoungPoisson { young, poisson } => YoungPoisson { young, poisson }.try_into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeSetDescription {
+    pub name: String,
+    pub selection: NodeSelection,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum NodeSelection {
+    /// Explicit (zero-based) node indices.
+    Indices(Vec<usize>),
+    /// All nodes inside the closed axis-aligned box `[min, max]`.
+    BoundingBox { min: Vec<f64>, max: Vec<f64> },
+}
+
+/// Prescribes all solution components of the nodes in the named node set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirichletDescription {
+    pub node_set: String,
+    /// The prescribed value, with one entry for each solution component. Defaults to zero.
+    ///
+    /// If a node is contained in several Dirichlet node sets, the last entry takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LoadDescription {
+    /// A uniform source density $\vec f$ in the domain, corresponding to the weak form term
+    /// $\int_\Omega \vec f \cdot \vec w \, \mathrm{d} \vec X$.
+    BodyForce { value: Vec<f64> },
+    /// A force applied to each node in the named node set.
+    NodalForce { node_set: String, value: Vec<f64> },
+}
+
+/// Parameters for the time integration of an elastodynamics problem.
+///
+/// See the [`dynamics`](crate::model::dynamics) module for the available integrators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeSteppingDescription {
+    pub time_step: f64,
+    pub num_steps: usize,
+    pub integrator: IntegratorDescription,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum IntegratorDescription {
+    /// See [`NewmarkIntegrator`].
+    ///
+    /// Parameters that are not given take the default values of [`NewmarkIntegrator`].
+    Newmark {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        beta: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        gamma: Option<f64>,
+        /// The relative tolerance of the Newton iterations in each step.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tolerance: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_iterations: Option<usize>,
+    },
+    /// See [`SymplecticEuler`].
+    SymplecticEuler {},
+}
+
+impl ProblemDescription {
+    pub fn find_material(&self, name: &str) -> Option<&MaterialDescription> {
+        self.materials.iter().find(|material| material.name == name)
+    }
+
+    pub fn find_node_set(&self, name: &str) -> Option<&NodeSetDescription> {
+        self.node_sets.iter().find(|node_set| node_set.name == name)
+    }
+
+    /// Checks that the description is self-consistent.
+    ///
+    /// This checks that names are unique, that all referenced materials and node sets exist
+    /// and that parameters are in a valid range. The returned error names the offending entry.
+    /// Checks that require the mesh, such as the dimensions of vectors, are performed by
+    /// [`ProblemBuilder`].
+    pub fn validate(&self) -> eyre::Result<()> {
+        let mut material_names = HashSet::new();
+        for (i, material) in self.materials.iter().enumerate() {
+            let entry = || format!("materials[{}] (\"{}\")", i, material.name);
+            if !material_names.insert(material.name.as_str()) {
+                return Err(eyre!("{}: duplicate material name", entry()));
+            }
+            if !(material.density.is_finite() && material.density >= 0.0) {
+                return Err(eyre!("{}: density must be finite and non-negative", entry()));
+            }
+            let lame = material
+                .parameters
+                .to_lame_parameters()
+                .wrap_err_with(|| format!("{}: invalid elastic parameters {:?}", entry(), material.parameters))?;
+            if !(lame.mu.is_finite() && lame.lambda.is_finite() && lame.mu > 0.0) {
+                return Err(eyre!(
+                    "{}: invalid elastic parameters {:?}",
+                    entry(),
+                    material.parameters
+                ));
+            }
+        }
+
+        let mut node_set_names = HashSet::new();
+        for (i, node_set) in self.node_sets.iter().enumerate() {
+            let entry = || format!("node_sets[{}] (\"{}\")", i, node_set.name);
+            if !node_set_names.insert(node_set.name.as_str()) {
+                return Err(eyre!("{}: duplicate node set name", entry()));
+            }
+            if let NodeSelection::BoundingBox { min, max } = &node_set.selection {
+                if min.len() != max.len() {
+                    return Err(eyre!("{}: bounds of bounding box have different dimensions", entry()));
+                }
+            }
+        }
+
+        if let PhysicsDescription::Elasticity { material } = &self.physics {
+            if self.find_material(material).is_none() {
+                return Err(eyre!("physics: unknown material \"{}\"", material));
+            }
+        }
+
+        for (i, dirichlet) in self.dirichlet.iter().enumerate() {
+            if self.find_node_set(&dirichlet.node_set).is_none() {
+                return Err(eyre!("dirichlet[{}]: unknown node set \"{}\"", i, dirichlet.node_set));
+            }
+        }
+
+        for (i, load) in self.loads.iter().enumerate() {
+            if let LoadDescription::NodalForce { node_set, .. } = load {
+                if self.find_node_set(node_set).is_none() {
+                    return Err(eyre!("loads[{}]: unknown node set \"{}\"", i, node_set));
+                }
+            }
+        }
+
+        if let Some(time_stepping) = &self.time_stepping {
+            if let PhysicsDescription::Poisson {} = &self.physics {
+                return Err(eyre!(
+                    "time_stepping: time stepping is only supported for elasticity problems"
+                ));
+            }
+            if !(time_stepping.time_step.is_finite() && time_stepping.time_step > 0.0) {
+                return Err(eyre!(
+                    "time_stepping: time step {} must be finite and positive",
+                    time_stepping.time_step
+                ));
+            }
+            if let IntegratorDescription::Newmark {
+                beta, gamma, tolerance, ..
+            } = &time_stepping.integrator
+            {
+                if let Some(beta) = *beta {
+                    if !(beta.is_finite() && beta > 0.0) {
+                        return Err(eyre!(
+                            "time_stepping.integrator: beta = {} must be finite and positive",
+                            beta
+                        ));
+                    }
+                }
+                if let Some(gamma) = *gamma {
+                    if !(gamma.is_finite() && gamma >= 0.0) {
+                        return Err(eyre!(
+                            "time_stepping.integrator: gamma = {} must be finite and non-negative",
+                            gamma
+                        ));
+                    }
+                }
+                if let Some(tolerance) = *tolerance {
+                    if !(tolerance.is_finite() && tolerance >= 0.0) {
+                        return Err(eyre!(
+                            "time_stepping.integrator: tolerance = {} must be finite and non-negative",
+                            tolerance
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates a [`ProblemDescription`] and constructs the corresponding [`Problem`].
+#[derive(Debug, Clone)]
+pub struct ProblemBuilder {
+    description: ProblemDescription,
+    base_dir: PathBuf,
+}
+
+impl ProblemBuilder {
+    pub fn new(description: ProblemDescription) -> Self {
+        Self {
+            description,
+            base_dir: PathBuf::new(),
+        }
+    }
+
+    /// Sets the directory that relative mesh paths are resolved against.
+    ///
+    /// Typically this is the directory of the problem file. By default, relative paths are
+    /// resolved against the current working directory.
+    pub fn with_base_dir(self, base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            ..self
+        }
+    }
+
+    pub fn description(&self) -> &ProblemDescription {
+        &self.description
+    }
+
+    /// Loads the mesh referenced by the description and constructs the problem.
+    pub fn build<D, C>(&self) -> eyre::Result<Problem<D, C>>
+    where
+        D: DimName,
+        C: MshConnectivity,
+        DefaultAllocator: DimAllocator<f64, D>,
+    {
+        let path = self.base_dir.join(&self.description.mesh.file);
+        let mesh = load_msh_from_file(&path).wrap_err_with(|| format!("mesh: failed to load {}", path.display()))?;
+        self.build_with_mesh(mesh)
+    }
+
+    /// Constructs the problem with the given mesh in place of the mesh file referenced by the
+    /// description.
+    pub fn build_with_mesh<D, C>(&self, mesh: Mesh<f64, D, C>) -> eyre::Result<Problem<D, C>>
+    where
+        D: DimName,
+        DefaultAllocator: DimAllocator<f64, D>,
+    {
+        let description = &self.description;
+        description.validate()?;
+
+        let num_nodes = mesh.vertices().len();
+        let solution_dim = solution_dim::<D>(&description.physics);
+
+        let mut node_sets = Vec::with_capacity(description.node_sets.len());
+        for (i, node_set) in description.node_sets.iter().enumerate() {
+            let entry = || format!("node_sets[{}] (\"{}\")", i, node_set.name);
+            let nodes = match &node_set.selection {
+                NodeSelection::Indices(indices) => {
+                    if let Some(index) = indices.iter().find(|&&index| index >= num_nodes) {
+                        return Err(eyre!(
+                            "{}: node index {} out of bounds for mesh with {} nodes",
+                            entry(),
+                            index,
+                            num_nodes
+                        ));
+                    }
+                    indices.clone()
+                }
+                NodeSelection::BoundingBox { min, max } => {
+                    if min.len() != D::dim() {
+                        return Err(eyre!(
+                            "{}: bounding box has dimension {}, but mesh has dimension {}",
+                            entry(),
+                            min.len(),
+                            D::dim()
+                        ));
+                    }
+                    let is_inside = |x: &OPoint<f64, D>| (0..D::dim()).all(|k| min[k] <= x[k] && x[k] <= max[k]);
+                    mesh.vertices()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, x)| is_inside(x))
+                        .map(|(index, _)| index)
+                        .collect()
+                }
+            };
+            if nodes.is_empty() {
+                return Err(eyre!("{}: node set is empty", entry()));
+            }
+            node_sets.push(nodes);
+        }
+
+        let check_value_dim = |entry: String, value: &[f64]| {
+            if value.len() == solution_dim {
+                Ok(())
+            } else {
+                Err(eyre!(
+                    "{}: value has {} components, but solution has {} components",
+                    entry,
+                    value.len(),
+                    solution_dim
+                ))
+            }
+        };
+        for (i, dirichlet) in description.dirichlet.iter().enumerate() {
+            if let Some(value) = &dirichlet.value {
+                check_value_dim(format!("dirichlet[{}]", i), value)?;
+            }
+        }
+        for (i, load) in description.loads.iter().enumerate() {
+            match load {
+                LoadDescription::BodyForce { value } | LoadDescription::NodalForce { value, .. } => {
+                    check_value_dim(format!("loads[{}]", i), value)?
+                }
+            }
+        }
+
+        Ok(Problem {
+            mesh,
+            description: description.clone(),
+            node_sets,
+        })
+    }
+}
+
+fn solution_dim<D: DimName>(physics: &PhysicsDescription) -> usize {
+    match physics {
+        PhysicsDescription::Elasticity { .. } => D::dim(),
+        PhysicsDescription::Poisson {} => 1,
+    }
+}
+
+/// A validated problem, constructed from a [`ProblemDescription`] by a [`ProblemBuilder`].
+#[derive(Debug, Clone)]
+pub struct Problem<D, C>
+where
+    D: DimName,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    mesh: Mesh<f64, D, C>,
+    description: ProblemDescription,
+    // Nodes of each node set, in the same order as in the description
+    node_sets: Vec<Vec<usize>>,
+}
+
+impl<D, C> Problem<D, C>
+where
+    D: DimName,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    pub fn mesh(&self) -> &Mesh<f64, D, C> {
+        &self.mesh
+    }
+
+    pub fn description(&self) -> &ProblemDescription {
+        &self.description
+    }
+
+    /// The number of solution components at each node.
+    pub fn solution_dim(&self) -> usize {
+        solution_dim::<D>(&self.description.physics)
+    }
+
+    /// The total number of degrees of freedom.
+    pub fn num_dofs(&self) -> usize {
+        self.solution_dim() * self.mesh.vertices().len()
+    }
+
+    /// The nodes in the node set with the given name.
+    pub fn node_set(&self, name: &str) -> Option<&[usize]> {
+        self.description
+            .node_sets
+            .iter()
+            .position(|node_set| node_set.name == name)
+            .map(|index| self.node_sets[index].as_slice())
+    }
+
+    /// The material of an elasticity problem.
+    pub fn material(&self) -> Option<&MaterialDescription> {
+        match &self.description.physics {
+            PhysicsDescription::Elasticity { material } => self.description.find_material(material),
+            PhysicsDescription::Poisson {} => None,
+        }
+    }
+
+    /// The sorted indices of all nodes with Dirichlet boundary conditions.
+    pub fn dirichlet_nodes(&self) -> Vec<usize> {
+        let mut nodes: Vec<_> = self
+            .description
+            .dirichlet
+            .iter()
+            .flat_map(|dirichlet| self.node_set(&dirichlet.node_set).unwrap_or(&[]))
+            .copied()
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    /// The Dirichlet boundary conditions for all degrees of freedom of the Dirichlet nodes.
+    pub fn dirichlet_bcs(&self) -> DirichletBcs<f64> {
+        let s = self.solution_dim();
+        let values = self.dirichlet_values();
+        let dofs: Vec<_> = self
+            .dirichlet_nodes()
+            .into_iter()
+            .flat_map(|node| (0..s).map(move |k| s * node + k))
+            .collect();
+        let dof_values = dofs.iter().map(|&dof| values[dof]).collect();
+        DirichletBcs::from_dofs_and_values(dofs, dof_values).expect("Dirichlet nodes are unique")
+    }
+
+    /// The vector of prescribed Dirichlet values, with zero entries for all free degrees of freedom.
+    pub fn dirichlet_values(&self) -> DVector<f64> {
+        let s = self.solution_dim();
+        let mut values = DVector::zeros(self.num_dofs());
+        for dirichlet in &self.description.dirichlet {
+            let nodes = self.node_set(&dirichlet.node_set).unwrap_or(&[]);
+            for &node in nodes {
+                for k in 0..s {
+                    values[s * node + k] = dirichlet.value.as_ref().map_or(0.0, |value| value[k]);
+                }
+            }
+        }
+        values
+    }
+}
+
+impl<D, C> Problem<D, C>
+where
+    D: PhysicalDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    Mesh<f64, D, C>: CanonicalStiffnessQuadrature<Quadrature = UniformQuadratureTable<f64, D>>
+        + CanonicalMassQuadrature<Quadrature = UniformQuadratureTable<f64, D>>,
+    DefaultAllocator: ElementConnectivityAllocator<f64, C>
+        + TriDimAllocator<f64, D, D, D>
+        + TriDimAllocator<f64, U1, D, D>
+        + TriDimAllocator<f64, D, D, U1>,
+{
+    /// Assembles the stiffness matrix, without boundary conditions applied.
+    ///
+    /// For nonlinear material models, this is the tangent stiffness matrix in the reference
+    /// configuration.
+    pub fn assemble_stiffness(&self) -> eyre::Result<CsrMatrix<f64>> {
+        match &self.description.physics {
+            PhysicsDescription::Elasticity { .. } => {
+                let material = self
+                    .material()
+                    .expect("Material must exist for validated problem");
+                let parameters = material.parameters.to_lame_parameters()?;
+                with_material!(material.model, |model| assemble_material_stiffness(
+                    &self.mesh, model, parameters
+                ))
+            }
+            PhysicsDescription::Poisson {} => {
+                let u = DVector::zeros(self.num_dofs());
+                let qtable = self.mesh.canonical_stiffness_quadrature();
+                let assembler = ElementEllipticAssemblerBuilder::new()
+                    .with_finite_element_space(&self.mesh)
+                    .with_operator(&LaplaceOperator)
+                    .with_quadrature_table(&qtable)
+                    .with_u(&u)
+                    .build();
+                CsrAssembler::default().assemble(&assembler)
+            }
+        }
+    }
+
+    /// Assembles the mass matrix, without boundary conditions applied.
+    ///
+    /// For elasticity problems, the density of the material is used. For the Poisson equation,
+    /// the density is one.
+    pub fn assemble_mass(&self) -> eyre::Result<CsrMatrix<f64>> {
+        let density = self.material().map_or(1.0, |material| material.density);
+        let qtable = self
+            .mesh
+            .canonical_mass_quadrature()
+            .with_uniform_data(Density(density));
+        let assembler = ElementMassAssembler::with_solution_dim(self.solution_dim())
+            .with_space(&self.mesh)
+            .with_quadrature_table(&qtable);
+        CsrAssembler::default().assemble(&assembler)
+    }
+
+    /// Assembles the load vector, without boundary conditions applied.
+    pub fn assemble_load_vector(&self) -> eyre::Result<DVector<f64>> {
+        let s = self.solution_dim();
+        let mut loads = DVector::zeros(self.num_dofs());
+        for load in &self.description.loads {
+            match load {
+                LoadDescription::BodyForce { value } => match &self.description.physics {
+                    PhysicsDescription::Elasticity { .. } => {
+                        let source = UniformSource::<D>::from_slice(value);
+                        self.assemble_source_into(&mut loads, &source)?
+                    }
+                    PhysicsDescription::Poisson {} => {
+                        let source = UniformSource::<U1>::from_slice(value);
+                        self.assemble_source_into(&mut loads, &source)?
+                    }
+                },
+                LoadDescription::NodalForce { node_set, value } => {
+                    let nodes = self.node_set(node_set).unwrap_or(&[]);
+                    for &node in nodes {
+                        for k in 0..s {
+                            loads[s * node + k] += value[k];
+                        }
+                    }
+                }
+            }
+        }
+        Ok(loads)
+    }
+
+    fn assemble_source_into<S>(&self, output: &mut DVector<f64>, source: &UniformSource<S>) -> eyre::Result<()>
+    where
+        S: SmallDim,
+        DefaultAllocator: TriDimAllocator<f64, D, D, S>,
+    {
+        let qtable = self.mesh.canonical_mass_quadrature();
+        let assembler = ElementSourceAssemblerBuilder::new()
+            .with_finite_element_space(&self.mesh)
+            .with_source(source)
+            .with_quadrature_table(&qtable)
+            .build();
+        let contribution = VectorAssembler::default().assemble_vector(&assembler)?;
+        *output += contribution;
+        Ok(())
+    }
+
+    /// Assembles the linear system $\vec A \vec x = \vec b$ for the static problem with
+    /// boundary conditions applied.
+    ///
+    /// Non-homogeneous Dirichlet boundary conditions are handled by *lifting*: the solution is
+    /// decomposed as $\vec u = \vec x + \vec u_D$, where $\vec u_D$ contains the prescribed
+    /// values (see [`dirichlet_values`](Self::dirichlet_values)) and $\vec x$ satisfies
+    /// homogeneous boundary conditions. The system for $\vec x$ is obtained by moving
+    /// $\vec K \vec u_D$ to the right-hand side and applying homogeneous boundary conditions.
+    ///
+    /// Returns an error if the material model is nonlinear, since the static problem is then not
+    /// a linear system.
+    pub fn assemble_linear_system(&self) -> eyre::Result<(CsrMatrix<f64>, DVector<f64>)> {
+        if let Some(material) = self.material() {
+            if !material.model.is_linear() {
+                return Err(eyre!(
+                    "physics: material \"{}\" has nonlinear model {:?}, so the static problem is not a linear system",
+                    material.name,
+                    material.model
+                ));
+            }
+        }
+        let s = self.solution_dim();
+        let mut matrix = self.assemble_stiffness()?;
+        let mut rhs = self.assemble_load_vector()?;
+        let u_dirichlet = self.dirichlet_values();
+        rhs -= &matrix * &u_dirichlet;
+
+        let dirichlet_nodes = self.dirichlet_nodes();
+        apply_homogeneous_dirichlet_bc_csr(&mut matrix, &dirichlet_nodes, s);
+        apply_homogeneous_dirichlet_bc_rhs(&mut rhs, &dirichlet_nodes, s);
+        Ok((matrix, rhs))
+    }
+
+    /// Solves the static problem.
+    ///
+    /// For linear problems, the linear system returned by
+    /// [`assemble_linear_system`](Self::assemble_linear_system) is solved with the provided
+    /// `solve` function, and the prescribed Dirichlet values are added to the result.
+    ///
+    /// For nonlinear material models, the full load is applied in a single load step of
+    /// [`QuasiStaticModel::solve_load_step`] with default settings, starting from the reference
+    /// configuration. Each Newton iteration then uses `solve` for the linear system.
+    pub fn solve_static(
+        &self,
+        mut solve: impl FnMut(&CsrMatrix<f64>, &DVector<f64>) -> eyre::Result<DVector<f64>>,
+    ) -> eyre::Result<DVector<f64>> {
+        if let Some(material) = self.material() {
+            if !material.model.is_linear() {
+                let parameters = material.parameters.to_lame_parameters()?;
+                let load = self.assemble_load_vector()?;
+                let dirichlet_bcs = self.dirichlet_bcs();
+                return with_material!(material.model, |model| {
+                    let qtable = self
+                        .mesh
+                        .canonical_stiffness_quadrature()
+                        .with_uniform_data(parameters);
+                    let quasi_static = QuasiStaticModel::new(&self.mesh, model, &qtable, dirichlet_bcs, load)?;
+                    let mut u = DVector::zeros(self.num_dofs());
+                    quasi_static
+                        .solve_load_step(&mut u, &LoadStepSettings::default(), &mut solve)
+                        .wrap_err("failed to solve nonlinear static problem")?;
+                    Ok(u)
+                });
+            }
+        }
+
+        let (matrix, rhs) = self.assemble_linear_system()?;
+        let x = solve(&matrix, &rhs)?;
+        if x.len() != self.num_dofs() {
+            return Err(eyre!(
+                "solution has {} entries, but problem has {} degrees of freedom",
+                x.len(),
+                self.num_dofs()
+            ));
+        }
+        Ok(x + self.dirichlet_values())
+    }
+}
+
+impl<D, C> Problem<D, C>
+where
+    D: PhysicalDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    Mesh<f64, D, C>: CanonicalStiffnessQuadrature<Quadrature = UniformQuadratureTable<f64, D>>
+        + CanonicalMassQuadrature<Quadrature = UniformQuadratureTable<f64, D>>,
+    DefaultAllocator: ElementConnectivityAllocator<f64, C>
+        + TriDimAllocator<f64, D, D, D>
+        + TriDimAllocator<f64, U1, D, D>
+        + TriDimAllocator<f64, D, D, U1>,
+{
+    /// Constructs the integrator described by the time stepping section of the description.
+    ///
+    /// The integrator uses the consistent mass matrix and the Dirichlet boundary conditions of
+    /// the problem. Returns an error if the description has no time stepping section.
+    pub fn time_integrator(&self) -> eyre::Result<TimeIntegrator<f64>> {
+        let time_stepping = self.time_stepping()?;
+        let mass = self.assemble_mass()?;
+        let dirichlet_bcs = self.dirichlet_bcs();
+        let time_step = time_stepping.time_step;
+        match time_stepping.integrator {
+            IntegratorDescription::Newmark {
+                beta,
+                gamma,
+                tolerance,
+                max_iterations,
+            } => {
+                let integrator = NewmarkIntegrator::new(mass, dirichlet_bcs, time_step);
+                let (beta, gamma) = (
+                    beta.unwrap_or_else(|| integrator.beta()),
+                    gamma.unwrap_or_else(|| integrator.gamma()),
+                );
+                let tolerance = tolerance.unwrap_or_else(|| integrator.tolerance());
+                let max_iterations = max_iterations.unwrap_or_else(|| integrator.max_iterations());
+                let integrator = integrator
+                    .with_parameters(beta, gamma)
+                    .with_newton_settings(tolerance, max_iterations);
+                Ok(TimeIntegrator::Newmark(integrator))
+            }
+            IntegratorDescription::SymplecticEuler {} => Ok(TimeIntegrator::SymplecticEuler(
+                SymplecticEuler::new(&mass, dirichlet_bcs, time_step).wrap_err("time_stepping.integrator")?,
+            )),
+        }
+    }
+
+    /// Simulates the dynamic problem described by the time stepping section of the description.
+    ///
+    /// Starting from the given state, the integrator returned by
+    /// [`time_integrator`](Self::time_integrator) is initialized and advanced by the prescribed
+    /// number of steps with [`TimeIntegrator::run`]. The internal forces are those of the
+    /// (possibly nonlinear) material, and the loads of the description are applied as constant
+    /// external forces. The linear solver `solve` is only used by implicit integrators.
+    pub fn simulate(
+        &self,
+        state: &mut DynamicState<f64>,
+        mut solve: impl FnMut(&CsrMatrix<f64>, &DVector<f64>) -> eyre::Result<DVector<f64>>,
+        recorder: Option<&mut Recorder<f64, DynamicState<f64>>>,
+    ) -> eyre::Result<()> {
+        let num_steps = self.time_stepping()?.num_steps;
+        let integrator = self.time_integrator()?;
+        let material = self
+            .material()
+            .expect("Material must exist for validated elasticity problem");
+        let parameters = material.parameters.to_lame_parameters()?;
+        let load = self.assemble_load_vector()?;
+        with_material!(material.model, |model| {
+            let qtable = self
+                .mesh
+                .canonical_stiffness_quadrature()
+                .with_uniform_data(parameters);
+            let system = QuasiStaticModel::new(&self.mesh, model, &qtable, self.dirichlet_bcs(), load)?;
+            integrator.initialize(&system, state, &mut solve)?;
+            integrator.run(&system, state, num_steps, &mut solve, recorder)
+        })
+    }
+
+    fn time_stepping(&self) -> eyre::Result<&TimeSteppingDescription> {
+        self.description
+            .time_stepping
+            .as_ref()
+            .ok_or_else(|| eyre!("time_stepping: description has no time stepping section"))
+    }
+}
+
+fn assemble_material_stiffness<D, C, Material>(
+    mesh: &Mesh<f64, D, C>,
+    material: &Material,
+    parameters: LameParameters<f64>,
+) -> eyre::Result<CsrMatrix<f64>>
+where
+    D: PhysicalDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    Material: HyperelasticMaterial<f64, D, Parameters = LameParameters<f64>>,
+    Mesh<f64, D, C>: CanonicalStiffnessQuadrature<Quadrature = UniformQuadratureTable<f64, D>>,
+    DefaultAllocator: ElementConnectivityAllocator<f64, C> + TriDimAllocator<f64, D, D, D>,
+{
+    let u = DVector::zeros(D::dim() * mesh.vertices().len());
+    let qtable = mesh
+        .canonical_stiffness_quadrature()
+        .with_uniform_data(parameters);
+    let operator = MaterialEllipticOperator::new(material);
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&operator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    CsrAssembler::default().assemble(&assembler)
+}
+
+/// A spatially uniform source density.
+#[derive(Debug, Clone)]
+struct UniformSource<S>
+where
+    S: SmallDim,
+    DefaultAllocator: DimAllocator<f64, S>,
+{
+    value: OVector<f64, S>,
+}
+
+impl<S> UniformSource<S>
+where
+    S: SmallDim,
+    DefaultAllocator: DimAllocator<f64, S>,
+{
+    fn from_slice(value: &[f64]) -> Self {
+        Self {
+            value: OVector::<f64, S>::from_column_slice(value),
+        }
+    }
+}
+
+impl<D, S> Operator<f64, D> for UniformSource<S>
+where
+    S: SmallDim,
+    DefaultAllocator: DimAllocator<f64, S>,
+{
+    type SolutionDim = S;
+    type Parameters = ();
+}
+
+impl<D, S> SourceFunction<f64, D> for UniformSource<S>
+where
+    D: SmallDim,
+    S: SmallDim,
+    DefaultAllocator: DimAllocator<f64, S> + TriDimAllocator<f64, D, D, S>,
+{
+    fn evaluate(&self, _coords: &OPoint<f64, D>, _data: &Self::Parameters) -> OVector<f64, S> {
+        self.value.clone()
+    }
+}
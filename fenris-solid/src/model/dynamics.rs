@@ -0,0 +1,587 @@
+//! Time integration of elastodynamics problems.
+//!
+//! The semi-discrete equations of motion of an elastic body form the second-order system
+//! $$ \vec M \ddot{\vec u} + \vec f_{\text{int}}(\vec u) = \vec f_{\text{ext}}, $$
+//! where $\vec M$ is the mass matrix. The forces are provided by a [`SecondOrderSystem`] through
+//! the residual $\vec r(\vec u) = \vec f_{\text{int}}(\vec u) - \vec f_{\text{ext}}$ and its
+//! tangent, which is implemented by [`QuasiStaticModel`].
+//!
+//! [`NewmarkIntegrator`] is an implicit integrator that solves a (nonlinear) system in every
+//! step, whereas [`SymplecticEuler`] is explicit and only requires the lumped mass matrix.
+//! Both integrators operate on a [`DynamicState`], which can be serialized for checkpointing.
+//! Their `run` methods advance the state by a number of steps, optionally recording diagnostics
+//! with a [`Recorder`] after each step.
+//!
+//! Dirichlet boundary conditions are given as [`DirichletBcs`]. The prescribed displacements are
+//! assumed to be constant in time, so that the velocity and acceleration of constrained degrees
+//! of freedom are zero.
+use crate::model::{QuasiStaticModel, Recorder};
+use crate::HyperelasticMaterial;
+use eyre::{eyre, WrapErr};
+use fenris::allocators::TriDimAllocator;
+use fenris::assembly::local::QuadratureTable;
+use fenris::assembly::DirichletBcs;
+use fenris::nalgebra::{DVector, DefaultAllocator, Scalar};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::space::VolumetricFiniteElementSpace;
+use fenris::Real;
+use serde::{Deserialize, Serialize};
+
+/// A second-order system $\vec M \ddot{\vec u} + \vec r(\vec u) = \vec 0$, where the mass matrix
+/// is provided separately to the integrators.
+pub trait SecondOrderSystem<T: Real> {
+    /// The number of degrees of freedom of the system.
+    fn num_dofs(&self) -> usize;
+
+    /// Assembles the residual $\vec r(\vec u) = \vec f_{\text{int}}(\vec u) - \vec f_{\text{ext}}$.
+    fn assemble_residual(&self, u: &DVector<T>) -> eyre::Result<DVector<T>>;
+
+    /// Assembles the tangent $\pd{\vec r}{\vec u}$.
+    fn assemble_tangent(&self, u: &DVector<T>) -> eyre::Result<CsrMatrix<T>>;
+}
+
+impl<'a, T, Space, Material, QTable> SecondOrderSystem<T> for QuasiStaticModel<'a, T, Space, Material, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Material: HyperelasticMaterial<T, Space::ReferenceDim>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim, Data = Material::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Space::ReferenceDim>,
+{
+    fn num_dofs(&self) -> usize {
+        QuasiStaticModel::num_dofs(self)
+    }
+
+    fn assemble_residual(&self, u: &DVector<T>) -> eyre::Result<DVector<T>> {
+        QuasiStaticModel::assemble_residual(self, u)
+    }
+
+    fn assemble_tangent(&self, u: &DVector<T>) -> eyre::Result<CsrMatrix<T>> {
+        QuasiStaticModel::assemble_tangent(self, u)
+    }
+}
+
+/// The displacement, velocity and acceleration of a second-order system at a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DynamicState<T: Scalar> {
+    pub u: DVector<T>,
+    pub v: DVector<T>,
+    pub a: DVector<T>,
+}
+
+impl<T: Real> DynamicState<T> {
+    /// A state at rest in the reference configuration.
+    pub fn zeros(num_dofs: usize) -> Self {
+        Self {
+            u: DVector::zeros(num_dofs),
+            v: DVector::zeros(num_dofs),
+            a: DVector::zeros(num_dofs),
+        }
+    }
+
+    pub fn num_dofs(&self) -> usize {
+        self.u.len()
+    }
+
+    fn check_dimensions(&self, num_dofs: usize) -> eyre::Result<()> {
+        if self.u.len() != num_dofs || self.v.len() != num_dofs || self.a.len() != num_dofs {
+            return Err(eyre!(
+                "State vectors have lengths ({}, {}, {}), but the system has {} degrees of freedom",
+                self.u.len(),
+                self.v.len(),
+                self.a.len(),
+                num_dofs
+            ));
+        }
+        Ok(())
+    }
+
+    /// Imposes the prescribed displacements, and zeros the velocity and acceleration of
+    /// the constrained degrees of freedom.
+    fn apply_dirichlet_bcs(&mut self, dirichlet_bcs: &DirichletBcs<T>) {
+        for (&dof, &value) in dirichlet_bcs.dofs().iter().zip(dirichlet_bcs.values()) {
+            self.u[dof] = value;
+            self.v[dof] = T::zero();
+            self.a[dof] = T::zero();
+        }
+    }
+}
+
+/// The Newmark family of implicit integrators.
+///
+/// Given the state $(\vec u_n, \vec v_n, \vec a_n)$, the next state is determined by
+/// <div>$$
+/// \begin{aligned}
+///   \vec u_{n + 1} &= \vec u_n + \Delta t \, \vec v_n
+///     + \Delta t^2 \left( (\tfrac{1}{2} - \beta) \vec a_n + \beta \vec a_{n + 1} \right), \\
+///   \vec v_{n + 1} &= \vec v_n + \Delta t \left( (1 - \gamma) \vec a_n + \gamma \vec a_{n + 1} \right), \\
+///   \vec M \vec a_{n + 1} &+ \vec r(\vec u_{n + 1}) = \vec 0.
+/// \end{aligned}
+/// $$</div>
+/// The default parameters $\beta = 1/4$, $\gamma = 1/2$ give the average acceleration method,
+/// which is second-order accurate, unconditionally stable and conserves energy for
+/// linear systems.
+///
+/// Each step solves for $\vec u_{n + 1}$ with Newton's method, where every iteration solves
+/// a linear system with the effective matrix $\vec M + \beta \Delta t^2 \vec K(\vec u)$ using
+/// a user-provided solver.
+#[derive(Debug, Clone)]
+pub struct NewmarkIntegrator<T: Real> {
+    mass: CsrMatrix<T>,
+    dirichlet_bcs: DirichletBcs<T>,
+    homogeneous_bcs: DirichletBcs<T>,
+    time_step: T,
+    beta: T,
+    gamma: T,
+    tolerance: T,
+    absolute_tolerance: T,
+    max_iterations: usize,
+}
+
+impl<T: Real> NewmarkIntegrator<T> {
+    /// Creates an average acceleration integrator ($\beta = 1/4$, $\gamma = 1/2$) with the given
+    /// mass matrix, Dirichlet boundary conditions and time step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mass matrix is not square or the time step is not positive.
+    pub fn new(mass: CsrMatrix<T>, dirichlet_bcs: DirichletBcs<T>, time_step: T) -> Self {
+        assert_eq!(mass.nrows(), mass.ncols(), "Mass matrix must be square");
+        assert!(time_step > T::zero(), "Time step must be positive");
+        let homogeneous_bcs = DirichletBcs::homogeneous(dirichlet_bcs.dofs().to_vec());
+        Self {
+            mass,
+            dirichlet_bcs,
+            homogeneous_bcs,
+            time_step,
+            beta: T::from_f64(0.25).unwrap(),
+            gamma: T::from_f64(0.5).unwrap(),
+            tolerance: T::from_f64(1e-8).unwrap(),
+            absolute_tolerance: T::zero(),
+            max_iterations: 20,
+        }
+    }
+
+    /// Sets the Newmark parameters $\beta$ and $\gamma$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if $\beta \leq 0$, since the explicit case $\beta = 0$ is not supported.
+    pub fn with_parameters(mut self, beta: T, gamma: T) -> Self {
+        assert!(beta > T::zero(), "Beta must be positive");
+        self.beta = beta;
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets the relative tolerance and the maximum number of iterations for the Newton solve
+    /// in each step.
+    ///
+    /// The iterations are considered converged once the norm of the residual of the
+    /// (scaled) equation of motion is at most `tolerance` times its initial norm.
+    pub fn with_newton_settings(mut self, tolerance: T, max_iterations: usize) -> Self {
+        self.tolerance = tolerance;
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets an absolute tolerance for the Newton solve in each step, zero by default.
+    ///
+    /// The iterations are also considered converged once the norm of the residual is at most
+    /// `absolute_tolerance`. This is necessary when the motion has decayed to the point where
+    /// the initial residual is comparable to the round-off error in the residual itself.
+    pub fn with_absolute_tolerance(mut self, absolute_tolerance: T) -> Self {
+        self.absolute_tolerance = absolute_tolerance;
+        self
+    }
+
+    pub fn mass(&self) -> &CsrMatrix<T> {
+        &self.mass
+    }
+
+    pub fn time_step(&self) -> T {
+        self.time_step
+    }
+
+    pub fn beta(&self) -> T {
+        self.beta
+    }
+
+    pub fn gamma(&self) -> T {
+        self.gamma
+    }
+
+    /// The relative tolerance of the Newton iterations in each step.
+    pub fn tolerance(&self) -> T {
+        self.tolerance
+    }
+
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Computes the acceleration consistent with the displacement of the state by solving
+    /// $\vec M \vec a = - \vec r(\vec u)$.
+    ///
+    /// This is typically used to initialize the acceleration before the first step.
+    pub fn compute_consistent_acceleration(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        mut solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<()> {
+        self.check_dimensions(system, state)?;
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+        let mut matrix = self.mass.clone();
+        let mut rhs = -system.assemble_residual(&state.u)?;
+        self.homogeneous_bcs
+            .apply_to_system(&mut matrix, &mut rhs)?;
+        state.a = solve(&matrix, &rhs)?;
+        state.check_dimensions(self.mass.nrows())?;
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+        Ok(())
+    }
+
+    /// Advances the state by a single time step.
+    ///
+    /// The user-provided closure `solve` must return the solution of the given linear system.
+    /// Returns the number of Newton iterations performed, or an error if the iterations
+    /// do not converge.
+    pub fn step(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        mut solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<usize> {
+        self.check_dimensions(system, state)?;
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+
+        let one = T::one();
+        let half = T::from_f64(0.5).unwrap();
+        let dt = self.time_step;
+        let scale = self.beta * dt * dt;
+        let u_predicted = &state.u + &state.v * dt + &state.a * (dt * dt * (half - self.beta));
+        let v_predicted = &state.v + &state.a * (dt * (one - self.gamma));
+
+        // Solve the scaled equation of motion
+        //  M (u - u_predicted) + beta dt^2 r(u) = 0
+        // for u with Newton's method
+        let mut u = u_predicted.clone();
+        for (&dof, &value) in self
+            .dirichlet_bcs
+            .dofs()
+            .iter()
+            .zip(self.dirichlet_bcs.values())
+        {
+            u[dof] = value;
+        }
+        let mut iterations = 0;
+        let mut threshold = None;
+        loop {
+            let mut g = &self.mass * &(&u - &u_predicted) + system.assemble_residual(&u)? * scale;
+            for &dof in self.dirichlet_bcs.dofs() {
+                g[dof] = T::zero();
+            }
+            let norm = g.norm();
+            if !norm.is_finite() {
+                return Err(eyre!("Residual is not finite after {} Newton iterations", iterations));
+            }
+            let threshold = *threshold.get_or_insert_with(|| self.absolute_tolerance.max(self.tolerance * norm));
+            if norm <= threshold {
+                break;
+            } else if iterations == self.max_iterations {
+                return Err(eyre!(
+                    "Newton iterations failed to converge within {} iterations",
+                    self.max_iterations
+                ));
+            }
+
+            let mut matrix = &self.mass + &(system.assemble_tangent(&u)? * scale);
+            g.neg_mut();
+            self.homogeneous_bcs.apply_to_system(&mut matrix, &mut g)?;
+            let mut du = solve(&matrix, &g)?;
+            if du.len() != u.len() {
+                return Err(eyre!(
+                    "Linear solve returned a vector of length {}, expected {}",
+                    du.len(),
+                    u.len()
+                ));
+            }
+            for &dof in self.dirichlet_bcs.dofs() {
+                du[dof] = T::zero();
+            }
+            u += du;
+            iterations += 1;
+        }
+
+        let a = (&u - &u_predicted) / scale;
+        state.v = v_predicted + &a * (self.gamma * dt);
+        state.a = a;
+        state.u = u;
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+        Ok(iterations)
+    }
+
+    /// Advances the state by the given number of time steps.
+    ///
+    /// If a recorder is given, it records the state after each step. See [`run_steps`] for
+    /// the times at which the state is recorded.
+    pub fn run(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        num_steps: usize,
+        mut solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+        recorder: Option<&mut Recorder<T, DynamicState<T>>>,
+    ) -> eyre::Result<()> {
+        run_steps(self.time_step, state, num_steps, recorder, |state| {
+            self.step(system, state, &mut solve).map(|_| ())
+        })
+    }
+
+    fn check_dimensions(&self, system: &impl SecondOrderSystem<T>, state: &DynamicState<T>) -> eyre::Result<()> {
+        let num_dofs = self.mass.nrows();
+        if system.num_dofs() != num_dofs {
+            return Err(eyre!(
+                "System has {} degrees of freedom, but the mass matrix has {} rows",
+                system.num_dofs(),
+                num_dofs
+            ));
+        }
+        state.check_dimensions(num_dofs)
+    }
+}
+
+/// The explicit symplectic Euler integrator.
+///
+/// The state is advanced by
+/// <div>$$
+/// \begin{aligned}
+///   \vec a_n &= - \vec M_L^{-1} \vec r(\vec u_n), \\
+///   \vec v_{n + 1} &= \vec v_n + \Delta t \, \vec a_n, \\
+///   \vec u_{n + 1} &= \vec u_n + \Delta t \, \vec v_{n + 1},
+/// \end{aligned}
+/// $$</div>
+/// where $\vec M_L$ is the row-sum lumped mass matrix. The method is first-order accurate and
+/// symplectic, so that the energy error remains bounded over long times instead of drifting.
+/// It is only conditionally stable: the time step must be smaller than $2 / \omega_{\max}$,
+/// where $\omega_{\max}$ is the highest angular frequency of the discrete system.
+#[derive(Debug, Clone)]
+pub struct SymplecticEuler<T: Real> {
+    lumped_mass: DVector<T>,
+    dirichlet_bcs: DirichletBcs<T>,
+    time_step: T,
+}
+
+impl<T: Real> SymplecticEuler<T> {
+    /// Creates an integrator with the given mass matrix, Dirichlet boundary conditions and
+    /// time step.
+    ///
+    /// The mass matrix is lumped by summing its rows, which leaves an already lumped matrix
+    /// unchanged. Returns an error if a lumped mass of an unconstrained degree of freedom is
+    /// not positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mass matrix is not square or the time step is not positive.
+    pub fn new(mass: &CsrMatrix<T>, dirichlet_bcs: DirichletBcs<T>, time_step: T) -> eyre::Result<Self> {
+        assert_eq!(mass.nrows(), mass.ncols(), "Mass matrix must be square");
+        let lumped_mass = DVector::from_iterator(
+            mass.nrows(),
+            mass.row_iter()
+                .map(|row| row.values().iter().fold(T::zero(), |sum, &m| sum + m)),
+        );
+        Self::from_lumped_mass(lumped_mass, dirichlet_bcs, time_step)
+    }
+
+    /// Creates an integrator with the given diagonal of the lumped mass matrix, Dirichlet boundary
+    /// conditions and time step.
+    ///
+    /// This avoids forming a sparse matrix when the lumped mass is assembled directly as a vector,
+    /// for example with [`LumpedElementAssembler`](fenris::assembly::local::LumpedElementAssembler).
+    /// Returns an error if a lumped mass of an unconstrained degree of freedom is not positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time step is not positive.
+    pub fn from_lumped_mass(
+        lumped_mass: DVector<T>,
+        dirichlet_bcs: DirichletBcs<T>,
+        time_step: T,
+    ) -> eyre::Result<Self> {
+        assert!(time_step > T::zero(), "Time step must be positive");
+        let num_dofs = lumped_mass.len();
+        let mut is_constrained = vec![false; num_dofs];
+        for &dof in dirichlet_bcs.dofs() {
+            if dof >= num_dofs {
+                return Err(eyre!("Constrained degree of freedom {} is out of bounds", dof));
+            }
+            is_constrained[dof] = true;
+        }
+        if let Some(dof) = (0..num_dofs).find(|&dof| !is_constrained[dof] && !(lumped_mass[dof] > T::zero())) {
+            return Err(eyre!(
+                "Lumped mass of degree of freedom {} is {}, but must be positive",
+                dof,
+                lumped_mass[dof]
+            ));
+        }
+        Ok(Self {
+            lumped_mass,
+            dirichlet_bcs,
+            time_step,
+        })
+    }
+
+    /// The diagonal of the lumped mass matrix.
+    pub fn lumped_mass(&self) -> &DVector<T> {
+        &self.lumped_mass
+    }
+
+    pub fn time_step(&self) -> T {
+        self.time_step
+    }
+
+    /// Advances the state by a single time step.
+    ///
+    /// After the step, the acceleration of the state is the acceleration $\vec a_n$ used to
+    /// update the velocity.
+    pub fn step(&self, system: &impl SecondOrderSystem<T>, state: &mut DynamicState<T>) -> eyre::Result<()> {
+        let num_dofs = self.lumped_mass.len();
+        if system.num_dofs() != num_dofs {
+            return Err(eyre!(
+                "System has {} degrees of freedom, but the mass matrix has {} rows",
+                system.num_dofs(),
+                num_dofs
+            ));
+        }
+        state.check_dimensions(num_dofs)?;
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+
+        let residual = system.assemble_residual(&state.u)?;
+        state.a = -residual.component_div(&self.lumped_mass);
+        // Constrained degrees of freedom may have zero mass
+        state.apply_dirichlet_bcs(&self.dirichlet_bcs);
+        state.v.axpy(self.time_step, &state.a, T::one());
+        state.u.axpy(self.time_step, &state.v, T::one());
+        Ok(())
+    }
+
+    /// Advances the state by the given number of time steps.
+    ///
+    /// If a recorder is given, it records the state after each step. See [`run_steps`] for
+    /// the times at which the state is recorded.
+    pub fn run(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        num_steps: usize,
+        recorder: Option<&mut Recorder<T, DynamicState<T>>>,
+    ) -> eyre::Result<()> {
+        run_steps(self.time_step, state, num_steps, recorder, |state| {
+            self.step(system, state)
+        })
+    }
+}
+
+/// One of the integrators in this module, chosen at runtime.
+///
+/// This is for example constructed from a problem description by
+/// [`Problem::time_integrator`](crate::model::Problem::time_integrator).
+#[derive(Debug, Clone)]
+pub enum TimeIntegrator<T: Real> {
+    Newmark(NewmarkIntegrator<T>),
+    SymplecticEuler(SymplecticEuler<T>),
+}
+
+impl<T: Real> TimeIntegrator<T> {
+    pub fn time_step(&self) -> T {
+        match self {
+            Self::Newmark(integrator) => integrator.time_step(),
+            Self::SymplecticEuler(integrator) => integrator.time_step(),
+        }
+    }
+
+    /// Prepares the initial state for the first step.
+    ///
+    /// For the Newmark integrator, this computes the acceleration consistent with the
+    /// displacement (see [`NewmarkIntegrator::compute_consistent_acceleration`]). The symplectic
+    /// Euler integrator computes the acceleration in each step, so the state is left unchanged.
+    pub fn initialize(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<()> {
+        match self {
+            Self::Newmark(integrator) => integrator.compute_consistent_acceleration(system, state, solve),
+            Self::SymplecticEuler(_) => Ok(()),
+        }
+    }
+
+    /// Advances the state by a single time step.
+    ///
+    /// The linear solver `solve` is only used by the Newmark integrator.
+    pub fn step(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<()> {
+        match self {
+            Self::Newmark(integrator) => integrator.step(system, state, solve).map(|_| ()),
+            Self::SymplecticEuler(integrator) => integrator.step(system, state),
+        }
+    }
+
+    /// Advances the state by the given number of time steps.
+    ///
+    /// The linear solver `solve` is only used by the Newmark integrator. If a recorder is given,
+    /// it records the state after each step. See [`run_steps`] for the times at which the state
+    /// is recorded.
+    pub fn run(
+        &self,
+        system: &impl SecondOrderSystem<T>,
+        state: &mut DynamicState<T>,
+        num_steps: usize,
+        solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+        recorder: Option<&mut Recorder<T, DynamicState<T>>>,
+    ) -> eyre::Result<()> {
+        match self {
+            Self::Newmark(integrator) => integrator.run(system, state, num_steps, solve, recorder),
+            Self::SymplecticEuler(integrator) => integrator.run(system, state, num_steps, recorder),
+        }
+    }
+}
+
+/// Advances the state by the given number of time steps with the given step function,
+/// recording the state after each step.
+///
+/// If the recorder does not contain any values, the initial state is recorded at time zero,
+/// and step $k$ is recorded at time $k \Delta t$. Otherwise the times continue from the last
+/// recorded time, so that a recorder can be reused across consecutive runs, for example
+/// when resuming from a checkpoint.
+///
+/// The integrators call this function in their `run` methods. It is useful on its own for
+/// drivers that perform additional work in each step.
+pub fn run_steps<T: Real>(
+    time_step: T,
+    state: &mut DynamicState<T>,
+    num_steps: usize,
+    mut recorder: Option<&mut Recorder<T, DynamicState<T>>>,
+    mut step: impl FnMut(&mut DynamicState<T>) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let mut t0 = T::zero();
+    if let Some(recorder) = recorder.as_deref_mut() {
+        match recorder.times().last() {
+            Some(&t) => t0 = t,
+            None => recorder.record(t0, state)?,
+        }
+    }
+    for k in 1..=num_steps {
+        step(state).wrap_err_with(|| format!("time step {} failed", k))?;
+        if let Some(recorder) = recorder.as_deref_mut() {
+            recorder.record(t0 + T::from_usize(k).unwrap() * time_step, state)?;
+        }
+    }
+    Ok(())
+}
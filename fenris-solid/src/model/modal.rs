@@ -0,0 +1,485 @@
+use crate::{HyperelasticMaterial, MaterialEllipticOperator};
+use eyre::eyre;
+use fenris::allocators::TriDimAllocator;
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, QuadratureTable};
+use fenris::nalgebra::{DMatrix, DVector, DVectorView, DefaultAllocator, DimName};
+use fenris::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use fenris::space::VolumetricFiniteElementSpace;
+use fenris::util::small_eig::symmetric_eigen;
+use fenris::util::spmv_into;
+use fenris::Real;
+use std::cmp::min;
+
+/// A generalized eigenvalue problem $\vec K \vec u = \lambda \vec M \vec u$ for modal analysis,
+/// with Dirichlet-constrained degrees of freedom eliminated by reduction.
+///
+/// The reduced stiffness and mass matrices only contain the rows and columns associated with
+/// the *free* degrees of freedom. Vectors in the reduced space can be expanded to the full
+/// space with [`expand`](Self::expand).
+#[derive(Debug, Clone)]
+pub struct ModalProblem<T: Real> {
+    stiffness: CsrMatrix<T>,
+    mass: CsrMatrix<T>,
+    free_dofs: Vec<usize>,
+    num_dofs: usize,
+}
+
+/// Settings for the shift-and-invert Lanczos eigensolver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanczosSettings<T> {
+    /// The maximum number of Lanczos iterations in each run.
+    pub max_iterations: usize,
+    /// The maximum number of runs.
+    ///
+    /// Converged modes are locked after each run, and the next run is restarted in the
+    /// complement of the locked modes. This is necessary to recover repeated eigenvalues.
+    pub max_runs: usize,
+    /// A Ritz pair $(\theta, \vec x)$ is considered converged when its residual in the
+    /// shift-and-invert problem is less than `tolerance` $\cdot |\theta|$.
+    pub tolerance: T,
+}
+
+impl<T: Real> Default for LanczosSettings<T> {
+    fn default() -> Self {
+        Self {
+            max_iterations: 300,
+            max_runs: 20,
+            tolerance: T::from_f64(1e-10).unwrap(),
+        }
+    }
+}
+
+/// Eigenmodes computed by [`ModalProblem::compute_lowest_modes`].
+///
+/// Eigenvalues are sorted in ascending order, and the mode shapes are $\vec M$-orthonormal vectors
+/// in the *reduced* space.
+#[derive(Debug, Clone)]
+pub struct Modes<T> {
+    pub eigenvalues: Vec<T>,
+    pub mode_shapes: Vec<DVector<T>>,
+}
+
+impl<T: Real> Modes<T> {
+    /// The angular frequencies $\omega = \sqrt{\lambda}$ of the modes.
+    ///
+    /// Slightly negative eigenvalues, which may occur for rigid body modes due to round-off
+    /// errors, are treated as zero.
+    pub fn angular_frequencies(&self) -> Vec<T> {
+        self.eigenvalues
+            .iter()
+            .map(|&lambda| lambda.max(T::zero()).sqrt())
+            .collect()
+    }
+
+    /// The natural frequencies $f = \omega / 2 \pi$ of the modes.
+    pub fn frequencies(&self) -> Vec<T> {
+        self.angular_frequencies()
+            .into_iter()
+            .map(|omega| omega / T::two_pi())
+            .collect()
+    }
+}
+
+impl<T: Real> ModalProblem<T> {
+    /// Assembles the modal problem for the given elastic body.
+    ///
+    /// The stiffness matrix is the tangent stiffness of the material in the undeformed
+    /// configuration. All solution components of the nodes in `dirichlet_nodes` are constrained.
+    pub fn assemble<Space, Material, StiffnessTable, MassTable>(
+        space: &Space,
+        material: &Material,
+        stiffness_qtable: &StiffnessTable,
+        mass_qtable: &MassTable,
+        dirichlet_nodes: &[usize],
+    ) -> eyre::Result<Self>
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        Material: HyperelasticMaterial<T, Space::ReferenceDim>,
+        StiffnessTable: QuadratureTable<T, Space::ReferenceDim, Data = Material::Parameters>,
+        MassTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+        DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Space::ReferenceDim>,
+    {
+        let d = Space::ReferenceDim::dim();
+        let u = DVector::zeros(d * space.num_nodes());
+        let operator = MaterialEllipticOperator::new(material);
+        let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(space)
+            .with_operator(&operator)
+            .with_quadrature_table(stiffness_qtable)
+            .with_u(&u)
+            .build();
+        let mass_assembler = ElementMassAssembler::with_solution_dim(d)
+            .with_space(space)
+            .with_quadrature_table(mass_qtable);
+
+        let csr_assembler = CsrAssembler::default();
+        let stiffness = csr_assembler.assemble(&stiffness_assembler)?;
+        let mass = csr_assembler.assemble(&mass_assembler)?;
+        Ok(Self::from_matrices(&stiffness, &mass, d, dirichlet_nodes))
+    }
+
+    /// Constructs the modal problem from the given (full) stiffness and mass matrices.
+    ///
+    /// All `solution_dim` components of the nodes in `dirichlet_nodes` are constrained and
+    /// eliminated from the matrices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrices are not square and of the same size, or if a node index
+    /// is out of bounds.
+    pub fn from_matrices(
+        stiffness: &CsrMatrix<T>,
+        mass: &CsrMatrix<T>,
+        solution_dim: usize,
+        dirichlet_nodes: &[usize],
+    ) -> Self {
+        let num_dofs = stiffness.nrows();
+        assert_eq!(stiffness.ncols(), num_dofs, "Stiffness matrix must be square");
+        assert_eq!(
+            mass.nrows(),
+            num_dofs,
+            "Mass and stiffness matrix dimensions must match"
+        );
+        assert_eq!(
+            mass.ncols(),
+            num_dofs,
+            "Mass and stiffness matrix dimensions must match"
+        );
+
+        let mut is_constrained = vec![false; num_dofs];
+        for &node in dirichlet_nodes {
+            for i in 0..solution_dim {
+                is_constrained[solution_dim * node + i] = true;
+            }
+        }
+
+        let free_dofs: Vec<usize> = (0..num_dofs).filter(|&dof| !is_constrained[dof]).collect();
+        // Map from full to reduced dof indices
+        let mut reduced_index = vec![None; num_dofs];
+        for (i, &dof) in free_dofs.iter().enumerate() {
+            reduced_index[dof] = Some(i);
+        }
+
+        let reduce = |matrix: &CsrMatrix<T>| {
+            let mut coo = CooMatrix::new(free_dofs.len(), free_dofs.len());
+            for (i, j, &v) in matrix.triplet_iter() {
+                if let (Some(i), Some(j)) = (reduced_index[i], reduced_index[j]) {
+                    coo.push(i, j, v);
+                }
+            }
+            CsrMatrix::from(&coo)
+        };
+
+        Self {
+            stiffness: reduce(stiffness),
+            mass: reduce(mass),
+            free_dofs,
+            num_dofs,
+        }
+    }
+
+    /// The reduced stiffness matrix.
+    pub fn stiffness(&self) -> &CsrMatrix<T> {
+        &self.stiffness
+    }
+
+    /// The reduced mass matrix.
+    pub fn mass(&self) -> &CsrMatrix<T> {
+        &self.mass
+    }
+
+    /// The indices of the free (unconstrained) degrees of freedom in the full space.
+    pub fn free_dofs(&self) -> &[usize] {
+        &self.free_dofs
+    }
+
+    /// The number of degrees of freedom in the full space.
+    pub fn num_dofs(&self) -> usize {
+        self.num_dofs
+    }
+
+    /// Returns the reduced stiffness and mass matrices.
+    pub fn into_reduced_matrices(self) -> (CsrMatrix<T>, CsrMatrix<T>) {
+        (self.stiffness, self.mass)
+    }
+
+    /// Computes the shifted reduced stiffness matrix $\vec K - \sigma \vec M$.
+    ///
+    /// This is the matrix that must be factorized or otherwise solved with when computing modes
+    /// with [`compute_lowest_modes`](Self::compute_lowest_modes).
+    pub fn shifted_stiffness(&self, shift: T) -> CsrMatrix<T> {
+        &self.stiffness - &(&self.mass * shift)
+    }
+
+    /// Expands a vector in the reduced space to the full space.
+    ///
+    /// Constrained degrees of freedom are set to zero. The expanded vector is compatible with
+    /// the nodes of the mesh, so that mode shapes can for example be exported with
+    /// [`FiniteElementMeshDataSetBuilder::with_point_vector_attributes`](fenris::io::vtk::FiniteElementMeshDataSetBuilder::with_point_vector_attributes).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector does not match the number of free degrees of freedom.
+    pub fn expand<'a>(&self, reduced: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let reduced = reduced.into();
+        assert_eq!(
+            reduced.len(),
+            self.free_dofs.len(),
+            "Reduced vector has wrong dimension"
+        );
+        let mut full = DVector::zeros(self.num_dofs);
+        for (&dof, &value) in self.free_dofs.iter().zip(reduced.iter()) {
+            full[dof] = value;
+        }
+        full
+    }
+
+    /// Restricts a vector in the full space to the reduced space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector does not match the number of degrees of freedom.
+    pub fn restrict<'a>(&self, full: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let full = full.into();
+        assert_eq!(full.len(), self.num_dofs, "Full vector has wrong dimension");
+        DVector::from_iterator(self.free_dofs.len(), self.free_dofs.iter().map(|&dof| full[dof]))
+    }
+
+    /// Computes the `num_modes` eigenmodes with eigenvalues closest to the shift $\sigma$.
+    ///
+    /// Uses the shift-and-invert Lanczos method with full reorthogonalization, applied to the
+    /// operator $(\vec K - \sigma \vec M)^{-1} \vec M$. The user-provided closure `solve` must
+    /// return the solution $\vec x$ of
+    /// $$ (\vec K - \sigma \vec M) \vec x = \vec b $$
+    /// for the given right-hand side $\vec b$, where the matrix is given by
+    /// [`shifted_stiffness`](Self::shifted_stiffness). Typically the matrix is factorized once,
+    /// and the factorization is reused for each solve.
+    ///
+    /// To obtain the lowest modes, the shift should be chosen less than or equal to the smallest
+    /// eigenvalue. For a sufficiently constrained problem, $\sigma = 0$ is a natural choice.
+    /// For unconstrained problems, the stiffness matrix is singular and a small negative shift is
+    /// required.
+    ///
+    /// Returns an error if the solve fails, or if the modes do not converge within
+    /// the prescribed number of iterations.
+    pub fn compute_lowest_modes(
+        &self,
+        num_modes: usize,
+        shift: T,
+        mut solve: impl FnMut(&DVector<T>) -> eyre::Result<DVector<T>>,
+        settings: &LanczosSettings<T>,
+    ) -> eyre::Result<Modes<T>> {
+        let n = self.free_dofs.len();
+        if num_modes > n {
+            return Err(eyre!(
+                "Cannot compute {} modes for problem with {} free degrees of freedom",
+                num_modes,
+                n
+            ));
+        } else if num_modes == 0 {
+            return Ok(Modes {
+                eigenvalues: Vec::new(),
+                mode_shapes: Vec::new(),
+            });
+        }
+
+        let abs_cmp = |a: &RitzPair<T>, b: &RitzPair<T>| {
+            b.theta
+                .abs()
+                .partial_cmp(&a.theta.abs())
+                .expect("Ritz values must not be NaN")
+        };
+
+        let mut locked: Vec<RitzPair<T>> = Vec::new();
+        let mut converged = false;
+        for run in 0..settings.max_runs {
+            if locked.len() == n {
+                converged = true;
+                break;
+            }
+
+            let target = num_modes.saturating_sub(locked.len()).max(1);
+            let candidates = lanczos_run(&self.mass, &mut solve, &locked, run, target, settings)?;
+
+            let mut num_new = 0;
+            for candidate in candidates {
+                let threshold = if locked.len() < num_modes {
+                    None
+                } else {
+                    locked.last().map(|pair| pair.theta.abs())
+                };
+                if threshold.map_or(true, |threshold| candidate.theta.abs() > threshold) {
+                    locked.push(candidate);
+                    locked.sort_by(abs_cmp);
+                    locked.truncate(num_modes);
+                    num_new += 1;
+                }
+            }
+
+            // Once we have the requested number of modes, we only terminate after a run that does
+            // not find any new modes. This ensures that modes that were not represented in the
+            // Krylov space of previous runs (such as repeated eigenvalues) are not missed
+            if num_new == 0 && locked.len() == num_modes {
+                converged = true;
+                break;
+            }
+        }
+
+        if !converged {
+            return Err(eyre!(
+                "Lanczos failed to converge to {} modes within {} runs",
+                num_modes,
+                settings.max_runs
+            ));
+        }
+
+        let mut modes: Vec<_> = locked
+            .into_iter()
+            .map(|pair| (shift + T::one() / pair.theta, pair.x))
+            .collect();
+        modes.sort_by(|(lambda1, _), (lambda2, _)| lambda1.partial_cmp(lambda2).unwrap());
+        let (eigenvalues, mode_shapes) = modes.into_iter().unzip();
+        Ok(Modes {
+            eigenvalues,
+            mode_shapes,
+        })
+    }
+}
+
+/// A Ritz pair $(\theta, \vec x)$ of the shift-and-invert operator, along with $\vec M \vec x$.
+#[derive(Debug, Clone)]
+struct RitzPair<T: Real> {
+    theta: T,
+    x: DVector<T>,
+    mx: DVector<T>,
+}
+
+/// Makes `w` $\vec M$-orthogonal to the given vectors, where each pair of vectors
+/// is $(\vec v, \vec M \vec v)$.
+fn m_orthogonalize<'a, T: Real>(
+    w: &mut DVector<T>,
+    basis: impl Clone + Iterator<Item = (&'a DVector<T>, &'a DVector<T>)>,
+) {
+    // Classical Gram-Schmidt twice is sufficient for numerical orthogonality
+    for _ in 0..2 {
+        for (v, mv) in basis.clone() {
+            let c = w.dot(mv);
+            w.axpy(-c, v, T::one());
+        }
+    }
+}
+
+/// Returns the vectors and mass-weighted vectors of the given Ritz pairs, suitable for [`m_orthogonalize`].
+fn ritz_basis<T: Real>(pairs: &[RitzPair<T>]) -> impl Clone + Iterator<Item = (&DVector<T>, &DVector<T>)> {
+    pairs.iter().map(|pair| (&pair.x, &pair.mx))
+}
+
+/// Performs a single run of Lanczos iterations in the $\vec M$-orthogonal complement of
+/// the locked Ritz pairs, and returns the converged Ritz pairs with the largest $|\theta|$.
+fn lanczos_run<T: Real>(
+    mass: &CsrMatrix<T>,
+    solve: &mut impl FnMut(&DVector<T>) -> eyre::Result<DVector<T>>,
+    locked: &[RitzPair<T>],
+    run: usize,
+    target: usize,
+    settings: &LanczosSettings<T>,
+) -> eyre::Result<Vec<RitzPair<T>>> {
+    let n = mass.nrows();
+    let max_dim = min(settings.max_iterations, n - locked.len());
+
+    // Deterministic starting vector, which differs between runs
+    let frequency = 1.0 + 0.37 * run as f64;
+    let mut v_next = DVector::from_fn(n, |i, _| T::from_f64(((i + 1) as f64 * frequency).sin()).unwrap());
+    m_orthogonalize(&mut v_next, ritz_basis(locked));
+    let mut mv_next = DVector::zeros(n);
+    spmv_into(mass, &v_next, &mut mv_next);
+    let norm = v_next.dot(&mv_next).max(T::zero()).sqrt();
+    if norm <= T::zero() {
+        return Ok(Vec::new());
+    }
+    v_next /= norm;
+    mv_next /= norm;
+
+    let mut v: Vec<DVector<T>> = Vec::new();
+    let mut mv: Vec<DVector<T>> = Vec::new();
+    let mut alphas: Vec<T> = Vec::new();
+    let mut betas: Vec<T> = Vec::new();
+
+    for j in 0..max_dim {
+        v.push(v_next);
+        mv.push(mv_next);
+
+        let mut w = solve(&mv[j])?;
+        let alpha = w.dot(&mv[j]);
+        alphas.push(alpha);
+        m_orthogonalize(&mut w, ritz_basis(locked).chain(v.iter().zip(&mv)));
+        let mut mw = DVector::zeros(n);
+        spmv_into(mass, &w, &mut mw);
+        let beta = w.dot(&mw).max(T::zero()).sqrt();
+
+        // Ritz pairs of the tridiagonal Lanczos matrix, ordered by decreasing |theta|
+        let m = j + 1;
+        let tridiagonal = DMatrix::from_fn(m, m, |r, c| {
+            if r == c {
+                alphas[r]
+            } else if r == c + 1 {
+                betas[c]
+            } else if c == r + 1 {
+                betas[r]
+            } else {
+                T::zero()
+            }
+        });
+        let eigen = symmetric_eigen(&tridiagonal);
+        let mut order: Vec<usize> = (0..m).collect();
+        order.sort_by(|&a, &b| {
+            eigen.eigenvalues[b]
+                .abs()
+                .partial_cmp(&eigen.eigenvalues[a].abs())
+                .unwrap()
+        });
+
+        // Only accept Ritz pairs that are converged and not preceded by unconverged pairs,
+        // so that the accepted pairs are the extremal ones
+        let num_converged = order
+            .iter()
+            .take_while(|&&idx| {
+                let theta = eigen.eigenvalues[idx];
+                let residual = (beta * eigen.eigenvectors[(j, idx)]).abs();
+                residual <= settings.tolerance * theta.abs()
+            })
+            .count();
+
+        let max_theta = eigen.eigenvalues[order[0]].abs();
+        let breakdown = beta <= T::default_epsilon() * max_theta;
+        if num_converged >= target || breakdown || m == max_dim {
+            let pairs = order
+                .iter()
+                .take(num_converged)
+                .map(|&idx| {
+                    let y = eigen.eigenvectors.column(idx);
+                    let mut x = DVector::zeros(n);
+                    let mut mx = DVector::zeros(n);
+                    for (&y_l, (v_l, mv_l)) in y.iter().zip(v.iter().zip(&mv)) {
+                        x.axpy(y_l, v_l, T::one());
+                        mx.axpy(y_l, mv_l, T::one());
+                    }
+                    let norm = x.dot(&mx).sqrt();
+                    RitzPair {
+                        theta: eigen.eigenvalues[idx],
+                        x: x / norm,
+                        mx: mx / norm,
+                    }
+                })
+                .collect();
+            return Ok(pairs);
+        }
+
+        betas.push(beta);
+        v_next = w / beta;
+        mv_next = mw / beta;
+    }
+
+    Ok(Vec::new())
+}
@@ -0,0 +1,210 @@
+use eyre::eyre;
+use fenris::allocators::{BiDimAllocator, TriDimAllocator};
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    Density, ElementMassAssembler, ElementSourceAssemblerBuilder, LumpedElementAssembler, LumpingStrategy,
+    QuadratureTable, SourceFunction,
+};
+use fenris::assembly::operators::Operator;
+use fenris::nalgebra::{DVector, DefaultAllocator, OPoint, OVector};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::space::VolumetricFiniteElementSpace;
+use fenris::{Real, SmallDim};
+use fenris_sparse::cg::{ConjugateGradient, RelativeResidualCriterion};
+use std::marker::PhantomData;
+
+/// The density-weighted field $\rho f$, viewed as a source function.
+struct WeightedFieldSource<F, S> {
+    field: F,
+    marker: PhantomData<S>,
+}
+
+impl<T, D, F, S> Operator<T, D> for WeightedFieldSource<F, S>
+where
+    T: Real,
+    S: SmallDim,
+{
+    type SolutionDim = S;
+    type Parameters = Density<T>;
+}
+
+impl<T, D, F, S> SourceFunction<T, D> for WeightedFieldSource<F, S>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    F: Fn(&OPoint<T, D>) -> OVector<T, S>,
+    DefaultAllocator: BiDimAllocator<T, D, S>,
+{
+    fn evaluate(&self, coords: &OPoint<T, D>, Density(density): &Self::Parameters) -> OVector<T, S> {
+        (self.field)(coords) * *density
+    }
+}
+
+/// Assembles the right-hand side $b_{I i} = \int_\Omega \rho f_i \phi_I$ of the projection.
+fn assemble_projection_rhs<T, S, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    f: impl Fn(&OPoint<T, Space::GeometryDim>) -> OVector<T, S>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    S: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    let source = WeightedFieldSource {
+        field: f,
+        marker: PhantomData,
+    };
+    let source_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(space)
+        .with_source(&source)
+        .with_quadrature_table(qtable)
+        .build();
+    VectorAssembler::default().assemble_vector(&source_assembler)
+}
+
+/// Assembles the lumped mass matrix as a vector, and checks that all lumped masses are positive.
+fn assemble_lumped_mass<T, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    solution_dim: usize,
+    strategy: LumpingStrategy,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let mass_assembler = ElementMassAssembler::with_solution_dim(solution_dim)
+        .with_space(space)
+        .with_quadrature_table(qtable);
+    let lumped_assembler = LumpedElementAssembler::new(mass_assembler, strategy);
+    let lumped_mass = VectorAssembler::default().assemble_vector(&lumped_assembler)?;
+    // Lumped masses that vanish in exact arithmetic may be slightly positive due to round-off
+    let threshold = T::default_epsilon().sqrt() * lumped_mass.amax();
+    if let Some(index) = lumped_mass.iter().position(|&m| m <= threshold) {
+        return Err(eyre!(
+            "Lumped mass {} at DOF {} is not positive. Consider a different lumping strategy",
+            lumped_mass[index],
+            index
+        ));
+    }
+    Ok(lumped_mass)
+}
+
+/// Computes the consistent $L^2$ projection of the field $f$ onto the given finite element space.
+///
+/// The projection $u_h = \sum_I \vec u_I \phi_I$ is the solution of
+/// $$
+/// \int_\Omega \rho \, u_h \phi_J \d{\vec x} = \int_\Omega \rho f \phi_J \d{\vec x}
+/// \qquad \text{for all } J,
+/// $$
+/// where the density $\rho$ is given by the data of the quadrature table. With unit density,
+/// this is the standard $L^2$ projection. The field $f: \Omega \rightarrow \mathbb{R}^s$ may be
+/// scalar- or vector-valued, and the result is a nodal vector with solution dimension $s$.
+///
+/// Both sides are integrated with the same quadrature, so that the projection minimizes the
+/// $L^2$ error as measured by that quadrature. Since the basis functions form a partition of
+/// unity, the projection conserves $\int_\Omega \rho f$. This is for example useful for
+/// initial velocities, where it conserves linear momentum.
+///
+/// The linear system with the mass matrix is solved by conjugate gradients, preconditioned
+/// with the inverse of the lumped mass matrix obtained by [`LumpingStrategy::Hrz`].
+/// Use [`project_initial_condition_with_solver`] to provide a different linear solver.
+pub fn project_initial_condition<T, S, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    f: impl Fn(&OPoint<T, Space::GeometryDim>) -> OVector<T, S>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    S: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    let lumped_mass = assemble_lumped_mass(space, qtable, S::dim(), LumpingStrategy::Hrz)?;
+    project_initial_condition_with_solver(space, qtable, f, |mass, rhs| {
+        let mut preconditioner = CsrMatrix::identity(rhs.len());
+        for (p_ii, &m_ii) in preconditioner.values_mut().iter_mut().zip(&lumped_mass) {
+            *p_ii = m_ii.recip();
+        }
+
+        let mut u = DVector::zeros(rhs.len());
+        let tolerance = T::from_f64(1e3).unwrap() * T::default_epsilon();
+        ConjugateGradient::new()
+            .with_operator(mass)
+            .with_preconditioner(&preconditioner)
+            .with_max_iter(rhs.len().max(1000))
+            .with_stopping_criterion(RelativeResidualCriterion::new(tolerance))
+            .solve_with_guess(rhs, &mut u)
+            .map_err(|cg_error| eyre!("{cg_error}"))?;
+        Ok(u)
+    })
+}
+
+/// Computes the consistent $L^2$ projection of the field $f$ with a user-provided linear solver.
+///
+/// The closure `solve(M, b)` must return the solution $\vec u$ of the linear system
+/// $\vec M \vec u = \vec b$, where $\vec M$ is the (symmetric positive definite) mass matrix.
+/// See [`project_initial_condition`] for details on the projection.
+pub fn project_initial_condition_with_solver<T, S, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    f: impl Fn(&OPoint<T, Space::GeometryDim>) -> OVector<T, S>,
+    solve: impl FnOnce(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    S: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    let mass_assembler = ElementMassAssembler::with_solution_dim(S::dim())
+        .with_space(space)
+        .with_quadrature_table(qtable);
+    let mass = CsrAssembler::default().assemble(&mass_assembler)?;
+    let rhs = assemble_projection_rhs(space, qtable, f)?;
+    let u = solve(&mass, &rhs)?;
+    if u.len() != rhs.len() {
+        return Err(eyre!(
+            "Linear solve returned a vector of length {}, expected {}",
+            u.len(),
+            rhs.len()
+        ));
+    }
+    Ok(u)
+}
+
+/// Computes the lumped $L^2$ projection of the field $f$ onto the given finite element space.
+///
+/// This is a cheap alternative to [`project_initial_condition`], in which the mass matrix is
+/// replaced by the lumped mass matrix obtained with the given lumping strategy, so that
+/// no linear system needs to be solved. With [`LumpingStrategy::RowSum`], the lumped projection
+/// conserves $\int_\Omega \rho f$, but it is in general less accurate than the consistent
+/// projection.
+///
+/// Returns an error if any lumped mass is not positive, which may happen with row-sum lumping
+/// for some higher-order elements.
+pub fn project_initial_condition_lumped<T, S, Space, QTable>(
+    space: &Space,
+    qtable: &QTable,
+    f: impl Fn(&OPoint<T, Space::GeometryDim>) -> OVector<T, S>,
+    strategy: LumpingStrategy,
+) -> eyre::Result<DVector<T>>
+where
+    T: Real,
+    S: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::GeometryDim, Data = Density<T>>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    let lumped_mass = assemble_lumped_mass(space, qtable, S::dim(), strategy)?;
+    let rhs = assemble_projection_rhs(space, qtable, f)?;
+    Ok(rhs.component_div(&lumped_mass))
+}
@@ -0,0 +1,419 @@
+use crate::{HyperelasticMaterial, MaterialEllipticOperator};
+use eyre::eyre;
+use fenris::allocators::TriDimAllocator;
+use fenris::assembly::global::{assemble_scalar, build_csr_pattern, CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{ElementEllipticAssembler, ElementEllipticAssemblerBuilder, QuadratureTable};
+use fenris::assembly::DirichletBcs;
+use fenris::nalgebra::{DVector, DefaultAllocator, DimName};
+use fenris::nalgebra_sparse::pattern::SparsityPattern;
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::space::VolumetricFiniteElementSpace;
+use fenris::Real;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Debug, Display};
+
+/// A quasi-static hyperelasticity problem with Dirichlet boundary conditions and a fixed
+/// external load.
+///
+/// The equilibrium displacement $\vec u$ is a stationary point of the total potential energy
+/// $$ E(\vec u) = \int_\Omega \psi(\nabla \vec u) \, \mathrm{d} \vec X - \vec f^T \vec u, $$
+/// where $\psi$ is the energy density of the material and $\vec f$ is the (assembled) external
+/// load vector. The residual is the gradient $\vec r(\vec u) = \vec f_{\text{int}}(\vec u) - \vec f$
+/// of the energy, and the tangent is the Hessian of the energy.
+///
+/// Degrees of freedom are interleaved, i.e. component $i$ of node $I$ has index $d I + i$,
+/// consistent with the assemblers and [`DirichletBcs`]. The sparsity pattern of the tangent
+/// is built once and reused for every assembly.
+pub struct QuasiStaticModel<'a, T: Real, Space, Material, QTable: ?Sized> {
+    space: &'a Space,
+    material: &'a Material,
+    qtable: &'a QTable,
+    dirichlet_bcs: DirichletBcs<T>,
+    homogeneous_bcs: DirichletBcs<T>,
+    external_load: DVector<T>,
+    pattern: SparsityPattern,
+}
+
+/// Settings for the Newton iterations in [`QuasiStaticModel::solve_load_step`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadStepSettings<T> {
+    /// The iterations are considered converged when the norm of the residual is at most
+    /// `tolerance` times the norm of the residual at the start of the load step.
+    pub tolerance: T,
+    /// The iterations are also considered converged when the norm of the residual is at most
+    /// `absolute_tolerance`.
+    pub absolute_tolerance: T,
+    /// The maximum number of Newton iterations.
+    pub max_iterations: usize,
+    /// Whether to perform a backtracking line search on the total potential energy.
+    ///
+    /// Without a line search, a full Newton step is taken in every iteration.
+    pub line_search: bool,
+}
+
+impl<T: Real> Default for LoadStepSettings<T> {
+    fn default() -> Self {
+        Self {
+            tolerance: T::from_f64(1e-10).unwrap(),
+            absolute_tolerance: T::zero(),
+            max_iterations: 50,
+            line_search: true,
+        }
+    }
+}
+
+/// The convergence history of a load step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvergenceHistory<T> {
+    /// The residual norm at the start of the load step, followed by the residual norm
+    /// after each Newton iteration.
+    pub residual_norms: Vec<T>,
+    /// The step length taken in each Newton iteration.
+    pub step_lengths: Vec<T>,
+}
+
+impl<T: Real> ConvergenceHistory<T> {
+    /// The number of Newton iterations performed.
+    pub fn iterations(&self) -> usize {
+        self.step_lengths.len()
+    }
+
+    /// The residual norm after the last iteration.
+    pub fn final_residual_norm(&self) -> T {
+        *self
+            .residual_norms
+            .last()
+            .expect("History always contains the initial residual")
+    }
+}
+
+/// Errors that may occur in [`QuasiStaticModel::solve_load_step`].
+///
+/// The variants that indicate divergence of the Newton iterations contain the convergence
+/// history up to the point of failure.
+#[derive(Debug)]
+pub enum LoadStepError<T> {
+    /// The residual did not converge within the maximum number of iterations.
+    MaxIterationsReached(ConvergenceHistory<T>),
+    /// The line search failed to find a step that sufficiently decreases the energy.
+    LineSearchFailed(ConvergenceHistory<T>),
+    /// The residual is no longer finite, typically because elements were inverted.
+    NonFiniteResidual(ConvergenceHistory<T>),
+    /// Assembly or the linear solve failed.
+    Other(eyre::Report),
+}
+
+impl<T: Real> Display for LoadStepError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadStepError::MaxIterationsReached(history) => write!(
+                f,
+                "Newton iterations failed to converge within {} iterations (residual norm {}).",
+                history.iterations(),
+                history.final_residual_norm()
+            ),
+            LoadStepError::LineSearchFailed(history) => write!(
+                f,
+                "Line search failed to decrease the energy in Newton iteration {}.",
+                history.iterations() + 1
+            ),
+            LoadStepError::NonFiniteResidual(history) => write!(
+                f,
+                "Residual is not finite after {} Newton iterations.",
+                history.iterations()
+            ),
+            LoadStepError::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<T: Real> Error for LoadStepError<T> {}
+
+impl<T> From<eyre::Report> for LoadStepError<T> {
+    fn from(err: eyre::Report) -> Self {
+        Self::Other(err)
+    }
+}
+
+impl<'a, T, Space, Material, QTable> QuasiStaticModel<'a, T, Space, Material, QTable>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Material: HyperelasticMaterial<T, Space::ReferenceDim>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim, Data = Material::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Space::ReferenceDim>,
+{
+    /// Sets up the model and builds the sparsity pattern of the tangent.
+    ///
+    /// Returns an error if the length of the external load vector or a constrained degree of
+    /// freedom is incompatible with the number of degrees of freedom of the space.
+    pub fn new(
+        space: &'a Space,
+        material: &'a Material,
+        qtable: &'a QTable,
+        dirichlet_bcs: DirichletBcs<T>,
+        external_load: DVector<T>,
+    ) -> eyre::Result<Self> {
+        let num_dofs = Space::ReferenceDim::dim() * space.num_nodes();
+        if external_load.len() != num_dofs {
+            return Err(eyre!(
+                "External load has length {}, but the space has {} degrees of freedom",
+                external_load.len(),
+                num_dofs
+            ));
+        }
+        if let Some(&dof) = dirichlet_bcs.dofs().last() {
+            if dof >= num_dofs {
+                return Err(eyre!(
+                    "Constrained degree of freedom {} is out of bounds for a space with {} degrees of freedom",
+                    dof,
+                    num_dofs
+                ));
+            }
+        }
+
+        let u = DVector::zeros(num_dofs);
+        let operator = MaterialEllipticOperator::new(material);
+        let pattern = build_csr_pattern(&Self::build_assembler(space, &operator, qtable, &u));
+        let homogeneous_bcs = DirichletBcs::homogeneous(dirichlet_bcs.dofs().to_vec());
+        Ok(Self {
+            space,
+            material,
+            qtable,
+            dirichlet_bcs,
+            homogeneous_bcs,
+            external_load,
+            pattern,
+        })
+    }
+
+    fn build_assembler<'b>(
+        space: &'b Space,
+        operator: &'b MaterialEllipticOperator<'b, Material>,
+        qtable: &'b QTable,
+        u: &'b DVector<T>,
+    ) -> ElementEllipticAssembler<'b, T, Space, MaterialEllipticOperator<'b, Material>, QTable> {
+        ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(space)
+            .with_operator(operator)
+            .with_quadrature_table(qtable)
+            .with_u(u)
+            .build()
+    }
+
+    /// The number of degrees of freedom, i.e. the number of nodes times the dimension.
+    pub fn num_dofs(&self) -> usize {
+        Space::ReferenceDim::dim() * self.space.num_nodes()
+    }
+
+    /// The Dirichlet boundary conditions imposed in each load step.
+    pub fn dirichlet_bcs(&self) -> &DirichletBcs<T> {
+        &self.dirichlet_bcs
+    }
+
+    /// The assembled external load vector $\vec f$.
+    pub fn external_load(&self) -> &DVector<T> {
+        &self.external_load
+    }
+
+    /// Replaces the external load, for example to apply the load incrementally.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the load vector does not match the number of degrees of freedom.
+    pub fn set_external_load(&mut self, external_load: DVector<T>) {
+        assert_eq!(
+            external_load.len(),
+            self.num_dofs(),
+            "External load must have one entry per degree of freedom"
+        );
+        self.external_load = external_load;
+    }
+
+    /// The sparsity pattern of the tangent.
+    pub fn tangent_pattern(&self) -> &SparsityPattern {
+        &self.pattern
+    }
+
+    /// Assembles the total potential energy $E(\vec u)$.
+    pub fn assemble_energy(&self, u: &DVector<T>) -> eyre::Result<T> {
+        let operator = MaterialEllipticOperator::new(self.material);
+        let assembler = Self::build_assembler(self.space, &operator, self.qtable, u);
+        Ok(assemble_scalar(&assembler)? - self.external_load.dot(u))
+    }
+
+    /// Assembles the residual $\vec r(\vec u) = \vec f_{\text{int}}(\vec u) - \vec f$.
+    ///
+    /// Entries associated with constrained degrees of freedom are set to zero, so that the norm
+    /// of the residual measures the violation of equilibrium at the free degrees of freedom.
+    pub fn assemble_residual(&self, u: &DVector<T>) -> eyre::Result<DVector<T>> {
+        let operator = MaterialEllipticOperator::new(self.material);
+        let assembler = Self::build_assembler(self.space, &operator, self.qtable, u);
+        let mut residual = VectorAssembler::default().assemble_vector(&assembler)?;
+        residual -= &self.external_load;
+        for &dof in self.dirichlet_bcs.dofs() {
+            residual[dof] = T::zero();
+        }
+        Ok(residual)
+    }
+
+    /// Assembles the tangent $\pd{\vec r}{\vec u}$ without boundary conditions applied.
+    ///
+    /// The result has the sparsity pattern given by [`tangent_pattern`](Self::tangent_pattern).
+    pub fn assemble_tangent(&self, u: &DVector<T>) -> eyre::Result<CsrMatrix<T>> {
+        let values = vec![T::zero(); self.pattern.nnz()];
+        let mut tangent = CsrMatrix::try_from_pattern_and_values(self.pattern.clone(), values)
+            .expect("CSR data must be valid by definition");
+        self.assemble_tangent_into(u, &mut tangent)?;
+        Ok(tangent)
+    }
+
+    /// Assembles the tangent into a matrix with a compatible sparsity pattern, overwriting its values.
+    pub fn assemble_tangent_into(&self, u: &DVector<T>, tangent: &mut CsrMatrix<T>) -> eyre::Result<()> {
+        let operator = MaterialEllipticOperator::new(self.material);
+        let assembler = Self::build_assembler(self.space, &operator, self.qtable, u);
+        CsrAssembler::default().assemble_into_preallocated_csr(tangent, &assembler)
+    }
+
+    /// Solves for the equilibrium displacement with Newton's method, starting from the given
+    /// displacement.
+    ///
+    /// The prescribed Dirichlet values are first imposed on `u`, after which each Newton
+    /// iteration solves the linear system $\vec K \Delta \vec u = - \vec r$ with homogeneous
+    /// boundary conditions. The user-provided closure `solve` must return the solution of the
+    /// given linear system. The system matrix is symmetric whenever the tangent is, but it is
+    /// only guaranteed to be positive definite close to a stable equilibrium.
+    ///
+    /// With a line search, $\alpha$ is halved starting from one until the step
+    /// $\vec u + \alpha \Delta \vec u$ satisfies the Armijo sufficient decrease condition for
+    /// the energy. Since energy differences are dominated by round-off errors close to convergence,
+    /// a step that sufficiently decreases the norm of the residual is also accepted.
+    ///
+    /// On success, `u` contains the converged displacement and the convergence history is
+    /// returned. On failure, `u` contains the last iterate.
+    pub fn solve_load_step(
+        &self,
+        u: &mut DVector<T>,
+        settings: &LoadStepSettings<T>,
+        mut solve: impl FnMut(&CsrMatrix<T>, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> Result<ConvergenceHistory<T>, LoadStepError<T>> {
+        let num_dofs = self.num_dofs();
+        if u.len() != num_dofs {
+            return Err(eyre!(
+                "Displacement has length {}, but the model has {} degrees of freedom",
+                u.len(),
+                num_dofs
+            )
+            .into());
+        }
+        for (&dof, &value) in self
+            .dirichlet_bcs
+            .dofs()
+            .iter()
+            .zip(self.dirichlet_bcs.values())
+        {
+            u[dof] = value;
+        }
+
+        let mut residual = self.assemble_residual(u)?;
+        let mut history = ConvergenceHistory {
+            residual_norms: vec![residual.norm()],
+            step_lengths: Vec::new(),
+        };
+        let initial_norm = history.final_residual_norm();
+        if !initial_norm.is_finite() {
+            return Err(LoadStepError::NonFiniteResidual(history));
+        }
+        let threshold = settings
+            .absolute_tolerance
+            .max(settings.tolerance * initial_norm);
+
+        let mut tangent = self.assemble_tangent(u)?;
+        while history.final_residual_norm() > threshold {
+            if history.iterations() == settings.max_iterations {
+                return Err(LoadStepError::MaxIterationsReached(history));
+            }
+
+            self.assemble_tangent_into(u, &mut tangent)?;
+            let mut rhs = -&residual;
+            self.homogeneous_bcs
+                .apply_to_system(&mut tangent, &mut rhs)?;
+            let mut du = solve(&tangent, &rhs)?;
+            if du.len() != num_dofs {
+                return Err(eyre!(
+                    "Linear solve returned a vector of length {}, expected {}",
+                    du.len(),
+                    num_dofs
+                )
+                .into());
+            }
+            for &dof in self.dirichlet_bcs.dofs() {
+                du[dof] = T::zero();
+            }
+
+            let step_length = if settings.line_search {
+                match self.backtrack(u, &du, &residual)? {
+                    Some(alpha) => alpha,
+                    None => return Err(LoadStepError::LineSearchFailed(history)),
+                }
+            } else {
+                T::one()
+            };
+            u.axpy(step_length, &du, T::one());
+
+            residual = self.assemble_residual(u)?;
+            history.residual_norms.push(residual.norm());
+            history.step_lengths.push(step_length);
+            if !history.final_residual_norm().is_finite() {
+                return Err(LoadStepError::NonFiniteResidual(history));
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// Finds an acceptable step length by backtracking, or returns `None` if no acceptable step
+    /// length is found.
+    fn backtrack(&self, u: &DVector<T>, du: &DVector<T>, residual: &DVector<T>) -> eyre::Result<Option<T>> {
+        let c = T::from_f64(1e-4).unwrap();
+        let min_step_length = T::from_f64(1e-8).unwrap();
+        let half = T::from_f64(0.5).unwrap();
+
+        let energy = self.assemble_energy(u)?;
+        let slope = residual.dot(du);
+        let residual_norm = residual.norm();
+
+        let mut alpha = T::one();
+        while alpha >= min_step_length {
+            let candidate = u + du * alpha;
+            let candidate_energy = self.assemble_energy(&candidate)?;
+            if candidate_energy.is_finite() {
+                if candidate_energy <= energy + c * alpha * slope {
+                    return Ok(Some(alpha));
+                }
+                // Close to convergence, changes in the energy are dominated by round-off errors,
+                // so we also accept steps that sufficiently decrease the residual
+                let candidate_residual_norm = self.assemble_residual(&candidate)?.norm();
+                if candidate_residual_norm <= (T::one() - c * alpha) * residual_norm {
+                    return Ok(Some(alpha));
+                }
+            }
+            alpha *= half;
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, T, Space, Material, QTable> Debug for QuasiStaticModel<'a, T, Space, Material, QTable>
+where
+    T: Real,
+    QTable: ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuasiStaticModel")
+            .field("dirichlet_bcs", &self.dirichlet_bcs)
+            .field("external_load", &self.external_load)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,229 @@
+use eyre::eyre;
+use fenris::allocators::{DimAllocator, TriDimAllocator};
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, QuadratureTable};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::nalgebra::{DVector, DefaultAllocator, OPoint, U1};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::space::VolumetricFiniteElementSpace;
+use fenris::util::spmv_into;
+use fenris::{Real, SmallDim};
+
+/// An implicit-explicit (IMEX) time integrator for reaction-diffusion systems with `S` species.
+///
+/// The system is given by
+/// $$
+/// \pd{u_i}{t} = D_i \Delta u_i + r_i(\vec u, \vec x), \qquad i = 1, \dots, S,
+/// $$
+/// where each species $u_i$ is a nodal field in the same scalar finite element space,
+/// $D_i$ are the diffusion coefficients and $\vec r$ is a pointwise reaction term. Homogeneous
+/// Neumann boundary conditions are implied.
+///
+/// Each step treats diffusion implicitly and reactions explicitly. With time step $\Delta t$,
+/// the reaction is first evaluated at every node and the species are then updated by solving
+/// $$
+/// (\vec M + \Delta t D_i \vec K) \vec u_i^{n + 1} = \vec M (\vec u_i^n + \Delta t \vec r_i^n),
+/// $$
+/// where $\vec M$ is the mass matrix and $\vec K$ the stiffness matrix of the Laplace operator.
+/// The system matrices share the sparsity pattern of $\vec M$ and $\vec K$ and are formed only
+/// once, so that they may be factored once up front. The scheme is first-order accurate in time.
+///
+/// # Time step restriction
+///
+/// The implicit diffusion is unconditionally stable. The explicit reaction requires that
+/// $$
+/// \Delta t \leq 1 / L,
+/// $$
+/// where $L$ bounds the Lipschitz constant of $\vec r$ with respect to $\vec u$ in the maximum
+/// norm over the states of interest. For example, for a linear decay $r = -\lambda u$ this
+/// means $\Delta t \lambda \leq 1$, which guarantees that the reaction step does not overshoot.
+/// The restriction does not depend on the mesh resolution.
+pub struct ImexReactionDiffusion<T, D, Reaction, const S: usize>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    mass: CsrMatrix<T>,
+    system_matrices: Vec<CsrMatrix<T>>,
+    nodes: Vec<OPoint<T, D>>,
+    diffusion: [T; S],
+    reaction: Reaction,
+    dt: T,
+}
+
+impl<T, D, Reaction, const S: usize> ImexReactionDiffusion<T, D, Reaction, S>
+where
+    T: Real,
+    D: SmallDim,
+    Reaction: Fn(&[T; S], &OPoint<T, D>) -> [T; S],
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Assembles the mass and stiffness matrices for the given scalar space and sets up the
+    /// integrator.
+    ///
+    /// The reaction is evaluated at the given node positions, which must correspond to the
+    /// nodes of the space.
+    pub fn assemble<Space, StiffnessTable, MassTable>(
+        space: &Space,
+        nodes: Vec<OPoint<T, D>>,
+        diffusion: [T; S],
+        reaction: Reaction,
+        dt: T,
+        stiffness_qtable: &StiffnessTable,
+        mass_qtable: &MassTable,
+    ) -> eyre::Result<Self>
+    where
+        Space: VolumetricFiniteElementSpace<T, ReferenceDim = D>,
+        StiffnessTable: QuadratureTable<T, D, Data = ()>,
+        MassTable: QuadratureTable<T, D, Data = Density<T>>,
+        DefaultAllocator: TriDimAllocator<T, U1, D, D>,
+    {
+        if nodes.len() != space.num_nodes() {
+            return Err(eyre!(
+                "Number of node positions ({}) does not match the number of nodes in the space ({})",
+                nodes.len(),
+                space.num_nodes()
+            ));
+        }
+        let u = DVector::zeros(space.num_nodes());
+        let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(space)
+            .with_operator(&LaplaceOperator)
+            .with_quadrature_table(stiffness_qtable)
+            .with_u(&u)
+            .build();
+        let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(space)
+            .with_quadrature_table(mass_qtable);
+
+        let csr_assembler = CsrAssembler::default();
+        let stiffness = csr_assembler.assemble(&stiffness_assembler)?;
+        let mass = csr_assembler.assemble(&mass_assembler)?;
+        Self::from_matrices(mass, &stiffness, nodes, diffusion, reaction, dt)
+    }
+
+    /// Sets up the integrator from the given scalar mass and stiffness matrices.
+    ///
+    /// Returns an error if the matrices do not have the same sparsity pattern, or if their
+    /// dimensions are not consistent with the number of nodes.
+    pub fn from_matrices(
+        mass: CsrMatrix<T>,
+        stiffness: &CsrMatrix<T>,
+        nodes: Vec<OPoint<T, D>>,
+        diffusion: [T; S],
+        reaction: Reaction,
+        dt: T,
+    ) -> eyre::Result<Self> {
+        if mass.pattern() != stiffness.pattern() {
+            return Err(eyre!("Mass and stiffness matrices must have the same sparsity pattern"));
+        }
+        if mass.nrows() != nodes.len() || mass.ncols() != nodes.len() {
+            return Err(eyre!(
+                "Matrix dimensions {}x{} are not consistent with {} nodes",
+                mass.nrows(),
+                mass.ncols(),
+                nodes.len()
+            ));
+        }
+
+        let system_matrices = diffusion
+            .iter()
+            .map(|&d_i| {
+                let values = mass
+                    .values()
+                    .iter()
+                    .zip(stiffness.values())
+                    .map(|(&m, &k)| m + dt * d_i * k)
+                    .collect();
+                CsrMatrix::try_from_pattern_and_values(mass.pattern().clone(), values)
+                    .expect("Pattern and values are consistent by construction")
+            })
+            .collect();
+
+        Ok(Self {
+            mass,
+            system_matrices,
+            nodes,
+            diffusion,
+            reaction,
+            dt,
+        })
+    }
+
+    /// The (scalar) mass matrix.
+    pub fn mass(&self) -> &CsrMatrix<T> {
+        &self.mass
+    }
+
+    /// The system matrix $\vec M + \Delta t D_i \vec K$ associated with the given species.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the species index is out of bounds.
+    pub fn system_matrix(&self, species: usize) -> &CsrMatrix<T> {
+        &self.system_matrices[species]
+    }
+
+    /// The diffusion coefficients of the species.
+    pub fn diffusion(&self) -> &[T; S] {
+        &self.diffusion
+    }
+
+    /// The time step.
+    pub fn time_step(&self) -> T {
+        self.dt
+    }
+
+    /// The number of nodes in each species field.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Advances the species fields by a single time step.
+    ///
+    /// The closure `solve(i, b)` must return the solution $\vec x$ of the linear system
+    /// $(\vec M + \Delta t D_i \vec K) \vec x = \vec b$ for species $i$, which is
+    /// available as [`system_matrix(i)`](Self::system_matrix).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of a field does not match the number of nodes.
+    pub fn step(
+        &self,
+        fields: &mut [DVector<T>; S],
+        mut solve: impl FnMut(usize, &DVector<T>) -> eyre::Result<DVector<T>>,
+    ) -> eyre::Result<()> {
+        let n = self.num_nodes();
+        for field in fields.iter() {
+            assert_eq!(field.len(), n, "Field length must match number of nodes");
+        }
+
+        // Explicit reaction step, evaluated pointwise at the nodes
+        for (j, x_j) in self.nodes.iter().enumerate() {
+            let u_j: [T; S] = std::array::from_fn(|i| fields[i][j]);
+            let r_j = (self.reaction)(&u_j, x_j);
+            for (field, r_ij) in fields.iter_mut().zip(r_j) {
+                field[j] += self.dt * r_ij;
+            }
+        }
+
+        // Implicit diffusion step
+        let mut rhs = DVector::zeros(n);
+        for (i, field) in fields.iter_mut().enumerate() {
+            spmv_into(&self.mass, &*field, &mut rhs);
+            let solution = solve(i, &rhs)?;
+            if solution.len() != n {
+                return Err(eyre!(
+                    "Linear solve for species {} returned a vector of length {}, expected {}",
+                    i,
+                    solution.len(),
+                    n
+                ));
+            }
+            *field = solution;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use eyre::WrapErr;
+use fenris::nalgebra::{DVector, DVectorView};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::Real;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Records scalar diagnostics over the course of a time-dependent simulation.
+///
+/// A recorder holds a number of named *probes*, which are functions that compute a scalar
+/// value from the current state of the simulation. Each call to [`record`](Self::record)
+/// evaluates all probes and appends the values to the associated series. The series can be
+/// accessed programmatically, for example for assertions in tests, or exported to CSV.
+///
+/// The type of the state is chosen by the driver that invokes the recorder. The integrators in
+/// [`dynamics`](crate::model::dynamics) record a
+/// [`DynamicState`](crate::model::dynamics::DynamicState) after each step in their `run`
+/// methods. The functions in
+/// this module, such as [`kinetic_energy`] and [`node_set_reaction`], as well as
+/// [`assemble_scalar`](fenris::assembly::global::assemble_scalar) for energies, provide the
+/// building blocks for common probes.
+pub struct Recorder<'a, T, State: ?Sized> {
+    times: Vec<T>,
+    probes: Vec<Probe<'a, T, State>>,
+}
+
+type ProbeFunction<'a, T, State> = Box<dyn FnMut(&State) -> eyre::Result<T> + 'a>;
+
+struct Probe<'a, T, State: ?Sized> {
+    name: String,
+    function: ProbeFunction<'a, T, State>,
+    values: Vec<T>,
+}
+
+impl<'a, T: Debug, State: ?Sized> Debug for Recorder<'a, T, State> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        map.entry(&"t", &self.times);
+        for probe in &self.probes {
+            map.entry(&probe.name, &probe.values);
+        }
+        map.finish()
+    }
+}
+
+impl<'a, T, State: ?Sized> Default for Recorder<'a, T, State> {
+    fn default() -> Self {
+        Self {
+            times: Vec::new(),
+            probes: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T: Real, State: ?Sized> Recorder<'a, T, State> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a probe with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a probe with the same name already exists, if the name is `t` or contains
+    /// characters that are not permitted in a CSV header (commas, quotes and line breaks),
+    /// or if values have already been recorded.
+    pub fn add_probe(
+        &mut self,
+        name: impl Into<String>,
+        probe: impl FnMut(&State) -> eyre::Result<T> + 'a,
+    ) -> &mut Self {
+        let name = name.into();
+        assert!(
+            self.times.is_empty(),
+            "Cannot add probe \"{}\" after values have been recorded",
+            name
+        );
+        assert!(
+            !name.contains([',', '"', '\n', '\r']),
+            "Probe name \"{}\" contains characters that are not permitted in CSV headers",
+            name
+        );
+        assert!(
+            name != "t" && self.series(&name).is_none(),
+            "Probe name \"{}\" is already in use",
+            name
+        );
+        self.probes.push(Probe {
+            name,
+            function: Box::new(probe),
+            values: Vec::new(),
+        });
+        self
+    }
+
+    /// Registers a probe with the given name.
+    ///
+    /// See [`add_probe`](Self::add_probe).
+    pub fn with_probe(mut self, name: impl Into<String>, probe: impl FnMut(&State) -> eyre::Result<T> + 'a) -> Self {
+        self.add_probe(name, probe);
+        self
+    }
+
+    /// Evaluates all probes for the given state and appends the values to their series.
+    ///
+    /// If a probe fails, no values are recorded for this time.
+    pub fn record(&mut self, t: T, state: &State) -> eyre::Result<()> {
+        let values = self
+            .probes
+            .iter_mut()
+            .map(|probe| (probe.function)(state).wrap_err_with(|| format!("probe \"{}\" failed", probe.name)))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        self.times.push(t);
+        for (probe, value) in self.probes.iter_mut().zip(values) {
+            probe.values.push(value);
+        }
+        Ok(())
+    }
+
+    /// The times at which values have been recorded.
+    pub fn times(&self) -> &[T] {
+        &self.times
+    }
+
+    /// The names of all probes, in the order in which they were registered.
+    pub fn probe_names(&self) -> impl Iterator<Item = &str> {
+        self.probes.iter().map(|probe| probe.name.as_str())
+    }
+
+    /// The values recorded by the named probe, one for each time in [`times`](Self::times).
+    pub fn series(&self, name: &str) -> Option<&[T]> {
+        self.probes
+            .iter()
+            .find(|probe| probe.name == name)
+            .map(|probe| probe.values.as_slice())
+    }
+
+    /// Writes all series in CSV format.
+    ///
+    /// The first column is the time `t`, followed by one column for each probe.
+    pub fn write_csv(&self, mut writer: impl Write) -> eyre::Result<()> {
+        write!(writer, "t")?;
+        for name in self.probe_names() {
+            write!(writer, ",{}", name)?;
+        }
+        writeln!(writer)?;
+        for (i, t) in self.times.iter().enumerate() {
+            write!(writer, "{}", t)?;
+            for probe in &self.probes {
+                write!(writer, ",{}", probe.values[i])?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Exports all series to the CSV file at the given path.
+    ///
+    /// See [`write_csv`](Self::write_csv).
+    pub fn export_csv(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).wrap_err_with(|| format!("failed to create file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        self.write_csv(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Computes the kinetic energy $\frac{1}{2} \vec v^T \vec M \vec v$.
+pub fn kinetic_energy<'a, T: Real>(mass: &CsrMatrix<T>, velocity: impl Into<DVectorView<'a, T>>) -> T {
+    let v = velocity.into();
+    let mut energy = T::zero();
+    for (i, row) in mass.row_iter().enumerate() {
+        for (&j, &m_ij) in row.col_indices().iter().zip(row.values()) {
+            energy += v[i] * m_ij * v[j];
+        }
+    }
+    energy * T::from_f64(0.5).unwrap()
+}
+
+/// Computes the largest Euclidean norm of the nodal values of `u`.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not divisible by `solution_dim`.
+pub fn max_nodal_norm<'a, T: Real>(u: impl Into<DVectorView<'a, T>>, solution_dim: usize) -> T {
+    let u = u.into();
+    assert_eq!(
+        u.len() % solution_dim,
+        0,
+        "Length of u must be divisible by solution dim"
+    );
+    u.as_slice()
+        .chunks_exact(solution_dim)
+        .map(|u_i| u_i.iter().fold(T::zero(), |sum, &x| sum + x * x).sqrt())
+        .fold(T::zero(), |max, norm| max.max(norm))
+}
+
+/// Computes the largest absolute value of the given component of the nodal values of `u`.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not divisible by `solution_dim`, or if the component is
+/// out of bounds.
+pub fn max_component_magnitude<'a, T: Real>(
+    u: impl Into<DVectorView<'a, T>>,
+    solution_dim: usize,
+    component: usize,
+) -> T {
+    let u = u.into();
+    assert_eq!(
+        u.len() % solution_dim,
+        0,
+        "Length of u must be divisible by solution dim"
+    );
+    assert!(component < solution_dim, "Component out of bounds");
+    u.iter()
+        .skip(component)
+        .step_by(solution_dim)
+        .fold(T::zero(), |max, u_i| max.max(u_i.abs()))
+}
+
+/// Computes the total reaction force on a set of nodes.
+///
+/// Given the nodal forces $\vec f$ that the body exerts on its supports, typically the
+/// internal forces assembled by an
+/// [`ElementEllipticAssembler`](fenris::assembly::local::ElementEllipticAssembler) (minus
+/// external and inertial forces, if present), the reaction force is the sum of the nodal
+/// forces over the given nodes. The result has `solution_dim` components.
+pub fn node_set_reaction<'a, T: Real>(
+    forces: impl Into<DVectorView<'a, T>>,
+    nodes: &[usize],
+    solution_dim: usize,
+) -> DVector<T> {
+    let forces = forces.into();
+    let mut reaction = DVector::zeros(solution_dim);
+    for &node in nodes {
+        reaction += forces.rows(solution_dim * node, solution_dim);
+    }
+    reaction
+}
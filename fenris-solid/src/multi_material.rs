@@ -0,0 +1,279 @@
+use crate::MaterialEllipticOperator;
+use eyre::{bail, eyre};
+use fenris::assembly::local::{
+    ElementConnectivityAssembler, ElementEllipticAssembler, ElementEllipticAssemblerBuilder, ElementMatrixAssembler,
+    ElementScalarAssembler, ElementVectorAssembler,
+};
+use fenris::nalgebra::{DMatrixViewMut, DVectorView, DVectorViewMut, Scalar};
+use fenris::Symmetry;
+use std::collections::BTreeMap;
+
+/// A collection of materials identified by integer material IDs.
+///
+/// Each material is paired with a quadrature table that provides the quadrature rule and the
+/// material parameters for each element. The quadrature tables are indexed by the element
+/// indices of the *full* finite element space, so that the same table may be used no matter
+/// which material an element is assigned to.
+///
+/// All materials in a registry share the same material type and quadrature table type,
+/// and therefore the same parameter representation. Different material models can be
+/// combined by using trait objects, for example
+/// `dyn HyperelasticMaterial<T, D, Parameters = LameParameters<T>>`.
+pub struct MaterialRegistry<'a, Material: ?Sized, QTable: ?Sized> {
+    materials: BTreeMap<usize, (MaterialEllipticOperator<'a, Material>, &'a QTable)>,
+}
+
+impl<'a, Material: ?Sized, QTable: ?Sized> Default for MaterialRegistry<'a, Material, QTable> {
+    fn default() -> Self {
+        Self {
+            materials: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a, Material: ?Sized, QTable: ?Sized> MaterialRegistry<'a, Material, QTable> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a material with the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a material with the same ID has already been registered.
+    pub fn add_material(&mut self, id: usize, material: &'a Material, qtable: &'a QTable) -> &mut Self {
+        let previous = self
+            .materials
+            .insert(id, (MaterialEllipticOperator::new(material), qtable));
+        assert!(previous.is_none(), "Material ID {} is already registered", id);
+        self
+    }
+
+    /// Registers a material with the given ID.
+    ///
+    /// See [`add_material`](Self::add_material).
+    pub fn with_material(mut self, id: usize, material: &'a Material, qtable: &'a QTable) -> Self {
+        self.add_material(id, material, qtable);
+        self
+    }
+
+    /// The registered material IDs in ascending order.
+    pub fn material_ids(&self) -> impl '_ + Iterator<Item = usize> {
+        self.materials.keys().copied()
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.materials.contains_key(&id)
+    }
+}
+
+/// An element assembler for bodies consisting of multiple materials.
+///
+/// Every element is assigned a material ID, and each material ID is associated with an element
+/// assembler defined on the full finite element space. Element quantities are computed by the
+/// assembler associated with the material of the element. Elements on material interfaces need
+/// no special treatment, since the materials are coupled through the shared nodes.
+///
+/// Compared to splitting the mesh into one submesh per material and combining the assemblers
+/// with an [`AggregateElementAssembler`](fenris::assembly::local::AggregateElementAssembler),
+/// the element indices of the original mesh are preserved.
+///
+/// Multi-material elasticity assemblers are most conveniently constructed from a
+/// [`MaterialRegistry`] with [`from_registry`](MultiMaterialAssembler::from_registry).
+#[derive(Debug, Clone)]
+pub struct MultiMaterialAssembler<Assembler> {
+    // Material IDs and the associated assemblers, sorted by material ID
+    assemblers: Vec<(usize, Assembler)>,
+    // For each element, the index of its assembler in `assemblers`
+    element_assembler_indices: Vec<usize>,
+    solution_dim: usize,
+    num_nodes: usize,
+}
+
+impl<Assembler> MultiMaterialAssembler<Assembler>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    /// Constructs a multi-material assembler from per-element material IDs and
+    /// (material ID, assembler) pairs.
+    ///
+    /// Returns an error if
+    ///
+    /// - no assemblers are given,
+    /// - a material ID is associated with more than one assembler,
+    /// - the assemblers do not share the same elements, nodes and solution dimension,
+    /// - the number of material IDs is not equal to the number of elements, or
+    /// - an element has a material ID that is not associated with an assembler.
+    pub fn from_assemblers(
+        material_ids: &[usize],
+        assemblers: impl IntoIterator<Item = (usize, Assembler)>,
+    ) -> eyre::Result<Self> {
+        let mut assemblers: Vec<_> = assemblers.into_iter().collect();
+        assemblers.sort_by_key(|(id, _)| *id);
+        if let Some(window) = assemblers.windows(2).find(|w| w[0].0 == w[1].0) {
+            bail!("material ID {} is associated with more than one assembler", window[0].0);
+        }
+
+        let (_, first) = assemblers
+            .first()
+            .ok_or_else(|| eyre!("at least one material is required"))?;
+        let (solution_dim, num_elements, num_nodes) = (first.solution_dim(), first.num_elements(), first.num_nodes());
+        for (id, assembler) in &assemblers {
+            if assembler.solution_dim() != solution_dim
+                || assembler.num_elements() != num_elements
+                || assembler.num_nodes() != num_nodes
+            {
+                bail!(
+                    "assembler for material ID {} is inconsistent with the other assemblers \
+                     (all assemblers must have the same solution dimension, elements and nodes)",
+                    id
+                );
+            }
+        }
+
+        if material_ids.len() != num_elements {
+            bail!(
+                "number of material IDs ({}) does not match number of elements ({})",
+                material_ids.len(),
+                num_elements
+            );
+        }
+        let element_assembler_indices = material_ids
+            .iter()
+            .enumerate()
+            .map(|(element_index, id)| {
+                assemblers
+                    .binary_search_by_key(id, |(id, _)| *id)
+                    .map_err(|_| {
+                        eyre!(
+                            "element {} has material ID {}, which is not registered",
+                            element_index,
+                            id
+                        )
+                    })
+            })
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(Self {
+            assemblers,
+            element_assembler_indices,
+            solution_dim,
+            num_nodes,
+        })
+    }
+
+    /// The material ID of the given element.
+    pub fn material_id(&self, element_index: usize) -> usize {
+        self.assemblers[self.element_assembler_indices[element_index]].0
+    }
+
+    fn element_assembler(&self, element_index: usize) -> &Assembler {
+        &self.assemblers[self.element_assembler_indices[element_index]].1
+    }
+}
+
+impl<'a, T, Space, Material, QTable>
+    MultiMaterialAssembler<ElementEllipticAssembler<'a, T, Space, MaterialEllipticOperator<'a, Material>, QTable>>
+where
+    T: Scalar,
+    Material: ?Sized,
+    QTable: ?Sized,
+    ElementEllipticAssembler<'a, T, Space, MaterialEllipticOperator<'a, Material>, QTable>:
+        ElementConnectivityAssembler,
+{
+    /// Constructs a multi-material elasticity assembler for the given space, displacement and
+    /// per-element material IDs.
+    ///
+    /// See [`from_assemblers`](Self::from_assemblers) for the conditions under which
+    /// an error is returned.
+    pub fn from_registry(
+        space: &'a Space,
+        u: impl Into<DVectorView<'a, T>>,
+        material_ids: &[usize],
+        registry: &'a MaterialRegistry<'a, Material, QTable>,
+    ) -> eyre::Result<Self> {
+        let u = u.into();
+        let assemblers = registry.materials.iter().map(|(id, (operator, qtable))| {
+            let assembler = ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(space)
+                .with_operator(operator)
+                .with_quadrature_table(*qtable)
+                .with_u(u.clone())
+                .build();
+            (*id, assembler)
+        });
+        Self::from_assemblers(material_ids, assemblers)
+    }
+}
+
+impl<Assembler> ElementConnectivityAssembler for MultiMaterialAssembler<Assembler>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.solution_dim
+    }
+
+    fn num_elements(&self) -> usize {
+        self.element_assembler_indices.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.element_assembler(element_index)
+            .element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.element_assembler(element_index)
+            .populate_element_nodes(output, element_index)
+    }
+}
+
+impl<T, Assembler> ElementScalarAssembler<T> for MultiMaterialAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementScalarAssembler<T>,
+{
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
+        self.element_assembler(element_index)
+            .assemble_element_scalar(element_index)
+    }
+}
+
+impl<T, Assembler> ElementVectorAssembler<T> for MultiMaterialAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementVectorAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        self.element_assembler(element_index)
+            .assemble_element_vector_into(element_index, output)
+    }
+}
+
+impl<T, Assembler> ElementMatrixAssembler<T> for MultiMaterialAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.element_assembler(element_index)
+            .assemble_element_matrix_into(element_index, output)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        let all_symmetric = self
+            .assemblers
+            .iter()
+            .all(|(_, assembler)| assembler.symmetry() == Symmetry::Symmetric);
+        if all_symmetric {
+            Symmetry::Symmetric
+        } else {
+            Symmetry::NonSymmetric
+        }
+    }
+}
@@ -0,0 +1,699 @@
+//! Post-processing of displacement fields.
+//!
+//! Strain and stress measures are computed at quadrature points from the displacement gradient
+//! $\nabla \vec u$, and can be recovered to cells and nodes for visualization. Cell values are
+//! the volume averages of the quadrature point values over each element, and node values are the
+//! volume-weighted averages of the cell values of the elements that share the node.
+//!
+//! Symmetric tensors are exported with 6 components in the VTK ordering
+//! `XX, YY, ZZ, XY, YZ, XZ`, so that e.g. ParaView recognizes them as symmetric tensors.
+//! Tensors in fewer than three dimensions are padded with zeros.
+use crate::{deformation_gradient, HyperelasticMaterial};
+use eyre::{bail, eyre};
+use fenris::allocators::{BiDimAllocator, DimAllocator};
+use fenris::assembly::buffers::{BufferUpdate, InterpolationBuffer};
+use fenris::assembly::local::QuadratureTable;
+use fenris::assembly::QuadraturePointData;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::nalgebra::allocator::Allocator;
+use fenris::nalgebra::{DMatrix, DVectorView, DefaultAllocator, DimName, OMatrix, OPoint, OVector};
+use fenris::space::{FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use fenris::util::small_eig::symmetric_eigen;
+use fenris::{Real, SmallDim};
+use num_traits::ToPrimitive;
+use numeric_literals::replace_float_literals;
+
+/// Computes the Green-Lagrange strain $\vec E = \frac{1}{2} (\vec F^T \vec F - \vec I)$ given the
+/// displacement gradient $\nabla \vec u$.
+///
+/// The strain is computed as
+/// $\vec E = \frac{1}{2} (\nabla \vec u + \nabla \vec u^T + \nabla \vec u \nabla \vec u^T)$,
+/// which avoids the loss of accuracy associated with forming $\vec F$ for small displacements.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn green_lagrange_strain<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    (u_grad + u_grad.transpose() + u_grad * u_grad.transpose()) * 0.5
+}
+
+/// Computes the infinitesimal strain
+/// $\vec \varepsilon = \frac{1}{2} (\nabla \vec u + \nabla \vec u^T)$ given the displacement
+/// gradient $\nabla \vec u$.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn infinitesimal_strain<T, D>(u_grad: &OMatrix<T, D, D>) -> OMatrix<T, D, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    (u_grad + u_grad.transpose()) * 0.5
+}
+
+/// Computes the principal strains and principal directions of a symmetric strain tensor.
+///
+/// The principal strains are returned in descending order, and the principal directions are
+/// stored as the columns of the returned matrix, in the same order. Only the upper triangular
+/// part of the strain tensor is accessed.
+pub fn principal_strains<T, D>(strain: &OMatrix<T, D, D>) -> (OVector<T, D>, OMatrix<T, D, D>)
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let n = D::dim();
+    let eigen = symmetric_eigen(&DMatrix::from_fn(n, n, |i, j| strain[(i, j)]));
+    // The eigenvalues are given in ascending order
+    let values = OVector::<T, D>::from_fn(|i, _| eigen.eigenvalues[n - 1 - i]);
+    let directions = OMatrix::<T, D, D>::from_fn(|i, j| eigen.eigenvectors[(i, n - 1 - j)]);
+    (values, directions)
+}
+
+/// Computes the equivalent (von Mises) strain
+/// $\sqrt{\frac{2}{3} \vec e : \vec e}$ of a symmetric strain tensor, where $\vec e$ is the
+/// deviatoric part of the strain.
+///
+/// Strain tensors in fewer than three dimensions are interpreted as plane strain, i.e. the
+/// remaining components of the three-dimensional strain tensor are zero.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn equivalent_strain<T, D>(strain: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    assert!(D::dim() <= 3, "Unable to support dimensions larger than 3.");
+    // The deviatoric part of the (padded) 3x3 strain is e = strain - tr(strain) / 3 I,
+    // and therefore e : e = strain : strain - tr(strain)^2 / 3.
+    let trace = strain.trace();
+    let deviatoric_norm_squared = (strain.norm_squared() - trace * trace / 3.0).max(0.0);
+    (2.0 / 3.0 * deviatoric_norm_squared).sqrt()
+}
+
+/// Returns the components of a symmetric tensor in the 6-component VTK ordering
+/// `XX, YY, ZZ, XY, YZ, XZ`.
+///
+/// Only the upper triangular part of the tensor is accessed. Tensors in fewer than three
+/// dimensions are padded with zeros.
+pub fn symmetric_tensor_vtk_components<T, D>(tensor: &OMatrix<T, D, D>) -> [T; 6]
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    assert!(D::dim() <= 3, "Unable to support dimensions larger than 3.");
+    let entry = |i: usize, j: usize| {
+        if i < D::dim() && j < D::dim() {
+            tensor[(i, j)]
+        } else {
+            T::zero()
+        }
+    };
+    [
+        entry(0, 0),
+        entry(1, 1),
+        entry(2, 2),
+        entry(0, 1),
+        entry(1, 2),
+        entry(0, 2),
+    ]
+}
+
+/// Strain measures at a single point.
+///
+/// The principal strains, principal directions and equivalent strain are derived from
+/// the Green-Lagrange strain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrainMeasures<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub green_lagrange: OMatrix<T, D, D>,
+    pub infinitesimal: OMatrix<T, D, D>,
+    /// Principal strains in descending order.
+    pub principal_strains: OVector<T, D>,
+    /// Principal directions stored as columns, in the same order as the principal strains.
+    pub principal_directions: OMatrix<T, D, D>,
+    pub equivalent_strain: T,
+}
+
+impl<T, D> StrainMeasures<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Computes strain measures from the displacement gradient $\nabla \vec u$.
+    pub fn from_displacement_gradient(u_grad: &OMatrix<T, D, D>) -> Self {
+        Self::from_strains(green_lagrange_strain(u_grad), infinitesimal_strain(u_grad))
+    }
+
+    /// Computes the derived strain measures from the Green-Lagrange and infinitesimal strains.
+    pub fn from_strains(green_lagrange: OMatrix<T, D, D>, infinitesimal: OMatrix<T, D, D>) -> Self {
+        let (principal_strains, principal_directions) = principal_strains(&green_lagrange);
+        let equivalent_strain = equivalent_strain(&green_lagrange);
+        Self {
+            green_lagrange,
+            infinitesimal,
+            principal_strains,
+            principal_directions,
+            equivalent_strain,
+        }
+    }
+}
+
+/// A named field with a fixed number of components for each point or cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedField<T> {
+    pub name: String,
+    pub num_components: usize,
+    /// The components of the field, stored contiguously for each point or cell.
+    pub values: Vec<T>,
+}
+
+/// Strain fields recovered to the nodes and cells of a finite element space.
+///
+/// Both point and cell fields consist of the following fields:
+///
+/// - `green_lagrange_strain`: the Green-Lagrange strain (6 components).
+/// - `infinitesimal_strain`: the infinitesimal strain (6 components).
+/// - `principal_strains`: the principal strains in descending order (3 components).
+/// - `principal_direction_1`, `principal_direction_2`, ...: the principal directions,
+///   one field per dimension (3 components each).
+/// - `equivalent_strain`: the equivalent (von Mises) strain (1 component).
+///
+/// The principal strains, principal directions and equivalent strain are derived from the
+/// recovered Green-Lagrange strain. See the [module-level documentation](self) for the
+/// recovery procedure and the tensor component ordering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrainFields<T> {
+    pub point_fields: Vec<NamedField<T>>,
+    pub cell_fields: Vec<NamedField<T>>,
+}
+
+impl<T> StrainFields<T> {
+    /// Returns the point field with the given name.
+    pub fn point_field(&self, name: &str) -> Option<&NamedField<T>> {
+        self.point_fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the cell field with the given name.
+    pub fn cell_field(&self, name: &str) -> Option<&NamedField<T>> {
+        self.cell_fields.iter().find(|field| field.name == name)
+    }
+
+    /// Adds all point and cell fields as attributes to the given VTK data set builder.
+    ///
+    /// # Panics
+    /// Panics if the number of points or cells of the fields does not match the mesh.
+    pub fn add_to_vtk_builder<'a, T2, D, C>(
+        &self,
+        builder: FiniteElementMeshDataSetBuilder<'a, T2, D, C>,
+    ) -> FiniteElementMeshDataSetBuilder<'a, T2, D, C>
+    where
+        T: Real + ToPrimitive,
+        T2: Real + ToPrimitive,
+        D: DimName,
+        DefaultAllocator: Allocator<T2, D>,
+    {
+        let builder = self.point_fields.iter().fold(builder, |builder, field| {
+            builder.with_point_scalar_attributes(&field.name, field.num_components, &field.values)
+        });
+        self.cell_fields.iter().fold(builder, |builder, field| {
+            builder.with_cell_scalar_attributes(&field.name, field.num_components, &field.values)
+        })
+    }
+}
+
+/// Computes the displacement gradient and the physical quadrature weight at every
+/// quadrature point of every element.
+fn compute_u_grad_and_weights<T, Space, QTable>(
+    space: &Space,
+    u: DVectorView<T>,
+    qtable: &QTable,
+) -> eyre::Result<QuadraturePointData<(OMatrix<T, Space::GeometryDim, Space::GeometryDim>, T)>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let d = Space::GeometryDim::dim();
+    if u.len() != d * space.num_nodes() {
+        bail!(
+            "displacement vector has length {}, expected {} ({} nodes with {} components each)",
+            u.len(),
+            d * space.num_nodes(),
+            space.num_nodes(),
+            d
+        );
+    }
+
+    let mut buffer = InterpolationBuffer::default();
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    let mut element_sizes = Vec::with_capacity(space.num_elements());
+    let mut values = Vec::new();
+    for element_index in 0..space.num_elements() {
+        let quadrature_size = qtable.element_quadrature_size(element_index);
+        points.resize(quadrature_size, OPoint::origin());
+        weights.resize(quadrature_size, T::zero());
+        qtable.populate_element_quadrature(element_index, &mut points, &mut weights);
+
+        let mut element_buffer = buffer.prepare_element_in_space(element_index, space, u, d);
+        for (xi, &w) in points.iter().zip(&weights) {
+            element_buffer.update_reference_point(xi, BufferUpdate::BasisGradients);
+            // Transform the gradient with respect to reference coordinates to
+            // physical coordinates
+            let j = element_buffer.element_reference_jacobian();
+            let j_det = j.determinant();
+            let j_inv_t = j
+                .try_inverse()
+                .ok_or_else(|| eyre!("element {} has a singular reference Jacobian", element_index))?
+                .transpose();
+            let u_grad = j_inv_t * element_buffer.interpolate_ref_gradient::<Space::GeometryDim>();
+            values.push((u_grad, w * j_det.abs()));
+        }
+        element_sizes.push(quadrature_size);
+    }
+
+    let mut values = values.into_iter();
+    Ok(QuadraturePointData::from_element_sizes_and_fn(element_sizes, |_, _| {
+        values
+            .next()
+            .expect("Number of values matches total quadrature size")
+    }))
+}
+
+/// Computes strain measures at every quadrature point of every element, given the
+/// displacement vector `u` with one entry per node and spatial dimension.
+///
+/// Returns an error if the length of `u` is incompatible with the space, or if an element
+/// has a singular reference Jacobian at a quadrature point.
+pub fn compute_strain_measures_at_quadrature_points<'a, T, Space, QTable>(
+    space: &Space,
+    u: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<QuadraturePointData<StrainMeasures<T, Space::GeometryDim>>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u_grad_and_weights = compute_u_grad_and_weights(space, u.into(), qtable)?;
+    Ok(u_grad_and_weights.map_values(|(u_grad, _)| StrainMeasures::from_displacement_gradient(u_grad)))
+}
+
+/// Computes strain fields recovered to the nodes and cells of the space, given the displacement
+/// vector `u` with one entry per node and spatial dimension.
+///
+/// The resulting fields can be directly added to a VTK data set with
+/// [`StrainFields::add_to_vtk_builder`].
+///
+/// Returns an error if the length of `u` is incompatible with the space, or if an element
+/// has a singular reference Jacobian at a quadrature point or a non-positive volume with
+/// respect to the quadrature rule.
+pub fn compute_strain_fields<'a, T, Space, QTable>(
+    space: &Space,
+    u: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<StrainFields<T>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u_grad_and_weights = compute_u_grad_and_weights(space, u.into(), qtable)?;
+
+    let zero = OMatrix::<T, Space::GeometryDim, Space::GeometryDim>::zeros();
+    // Volume-weighted sums of Green-Lagrange and infinitesimal strains and the total volume
+    // of adjacent elements for each node
+    let mut node_sums = vec![(zero.clone(), zero.clone(), T::zero()); space.num_nodes()];
+    let mut cell_measures = Vec::with_capacity(space.num_elements());
+    let mut element_nodes = Vec::new();
+    for (element_index, element_data) in u_grad_and_weights.iter().enumerate() {
+        let mut volume = T::zero();
+        let mut green_lagrange = zero.clone();
+        let mut infinitesimal = zero.clone();
+        for (u_grad, w) in element_data {
+            volume += *w;
+            green_lagrange += green_lagrange_strain(u_grad) * *w;
+            infinitesimal += infinitesimal_strain(u_grad) * *w;
+        }
+        if volume <= T::zero() {
+            bail!(
+                "element {} has non-positive volume with respect to the quadrature rule",
+                element_index
+            );
+        }
+        green_lagrange /= volume;
+        infinitesimal /= volume;
+
+        element_nodes.resize(space.element_node_count(element_index), usize::MAX);
+        space.populate_element_nodes(&mut element_nodes, element_index);
+        for &node_index in &element_nodes {
+            let (node_green_lagrange, node_infinitesimal, node_volume) = &mut node_sums[node_index];
+            *node_green_lagrange += &green_lagrange * volume;
+            *node_infinitesimal += &infinitesimal * volume;
+            *node_volume += volume;
+        }
+        cell_measures.push(StrainMeasures::from_strains(green_lagrange, infinitesimal));
+    }
+
+    let point_measures: Vec<_> = node_sums
+        .into_iter()
+        .map(|(green_lagrange, infinitesimal, volume)| {
+            // Nodes that are not part of any element are assigned zero strain
+            if volume > T::zero() {
+                StrainMeasures::from_strains(green_lagrange / volume, infinitesimal / volume)
+            } else {
+                StrainMeasures::from_strains(zero.clone(), zero.clone())
+            }
+        })
+        .collect();
+
+    Ok(StrainFields {
+        point_fields: strain_measure_fields(&point_measures),
+        cell_fields: strain_measure_fields(&cell_measures),
+    })
+}
+
+fn padded_vector<T: Real>(components: impl IntoIterator<Item = T>) -> [T; 3] {
+    let mut padded = [T::zero(); 3];
+    for (padded_i, component) in padded.iter_mut().zip(components) {
+        *padded_i = component;
+    }
+    padded
+}
+
+fn strain_measure_fields<T, D>(measures: &[StrainMeasures<T, D>]) -> Vec<NamedField<T>>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let field = |name: &str, num_components, values: Vec<T>| NamedField {
+        name: name.to_string(),
+        num_components,
+        values,
+    };
+
+    let mut fields = vec![
+        field(
+            "green_lagrange_strain",
+            6,
+            measures
+                .iter()
+                .flat_map(|m| symmetric_tensor_vtk_components(&m.green_lagrange))
+                .collect(),
+        ),
+        field(
+            "infinitesimal_strain",
+            6,
+            measures
+                .iter()
+                .flat_map(|m| symmetric_tensor_vtk_components(&m.infinitesimal))
+                .collect(),
+        ),
+        field(
+            "principal_strains",
+            3,
+            measures
+                .iter()
+                .flat_map(|m| padded_vector(m.principal_strains.iter().copied()))
+                .collect(),
+        ),
+    ];
+    for k in 0..D::dim() {
+        fields.push(field(
+            &format!("principal_direction_{}", k + 1),
+            3,
+            measures
+                .iter()
+                .flat_map(|m| padded_vector(m.principal_directions.column(k).iter().copied()))
+                .collect(),
+        ));
+    }
+    fields.push(field(
+        "equivalent_strain",
+        1,
+        measures.iter().map(|m| m.equivalent_strain).collect(),
+    ));
+    fields
+}
+
+/// Computes the Cauchy stress $\vec \sigma = J^{-1} \vec P \vec F^T$ from the first Piola-Kirchhoff
+/// stress $\vec P$ and the deformation gradient $\vec F$, where $J = \det \vec F$.
+///
+/// Returns `None` if $J \leq 0$.
+#[allow(non_snake_case)]
+pub fn cauchy_stress<T, D>(
+    first_piola_kirchhoff: &OMatrix<T, D, D>,
+    deformation_gradient: &OMatrix<T, D, D>,
+) -> Option<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let F = deformation_gradient;
+    let J = F.determinant();
+    (J > T::zero()).then(|| first_piola_kirchhoff * F.transpose() / J)
+}
+
+/// Computes the von Mises stress $\sqrt{\frac{3}{2} \vec s : \vec s}$ of a symmetric stress
+/// tensor, where $\vec s$ is the deviatoric part of the stress.
+///
+/// Stress tensors in fewer than three dimensions are interpreted as plane stress, i.e. the
+/// remaining components of the three-dimensional stress tensor are zero.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn von_mises_stress<T, D>(stress: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    assert!(D::dim() <= 3, "Unable to support dimensions larger than 3.");
+    let trace = stress.trace();
+    let deviatoric_norm_squared = (stress.norm_squared() - trace * trace / 3.0).max(0.0);
+    (1.5 * deviatoric_norm_squared).sqrt()
+}
+
+/// Stress measures of a single element or node.
+///
+/// The principal stresses, principal directions and von Mises stress are derived from
+/// the Cauchy stress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressResult<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub first_piola_kirchhoff: OMatrix<T, D, D>,
+    pub cauchy: OMatrix<T, D, D>,
+    /// Principal stresses in descending order.
+    pub principal_stresses: OVector<T, D>,
+    /// Principal directions stored as columns, in the same order as the principal stresses.
+    pub principal_directions: OMatrix<T, D, D>,
+    pub von_mises: T,
+    /// The volume over which the stresses were averaged.
+    pub volume: T,
+}
+
+impl<T, D> StressResult<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Computes the derived stress measures from the first Piola-Kirchhoff and Cauchy stresses.
+    pub fn from_stresses(first_piola_kirchhoff: OMatrix<T, D, D>, cauchy: OMatrix<T, D, D>, volume: T) -> Self {
+        // The principal values of a symmetric tensor do not depend on the kind of tensor
+        let (principal_stresses, principal_directions) = principal_strains(&cauchy);
+        let von_mises = von_mises_stress(&cauchy);
+        Self {
+            first_piola_kirchhoff,
+            cauchy,
+            principal_stresses,
+            principal_directions,
+            von_mises,
+            volume,
+        }
+    }
+}
+
+/// Computes the stresses of each element, given the displacement vector `u` with one entry per
+/// node and spatial dimension.
+///
+/// The stresses are evaluated at the quadrature points of the quadrature table, whose data
+/// provides the material parameters, and averaged over the volume of each element. Using a
+/// single-point quadrature rule therefore gives the stresses at the (reference) centroid of each
+/// element. The Cauchy stress is obtained from the first Piola-Kirchhoff stress with
+/// [`cauchy_stress`] at each quadrature point.
+///
+/// Returns an error if the length of `u` is incompatible with the space, if an element
+/// has a singular reference Jacobian or is inverted ($\det \vec F \leq 0$) at a quadrature point,
+/// or if an element has a non-positive volume with respect to the quadrature rule.
+#[allow(non_snake_case)]
+pub fn compute_element_stresses<'a, T, Space, Material, QTable>(
+    space: &Space,
+    material: &Material,
+    parameters_table: &QTable,
+    u: impl Into<DVectorView<'a, T>>,
+) -> eyre::Result<Vec<StressResult<T, Space::GeometryDim>>>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Material: ?Sized + HyperelasticMaterial<T, Space::ReferenceDim>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim, Data = Material::Parameters>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u_grad_and_weights = compute_u_grad_and_weights(space, u.into(), parameters_table)?;
+
+    let zero = OMatrix::<T, Space::GeometryDim, Space::GeometryDim>::zeros();
+    let mut parameters = Vec::new();
+    let mut results = Vec::with_capacity(space.num_elements());
+    for (element_index, element_data) in u_grad_and_weights.iter().enumerate() {
+        parameters.resize(element_data.len(), Material::Parameters::default());
+        parameters_table.populate_element_data(element_index, &mut parameters);
+
+        let mut volume = T::zero();
+        let mut P_sum = zero.clone();
+        let mut cauchy_sum = zero.clone();
+        for ((u_grad, w), params) in element_data.iter().zip(&parameters) {
+            let P = material.compute_stress_tensor_du(u_grad, params);
+            let cauchy = cauchy_stress(&P, &deformation_gradient(u_grad))
+                .ok_or_else(|| eyre!("element {} is inverted at a quadrature point", element_index))?;
+            volume += *w;
+            P_sum += P * *w;
+            cauchy_sum += cauchy * *w;
+        }
+        if volume <= T::zero() {
+            bail!(
+                "element {} has non-positive volume with respect to the quadrature rule",
+                element_index
+            );
+        }
+        results.push(StressResult::from_stresses(P_sum / volume, cauchy_sum / volume, volume));
+    }
+    Ok(results)
+}
+
+/// Recovers nodal stresses from element stresses by volume-weighted averaging.
+///
+/// The stresses of each node are the averages of the stresses of the elements that share the
+/// node, weighted by the [volume](StressResult::volume) of each element. The derived measures are
+/// recomputed from the averaged stresses. Nodes that are not part of any element are assigned
+/// zero stress and zero volume.
+///
+/// The nodal stresses can be exported as VTK point data with [`stress_result_fields`].
+///
+/// # Panics
+///
+/// Panics if the number of element stresses does not match the number of elements in the space.
+pub fn project_stress_to_nodes<T, D, Space>(
+    space: &Space,
+    element_stresses: &[StressResult<T, D>],
+) -> Vec<StressResult<T, D>>
+where
+    T: Real,
+    D: DimName,
+    Space: ?Sized + FiniteElementConnectivity,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    assert_eq!(
+        element_stresses.len(),
+        space.num_elements(),
+        "Number of element stresses must match number of elements"
+    );
+    let zero = OMatrix::<T, D, D>::zeros();
+    let mut node_sums = vec![(zero.clone(), zero.clone(), T::zero()); space.num_nodes()];
+    let mut element_nodes = Vec::new();
+    for (element_index, stress) in element_stresses.iter().enumerate() {
+        element_nodes.resize(space.element_node_count(element_index), usize::MAX);
+        space.populate_element_nodes(&mut element_nodes, element_index);
+        for &node_index in &element_nodes {
+            let (node_first_piola_kirchhoff, node_cauchy, node_volume) = &mut node_sums[node_index];
+            *node_first_piola_kirchhoff += &stress.first_piola_kirchhoff * stress.volume;
+            *node_cauchy += &stress.cauchy * stress.volume;
+            *node_volume += stress.volume;
+        }
+    }
+
+    node_sums
+        .into_iter()
+        .map(|(first_piola_kirchhoff, cauchy, volume)| {
+            if volume > T::zero() {
+                StressResult::from_stresses(first_piola_kirchhoff / volume, cauchy / volume, volume)
+            } else {
+                StressResult::from_stresses(zero.clone(), zero.clone(), T::zero())
+            }
+        })
+        .collect()
+}
+
+/// Returns named fields for the given stresses, for export as point or cell data.
+///
+/// The fields are:
+///
+/// - `cauchy_stress`: the Cauchy stress (6 components).
+/// - `principal_stresses`: the principal stresses in descending order (3 components).
+/// - `principal_stress_direction_1`, `principal_stress_direction_2`, ...: the principal
+///   directions, one field per dimension (3 components each).
+/// - `von_mises_stress`: the von Mises stress (1 component).
+///
+/// See the [module-level documentation](self) for the tensor component ordering.
+pub fn stress_result_fields<T, D>(results: &[StressResult<T, D>]) -> Vec<NamedField<T>>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let field = |name: &str, num_components, values: Vec<T>| NamedField {
+        name: name.to_string(),
+        num_components,
+        values,
+    };
+
+    let mut fields = vec![
+        field(
+            "cauchy_stress",
+            6,
+            results
+                .iter()
+                .flat_map(|r| symmetric_tensor_vtk_components(&r.cauchy))
+                .collect(),
+        ),
+        field(
+            "principal_stresses",
+            3,
+            results
+                .iter()
+                .flat_map(|r| padded_vector(r.principal_stresses.iter().copied()))
+                .collect(),
+        ),
+    ];
+    for k in 0..D::dim() {
+        fields.push(field(
+            &format!("principal_stress_direction_{}", k + 1),
+            3,
+            results
+                .iter()
+                .flat_map(|r| padded_vector(r.principal_directions.column(k).iter().copied()))
+                .collect(),
+        ));
+    }
+    fields.push(field(
+        "von_mises_stress",
+        1,
+        results.iter().map(|r| r.von_mises).collect(),
+    ));
+    fields
+}
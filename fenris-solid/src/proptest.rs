@@ -0,0 +1,85 @@
+//! Strategies for property-based testing of material models with `proptest`.
+use crate::{dispatch_physical_dim, PhysicalDim};
+use ::proptest::collection::vec;
+use ::proptest::prelude::*;
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DefaultAllocator, OMatrix, OVector, Rotation2, Rotation3, Vector3};
+use std::f64::consts::PI;
+
+/// A strategy for deformation gradients $\vec F = \vec I + \vec E$, where each entry of $\vec E$ is
+/// bounded by `max_perturbation` in absolute value.
+///
+/// Since $\| \vec E \|_2 \leq d \cdot$ `max_perturbation` $< 1$, the deformation gradient is
+/// guaranteed to have a positive determinant. Deformation gradients shrink towards the identity.
+///
+/// # Panics
+///
+/// Panics if `max_perturbation` is not in the interval $[0, 1 / d)$.
+pub fn deformation_gradient_near_identity<D>(max_perturbation: f64) -> impl Strategy<Value = OMatrix<f64, D, D>>
+where
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let d = D::dim();
+    assert!(
+        max_perturbation >= 0.0 && max_perturbation * (d as f64) < 1.0,
+        "max_perturbation must be in [0, 1/d)"
+    );
+    vec(-max_perturbation..=max_perturbation, d * d)
+        .prop_map(|perturbation| OMatrix::<f64, D, D>::identity() + OMatrix::<f64, D, D>::from_iterator(perturbation))
+}
+
+/// A strategy for deformation gradients with determinant at least `min_det` and singular values
+/// at most `max_singular_value`.
+///
+/// The deformation gradient is constructed as $\vec F = \vec U \vec \Sigma \vec V^T$ from random
+/// rotations $\vec U$ and $\vec V$ and a diagonal matrix $\vec \Sigma$ of singular values in the
+/// interval $[\texttt{min\\_det}^{1/d}, \texttt{max\\_singular\\_value}]$, so that the bound on the
+/// determinant holds by construction (up to roundoff). Deformation gradients shrink towards pure
+/// stretches, with the singular values shrinking towards $1$ if permitted by the bounds.
+///
+/// # Panics
+///
+/// Panics if `min_det` is not positive or if $\texttt{min\\_det} > \texttt{max\\_singular\\_value}^d$.
+pub fn deformation_gradient_positive_det<D>(
+    min_det: f64,
+    max_singular_value: f64,
+) -> impl Strategy<Value = OMatrix<f64, D, D>>
+where
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let d = D::dim();
+    assert!(min_det > 0.0, "min_det must be positive");
+    let min_singular_value = min_det.powf(1.0 / d as f64);
+    assert!(
+        min_singular_value <= max_singular_value,
+        "min_det must not exceed max_singular_value^d"
+    );
+
+    // Sampling the logarithm of the singular values makes them shrink towards 1
+    let log_singular_values = vec(min_singular_value.ln()..=max_singular_value.ln(), d);
+    let num_angles = d * (d - 1) / 2;
+    let angles = || vec(-PI..=PI, num_angles);
+    (log_singular_values, angles(), angles()).prop_map(|(log_singular_values, u_angles, v_angles)| {
+        let singular_values = OVector::<f64, D>::from_iterator(log_singular_values.into_iter().map(f64::exp));
+        let sigma = OMatrix::<f64, D, D>::from_diagonal(&singular_values);
+        rotation::<D>(&u_angles) * sigma * rotation::<D>(&v_angles).transpose()
+    })
+}
+
+/// Constructs the rotation with the given angle (2D) or scaled axis (3D).
+fn rotation<D>(angles: &[f64]) -> OMatrix<f64, D, D>
+where
+    D: PhysicalDim,
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    dispatch_physical_dim::<D, _>(
+        |_| OMatrix::<f64, D, D>::identity(),
+        |dim| dim.generic_matrix(Rotation2::new(angles[0]).into_inner()),
+        |dim| {
+            let axis = Vector3::new(angles[0], angles[1], angles[2]);
+            dim.generic_matrix(Rotation3::from_scaled_axis(axis).into_inner())
+        },
+    )
+}
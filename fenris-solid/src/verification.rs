@@ -0,0 +1,202 @@
+//! Finite difference verification of [`HyperelasticMaterial`] implementations.
+//!
+//! Every material must satisfy two consistency conditions: the stress tensor must be the derivative
+//! of the strain energy density, $\vec P = \pd{\psi}{\vec F}$, and the stress contraction must be
+//! consistent with the derivative of the stress tensor, i.e.
+//! <div>$$
+//! \left[ \mathcal{C}_{\vec P}(\vec F, \vec e_k, \vec e_m) \right]_{ij} = \pd{P_{ik}}{F_{jm}}.
+//! $$</div>
+//! The functions in this module check these conditions with central finite differences over all
+//! components of $\vec F$, and are intended for use in the test suites of material implementations,
+//! including those in downstream crates.
+//!
+//! # Step size
+//!
+//! The step size is chosen adaptively for the given deformation gradient. With unit roundoff
+//! $\epsilon$, it is given by
+//! <div>$$
+//! h = \epsilon^{1/3} \min \left( \max(1, \| \vec F \|_{\max}), \sigma_{\min}(\vec F) \right),
+//! $$</div>
+//! where $\sigma_{\min}(\vec F)$ is the smallest singular value of $\vec F$, which measures the
+//! distance to the nearest singular matrix. This ensures that the perturbed deformation gradients
+//! do not cross the singularity at $\det \vec F = 0$, at which many materials are undefined.
+//! The step size is never smaller than $\epsilon^{2/3} \max(1, \| \vec F \|_{\max})$, so that
+//! the verification of (nearly) singular deformation gradients is dominated by roundoff errors
+//! and should be interpreted with care.
+use crate::HyperelasticMaterial;
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra::{DMatrix, DefaultAllocator, DimName, OMatrix, OVector};
+use fenris::Real;
+
+/// The result of comparing an analytic derivative with its finite difference approximation.
+///
+/// For the stress tensor, the matrices are the $d \times d$ stress tensors. For the stress contraction,
+/// the matrices have size $d^2 \times d^2$, where the $d \times d$ block $(k, m)$ is the contraction
+/// $\mathcal{C}_{\vec P}(\vec F, \vec e_k, \vec e_m)$.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsistencyReport<T: Real> {
+    /// The analytic derivative computed by the material.
+    pub analytic: DMatrix<T>,
+    /// The finite difference approximation of the derivative.
+    pub approximate: DMatrix<T>,
+    /// The absolute error of each component.
+    pub errors: DMatrix<T>,
+    /// The maximum absolute error over all components.
+    pub max_error: T,
+    /// The maximum absolute error relative to the largest component of the analytic or approximate derivative.
+    ///
+    /// Zero if both derivatives vanish.
+    pub relative_error: T,
+    /// The finite difference step size.
+    pub step_size: T,
+    /// The tolerance for the relative error.
+    pub tolerance: T,
+}
+
+impl<T: Real> ConsistencyReport<T> {
+    fn from_derivatives(analytic: DMatrix<T>, approximate: DMatrix<T>, step_size: T, tolerance: T) -> Self {
+        let errors = (&analytic - &approximate).abs();
+        let max_error = errors.max();
+        let scale = analytic.amax().max(approximate.amax());
+        let relative_error = if scale > T::zero() {
+            max_error / scale
+        } else {
+            T::zero()
+        };
+        Self {
+            analytic,
+            approximate,
+            errors,
+            max_error,
+            relative_error,
+            step_size,
+            tolerance,
+        }
+    }
+
+    /// Returns `true` if the relative error does not exceed the tolerance.
+    pub fn is_consistent(&self) -> bool {
+        self.relative_error <= self.tolerance
+    }
+}
+
+/// Computes the adaptive finite difference step size for the given deformation gradient.
+///
+/// See the [module-level documentation](crate::verification) for details.
+#[allow(non_snake_case)]
+pub fn finite_difference_step_size<T, D>(deformation_gradient: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let d = D::dim();
+    let F = DMatrix::from_column_slice(d, d, deformation_gradient.as_slice());
+    let scale = F.amax().max(T::one());
+    let sigma_min = F.singular_values().min();
+    let eps = T::default_epsilon();
+    let cbrt_eps = eps.cbrt();
+    let h = cbrt_eps * scale.min(sigma_min);
+    h.max(cbrt_eps * cbrt_eps * scale)
+}
+
+/// Verifies that the stress tensor of the material is the derivative of its strain energy density.
+///
+/// The stress tensor at `deformation_gradient` is compared with a central finite difference
+/// approximation of the derivative of the energy density, and the report is considered consistent
+/// if the relative error does not exceed `tolerance`.
+#[allow(non_snake_case)]
+pub fn verify_stress_consistency<T, D, M>(
+    material: &M,
+    deformation_gradient: &OMatrix<T, D, D>,
+    parameters: &M::Parameters,
+    tolerance: T,
+) -> ConsistencyReport<T>
+where
+    T: Real,
+    D: DimName,
+    M: ?Sized + HyperelasticMaterial<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let d = D::dim();
+    let h = finite_difference_step_size(deformation_gradient);
+    let two_h = h + h;
+
+    let P = material.compute_stress_tensor(deformation_gradient, parameters);
+    let analytic = DMatrix::from_column_slice(d, d, P.as_slice());
+
+    let mut F = deformation_gradient.clone();
+    let mut approximate = DMatrix::zeros(d, d);
+    for i in 0..d {
+        for j in 0..d {
+            let F_ij = F[(i, j)];
+            F[(i, j)] = F_ij + h;
+            let psi_plus = material.compute_energy_density(&F, parameters);
+            F[(i, j)] = F_ij - h;
+            let psi_minus = material.compute_energy_density(&F, parameters);
+            F[(i, j)] = F_ij;
+            approximate[(i, j)] = (psi_plus - psi_minus) / two_h;
+        }
+    }
+
+    ConsistencyReport::from_derivatives(analytic, approximate, h, tolerance)
+}
+
+/// Verifies that the stress contraction of the material is consistent with its stress tensor.
+///
+/// The contractions $\mathcal{C}_{\vec P}(\vec F, \vec e_k, \vec e_m)$ for all pairs of standard
+/// basis vectors are compared with central finite difference approximations of the derivatives of the
+/// stress tensor, and the report is considered consistent if the relative error does not exceed `tolerance`.
+#[allow(non_snake_case)]
+pub fn verify_contraction_consistency<T, D, M>(
+    material: &M,
+    deformation_gradient: &OMatrix<T, D, D>,
+    parameters: &M::Parameters,
+    tolerance: T,
+) -> ConsistencyReport<T>
+where
+    T: Real,
+    D: DimName,
+    M: ?Sized + HyperelasticMaterial<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let d = D::dim();
+    let h = finite_difference_step_size(deformation_gradient);
+    let two_h = h + h;
+
+    let mut analytic = DMatrix::zeros(d * d, d * d);
+    for k in 0..d {
+        for m in 0..d {
+            let e_k = OVector::<T, D>::from_fn(|i, _| if i == k { T::one() } else { T::zero() });
+            let e_m = OVector::<T, D>::from_fn(|i, _| if i == m { T::one() } else { T::zero() });
+            let C = material.compute_stress_contraction(deformation_gradient, &e_k, &e_m, parameters);
+            analytic
+                .view_mut((d * k, d * m), (d, d))
+                .copy_from_slice(C.as_slice());
+        }
+    }
+
+    // The derivative of P with respect to F_jm gives the entries (i, j) of the blocks (k, m)
+    // for all i and k
+    let mut F = deformation_gradient.clone();
+    let mut approximate = DMatrix::zeros(d * d, d * d);
+    for j in 0..d {
+        for m in 0..d {
+            let F_jm = F[(j, m)];
+            F[(j, m)] = F_jm + h;
+            let P_plus = material.compute_stress_tensor(&F, parameters);
+            F[(j, m)] = F_jm - h;
+            let P_minus = material.compute_stress_tensor(&F, parameters);
+            F[(j, m)] = F_jm;
+
+            let dP_dFjm = (P_plus - P_minus) / two_h;
+            for i in 0..d {
+                for k in 0..d {
+                    approximate[(d * k + i, d * m + j)] = dP_dFjm[(i, k)];
+                }
+            }
+        }
+    }
+
+    ConsistencyReport::from_derivatives(analytic, approximate, h, tolerance)
+}
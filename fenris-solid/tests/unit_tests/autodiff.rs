@@ -0,0 +1,185 @@
+use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters};
+use fenris::allocators::DimAllocator;
+use fenris::nalgebra;
+use fenris::nalgebra::{vector, ComplexField, DefaultAllocator, Matrix2, OMatrix, RealField};
+use fenris_solid::autodiff::{AutodiffMaterial, EnergyDensity, HyperDual};
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial};
+use fenris_solid::{HyperelasticMaterial, PhysicalDim};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn lift_lame_parameters(parameters: &LameParameters<f64>) -> LameParameters<HyperDual<f64>> {
+    LameParameters {
+        mu: HyperDual::constant(parameters.mu),
+        lambda: HyperDual::constant(parameters.lambda),
+    }
+}
+
+/// Energy density of the linear elastic material, evaluated with hyper-dual numbers.
+pub struct LinearElasticEnergy;
+
+impl<D: PhysicalDim> EnergyDensity<f64, D> for LinearElasticEnergy
+where
+    DefaultAllocator: DimAllocator<HyperDual<f64>, D>,
+{
+    type Parameters = LameParameters<f64>;
+
+    fn compute_energy_density(
+        &self,
+        deformation_gradient: &OMatrix<HyperDual<f64>, D, D>,
+        parameters: &Self::Parameters,
+    ) -> HyperDual<f64> {
+        LinearElasticMaterial.compute_energy_density(deformation_gradient, &lift_lame_parameters(parameters))
+    }
+}
+
+/// Energy density of the Neo-Hookean material, evaluated with hyper-dual numbers.
+pub struct NeoHookeanEnergy;
+
+impl<D: PhysicalDim> EnergyDensity<f64, D> for NeoHookeanEnergy
+where
+    DefaultAllocator: DimAllocator<HyperDual<f64>, D>,
+{
+    type Parameters = LameParameters<f64>;
+
+    fn compute_energy_density(
+        &self,
+        deformation_gradient: &OMatrix<HyperDual<f64>, D, D>,
+        parameters: &Self::Parameters,
+    ) -> HyperDual<f64> {
+        NeoHookeanMaterial.compute_energy_density(deformation_gradient, &lift_lame_parameters(parameters))
+    }
+}
+
+#[test]
+fn hyper_dual_computes_exact_second_derivatives() {
+    // f(x) = exp(x) sin(x) / sqrt(x)
+    let f = |x: HyperDual<f64>| x.exp() * x.sin() / x.sqrt();
+    let df = |x: f64| x.exp() * (x.sin() + x.cos()) / x.sqrt() - 0.5 * x.exp() * x.sin() / x.powf(1.5);
+    let d2f = |x: f64| {
+        let e = x.exp();
+        let (s, c) = x.sin_cos();
+        2.0 * e * c / x.sqrt() - e * (s + c) / x.powf(1.5) + 0.75 * e * s / x.powf(2.5)
+    };
+
+    let x = 1.3;
+    let y = f(HyperDual::new(x, 1.0, 1.0, 0.0));
+    assert_scalar_eq!(y.re, x.exp() * x.sin() / x.sqrt(), comp = float);
+    assert_scalar_eq!(y.e1, df(x), comp = abs, tol = 1e-13);
+    assert_scalar_eq!(y.e2, df(x), comp = abs, tol = 1e-13);
+    assert_scalar_eq!(y.e12, d2f(x), comp = abs, tol = 1e-13);
+
+    // The mixed partial derivative of g(x, y) = atan2(y, x) is (y^2 - x^2) / (x^2 + y^2)^2
+    let (x, y) = (0.7, -1.9);
+    let g = HyperDual::new(y, 0.0, 1.0, 0.0).atan2(HyperDual::new(x, 1.0, 0.0, 0.0));
+    let r2: f64 = x * x + y * y;
+    assert_scalar_eq!(g.re, y.atan2(x), comp = float);
+    assert_scalar_eq!(g.e1, -y / r2, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(g.e2, x / r2, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(g.e12, (y * y - x * x) / r2.powi(2), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn autodiff_linear_elastic_matches_analytic_2d() {
+    let material = AutodiffMaterial::new(LinearElasticEnergy);
+    let lame = lame_parameters();
+    let f = deformation_gradient_2d();
+    let (a, b) = (vector![-3.0, 4.0], vector![-5.0, 2.0]);
+
+    let psi = material.compute_energy_density(&f, &lame);
+    let stress = material.compute_stress_tensor(&f, &lame);
+    let contraction = material.compute_stress_contraction(&f, &a, &b, &lame);
+
+    let expected_psi = LinearElasticMaterial.compute_energy_density(&f, &lame);
+    let expected_stress = LinearElasticMaterial.compute_stress_tensor(&f, &lame);
+    let expected_contraction = LinearElasticMaterial.compute_stress_contraction(&f, &a, &b, &lame);
+    assert_scalar_eq!(psi, expected_psi, comp = float);
+    assert_matrix_eq!(
+        stress,
+        expected_stress,
+        comp = abs,
+        tol = 1e-14 * expected_stress.amax()
+    );
+    assert_matrix_eq!(
+        contraction,
+        expected_contraction,
+        comp = abs,
+        tol = 1e-14 * expected_contraction.amax()
+    );
+}
+
+#[test]
+fn autodiff_linear_elastic_matches_analytic_3d() {
+    let material = AutodiffMaterial::new(LinearElasticEnergy);
+    let lame = lame_parameters();
+    let f = deformation_gradient_3d();
+    let (a, b) = (vector![-3.0, 4.0, -5.0], vector![-5.0, 2.0, 1.0]);
+
+    let psi = material.compute_energy_density(&f, &lame);
+    let stress = material.compute_stress_tensor(&f, &lame);
+    let contraction = material.compute_stress_contraction(&f, &a, &b, &lame);
+
+    let expected_psi = LinearElasticMaterial.compute_energy_density(&f, &lame);
+    let expected_stress = LinearElasticMaterial.compute_stress_tensor(&f, &lame);
+    let expected_contraction = LinearElasticMaterial.compute_stress_contraction(&f, &a, &b, &lame);
+    assert_scalar_eq!(psi, expected_psi, comp = float);
+    assert_matrix_eq!(
+        stress,
+        expected_stress,
+        comp = abs,
+        tol = 1e-14 * expected_stress.amax()
+    );
+    assert_matrix_eq!(
+        contraction,
+        expected_contraction,
+        comp = abs,
+        tol = 1e-14 * expected_contraction.amax()
+    );
+}
+
+#[test]
+fn autodiff_neo_hookean_matches_analytic() {
+    let material = AutodiffMaterial::new(NeoHookeanEnergy);
+    let lame = lame_parameters();
+    let f = deformation_gradient_3d();
+    let (a, b) = (vector![-3.0, 4.0, -5.0], vector![-5.0, 2.0, 1.0]);
+
+    let stress = material.compute_stress_tensor(&f, &lame);
+    let contraction = material.compute_stress_contraction(&f, &a, &b, &lame);
+    let expected_stress = NeoHookeanMaterial.compute_stress_tensor(&f, &lame);
+    let expected_contraction = NeoHookeanMaterial.compute_stress_contraction(&f, &a, &b, &lame);
+    assert_matrix_eq!(
+        stress,
+        expected_stress,
+        comp = abs,
+        tol = 1e-12 * expected_stress.amax()
+    );
+    assert_matrix_eq!(
+        contraction,
+        expected_contraction,
+        comp = abs,
+        tol = 1e-12 * expected_contraction.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn autodiff_material_from_closure() {
+    let material = AutodiffMaterial::from_energy_fn(|F: &Matrix2<HyperDual<f64>>, parameters: &LameParameters<f64>| {
+        LinearElasticMaterial.compute_energy_density(F, &lift_lame_parameters(parameters))
+    });
+    let lame = lame_parameters();
+    let f = deformation_gradient_2d();
+    let stress = material.compute_stress_tensor(&f, &lame);
+    let expected_stress = LinearElasticMaterial.compute_stress_tensor(&f, &lame);
+    assert_matrix_eq!(
+        stress,
+        expected_stress,
+        comp = abs,
+        tol = 1e-14 * expected_stress.amax()
+    );
+    // The real part of a hyper-dual number is exactly the real-valued result
+    assert_eq!(
+        material.compute_energy_density(&f, &lame),
+        LinearElasticMaterial.compute_energy_density(&f, &lame)
+    );
+}
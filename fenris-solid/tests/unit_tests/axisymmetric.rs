@@ -0,0 +1,114 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{ElementMatrixAssembler, UniformQuadratureTable};
+use fenris::assembly::DirichletBcs;
+use fenris::mesh::procedural::create_graded_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, U2};
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, YoungPoisson};
+use fenris_solid::AxisymmetricElasticityAssembler;
+use matrixcompare::assert_matrix_eq;
+use std::f64::consts::PI;
+
+fn linspace(a: f64, b: f64, num_cells: usize) -> Vec<f64> {
+    (0..=num_cells)
+        .map(|i| a + (b - a) * i as f64 / num_cells as f64)
+        .collect()
+}
+
+fn quadrature_table(lame: LameParameters<f64>) -> UniformQuadratureTable<f64, U2, LameParameters<f64>> {
+    UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), lame)
+}
+
+#[test]
+fn axisymmetric_stiffness_has_axial_translation_in_null_space() {
+    let mesh: QuadMesh2d<f64> = create_graded_quad_mesh_2d(&[0.0, 0.5, 1.0], &[0.0, 1.0, 1.5]);
+    let qtable = quadrature_table(LameParameters { mu: 3.0, lambda: 5.0 });
+    let assembler = AxisymmetricElasticityAssembler::new(&mesh, &qtable);
+    let stiffness = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+
+    let n = mesh.vertices().len();
+    let axial_translation = DVector::from_fn(2 * n, |i, _| if i % 2 == 1 { 1.0 } else { 0.0 });
+    assert_matrix_eq!(
+        stiffness * &axial_translation,
+        DVector::<f64>::zeros(2 * n),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // A radial translation is not a rigid motion of the body of revolution, because of the hoop strain
+    let element_matrix = assembler.assemble_element_matrix(0).unwrap();
+    let radial_translation = DVector::from_fn(element_matrix.nrows(), |i, _| if i % 2 == 0 { 1.0 } else { 0.0 });
+    assert!(radial_translation.dot(&(element_matrix * &radial_translation)) > 0.0);
+}
+
+#[test]
+fn assembly_fails_for_quadrature_points_on_axis() {
+    let mesh: QuadMesh2d<f64> = create_graded_quad_mesh_2d(&[0.0, 1.0], &[0.0, 1.0]);
+    // The 1-point rule on the left edge of the reference square, which is mapped to the axis
+    let (weights, _) = quadrature::tensor::quadrilateral_gauss::<f64>(1);
+    let qtable = UniformQuadratureTable::from_points_weights_and_data(
+        vec![[-1.0, 0.0].into()],
+        weights,
+        vec![LameParameters { mu: 1.0, lambda: 1.0 }],
+    );
+    let assembler = AxisymmetricElasticityAssembler::new(&mesh, &qtable);
+    assert!(assembler.assemble_element_matrix(0).is_err());
+}
+
+#[test]
+fn thick_walled_cylinder_under_internal_pressure() {
+    // A long cylinder with inner radius a and outer radius b, loaded by the pressure p on its inner surface.
+    // A slice of the cylinder with axial displacement constrained on its top and bottom is in plane strain,
+    // and the radial displacement is given by the Lamé solution
+    //  u_r(r) = (1 + nu) / E * ((1 - 2 nu) C1 r + C2 / r),
+    // with C1 = p a^2 / (b^2 - a^2) and C2 = p a^2 b^2 / (b^2 - a^2)
+    let (a, b, height) = (1.0, 2.0, 0.25);
+    let p = 10.0;
+    let young_poisson = YoungPoisson {
+        young: 1000.0,
+        poisson: 0.3,
+    };
+    let YoungPoisson { young, poisson } = young_poisson;
+    let c1 = p * a * a / (b * b - a * a);
+    let c2 = p * a * a * b * b / (b * b - a * a);
+    let u_r_exact = |r: f64| (1.0 + poisson) / young * ((1.0 - 2.0 * poisson) * c1 * r + c2 / r);
+
+    let mesh: QuadMesh2d<f64> = create_graded_quad_mesh_2d(&linspace(a, b, 32), &linspace(0.0, height, 2));
+    let qtable = quadrature_table(LameParameters::try_from(young_poisson).unwrap());
+    let assembler = AxisymmetricElasticityAssembler::new(&mesh, &qtable);
+    let mut stiffness = CsrAssembler::default().assemble(&assembler).unwrap();
+
+    // The pressure acts on the inner surface, where the surface element is 2 pi a dz. Since the
+    // elements are bilinear and the radius is constant along the surface, each edge of length dz
+    // contributes p 2 pi a dz / 2 to the radial force at each of its vertices
+    let n = mesh.vertices().len();
+    let mut inner_vertices: Vec<_> = (0..n).filter(|&i| mesh.vertices()[i].x == a).collect();
+    inner_vertices.sort_by(|&i, &j| mesh.vertices()[i].y.total_cmp(&mesh.vertices()[j].y));
+    let mut rhs = DVector::zeros(2 * n);
+    for edge in inner_vertices.windows(2) {
+        let dz = mesh.vertices()[edge[1]].y - mesh.vertices()[edge[0]].y;
+        for &i in edge {
+            rhs[2 * i] += p * 2.0 * PI * a * dz / 2.0;
+        }
+    }
+
+    let axially_constrained_dofs = (0..n)
+        .filter(|&i| {
+            let z = mesh.vertices()[i].y;
+            z == 0.0 || z == height
+        })
+        .map(|i| 2 * i + 1)
+        .collect();
+    DirichletBcs::homogeneous(axially_constrained_dofs)
+        .apply_to_system(&mut stiffness, &mut rhs)
+        .unwrap();
+    let u = DMatrix::from(&stiffness).lu().solve(&rhs).unwrap();
+
+    let u_r_max = u_r_exact(a);
+    for (i, vertex) in mesh.vertices().iter().enumerate() {
+        let (u_r, u_z) = (u[2 * i], u[2 * i + 1]);
+        assert!((u_r - u_r_exact(vertex.x)).abs() <= 1e-3 * u_r_max);
+        assert!(u_z.abs() <= 1e-8 * u_r_max);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::unit_tests::lame_parameters;
+use fenris::nalgebra::U3;
+use fenris_solid::calibration::{
+    biaxial_deformation_gradient, calibrate_material_parameters, uniaxial_deformation_gradient, BiaxialSample,
+    CalibrationError, LevenbergMarquardtSettings, StressStrainSample, UniaxialSample,
+};
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial};
+use fenris_solid::HyperelasticMaterial;
+use matrixcompare::assert_scalar_eq;
+
+/// Generates synthetic samples for the given material and parameters.
+fn generate_samples<M>(material: &M, parameters: &LameParameters<f64>) -> Vec<StressStrainSample<f64>>
+where
+    M: HyperelasticMaterial<f64, U3, Parameters = LameParameters<f64>>,
+{
+    let mut samples = Vec::new();
+    for stretch in [0.8, 0.95, 1.02, 1.1, 1.3, 1.5] {
+        let f = uniaxial_deformation_gradient(material, parameters, stretch).unwrap();
+        let p = material.compute_stress_tensor(&f, parameters);
+        samples.push(
+            UniaxialSample {
+                stretch,
+                stress: p[(0, 0)],
+            }
+            .into(),
+        );
+    }
+    for stretches in [[1.1, 1.05], [1.2, 0.9], [0.95, 1.15]] {
+        let f = biaxial_deformation_gradient(material, parameters, stretches).unwrap();
+        let p = material.compute_stress_tensor(&f, parameters);
+        samples.push(
+            BiaxialSample {
+                stretches,
+                stresses: [p[(0, 0)], p[(1, 1)]],
+            }
+            .into(),
+        );
+    }
+    samples
+}
+
+fn assert_recovers_parameters<M>(material: &M)
+where
+    M: HyperelasticMaterial<f64, U3, Parameters = LameParameters<f64>>,
+{
+    let true_parameters = lame_parameters();
+    let samples = generate_samples(material, &true_parameters);
+    let initial_parameters = LameParameters {
+        mu: 200.0,
+        lambda: 400.0,
+    };
+    let report = calibrate_material_parameters(
+        material,
+        &initial_parameters,
+        &samples,
+        &LevenbergMarquardtSettings::default(),
+    )
+    .unwrap();
+
+    assert!(report.converged);
+    assert_eq!(report.parameter_names, vec!["mu", "lambda"]);
+    assert_eq!(
+        report.parameter_values,
+        vec![report.parameters.mu, report.parameters.lambda]
+    );
+    assert_eq!(report.residuals.len(), 6 + 2 * 3);
+    assert!(report.residual_norm <= 1e-8);
+    assert_scalar_eq!(report.parameters.mu, true_parameters.mu, comp = abs, tol = 1e-8);
+    assert_scalar_eq!(report.parameters.lambda, true_parameters.lambda, comp = abs, tol = 1e-8);
+}
+
+#[test]
+fn uniaxial_deformation_gradient_has_zero_lateral_stress() {
+    let parameters = lame_parameters();
+    for stretch in [0.7, 1.0, 1.2, 2.0] {
+        let f = uniaxial_deformation_gradient(&NeoHookeanMaterial, &parameters, stretch).unwrap();
+        let p = NeoHookeanMaterial.compute_stress_tensor(&f, &parameters);
+        assert_eq!(f[(0, 0)], stretch);
+        assert_eq!(f[(1, 1)], f[(2, 2)]);
+        assert_scalar_eq!(p[(1, 1)], 0.0, comp = abs, tol = 1e-9);
+        assert_scalar_eq!(p[(2, 2)], 0.0, comp = abs, tol = 1e-9);
+    }
+
+    // For linear elasticity, the lateral strain is -nu times the axial strain
+    let LameParameters { mu, lambda } = parameters;
+    let poisson = lambda / (2.0 * (lambda + mu));
+    let f = uniaxial_deformation_gradient(&LinearElasticMaterial, &parameters, 1.01).unwrap();
+    assert_scalar_eq!(f[(1, 1)], 1.0 - poisson * 0.01, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn biaxial_deformation_gradient_has_zero_out_of_plane_stress() {
+    let parameters = lame_parameters();
+    let f = biaxial_deformation_gradient(&NeoHookeanMaterial, &parameters, [1.3, 0.9]).unwrap();
+    let p = NeoHookeanMaterial.compute_stress_tensor(&f, &parameters);
+    assert_eq!(f[(0, 0)], 1.3);
+    assert_eq!(f[(1, 1)], 0.9);
+    assert_scalar_eq!(p[(2, 2)], 0.0, comp = abs, tol = 1e-9);
+}
+
+#[test]
+fn calibration_recovers_linear_elastic_parameters() {
+    assert_recovers_parameters(&LinearElasticMaterial);
+}
+
+#[test]
+fn calibration_recovers_neo_hookean_parameters() {
+    assert_recovers_parameters(&NeoHookeanMaterial);
+}
+
+#[test]
+fn calibration_without_samples_fails() {
+    let result = calibrate_material_parameters(
+        &NeoHookeanMaterial,
+        &lame_parameters(),
+        &[],
+        &LevenbergMarquardtSettings::default(),
+    );
+    assert_eq!(result.unwrap_err(), CalibrationError::NoSamples);
+}
@@ -0,0 +1,235 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    AggregateElementAssembler, ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementVectorAssembler,
+    UniformQuadratureTable,
+};
+use fenris::geometry::sdf::SdfCircle;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra;
+use fenris::nalgebra::{point, DMatrix, DVector, Vector2, U2};
+use fenris::quadrature;
+use fenris_solid::contact::{BallObstacle, ContactObstacle, PenaltyContactAssembler, PlaneObstacle, SdfObstacle};
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, YoungPoisson};
+use fenris_solid::MaterialEllipticOperator;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// The unit square resting on the rigid half-space y <= 0.
+struct BlockOnPlane {
+    mesh: QuadMesh2d<f64>,
+    obstacle: PlaneObstacle<f64, U2>,
+    bottom_nodes: Vec<usize>,
+    top_nodes: Vec<usize>,
+}
+
+impl BlockOnPlane {
+    fn new() -> Self {
+        let mesh = create_unit_square_uniform_quad_mesh_2d(4);
+        let nodes_at_height = |y: f64| -> Vec<usize> {
+            mesh.vertices()
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.y == y)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        let bottom_nodes = nodes_at_height(0.0);
+        let top_nodes = nodes_at_height(1.0);
+        let obstacle = PlaneObstacle::from_point_and_normal(point![0.0, 0.0], Vector2::y_axis());
+        Self {
+            mesh,
+            obstacle,
+            bottom_nodes,
+            top_nodes,
+        }
+    }
+
+    /// External forces corresponding to a total downward force `load` distributed evenly over the top nodes.
+    fn external_forces(&self, load: f64) -> DVector<f64> {
+        let mut f = DVector::zeros(2 * self.mesh.vertices().len());
+        for &node in &self.top_nodes {
+            f[2 * node + 1] = -load / self.top_nodes.len() as f64;
+        }
+        f
+    }
+
+    /// Solves for static equilibrium with Newton's method, starting from `u`.
+    ///
+    /// The horizontal displacement of the first bottom node is fixed to remove the
+    /// horizontal rigid body mode, which is not constrained by frictionless contact.
+    fn solve_equilibrium(&self, penalty: f64, f_external: &DVector<f64>, mut u: DVector<f64>) -> DVector<f64> {
+        let parameters: LameParameters<f64> = YoungPoisson {
+            young: 100.0,
+            poisson: 0.3,
+        }
+        .try_into()
+        .unwrap();
+        let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+            quadrature::tensor::quadrilateral_gauss(2),
+            parameters,
+        );
+        let operator = MaterialEllipticOperator::new(&LinearElasticMaterial);
+        let fixed_dof = 2 * self.bottom_nodes[0];
+
+        for _ in 0..50 {
+            let elastic = ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(&self.mesh)
+                .with_operator(&operator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build();
+            let contact =
+                PenaltyContactAssembler::new(self.mesh.vertices(), &self.bottom_nodes, &self.obstacle, penalty, &u);
+
+            let vector_assemblers: [&dyn ElementVectorAssembler<f64>; 2] = [&elastic, &contact];
+            let mut residual = VectorAssembler::default()
+                .assemble_vector(&AggregateElementAssembler::from_assemblers(&vector_assemblers))
+                .unwrap()
+                - f_external;
+            residual[fixed_dof] = 0.0;
+            if residual.norm() <= 1e-12 * f_external.norm().max(1.0) {
+                return u;
+            }
+
+            let matrix_assemblers: [&dyn ElementMatrixAssembler<f64>; 2] = [&elastic, &contact];
+            let mut jacobian = DMatrix::from(
+                &CsrAssembler::default()
+                    .assemble(&AggregateElementAssembler::from_assemblers(&matrix_assemblers))
+                    .unwrap(),
+            );
+            jacobian.row_mut(fixed_dof).fill(0.0);
+            jacobian.column_mut(fixed_dof).fill(0.0);
+            jacobian[(fixed_dof, fixed_dof)] = 1.0;
+            u -= jacobian.lu().solve(&residual).unwrap();
+        }
+        panic!("Newton iterations did not converge");
+    }
+}
+
+#[test]
+fn block_pressed_onto_plane_reaches_force_balance() {
+    let block = BlockOnPlane::new();
+    let load = 2.0;
+    let f_external = block.external_forces(load);
+    let num_dofs = f_external.len();
+
+    let mut mean_penetrations = Vec::new();
+    for penalty in [1e3, 1e4] {
+        let u = block.solve_equilibrium(penalty, &f_external, DVector::zeros(num_dofs));
+        let contact =
+            PenaltyContactAssembler::new(block.mesh.vertices(), &block.bottom_nodes, &block.obstacle, penalty, &u);
+
+        // The contact force balances the applied load
+        let total_force = contact.compute_total_contact_force().unwrap();
+        assert_scalar_eq!(total_force.x, 0.0, comp = abs, tol = 1e-10);
+        assert_scalar_eq!(total_force.y, load, comp = abs, tol = 1e-10 * load);
+
+        let state = contact.compute_contact_state();
+        for node in 0..block.mesh.vertices().len() {
+            let is_bottom_node = block.bottom_nodes.contains(&node);
+            assert_eq!(state.in_contact[node], is_bottom_node);
+            assert_eq!(state.gap[node] < 0.0, is_bottom_node);
+            if is_bottom_node {
+                assert_eq!(state.penetration[node], -state.gap[node]);
+            } else {
+                assert_eq!(state.penetration[node], 0.0);
+            }
+        }
+
+        let total_penetration: f64 = block
+            .bottom_nodes
+            .iter()
+            .map(|&node| state.penetration[node])
+            .sum();
+        mean_penetrations.push(total_penetration / block.bottom_nodes.len() as f64);
+
+        FiniteElementMeshDataSetBuilder::from_mesh(&block.mesh)
+            .with_point_vector_attributes("u", 2, u.as_slice())
+            .with_point_scalar_attributes("gap", 1, &state.gap)
+            .with_point_scalar_attributes("penetration", 1, &state.penetration)
+            .with_point_scalar_attributes("contact", 1, &state.contact_indicator())
+            .try_build()
+            .unwrap();
+    }
+
+    // Since the sum of the penalty forces balances the load, the mean penetration is exactly
+    // inversely proportional to the penalty stiffness
+    assert_scalar_eq!(
+        mean_penetrations[0] / mean_penetrations[1],
+        10.0,
+        comp = abs,
+        tol = 1e-8
+    );
+}
+
+#[test]
+fn releasing_load_removes_contact_force() {
+    let block = BlockOnPlane::new();
+    let penalty = 1e3;
+    let num_dofs = 2 * block.mesh.vertices().len();
+    let u_loaded = block.solve_equilibrium(penalty, &block.external_forces(2.0), DVector::zeros(num_dofs));
+    let u = block.solve_equilibrium(penalty, &DVector::zeros(num_dofs), u_loaded);
+
+    let contact =
+        PenaltyContactAssembler::new(block.mesh.vertices(), &block.bottom_nodes, &block.obstacle, penalty, &u);
+    let total_force = contact.compute_total_contact_force().unwrap();
+    assert_scalar_eq!(total_force.norm(), 0.0, comp = abs, tol = 1e-10);
+    assert_matrix_eq!(u, DVector::zeros(num_dofs), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn contact_assembler_is_consistent_with_finite_differences() {
+    // A single node penetrating a disk obstacle
+    let vertices = [point![0.3, 0.1]];
+    let obstacle = BallObstacle {
+        center: point![0.0, 0.0],
+        radius: 0.5,
+    };
+    let penalty = 10.0;
+    let u = DVector::from_column_slice(&[0.05, -0.02]);
+    let contact = PenaltyContactAssembler::new(&vertices, &[0], &obstacle, penalty, &u);
+
+    // The gradient of the penalty energy kappa/2 g^2
+    let energy = |u: &DVector<f64>| {
+        let g = obstacle.signed_distance(&(vertices[0] + Vector2::new(u[0], u[1])));
+        0.5 * penalty * g.min(0.0).powi(2)
+    };
+    let h = 1e-6;
+    let gradient_fd = DVector::from_fn(2, |i, _| {
+        let mut u_plus = u.clone();
+        let mut u_minus = u.clone();
+        u_plus[i] += h;
+        u_minus[i] -= h;
+        (energy(&u_plus) - energy(&u_minus)) / (2.0 * h)
+    });
+    let gradient = contact.assemble_element_vector(0).unwrap();
+    assert_matrix_eq!(gradient, gradient_fd, comp = abs, tol = 1e-8);
+
+    // The stiffness neglects the curvature of the obstacle, so it is the outer product of the normal
+    let normal = obstacle
+        .signed_distance_gradient(&(vertices[0] + Vector2::new(u[0], u[1])))
+        .unwrap();
+    let stiffness = contact.assemble_element_matrix(0).unwrap();
+    assert_matrix_eq!(
+        stiffness,
+        penalty * &normal * normal.transpose(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // Obstacles given by existing 2D signed distance functions behave identically
+    let sdf_obstacle = SdfObstacle(SdfCircle {
+        radius: 0.5,
+        center: Vector2::zeros(),
+    });
+    let sdf_contact = PenaltyContactAssembler::new(&vertices, &[0], &sdf_obstacle, penalty, &u);
+    assert_matrix_eq!(sdf_contact.assemble_element_vector(0).unwrap(), gradient, comp = float);
+
+    // Nodes outside the obstacle do not contribute
+    let u_outside = DVector::from_column_slice(&[0.5, 0.0]);
+    let contact = PenaltyContactAssembler::new(&vertices, &[0], &obstacle, penalty, &u_outside);
+    assert_eq!(contact.assemble_element_vector(0).unwrap(), DVector::zeros(2));
+    assert_eq!(contact.assemble_element_matrix(0).unwrap(), DMatrix::zeros(2, 2));
+    assert!(!contact.compute_contact_state().in_contact[0]);
+}
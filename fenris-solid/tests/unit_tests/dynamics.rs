@@ -0,0 +1,210 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::assembly::DirichletBcs;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
+use fenris_solid::model::dynamics::{DynamicState, NewmarkIntegrator, SymplecticEuler};
+use fenris_solid::model::QuasiStaticModel;
+
+fn solve_dense(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> eyre::Result<DVector<f64>> {
+    DMatrix::from(matrix)
+        .lu()
+        .solve(rhs)
+        .ok_or_else(|| eyre::eyre!("matrix is singular"))
+}
+
+/// A single Quad4 element on the unit square, clamped on the left side.
+struct Oscillator {
+    mesh: QuadMesh2d<f64>,
+    qtable: UniformQuadratureTable<f64, U2, LameParameters<f64>>,
+    bcs: DirichletBcs<f64>,
+    mass: CsrMatrix<f64>,
+}
+
+impl Oscillator {
+    fn new() -> Self {
+        let mesh = create_unit_square_uniform_quad_mesh_2d(1);
+        let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+            quadrature::tensor::quadrilateral_gauss(2),
+            LameParameters { mu: 2.0, lambda: 3.0 },
+        );
+        let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+            quadrature::tensor::quadrilateral_gauss(2),
+            Density(1.0),
+        );
+        let mass = CsrAssembler::default()
+            .assemble(
+                &ElementMassAssembler::with_solution_dim(2)
+                    .with_space(&mesh)
+                    .with_quadrature_table(&mass_qtable),
+            )
+            .unwrap();
+        let bcs = DirichletBcs::from_mesh_boundary(&mesh, 2, &[0, 1], |x| x.x == 0.0, |_, _| 0.0);
+        Self {
+            mesh,
+            qtable,
+            bcs,
+            mass,
+        }
+    }
+
+    fn model(
+        &self,
+    ) -> QuasiStaticModel<
+        f64,
+        QuadMesh2d<f64>,
+        LinearElasticMaterial,
+        UniformQuadratureTable<f64, U2, LameParameters<f64>>,
+    > {
+        let num_dofs = 2 * self.mesh.vertices().len();
+        QuasiStaticModel::new(
+            &self.mesh,
+            &LinearElasticMaterial,
+            &self.qtable,
+            self.bcs.clone(),
+            DVector::zeros(num_dofs),
+        )
+        .unwrap()
+    }
+
+    /// The initial state, in which the free nodes are displaced and at rest.
+    fn initial_state(&self) -> DynamicState<f64> {
+        let mut state = DynamicState::zeros(2 * self.mesh.vertices().len());
+        for (i, x) in self.mesh.vertices().iter().enumerate() {
+            if x.x == 1.0 {
+                state.u[2 * i] = 0.01;
+                state.u[2 * i + 1] = -0.02 * x.y;
+            }
+        }
+        state
+    }
+
+    /// The largest angular frequency of the system with the given (diagonal) mass matrix.
+    fn max_angular_frequency(&self, lumped_mass: &DVector<f64>) -> f64 {
+        let stiffness = DMatrix::from(
+            &self
+                .model()
+                .assemble_tangent(&DVector::zeros(lumped_mass.len()))
+                .unwrap(),
+        );
+        let free_dofs: Vec<_> = (0..lumped_mass.len())
+            .filter(|dof| !self.bcs.dofs().contains(dof))
+            .collect();
+        let n = free_dofs.len();
+        let scaled = DMatrix::from_fn(n, n, |i, j| {
+            let (i, j) = (free_dofs[i], free_dofs[j]);
+            stiffness[(i, j)] / (lumped_mass[i] * lumped_mass[j]).sqrt()
+        });
+        scaled.symmetric_eigenvalues().max().sqrt()
+    }
+}
+
+fn total_energy(stiffness: &CsrMatrix<f64>, mass: &CsrMatrix<f64>, state: &DynamicState<f64>) -> f64 {
+    0.5 * state.v.dot(&(mass * &state.v)) + 0.5 * state.u.dot(&(stiffness * &state.u))
+}
+
+fn total_energy_lumped(stiffness: &CsrMatrix<f64>, lumped_mass: &DVector<f64>, state: &DynamicState<f64>) -> f64 {
+    0.5 * state.v.component_mul(&state.v).dot(lumped_mass) + 0.5 * state.u.dot(&(stiffness * &state.u))
+}
+
+#[test]
+fn symplectic_euler_energy_error_is_first_order_and_bounded() {
+    let oscillator = Oscillator::new();
+    let model = oscillator.model();
+    let stiffness = model
+        .assemble_tangent(&DVector::zeros(model.num_dofs()))
+        .unwrap();
+    let lumped_mass = SymplecticEuler::new(&oscillator.mass, oscillator.bcs.clone(), 1.0)
+        .unwrap()
+        .lumped_mass()
+        .clone();
+    let critical_dt = 2.0 / oscillator.max_angular_frequency(&lumped_mass);
+
+    let max_energy_error = |dt_fraction: f64| {
+        let integrator =
+            SymplecticEuler::new(&oscillator.mass, oscillator.bcs.clone(), dt_fraction * critical_dt).unwrap();
+        let mut state = oscillator.initial_state();
+        let initial_energy = total_energy_lumped(&stiffness, &lumped_mass, &state);
+        let mut max_error: f64 = 0.0;
+        // Integrate over the same time interval for every time step
+        for _ in 0..(20.0 / dt_fraction) as usize {
+            integrator.step(&model, &mut state).unwrap();
+            let energy = total_energy_lumped(&stiffness, &lumped_mass, &state);
+            max_error = max_error.max((energy - initial_energy).abs() / initial_energy);
+        }
+        max_error
+    };
+
+    let error_coarse = max_energy_error(0.1);
+    let error_fine = max_energy_error(0.05);
+    assert!(error_coarse < 0.5, "Energy error {} is not bounded", error_coarse);
+    let ratio = error_coarse / error_fine;
+    assert!(
+        ratio > 1.6 && ratio < 2.4,
+        "Energy error ratio {} is not first order",
+        ratio
+    );
+}
+
+#[test]
+fn newmark_average_acceleration_is_stable_for_large_time_steps() {
+    let oscillator = Oscillator::new();
+    let model = oscillator.model();
+    let stiffness = model
+        .assemble_tangent(&DVector::zeros(model.num_dofs()))
+        .unwrap();
+    let explicit = SymplecticEuler::new(&oscillator.mass, oscillator.bcs.clone(), 1.0).unwrap();
+    let critical_dt = 2.0 / oscillator.max_angular_frequency(explicit.lumped_mass());
+
+    // The average acceleration method conserves energy for linear systems
+    let dt = 100.0 * critical_dt;
+    let newmark = NewmarkIntegrator::new(oscillator.mass.clone(), oscillator.bcs.clone(), dt);
+    let mut state = oscillator.initial_state();
+    newmark
+        .compute_consistent_acceleration(&model, &mut state, solve_dense)
+        .unwrap();
+    let initial_energy = total_energy(&stiffness, &oscillator.mass, &state);
+    for _ in 0..200 {
+        let iterations = newmark.step(&model, &mut state, solve_dense).unwrap();
+        assert!(iterations <= 2);
+        let energy = total_energy(&stiffness, &oscillator.mass, &state);
+        assert!((energy - initial_energy).abs() <= 1e-8 * initial_energy);
+    }
+
+    // Numerical damping with gamma > 1/2 dissipates energy, but remains stable. Since the motion
+    // decays to the level of round-off errors, we also need an absolute tolerance
+    let damped = NewmarkIntegrator::new(oscillator.mass.clone(), oscillator.bcs.clone(), dt)
+        .with_parameters(0.3025, 0.6)
+        .with_absolute_tolerance(1e-12);
+    let mut state = oscillator.initial_state();
+    damped
+        .compute_consistent_acceleration(&model, &mut state, solve_dense)
+        .unwrap();
+    for _ in 0..200 {
+        damped.step(&model, &mut state, solve_dense).unwrap();
+    }
+    assert!(total_energy(&stiffness, &oscillator.mass, &state) < initial_energy);
+
+    // In contrast, the explicit scheme blows up beyond the critical time step
+    let explicit = SymplecticEuler::new(&oscillator.mass, oscillator.bcs.clone(), 1.5 * critical_dt).unwrap();
+    let mut state = oscillator.initial_state();
+    for _ in 0..50 {
+        explicit.step(&model, &mut state).unwrap();
+    }
+    assert!(state.u.amax() > 1e3);
+}
+
+#[test]
+fn dynamic_state_serde_round_trip() {
+    let mut state = DynamicState::zeros(3);
+    state.u[0] = 1.0;
+    state.v[1] = -2.5;
+    state.a[2] = 1e-300;
+    let json = serde_json::to_string(&state).unwrap();
+    let deserialized: DynamicState<f64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, state);
+}
@@ -0,0 +1,97 @@
+use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d};
+use fenris::nalgebra::{Const, Matrix1, SMatrix, SVector};
+use fenris_solid::invariants::{
+    determinant, determinant_contraction, determinant_gradient, first_invariant, first_invariant_contraction,
+    first_invariant_gradient, second_invariant, second_invariant_contraction, second_invariant_gradient,
+};
+use fenris_solid::PhysicalDim;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+type Invariant<const D: usize> = fn(&SMatrix<f64, D, D>) -> f64;
+type Gradient<const D: usize> = fn(&SMatrix<f64, D, D>) -> SMatrix<f64, D, D>;
+type Contraction<const D: usize> = fn(&SMatrix<f64, D, D>, &SVector<f64, D>, &SVector<f64, D>) -> SMatrix<f64, D, D>;
+
+/// Checks the gradient and the contraction of an invariant against central finite differences.
+#[allow(non_snake_case)]
+fn assert_derivatives_match_finite_differences<const D: usize>(
+    F: &SMatrix<f64, D, D>,
+    invariant: Invariant<D>,
+    gradient: Gradient<D>,
+    contraction: Contraction<D>,
+) {
+    let h = 1e-6;
+    let perturbed = |i: usize, j: usize, delta: f64| {
+        let mut F = *F;
+        F[(i, j)] += delta;
+        F
+    };
+
+    let G = gradient(F);
+    let G_fd = SMatrix::<f64, D, D>::from_fn(|i, j| {
+        (invariant(&perturbed(i, j, h)) - invariant(&perturbed(i, j, -h))) / (2.0 * h)
+    });
+    assert_matrix_eq!(G, G_fd, comp = abs, tol = 1e-6 * G.amax().max(1.0));
+
+    let a = SVector::<f64, D>::from_fn(|i, _| 1.0 + i as f64);
+    let b = SVector::<f64, D>::from_fn(|i, _| 2.0 - 3.0 * i as f64);
+    let C = contraction(F, &a, &b);
+    // C_ij = a_k (d G_ik / d F_jm) b_m
+    let mut C_fd = SMatrix::<f64, D, D>::zeros();
+    for j in 0..D {
+        for m in 0..D {
+            let dG_dFjm = (gradient(&perturbed(j, m, h)) - gradient(&perturbed(j, m, -h))) / (2.0 * h);
+            let mut column = C_fd.column_mut(j);
+            column += dG_dFjm * a * b[m];
+        }
+    }
+    assert_matrix_eq!(C, C_fd, comp = abs, tol = 1e-6 * C.amax().max(1.0));
+}
+
+#[allow(non_snake_case)]
+fn assert_invariant_derivatives<const D: usize>(F: &SMatrix<f64, D, D>)
+where
+    Const<D>: PhysicalDim,
+{
+    assert_derivatives_match_finite_differences(
+        F,
+        first_invariant,
+        first_invariant_gradient,
+        first_invariant_contraction,
+    );
+    assert_derivatives_match_finite_differences(
+        F,
+        second_invariant,
+        second_invariant_gradient,
+        second_invariant_contraction,
+    );
+    assert_derivatives_match_finite_differences(F, determinant, determinant_gradient, determinant_contraction);
+}
+
+#[test]
+fn invariant_derivatives_match_finite_differences() {
+    assert_invariant_derivatives(&Matrix1::new(1.5));
+    assert_invariant_derivatives(&deformation_gradient_2d());
+    assert_invariant_derivatives(&deformation_gradient_3d());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn invariants_agree_with_right_cauchy_green_tensor() {
+    let F = deformation_gradient_3d();
+    let C = F.transpose() * F;
+    assert_scalar_eq!(first_invariant(&F), C.trace(), comp = abs, tol = 1e-12);
+    // I2 is the sum of the principal minors of C
+    let minors = C[(0, 0)] * C[(1, 1)] - C[(0, 1)] * C[(1, 0)] + C[(0, 0)] * C[(2, 2)] - C[(0, 2)] * C[(2, 0)]
+        + C[(1, 1)] * C[(2, 2)]
+        - C[(1, 2)] * C[(2, 1)];
+    assert_scalar_eq!(second_invariant(&F), minors, comp = abs, tol = 1e-9);
+    assert_scalar_eq!(determinant(&F).powi(2), C.determinant(), comp = abs, tol = 1e-9);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn determinant_gradient_is_cofactor_matrix() {
+    let F = deformation_gradient_3d();
+    let expected = F.try_inverse().unwrap().transpose() * F.determinant();
+    assert_matrix_eq!(determinant_gradient(&F), expected, comp = abs, tol = 1e-12);
+}
@@ -1,7 +1,9 @@
 use fenris::nalgebra;
-use fenris::nalgebra::{matrix, vector, Matrix3, Rotation3};
-use fenris_solid::{log_det_F, u_grad_from_F};
-use matrixcompare::assert_scalar_eq;
+use fenris::nalgebra::{matrix, vector, Const, Matrix1, Matrix2, Matrix3, Rotation3, SMatrix, SVector, Vector3};
+use fenris_solid::proptest::{deformation_gradient_near_identity, deformation_gradient_positive_det};
+use fenris_solid::{log_det_F, log_det_F_contraction, log_det_F_gradient, u_grad_from_F, PhysicalDim};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use proptest::prelude::*;
 
 #[allow(non_snake_case)]
 fn arbitrary_F() -> Matrix3<f64> {
@@ -48,3 +50,158 @@ fn log_det_negative_determinant() {
     let du_dX = u_grad_from_F(&F).transpose();
     assert!(log_det_F(&du_dX).is_none());
 }
+
+#[test]
+#[allow(non_snake_case)]
+fn log_det_F_1d() {
+    let du_dX = Matrix1::new(0.5);
+    assert_scalar_eq!(log_det_F(&du_dX).unwrap(), 1.5f64.ln(), comp = abs, tol = 1e-14);
+    assert!(log_det_F(&Matrix1::new(-1.0)).is_none());
+    assert!(log_det_F(&Matrix1::new(-1.5)).is_none());
+}
+
+#[allow(non_snake_case)]
+fn assert_log_det_F_derivatives_agree_with_inverse<const D: usize>(F: &SMatrix<f64, D, D>)
+where
+    Const<D>: PhysicalDim,
+{
+    let du_dX = F - SMatrix::<f64, D, D>::identity();
+    let F_inv_T = F.try_inverse().unwrap().transpose();
+
+    let gradient = SMatrix::<f64, D, D>::identity() + log_det_F_gradient(&du_dX).unwrap();
+    assert_matrix_eq!(gradient, F_inv_T, comp = abs, tol = 1e-12);
+
+    let a = SVector::<f64, D>::from_fn(|i, _| 1.0 + i as f64);
+    let b = SVector::<f64, D>::from_fn(|i, _| 2.0 - 3.0 * i as f64);
+    let contraction = log_det_F_contraction(&du_dX, &a, &b).unwrap();
+    let expected = -(F_inv_T * b) * (F_inv_T * a).transpose();
+    assert_matrix_eq!(contraction, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn log_det_F_derivatives_agree_with_inverse() {
+    assert_log_det_F_derivatives_agree_with_inverse(&Matrix1::new(1.3));
+    assert_log_det_F_derivatives_agree_with_inverse(&matrix![1.2, -0.3; 0.4, 0.9]);
+    assert_log_det_F_derivatives_agree_with_inverse(&arbitrary_F());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn log_det_F_derivatives_agree_with_finite_differences() {
+    let F = arbitrary_F();
+    let du_dX = F - Matrix3::identity();
+    let h = 1e-6;
+
+    let gradient = Matrix3::identity() + log_det_F_gradient(&du_dX).unwrap();
+    let gradient_fd = Matrix3::from_fn(|i, j| {
+        let mut U_plus = du_dX;
+        let mut U_minus = du_dX;
+        U_plus[(i, j)] += h;
+        U_minus[(i, j)] -= h;
+        (log_det_F(&U_plus).unwrap() - log_det_F(&U_minus).unwrap()) / (2.0 * h)
+    });
+    assert_matrix_eq!(gradient, gradient_fd, comp = abs, tol = 1e-8);
+
+    // The contraction with the standard basis vectors e_k and e_m gives the derivatives
+    // of the gradient entries (i, k) with respect to F_jm
+    for k in 0..3 {
+        for m in 0..3 {
+            let contraction = log_det_F_contraction(&du_dX, &Vector3::ith(k, 1.0), &Vector3::ith(m, 1.0)).unwrap();
+            let mut U_plus = du_dX;
+            let mut U_minus = du_dX;
+            let contraction_fd = Matrix3::from_fn(|i, j| {
+                U_plus[(j, m)] += h;
+                U_minus[(j, m)] -= h;
+                let G_plus = log_det_F_gradient(&U_plus).unwrap();
+                let G_minus = log_det_F_gradient(&U_minus).unwrap();
+                U_plus[(j, m)] -= h;
+                U_minus[(j, m)] += h;
+                (G_plus[(i, k)] - G_minus[(i, k)]) / (2.0 * h)
+            });
+            assert_matrix_eq!(contraction, contraction_fd, comp = abs, tol = 1e-8);
+        }
+    }
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn log_det_F_gradient_is_accurate_for_small_displacement_gradients() {
+    let U2: Matrix2<f64> = 1e-12 * matrix![0.3, -0.7; 0.9, 0.4];
+    let U3 = 1e-12 * (arbitrary_F() - Matrix3::identity());
+
+    // For tiny U, the Neumann series F^{-T} - I = -U^T + (U^T)^2 - ... converges very rapidly
+    let expected2 = -U2.transpose() + U2.transpose() * U2.transpose();
+    let expected3 = -U3.transpose() + U3.transpose() * U3.transpose();
+
+    let relative_error2 = |G: Matrix2<f64>| (G - expected2).amax() / expected2.amax();
+    let relative_error3 = |G: Matrix3<f64>| (G - expected3).amax() / expected3.amax();
+
+    let naive2 = (Matrix2::identity() + U2)
+        .try_inverse()
+        .unwrap()
+        .transpose()
+        - Matrix2::identity();
+    let naive3 = (Matrix3::identity() + U3)
+        .try_inverse()
+        .unwrap()
+        .transpose()
+        - Matrix3::identity();
+    assert!(relative_error2(log_det_F_gradient(&U2).unwrap()) < 1e-13);
+    assert!(relative_error3(log_det_F_gradient(&U3).unwrap()) < 1e-13);
+    // Forming F discards most of the significant digits of U
+    assert!(relative_error2(naive2) > 1e-8);
+    assert!(relative_error3(naive3) > 1e-8);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn log_det_F_derivatives_negative_determinant() {
+    let du_dX = matrix![-2.0, 0.0; 0.0, 0.0];
+    assert!(log_det_F_gradient(&du_dX).is_none());
+    assert!(log_det_F_contraction(&du_dX, &vector![1.0, 0.0], &vector![0.0, 1.0]).is_none());
+    assert!(log_det_F_gradient(&Matrix1::new(-1.0)).is_none());
+}
+
+#[allow(non_snake_case)]
+fn check_log_det_F_against_naive<const D: usize>(F: &SMatrix<f64, D, D>, tol: f64) -> Result<(), TestCaseError>
+where
+    Const<D>: PhysicalDim,
+{
+    let det = F.determinant();
+    prop_assert!(det > 0.0);
+    let log_det = log_det_F(&(F - SMatrix::<f64, D, D>::identity())).unwrap();
+    prop_assert!(
+        (log_det - det.ln()).abs() <= tol,
+        "log_det_F: {}, naive: {}",
+        log_det,
+        det.ln()
+    );
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    #[allow(non_snake_case)]
+    fn log_det_F_agrees_with_naive_log_determinant_2d(
+        F_near_identity in deformation_gradient_near_identity::<Const<2>>(0.4),
+        F in deformation_gradient_positive_det::<Const<2>>(0.1, 10.0)
+    ) {
+        check_log_det_F_against_naive(&F_near_identity, 1e-13)?;
+        prop_assert!(F.determinant() >= 0.1 * (1.0 - 1e-12));
+        prop_assert!(F.singular_values().max() <= 10.0 * (1.0 + 1e-12));
+        check_log_det_F_against_naive(&F, 1e-12)?;
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn log_det_F_agrees_with_naive_log_determinant_3d(
+        F_near_identity in deformation_gradient_near_identity::<Const<3>>(0.3),
+        F in deformation_gradient_positive_det::<Const<3>>(0.1, 10.0)
+    ) {
+        check_log_det_F_against_naive(&F_near_identity, 1e-13)?;
+        prop_assert!(F.determinant() >= 0.1 * (1.0 - 1e-12));
+        prop_assert!(F.singular_values().max() <= 10.0 * (1.0 + 1e-12));
+        check_log_det_F_against_naive(&F, 1e-12)?;
+    }
+}
@@ -1,10 +1,19 @@
 use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 
 use fenris::nalgebra;
-use fenris::nalgebra::{dvector, vector, DMatrix, DMatrixViewMut, DVectorView, Matrix2, Matrix3, SMatrix, SVector};
-use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial, StVKMaterial, YoungPoisson};
+use fenris::nalgebra::{
+    dvector, vector, Const, DMatrix, DMatrixViewMut, DVectorView, DimMin, Matrix2, Matrix3, SMatrix, SVector,
+};
+use fenris_solid::autodiff::AutodiffMaterial;
+use fenris_solid::materials::{
+    BulkShearParameters, CorotatedLinearElasticMaterial, LameParameters, LinearElasticMaterial, NeoHookeanMaterial,
+    PlaneStrain, PlaneStress, StVKMaterial, StableNeoHookeanMaterial, TransverselyIsotropicMaterial,
+    TransverselyIsotropicParameters, YoungPoisson,
+};
 use fenris_solid::HyperelasticMaterial;
+use proptest::prelude::*;
 
+use crate::unit_tests::autodiff::NeoHookeanEnergy;
 use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters};
 
 /// Approximates stress tensor using central Finite Differences with step size `h`.
@@ -77,10 +86,98 @@ fn lame_from_young_poisson() {
         young: 1e3,
         poisson: 0.3,
     };
-    let lame = LameParameters::from(young_poisson);
+    let lame = LameParameters::try_from(young_poisson).unwrap();
 
     assert_scalar_eq!(lame.mu, 384.6153846153846, comp = float);
     assert_scalar_eq!(lame.lambda, 576.9230769230769, comp = float);
+
+    let bulk_shear = BulkShearParameters::try_from(young_poisson).unwrap();
+    assert_scalar_eq!(bulk_shear.kappa, 833.3333333333333, comp = float);
+    assert_scalar_eq!(bulk_shear.mu, lame.mu, comp = float);
+}
+
+#[test]
+fn elastic_parameter_conversions_reject_invalid_parameters() {
+    let young_poisson = |young, poisson| YoungPoisson { young, poisson };
+    for invalid in [
+        young_poisson(1e3, 0.5),
+        young_poisson(1e3, -1.0),
+        young_poisson(1e3, 0.7),
+        young_poisson(0.0, 0.3),
+        young_poisson(-1e3, 0.3),
+        young_poisson(f64::NAN, 0.3),
+        young_poisson(1e3, f64::NAN),
+    ] {
+        assert!(LameParameters::try_from(invalid).is_err());
+        assert!(BulkShearParameters::try_from(invalid).is_err());
+    }
+
+    let lame = |mu, lambda| LameParameters { mu, lambda };
+    // lambda > -2 mu / 3 is required for a positive bulk modulus
+    for invalid in [
+        lame(0.0, 1.0),
+        lame(-1.0, 1.0),
+        lame(3.0, -2.0),
+        lame(3.0, f64::INFINITY),
+    ] {
+        assert!(YoungPoisson::try_from(invalid).is_err());
+        assert!(BulkShearParameters::try_from(invalid).is_err());
+    }
+    assert!(YoungPoisson::try_from(lame(3.0, -1.9)).is_ok());
+
+    let bulk_shear = |kappa, mu| BulkShearParameters { kappa, mu };
+    for invalid in [
+        bulk_shear(0.0, 1.0),
+        bulk_shear(1.0, -1.0),
+        bulk_shear(f64::INFINITY, 1.0),
+    ] {
+        assert!(LameParameters::try_from(invalid).is_err());
+        assert!(YoungPoisson::try_from(invalid).is_err());
+    }
+}
+
+#[test]
+fn plane_stress_lame_parameters() {
+    // For plane stress, the effective lambda is E nu / (1 - nu^2)
+    let young_poisson = YoungPoisson {
+        young: 1e3,
+        poisson: 0.3,
+    };
+    let lame = LameParameters::try_from(young_poisson).unwrap();
+    let plane_stress = lame.to_plane_stress();
+    assert_eq!(plane_stress.mu, lame.mu);
+    assert_scalar_eq!(plane_stress.lambda, 1e3 * 0.3 / (1.0 - 0.3 * 0.3), comp = float);
+}
+
+fn relative_error(a: f64, b: f64) -> f64 {
+    (a - b).abs() / b.abs().max(f64::MIN_POSITIVE)
+}
+
+proptest! {
+    #[test]
+    fn elastic_parameter_conversions_are_mutually_consistent(young in 1e-3 .. 1e6, poisson in -0.99 .. 0.49) {
+        let tol = 1e-12;
+        let young_poisson = YoungPoisson { young, poisson };
+        let lame = LameParameters::try_from(young_poisson).unwrap();
+        let bulk_shear = BulkShearParameters::try_from(young_poisson).unwrap();
+
+        let young_poisson_from_lame = YoungPoisson::try_from(lame).unwrap();
+        prop_assert!(relative_error(young_poisson_from_lame.young, young) <= tol);
+        prop_assert!((young_poisson_from_lame.poisson - poisson).abs() <= tol);
+
+        let young_poisson_from_bulk_shear = YoungPoisson::try_from(bulk_shear).unwrap();
+        prop_assert!(relative_error(young_poisson_from_bulk_shear.young, young) <= tol);
+        prop_assert!((young_poisson_from_bulk_shear.poisson - poisson).abs() <= tol);
+
+        let bulk_shear_from_lame = BulkShearParameters::try_from(lame).unwrap();
+        prop_assert!(relative_error(bulk_shear_from_lame.kappa, bulk_shear.kappa) <= tol);
+        prop_assert!(relative_error(bulk_shear_from_lame.mu, bulk_shear.mu) <= tol);
+
+        // lambda may be arbitrarily close to zero, so we measure its error relative to mu
+        let lame_from_bulk_shear = LameParameters::try_from(bulk_shear).unwrap();
+        prop_assert!(relative_error(lame_from_bulk_shear.mu, lame.mu) <= tol);
+        prop_assert!((lame_from_bulk_shear.lambda - lame.lambda).abs() <= tol * lame.mu);
+    }
 }
 
 /// Uses finite differences to check that the stress tensor is the derivative of the energy
@@ -332,6 +429,42 @@ test_contraction_is_consistent_with_tensor!(
 test_multi_contraction_consistency!(dim = 2, StVKMaterial, stvk_multi_contraction_consistency_2d);
 test_multi_contraction_consistency!(dim = 3, StVKMaterial, stvk_multi_contraction_consistency_3d);
 
+/// Checks that the StVK stress agrees with the linear elastic stress to second order for small strains.
+#[allow(non_snake_case)]
+fn assert_stvk_stress_agrees_with_linear_elastic_for_small_strains<const D: usize>(G: SMatrix<f64, D, D>)
+where
+    StVKMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+    LinearElasticMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+{
+    let lame = lame_parameters();
+    let stress_difference = |eps: f64| {
+        let F = SMatrix::identity() + G * eps;
+        let P_stvk = StVKMaterial.compute_stress_tensor(&F, &lame);
+        let P_linear = LinearElasticMaterial.compute_stress_tensor(&F, &lame);
+        (P_stvk - P_linear).norm()
+    };
+
+    let differences: Vec<_> = [1e-3, 1e-4, 1e-5]
+        .into_iter()
+        .map(stress_difference)
+        .collect();
+    for pair in differences.windows(2) {
+        let rate = (pair[0] / pair[1]).log10();
+        assert!(
+            (rate - 2.0).abs() < 0.05,
+            "Expected second order agreement, got rate {} (differences {:?})",
+            rate,
+            differences
+        );
+    }
+}
+
+#[test]
+fn stvk_stress_agrees_with_linear_elastic_for_small_strains() {
+    assert_stvk_stress_agrees_with_linear_elastic_for_small_strains(deformation_gradient_2d());
+    assert_stvk_stress_agrees_with_linear_elastic_for_small_strains(deformation_gradient_3d());
+}
+
 #[test]
 fn neo_hookean_strain_energy_2d() {
     let lame = lame_parameters();
@@ -397,3 +530,626 @@ fn neo_hookean_zero_for_rest_state_3d() {
     let energy = NeoHookeanMaterial.compute_energy_density(&Matrix3::identity(), &lame);
     assert_scalar_eq!(energy, 0.0, comp = float);
 }
+
+/// Checks that the Neo-Hookean stress at a tiny displacement gradient agrees with the linearized stress,
+/// which requires avoiding cancellation in $\log J$ and $\vec F - \vec F^{-T}$.
+#[allow(non_snake_case)]
+fn assert_neo_hookean_stress_accurate_for_small_strains<const D: usize>(G: SMatrix<f64, D, D>)
+where
+    NeoHookeanMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+{
+    let lame = lame_parameters();
+    let eps = 1e-12;
+    let u_grad = G.transpose() * eps;
+    let U = u_grad.transpose();
+    let expected_stress = (U + U.transpose()) * lame.mu + SMatrix::identity() * (lame.lambda * U.trace());
+    let stress = NeoHookeanMaterial.compute_stress_tensor_du(&u_grad, &lame);
+    assert_matrix_eq!(stress, expected_stress, comp = abs, tol = 1e-8 * expected_stress.amax());
+}
+
+#[test]
+fn neo_hookean_stress_accurate_for_small_strains() {
+    assert_neo_hookean_stress_accurate_for_small_strains(deformation_gradient_2d());
+    assert_neo_hookean_stress_accurate_for_small_strains(deformation_gradient_3d());
+}
+
+/// Checks that the energy is infinite and that the stress and contractions are NaN (without panicking)
+/// for a deformation gradient with negative determinant.
+#[allow(non_snake_case)]
+fn assert_neo_hookean_undefined_for_inverted_deformation<const D: usize>(F: SMatrix<f64, D, D>)
+where
+    Const<D>: DimMin<Const<D>, Output = Const<D>>,
+    NeoHookeanMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+{
+    assert!(F.determinant() < 0.0);
+    let lame = lame_parameters();
+    let a = SVector::<f64, D>::repeat(1.0);
+    let b = SVector::<f64, D>::from_fn(|i, _| i as f64 - 1.0);
+
+    let energy = NeoHookeanMaterial.compute_energy_density(&F, &lame);
+    assert!(energy.is_infinite() && energy > 0.0);
+    let stress = NeoHookeanMaterial.compute_stress_tensor(&F, &lame);
+    assert!(stress.iter().all(|p_ij| p_ij.is_nan()));
+    let contraction = NeoHookeanMaterial.compute_stress_contraction(&F, &a, &b, &lame);
+    assert!(contraction.iter().all(|c_ij| c_ij.is_nan()));
+
+    let mut output = DMatrix::zeros(D, D);
+    NeoHookeanMaterial.accumulate_stress_contractions_into(
+        DMatrixViewMut::from(&mut output),
+        2.0,
+        &F,
+        DVectorView::from(&a),
+        DVectorView::from(&b),
+        &lame,
+    );
+    // Only the upper triangle is accumulated
+    for i in 0..D {
+        for j in i..D {
+            assert!(output[(i, j)].is_nan());
+        }
+    }
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn neo_hookean_undefined_for_inverted_deformation() {
+    // Swapping two rows flips the sign of the determinant
+    let mut F = deformation_gradient_2d();
+    F.swap_rows(0, 1);
+    assert_neo_hookean_undefined_for_inverted_deformation(F);
+    let mut F = deformation_gradient_3d();
+    F.swap_rows(0, 1);
+    assert_neo_hookean_undefined_for_inverted_deformation(F);
+}
+
+// Tests for StableNeoHookeanMaterial
+
+#[allow(non_snake_case)]
+fn inverted_deformation_gradient_2d() -> Matrix2<f64> {
+    let mut F = deformation_gradient_2d();
+    F.swap_rows(0, 1);
+    F
+}
+
+#[allow(non_snake_case)]
+fn inverted_deformation_gradient_3d() -> Matrix3<f64> {
+    let mut F = deformation_gradient_3d();
+    F.swap_rows(0, 1);
+    F
+}
+
+test_stress_is_derivative_of_energy!(
+    dim = 2,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_is_derivative_of_energy_2d
+);
+test_stress_is_derivative_of_energy!(
+    dim = 3,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_is_derivative_of_energy_3d
+);
+test_stress_is_derivative_of_energy!(
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_is_derivative_of_energy_inverted_2d,
+    inverted_deformation_gradient_2d()
+);
+test_stress_is_derivative_of_energy!(
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_is_derivative_of_energy_inverted_3d,
+    inverted_deformation_gradient_3d()
+);
+
+test_contraction_is_consistent_with_tensor!(
+    dim = 2,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_contraction_is_consistent_with_tensor_2d
+);
+test_contraction_is_consistent_with_tensor!(
+    dim = 3,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_contraction_is_consistent_with_tensor_3d
+);
+test_contraction_is_consistent_with_tensor!(
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_contraction_is_consistent_with_tensor_inverted_2d,
+    inverted_deformation_gradient_2d(),
+    vector![-3.0, 4.0],
+    vector![-5.0, 2.0]
+);
+test_contraction_is_consistent_with_tensor!(
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_stress_contraction_is_consistent_with_tensor_inverted_3d,
+    inverted_deformation_gradient_3d(),
+    vector![-3.0, 4.0, -5.0],
+    vector![-5.0, 2.0, 1.0]
+);
+
+test_multi_contraction_consistency!(
+    dim = 2,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_multi_contraction_consistency_2d
+);
+test_multi_contraction_consistency!(
+    dim = 3,
+    StableNeoHookeanMaterial,
+    stable_neo_hookean_multi_contraction_consistency_3d
+);
+
+/// Checks that the rest state is stress-free with zero energy, that all quantities are finite for
+/// inverted and singular deformation gradients, and that the stress agrees with linear elasticity for small strains.
+#[allow(non_snake_case)]
+fn assert_stable_neo_hookean_properties<const D: usize>(F: SMatrix<f64, D, D>, F_inverted: SMatrix<f64, D, D>)
+where
+    StableNeoHookeanMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+    LinearElasticMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+    Const<D>: DimMin<Const<D>, Output = Const<D>>,
+{
+    let lame = lame_parameters();
+    let material = StableNeoHookeanMaterial;
+    let I = SMatrix::<f64, D, D>::identity();
+    assert_scalar_eq!(material.compute_energy_density(&I, &lame), 0.0, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(
+        material.compute_stress_tensor(&I, &lame),
+        SMatrix::<f64, D, D>::zeros(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let a = SVector::<f64, D>::repeat(1.0);
+    let b = SVector::<f64, D>::from_fn(|i, _| i as f64 - 1.0);
+    let mut F_singular = F;
+    F_singular.set_row(0, &(F.row(1) * 2.0));
+    assert!(F_inverted.determinant() < 0.0);
+    for F in [F_inverted, F_singular] {
+        assert!(material.compute_energy_density(&F, &lame).is_finite());
+        assert!(material
+            .compute_stress_tensor(&F, &lame)
+            .iter()
+            .all(|p_ij| p_ij.is_finite()));
+        assert!(material
+            .compute_stress_contraction(&F, &a, &b, &lame)
+            .iter()
+            .all(|c_ij| c_ij.is_finite()));
+    }
+
+    // Consistent with linear elasticity (with the same Lamé parameters) for small strains
+    let eps = 1e-6;
+    let F_small = I + F * eps;
+    let P = material.compute_stress_tensor(&F_small, &lame);
+    let P_linear = LinearElasticMaterial.compute_stress_tensor(&F_small, &lame);
+    assert_matrix_eq!(P, P_linear, comp = abs, tol = 1e-4 * P_linear.amax());
+}
+
+#[test]
+fn stable_neo_hookean_properties() {
+    assert_stable_neo_hookean_properties(deformation_gradient_2d(), inverted_deformation_gradient_2d());
+    assert_stable_neo_hookean_properties(deformation_gradient_3d(), inverted_deformation_gradient_3d());
+}
+
+// Tests for CorotatedLinearElasticMaterial
+
+test_stress_is_derivative_of_energy!(
+    dim = 2,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_stress_is_derivative_of_energy_2d
+);
+test_stress_is_derivative_of_energy!(
+    dim = 3,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_stress_is_derivative_of_energy_3d
+);
+
+test_contraction_is_consistent_with_tensor!(
+    dim = 2,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_stress_contraction_is_consistent_with_tensor_2d
+);
+test_contraction_is_consistent_with_tensor!(
+    dim = 3,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_stress_contraction_is_consistent_with_tensor_3d
+);
+
+test_multi_contraction_consistency!(
+    dim = 2,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_multi_contraction_consistency_2d
+);
+test_multi_contraction_consistency!(
+    dim = 3,
+    CorotatedLinearElasticMaterial::new(),
+    corotated_multi_contraction_consistency_3d
+);
+test_multi_contraction_consistency!(
+    dim = 2,
+    CorotatedLinearElasticMaterial::new().with_rotation_derivatives(false),
+    fixed_corotated_multi_contraction_consistency_2d
+);
+test_multi_contraction_consistency!(
+    dim = 3,
+    CorotatedLinearElasticMaterial::new().with_rotation_derivatives(false),
+    fixed_corotated_multi_contraction_consistency_3d
+);
+
+/// Checks that the energy is invariant and the stress transforms as $\vec P(\vec Q \vec F) = \vec Q \vec P(\vec F)$
+/// under the rotation `Q`, that the material agrees with linear elasticity for small strains, and that
+/// the fixed corotated contraction is positive semi-definite.
+#[allow(non_snake_case)]
+fn assert_corotated_properties<const D: usize>(F: SMatrix<f64, D, D>, Q: SMatrix<f64, D, D>)
+where
+    CorotatedLinearElasticMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+    LinearElasticMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+    Const<D>: DimMin<Const<D>, Output = Const<D>>,
+{
+    let lame = lame_parameters();
+    let material = CorotatedLinearElasticMaterial::new();
+    assert_scalar_eq!(Q.determinant(), 1.0, comp = abs, tol = 1e-12);
+
+    let psi = material.compute_energy_density(&F, &lame);
+    let psi_rotated = material.compute_energy_density(&(Q * F), &lame);
+    assert_scalar_eq!(psi_rotated, psi, comp = abs, tol = 1e-12 * psi);
+
+    let P = material.compute_stress_tensor(&F, &lame);
+    let P_rotated = material.compute_stress_tensor(&(Q * F), &lame);
+    assert_matrix_eq!(P_rotated, Q * P, comp = abs, tol = 1e-12 * P.amax());
+
+    // Consistent with linear elasticity (with the same Lamé parameters) for small strains
+    let I = SMatrix::<f64, D, D>::identity();
+    let eps = 1e-6;
+    let F_small = I + F * eps;
+    let P = material.compute_stress_tensor(&F_small, &lame);
+    let P_linear = LinearElasticMaterial.compute_stress_tensor(&F_small, &lame);
+    assert_matrix_eq!(P, P_linear, comp = abs, tol = 1e-4 * P_linear.amax());
+
+    let fixed_material = material.with_rotation_derivatives(false);
+    let a = SVector::<f64, D>::from_fn(|i, _| i as f64 - 1.5);
+    let C = fixed_material.compute_stress_contraction(&F, &a, &a, &lame);
+    assert_matrix_eq!(C, C.transpose(), comp = abs, tol = 1e-12 * C.amax());
+    for v in [
+        a,
+        SVector::repeat(1.0),
+        SVector::from_fn(|i, _| (i as f64).powi(2) - 2.0),
+    ] {
+        assert!(v.dot(&(C * v)) >= 0.0);
+    }
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn corotated_properties() {
+    let theta: f64 = 0.7;
+    let Q = Matrix2::new(theta.cos(), -theta.sin(), theta.sin(), theta.cos());
+    assert_corotated_properties(deformation_gradient_2d(), Q);
+
+    let axis = nalgebra::Unit::new_normalize(vector![1.0, -2.0, 3.0]);
+    let Q = nalgebra::Rotation3::from_axis_angle(&axis, 1.3).into_inner();
+    assert_corotated_properties(deformation_gradient_3d(), Q);
+}
+
+// Tests for TransverselyIsotropicMaterial
+
+fn transversely_isotropic_parameters<const D: usize>(
+    fiber_stiffness: f64,
+    fiber_nonlinearity: f64,
+) -> TransverselyIsotropicParameters<f64, Const<D>> {
+    TransverselyIsotropicParameters {
+        lame: lame_parameters(),
+        fiber_direction: SVector::<f64, D>::from_fn(|i, _| i as f64 + 1.0).normalize(),
+        fiber_stiffness,
+        fiber_nonlinearity,
+    }
+}
+
+/// Uses finite differences to check that the stress tensor is the derivative of the energy, and that the
+/// contraction is consistent with the stress tensor.
+#[allow(non_snake_case)]
+fn assert_transversely_isotropic_derivatives<const D: usize>(
+    deformation_gradient: SMatrix<f64, D, D>,
+    a: SVector<f64, D>,
+    b: SVector<f64, D>,
+    parameters: &TransverselyIsotropicParameters<f64, Const<D>>,
+) where
+    TransverselyIsotropicMaterial:
+        HyperelasticMaterial<f64, Const<D>, Parameters = TransverselyIsotropicParameters<f64, Const<D>>>,
+{
+    let material = TransverselyIsotropicMaterial;
+    let h = 1e-5;
+
+    let stress_tensor = material.compute_stress_tensor(&deformation_gradient, parameters);
+    let approx_stress_tensor = approximate_stress_tensor_fd(
+        |F| material.compute_energy_density(F, parameters),
+        deformation_gradient,
+        h,
+    );
+    assert_matrix_eq!(
+        stress_tensor,
+        approx_stress_tensor,
+        comp = abs,
+        tol = 1e-8 * stress_tensor.amax()
+    );
+
+    let contraction = material.compute_stress_contraction(&deformation_gradient, &a, &b, parameters);
+    let approx_contraction = approximate_stress_contraction_fd(
+        |F| material.compute_stress_tensor(F, parameters),
+        deformation_gradient,
+        a,
+        b,
+        h,
+    );
+    assert_matrix_eq!(
+        contraction,
+        approx_contraction,
+        comp = abs,
+        tol = 1e-8 * contraction.amax()
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn transversely_isotropic_derivatives_are_consistent() {
+    // Moderate deformations, so that the exponential fiber term does not dominate completely
+    let F_2d = Matrix2::identity() + deformation_gradient_2d() * 0.1;
+    let F_3d = Matrix3::identity() + deformation_gradient_3d() * 0.1;
+    for (k1, k2) in [(200.0, 0.1), (200.0, 0.0)] {
+        assert_transversely_isotropic_derivatives(
+            F_2d,
+            vector![-3.0, 4.0],
+            vector![-5.0, 2.0],
+            &transversely_isotropic_parameters(k1, k2),
+        );
+        assert_transversely_isotropic_derivatives(
+            F_3d,
+            vector![-3.0, 4.0, -5.0],
+            vector![-5.0, 2.0, 1.0],
+            &transversely_isotropic_parameters(k1, k2),
+        );
+    }
+}
+
+#[allow(non_snake_case)]
+fn assert_transversely_isotropic_reduces_to_neo_hookean<const D: usize>(F: SMatrix<f64, D, D>)
+where
+    TransverselyIsotropicMaterial:
+        HyperelasticMaterial<f64, Const<D>, Parameters = TransverselyIsotropicParameters<f64, Const<D>>>,
+    NeoHookeanMaterial: HyperelasticMaterial<f64, Const<D>, Parameters = LameParameters<f64>>,
+{
+    let parameters = transversely_isotropic_parameters::<D>(0.0, 0.5);
+    let lame = lame_parameters();
+    let a = SVector::<f64, D>::from_fn(|i, _| i as f64 - 1.5);
+    let b = SVector::<f64, D>::from_fn(|i, _| 2.0 - i as f64);
+    let material = TransverselyIsotropicMaterial;
+    assert_eq!(
+        material.compute_energy_density(&F, &parameters),
+        NeoHookeanMaterial.compute_energy_density(&F, &lame)
+    );
+    assert_eq!(
+        material.compute_stress_tensor(&F, &parameters),
+        NeoHookeanMaterial.compute_stress_tensor(&F, &lame)
+    );
+    assert_eq!(
+        material.compute_stress_contraction(&F, &a, &b, &parameters),
+        NeoHookeanMaterial.compute_stress_contraction(&F, &a, &b, &lame)
+    );
+}
+
+#[test]
+fn transversely_isotropic_reduces_to_neo_hookean_without_fiber_stiffness() {
+    assert_transversely_isotropic_reduces_to_neo_hookean(deformation_gradient_2d());
+    assert_transversely_isotropic_reduces_to_neo_hookean(deformation_gradient_3d());
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn transversely_isotropic_fibers_stiffen_along_fiber_direction() {
+    // Stretching along the fiber direction requires more energy than stretching by the same amount
+    // in an orthogonal direction
+    let parameters = transversely_isotropic_parameters::<2>(200.0, 0.1);
+    let n = parameters.fiber_direction;
+    let m = vector![-n.y, n.x];
+    let F_along = Matrix2::identity() + n * n.transpose() * 0.2;
+    let F_across = Matrix2::identity() + m * m.transpose() * 0.2;
+    let material = TransverselyIsotropicMaterial;
+    let psi_along = material.compute_energy_density(&F_along, &parameters);
+    let psi_across = material.compute_energy_density(&F_across, &parameters);
+    assert_scalar_eq!(
+        psi_across,
+        NeoHookeanMaterial.compute_energy_density(&F_across, &parameters.lame),
+        comp = abs,
+        tol = 1e-12 * psi_across
+    );
+    assert!(psi_along > psi_across);
+}
+
+// Tests for the plane strain and plane stress adapters
+
+test_stress_is_derivative_of_energy!(
+    dim = 2,
+    PlaneStrain::new(NeoHookeanMaterial),
+    plane_strain_neo_hookean_stress_is_derivative_of_energy
+);
+test_contraction_is_consistent_with_tensor!(
+    dim = 2,
+    PlaneStrain::new(NeoHookeanMaterial),
+    plane_strain_neo_hookean_stress_contraction_is_consistent_with_tensor
+);
+test_multi_contraction_consistency!(
+    dim = 2,
+    PlaneStrain::new(NeoHookeanMaterial),
+    plane_strain_neo_hookean_multi_contraction_consistency
+);
+
+test_stress_is_derivative_of_energy!(
+    dim = 2,
+    PlaneStress::new(NeoHookeanMaterial),
+    plane_stress_neo_hookean_stress_is_derivative_of_energy
+);
+test_contraction_is_consistent_with_tensor!(
+    dim = 2,
+    PlaneStress::new(NeoHookeanMaterial),
+    plane_stress_neo_hookean_stress_contraction_is_consistent_with_tensor
+);
+test_multi_contraction_consistency!(
+    dim = 2,
+    PlaneStress::new(NeoHookeanMaterial),
+    plane_stress_neo_hookean_multi_contraction_consistency
+);
+
+#[test]
+#[allow(non_snake_case)]
+fn plane_strain_coincides_with_2d_materials() {
+    let lame = lame_parameters();
+    let F = deformation_gradient_2d();
+    let (a, b) = (vector![1.0, 2.0], vector![-0.5, 3.0]);
+
+    let plane_strain = PlaneStrain::new(LinearElasticMaterial);
+    assert_matrix_eq!(
+        plane_strain.compute_stress_tensor(&F, &lame),
+        LinearElasticMaterial.compute_stress_tensor(&F, &lame),
+        comp = abs,
+        tol = 1e-9
+    );
+
+    let plane_strain = PlaneStrain::new(NeoHookeanMaterial);
+    assert_scalar_eq!(
+        plane_strain.compute_energy_density(&F, &lame),
+        NeoHookeanMaterial.compute_energy_density(&F, &lame),
+        comp = abs,
+        tol = 1e-9
+    );
+    assert_matrix_eq!(
+        plane_strain.compute_stress_tensor(&F, &lame),
+        NeoHookeanMaterial.compute_stress_tensor(&F, &lame),
+        comp = abs,
+        tol = 1e-9
+    );
+    assert_matrix_eq!(
+        plane_strain.compute_stress_contraction(&F, &a, &b, &lame),
+        NeoHookeanMaterial.compute_stress_contraction(&F, &a, &b, &lame),
+        comp = abs,
+        tol = 1e-9
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn plane_stress_linear_elastic_coincides_with_plane_stress_lame_parameters() {
+    let lame = lame_parameters();
+    // Moderate deformations, so that the out-of-plane stretch remains positive
+    let F = Matrix2::identity() + deformation_gradient_2d() * 0.1;
+    let (a, b) = (vector![1.0, 2.0], vector![-0.5, 3.0]);
+    let plane_stress = PlaneStress::new(LinearElasticMaterial);
+    let lame_2d = lame.to_plane_stress();
+
+    assert_scalar_eq!(
+        plane_stress.compute_energy_density(&F, &lame),
+        LinearElasticMaterial.compute_energy_density(&F, &lame_2d),
+        comp = abs,
+        tol = 1e-9
+    );
+    assert_matrix_eq!(
+        plane_stress.compute_stress_tensor(&F, &lame),
+        LinearElasticMaterial.compute_stress_tensor(&F, &lame_2d),
+        comp = abs,
+        tol = 1e-9
+    );
+    assert_matrix_eq!(
+        plane_stress.compute_stress_contraction(&F, &a, &b, &lame),
+        LinearElasticMaterial.compute_stress_contraction(&F, &a, &b, &lame_2d),
+        comp = abs,
+        tol = 1e-9
+    );
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn plane_linear_elasticity_uniaxial_tension() {
+    let young_poisson = YoungPoisson {
+        young: 1e4,
+        poisson: 0.3,
+    };
+    let YoungPoisson { young, poisson } = young_poisson;
+    let lame: LameParameters<f64> = young_poisson.try_into().unwrap();
+    let strain = 1e-3;
+
+    // Plane stress: the lateral strain is -nu * strain, and the axial stress is E * strain
+    let F = Matrix2::new(1.0 + strain, 0.0, 0.0, 1.0 - poisson * strain);
+    let P = PlaneStress::new(LinearElasticMaterial).compute_stress_tensor(&F, &lame);
+    assert_matrix_eq!(P, Matrix2::new(young * strain, 0.0, 0.0, 0.0), comp = abs, tol = 1e-9);
+    let lambda_3 = PlaneStress::new(LinearElasticMaterial).compute_out_of_plane_stretch(&F, &lame);
+    assert_scalar_eq!(lambda_3, 1.0 - poisson * strain, comp = abs, tol = 1e-14);
+
+    // Plane strain: the lateral strain is -nu / (1 - nu) * strain, and the axial stress
+    // is E / (1 - nu^2) * strain
+    let F = Matrix2::new(1.0 + strain, 0.0, 0.0, 1.0 - poisson / (1.0 - poisson) * strain);
+    let P = PlaneStrain::new(LinearElasticMaterial).compute_stress_tensor(&F, &lame);
+    let expected_stress = young / (1.0 - poisson * poisson) * strain;
+    assert_matrix_eq!(P, Matrix2::new(expected_stress, 0.0, 0.0, 0.0), comp = abs, tol = 1e-9);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn plane_neo_hookean_uniaxial_tension() {
+    let lame = lame_parameters();
+    let LameParameters { mu, lambda } = lame;
+    // For a diagonal deformation gradient diag(l1, l2, l3), the Neo-Hookean stress is diagonal with
+    //  P_ii = mu (l_i - 1 / l_i) + lambda log(J) / l_i.
+    // Given the lateral stretch s, the vanishing lateral stress determines the axial stretch l1.
+    let s: f64 = 0.9;
+    let axial_stress = |l1: f64, J: f64| mu * (l1 - 1.0 / l1) + lambda * J.ln() / l1;
+
+    // Plane stress: both lateral stretches are s by symmetry, and mu (s^2 - 1) + lambda log(l1 s^2) = 0
+    let l1 = (mu * (1.0 - s * s) / lambda).exp() / (s * s);
+    let F = Matrix2::new(l1, 0.0, 0.0, s);
+    let plane_stress = PlaneStress::new(NeoHookeanMaterial);
+    let P = plane_stress.compute_stress_tensor(&F, &lame);
+    let expected = Matrix2::new(axial_stress(l1, l1 * s * s), 0.0, 0.0, 0.0);
+    assert_matrix_eq!(P, expected, comp = abs, tol = 1e-9 * expected.amax());
+    assert_scalar_eq!(
+        plane_stress.compute_out_of_plane_stretch(&F, &lame),
+        s,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // Plane strain: the out-of-plane stretch is 1, and mu (s^2 - 1) + lambda log(l1 s) = 0
+    let l1 = (mu * (1.0 - s * s) / lambda).exp() / s;
+    let F = Matrix2::new(l1, 0.0, 0.0, s);
+    let P = PlaneStrain::new(NeoHookeanMaterial).compute_stress_tensor(&F, &lame);
+    let expected = Matrix2::new(axial_stress(l1, l1 * s), 0.0, 0.0, 0.0);
+    assert_matrix_eq!(P, expected, comp = abs, tol = 1e-9 * expected.amax());
+}
+
+// Tests for a Neo-Hookean material with derivatives computed by automatic differentiation
+
+test_stress_is_derivative_of_energy!(
+    dim = 2,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_stress_is_derivative_of_energy_2d
+);
+test_stress_is_derivative_of_energy!(
+    dim = 3,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_stress_is_derivative_of_energy_3d
+);
+
+test_contraction_is_consistent_with_tensor!(
+    dim = 2,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_stress_contraction_is_consistent_with_tensor_2d
+);
+test_contraction_is_consistent_with_tensor!(
+    dim = 3,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_stress_contraction_is_consistent_with_tensor_3d
+);
+
+test_multi_contraction_consistency!(
+    dim = 2,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_multi_contraction_consistency_2d
+);
+test_multi_contraction_consistency!(
+    dim = 3,
+    AutodiffMaterial::new(NeoHookeanEnergy),
+    autodiff_neo_hookean_multi_contraction_consistency_3d
+);
@@ -3,10 +3,25 @@ use fenris::nalgebra;
 use fenris::nalgebra::{matrix, Matrix2, Matrix3, Point3};
 use fenris_solid::materials::LameParameters;
 
+mod autodiff;
+mod axisymmetric;
+mod calibration;
+mod contact;
+mod dynamics;
 mod gravity_source;
+mod invariants;
 mod logdet;
 mod material_elliptic_operator;
 mod materials;
+mod model;
+mod multi_material;
+mod post_processing;
+mod problem_description;
+mod projection;
+mod quasi_static;
+mod reaction_diffusion;
+mod recorder;
+mod verification;
 
 fn lame_parameters() -> LameParameters<f64> {
     LameParameters {
@@ -0,0 +1,165 @@
+use fenris::assembly::local::{Density, UniformQuadratureTable};
+use fenris::connectivity::Quad9d2Connectivity;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
+use fenris::mesh::Mesh2d;
+use fenris::nalgebra::{DMatrix, Vector2};
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
+use fenris_solid::model::{LanczosSettings, ModalProblem, Modes};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+const LENGTH: f64 = 1.0;
+const HEIGHT: f64 = 0.05;
+
+/// A slender beam [0, 1] x [0, 0.05] discretized with Quad9 elements.
+fn beam_mesh() -> Mesh2d<f64, Quad9d2Connectivity> {
+    let quad_mesh = create_rectangular_uniform_quad_mesh_2d(HEIGHT, 20, 1, 1, &Vector2::new(0.0, HEIGHT));
+    Mesh2d::from(quad_mesh)
+}
+
+/// Assembles the modal problem for a beam with Young's modulus 1, Poisson ratio 0 and density 1.
+fn beam_modal_problem(mesh: &Mesh2d<f64, Quad9d2Connectivity>, dirichlet_nodes: &[usize]) -> ModalProblem<f64> {
+    let parameters = LameParameters { mu: 0.5, lambda: 0.0 };
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(3),
+        parameters,
+    );
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(3),
+        Density(1.0),
+    );
+    ModalProblem::assemble(
+        mesh,
+        &LinearElasticMaterial,
+        &stiffness_qtable,
+        &mass_qtable,
+        dirichlet_nodes,
+    )
+    .unwrap()
+}
+
+/// The nodes at the left end of the beam.
+fn clamped_nodes(mesh: &Mesh2d<f64, Quad9d2Connectivity>) -> Vec<usize> {
+    mesh.vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.x == 0.0)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn compute_modes(problem: &ModalProblem<f64>, num_modes: usize, shift: f64) -> Modes<f64> {
+    let shifted_stiffness = DMatrix::from(&problem.shifted_stiffness(shift));
+    let cholesky = shifted_stiffness
+        .cholesky()
+        .expect("Shifted stiffness must be positive definite");
+    problem
+        .compute_lowest_modes(num_modes, shift, |b| Ok(cholesky.solve(b)), &LanczosSettings::default())
+        .unwrap()
+}
+
+/// Euler-Bernoulli angular frequency for the given eigenvalue parameter (beta L)
+/// of a beam with unit Young's modulus and density.
+fn euler_bernoulli_angular_frequency(beta_l: f64) -> f64 {
+    let flexural_rigidity = HEIGHT.powi(3) / 12.0;
+    let mass_per_length = HEIGHT;
+    (beta_l / LENGTH).powi(2) * (flexural_rigidity / mass_per_length).sqrt()
+}
+
+fn assert_modes_are_mass_orthonormal(problem: &ModalProblem<f64>, modes: &Modes<f64>) {
+    let mass = DMatrix::from(problem.mass());
+    for (i, x_i) in modes.mode_shapes.iter().enumerate() {
+        for (j, x_j) in modes.mode_shapes.iter().enumerate() {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert_scalar_eq!(x_i.dot(&(&mass * x_j)), expected, comp = abs, tol = 1e-8);
+        }
+    }
+}
+
+#[test]
+fn cantilever_bending_frequencies_match_euler_bernoulli() {
+    let mesh = beam_mesh();
+    let clamped_nodes = clamped_nodes(&mesh);
+    assert_eq!(clamped_nodes.len(), 3);
+
+    let problem = beam_modal_problem(&mesh, &clamped_nodes);
+    assert_eq!(problem.num_dofs(), 2 * mesh.vertices().len());
+    assert_eq!(problem.free_dofs().len(), problem.num_dofs() - 6);
+    assert_eq!(problem.stiffness().nrows(), problem.free_dofs().len());
+    assert_eq!(problem.mass().nrows(), problem.free_dofs().len());
+
+    let modes = compute_modes(&problem, 2, 0.0);
+    assert_eq!(modes.eigenvalues.len(), 2);
+    assert_modes_are_mass_orthonormal(&problem, &modes);
+
+    let stiffness = DMatrix::from(problem.stiffness());
+    let mass = DMatrix::from(problem.mass());
+    for (lambda, x) in modes.eigenvalues.iter().zip(&modes.mode_shapes) {
+        let residual = &stiffness * x - *lambda * (&mass * x);
+        assert!(residual.norm() <= 1e-6 * lambda * (&mass * x).norm());
+    }
+
+    // Euler-Bernoulli neglects shear deformation and rotary inertia, which leads to slightly
+    // higher frequencies than for the 2D continuum, especially for higher modes
+    let omega = modes.angular_frequencies();
+    let expected = [
+        euler_bernoulli_angular_frequency(1.87510407),
+        euler_bernoulli_angular_frequency(4.69409113),
+    ];
+    for (omega, expected) in omega.iter().zip(expected) {
+        assert_scalar_eq!(*omega, expected, comp = abs, tol = 0.02 * expected);
+    }
+    assert_scalar_eq!(
+        modes.frequencies()[0],
+        omega[0] / (2.0 * std::f64::consts::PI),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+#[test]
+fn unconstrained_beam_has_rigid_body_modes() {
+    let mesh = beam_mesh();
+    let problem = beam_modal_problem(&mesh, &[]);
+    assert_eq!(problem.free_dofs().len(), problem.num_dofs());
+
+    // The stiffness matrix is singular, so we need a (small) negative shift
+    let modes = compute_modes(&problem, 4, -0.01);
+    assert_eq!(modes.eigenvalues.len(), 4);
+    assert_modes_are_mass_orthonormal(&problem, &modes);
+
+    // Two translations and one rotation in 2D
+    for &lambda in &modes.eigenvalues[0..3] {
+        assert!(
+            lambda.abs() < 1e-8,
+            "Expected rigid body mode, found eigenvalue {}",
+            lambda
+        );
+    }
+    let omega = modes.angular_frequencies()[3];
+    let expected = euler_bernoulli_angular_frequency(4.73004074);
+    assert_scalar_eq!(omega, expected, comp = abs, tol = 0.02 * expected);
+}
+
+#[test]
+fn mode_shapes_can_be_expanded_and_exported() {
+    let mesh = beam_mesh();
+    let clamped_nodes = clamped_nodes(&mesh);
+    let problem = beam_modal_problem(&mesh, &clamped_nodes);
+    let modes = compute_modes(&problem, 1, 0.0);
+
+    let mode = modes.mode_shapes[0].clone();
+    let expanded = problem.expand(&mode);
+    assert_eq!(expanded.len(), problem.num_dofs());
+    for node in clamped_nodes {
+        assert_eq!(expanded[2 * node], 0.0);
+        assert_eq!(expanded[2 * node + 1], 0.0);
+    }
+    assert_matrix_eq!(problem.restrict(&expanded), mode);
+
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_point_vector_attributes("mode", 2, expanded.as_slice())
+        .try_build()
+        .unwrap();
+}
@@ -0,0 +1,277 @@
+use fenris::assembly::global::{assemble_scalar, CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    AggregateElementAssembler, ElementEllipticAssemblerBuilder, GeneralQuadratureTable, UniformQuadratureTable,
+};
+use fenris::connectivity::{Connectivity, Quad4d2Connectivity};
+use fenris::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix2, Vector2, U2};
+use fenris::quadrature;
+use fenris::space::FiniteElementSpace;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial, StVKMaterial};
+use fenris_solid::{HyperelasticMaterial, MaterialEllipticOperator, MaterialRegistry, MultiMaterialAssembler};
+use matrixcompare::assert_scalar_eq;
+
+type DynMaterial = dyn HyperelasticMaterial<f64, U2, Parameters = LameParameters<f64>>;
+type LameTable = UniformQuadratureTable<f64, U2, LameParameters<f64>>;
+
+fn lame_table(mu: f64, lambda: f64) -> LameTable {
+    UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        LameParameters { mu, lambda },
+    )
+}
+
+/// The bar [0, 2] x [0, 0.5].
+fn bar_mesh() -> QuadMesh2d<f64> {
+    create_rectangular_uniform_quad_mesh_2d(0.25, 8, 2, 1, &Vector2::new(0.0, 0.5))
+}
+
+/// Assigns material ID 0 to the elements in the left half of the bar, and 1 to the right half.
+fn left_right_material_ids(mesh: &QuadMesh2d<f64>) -> Vec<usize> {
+    mesh.connectivity()
+        .iter()
+        .map(|cell| {
+            let centroid_x = cell
+                .vertex_indices()
+                .iter()
+                .map(|&v| mesh.vertices()[v].x)
+                .sum::<f64>()
+                / 4.0;
+            if centroid_x < 1.0 {
+                0
+            } else {
+                1
+            }
+        })
+        .collect()
+}
+
+/// Solves for the displacement of the bar under a uniform traction `t` on the right end, with
+/// `u_x` fixed on the left end and `u_y` fixed in the bottom left corner.
+fn solve_bar_under_tension(mesh: &QuadMesh2d<f64>, stiffness: DMatrix<f64>, t: f64) -> DVector<f64> {
+    // Consistent nodal forces for the uniform traction t on the right end
+    let num_nodes = mesh.vertices().len();
+    let mut f = DVector::zeros(2 * num_nodes);
+    for (i, v) in mesh.vertices().iter().enumerate() {
+        if v.x == 2.0 {
+            let is_corner = v.y == 0.0 || v.y == 0.5;
+            f[2 * i] = if is_corner { 0.125 * t } else { 0.25 * t };
+        }
+    }
+
+    let free_dofs: Vec<_> = (0..2 * num_nodes)
+        .filter(|&dof| {
+            let v = mesh.vertices()[dof / 2];
+            let fixed = (dof % 2 == 0 && v.x == 0.0) || (dof % 2 == 1 && v.x == 0.0 && v.y == 0.0);
+            !fixed
+        })
+        .collect();
+    let k_free = stiffness.select_rows(&free_dofs).select_columns(&free_dofs);
+    let f_free = f.select_rows(&free_dofs);
+    let u_free = k_free.cholesky().unwrap().solve(&f_free);
+    let mut u = DVector::zeros(2 * num_nodes);
+    for (&dof, &u_dof) in free_dofs.iter().zip(&u_free) {
+        u[dof] = u_dof;
+    }
+    u
+}
+
+#[test]
+fn bimaterial_bar_under_tension_has_series_stiffness_displacement() {
+    // The left half of the bar has Young's modulus E_1 and the right half E_2. With lambda = 0,
+    // Poisson's ratio is zero and E = 2 mu. Under a uniform traction t at the right end and
+    // with the left end fixed in the x direction, the exact displacement is u_y = 0 and
+    // u_x piecewise linear, with strain t / E_i in each material. In particular, the
+    // displacement at the right end is t (L_1 / E_1 + L_2 / E_2).
+    let mesh = bar_mesh();
+    let material_ids = left_right_material_ids(&mesh);
+    assert!(material_ids.contains(&0) && material_ids.contains(&1));
+
+    let (mu_1, mu_2) = (10.0, 40.0);
+    let (e_1, e_2) = (2.0 * mu_1, 2.0 * mu_2);
+    let (table_1, table_2) = (lame_table(mu_1, 0.0), lame_table(mu_2, 0.0));
+    let registry = MaterialRegistry::new()
+        .with_material(0, &LinearElasticMaterial, &table_1)
+        .with_material(1, &LinearElasticMaterial, &table_2);
+
+    let num_nodes = mesh.vertices().len();
+    let u0 = DVector::zeros(2 * num_nodes);
+    let assembler = MultiMaterialAssembler::from_registry(&mesh, &u0, &material_ids, &registry).unwrap();
+    assert_eq!(assembler.material_id(0), material_ids[0]);
+    let stiffness = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+
+    let t = 2.0;
+    let u = solve_bar_under_tension(&mesh, stiffness, t);
+
+    for (i, v) in mesh.vertices().iter().enumerate() {
+        let expected_ux = if v.x <= 1.0 {
+            t * v.x / e_1
+        } else {
+            t / e_1 + t * (v.x - 1.0) / e_2
+        };
+        assert_scalar_eq!(u[2 * i], expected_ux, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(u[2 * i + 1], 0.0, comp = abs, tol = 1e-12);
+    }
+    let right_end_displacement = t * (1.0 / e_1 + 1.0 / e_2);
+    let max_ux = u.iter().step_by(2).copied().fold(0.0, f64::max);
+    assert_scalar_eq!(max_ux, right_end_displacement, comp = abs, tol = 1e-12);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn bimaterial_bar_with_per_element_parameters_has_piecewise_strain() {
+    // The same problem as above, but with a single assembler that obtains the material
+    // parameters from a quadrature table with per-element parameters
+    let mesh = bar_mesh();
+    let num_elements = mesh.connectivity().len();
+    let (mu_1, mu_2) = (10.0, 40.0);
+    let lame = |is_left: bool| LameParameters {
+        mu: if is_left { mu_1 } else { mu_2 },
+        lambda: 0.0,
+    };
+    let element_parameters: Vec<_> = left_right_material_ids(&mesh)
+        .into_iter()
+        .map(|material_id| lame(material_id == 0))
+        .collect();
+    let quadrature = quadrature::tensor::quadrilateral_gauss(2);
+    let table =
+        GeneralQuadratureTable::from_uniform_quadrature_and_element_data(quadrature.clone(), &element_parameters);
+
+    // The parameters can equivalently be given as a function of the physical quadrature points
+    let table_from_fn = GeneralQuadratureTable::from_uniform_quadrature_and_fn(quadrature, num_elements, |i, xi| {
+        lame(mesh.map_element_reference_coords(i, xi).x < 1.0)
+    });
+    assert_eq!(table_from_fn, table);
+
+    let operator = MaterialEllipticOperator::new(&LinearElasticMaterial);
+    let u0 = DVector::zeros(2 * mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&operator)
+        .with_quadrature_table(&table)
+        .with_u(&u0)
+        .build();
+    let stiffness = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+    let t = 2.0;
+    let u = solve_bar_under_tension(&mesh, stiffness, t);
+
+    // In each element, the strain is t / E_i, where E_i is given by the parameters of the element,
+    // and the stress P_xx equals the traction
+    for (cell, parameters) in mesh.connectivity().iter().zip(&element_parameters) {
+        let by_x = |&&i: &&usize, &&j: &&usize| mesh.vertices()[i].x.total_cmp(&mesh.vertices()[j].x);
+        let i = *cell.vertex_indices().iter().min_by(by_x).unwrap();
+        let j = *cell.vertex_indices().iter().max_by(by_x).unwrap();
+        let strain = (u[2 * j] - u[2 * i]) / (mesh.vertices()[j].x - mesh.vertices()[i].x);
+        assert_scalar_eq!(strain, t / (2.0 * parameters.mu), comp = abs, tol = 1e-12);
+
+        let F = Matrix2::new(1.0 + strain, 0.0, 0.0, 1.0);
+        let P = LinearElasticMaterial.compute_stress_tensor(&F, parameters);
+        assert_scalar_eq!(P[(0, 0)], t, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn multi_material_assembler_matches_submesh_aggregate() {
+    // Two different material models combined through trait objects, with non-contiguous IDs.
+    // The first half of the elements are assigned to the first material, so that the aggregate
+    // of the submesh assemblers processes the elements in the same order as the
+    // multi-material assembler, and the results must agree exactly.
+    let mesh = bar_mesh();
+    let num_elements = mesh.connectivity().len();
+    let half = num_elements / 2;
+    let material_ids: Vec<_> = (0..num_elements)
+        .map(|i| if i < half { 3 } else { 7 })
+        .collect();
+
+    let neo_hookean: &DynMaterial = &NeoHookeanMaterial;
+    let stvk: &DynMaterial = &StVKMaterial;
+    let (table_3, table_7) = (lame_table(10.0, 40.0), lame_table(300.0, 200.0));
+    let registry = MaterialRegistry::new()
+        .with_material(7, stvk, &table_7)
+        .with_material(3, neo_hookean, &table_3);
+    assert_eq!(registry.material_ids().collect::<Vec<_>>(), vec![3, 7]);
+
+    let u = DVector::from_fn(2 * mesh.vertices().len(), |i, _| {
+        let x = mesh.vertices()[i / 2];
+        0.01 * (x.x + 2.0 * x.y).sin() + 0.005 * (i % 2) as f64 * x.x * x.x
+    });
+    let multi_material = MultiMaterialAssembler::from_registry(&mesh, &u, &material_ids, &registry).unwrap();
+
+    let submesh = |cells: &[Quad4d2Connectivity]| {
+        QuadMesh2d::from_vertices_and_connectivity(mesh.vertices().to_vec(), cells.to_vec())
+    };
+    let (submesh_3, submesh_7) = (
+        submesh(&mesh.connectivity()[..half]),
+        submesh(&mesh.connectivity()[half..]),
+    );
+    let (operator_3, operator_7) = (
+        MaterialEllipticOperator::new(neo_hookean),
+        MaterialEllipticOperator::new(stvk),
+    );
+    let assemblers = [
+        ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&submesh_3)
+            .with_operator(&operator_3)
+            .with_quadrature_table(&table_3)
+            .with_u(&u)
+            .build(),
+        ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&submesh_7)
+            .with_operator(&operator_7)
+            .with_quadrature_table(&table_7)
+            .with_u(&u)
+            .build(),
+    ];
+    let aggregate = AggregateElementAssembler::from_assemblers(&assemblers);
+
+    let csr_assembler = CsrAssembler::default();
+    assert_eq!(
+        csr_assembler.assemble(&multi_material).unwrap(),
+        csr_assembler.assemble(&aggregate).unwrap()
+    );
+    let vector_assembler = VectorAssembler::default();
+    assert_eq!(
+        vector_assembler.assemble_vector(&multi_material).unwrap(),
+        vector_assembler.assemble_vector(&aggregate).unwrap()
+    );
+    assert_eq!(
+        assemble_scalar(&multi_material).unwrap(),
+        assemble_scalar(&aggregate).unwrap()
+    );
+}
+
+#[test]
+fn multi_material_assembler_rejects_invalid_material_ids() {
+    let mesh = bar_mesh();
+    let num_elements = mesh.connectivity().len();
+    let u = DVector::zeros(2 * mesh.vertices().len());
+    let table = lame_table(1.0, 1.0);
+    let registry = MaterialRegistry::new()
+        .with_material(0, &LinearElasticMaterial, &table)
+        .with_material(1, &LinearElasticMaterial, &table);
+
+    let mut material_ids = vec![0; num_elements];
+    material_ids[5] = 2;
+    let error = MultiMaterialAssembler::from_registry(&mesh, &u, &material_ids, &registry)
+        .err()
+        .expect("Unknown material ID must be rejected");
+    assert!(error.to_string().contains("element 5 has material ID 2"));
+
+    let error = MultiMaterialAssembler::from_registry(&mesh, &u, &material_ids[1..], &registry)
+        .err()
+        .expect("Mismatched number of material IDs must be rejected");
+    assert!(error.to_string().contains("number of material IDs"));
+
+    let empty_registry = MaterialRegistry::<LinearElasticMaterial, LameTable>::new();
+    assert!(MultiMaterialAssembler::from_registry(&mesh, &u, &vec![0; num_elements], &empty_registry).is_err());
+}
+
+#[test]
+#[should_panic]
+fn material_registry_rejects_duplicate_ids() {
+    let table = lame_table(1.0, 1.0);
+    MaterialRegistry::new()
+        .with_material(0, &LinearElasticMaterial, &table)
+        .with_material(0, &LinearElasticMaterial, &table);
+}
@@ -0,0 +1,251 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{HexMesh, QuadMesh2d};
+use fenris::nalgebra::{DVector, Matrix3, Vector3};
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
+use fenris_solid::post_processing::{
+    compute_element_stresses, compute_strain_fields, compute_strain_measures_at_quadrature_points, equivalent_strain,
+    principal_strains, project_stress_to_nodes, stress_result_fields, von_mises_stress, StrainFields,
+};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// Checks that the values of the field with the given name are the same for every point or cell,
+/// and equal to the expected value.
+fn assert_uniform_field(fields: &StrainFields<f64>, name: &str, expected: &[f64]) {
+    for named_fields in [&fields.point_fields, &fields.cell_fields] {
+        let field = named_fields
+            .iter()
+            .find(|field| field.name == name)
+            .unwrap();
+        assert_eq!(field.num_components, expected.len());
+        for values in field.values.chunks_exact(field.num_components) {
+            assert_matrix_eq!(
+                DVector::from_column_slice(values),
+                DVector::from_column_slice(expected),
+                comp = abs,
+                tol = 1e-12
+            );
+        }
+    }
+}
+
+#[test]
+fn simple_shear_strain_fields_hex8() {
+    // The simple shear u = (gamma * y, 0, 0) has the deformation gradient F = I + gamma e_x e_y^T,
+    // so that E = 1/2 (F^T F - I) has E_xy = gamma / 2 and E_yy = gamma^2 / 2,
+    // and the infinitesimal strain has the single component eps_xy = gamma / 2.
+    let gamma = 0.3;
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(3);
+    let u = DVector::from_iterator(
+        3 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|x| [gamma * x.y, 0.0, 0.0]),
+    );
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::hexahedron_gauss(2));
+
+    let expected_green_lagrange = Matrix3::new(
+        0.0,
+        0.5 * gamma,
+        0.0,
+        0.5 * gamma,
+        0.5 * gamma * gamma,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+    );
+    let expected_infinitesimal = Matrix3::new(0.0, 0.5 * gamma, 0.0, 0.5 * gamma, 0.0, 0.0, 0.0, 0.0, 0.0);
+    let measures = compute_strain_measures_at_quadrature_points(&mesh, &u, &qtable).unwrap();
+    assert_eq!(measures.num_elements(), mesh.connectivity().len());
+    assert_eq!(measures.len(), 8 * mesh.connectivity().len());
+    for m in measures.as_slice() {
+        assert_matrix_eq!(m.green_lagrange, expected_green_lagrange, comp = abs, tol = 1e-12);
+        assert_matrix_eq!(m.infinitesimal, expected_infinitesimal, comp = abs, tol = 1e-12);
+    }
+
+    let fields = compute_strain_fields(&mesh, &u, &qtable).unwrap();
+    let half_gamma = 0.5 * gamma;
+    assert_uniform_field(
+        &fields,
+        "green_lagrange_strain",
+        &[0.0, half_gamma * gamma, 0.0, half_gamma, 0.0, 0.0],
+    );
+    assert_uniform_field(&fields, "infinitesimal_strain", &[0.0, 0.0, 0.0, half_gamma, 0.0, 0.0]);
+    assert_uniform_field(
+        &fields,
+        "equivalent_strain",
+        &[equivalent_strain(&expected_green_lagrange)],
+    );
+    for name in [
+        "principal_strains",
+        "principal_direction_1",
+        "principal_direction_2",
+        "principal_direction_3",
+    ] {
+        assert!(fields.point_field(name).is_some() && fields.cell_field(name).is_some());
+    }
+    assert_eq!(
+        fields
+            .point_field("equivalent_strain")
+            .unwrap()
+            .values
+            .len(),
+        mesh.vertices().len()
+    );
+    assert_eq!(
+        fields.cell_field("equivalent_strain").unwrap().values.len(),
+        mesh.connectivity().len()
+    );
+
+    // The fields are compatible with the VTK exporter
+    let builder = FiniteElementMeshDataSetBuilder::from_mesh(&mesh);
+    fields.add_to_vtk_builder(builder).try_build().unwrap();
+}
+
+#[test]
+fn simple_shear_strain_fields_quad4() {
+    // Two-dimensional tensors are padded with zeros in the VTK ordering
+    let gamma = -0.2;
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let u = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|x| [gamma * x.y, 0.0]),
+    );
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let fields = compute_strain_fields(&mesh, &u, &qtable).unwrap();
+
+    let half_gamma = 0.5 * gamma;
+    assert_uniform_field(
+        &fields,
+        "green_lagrange_strain",
+        &[0.0, half_gamma * gamma, 0.0, half_gamma, 0.0, 0.0],
+    );
+    assert_uniform_field(&fields, "infinitesimal_strain", &[0.0, 0.0, 0.0, half_gamma, 0.0, 0.0]);
+    assert!(fields.point_field("principal_direction_2").is_some());
+    assert!(fields.point_field("principal_direction_3").is_none());
+}
+
+#[test]
+fn strain_fields_reject_incompatible_displacement() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::zeros(2 * mesh.vertices().len() - 1);
+    assert!(compute_strain_fields(&mesh, &u, &qtable).is_err());
+}
+
+#[test]
+fn principal_strains_match_dense_eigen_decomposition() {
+    let strain: Matrix3<f64> = Matrix3::new(0.3, -0.1, 0.05, -0.1, 0.2, 0.07, 0.05, 0.07, -0.4);
+    let (values, directions) = principal_strains(&strain);
+
+    let eigen = strain.symmetric_eigen();
+    let mut expected: Vec<_> = eigen
+        .eigenvalues
+        .iter()
+        .copied()
+        .zip(eigen.eigenvectors.column_iter().map(|v| v.into_owned()))
+        .collect();
+    expected.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap());
+
+    for (k, (expected_value, expected_direction)) in expected.iter().enumerate() {
+        assert_scalar_eq!(values[k], *expected_value, comp = abs, tol = 1e-12);
+        // Eigenvectors are only unique up to sign
+        let direction: Vector3<f64> = directions.column(k).into_owned();
+        assert_scalar_eq!(direction.dot(expected_direction).abs(), 1.0, comp = abs, tol = 1e-12);
+    }
+    assert_matrix_eq!(
+        directions.transpose() * directions,
+        Matrix3::identity(),
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn equivalent_strain_of_isochoric_uniaxial_strain() {
+    // For isochoric uniaxial strain diag(e, -e/2, -e/2), the equivalent strain is e
+    let e = 0.02;
+    let strain = Matrix3::from_diagonal(&Vector3::new(e, -0.5 * e, -0.5 * e));
+    assert_scalar_eq!(equivalent_strain(&strain), e, comp = abs, tol = 1e-15);
+
+    // Volumetric strain does not contribute
+    let volumetric = Matrix3::from_diagonal_element(0.1);
+    assert_scalar_eq!(equivalent_strain(&(strain + volumetric)), e, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn uniaxial_stretch_stresses_linear_elastic_hex8() {
+    // The stretch u = (e * x, 0, 0) has the deformation gradient F = diag(1 + e, 1, 1), so that
+    // P = diag((2 mu + lambda) e, lambda e, lambda e) and sigma = P F^T / det F
+    let e = 0.1;
+    let lame = LameParameters { mu: 2.0, lambda: 3.0 };
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(1);
+    let u = DVector::from_iterator(
+        3 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|x| [e * x.x, 0.0, 0.0]),
+    );
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::hexahedron_gauss(1), lame);
+
+    let sigma_xx = (2.0 * lame.mu + lame.lambda) * e;
+    let sigma_yy = lame.lambda * e / (1.0 + e);
+    let expected_cauchy = Matrix3::from_diagonal(&Vector3::new(sigma_xx, sigma_yy, sigma_yy));
+    // For a stress of the form diag(a, b, b), the von Mises stress is |a - b|
+    let expected_von_mises = (sigma_xx - sigma_yy).abs();
+
+    let stresses = compute_element_stresses(&mesh, &LinearElasticMaterial, &qtable, &u).unwrap();
+    assert_eq!(stresses.len(), 1);
+    let stress = &stresses[0];
+    assert_matrix_eq!(stress.cauchy, expected_cauchy, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(stress.von_mises, expected_von_mises, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(
+        von_mises_stress(&expected_cauchy),
+        expected_von_mises,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_matrix_eq!(
+        stress.principal_stresses,
+        Vector3::new(sigma_xx, sigma_yy, sigma_yy),
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(stress.volume, 1.0, comp = abs, tol = 1e-12);
+
+    // A uniform stress is reproduced at every node
+    let nodal_stresses = project_stress_to_nodes(&mesh, &stresses);
+    assert_eq!(nodal_stresses.len(), mesh.vertices().len());
+    for nodal_stress in &nodal_stresses {
+        assert_matrix_eq!(nodal_stress.cauchy, expected_cauchy, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(nodal_stress.von_mises, expected_von_mises, comp = abs, tol = 1e-12);
+    }
+
+    // The fields are compatible with the VTK exporter
+    let builder = stress_result_fields(&nodal_stresses)
+        .iter()
+        .fold(FiniteElementMeshDataSetBuilder::from_mesh(&mesh), |builder, field| {
+            builder.with_point_scalar_attributes(&field.name, field.num_components, &field.values)
+        });
+    let builder = stress_result_fields(&stresses)
+        .iter()
+        .fold(builder, |builder, field| {
+            builder.with_cell_scalar_attributes(&field.name, field.num_components, &field.values)
+        });
+    builder.try_build().unwrap();
+}
+
+#[test]
+fn element_stresses_reject_inverted_elements() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+    // The reflection x -> -x inverts the element
+    let u = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|x| [-2.0 * x.x, 0.0]),
+    );
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        LameParameters::default(),
+    );
+    assert!(compute_element_stresses(&mesh, &LinearElasticMaterial, &qtable, &u).is_err());
+}
@@ -0,0 +1,402 @@
+use fenris::connectivity::{Quad4d2Connectivity, Quad9d2Connectivity};
+use fenris::nalgebra::{DMatrix, DVector, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris_solid::model::dynamics::{DynamicState, TimeIntegrator};
+use fenris_solid::model::{
+    DirichletDescription, ElasticParameters, IntegratorDescription, LoadDescription, MaterialDescription,
+    MaterialModel, MeshDescription, NodeSelection, NodeSetDescription, PhysicsDescription, ProblemBuilder,
+    ProblemDescription, Recorder, TimeSteppingDescription,
+};
+use matrixcompare::assert_scalar_eq;
+
+const FIXTURE_DIR: &str = "tests/fixtures";
+
+fn load_fixture(name: &str) -> ProblemDescription {
+    let path = format!("{}/{}", FIXTURE_DIR, name);
+    let json = std::fs::read_to_string(&path).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn solve_dense(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> eyre::Result<DVector<f64>> {
+    let cholesky = DMatrix::from(matrix)
+        .cholesky()
+        .ok_or_else(|| eyre::eyre!("matrix is not positive definite"))?;
+    Ok(cholesky.solve(rhs))
+}
+
+// The tangent of a nonlinear material is not necessarily positive definite away from equilibrium
+fn solve_lu(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> eyre::Result<DVector<f64>> {
+    DMatrix::from(matrix)
+        .lu()
+        .solve(rhs)
+        .ok_or_else(|| eyre::eyre!("matrix is singular"))
+}
+
+fn node_set(name: &str, min: [f64; 2], max: [f64; 2]) -> NodeSetDescription {
+    NodeSetDescription {
+        name: name.to_string(),
+        selection: NodeSelection::BoundingBox {
+            min: min.to_vec(),
+            max: max.to_vec(),
+        },
+    }
+}
+
+/// An elasticity problem on the square [0, 0.5]^2 that exercises most parts of a description.
+fn square_elasticity_description() -> ProblemDescription {
+    ProblemDescription {
+        mesh: MeshDescription {
+            file: "../assets/meshes/square_quad4_79.msh".into(),
+        },
+        physics: PhysicsDescription::Elasticity {
+            material: "rubber".to_string(),
+        },
+        materials: vec![
+            MaterialDescription {
+                name: "steel".to_string(),
+                model: MaterialModel::LinearElastic,
+                parameters: ElasticParameters::Lame {
+                    mu: 80e9,
+                    lambda: 120e9,
+                },
+                density: 7850.0,
+            },
+            MaterialDescription {
+                name: "rubber".to_string(),
+                model: MaterialModel::LinearElastic,
+                parameters: ElasticParameters::YoungPoisson {
+                    young: 1e6,
+                    poisson: 0.45,
+                },
+                density: 1100.0,
+            },
+        ],
+        node_sets: vec![
+            node_set("left", [-1e-9, -1.0], [1e-9, 1.0]),
+            node_set("right", [0.499999999, -1.0], [0.500000001, 1.0]),
+            NodeSetDescription {
+                name: "corners".to_string(),
+                selection: NodeSelection::Indices(vec![0, 1, 2, 3]),
+            },
+        ],
+        dirichlet: vec![
+            DirichletDescription {
+                node_set: "left".to_string(),
+                value: None,
+            },
+            DirichletDescription {
+                node_set: "right".to_string(),
+                value: Some(vec![0.01, -0.02]),
+            },
+        ],
+        loads: vec![
+            LoadDescription::BodyForce {
+                value: vec![0.0, -10791.0],
+            },
+            LoadDescription::NodalForce {
+                node_set: "corners".to_string(),
+                value: vec![100.0, 50.0],
+            },
+        ],
+        time_stepping: Some(TimeSteppingDescription {
+            time_step: 1e-3,
+            num_steps: 10,
+            integrator: IntegratorDescription::Newmark {
+                beta: Some(0.3),
+                gamma: None,
+                tolerance: Some(1e-6),
+                max_iterations: None,
+            },
+        }),
+    }
+}
+
+#[test]
+fn cantilever_fixture_matches_beam_theory() {
+    let description = load_fixture("cantilever.json");
+    let problem = ProblemBuilder::new(description)
+        .with_base_dir(FIXTURE_DIR)
+        .build::<U2, Quad9d2Connectivity>()
+        .unwrap();
+
+    let clamped = problem.node_set("clamped").unwrap().to_vec();
+    let tip = problem.node_set("tip").unwrap().to_vec();
+    assert_eq!(clamped.len(), 5);
+    assert_eq!(tip.len(), 5);
+    assert_eq!(problem.dirichlet_nodes(), clamped);
+
+    let u = problem.solve_static(solve_dense).unwrap();
+    for &node in &clamped {
+        assert_eq!(u[2 * node], 0.0);
+        assert_eq!(u[2 * node + 1], 0.0);
+    }
+
+    // Beam with E = 1e4, nu = 0, length 1 and height 0.1, with total tip load P = 1e-3.
+    // The Timoshenko deflection is the Euler-Bernoulli deflection plus a small shear contribution.
+    let (young, height, load) = (1e4, 0.1, 1e-3);
+    let shear_modulus = young / 2.0;
+    let second_moment = height * height * height / 12.0;
+    let expected_deflection = load / (3.0 * young * second_moment) + load / (5.0 / 6.0 * shear_modulus * height);
+    assert_scalar_eq!(
+        tip_deflection(&tip, &u),
+        expected_deflection,
+        comp = abs,
+        tol = 0.02 * expected_deflection
+    );
+}
+
+fn tip_deflection(tip: &[usize], u: &DVector<f64>) -> f64 {
+    -tip.iter().map(|&node| u[2 * node + 1]).sum::<f64>() / tip.len() as f64
+}
+
+#[test]
+fn cantilever_fixture_with_suddenly_applied_load_overshoots_static_deflection() {
+    let mut description = load_fixture("cantilever.json");
+    description.time_stepping = Some(
+        serde_json::from_str(r#"{ "time_step": 0.01, "num_steps": 80, "integrator": { "type": "newmark" } }"#).unwrap(),
+    );
+    let problem = ProblemBuilder::new(description)
+        .with_base_dir(FIXTURE_DIR)
+        .build::<U2, Quad9d2Connectivity>()
+        .unwrap();
+    let tip = problem.node_set("tip").unwrap().to_vec();
+    let static_deflection = tip_deflection(&tip, &problem.solve_static(solve_dense).unwrap());
+
+    // The fundamental period of the beam is roughly 0.6, so 80 steps cover more than one period.
+    // Without damping, a suddenly applied constant load makes the beam oscillate about the static
+    // deflection, with a maximum deflection of about twice the static deflection.
+    let mut state = DynamicState::zeros(problem.num_dofs());
+    let mut recorder = Recorder::new().with_probe("tip_deflection", |state: &DynamicState<f64>| {
+        Ok(tip_deflection(&tip, &state.u))
+    });
+    problem
+        .simulate(&mut state, solve_dense, Some(&mut recorder))
+        .unwrap();
+    let times = recorder.times();
+    assert_eq!(times.len(), 81);
+    assert_scalar_eq!(times[80], 0.8, comp = abs, tol = 1e-12);
+    let max_deflection = recorder
+        .series("tip_deflection")
+        .unwrap()
+        .iter()
+        .copied()
+        .fold(0.0, f64::max);
+    assert_scalar_eq!(
+        max_deflection,
+        2.0 * static_deflection,
+        comp = abs,
+        tol = 0.1 * static_deflection
+    );
+}
+
+#[test]
+fn time_integrator_is_built_from_description() {
+    let problem = ProblemBuilder::new(square_elasticity_description())
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    match problem.time_integrator().unwrap() {
+        TimeIntegrator::Newmark(integrator) => {
+            assert_eq!(integrator.time_step(), 1e-3);
+            assert_eq!(integrator.beta(), 0.3);
+            assert_eq!(integrator.gamma(), 0.5);
+            assert_eq!(integrator.tolerance(), 1e-6);
+            assert_eq!(integrator.max_iterations(), 20);
+            assert_eq!(integrator.mass(), &problem.assemble_mass().unwrap());
+        }
+        _ => panic!("Expected Newmark integrator"),
+    }
+
+    let mut description = square_elasticity_description();
+    description.time_stepping.as_mut().unwrap().integrator = IntegratorDescription::SymplecticEuler {};
+    let problem = ProblemBuilder::new(description)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    assert!(matches!(
+        problem.time_integrator().unwrap(),
+        TimeIntegrator::SymplecticEuler(_)
+    ));
+
+    let mut description = square_elasticity_description();
+    description.time_stepping = None;
+    let problem = ProblemBuilder::new(description)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    let error = problem.time_integrator().unwrap_err();
+    assert!(error.to_string().contains("time_stepping"), "{}", error);
+}
+
+#[test]
+fn nonlinear_material_is_solved_with_newton() {
+    let mut description = square_elasticity_description();
+    description.materials[1].model = MaterialModel::StVK;
+    let problem = ProblemBuilder::new(description)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    let error = problem.assemble_linear_system().unwrap_err();
+    assert!(error.to_string().contains("rubber"), "{}", error);
+
+    let linear_problem = ProblemBuilder::new(square_elasticity_description())
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    let u = problem.solve_static(solve_lu).unwrap();
+    let u_linear = linear_problem.solve_static(solve_lu).unwrap();
+    assert_eq!(u.len(), u_linear.len());
+    for &node in &problem.dirichlet_nodes() {
+        for k in 0..2 {
+            assert_eq!(u[2 * node + k], u_linear[2 * node + k]);
+        }
+    }
+    // The displacements are small compared to the size of the domain, so the solutions are close,
+    // but geometric nonlinearity must make a difference
+    let relative_difference = (&u - &u_linear).norm() / u_linear.norm();
+    assert!(relative_difference > 1e-6, "{}", relative_difference);
+    assert!(relative_difference < 0.1, "{}", relative_difference);
+}
+
+#[test]
+fn round_tripped_description_produces_identical_systems() {
+    let description = square_elasticity_description();
+    let json = serde_json::to_string_pretty(&description).unwrap();
+    let deserialized: ProblemDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, description);
+
+    let original = ProblemBuilder::new(description)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    let round_tripped = ProblemBuilder::new(deserialized)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+
+    let (matrix, rhs) = original.assemble_linear_system().unwrap();
+    let (round_tripped_matrix, round_tripped_rhs) = round_tripped.assemble_linear_system().unwrap();
+    assert_eq!(round_tripped_matrix, matrix);
+    assert_eq!(round_tripped_rhs, rhs);
+    assert_eq!(
+        round_tripped.assemble_mass().unwrap(),
+        original.assemble_mass().unwrap()
+    );
+}
+
+#[test]
+fn poisson_problem_reproduces_linear_solution() {
+    // With u = 0 at x = 0, u = 1 at x = 0.5 and no source, the solution is u(x, y) = 2x
+    let json = r#"{
+        "mesh": { "file": "../assets/meshes/square_quad4_79.msh" },
+        "physics": { "type": "poisson" },
+        "node_sets": [
+            { "name": "left", "selection": { "bounding_box": { "min": [-1e-9, -1.0], "max": [1e-9, 1.0] } } },
+            { "name": "right", "selection": { "bounding_box": { "min": [0.499999999, -1.0], "max": [0.500000001, 1.0] } } }
+        ],
+        "dirichlet": [
+            { "node_set": "left" },
+            { "node_set": "right", "value": [1.0] }
+        ]
+    }"#;
+    let description: ProblemDescription = serde_json::from_str(json).unwrap();
+    let problem = ProblemBuilder::new(description)
+        .build::<U2, Quad4d2Connectivity>()
+        .unwrap();
+    assert_eq!(problem.solution_dim(), 1);
+    assert!(problem.material().is_none());
+
+    let u = problem.solve_static(solve_dense).unwrap();
+    for (u_i, x_i) in u.iter().zip(problem.mesh().vertices()) {
+        assert_scalar_eq!(*u_i, 2.0 * x_i.x, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let mut value = serde_json::to_value(square_elasticity_description()).unwrap();
+    value["materials"][0]["youngs_modulus"] = 1e9.into();
+    let error = serde_json::from_value::<ProblemDescription>(value).unwrap_err();
+    assert!(error.to_string().contains("youngs_modulus"), "{}", error);
+
+    let mut value = serde_json::to_value(square_elasticity_description()).unwrap();
+    value["dirichlet_conditions"] = serde_json::Value::Array(vec![]);
+    let error = serde_json::from_value::<ProblemDescription>(value).unwrap_err();
+    assert!(error.to_string().contains("dirichlet_conditions"), "{}", error);
+
+    let mut value = serde_json::to_value(square_elasticity_description()).unwrap();
+    value["loads"][0]["node_set"] = "left".into();
+    assert!(serde_json::from_value::<ProblemDescription>(value).is_err());
+
+    let mut value = serde_json::to_value(square_elasticity_description()).unwrap();
+    value["time_stepping"]["integrator"]["alpha"] = 0.1.into();
+    let error = serde_json::from_value::<ProblemDescription>(value).unwrap_err();
+    assert!(error.to_string().contains("alpha"), "{}", error);
+}
+
+#[test]
+fn validation_errors_name_offending_entry() {
+    let assert_error_contains = |description: ProblemDescription, expected: &[&str]| {
+        let error = ProblemBuilder::new(description)
+            .build::<U2, Quad4d2Connectivity>()
+            .expect_err("Description must be invalid");
+        let message = format!("{:#}", error);
+        for expected in expected {
+            assert!(
+                message.contains(expected),
+                "\"{}\" does not contain \"{}\"",
+                message,
+                expected
+            );
+        }
+    };
+
+    let mut description = square_elasticity_description();
+    description.physics = PhysicsDescription::Elasticity {
+        material: "wood".to_string(),
+    };
+    assert_error_contains(description, &["physics", "wood"]);
+
+    let mut description = square_elasticity_description();
+    description.dirichlet[1].node_set = "top".to_string();
+    assert_error_contains(description, &["dirichlet[1]", "top"]);
+
+    let mut description = square_elasticity_description();
+    description
+        .node_sets
+        .push(node_set("left", [0.0, 0.0], [0.1, 0.1]));
+    assert_error_contains(description, &["node_sets[3]", "left", "duplicate"]);
+
+    let mut description = square_elasticity_description();
+    description.node_sets[1] = node_set("right", [2.0, 2.0], [3.0, 3.0]);
+    assert_error_contains(description, &["node_sets[1]", "right", "empty"]);
+
+    let mut description = square_elasticity_description();
+    description.node_sets[2].selection = NodeSelection::Indices(vec![0, 100000]);
+    assert_error_contains(description, &["node_sets[2]", "corners", "100000"]);
+
+    let mut description = square_elasticity_description();
+    description.loads[1] = LoadDescription::NodalForce {
+        node_set: "corners".to_string(),
+        value: vec![1.0, 2.0, 3.0],
+    };
+    assert_error_contains(description, &["loads[1]", "3 components"]);
+
+    let mut description = square_elasticity_description();
+    description.materials[0].density = -1.0;
+    assert_error_contains(description, &["materials[0]", "steel", "density"]);
+
+    let mut description = square_elasticity_description();
+    description.time_stepping.as_mut().unwrap().time_step = -1.0;
+    assert_error_contains(description, &["time_stepping", "-1"]);
+
+    let mut description = square_elasticity_description();
+    description.time_stepping.as_mut().unwrap().integrator = IntegratorDescription::Newmark {
+        beta: Some(0.0),
+        gamma: None,
+        tolerance: None,
+        max_iterations: None,
+    };
+    assert_error_contains(description, &["time_stepping.integrator", "beta"]);
+
+    let mut description = square_elasticity_description();
+    description.physics = PhysicsDescription::Poisson {};
+    assert_error_contains(description, &["time_stepping", "elasticity"]);
+
+    let mut description = square_elasticity_description();
+    description.mesh.file = "does_not_exist.msh".into();
+    assert_error_contains(description, &["mesh", "does_not_exist.msh"]);
+}
@@ -0,0 +1,158 @@
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{
+    Density, ElementMassAssembler, ElementMatrixAssembler, LumpedElementAssembler, LumpingStrategy,
+    UniformQuadratureTable,
+};
+use fenris::error::estimate_L2_error;
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::{Quad9Mesh2d, Tri6Mesh2d, TriangleMesh2d};
+use fenris::nalgebra::allocator::Allocator;
+use fenris::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, OVector, Point2, Vector1, Vector2, U2};
+use fenris::quadrature;
+use fenris::quadrature::QuadraturePair2d;
+use fenris_solid::model::{
+    project_initial_condition, project_initial_condition_lumped, project_initial_condition_with_solver,
+};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+fn mass_qtable(quadrature: QuadraturePair2d<f64>) -> UniformQuadratureTable<f64, U2, Density<f64>> {
+    UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature, Density(1.0))
+}
+
+/// Computes the integrals of the basis functions of the space from a scalar mass assembler
+/// with unit density.
+fn compute_basis_integrals(mass_assembler: &impl ElementMatrixAssembler<f64>) -> DVector<f64> {
+    let lumped_assembler = LumpedElementAssembler::new(mass_assembler, LumpingStrategy::RowSum);
+    VectorAssembler::default()
+        .assemble_vector(&lumped_assembler)
+        .unwrap()
+}
+
+fn interpolate<S>(vertices: &[Point2<f64>], f: impl Fn(&Point2<f64>) -> OVector<f64, S>) -> DVector<f64>
+where
+    S: DimName,
+    DefaultAllocator: Allocator<f64, S>,
+{
+    let s = S::dim();
+    let mut u = DVector::zeros(s * vertices.len());
+    for (i, x) in vertices.iter().enumerate() {
+        u.rows_mut(s * i, s).copy_from(&f(x));
+    }
+    u
+}
+
+#[test]
+fn projection_recovers_functions_in_the_space() {
+    // Vector-valued linear field on linear triangles
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::total_order::triangle(2).unwrap(),
+        Density(2.0),
+    );
+    let f = |x: &Point2<f64>| Vector2::new(1.0 + x.x - 2.0 * x.y, 3.0 * x.x + x.y);
+    let u_interpolated = interpolate(mesh.vertices(), f);
+    let u_h = project_initial_condition(&mesh, &qtable, f).unwrap();
+    assert_matrix_eq!(u_h, u_interpolated, comp = abs, tol = 1e-12);
+
+    let u_h = project_initial_condition_with_solver(&mesh, &qtable, f, |mass, rhs| {
+        Ok(DMatrix::from(mass).cholesky().unwrap().solve(rhs))
+    })
+    .unwrap();
+    assert_matrix_eq!(u_h, u_interpolated, comp = abs, tol = 1e-12);
+
+    // Scalar biquadratic field on biquadratic quadrilaterals
+    let mesh = Quad9Mesh2d::from(create_unit_square_uniform_quad_mesh_2d(3));
+    let qtable = mass_qtable(quadrature::tensor::quadrilateral_gauss(3));
+    let f = |x: &Point2<f64>| Vector1::new(x.x * x.x * x.y - 2.0 * x.y * x.y + 0.5);
+    let u_h = project_initial_condition(&mesh, &qtable, f).unwrap();
+    let u_interpolated = interpolate(mesh.vertices(), f);
+    assert_matrix_eq!(u_h, u_interpolated, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn projection_of_smooth_function_converges_at_optimal_order() {
+    // Nodal interpolation is also third order in L2 for quadratic elements, but the projection
+    // is the best approximation in the (discrete) L2 norm, and can therefore never be less
+    // accurate than the interpolant
+    let f = |x: &Point2<f64>| Vector1::new((PI * x.x).sin() * (PI * x.y).sin());
+    let quadrature = quadrature::tensor::quadrilateral_gauss(4);
+    let error_qtable = UniformQuadratureTable::from_quadrature(quadrature.clone());
+    let mass_qtable = mass_qtable(quadrature);
+
+    let mut projection_errors = Vec::new();
+    for res in [8, 16, 32] {
+        let mesh = Quad9Mesh2d::from(create_unit_square_uniform_quad_mesh_2d(res));
+        let u_h = project_initial_condition(&mesh, &mass_qtable, f).unwrap();
+        let u_interpolated = interpolate(mesh.vertices(), f);
+        let projection_error = estimate_L2_error(&mesh, &f, &u_h, &error_qtable).unwrap();
+        let interpolation_error = estimate_L2_error(&mesh, &f, &u_interpolated, &error_qtable).unwrap();
+        assert!(
+            projection_error <= interpolation_error,
+            "Projection error {} exceeds interpolation error {} at resolution {}",
+            projection_error,
+            interpolation_error,
+            res
+        );
+        projection_errors.push(projection_error);
+    }
+
+    for pair in projection_errors.windows(2) {
+        let rate = (pair[0] / pair[1]).log2();
+        assert!(
+            rate >= 2.8,
+            "Expected third order convergence, got rate {} (errors {:?})",
+            rate,
+            projection_errors
+        );
+    }
+}
+
+#[test]
+fn projection_conserves_integral() {
+    // Integrating the projection against the constant function, the integral is given by the
+    // dot product of the nodal values with the integrals of the basis functions
+    let e = std::f64::consts::E;
+    let mesh = Quad9Mesh2d::from(create_unit_square_uniform_quad_mesh_2d(4));
+    let qtable = mass_qtable(quadrature::tensor::quadrilateral_gauss(4));
+    let f = |x: &Point2<f64>| Vector1::new((x.x + x.y).exp());
+    let u_h = project_initial_condition(&mesh, &qtable, f).unwrap();
+    let basis_integrals = compute_basis_integrals(
+        &ElementMassAssembler::with_solution_dim(1)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable),
+    );
+    assert_scalar_eq!(basis_integrals.sum(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(basis_integrals.dot(&u_h), (e - 1.0).powi(2), comp = abs, tol = 1e-10);
+
+    // Row-sum lumping also conserves the integral, here for a vector-valued field
+    let mesh = create_unit_square_uniform_quad_mesh_2d(6);
+    let qtable = mass_qtable(quadrature::tensor::quadrilateral_gauss(4));
+    let f = |x: &Point2<f64>| Vector2::new((x.x + x.y).exp(), x.x * x.y);
+    let u_h = project_initial_condition_lumped(&mesh, &qtable, f, LumpingStrategy::RowSum).unwrap();
+    let basis_integrals = compute_basis_integrals(
+        &ElementMassAssembler::with_solution_dim(1)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable),
+    );
+    let integral: Vector2<f64> = basis_integrals
+        .iter()
+        .zip(u_h.as_slice().chunks_exact(2))
+        .map(|(w_i, u_i)| *w_i * Vector2::from_column_slice(u_i))
+        .sum();
+    assert_scalar_eq!(integral.x, (e - 1.0).powi(2), comp = abs, tol = 1e-10);
+    assert_scalar_eq!(integral.y, 0.25, comp = abs, tol = 1e-10);
+}
+
+#[test]
+fn lumped_projection_rejects_non_positive_lumped_masses() {
+    // Row-sum lumping yields zero masses at the vertices of quadratic triangles
+    let mesh = Tri6Mesh2d::from(create_unit_square_uniform_tri_mesh_2d(2));
+    let qtable = mass_qtable(quadrature::total_order::triangle(4).unwrap());
+    let f = |x: &Point2<f64>| Vector1::new(x.x);
+    let error = project_initial_condition_lumped(&mesh, &qtable, f, LumpingStrategy::RowSum).unwrap_err();
+    assert!(error.to_string().contains("not positive"));
+
+    let u_h = project_initial_condition_lumped(&mesh, &qtable, f, LumpingStrategy::Hrz).unwrap();
+    assert!(u_h.iter().all(|u_i| u_i.is_finite()));
+}
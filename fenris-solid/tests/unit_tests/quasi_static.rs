@@ -0,0 +1,180 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::assembly::DirichletBcs;
+use fenris::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Vector2, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial};
+use fenris_solid::model::{LoadStepError, LoadStepSettings, QuasiStaticModel};
+use matrixcompare::assert_scalar_eq;
+
+fn solve_dense(matrix: &CsrMatrix<f64>, rhs: &DVector<f64>) -> eyre::Result<DVector<f64>> {
+    DMatrix::from(matrix)
+        .lu()
+        .solve(rhs)
+        .ok_or_else(|| eyre::eyre!("matrix is singular"))
+}
+
+/// A cantilever [0, 1] x [0, 0.1] discretized with Quad4 elements.
+fn cantilever_mesh() -> QuadMesh2d<f64> {
+    create_rectangular_uniform_quad_mesh_2d(0.1, 10, 1, 2, &Vector2::new(0.0, 0.1))
+}
+
+fn cantilever_qtable() -> UniformQuadratureTable<f64, U2, LameParameters<f64>> {
+    UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        LameParameters {
+            mu: 4000.0,
+            lambda: 6000.0,
+        },
+    )
+}
+
+/// Clamps the left end of the cantilever.
+fn clamped_bcs(mesh: &QuadMesh2d<f64>) -> DirichletBcs<f64> {
+    DirichletBcs::from_mesh_boundary(mesh, 2, &[0, 1], |x| x.x == 0.0, |_, _| 0.0)
+}
+
+/// A downward load of the given total magnitude, distributed evenly among the nodes at the tip.
+fn tip_load(mesh: &QuadMesh2d<f64>, total_load: f64) -> DVector<f64> {
+    let tip_nodes: Vec<_> = (0..mesh.vertices().len())
+        .filter(|&i| mesh.vertices()[i].x == 1.0)
+        .collect();
+    let mut load = DVector::zeros(2 * mesh.vertices().len());
+    for &node in &tip_nodes {
+        load[2 * node + 1] = -total_load / tip_nodes.len() as f64;
+    }
+    load
+}
+
+#[test]
+fn neo_hookean_cantilever_matches_linear_elasticity_for_small_loads() {
+    let mesh = cantilever_mesh();
+    let qtable = cantilever_qtable();
+    let load = tip_load(&mesh, 1e-4);
+
+    // The tolerance accounts for round-off errors in the linear solves, which are significant
+    // for a slender beam
+    let settings = LoadStepSettings {
+        tolerance: 1e-8,
+        ..LoadStepSettings::default()
+    };
+    let model = QuasiStaticModel::new(&mesh, &NeoHookeanMaterial, &qtable, clamped_bcs(&mesh), load.clone()).unwrap();
+    let mut u = DVector::zeros(model.num_dofs());
+    let history = model
+        .solve_load_step(&mut u, &settings, solve_dense)
+        .unwrap();
+    assert!(history.iterations() <= 5);
+    assert_eq!(history.residual_norms.len(), history.iterations() + 1);
+    assert!(history.final_residual_norm() <= 1e-8 * history.residual_norms[0]);
+
+    // Linear elasticity reference, with the same discretization
+    let linear_model = QuasiStaticModel::new(&mesh, &LinearElasticMaterial, &qtable, clamped_bcs(&mesh), load).unwrap();
+    let mut u_linear = DVector::zeros(linear_model.num_dofs());
+    let linear_history = linear_model
+        .solve_load_step(&mut u_linear, &settings, solve_dense)
+        .unwrap();
+    assert_eq!(linear_history.iterations(), 1);
+
+    assert!(u_linear.amax() > 1e-6);
+    assert_scalar_eq!((&u - &u_linear).amax(), 0.0, comp = abs, tol = 1e-3 * u_linear.amax());
+    for &dof in clamped_bcs(&mesh).dofs() {
+        assert_eq!(u[dof], 0.0);
+    }
+}
+
+#[test]
+fn quasi_static_residual_is_gradient_of_energy() {
+    let mesh = cantilever_mesh();
+    let qtable = cantilever_qtable();
+    let model = QuasiStaticModel::new(
+        &mesh,
+        &NeoHookeanMaterial,
+        &qtable,
+        DirichletBcs::homogeneous(vec![]),
+        tip_load(&mesh, 1.0),
+    )
+    .unwrap();
+    let u = DVector::from_fn(model.num_dofs(), |i, _| 1e-2 * (i as f64).sin());
+
+    let residual = model.assemble_residual(&u).unwrap();
+    let h = 1e-6;
+    for dof in [0, 7, 20, model.num_dofs() - 1] {
+        let mut u_plus = u.clone();
+        let mut u_minus = u.clone();
+        u_plus[dof] += h;
+        u_minus[dof] -= h;
+        let fd = (model.assemble_energy(&u_plus).unwrap() - model.assemble_energy(&u_minus).unwrap()) / (2.0 * h);
+        assert_scalar_eq!(residual[dof], fd, comp = abs, tol = 1e-4 * residual.amax());
+    }
+
+    let tangent = model.assemble_tangent(&u).unwrap();
+    assert_eq!(tangent.pattern(), model.tangent_pattern());
+}
+
+#[test]
+fn quasi_static_load_step_reports_divergence() {
+    let mesh = cantilever_mesh();
+    let qtable = cantilever_qtable();
+    let model = QuasiStaticModel::new(
+        &mesh,
+        &NeoHookeanMaterial,
+        &qtable,
+        clamped_bcs(&mesh),
+        tip_load(&mesh, 0.2),
+    )
+    .unwrap();
+
+    let settings = LoadStepSettings {
+        max_iterations: 2,
+        ..LoadStepSettings::default()
+    };
+    let mut u = DVector::zeros(model.num_dofs());
+    match model.solve_load_step(&mut u, &settings, solve_dense) {
+        Err(LoadStepError::MaxIterationsReached(history)) => {
+            assert_eq!(history.iterations(), 2);
+            assert_eq!(history.residual_norms.len(), 3);
+        }
+        other => panic!("Expected MaxIterationsReached, got {:?}", other),
+    }
+}
+
+#[test]
+fn cantilever_support_reaction_balances_tip_load() {
+    let mesh = cantilever_mesh();
+    let qtable = cantilever_qtable();
+    let total_load = 2.5;
+    let load = tip_load(&mesh, total_load);
+    let bcs = clamped_bcs(&mesh);
+    let model = QuasiStaticModel::new(&mesh, &LinearElasticMaterial, &qtable, bcs.clone(), load.clone()).unwrap();
+
+    // The tangent of linear elasticity is the stiffness matrix, independently of u
+    let stiffness = model
+        .assemble_tangent(&DVector::zeros(model.num_dofs()))
+        .unwrap();
+    let constrained_rows = bcs.extract_constrained_rows(&stiffness, &load).unwrap();
+    let mut matrix = stiffness.clone();
+    let mut rhs = load.clone();
+    bcs.apply_to_system(&mut matrix, &mut rhs).unwrap();
+    let u = solve_dense(&matrix, &rhs).unwrap();
+
+    let reactions = constrained_rows.compute_reactions(&u).unwrap();
+    let clamped_nodes: Vec<_> = (0..mesh.vertices().len())
+        .filter(|&i| mesh.vertices()[i].x == 0.0)
+        .collect();
+    let net_reaction = reactions.sum_reaction_force::<U2>(&clamped_nodes);
+    assert_scalar_eq!(net_reaction.x, 0.0, comp = abs, tol = 1e-10);
+    assert_scalar_eq!(net_reaction.y, total_load, comp = abs, tol = 1e-10);
+
+    let residual = &stiffness * &u - &load;
+    let reactions_from_residual = bcs.reactions_from_residual(&residual).unwrap();
+    assert_eq!(reactions_from_residual.dofs(), reactions.dofs());
+    for (r1, r2) in reactions_from_residual
+        .values()
+        .iter()
+        .zip(reactions.values())
+    {
+        assert_scalar_eq!(*r1, *r2, comp = abs, tol = 1e-12);
+    }
+}
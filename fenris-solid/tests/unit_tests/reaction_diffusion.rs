@@ -0,0 +1,170 @@
+use fenris::assembly::local::{Density, UniformQuadratureTable};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, U2};
+use fenris::quadrature;
+use fenris_solid::model::ImexReactionDiffusion;
+use matrixcompare::assert_scalar_eq;
+use std::f64::consts::PI;
+
+fn assemble_imex<Reaction, const S: usize>(
+    mesh: &QuadMesh2d<f64>,
+    diffusion: [f64; S],
+    reaction: Reaction,
+    dt: f64,
+) -> ImexReactionDiffusion<f64, U2, Reaction, S>
+where
+    Reaction: Fn(&[f64; S], &Point2<f64>) -> [f64; S],
+{
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(1.0),
+    );
+    ImexReactionDiffusion::assemble(
+        mesh,
+        mesh.vertices().to_vec(),
+        diffusion,
+        reaction,
+        dt,
+        &stiffness_qtable,
+        &mass_qtable,
+    )
+    .unwrap()
+}
+
+/// Integrates the system for the given number of steps, using dense Cholesky factorizations
+/// of the system matrices that are computed once up front.
+fn integrate<Reaction, const S: usize>(
+    imex: &ImexReactionDiffusion<f64, U2, Reaction, S>,
+    fields: &mut [DVector<f64>; S],
+    num_steps: usize,
+) where
+    Reaction: Fn(&[f64; S], &Point2<f64>) -> [f64; S],
+{
+    let factors: Vec<_> = (0..S)
+        .map(|i| {
+            DMatrix::from(imex.system_matrix(i))
+                .cholesky()
+                .expect("System matrix must be positive definite")
+        })
+        .collect();
+    for _ in 0..num_steps {
+        imex.step(fields, |i, b| Ok(factors[i].solve(b))).unwrap();
+    }
+}
+
+#[test]
+fn gray_scott_is_stable_at_documented_time_step() {
+    // Gray-Scott: u' = D_u Δu - u v^2 + F (1 - u), v' = D_v Δv + u v^2 - (F + k) v.
+    // For states in [0, 1]^2, the rows of the reaction Jacobian have absolute sums bounded by
+    // 3 + F and 3 respectively, so the documented restriction is dt <= 1 / (3 + F).
+    let (f, k) = (0.04, 0.06);
+    let reaction = |&[u, v]: &[f64; 2], _: &Point2<f64>| {
+        let uv2 = u * v * v;
+        [-uv2 + f * (1.0 - u), uv2 - (f + k) * v]
+    };
+    let dt = 1.0 / (3.0 + f);
+    let mesh = create_unit_square_uniform_quad_mesh_2d(16);
+    let imex = assemble_imex(&mesh, [1e-3, 5e-4], reaction, dt);
+    assert_eq!(imex.time_step(), dt);
+    assert_eq!(imex.num_nodes(), mesh.vertices().len());
+    assert_eq!(imex.system_matrix(0).pattern(), imex.mass().pattern());
+    assert_eq!(imex.system_matrix(1).pattern(), imex.mass().pattern());
+
+    // Start from the trivial steady state u = 1, v = 0, perturbed in a disk at the center
+    let is_perturbed = |x: &Point2<f64>| (x - Point2::new(0.5, 0.5)).norm() < 0.15;
+    let mut fields = [
+        DVector::from_iterator(
+            mesh.vertices().len(),
+            mesh.vertices()
+                .iter()
+                .map(|x| if is_perturbed(x) { 0.5 } else { 1.0 }),
+        ),
+        DVector::from_iterator(
+            mesh.vertices().len(),
+            mesh.vertices()
+                .iter()
+                .map(|x| if is_perturbed(x) { 0.25 } else { 0.0 }),
+        ),
+    ];
+    assert!(fields[1].max() > 0.0);
+
+    integrate(&imex, &mut fields, 500);
+    for field in &fields {
+        assert!(field.iter().all(|value| value.is_finite()));
+        assert!(field.min() >= -0.05, "Minimum {} is out of bounds", field.min());
+        assert!(field.max() <= 1.05, "Maximum {} is out of bounds", field.max());
+    }
+}
+
+#[test]
+fn manufactured_single_species_solution_converges_at_first_order_in_time() {
+    // u = exp(-t) cos(πx) cos(πy) satisfies homogeneous Neumann conditions on the unit square,
+    // and solves u' = D Δu + r(u) with the linear reaction r(u) = (2 π^2 D - 1) u
+    let diffusion = 0.1;
+    let lambda = 2.0 * PI * PI * diffusion - 1.0;
+    let reaction = move |&[u]: &[f64; 1], _: &Point2<f64>| [lambda * u];
+    let u_exact = |x: &Point2<f64>, t: f64| (-t).exp() * (PI * x.x).cos() * (PI * x.y).cos();
+    let mesh = create_unit_square_uniform_quad_mesh_2d(8);
+    let final_time = 0.5;
+
+    let solve = |num_steps: usize| {
+        let imex = assemble_imex(&mesh, [diffusion], reaction, final_time / num_steps as f64);
+        let mut fields = [DVector::from_iterator(
+            mesh.vertices().len(),
+            mesh.vertices().iter().map(|x| u_exact(x, 0.0)),
+        )];
+        integrate(&imex, &mut fields, num_steps);
+        let [u] = fields;
+        u
+    };
+
+    // Isolate the temporal error by comparing with a reference solution on the same mesh
+    let u_reference = solve(2000);
+    for (x, u) in mesh.vertices().iter().zip(&u_reference) {
+        assert_scalar_eq!(*u, u_exact(x, final_time), comp = abs, tol = 0.03);
+    }
+
+    let errors: Vec<f64> = [5, 10, 20]
+        .into_iter()
+        .map(|num_steps| (solve(num_steps) - &u_reference).amax())
+        .collect();
+    for pair in errors.windows(2) {
+        let rate = (pair[0] / pair[1]).log2();
+        assert!(
+            (0.85..=1.15).contains(&rate),
+            "Expected first order convergence, got rate {} (errors {:?})",
+            rate,
+            errors
+        );
+    }
+}
+
+#[test]
+fn imex_rejects_inconsistent_input() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d(2);
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(1.0),
+    );
+    let reaction = |u: &[f64; 1], _: &Point2<f64>| *u;
+    let result = ImexReactionDiffusion::assemble(
+        &mesh,
+        mesh.vertices()[1..].to_vec(),
+        [1.0],
+        reaction,
+        0.1,
+        &stiffness_qtable,
+        &mass_qtable,
+    );
+    assert!(result.is_err());
+
+    let imex = assemble_imex(&mesh, [1.0], reaction, 0.1);
+    let mut fields = [DVector::zeros(mesh.vertices().len())];
+    let error = imex
+        .step(&mut fields, |_, b| Ok(b.rows(0, 1).into_owned()))
+        .unwrap_err();
+    assert!(error.to_string().contains("Linear solve for species 0"));
+}
@@ -0,0 +1,210 @@
+use fenris::assembly::global::{assemble_scalar, CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, ElementMatrixAssembler, ElementScalarAssembler,
+    ElementVectorAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::DirichletBcs;
+use fenris::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Vector2, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
+use fenris_solid::model::dynamics::{DynamicState, NewmarkIntegrator};
+use fenris_solid::model::{
+    kinetic_energy, max_component_magnitude, max_nodal_norm, node_set_reaction, QuasiStaticModel, Recorder,
+};
+use fenris_solid::MaterialEllipticOperator;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// The elastic assembler for the given displacement.
+fn elastic_assembler<'a>(
+    mesh: &'a QuadMesh2d<f64>,
+    operator: &'a MaterialEllipticOperator<LinearElasticMaterial>,
+    qtable: &'a UniformQuadratureTable<f64, U2, LameParameters<f64>>,
+    u: &'a DVector<f64>,
+) -> impl ElementMatrixAssembler<f64> + ElementVectorAssembler<f64> + ElementScalarAssembler<f64> + 'a {
+    ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(operator)
+        .with_quadrature_table(qtable)
+        .with_u(u)
+        .build()
+}
+
+#[test]
+fn recorded_energy_of_undamped_newmark_run_is_conserved() {
+    // A beam [0, 1] x [0, 0.25], clamped at the left end
+    let mesh: QuadMesh2d<f64> = create_rectangular_uniform_quad_mesh_2d(0.125, 8, 2, 1, &Vector2::new(0.0, 0.25));
+    let clamped_nodes: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.x == 0.0)
+        .map(|(i, _)| i)
+        .collect();
+
+    let parameters = LameParameters { mu: 10.0, lambda: 20.0 };
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        parameters,
+    );
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(1.0),
+    );
+    let operator = MaterialEllipticOperator::new(&LinearElasticMaterial);
+    let num_dofs = 2 * mesh.vertices().len();
+    let mass = CsrAssembler::default()
+        .assemble(
+            &ElementMassAssembler::with_solution_dim(2)
+                .with_space(&mesh)
+                .with_quadrature_table(&mass_qtable),
+        )
+        .unwrap();
+    let clamped_dofs = clamped_nodes
+        .iter()
+        .flat_map(|&node| [2 * node, 2 * node + 1])
+        .collect();
+    let dirichlet_bcs = DirichletBcs::homogeneous(clamped_dofs);
+    let system = QuasiStaticModel::new(
+        &mesh,
+        &LinearElasticMaterial,
+        &stiffness_qtable,
+        dirichlet_bcs.clone(),
+        DVector::zeros(num_dofs),
+    )
+    .unwrap();
+
+    let mut recorder = Recorder::new();
+    recorder
+        .add_probe("kinetic_energy", |state: &DynamicState<f64>| {
+            Ok(kinetic_energy(&mass, &state.v))
+        })
+        .add_probe("strain_energy", |state: &DynamicState<f64>| {
+            assemble_scalar(&elastic_assembler(&mesh, &operator, &stiffness_qtable, &state.u))
+        })
+        .add_probe("max_displacement", |state: &DynamicState<f64>| {
+            Ok(max_nodal_norm(&state.u, 2))
+        })
+        .add_probe("max_uy", |state: &DynamicState<f64>| {
+            Ok(max_component_magnitude(&state.u, 2, 1))
+        })
+        .add_probe("reaction_y", |state: &DynamicState<f64>| {
+            let internal_forces = VectorAssembler::default().assemble_vector(&elastic_assembler(
+                &mesh,
+                &operator,
+                &stiffness_qtable,
+                &state.u,
+            ))?;
+            Ok(node_set_reaction(&internal_forces, &clamped_nodes, 2)[1])
+        });
+
+    // The default Newmark parameters give the average acceleration scheme, which conserves
+    // energy exactly for linear problems without damping
+    let dt = 0.01;
+    let integrator = NewmarkIntegrator::new(mass.clone(), dirichlet_bcs, dt);
+    let solve = |matrix: &CsrMatrix<f64>, rhs: &DVector<f64>| {
+        let cholesky = DMatrix::from(matrix)
+            .cholesky()
+            .ok_or_else(|| eyre::eyre!("matrix is not positive definite"))?;
+        Ok(cholesky.solve(rhs))
+    };
+
+    // Initial condition: uniform vertical velocity of all free nodes
+    let mut state = DynamicState::zeros(num_dofs);
+    state.v = DVector::from_fn(num_dofs, |i, _| if i % 2 == 1 { 1.0 } else { 0.0 });
+    integrator
+        .compute_consistent_acceleration(&system, &mut state, solve)
+        .unwrap();
+    let num_steps = 200;
+    integrator
+        .run(&system, &mut state, num_steps, solve, Some(&mut recorder))
+        .unwrap();
+
+    assert_eq!(recorder.times().len(), num_steps + 1);
+    let kinetic = recorder.series("kinetic_energy").unwrap();
+    let strain = recorder.series("strain_energy").unwrap();
+    let initial_energy = kinetic[0] + strain[0];
+    assert!(initial_energy > 0.0);
+    assert_eq!(strain[0], 0.0);
+    for (e_kin, e_strain) in kinetic.iter().zip(strain) {
+        assert_scalar_eq!(
+            e_kin + e_strain,
+            initial_energy,
+            comp = abs,
+            tol = 1e-10 * initial_energy
+        );
+    }
+    // The energy must actually be exchanged between kinetic and strain energy
+    let max_strain = strain.iter().copied().fold(0.0, f64::max);
+    assert!(max_strain > 0.25 * initial_energy);
+
+    let max_displacement = recorder.series("max_displacement").unwrap();
+    let max_uy = recorder.series("max_uy").unwrap();
+    assert!(max_uy
+        .iter()
+        .zip(max_displacement)
+        .all(|(uy, u)| *uy <= *u + 1e-14));
+    assert!(recorder.series("reaction_y").unwrap()[1..]
+        .iter()
+        .any(|&r| r.abs() > 0.0));
+    assert!(recorder.series("does_not_exist").is_none());
+
+    // The CSV export contains exactly the in-memory series
+    let mut csv = Vec::new();
+    recorder.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    let header: Vec<_> = lines.next().unwrap().split(',').collect();
+    let expected_header: Vec<_> = ["t"].into_iter().chain(recorder.probe_names()).collect();
+    assert_eq!(header, expected_header);
+
+    let rows: Vec<Vec<f64>> = lines
+        .map(|line| {
+            line.split(',')
+                .map(|entry| entry.parse().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(rows.len(), recorder.times().len());
+    let csv_column = |j: usize| DVector::from_iterator(rows.len(), rows.iter().map(|row| row[j]));
+    assert_matrix_eq!(csv_column(0), DVector::from_column_slice(recorder.times()));
+    for (j, name) in recorder.probe_names().enumerate() {
+        let series = DVector::from_column_slice(recorder.series(name).unwrap());
+        assert_matrix_eq!(csv_column(j + 1), series);
+    }
+    // A subsequent run continues the recorded series
+    integrator
+        .run(&system, &mut state, 10, solve, Some(&mut recorder))
+        .unwrap();
+    assert_eq!(recorder.times().len(), num_steps + 11);
+    assert_scalar_eq!(recorder.times()[num_steps + 10], 2.1, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn failing_probe_records_nothing() {
+    let mut recorder = Recorder::new()
+        .with_probe("value", |x: &f64| Ok(*x))
+        .with_probe("sqrt", |x: &f64| {
+            if *x >= 0.0 {
+                Ok(x.sqrt())
+            } else {
+                Err(eyre::eyre!("negative value"))
+            }
+        });
+    recorder.record(0.0, &4.0).unwrap();
+    let error = recorder.record(1.0, &-1.0).unwrap_err();
+    assert!(format!("{:#}", error).contains("sqrt"));
+    assert_eq!(recorder.times(), &[0.0]);
+    assert_eq!(recorder.series("value").unwrap(), &[4.0]);
+    assert_eq!(recorder.series("sqrt").unwrap(), &[2.0]);
+}
+
+#[test]
+#[should_panic]
+fn duplicate_probe_names_are_rejected() {
+    Recorder::<f64, f64>::new()
+        .with_probe("value", |x: &f64| Ok(*x))
+        .with_probe("value", |x: &f64| Ok(2.0 * *x));
+}
@@ -0,0 +1,120 @@
+use crate::unit_tests::{deformation_gradient_2d, deformation_gradient_3d, lame_parameters};
+use fenris::nalgebra::{Matrix2, OMatrix, OVector, Rotation2, U2, U3};
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial, NeoHookeanMaterial};
+use fenris_solid::proptest::deformation_gradient_positive_det;
+use fenris_solid::verification::{
+    finite_difference_step_size, verify_contraction_consistency, verify_stress_consistency,
+};
+use fenris_solid::HyperelasticMaterial;
+use proptest::prelude::*;
+
+#[test]
+#[allow(non_snake_case)]
+fn linear_elastic_material_passes_verification() {
+    let lame = lame_parameters();
+    let tol = 1e-8;
+
+    let F = deformation_gradient_2d();
+    let report = verify_stress_consistency(&LinearElasticMaterial, &F, &lame, tol);
+    assert!(report.is_consistent(), "{:?}", report);
+    assert_eq!(report.errors.shape(), (2, 2));
+    let report = verify_contraction_consistency(&LinearElasticMaterial, &F, &lame, tol);
+    assert!(report.is_consistent(), "{:?}", report);
+    assert_eq!(report.errors.shape(), (4, 4));
+
+    let F = deformation_gradient_3d();
+    let report = verify_stress_consistency(&LinearElasticMaterial, &F, &lame, tol);
+    assert!(report.is_consistent(), "{:?}", report);
+    assert_eq!(report.errors.shape(), (3, 3));
+    let report = verify_contraction_consistency(&LinearElasticMaterial, &F, &lame, tol);
+    assert!(report.is_consistent(), "{:?}", report);
+    assert_eq!(report.errors.shape(), (9, 9));
+    assert_eq!(report.max_error, report.errors.max());
+}
+
+/// A linear elastic material whose stress contraction misses the term $\mu \vec b \vec a^T$.
+struct InconsistentMaterial;
+
+impl HyperelasticMaterial<f64, U2> for InconsistentMaterial {
+    type Parameters = LameParameters<f64>;
+
+    fn compute_energy_density(&self, deformation_gradient: &Matrix2<f64>, parameters: &Self::Parameters) -> f64 {
+        LinearElasticMaterial.compute_energy_density(deformation_gradient, parameters)
+    }
+
+    fn compute_stress_tensor(
+        &self,
+        deformation_gradient: &Matrix2<f64>,
+        parameters: &Self::Parameters,
+    ) -> Matrix2<f64> {
+        LinearElasticMaterial.compute_stress_tensor(deformation_gradient, parameters)
+    }
+
+    fn compute_stress_contraction(
+        &self,
+        _deformation_gradient: &Matrix2<f64>,
+        a: &OVector<f64, U2>,
+        b: &OVector<f64, U2>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<f64, U2, U2> {
+        let &LameParameters { mu, lambda } = parameters;
+        Matrix2::identity() * (mu * a.dot(b)) + a * b.transpose() * lambda
+    }
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn verification_detects_inconsistent_contraction() {
+    let lame = lame_parameters();
+    let F = deformation_gradient_2d();
+    let report = verify_stress_consistency(&InconsistentMaterial, &F, &lame, 1e-8);
+    assert!(report.is_consistent(), "{:?}", report);
+
+    let report = verify_contraction_consistency(&InconsistentMaterial, &F, &lame, 1e-8);
+    assert!(!report.is_consistent());
+    // The missing term contributes mu e_m e_k^T to the block (k, m), so that the error is mu
+    // for the entry (m, k) of each block
+    assert!((report.max_error - lame.mu).abs() <= 1e-6 * lame.mu);
+    assert!((report.errors[(1, 2)] - lame.mu).abs() <= 1e-6 * lame.mu);
+    assert!(report.errors[(0, 2)] <= 1e-6 * lame.mu);
+}
+
+#[test]
+#[allow(non_snake_case)]
+fn verification_adapts_step_size_to_nearly_singular_deformation() {
+    // A rotated deformation with smallest singular value 1e-4. A step size of order 1e-4 or larger
+    // would invert the deformation, at which point the Neo-Hookean material is undefined
+    let sigma_min = 1e-4;
+    let F = Rotation2::new(0.3).into_inner() * Matrix2::new(1.0, 0.0, 0.0, sigma_min);
+    let h = finite_difference_step_size(&F);
+    assert!(h < 1e-3 * sigma_min);
+    assert!(h > 0.0);
+    assert!(finite_difference_step_size(&Matrix2::<f64>::identity()) > h);
+
+    let lame = lame_parameters();
+    let report = verify_stress_consistency(&NeoHookeanMaterial, &F, &lame, 1e-6);
+    assert_eq!(report.step_size, h);
+    assert!(report.is_consistent(), "{:?}", report);
+    let report = verify_contraction_consistency(&NeoHookeanMaterial, &F, &lame, 1e-6);
+    assert!(report.is_consistent(), "{:?}", report);
+}
+
+proptest! {
+    #[test]
+    #[allow(non_snake_case)]
+    fn linear_elastic_material_passes_verification_for_random_deformations(
+        F2 in deformation_gradient_positive_det::<U2>(0.1, 5.0),
+        F3 in deformation_gradient_positive_det::<U3>(0.1, 5.0)
+    ) {
+        let lame = lame_parameters();
+        let tol = 1e-7;
+        let report = verify_stress_consistency(&LinearElasticMaterial, &F2, &lame, tol);
+        prop_assert!(report.is_consistent(), "{:?}", report);
+        let report = verify_contraction_consistency(&LinearElasticMaterial, &F2, &lame, tol);
+        prop_assert!(report.is_consistent(), "{:?}", report);
+        let report = verify_stress_consistency(&LinearElasticMaterial, &F3, &lame, tol);
+        prop_assert!(report.is_consistent(), "{:?}", report);
+        let report = verify_contraction_consistency(&LinearElasticMaterial, &F3, &lame, tol);
+        prop_assert!(report.is_consistent(), "{:?}", report);
+    }
+}
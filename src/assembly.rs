@@ -163,7 +163,18 @@
 //!
 //!
 
+mod boundary;
 pub mod buffers;
+mod coloring;
+mod constraints;
+mod dirichlet;
 pub mod global;
 pub mod local;
 pub mod operators;
+mod quadrature_point_data;
+
+pub use boundary::*;
+pub use coloring::*;
+pub use constraints::*;
+pub use dirichlet::*;
+pub use quadrature_point_data::*;
@@ -0,0 +1,340 @@
+use crate::allocators::TriDimAllocator;
+use crate::assembly::global::add_local_to_global;
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, ElementVectorAssembler};
+use crate::connectivity::Connectivity;
+use crate::element::{ElementConnectivity, FiniteElement, ReferenceFiniteElement};
+use crate::integrate::volume_form;
+use crate::mesh::{BoundaryPatches, Mesh};
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DMatrixViewMut, DVector, DVectorViewMut, DefaultAllocator, DimName, OPoint, OVector, Scalar};
+use crate::quadrature::Quadrature;
+use crate::{Real, SmallDim, Symmetry};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+type FaceReferenceDim<T, C> = <<C as Connectivity>::FaceConnectivity as ElementConnectivity<T>>::ReferenceDim;
+
+/// Computes consistent nodal loads for the given boundary data.
+///
+/// The boundary data is given as a pointwise function $g(x)$, for example a traction in solid
+/// mechanics or a normal flux in heat conduction problems. The consistent nodal loads are
+/// then given by
+/// $$ f_I = \int_{\Gamma} \varphi_I \, g \enspace \mathrm{d}s, $$
+/// where $\Gamma$ is the selected part of the boundary of the mesh and $\varphi_I$ is the
+/// trace of the basis function associated with node $I$. The returned vector is associated with
+/// the degrees of freedom of the *volume* mesh, with the solution dimension given by the output
+/// dimension of the load function.
+///
+/// A boundary face is selected if `boundary_selection` returns `true` for all of its nodes.
+/// The quadrature rule is given on the reference domain of the faces.
+///
+/// This is a convenience function that integrates directly over the selected faces, without having
+/// to set up a surface mesh and an associated assembler.
+pub fn compute_consistent_boundary_loads<T, D, C, S>(
+    mesh: &Mesh<T, D, C>,
+    boundary_selection: impl Fn(&OPoint<T, D>) -> bool,
+    load_fn: impl Fn(&OPoint<T, D>) -> OVector<T, S>,
+    quadrature: impl Quadrature<T, FaceReferenceDim<T, C>>,
+) -> DVector<T>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: TriDimAllocator<T, D, FaceReferenceDim<T, C>, S>,
+{
+    let s = S::dim();
+    let vertices = mesh.vertices();
+    let mut loads = DVector::zeros(s * vertices.len());
+    let mut basis_values = Vec::new();
+    let mut local_loads = DVector::zeros(0);
+
+    let weights = quadrature.weights();
+    let points = quadrature.points();
+
+    for (face, _, _) in mesh.find_boundary_faces() {
+        let nodes = face.vertex_indices();
+        if !nodes
+            .iter()
+            .all(|&node| boundary_selection(&vertices[node]))
+        {
+            continue;
+        }
+
+        let element = face
+            .element(vertices)
+            .expect("Boundary faces must correspond to valid elements");
+        let n = element.num_nodes();
+        basis_values.resize(n, T::zero());
+        local_loads.resize_vertically_mut(s * n, T::zero());
+        local_loads.fill(T::zero());
+
+        for (&w, xi) in weights.iter().zip(points) {
+            let x = element.map_reference_coords(xi);
+            let j = element.reference_jacobian(xi);
+            let g = load_fn(&x);
+            element.populate_basis(&mut basis_values, xi);
+            let w_ds = w * volume_form(&j);
+            for (node_loads, &phi_i) in local_loads
+                .as_mut_slice()
+                .chunks_exact_mut(s)
+                .zip(&basis_values)
+            {
+                for (f_i, &g_i) in node_loads.iter_mut().zip(g.iter()) {
+                    *f_i += w_ds * phi_i * g_i;
+                }
+            }
+        }
+
+        add_local_to_global(&local_loads, &mut loads, nodes, s);
+    }
+
+    loads
+}
+
+/// Computes the total consistent nodal loads on each of the given node sets.
+///
+/// The consistent nodal loads are computed as in [`compute_consistent_boundary_loads`], and the
+/// loads of the nodes in each node set are summed. This is primarily useful for verifying that
+/// the load has been correctly distributed, for example to check that the total load matches
+/// the integral of the boundary data, or that the load is distributed as expected among corner,
+/// edge and interior nodes.
+pub fn compute_consistent_boundary_load_totals<T, D, C, S>(
+    mesh: &Mesh<T, D, C>,
+    boundary_selection: impl Fn(&OPoint<T, D>) -> bool,
+    load_fn: impl Fn(&OPoint<T, D>) -> OVector<T, S>,
+    quadrature: impl Quadrature<T, FaceReferenceDim<T, C>>,
+    node_sets: &[&[usize]],
+) -> Vec<OVector<T, S>>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: Connectivity,
+    C::FaceConnectivity: ElementConnectivity<T, GeometryDim = D>,
+    DefaultAllocator: TriDimAllocator<T, D, FaceReferenceDim<T, C>, S>,
+{
+    let s = S::dim();
+    let loads = compute_consistent_boundary_loads(mesh, boundary_selection, load_fn, quadrature);
+    node_sets
+        .iter()
+        .map(|node_set| {
+            let mut total = OVector::<T, S>::zeros();
+            for &node in node_set.iter() {
+                total += loads.rows_generic(s * node, S::name());
+            }
+            total
+        })
+        .collect()
+}
+
+/// Assembles Robin and traction boundary terms on a single patch of [`BoundaryPatches`].
+///
+/// For a scalar coefficient $\alpha(x)$ and boundary data $g(x) \in \mathbb{R}^s$, the assembler
+/// computes the element matrices and vectors associated with the terms
+/// $$ \int_{\Gamma_p} \alpha \, u \cdot v \enspace \mathrm{d}s
+///     \quad \text{and} \quad
+///     \int_{\Gamma_p} \alpha \, g \cdot v \enspace \mathrm{d}s, $$
+/// where $\Gamma_p$ is the patch. These are the terms that arise from a Robin condition
+/// $\nabla u \cdot n = \alpha (g - u)$. With $\alpha = 1$, the vector corresponds to the
+/// consistent nodal loads for the traction $g$, see [`traction`](Self::traction).
+///
+/// The element nodes are given in the node numbering of the volume mesh from which the patches
+/// were extracted. Assemblers for different patches of the same [`BoundaryPatches`] therefore
+/// assemble consistently into the same global system, and nodes shared between patches receive
+/// the contributions of each patch exactly once.
+#[derive(Debug, Clone)]
+pub struct BoundaryPatchAssembler<'a, T, D, C, S, Q, Coefficient, Data>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    patches: &'a BoundaryPatches<T, D, C>,
+    elements: Range<usize>,
+    quadrature: Q,
+    coefficient: Coefficient,
+    data: Data,
+    marker: PhantomData<S>,
+}
+
+impl<'a, T, D, C, S, Q, Coefficient, Data> BoundaryPatchAssembler<'a, T, D, C, S, Q, Coefficient, Data>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    Q: Quadrature<T, C::ReferenceDim>,
+    Coefficient: Fn(&OPoint<T, D>) -> T,
+    Data: Fn(&OPoint<T, D>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, D, C::ReferenceDim, S>,
+{
+    /// Constructs an assembler for the Robin terms with the given coefficient $\alpha$ and
+    /// reference value $g$ on the patch with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patch index is out of bounds.
+    pub fn robin(
+        patches: &'a BoundaryPatches<T, D, C>,
+        patch_index: usize,
+        quadrature: Q,
+        coefficient: Coefficient,
+        reference_value: Data,
+    ) -> Self {
+        Self {
+            patches,
+            elements: patches.element_range(patch_index),
+            quadrature,
+            coefficient,
+            data: reference_value,
+            marker: PhantomData,
+        }
+    }
+
+    fn face_connectivity(&self, element_index: usize) -> &C {
+        &self.patches.surface_mesh().connectivity()[self.elements.start + element_index]
+    }
+
+    /// Integrates $\alpha \varphi_I \varphi_J$ and $\alpha \varphi_I g$ over the given element,
+    /// passing the contributions at each quadrature point to the provided closure.
+    fn integrate_element(&self, element_index: usize, mut f: impl FnMut(T, &[T], &OVector<T, S>)) {
+        let element = self
+            .face_connectivity(element_index)
+            .element(self.patches.surface_mesh().vertices())
+            .expect("Patch faces must correspond to valid elements");
+        let mut basis_values = vec![T::zero(); element.num_nodes()];
+        for (&w, xi) in self
+            .quadrature
+            .weights()
+            .iter()
+            .zip(self.quadrature.points())
+        {
+            let x = element.map_reference_coords(xi);
+            let j = element.reference_jacobian(xi);
+            element.populate_basis(&mut basis_values, xi);
+            let alpha_w_ds = (self.coefficient)(&x) * w * volume_form(&j);
+            f(alpha_w_ds, &basis_values, &(self.data)(&x));
+        }
+    }
+}
+
+impl<'a, T, D, C, S, Q, Data> BoundaryPatchAssembler<'a, T, D, C, S, Q, fn(&OPoint<T, D>) -> T, Data>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    Q: Quadrature<T, C::ReferenceDim>,
+    Data: Fn(&OPoint<T, D>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, D, C::ReferenceDim, S>,
+{
+    /// Constructs an assembler for the consistent nodal loads of the given traction on the
+    /// patch with the given index.
+    ///
+    /// This corresponds to a Robin assembler with unit coefficient, and only the vector
+    /// is meaningful.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patch index is out of bounds.
+    pub fn traction(patches: &'a BoundaryPatches<T, D, C>, patch_index: usize, quadrature: Q, traction: Data) -> Self {
+        Self::robin(patches, patch_index, quadrature, |_| T::one(), traction)
+    }
+}
+
+impl<'a, T, D, C, S, Q, Coefficient, Data> ElementConnectivityAssembler
+    for BoundaryPatchAssembler<'a, T, D, C, S, Q, Coefficient, Data>
+where
+    T: Scalar,
+    D: DimName,
+    S: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn solution_dim(&self) -> usize {
+        S::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.patches.num_volume_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.patches.surface_mesh().connectivity()[self.elements.start + element_index]
+            .vertex_indices()
+            .len()
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        let boundary_to_volume = self.patches.boundary_to_volume();
+        let face = &self.patches.surface_mesh().connectivity()[self.elements.start + element_index];
+        for (volume_node, &boundary_node) in output.iter_mut().zip(face.vertex_indices()) {
+            *volume_node = boundary_to_volume[boundary_node];
+        }
+    }
+}
+
+impl<'a, T, D, C, S, Q, Coefficient, Data> ElementMatrixAssembler<T>
+    for BoundaryPatchAssembler<'a, T, D, C, S, Q, Coefficient, Data>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    Q: Quadrature<T, C::ReferenceDim>,
+    Coefficient: Fn(&OPoint<T, D>) -> T,
+    Data: Fn(&OPoint<T, D>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, D, C::ReferenceDim, S>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let s = S::dim();
+        output.fill(T::zero());
+        self.integrate_element(element_index, |alpha_w_ds, phi, _| {
+            for (i, &phi_i) in phi.iter().enumerate() {
+                for (j, &phi_j) in phi.iter().enumerate() {
+                    let m_ij = alpha_w_ds * phi_i * phi_j;
+                    for k in 0..s {
+                        output[(s * i + k, s * j + k)] += m_ij;
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
+
+impl<'a, T, D, C, S, Q, Coefficient, Data> ElementVectorAssembler<T>
+    for BoundaryPatchAssembler<'a, T, D, C, S, Q, Coefficient, Data>
+where
+    T: Real,
+    D: SmallDim,
+    S: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    Q: Quadrature<T, C::ReferenceDim>,
+    Coefficient: Fn(&OPoint<T, D>) -> T,
+    Data: Fn(&OPoint<T, D>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, D, C::ReferenceDim, S>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = S::dim();
+        output.fill(T::zero());
+        self.integrate_element(element_index, |alpha_w_ds, phi, g| {
+            for (i, &phi_i) in phi.iter().enumerate() {
+                for k in 0..s {
+                    output[s * i + k] += alpha_w_ds * phi_i * g[k];
+                }
+            }
+        });
+        Ok(())
+    }
+}
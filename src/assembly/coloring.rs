@@ -0,0 +1,292 @@
+use crate::assembly::local::ElementConnectivityAssembler;
+use eyre::eyre;
+use fenris_nested_vec::NestedVec;
+use fenris_paradis::DisjointSubsets;
+
+/// The strategy used to order elements when computing an [`ElementColoring`].
+///
+/// Greedy coloring assigns colors to elements one at a time, and the order in which elements
+/// are visited can strongly affect the resulting number of colors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ElementColoringStrategy {
+    /// Visit elements in the order given by their element index.
+    #[default]
+    Greedy,
+    /// Visit elements in order of decreasing degree, where the degree of an element is the
+    /// number of other elements that share at least one node with the element.
+    ///
+    /// Ties are broken by element index.
+    LargestDegreeFirst,
+}
+
+/// A coloring of the elements of an assembler.
+///
+/// Two elements that share a node never have the same color. Consequently, the local
+/// contributions of all elements of the same color can be added to global vectors
+/// and matrices in parallel without any synchronization.
+///
+/// The parallel assemblers, such as [`CsrParAssembler`](crate::assembly::global::CsrParAssembler),
+/// take the coloring as a set of [`DisjointSubsets`], which is obtained with
+/// [`to_disjoint_subsets`](Self::to_disjoint_subsets) or the corresponding [`From`] conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementColoring {
+    num_elements: usize,
+    colors: NestedVec<usize>,
+    // The nodes of each element, as given by the assembler that was colored
+    element_nodes: NestedVec<usize>,
+}
+
+impl ElementColoring {
+    /// The total number of elements across all colors.
+    pub fn num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    pub fn num_colors(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns the (sorted) indices of the elements with the given color.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the color index is out of bounds.
+    pub fn color(&self, color_index: usize) -> &[usize] {
+        self.colors
+            .get(color_index)
+            .expect("Color index out of bounds")
+    }
+
+    /// Returns an iterator over the element indices of each color.
+    pub fn colors(&self) -> impl Iterator<Item = &[usize]> {
+        self.colors.iter()
+    }
+
+    /// Verifies that the coloring is valid for the given assembler.
+    ///
+    /// A coloring is valid if every element of the assembler is assigned exactly one color,
+    /// and no two elements of the same color share a node.
+    pub fn verify(&self, assembler: &impl ElementConnectivityAssembler) -> eyre::Result<()> {
+        let num_elements = assembler.num_elements();
+        if self.num_elements != num_elements {
+            return Err(eyre!(
+                "Coloring has {} elements, but assembler has {} elements",
+                self.num_elements,
+                num_elements
+            ));
+        }
+
+        let mut element_color = vec![None; num_elements];
+        for (color_index, elements) in self.colors().enumerate() {
+            for &element_index in elements {
+                let color = element_color
+                    .get_mut(element_index)
+                    .ok_or_else(|| eyre!("Element index {} out of bounds", element_index))?;
+                if let Some(other_color) = color.replace(color_index) {
+                    return Err(eyre!(
+                        "Element {} is assigned to both color {} and color {}",
+                        element_index,
+                        other_color,
+                        color_index
+                    ));
+                }
+            }
+        }
+
+        if let Some(element_index) = element_color.iter().position(Option::is_none) {
+            return Err(eyre!("Element {} is not assigned a color", element_index));
+        }
+
+        // For each node, store the last element that visited the node along with its color
+        let mut node_visitor: Vec<Option<(usize, usize)>> = vec![None; assembler.num_nodes()];
+        let mut nodes = Vec::new();
+        for (color_index, elements) in self.colors().enumerate() {
+            for &element_index in elements {
+                nodes.resize(assembler.element_node_count(element_index), 0);
+                assembler.populate_element_nodes(&mut nodes, element_index);
+                for &node_index in &nodes {
+                    let visitor = node_visitor
+                        .get_mut(node_index)
+                        .ok_or_else(|| eyre!("Node index {} out of bounds", node_index))?;
+                    match *visitor {
+                        Some((other_element, other_color))
+                            if other_color == color_index && other_element != element_index =>
+                        {
+                            return Err(eyre!(
+                                "Elements {} and {} share node {}, but are both assigned color {}",
+                                other_element,
+                                element_index,
+                                node_index,
+                                color_index
+                            ));
+                        }
+                        _ => *visitor = Some((element_index, color_index)),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Converts the coloring to a set of [`DisjointSubsets`] suitable for parallel assembly.
+    ///
+    /// Each color is represented by one instance of [`DisjointSubsets`], in which every subset
+    /// consists of the nodes of an element, labeled by the element index. The nodes are those of
+    /// the assembler that was colored.
+    pub fn to_disjoint_subsets(&self) -> Vec<DisjointSubsets> {
+        self.colors()
+            .map(|elements| {
+                let mut subsets = NestedVec::new();
+                for &element_index in elements {
+                    subsets.push(self.element_nodes.get(element_index).unwrap());
+                }
+                DisjointSubsets::try_from_disjoint_subsets(subsets, elements.to_vec())
+                    .expect("Elements of the same color never share nodes")
+            })
+            .collect()
+    }
+}
+
+impl From<ElementColoring> for Vec<DisjointSubsets> {
+    fn from(coloring: ElementColoring) -> Self {
+        coloring.to_disjoint_subsets()
+    }
+}
+
+impl From<&ElementColoring> for Vec<DisjointSubsets> {
+    fn from(coloring: &ElementColoring) -> Self {
+        coloring.to_disjoint_subsets()
+    }
+}
+
+/// Computes a coloring of the elements of the given assembler with the default strategy.
+///
+/// See [`color_elements_with_strategy`] for more information.
+pub fn color_elements(assembler: &impl ElementConnectivityAssembler) -> ElementColoring {
+    color_elements_with_strategy(assembler, ElementColoringStrategy::default())
+}
+
+/// Computes a coloring of the elements of the given assembler.
+///
+/// Elements are visited in the order determined by the given strategy. Each element is assigned
+/// the admissible color with the fewest elements so far, with ties broken by choosing the
+/// color with the smallest index. A new color is only introduced when no existing color is
+/// admissible. This keeps the number of elements in each color approximately balanced,
+/// which is beneficial for parallel assembly. The coloring is fully deterministic.
+pub fn color_elements_with_strategy(
+    assembler: &impl ElementConnectivityAssembler,
+    strategy: ElementColoringStrategy,
+) -> ElementColoring {
+    let num_elements = assembler.num_elements();
+    let element_nodes = collect_element_nodes(assembler);
+    let node_elements = compute_node_elements(&element_nodes, assembler.num_nodes());
+
+    let element_order: Vec<usize> = match strategy {
+        ElementColoringStrategy::Greedy => (0..num_elements).collect(),
+        ElementColoringStrategy::LargestDegreeFirst => {
+            let degrees = compute_element_degrees(&element_nodes, &node_elements);
+            let mut order: Vec<usize> = (0..num_elements).collect();
+            // The sort is stable, so ties are broken by element index
+            order.sort_by(|&a, &b| degrees[b].cmp(&degrees[a]));
+            order
+        }
+    };
+
+    let mut element_color: Vec<Option<usize>> = vec![None; num_elements];
+    let mut color_sizes: Vec<usize> = Vec::new();
+    // Stores, for each color, the last element for which the color was found to be inadmissible
+    let mut forbidden_by: Vec<usize> = Vec::new();
+
+    for &element_index in &element_order {
+        for &node_index in element_nodes.get(element_index).unwrap() {
+            for &neighbor in node_elements.get(node_index).unwrap() {
+                if let Some(color) = element_color[neighbor] {
+                    forbidden_by[color] = element_index;
+                }
+            }
+        }
+
+        let admissible_color = (0..color_sizes.len())
+            .filter(|&color| forbidden_by[color] != element_index)
+            .min_by_key(|&color| (color_sizes[color], color));
+
+        let color = admissible_color.unwrap_or_else(|| {
+            color_sizes.push(0);
+            forbidden_by.push(usize::MAX);
+            color_sizes.len() - 1
+        });
+        color_sizes[color] += 1;
+        element_color[element_index] = Some(color);
+    }
+
+    let mut elements_per_color = vec![Vec::new(); color_sizes.len()];
+    for (element_index, color) in element_color.into_iter().enumerate() {
+        let color = color.expect("All elements have been colored");
+        elements_per_color[color].push(element_index);
+    }
+
+    let mut colors = NestedVec::new();
+    for elements in &elements_per_color {
+        colors.push(elements);
+    }
+
+    ElementColoring {
+        num_elements,
+        colors,
+        element_nodes,
+    }
+}
+
+fn collect_element_nodes(assembler: &impl ElementConnectivityAssembler) -> NestedVec<usize> {
+    let mut element_nodes = NestedVec::new();
+    let mut nodes = Vec::new();
+    for element_index in 0..assembler.num_elements() {
+        nodes.resize(assembler.element_node_count(element_index), 0);
+        assembler.populate_element_nodes(&mut nodes, element_index);
+        element_nodes.push(&nodes);
+    }
+    element_nodes
+}
+
+/// Computes the (sorted and deduplicated) elements that each node belongs to.
+fn compute_node_elements(element_nodes: &NestedVec<usize>, num_nodes: usize) -> NestedVec<usize> {
+    let mut node_elements = vec![Vec::new(); num_nodes];
+    for (element_index, nodes) in element_nodes.iter().enumerate() {
+        for &node_index in nodes {
+            let elements: &mut Vec<usize> = &mut node_elements[node_index];
+            // Elements are visited in order, so duplicate nodes within an element can
+            // only lead to duplicates at the end of the list
+            if elements.last() != Some(&element_index) {
+                elements.push(element_index);
+            }
+        }
+    }
+
+    let mut result = NestedVec::new();
+    for elements in &node_elements {
+        result.push(elements);
+    }
+    result
+}
+
+/// Computes the number of distinct neighbors of each element.
+fn compute_element_degrees(element_nodes: &NestedVec<usize>, node_elements: &NestedVec<usize>) -> Vec<usize> {
+    let mut last_visitor = vec![usize::MAX; element_nodes.len()];
+    element_nodes
+        .iter()
+        .enumerate()
+        .map(|(element_index, nodes)| {
+            let mut degree = 0;
+            for &node_index in nodes {
+                for &neighbor in node_elements.get(node_index).unwrap() {
+                    if neighbor != element_index && last_visitor[neighbor] != element_index {
+                        last_visitor[neighbor] = element_index;
+                        degree += 1;
+                    }
+                }
+            }
+            degree
+        })
+        .collect()
+}
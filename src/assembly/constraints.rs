@@ -0,0 +1,350 @@
+use crate::nalgebra::{DVector, DVectorView};
+use crate::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use crate::Real;
+use eyre::bail;
+
+/// An affine constraint $u_i = \sum_j c_j u_j + b$ on a single degree of freedom.
+///
+/// The degree of freedom $u_i$ is the *constrained* degree of freedom, and the degrees of freedom
+/// $u_j$ are its *masters*, with coefficients $c_j$. The constant $b$ is the *inhomogeneity*.
+/// A constraint without masters prescribes the value of the degree of freedom, like a Dirichlet
+/// boundary condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffineConstraint<T> {
+    dof: usize,
+    masters: Vec<(usize, T)>,
+    inhomogeneity: T,
+}
+
+impl<T: Real> AffineConstraint<T> {
+    /// Constrains the degree of freedom to the linear combination of the given masters,
+    /// given as pairs of master degrees of freedom and coefficients.
+    pub fn new(dof: usize, masters: Vec<(usize, T)>) -> Self {
+        Self {
+            dof,
+            masters,
+            inhomogeneity: T::zero(),
+        }
+    }
+
+    /// Constrains the degree of freedom to the given value.
+    pub fn fixed(dof: usize, value: T) -> Self {
+        Self::new(dof, Vec::new()).with_inhomogeneity(value)
+    }
+
+    pub fn with_inhomogeneity(mut self, inhomogeneity: T) -> Self {
+        self.inhomogeneity = inhomogeneity;
+        self
+    }
+
+    /// The constrained degree of freedom.
+    pub fn dof(&self) -> usize {
+        self.dof
+    }
+
+    /// The master degrees of freedom and their coefficients.
+    pub fn masters(&self) -> &[(usize, T)] {
+        &self.masters
+    }
+
+    pub fn inhomogeneity(&self) -> T {
+        self.inhomogeneity
+    }
+}
+
+/// A set of affine constraints on the degrees of freedom of a linear system.
+///
+/// Affine constraints of the form $u_i = \sum_j c_{ij} u_j + b_i$ (see [`AffineConstraint`])
+/// arise for example from hanging nodes in adaptively refined meshes, periodic boundary
+/// conditions and tied interfaces. Degrees of freedom are indices into the global solution
+/// vector, with the same numbering as for [`DirichletBcs`](crate::assembly::DirichletBcs).
+///
+/// The constraints can be written as $\vec u = \vec C \hat{\vec u} + \vec g$, where
+/// $\hat{\vec u}$ is the vector of *reduced* degrees of freedom, consisting of the unconstrained
+/// degrees of freedom in ascending order. The linear system $\vec A \vec u = \vec f$ is condensed
+/// to the reduced system
+/// <div>$$
+///   \vec C^T \vec A \vec C \hat{\vec u} = \vec C^T (\vec f - \vec A \vec g)
+/// $$</div>
+/// with [`condense_system`](Self::condense_system), and the solution of the reduced system is
+/// expanded to the full solution with [`expand_solution`](Self::expand_solution).
+///
+/// Chained constraints, where a master is itself constrained, are resolved transitively when the
+/// constraints are constructed, so that no master of a resolved constraint is constrained.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffineConstraints<T> {
+    // Sorted and without duplicates
+    dofs: Vec<usize>,
+    // The resolved masters of constraint i are given by master_dofs[offsets[i] .. offsets[i + 1]],
+    // sorted and without duplicates, and none of them are constrained.
+    offsets: Vec<usize>,
+    master_dofs: Vec<usize>,
+    coefficients: Vec<T>,
+    inhomogeneities: Vec<T>,
+}
+
+impl<T: Real> AffineConstraints<T> {
+    /// Constructs the set of constraints, resolving chained constraints transitively.
+    ///
+    /// The constraints do not need to be sorted. If a master appears several times in a
+    /// resolved constraint, the coefficients are summed.
+    ///
+    /// Returns an error if a degree of freedom is constrained more than once, or if the
+    /// constraints are cyclic, for example if a degree of freedom is its own master.
+    pub fn from_constraints(constraints: impl IntoIterator<Item = AffineConstraint<T>>) -> eyre::Result<Self> {
+        let mut constraints: Vec<_> = constraints.into_iter().collect();
+        constraints.sort_by_key(|constraint| constraint.dof);
+        for pair in constraints.windows(2) {
+            if pair[0].dof == pair[1].dof {
+                bail!("DOF {} is constrained more than once", pair[0].dof);
+            }
+        }
+
+        let mut resolved = vec![None; constraints.len()];
+        let mut in_progress = vec![false; constraints.len()];
+        for index in 0..constraints.len() {
+            resolve_constraint(index, &constraints, &mut resolved, &mut in_progress)?;
+        }
+
+        let mut offsets = Vec::with_capacity(constraints.len() + 1);
+        let mut master_dofs = Vec::new();
+        let mut coefficients = Vec::new();
+        let mut inhomogeneities = Vec::with_capacity(constraints.len());
+        offsets.push(0);
+        for (masters, inhomogeneity) in resolved.into_iter().map(Option::unwrap) {
+            for (master, coefficient) in masters {
+                master_dofs.push(master);
+                coefficients.push(coefficient);
+            }
+            offsets.push(master_dofs.len());
+            inhomogeneities.push(inhomogeneity);
+        }
+
+        Ok(Self {
+            dofs: constraints
+                .iter()
+                .map(|constraint| constraint.dof)
+                .collect(),
+            offsets,
+            master_dofs,
+            coefficients,
+            inhomogeneities,
+        })
+    }
+
+    /// The constrained degrees of freedom, sorted in ascending order.
+    pub fn dofs(&self) -> &[usize] {
+        &self.dofs
+    }
+
+    /// Returns the resolved masters, coefficients and inhomogeneity of the constraint on the
+    /// given degree of freedom, or `None` if the degree of freedom is not constrained.
+    pub fn constraint(&self, dof: usize) -> Option<(&[usize], &[T], T)> {
+        let index = self.dofs.binary_search(&dof).ok()?;
+        let range = self.offsets[index]..self.offsets[index + 1];
+        Some((
+            &self.master_dofs[range.clone()],
+            &self.coefficients[range],
+            self.inhomogeneities[index],
+        ))
+    }
+
+    /// The number of constrained degrees of freedom.
+    pub fn len(&self) -> usize {
+        self.dofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dofs.is_empty()
+    }
+
+    fn check_bounds(&self, num_dofs: usize) -> eyre::Result<()> {
+        if let Some(&dof) = self.dofs.last() {
+            if dof >= num_dofs {
+                bail!(
+                    "Constrained DOF {} is out of bounds for a system with {} DOFs",
+                    dof,
+                    num_dofs
+                );
+            }
+        }
+        if let Some(&master) = self.master_dofs.iter().max() {
+            if master >= num_dofs {
+                bail!(
+                    "Master DOF {} is out of bounds for a system with {} DOFs",
+                    master,
+                    num_dofs
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the index of each unconstrained degree of freedom in the reduced system.
+    fn reduced_indices(&self, num_dofs: usize) -> Vec<Option<usize>> {
+        let mut constrained = self.dofs.iter().peekable();
+        let mut num_reduced = 0;
+        (0..num_dofs)
+            .map(|dof| {
+                if constrained.next_if_eq(&&dof).is_some() {
+                    None
+                } else {
+                    num_reduced += 1;
+                    Some(num_reduced - 1)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the matrix $\vec C$ that maps the reduced degrees of freedom to the full
+    /// degrees of freedom, excluding the inhomogeneities.
+    ///
+    /// Returns an error if a constrained or master degree of freedom is out of bounds.
+    pub fn expansion_matrix(&self, num_dofs: usize) -> eyre::Result<CsrMatrix<T>> {
+        self.check_bounds(num_dofs)?;
+        let reduced_indices = self.reduced_indices(num_dofs);
+        let mut coo = CooMatrix::new(num_dofs, num_dofs - self.len());
+        for (dof, reduced_index) in reduced_indices.iter().enumerate() {
+            if let Some(reduced_index) = reduced_index {
+                coo.push(dof, *reduced_index, T::one());
+            }
+        }
+        for (index, &dof) in self.dofs.iter().enumerate() {
+            let range = self.offsets[index]..self.offsets[index + 1];
+            for (&master, &coefficient) in self.master_dofs[range.clone()]
+                .iter()
+                .zip(&self.coefficients[range])
+            {
+                let master_index = reduced_indices[master].expect("Masters are never constrained");
+                coo.push(dof, master_index, coefficient);
+            }
+        }
+        Ok(CsrMatrix::from(&coo))
+    }
+
+    /// Returns the vector $\vec g$ of inhomogeneities.
+    fn inhomogeneity_vector(&self, num_dofs: usize) -> DVector<T> {
+        let mut g = DVector::zeros(num_dofs);
+        for (&dof, &b) in self.dofs.iter().zip(&self.inhomogeneities) {
+            g[dof] = b;
+        }
+        g
+    }
+
+    /// Condenses the linear system $\vec A \vec u = \vec f$ to the reduced system
+    /// $\vec C^T \vec A \vec C \hat{\vec u} = \vec C^T (\vec f - \vec A \vec g)$.
+    ///
+    /// The rows and columns associated with constrained degrees of freedom are eliminated, so that
+    /// the reduced system has one row for each unconstrained degree of freedom. The reduced
+    /// matrix is symmetric if $\vec A$ is symmetric.
+    ///
+    /// Returns an error if the matrix is not square, the dimensions of the matrix and right-hand
+    /// side are inconsistent, or a constrained or master degree of freedom is out of bounds.
+    pub fn condense_system(&self, matrix: &CsrMatrix<T>, rhs: &DVector<T>) -> eyre::Result<(CsrMatrix<T>, DVector<T>)> {
+        let n = matrix.nrows();
+        if matrix.ncols() != n {
+            bail!("Matrix must be square, but has dimensions {}x{}", n, matrix.ncols());
+        }
+        if rhs.len() != n {
+            bail!(
+                "Right-hand side has length {}, but the matrix has {} rows",
+                rhs.len(),
+                n
+            );
+        }
+
+        let c = self.expansion_matrix(n)?;
+        let c_t = c.transpose();
+        let g = self.inhomogeneity_vector(n);
+        let reduced_matrix = &c_t * &(matrix * &c);
+        let reduced_rhs = &c_t * &(rhs - matrix * &g);
+        Ok((reduced_matrix, reduced_rhs))
+    }
+
+    /// Expands the solution $\hat{\vec u}$ of the reduced system to the full solution
+    /// $\vec u = \vec C \hat{\vec u} + \vec g$.
+    ///
+    /// The number of degrees of freedom of the full system is the length of the reduced solution
+    /// plus the number of constrained degrees of freedom.
+    ///
+    /// Returns an error if a constrained or master degree of freedom is out of bounds.
+    pub fn expand_solution<'a>(&self, reduced_solution: impl Into<DVectorView<'a, T>>) -> eyre::Result<DVector<T>> {
+        let reduced_solution = reduced_solution.into();
+        let n = reduced_solution.len() + self.len();
+        self.check_bounds(n)?;
+
+        let reduced_indices = self.reduced_indices(n);
+        let mut u = self.inhomogeneity_vector(n);
+        for (dof, reduced_index) in reduced_indices.iter().enumerate() {
+            if let Some(reduced_index) = reduced_index {
+                u[dof] = reduced_solution[*reduced_index];
+            }
+        }
+        for (index, &dof) in self.dofs.iter().enumerate() {
+            let range = self.offsets[index]..self.offsets[index + 1];
+            for (&master, &coefficient) in self.master_dofs[range.clone()]
+                .iter()
+                .zip(&self.coefficients[range])
+            {
+                // Masters are never constrained, so their values are already final
+                let master_value = u[master].clone();
+                u[dof] += coefficient * master_value;
+            }
+        }
+        Ok(u)
+    }
+}
+
+/// The resolved masters and inhomogeneity of a constraint.
+type ResolvedConstraint<T> = (Vec<(usize, T)>, T);
+
+/// Resolves the constraint with the given index by recursively substituting constrained masters
+/// with their own resolved constraints.
+fn resolve_constraint<T: Real>(
+    index: usize,
+    constraints: &[AffineConstraint<T>],
+    resolved: &mut [Option<ResolvedConstraint<T>>],
+    in_progress: &mut [bool],
+) -> eyre::Result<()> {
+    if resolved[index].is_some() {
+        return Ok(());
+    }
+    let constraint = &constraints[index];
+    if in_progress[index] {
+        bail!("Constraints are cyclic: DOF {} depends on itself", constraint.dof);
+    }
+    in_progress[index] = true;
+
+    let mut masters = Vec::with_capacity(constraint.masters.len());
+    let mut inhomogeneity = constraint.inhomogeneity;
+    for &(master, coefficient) in &constraint.masters {
+        match constraints.binary_search_by_key(&master, |constraint| constraint.dof) {
+            Ok(master_index) => {
+                resolve_constraint(master_index, constraints, resolved, in_progress)?;
+                let (master_masters, master_inhomogeneity) = resolved[master_index]
+                    .as_ref()
+                    .expect("Constraint must be resolved");
+                masters.extend(
+                    master_masters
+                        .iter()
+                        .map(|&(j, c_j)| (j, coefficient * c_j)),
+                );
+                inhomogeneity += coefficient * *master_inhomogeneity;
+            }
+            Err(_) => masters.push((master, coefficient)),
+        }
+    }
+
+    masters.sort_by_key(|&(master, _)| master);
+    let mut merged: Vec<(usize, T)> = Vec::with_capacity(masters.len());
+    for (master, coefficient) in masters {
+        match merged.last_mut() {
+            Some((last, sum)) if *last == master => *sum += coefficient,
+            _ => merged.push((master, coefficient)),
+        }
+    }
+
+    in_progress[index] = false;
+    resolved[index] = Some((merged, inhomogeneity));
+    Ok(())
+}
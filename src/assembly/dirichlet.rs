@@ -0,0 +1,453 @@
+use crate::connectivity::Connectivity;
+use crate::mesh::Mesh;
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DVector, DVectorView, DefaultAllocator, DimName, OPoint, OVector, Scalar};
+use crate::nalgebra_sparse::{CsrMatrix, SparseEntry};
+use crate::Real;
+use eyre::{bail, eyre};
+
+/// Determines how Dirichlet boundary conditions are imposed on an assembled linear system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirichletEliminationMode {
+    /// Zero both the rows and the columns associated with constrained degrees of freedom.
+    ///
+    /// The contributions of the prescribed values are moved to the right-hand side,
+    /// so that the symmetry of the system matrix is preserved.
+    #[default]
+    Symmetric,
+    /// Only zero the rows associated with constrained degrees of freedom.
+    ///
+    /// This is cheaper than symmetric elimination, but the resulting system matrix is in general
+    /// not symmetric.
+    RowOnly,
+}
+
+/// Dirichlet boundary conditions, given as a set of constrained degrees of freedom with
+/// prescribed values.
+///
+/// Degrees of freedom are indices into the global solution vector. With solution dimension $s$,
+/// component $i$ of node $I$ corresponds to the degree of freedom $s I + i$, so that individual
+/// components of a node can be constrained independently.
+///
+/// The boundary conditions are imposed on an assembled system $\vec A \vec u = \vec b$ with
+/// [`apply_to_system`](Self::apply_to_system). Each constrained row is replaced by the equation
+/// $\alpha u_i = \alpha g_i$, where $g_i$ is the prescribed value and $\alpha$ is the
+/// [diagonal value](Self::with_diagonal_value), which defaults to one. Choosing $\alpha$ on the
+/// order of the diagonal entries of $\vec A$ may improve the conditioning of the system.
+///
+/// Reaction forces at the constrained degrees of freedom can be recovered after solving with
+/// [`extract_constrained_rows`](Self::extract_constrained_rows) for linear systems, or
+/// [`reactions_from_residual`](Self::reactions_from_residual) if the residual is available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirichletBcs<T> {
+    // Sorted and without duplicates
+    dofs: Vec<usize>,
+    values: Vec<T>,
+    diagonal_value: T,
+    mode: DirichletEliminationMode,
+}
+
+impl<T: Real> DirichletBcs<T> {
+    /// Constructs boundary conditions from constrained degrees of freedom and their prescribed
+    /// values.
+    ///
+    /// The degrees of freedom do not need to be sorted. A degree of freedom may be given more than
+    /// once, provided that the prescribed values are identical.
+    ///
+    /// Returns an error if the number of degrees of freedom and values differ, or if a degree of
+    /// freedom is given with conflicting values.
+    pub fn from_dofs_and_values(dofs: Vec<usize>, values: Vec<T>) -> eyre::Result<Self> {
+        if dofs.len() != values.len() {
+            bail!(
+                "Number of Dirichlet DOFs ({}) does not match the number of values ({})",
+                dofs.len(),
+                values.len()
+            );
+        }
+
+        let mut pairs: Vec<_> = dofs.into_iter().zip(values).collect();
+        pairs.sort_by_key(|&(dof, _)| dof);
+        let mut dofs = Vec::with_capacity(pairs.len());
+        let mut values: Vec<T> = Vec::with_capacity(pairs.len());
+        for (dof, value) in pairs {
+            if dofs.last() == Some(&dof) {
+                let existing = *values.last().unwrap();
+                if existing != value {
+                    bail!(
+                        "Dirichlet DOF {} is prescribed conflicting values {} and {}",
+                        dof,
+                        existing,
+                        value
+                    );
+                }
+            } else {
+                dofs.push(dof);
+                values.push(value);
+            }
+        }
+
+        Ok(Self {
+            dofs,
+            values,
+            diagonal_value: T::one(),
+            mode: DirichletEliminationMode::default(),
+        })
+    }
+
+    /// Constructs homogeneous boundary conditions, i.e. all prescribed values are zero.
+    ///
+    /// Duplicate degrees of freedom are permitted.
+    pub fn homogeneous(dofs: Vec<usize>) -> Self {
+        let values = vec![T::zero(); dofs.len()];
+        Self::from_dofs_and_values(dofs, values).expect("Homogeneous values can never conflict")
+    }
+
+    /// Constructs boundary conditions for the given components of the boundary vertices of
+    /// the mesh for which the predicate holds.
+    ///
+    /// The prescribed value for component $i$ at a vertex with coordinates $\vec x$ is given by
+    /// `value_fn(x, i)`. See [`find_boundary_dofs`] for how the degrees of freedom are determined.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any component is not smaller than `solution_dim`.
+    pub fn from_mesh_boundary<D, C>(
+        mesh: &Mesh<T, D, C>,
+        solution_dim: usize,
+        components: &[usize],
+        predicate: impl Fn(&OPoint<T, D>) -> bool,
+        value_fn: impl Fn(&OPoint<T, D>, usize) -> T,
+    ) -> Self
+    where
+        D: DimName,
+        C: Connectivity,
+        C::FaceConnectivity: Connectivity,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let dofs = find_boundary_dofs(mesh, solution_dim, components, predicate);
+        let values = dofs
+            .iter()
+            .map(|&dof| value_fn(&mesh.vertices()[dof / solution_dim], dof % solution_dim))
+            .collect();
+        Self::from_dofs_and_values(dofs, values).expect("DOFs are unique by construction")
+    }
+
+    /// Sets the value placed on the diagonal of constrained rows.
+    pub fn with_diagonal_value(mut self, diagonal_value: T) -> Self {
+        self.diagonal_value = diagonal_value;
+        self
+    }
+
+    /// Sets the elimination mode used by [`apply_to_system`](Self::apply_to_system).
+    pub fn with_mode(mut self, mode: DirichletEliminationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The constrained degrees of freedom, sorted in ascending order.
+    pub fn dofs(&self) -> &[usize] {
+        &self.dofs
+    }
+
+    /// The prescribed values, in the same order as [`dofs`](Self::dofs).
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn diagonal_value(&self) -> T {
+        self.diagonal_value
+    }
+
+    pub fn mode(&self) -> DirichletEliminationMode {
+        self.mode
+    }
+
+    /// The number of constrained degrees of freedom.
+    pub fn len(&self) -> usize {
+        self.dofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dofs.is_empty()
+    }
+
+    /// Imposes the boundary conditions on the linear system $\vec A \vec u = \vec b$.
+    ///
+    /// Each constrained row $i$ of $\vec A$ is zeroed, the diagonal entry is set to the
+    /// diagonal value $\alpha$ and $b_i$ is set to $\alpha g_i$. With
+    /// [symmetric elimination](DirichletEliminationMode::Symmetric), each constrained column $j$
+    /// is furthermore zeroed after its contribution $A_{kj} g_j$ has been subtracted from the
+    /// right-hand side of every unconstrained row $k$. The sparsity pattern of the matrix is
+    /// left unchanged, and the pattern does not need to be symmetric.
+    ///
+    /// Returns an error without modifying the system if the matrix is not square, the dimensions
+    /// of the matrix and right-hand side are inconsistent, or a constrained degree of freedom
+    /// is out of bounds or does not have a diagonal entry in the sparsity pattern.
+    pub fn apply_to_system(&self, matrix: &mut CsrMatrix<T>, rhs: &mut DVector<T>) -> eyre::Result<()> {
+        let n = matrix.nrows();
+        if matrix.ncols() != n {
+            bail!("Matrix must be square, but has dimensions {}x{}", n, matrix.ncols());
+        }
+        if rhs.len() != n {
+            bail!(
+                "Right-hand side has length {}, but the matrix has {} rows",
+                rhs.len(),
+                n
+            );
+        }
+        for &dof in &self.dofs {
+            if dof >= n {
+                bail!("Dirichlet DOF {} is out of bounds for a system with {} DOFs", dof, n);
+            }
+            if !matches!(matrix.row(dof).get_entry(dof), Some(SparseEntry::NonZero(_))) {
+                bail!(
+                    "Dirichlet DOF {} does not have a diagonal entry in the sparsity pattern",
+                    dof
+                );
+            }
+        }
+
+        // Prescribed values for each row, if the row is constrained
+        let mut prescribed = vec![None; n];
+        for (&dof, &value) in self.dofs.iter().zip(&self.values) {
+            prescribed[dof] = Some(value);
+        }
+
+        for (i, mut row) in matrix.row_iter_mut().enumerate() {
+            let (cols, values) = row.cols_and_values_mut();
+            if let Some(g_i) = prescribed[i] {
+                for (&j, a_ij) in cols.iter().zip(values) {
+                    *a_ij = if j == i { self.diagonal_value } else { T::zero() };
+                }
+                rhs[i] = self.diagonal_value * g_i;
+            } else if self.mode == DirichletEliminationMode::Symmetric {
+                for (&j, a_ij) in cols.iter().zip(values) {
+                    if let Some(g_j) = prescribed[j] {
+                        rhs[i] -= *a_ij * g_j;
+                        *a_ij = T::zero();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the rows of the system $\vec A \vec u = \vec b$ associated with the constrained
+    /// degrees of freedom.
+    ///
+    /// The constrained rows are overwritten by [`apply_to_system`](Self::apply_to_system), but
+    /// they are needed to compute the reaction forces once the system has been solved. The rows
+    /// must therefore be extracted from the unconstrained system *before* the boundary
+    /// conditions are imposed. See [`ConstrainedRows::compute_reactions`].
+    ///
+    /// Returns an error if the dimensions of the matrix and right-hand side are inconsistent,
+    /// or a constrained degree of freedom is out of bounds.
+    pub fn extract_constrained_rows(
+        &self,
+        matrix: &CsrMatrix<T>,
+        rhs: &DVector<T>,
+    ) -> eyre::Result<ConstrainedRows<T>> {
+        let n = matrix.nrows();
+        if rhs.len() != n {
+            bail!(
+                "Right-hand side has length {}, but the matrix has {} rows",
+                rhs.len(),
+                n
+            );
+        }
+
+        let mut offsets = Vec::with_capacity(self.dofs.len() + 1);
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        let mut constrained_rhs = Vec::with_capacity(self.dofs.len());
+        offsets.push(0);
+        for &dof in &self.dofs {
+            if dof >= n {
+                bail!("Dirichlet DOF {} is out of bounds for a system with {} DOFs", dof, n);
+            }
+            let row = matrix.row(dof);
+            col_indices.extend_from_slice(row.col_indices());
+            values.extend_from_slice(row.values());
+            offsets.push(col_indices.len());
+            constrained_rhs.push(rhs[dof]);
+        }
+        let rows = CsrMatrix::try_from_csr_data(self.dofs.len(), matrix.ncols(), offsets, col_indices, values)
+            .map_err(|err| eyre!("Failed to extract constrained rows: {}", err))?;
+
+        Ok(ConstrainedRows {
+            dofs: self.dofs.clone(),
+            rows,
+            rhs: constrained_rhs,
+        })
+    }
+
+    /// Returns the reactions at the constrained degrees of freedom given the residual
+    /// $\vec r = \vec A \vec u - \vec b$ of the unconstrained system.
+    ///
+    /// This is useful for nonlinear problems, where the residual $\vec r(\vec u)$ is typically
+    /// assembled anyway, e.g. as the difference of internal and external forces.
+    ///
+    /// Returns an error if a constrained degree of freedom is out of bounds for the residual.
+    pub fn reactions_from_residual<'a>(
+        &self,
+        residual: impl Into<DVectorView<'a, T>>,
+    ) -> eyre::Result<DirichletReactions<T>> {
+        let residual = residual.into();
+        let values = self
+            .dofs
+            .iter()
+            .map(|&dof| {
+                residual.get(dof).copied().ok_or_else(|| {
+                    eyre!(
+                        "Dirichlet DOF {} is out of bounds for a residual of length {}",
+                        dof,
+                        residual.len()
+                    )
+                })
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(DirichletReactions {
+            dofs: self.dofs.clone(),
+            values,
+        })
+    }
+}
+
+/// The rows of a linear system associated with constrained degrees of freedom.
+///
+/// See [`DirichletBcs::extract_constrained_rows`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstrainedRows<T> {
+    dofs: Vec<usize>,
+    rows: CsrMatrix<T>,
+    rhs: Vec<T>,
+}
+
+impl<T: Real> ConstrainedRows<T> {
+    /// The constrained degrees of freedom, sorted in ascending order.
+    pub fn dofs(&self) -> &[usize] {
+        &self.dofs
+    }
+
+    /// The constrained rows of the matrix, in the same order as [`dofs`](Self::dofs).
+    pub fn rows(&self) -> &CsrMatrix<T> {
+        &self.rows
+    }
+
+    /// Computes the reactions $\vec r = \vec A \vec u - \vec b$ at the constrained degrees of
+    /// freedom for the solution $\vec u$.
+    ///
+    /// Returns an error if the length of `u` does not match the number of columns of the matrix.
+    pub fn compute_reactions<'a>(&self, u: impl Into<DVectorView<'a, T>>) -> eyre::Result<DirichletReactions<T>> {
+        let u = u.into();
+        if u.len() != self.rows.ncols() {
+            bail!(
+                "Solution vector has length {}, but the matrix has {} columns",
+                u.len(),
+                self.rows.ncols()
+            );
+        }
+        let values = self
+            .rows
+            .row_iter()
+            .zip(&self.rhs)
+            .map(|(row, &b_i)| {
+                row.col_indices()
+                    .iter()
+                    .zip(row.values())
+                    .fold(-b_i, |r_i, (&j, &a_ij)| r_i + a_ij * u[j])
+            })
+            .collect();
+        Ok(DirichletReactions {
+            dofs: self.dofs.clone(),
+            values,
+        })
+    }
+}
+
+/// Reaction forces at the degrees of freedom constrained by Dirichlet boundary conditions.
+///
+/// The reaction at a constrained degree of freedom $i$ is the entry $r_i$ of the residual
+/// $\vec r = \vec A \vec u - \vec b$ of the *unconstrained* system, i.e. the force that the
+/// support must exert on the body in order to maintain equilibrium. In the absence of other
+/// forces, the reactions therefore balance the applied loads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirichletReactions<T> {
+    dofs: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T: Real> DirichletReactions<T> {
+    /// The constrained degrees of freedom, sorted in ascending order.
+    pub fn dofs(&self) -> &[usize] {
+        &self.dofs
+    }
+
+    /// The reactions, in the same order as [`dofs`](Self::dofs).
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Computes the net reaction force on the given set of nodes.
+    ///
+    /// The solution dimension is given by `D`, i.e. component $i$ of node $I$ corresponds to the
+    /// degree of freedom $s I + i$ with $s$ = `D::dim()`. Components that are not constrained
+    /// do not contribute to the net force.
+    pub fn sum_reaction_force<D>(&self, nodes: &[usize]) -> OVector<T, D>
+    where
+        D: DimName,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let s = D::dim();
+        let mut nodes = nodes.to_vec();
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        let mut force = OVector::<T, D>::zeros();
+        for (&dof, &r) in self.dofs.iter().zip(&self.values) {
+            if nodes.binary_search(&(dof / s)).is_ok() {
+                force[dof % s] += r;
+            }
+        }
+        force
+    }
+}
+
+/// Finds the degrees of freedom associated with the given components of the boundary vertices of
+/// the mesh for which the predicate holds.
+///
+/// Boundary vertices are determined by [`Mesh::find_boundary_vertices`], and the
+/// degrees of freedom are numbered with solution dimension `solution_dim`, i.e. component $i$
+/// of vertex $I$ corresponds to degree of freedom $s I + i$. The result is sorted.
+///
+/// # Panics
+///
+/// Panics if any component is not smaller than `solution_dim`.
+pub fn find_boundary_dofs<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    solution_dim: usize,
+    components: &[usize],
+    predicate: impl Fn(&OPoint<T, D>) -> bool,
+) -> Vec<usize>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    C::FaceConnectivity: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    assert!(
+        components.iter().all(|&i| i < solution_dim),
+        "Components must be smaller than the solution dimension"
+    );
+    let mut components = components.to_vec();
+    components.sort_unstable();
+    components.dedup();
+
+    mesh.find_boundary_vertices()
+        .into_iter()
+        .filter(|&vertex| predicate(&mesh.vertices()[vertex]))
+        .flat_map(|vertex| components.iter().map(move |&i| solution_dim * vertex + i))
+        .collect()
+}
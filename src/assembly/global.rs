@@ -1,8 +1,10 @@
 use crate::assembly::local::{
     ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
 };
+use crate::error::AssemblyError;
 use crate::space::FiniteElementConnectivity;
-use crate::Real;
+use crate::{Real, Symmetry};
+use eyre::eyre;
 use fenris_nested_vec::NestedVec;
 use fenris_paradis::adapter::BlockAdapter;
 use fenris_paradis::coloring::sequential_greedy_coloring;
@@ -11,7 +13,7 @@ use fenris_sparse::ParallelCsrRowCollection;
 use itertools::{enumerate, izip};
 use nalgebra::base::storage::Storage;
 use nalgebra::{DMatrix, DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DimName, Dyn, Matrix, Scalar, U1};
-use nalgebra_sparse::{pattern::SparsityPattern, CsrMatrix};
+use nalgebra_sparse::{pattern::SparsityPattern, CooMatrix, CsrMatrix};
 use num::integer::div_ceil;
 use parking_lot::Mutex;
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
@@ -60,64 +62,91 @@ impl<T: Scalar> Default for CsrAssemblerWorkspace<T> {
 impl<T: Scalar> CsrAssembler<T> {
     /// Assembles the sparsity pattern associated with the given element assembler.
     ///
-    /// The implementation explicitly avoids storing duplicate entries in order to prevent
-    /// excessive memory costs.
+    /// See [`build_csr_pattern`].
     pub fn assemble_pattern(&self, element_assembler: &impl ElementConnectivityAssembler) -> SparsityPattern {
-        let sdim = element_assembler.solution_dim();
-        let num_nodes = element_assembler.num_nodes();
-        let num_rows = sdim * num_nodes;
-        let mut node_sets: Vec<FxHashSet<usize>> = vec![FxHashSet::default(); num_nodes];
-        let mut element_global_nodes = Vec::new();
-        for i in 0..element_assembler.num_elements() {
-            let element_node_count = element_assembler.element_node_count(i);
-            element_global_nodes.resize(element_node_count, usize::MAX);
-            element_assembler.populate_element_nodes(&mut element_global_nodes, i);
+        build_csr_pattern(element_assembler)
+    }
+}
 
-            for &node_i in &element_global_nodes {
-                for &node_j in &element_global_nodes {
-                    node_sets[node_i].insert(node_j);
-                }
-            }
-        }
+/// Builds the CSR sparsity pattern associated with the given element assembler.
+///
+/// Each node is expanded into a `solution_dim x solution_dim` block of entries. The pattern only
+/// depends on the connectivity of the assembler, so it can be built once and reused across many
+/// assemblies, for example with [`CsrAssembler::assemble_into_preallocated_csr`].
+///
+/// The implementation explicitly avoids storing duplicate entries in order to prevent
+/// excessive memory costs.
+pub fn build_csr_pattern<A: ElementConnectivityAssembler + ?Sized>(element_assembler: &A) -> SparsityPattern {
+    build_csr_pattern_impl(element_assembler, false)
+}
 
-        let mut offsets = Vec::with_capacity(num_rows);
-        offsets.push(0);
-        let mut current_offset = 0;
-        for node_set in &node_sets {
-            for _ in 0..sdim {
-                let count = sdim * node_set.len();
-                offsets.push(current_offset + count);
-                current_offset += count;
+/// Builds the lower triangular part of the CSR sparsity pattern associated with the given
+/// element assembler.
+///
+/// The pattern consists of the entries of the pattern given by [`build_csr_pattern`] that are on
+/// or below the diagonal. It is intended for storing symmetric matrices,
+/// see [`CsrAssembler::assemble_with_symmetry`].
+pub fn build_lower_triangular_csr_pattern<A: ElementConnectivityAssembler + ?Sized>(
+    element_assembler: &A,
+) -> SparsityPattern {
+    build_csr_pattern_impl(element_assembler, true)
+}
+
+fn build_csr_pattern_impl<A: ElementConnectivityAssembler + ?Sized>(
+    element_assembler: &A,
+    lower_triangular: bool,
+) -> SparsityPattern {
+    let sdim = element_assembler.solution_dim();
+    let num_nodes = element_assembler.num_nodes();
+    let num_rows = sdim * num_nodes;
+    let mut node_sets: Vec<FxHashSet<usize>> = vec![FxHashSet::default(); num_nodes];
+    let mut element_global_nodes = Vec::new();
+    for i in 0..element_assembler.num_elements() {
+        let element_node_count = element_assembler.element_node_count(i);
+        element_global_nodes.resize(element_node_count, usize::MAX);
+        element_assembler.populate_element_nodes(&mut element_global_nodes, i);
+
+        for &node_i in &element_global_nodes {
+            for &node_j in &element_global_nodes {
+                node_sets[node_i].insert(node_j);
             }
         }
-        assert_eq!(offsets.len(), num_rows + 1);
+    }
 
-        let mut col_indices = Vec::with_capacity(*offsets.last().unwrap());
-        let mut node_buffer: Vec<usize> = Vec::new();
-        for node_set in &node_sets {
-            node_buffer.clear();
-            node_buffer.extend(node_set);
-            node_buffer.sort_unstable();
-            // We have sdim identical rows (in terms of pattern)
-            for _ in 0..sdim {
-                for node_j in &node_buffer {
-                    for j in 0..sdim {
-                        let col_idx = sdim * node_j + j;
+    let mut offsets = Vec::with_capacity(num_rows + 1);
+    offsets.push(0);
+    let max_nnz = node_sets
+        .iter()
+        .map(|node_set| sdim * sdim * node_set.len())
+        .sum();
+    let mut col_indices = Vec::with_capacity(max_nnz);
+    let mut node_buffer: Vec<usize> = Vec::new();
+    for (node_i, node_set) in node_sets.iter().enumerate() {
+        node_buffer.clear();
+        node_buffer.extend(node_set);
+        node_buffer.sort_unstable();
+        // Without the triangular restriction, we have sdim identical rows (in terms of pattern)
+        for i in 0..sdim {
+            let row_idx = sdim * node_i + i;
+            for node_j in &node_buffer {
+                for j in 0..sdim {
+                    let col_idx = sdim * node_j + j;
+                    if !lower_triangular || col_idx <= row_idx {
                         col_indices.push(col_idx);
                     }
                 }
             }
+            offsets.push(col_indices.len());
         }
+    }
 
-        assert_eq!(*offsets.last().unwrap(), col_indices.len());
+    assert_eq!(offsets.len(), num_rows + 1);
 
-        debug_assert!(
-            SparsityPattern::try_from_offsets_and_indices(num_rows, num_rows, offsets.clone(), col_indices.clone())
-                .is_ok(),
-            "Internal error: constructed sparsity pattern is not valid. This is a bug!"
-        );
-        unsafe { SparsityPattern::from_offset_and_indices_unchecked(num_rows, num_rows, offsets, col_indices) }
-    }
+    debug_assert!(
+        SparsityPattern::try_from_offsets_and_indices(num_rows, num_rows, offsets.clone(), col_indices.clone()).is_ok(),
+        "Internal error: constructed sparsity pattern is not valid. This is a bug!"
+    );
+    unsafe { SparsityPattern::from_offset_and_indices_unchecked(num_rows, num_rows, offsets, col_indices) }
 }
 
 impl<T: Real> CsrAssembler<T> {
@@ -130,60 +159,434 @@ impl<T: Real> CsrAssembler<T> {
         Ok(matrix)
     }
 
+    /// Assembles the matrix associated with the given element assembler and *adds* it to
+    /// the given CSR matrix.
+    ///
+    /// Returns an error if an element contributes to an entry that is not present in the
+    /// sparsity pattern of the matrix. In this case, the values of the matrix are unspecified.
     pub fn assemble_into_csr(
         &self,
         csr: &mut CsrMatrix<T>,
         element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        self.assemble_into_csr_impl(csr, T::one(), element_assembler, Symmetry::NonSymmetric)
+    }
+
+    /// Assembles the matrix associated with the given element assembler, scales it by `alpha`
+    /// and *adds* it to the given CSR matrix.
+    ///
+    /// This computes $B \leftarrow B + \alpha A$ without assembling $A$ separately, which is useful
+    /// for combining matrices such as $M + \Delta t^2 K$ in time integrators. The sparsity pattern
+    /// of $B$ must contain the sparsity pattern of $A$.
+    ///
+    /// Returns an error if the dimensions of the matrix are not compatible with the assembler,
+    /// or if an element contributes to an entry that is not present in the sparsity pattern
+    /// of the matrix. In the latter case, the values of the matrix are unspecified.
+    pub fn assemble_scaled_add_into(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        alpha: T,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        let num_rows = element_assembler.solution_dim() * element_assembler.num_nodes();
+        if csr.nrows() != num_rows || csr.ncols() != num_rows {
+            return Err(eyre!(
+                "Matrix has dimensions {}x{}, but the element assembler requires {}x{}",
+                csr.nrows(),
+                csr.ncols(),
+                num_rows,
+                num_rows
+            ));
+        }
+        self.assemble_into_csr_impl(csr, alpha, element_assembler, Symmetry::NonSymmetric)
+    }
+
+    /// Assembles the matrix associated with the given symmetric element assembler, storing only
+    /// its lower triangular part.
+    ///
+    /// With [`Symmetry::NonSymmetric`], this is equivalent to [`assemble`](Self::assemble).
+    /// With [`Symmetry::Symmetric`], only entries on or below the diagonal are stored and
+    /// assembled, which roughly halves the memory and assembly cost. The full matrix can be
+    /// recovered with [`expand_lower_triangular_csr`] for solvers that require it.
+    ///
+    /// Returns an error if [`Symmetry::Symmetric`] is requested but the element assembler does
+    /// not declare its element matrices to be symmetric through
+    /// [`ElementMatrixAssembler::symmetry`]. In debug builds, the element matrices are
+    /// additionally checked to be symmetric up to a small tolerance.
+    pub fn assemble_with_symmetry(
+        &self,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+        symmetry: Symmetry,
+    ) -> eyre::Result<CsrMatrix<T>> {
+        match symmetry {
+            Symmetry::NonSymmetric => self.assemble(element_assembler),
+            Symmetry::Symmetric => {
+                let pattern = build_lower_triangular_csr_pattern(element_assembler);
+                let initial_matrix_values = vec![T::zero(); pattern.nnz()];
+                let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern, initial_matrix_values)
+                    .expect("CSR data must be valid by definition");
+                self.assemble_lower_triangle_into_csr(&mut matrix, element_assembler)?;
+                Ok(matrix)
+            }
+        }
+    }
+
+    /// Assembles the lower triangular part of the matrix associated with the given symmetric
+    /// element assembler and *adds* it to the given CSR matrix.
+    ///
+    /// Element contributions above the diagonal are ignored, so the sparsity pattern of the
+    /// matrix only needs to contain the lower triangular part, as given by
+    /// [`build_lower_triangular_csr_pattern`].
+    ///
+    /// Returns an error if the element assembler does not declare its element matrices to be
+    /// symmetric, or if an element contributes to an entry that is not present in the sparsity
+    /// pattern of the matrix. In the latter case, the values of the matrix are unspecified.
+    pub fn assemble_lower_triangle_into_csr(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        if element_assembler.symmetry() != Symmetry::Symmetric {
+            return Err(eyre!(
+                "Cannot assemble the lower triangle of a matrix whose element assembler \
+                 does not declare symmetric element matrices"
+            ));
+        }
+        self.assemble_into_csr_impl(csr, T::one(), element_assembler, Symmetry::Symmetric)
+    }
+
+    fn assemble_into_csr_impl(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        alpha: T,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+        symmetry: Symmetry,
     ) -> eyre::Result<()> {
         // Reuse previously allocated buffers
         let ws = &mut *self.workspace.borrow_mut();
         let connectivity_permutation = &mut ws.connectivity_permutation;
-        let element_global_nodes = &mut ws.element_global_nodes;
-        let element_matrix = &mut ws.element_matrix;
-
         let sdim = element_assembler.solution_dim();
 
-        for i in 0..element_assembler.num_elements() {
-            let element_node_count = element_assembler.element_node_count(i);
-            let element_matrix_dim = sdim * element_node_count;
-
-            element_global_nodes.resize(element_node_count, 0);
-            element_matrix.resize_mut(element_matrix_dim, element_matrix_dim, T::zero());
-
-            let matrix_slice = DMatrixViewMut::from(&mut *element_matrix);
-            element_assembler.assemble_element_matrix_into(i, matrix_slice)?;
-            element_assembler.populate_element_nodes(element_global_nodes, i);
-
-            connectivity_permutation.clear();
-            connectivity_permutation.extend(0..element_node_count);
-            connectivity_permutation.sort_unstable_by_key(|i| element_global_nodes[*i]);
-
-            for (local_node_idx, global_node_idx) in element_global_nodes.iter().enumerate() {
-                for i in 0..sdim {
-                    let local_row_index = sdim * local_node_idx + i;
-                    let global_row_index = sdim * *global_node_idx + i;
-                    let mut csr_row = csr.row_mut(global_row_index);
-                    let (cols, values) = csr_row.cols_and_values_mut();
-
-                    let a_row = element_matrix.row(local_row_index);
-                    add_element_row_to_csr_row(
-                        values,
-                        cols,
-                        &element_global_nodes,
-                        &connectivity_permutation,
-                        sdim,
-                        &a_row,
-                    );
+        for_each_element_matrix(
+            element_assembler,
+            &mut ws.element_global_nodes,
+            &mut ws.element_matrix,
+            symmetry,
+            |element_index, element_global_nodes, element_matrix| {
+                connectivity_permutation.clear();
+                connectivity_permutation.extend(0..element_global_nodes.len());
+                connectivity_permutation.sort_unstable_by_key(|i| element_global_nodes[*i]);
+
+                for (local_node_idx, global_node_idx) in element_global_nodes.iter().enumerate() {
+                    for i in 0..sdim {
+                        let local_row_index = sdim * local_node_idx + i;
+                        let global_row_index = sdim * *global_node_idx + i;
+                        let mut csr_row = csr.row_mut(global_row_index);
+                        let (cols, values) = csr_row.cols_and_values_mut();
+
+                        // For symmetric matrices we only assemble the lower triangular part
+                        let max_col_index = match symmetry {
+                            Symmetry::NonSymmetric => usize::MAX,
+                            Symmetry::Symmetric => global_row_index,
+                        };
+                        let a_row = element_matrix.row(local_row_index);
+                        add_element_row_to_csr_row(
+                            values,
+                            cols,
+                            element_global_nodes,
+                            connectivity_permutation.as_slice(),
+                            sdim,
+                            max_col_index,
+                            alpha,
+                            &a_row,
+                        )
+                        .map_err(|col| missing_entry_error(element_index, global_row_index, col))?;
+                    }
                 }
-            }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assembles the matrix associated with the given element assembler into a matrix with a
+    /// preallocated sparsity pattern, overwriting its values.
+    ///
+    /// This avoids rebuilding the sparsity pattern when the same structure is assembled repeatedly,
+    /// such as in time-dependent simulations or Newton iterations. A compatible pattern can be
+    /// obtained with [`build_csr_pattern`]. The pattern may contain more entries than required,
+    /// in which case the additional entries are set to zero.
+    ///
+    /// Returns an error if the dimensions of the matrix are not compatible with the assembler,
+    /// or if an element contributes to an entry that is not present in the sparsity pattern.
+    /// In the latter case, the values of the matrix are unspecified.
+    pub fn assemble_into_preallocated_csr(
+        &self,
+        csr: &mut CsrMatrix<T>,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+    ) -> eyre::Result<()> {
+        let num_rows = element_assembler.solution_dim() * element_assembler.num_nodes();
+        if csr.nrows() != num_rows || csr.ncols() != num_rows {
+            return Err(eyre!(
+                "Preallocated matrix has dimensions {}x{}, but the element assembler requires {}x{}",
+                csr.nrows(),
+                csr.ncols(),
+                num_rows,
+                num_rows
+            ));
         }
+        csr.values_mut().fill(T::zero());
+        self.assemble_into_csr(csr, element_assembler)
+    }
+}
 
-        Ok(())
+/// Checks that the matrix is symmetric up to a tolerance relative to its largest entry.
+fn is_approximately_symmetric<T: Real>(matrix: &DMatrix<T>) -> bool {
+    let tol = T::default_epsilon().sqrt() * matrix.amax();
+    matrix.is_square() && (0..matrix.nrows()).all(|i| (0..i).all(|j| (matrix[(i, j)] - matrix[(j, i)]).abs() <= tol))
+}
+
+/// Expands a lower triangular CSR matrix into the full symmetric matrix.
+///
+/// The input is typically obtained with [`CsrAssembler::assemble_with_symmetry`], and the
+/// result has the same entries as the matrix assembled without exploiting symmetry.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square or if it has explicitly stored entries above the diagonal.
+pub fn expand_lower_triangular_csr<T: Real>(lower: &CsrMatrix<T>) -> CsrMatrix<T> {
+    assert_eq!(lower.nrows(), lower.ncols(), "Matrix must be square");
+    let mut coo = CooMatrix::new(lower.nrows(), lower.ncols());
+    for (i, j, &a_ij) in lower.triplet_iter() {
+        assert!(j <= i, "Matrix has an entry ({}, {}) above the diagonal", i, j);
+        coo.push(i, j, a_ij);
+        if i != j {
+            coo.push(j, i, a_ij);
+        }
+    }
+    CsrMatrix::from(&coo)
+}
+
+/// Assembles the element matrices of the given element assembler one after another, passing the
+/// index, global nodes and element matrix of each element to the provided closure.
+///
+/// With [`Symmetry::Symmetric`], the element matrices are checked to be symmetric in debug builds.
+fn for_each_element_matrix<T: Real>(
+    element_assembler: &(impl ElementMatrixAssembler<T> + ?Sized),
+    element_global_nodes: &mut Vec<usize>,
+    element_matrix: &mut DMatrix<T>,
+    symmetry: Symmetry,
+    mut f: impl FnMut(usize, &[usize], &DMatrix<T>) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let sdim = element_assembler.solution_dim();
+    for element_index in 0..element_assembler.num_elements() {
+        let element_node_count = element_assembler.element_node_count(element_index);
+        let element_matrix_dim = sdim * element_node_count;
+
+        element_global_nodes.resize(element_node_count, 0);
+        element_matrix.resize_mut(element_matrix_dim, element_matrix_dim, T::zero());
+
+        let matrix_slice = DMatrixViewMut::from(&mut *element_matrix);
+        element_assembler.assemble_element_matrix_into(element_index, matrix_slice)?;
+        element_assembler.populate_element_nodes(element_global_nodes, element_index);
+        if symmetry == Symmetry::Symmetric {
+            debug_assert!(
+                is_approximately_symmetric(element_matrix),
+                "Element matrix of element {} is not symmetric, \
+                 but the element assembler declares symmetric element matrices",
+                element_index
+            );
+        }
+
+        f(element_index, element_global_nodes, element_matrix)?;
     }
+    Ok(())
+}
+
+/// An assembler for matrices in coordinate (COO) format.
+///
+/// Each entry of each element matrix is stored as a separate triplet, without summing duplicate
+/// entries. This is mainly useful for passing matrices to external solvers that accept triplet
+/// input, for which first assembling a CSR matrix would be wasteful. The triplets of each element
+/// are stored consecutively in the order of the elements. Converting the result to CSR, for example with
+/// `CsrMatrix::from`, gives the same matrix as [`CsrAssembler`] up to round-off errors due to
+/// the order in which duplicate entries are summed.
+///
+/// By default, all entries of the element matrices are stored, including explicit zeros, so that
+/// the sparsity pattern matches [`build_csr_pattern`]. A drop tolerance can be set with
+/// [`with_drop_tolerance`](Self::with_drop_tolerance).
+#[derive(Debug, Clone)]
+pub struct CooAssembler<T: Scalar> {
+    drop_tolerance: Option<T>,
+    workspace: RefCell<CooAssemblerWorkspace<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct CooAssemblerWorkspace<T: Scalar> {
+    element_global_nodes: Vec<usize>,
+    element_matrix: DMatrix<T>,
+}
+
+impl<T: Scalar> Default for CooAssembler<T> {
+    fn default() -> Self {
+        Self {
+            drop_tolerance: None,
+            workspace: RefCell::new(CooAssemblerWorkspace {
+                element_global_nodes: Vec::new(),
+                element_matrix: DMatrix::from_row_slice(0, 0, &[]),
+            }),
+        }
+    }
+}
+
+impl<T: Real> CooAssembler<T> {
+    /// Drops entries of the element matrices whose absolute value does not exceed the given tolerance.
+    ///
+    /// Note that the tolerance applies to the individual element contributions, not to the
+    /// entries of the assembled matrix.
+    pub fn with_drop_tolerance(self, drop_tolerance: T) -> Self {
+        Self {
+            drop_tolerance: Some(drop_tolerance),
+            ..self
+        }
+    }
+
+    pub fn drop_tolerance(&self) -> Option<T> {
+        self.drop_tolerance
+    }
+
+    pub fn assemble(&self, element_assembler: &impl ElementMatrixAssembler<T>) -> eyre::Result<CooMatrix<T>> {
+        self.assemble_with_symmetry(element_assembler, Symmetry::NonSymmetric)
+    }
+
+    /// Assembles the matrix associated with the given element assembler, storing only
+    /// its lower triangular part if [`Symmetry::Symmetric`] is requested.
+    ///
+    /// Returns an error if [`Symmetry::Symmetric`] is requested but the element assembler does
+    /// not declare its element matrices to be symmetric.
+    pub fn assemble_with_symmetry(
+        &self,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+        symmetry: Symmetry,
+    ) -> eyre::Result<CooMatrix<T>> {
+        let num_rows = element_assembler.solution_dim() * element_assembler.num_nodes();
+        let capacity = estimate_coo_nnz(element_assembler, symmetry);
+        let mut row_indices = Vec::with_capacity(capacity);
+        let mut col_indices = Vec::with_capacity(capacity);
+        let mut values = Vec::with_capacity(capacity);
+        self.assemble_triplets(element_assembler, symmetry, |i, j, a_ij| {
+            row_indices.push(i);
+            col_indices.push(j);
+            values.push(a_ij);
+        })?;
+        Ok(
+            CooMatrix::try_from_triplets(num_rows, num_rows, row_indices, col_indices, values)
+                .expect("Triplets must be valid by definition"),
+        )
+    }
+
+    /// Assembles the matrix associated with the given element assembler and *appends* its
+    /// triplets to the given COO matrix.
+    ///
+    /// Returns an error if the dimensions of the matrix are not compatible with the assembler,
+    /// or if [`Symmetry::Symmetric`] is requested but the element assembler does
+    /// not declare its element matrices to be symmetric.
+    pub fn assemble_into_coo(
+        &self,
+        coo: &mut CooMatrix<T>,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+        symmetry: Symmetry,
+    ) -> eyre::Result<()> {
+        let num_rows = element_assembler.solution_dim() * element_assembler.num_nodes();
+        if coo.nrows() != num_rows || coo.ncols() != num_rows {
+            return Err(eyre!(
+                "COO matrix has dimensions {}x{}, but the element assembler requires {}x{}",
+                coo.nrows(),
+                coo.ncols(),
+                num_rows,
+                num_rows
+            ));
+        }
+        self.assemble_triplets(element_assembler, symmetry, |i, j, a_ij| coo.push(i, j, a_ij))
+    }
+
+    fn assemble_triplets(
+        &self,
+        element_assembler: &impl ElementMatrixAssembler<T>,
+        symmetry: Symmetry,
+        mut push: impl FnMut(usize, usize, T),
+    ) -> eyre::Result<()> {
+        if symmetry == Symmetry::Symmetric && element_assembler.symmetry() != Symmetry::Symmetric {
+            return Err(eyre!(
+                "Cannot assemble the lower triangle of a matrix whose element assembler \
+                 does not declare symmetric element matrices"
+            ));
+        }
+
+        let ws = &mut *self.workspace.borrow_mut();
+        let sdim = element_assembler.solution_dim();
+        for_each_element_matrix(
+            element_assembler,
+            &mut ws.element_global_nodes,
+            &mut ws.element_matrix,
+            symmetry,
+            |_, element_global_nodes, element_matrix| {
+                for (local_node_j, global_node_j) in element_global_nodes.iter().enumerate() {
+                    for (local_node_i, global_node_i) in element_global_nodes.iter().enumerate() {
+                        for j in 0..sdim {
+                            let global_col_index = sdim * global_node_j + j;
+                            for i in 0..sdim {
+                                let global_row_index = sdim * global_node_i + i;
+                                if symmetry == Symmetry::Symmetric && global_col_index > global_row_index {
+                                    continue;
+                                }
+                                let a_ij = element_matrix[(sdim * local_node_i + i, sdim * local_node_j + j)];
+                                if self.drop_tolerance.map_or(false, |tol| a_ij.abs() <= tol) {
+                                    continue;
+                                }
+                                push(global_row_index, global_col_index, a_ij);
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Computes an upper bound for the number of triplets produced by [`CooAssembler`].
+fn estimate_coo_nnz<A: ElementConnectivityAssembler + ?Sized>(element_assembler: &A, symmetry: Symmetry) -> usize {
+    let sdim = element_assembler.solution_dim();
+    (0..element_assembler.num_elements())
+        .map(|i| {
+            let m = sdim * element_assembler.element_node_count(i);
+            match symmetry {
+                Symmetry::NonSymmetric => m * m,
+                Symmetry::Symmetric => m * (m + 1) / 2,
+            }
+        })
+        .sum()
+}
+
+fn missing_entry_error(element_index: usize, row: usize, col: usize) -> eyre::Report {
+    AssemblyError::MissingSparsityEntry {
+        element_index,
+        row,
+        col,
+    }
+    .into()
 }
 
 /// A parallel assembler for CSR matrices relying on a graph coloring of elements.
 ///
+/// The colors are assembled one after another. Since no two elements of the same color share
+/// a node, the elements of a color can be assembled in parallel without locks. For a given
+/// coloring, every matrix entry receives the contributions of its elements in the same order
+/// regardless of the number of threads, so the result is deterministic. A suitable coloring
+/// can be computed with [`color_elements`](crate::assembly::color_elements) and converted with
+/// [`ElementColoring::to_disjoint_subsets`](crate::assembly::ElementColoring::to_disjoint_subsets),
+/// or with [`color_nodes`].
+///
 /// TODO: Consider using type erasure to store buffers without needing the generic type parameter
 #[derive(Debug)]
 pub struct CsrParAssembler<T: Scalar + Send> {
@@ -362,8 +765,14 @@ impl<T: Real + Send> CsrParAssembler<T> {
                                 &ws.element_global_nodes,
                                 &ws.connectivity_permutation,
                                 sdim,
+                                usize::MAX,
+                                T::one(),
                                 &a_row,
-                            );
+                            )
+                            .map_err(|col| {
+                                let global_row_index = sdim * ws.element_global_nodes[local_node_idx] + i;
+                                missing_entry_error(element_index, global_row_index, col)
+                            })?;
                         }
                     }
 
@@ -501,14 +910,19 @@ pub fn apply_homogeneous_dirichlet_bc_rhs<'a, T>(
 ///    corresponding global indices are sorted.
 /// `dim`: The solution dimension.
 /// `local_row`: The local row of the element matrix that should be added to the CSR matrix.
+///
+/// Returns the global column index of the first entry that is not present in the CSR row, if any.
 fn add_element_row_to_csr_row<T, S>(
     row_values: &mut [T],
     row_col_indices: &[usize],
     node_connectivity: &[usize],
     sorted_permutation: &[usize],
     dim: usize,
+    max_col_index: usize,
+    alpha: T,
     local_row: &Matrix<T, U1, Dyn, S>,
-) where
+) -> Result<(), usize>
+where
     T: Real,
     S: Storage<T, U1, Dyn>,
 {
@@ -525,18 +939,27 @@ fn add_element_row_to_csr_row<T, S>(
         for i in 0..dim {
             let local_col_idx = dim * node_local_idx + i;
             let global_col_index = dim * node_global_idx + i;
+            // The global column indices are visited in ascending order
+            if global_col_index > max_col_index {
+                return Ok(());
+            }
 
             // TODO: If the CSR matrix has a large number of entries in each row,
             // an exponential search may be faster than a linear search as we do here
             let (local_csr_col_idx, _) = csr_col_idx_iter
                 .find(|(_, csr_col_idx)| *csr_col_idx == global_col_index)
-                .expect("Could not find column index associated with node in CSR row");
-            values[local_csr_col_idx] += local_row[local_col_idx];
+                .ok_or(global_col_index)?;
+            values[local_csr_col_idx] += alpha * local_row[local_col_idx];
         }
     }
+
+    Ok(())
 }
 
 /// Computes a coloring for the nodes of the given element connectivity.
+///
+/// For element assemblers, [`color_elements`](crate::assembly::color_elements) computes a
+/// balanced coloring, which can be converted to [`DisjointSubsets`].
 pub fn color_nodes<C: FiniteElementConnectivity + ?Sized>(connectivity: &C) -> Vec<DisjointSubsets> {
     let mut nested = NestedVec::new();
 
@@ -583,6 +1006,21 @@ impl<T: Real> VectorAssembler<T> {
         &self,
         output: impl Into<DVectorViewMut<'a, T>>,
         element_assembler: &impl ElementVectorAssembler<T>,
+    ) -> eyre::Result<()> {
+        self.assemble_scaled_add_into_vector(output, T::one(), element_assembler)
+    }
+
+    /// Assembles the vector associated with the given element assembler, scales it by `alpha`
+    /// and *adds* it to the output vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the output vector is not compatible with the assembler.
+    pub fn assemble_scaled_add_into_vector<'a>(
+        &self,
+        output: impl Into<DVectorViewMut<'a, T>>,
+        alpha: T,
+        element_assembler: &impl ElementVectorAssembler<T>,
     ) -> eyre::Result<()> {
         // TODO: Move impl into _ method to remove the impl Into<> compilation overhead
         let mut output = output.into();
@@ -601,6 +1039,7 @@ impl<T: Real> VectorAssembler<T> {
                 .resize_vertically_mut(s * element_node_count, T::zero());
             element_assembler.populate_element_nodes(&mut workspace.nodes, i);
             element_assembler.assemble_element_vector_into(i, (&mut workspace.vector).into())?;
+            workspace.vector *= alpha;
             add_local_to_global(&workspace.vector, &mut output, &workspace.nodes, s);
         }
 
@@ -683,6 +1122,72 @@ impl<T: Real> VectorParAssembler<T> {
 
         Ok(())
     }
+
+    /// Assembles the vector associated with the given element assembler in parallel by
+    /// accumulating element contributions into per-thread vectors, which are summed at the end.
+    ///
+    /// Unlike [`assemble_vector`](Self::assemble_vector), no coloring of the elements is
+    /// required, and there is no synchronization between colors. On the other hand, a number of
+    /// additional global vectors proportional to the number of threads must be stored, which may
+    /// be prohibitive for very large meshes. Moreover, the order in which contributions are summed
+    /// depends on how the work is distributed among threads, so the result may differ by round-off
+    /// errors between runs. Use the colored assembly if deterministic results are required.
+    pub fn assemble_vector_by_reduction(
+        &self,
+        element_assembler: &(impl ElementVectorAssembler<T> + ?Sized + Sync),
+    ) -> eyre::Result<DVector<T>> {
+        let ndof = element_assembler.solution_dim() * element_assembler.num_nodes();
+        let s = element_assembler.solution_dim();
+        let num_elements = element_assembler.num_elements();
+        // Bound the number of accumulation vectors by preventing rayon from splitting the
+        // elements into more than a few chunks per thread
+        let min_chunk_size = div_ceil(num_elements, 4 * rayon::current_num_threads()).max(1);
+
+        (0..num_elements)
+            .into_par_iter()
+            .with_min_len(min_chunk_size)
+            .try_fold(
+                || DVector::zeros(ndof),
+                |mut output, element_index| {
+                    let ws = &mut *self.workspace.get_or_default().borrow_mut();
+                    let element_node_count = element_assembler.element_node_count(element_index);
+                    ws.nodes.resize(element_node_count, usize::MAX);
+                    ws.vector
+                        .resize_vertically_mut(s * element_node_count, T::zero());
+                    element_assembler.populate_element_nodes(&mut ws.nodes, element_index);
+                    element_assembler.assemble_element_vector_into(element_index, (&mut ws.vector).into())?;
+                    add_local_to_global(&ws.vector, &mut output, &ws.nodes, s);
+                    Ok(output)
+                },
+            )
+            .try_reduce(
+                || DVector::zeros(ndof),
+                |mut a, b| {
+                    a += b;
+                    Ok(a)
+                },
+            )
+    }
+
+    /// Assembles the vector associated with the given element assembler in parallel with
+    /// per-thread accumulation and *adds* it to the output vector.
+    ///
+    /// See [`assemble_vector_by_reduction`](Self::assemble_vector_by_reduction).
+    pub fn assemble_vector_into_by_reduction<'a>(
+        &self,
+        output: impl Into<DVectorViewMut<'a, T>>,
+        element_assembler: &(impl ElementVectorAssembler<T> + ?Sized + Sync),
+    ) -> eyre::Result<()> {
+        let mut output = output.into();
+        let s = element_assembler.solution_dim();
+        assert_eq!(
+            output.len(),
+            s * element_assembler.num_nodes(),
+            "Output dimensions mismatch"
+        );
+        output += self.assemble_vector_by_reduction(element_assembler)?;
+        Ok(())
+    }
 }
 
 #[deprecated = "Use assemble_scalar instead"]
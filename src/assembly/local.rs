@@ -3,17 +3,35 @@ use crate::mesh::Mesh;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{DMatrix, DVector, DVectorViewMut};
 use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, Scalar};
-use crate::Real;
+use crate::{Real, Symmetry};
 
+mod axisymmetric;
+mod cached_basis;
+mod convection_diffusion;
 mod elliptic;
+mod instrumented;
+mod jacobian;
 mod mass;
+mod mass_scaling;
+mod pressure;
 mod quadrature_table;
 mod source;
+mod spd_projection;
+mod surface_source;
 
+pub use axisymmetric::*;
+pub use cached_basis::*;
+pub use convection_diffusion::*;
 pub use elliptic::*;
+pub use instrumented::*;
+pub use jacobian::*;
 pub use mass::*;
+pub use mass_scaling::*;
+pub use pressure::*;
 pub use quadrature_table::*;
 pub use source::*;
+pub use spd_projection::*;
+pub use surface_source::*;
 
 pub trait ElementConnectivityAssembler {
     fn solution_dim(&self) -> usize;
@@ -87,6 +105,16 @@ pub trait ElementMatrixAssembler<T: Scalar>: ElementConnectivityAssembler {
         Ok(output)
     }
 
+    /// Declares whether all element matrices produced by the assembler are symmetric.
+    ///
+    /// Assemblers that declare [`Symmetry::Symmetric`] can be assembled into a lower triangular
+    /// global matrix with
+    /// [`CsrAssembler::assemble_with_symmetry`](crate::assembly::global::CsrAssembler::assemble_with_symmetry).
+    /// The default implementation conservatively returns [`Symmetry::NonSymmetric`].
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::NonSymmetric
+    }
+
     fn transform_element_matrix<Transformation>(
         self,
         transformation: Transformation,
@@ -148,6 +176,12 @@ pub trait ElementScalarAssembler<T: Scalar>: ElementConnectivityAssembler {
     }
 }
 
+/// An element assembler that combines the elements of several assemblers sharing the same node index space.
+///
+/// The elements of the aggregate are the elements of the first assembler, followed by the elements of the second
+/// assembler and so on. In order to aggregate assemblers of different types, for example a volumetric
+/// assembler and a surface assembler, use trait objects such as `&dyn ElementMatrixAssembler<T>`
+/// or `Box<dyn ElementMatrixAssembler<T>>` as the element assembler type.
 #[derive(Debug, Clone)]
 pub struct AggregateElementAssembler<'a, ElementAssembler> {
     assemblers: &'a [ElementAssembler],
@@ -163,12 +197,13 @@ where
 {
     /// Constructs a new aggregate element assembler from a slice of assemblers.
     ///
-    /// Te
+    /// Assemblers without any elements are permitted.
     ///
     /// # Panics
     ///
     /// - Panics if the slice of assemblers is empty.
     /// - Panics if the assemblers do not all have the same solution dimension.
+    /// - Panics if the assemblers do not all have the same number of nodes.
     pub fn from_assemblers(assemblers: &'a [ElementAssembler]) -> Self {
         assert!(!assemblers.is_empty(), "Must have at least one assembler in aggregate");
         let solution_dim = assemblers[0].solution_dim();
@@ -198,16 +233,23 @@ where
             solution_dim,
             element_offsets,
             num_elements: num_total_elements,
-            num_nodes: num_nodes,
+            num_nodes,
         }
     }
 
     fn find_assembler_and_offset_for_element_index(&self, element_index: usize) -> (&ElementAssembler, usize) {
-        assert!(element_index <= self.num_elements);
-        let assembler_idx = match self.element_offsets.binary_search(&element_index) {
-            Ok(idx) => idx,
-            Err(idx) => idx - 1,
-        };
+        assert!(
+            element_index < self.num_elements,
+            "Element index {} out of bounds for aggregate with {} elements",
+            element_index,
+            self.num_elements
+        );
+        // Assemblers without elements share their offset with the next assembler, so we need to pick the *last*
+        // assembler whose offset does not exceed the element index
+        let assembler_idx = self
+            .element_offsets
+            .partition_point(|&offset| offset <= element_index)
+            - 1;
         (&self.assemblers[assembler_idx], self.element_offsets[assembler_idx])
     }
 }
@@ -278,6 +320,18 @@ where
         let (assembler, element_offset) = self.find_assembler_and_offset_for_element_index(aggregate_element_index);
         assembler.assemble_element_matrix_into(aggregate_element_index - element_offset, output)
     }
+
+    fn symmetry(&self) -> Symmetry {
+        let all_symmetric = self
+            .assemblers
+            .iter()
+            .all(|assembler| assembler.symmetry() == Symmetry::Symmetric);
+        if all_symmetric {
+            Symmetry::Symmetric
+        } else {
+            Symmetry::NonSymmetric
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -417,6 +471,10 @@ macro_rules! delegate {
             -> eyre::Result<()> {
                 $self.$delegate_var.assemble_element_matrix_into(element_index, output)
             }
+
+            fn symmetry(&$self) -> Symmetry {
+                $self.$delegate_var.symmetry()
+            }
         }
     };
     // This branch allows us to use all of the preceding matchers without having a trailing `where` in the macro call
@@ -516,3 +574,121 @@ where
         (self.function)(output)
     }
 }
+
+impl<'a, Assembler> ElementConnectivityAssembler for &'a Assembler
+where
+    Assembler: ?Sized + ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        (**self).solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        (**self).num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        (**self).num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        (**self).element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        (**self).populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, Assembler> ElementScalarAssembler<T> for &'a Assembler
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementScalarAssembler<T>,
+{
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
+        (**self).assemble_element_scalar(element_index)
+    }
+}
+
+impl<'a, T, Assembler> ElementVectorAssembler<T> for &'a Assembler
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementVectorAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        (**self).assemble_element_vector_into(element_index, output)
+    }
+}
+
+impl<'a, T, Assembler> ElementMatrixAssembler<T> for &'a Assembler
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        (**self).assemble_element_matrix_into(element_index, output)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        (**self).symmetry()
+    }
+}
+
+impl<Assembler> ElementConnectivityAssembler for Box<Assembler>
+where
+    Assembler: ?Sized + ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        (**self).solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        (**self).num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        (**self).num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        (**self).element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        (**self).populate_element_nodes(output, element_index)
+    }
+}
+
+impl<T, Assembler> ElementScalarAssembler<T> for Box<Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementScalarAssembler<T>,
+{
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
+        (**self).assemble_element_scalar(element_index)
+    }
+}
+
+impl<T, Assembler> ElementVectorAssembler<T> for Box<Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementVectorAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        (**self).assemble_element_vector_into(element_index, output)
+    }
+}
+
+impl<T, Assembler> ElementMatrixAssembler<T> for Box<Assembler>
+where
+    T: Scalar,
+    Assembler: ?Sized + ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        (**self).assemble_element_matrix_into(element_index, output)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        (**self).symmetry()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::assembly::local::{BasisCacheView, QuadratureTable};
+use crate::nalgebra::{OPoint, U2};
+use crate::space::FiniteElementSpace;
+use crate::Real;
+
+/// A quadrature table for axisymmetric problems.
+///
+/// For problems that are rotationally symmetric about the $z$-axis, the computational domain is the
+/// half-plane $r \geq 0$ in the $(r, z)$ coordinates, and the volume element is
+/// $\mathrm{d} V = 2 \pi r \\, \mathrm{d}r \\, \mathrm{d}z$. This table wraps a quadrature table for
+/// the two-dimensional elements of the space and multiplies the weight of each quadrature point by
+/// $2 \pi r$, where $r$ is the first coordinate of the quadrature point mapped to physical space.
+/// Integrals over the two-dimensional elements then become integrals over the corresponding
+/// three-dimensional bodies of revolution.
+///
+/// This is sufficient for assemblers whose integrands do not otherwise change in cylindrical
+/// coordinates, such as the mass matrix, source terms and scalar diffusion problems without
+/// dependence on the angle. The strain in axisymmetric elasticity has an additional hoop component,
+/// which is not accounted for by modifying the quadrature alone.
+///
+/// Quadrature points on the axis $r = 0$ have zero weight.
+#[derive(Debug)]
+pub struct AxisymmetricQuadratureTable<'a, Space, QTable: ?Sized> {
+    space: &'a Space,
+    table: &'a QTable,
+}
+
+impl<'a, Space, QTable: ?Sized> Clone for AxisymmetricQuadratureTable<'a, Space, QTable> {
+    fn clone(&self) -> Self {
+        Self {
+            space: self.space,
+            table: self.table,
+        }
+    }
+}
+
+impl<'a, Space, QTable: ?Sized> AxisymmetricQuadratureTable<'a, Space, QTable> {
+    /// Wraps the quadrature table for the elements of the given space, whose first coordinate
+    /// is taken to be the radial coordinate.
+    pub fn new(space: &'a Space, table: &'a QTable) -> Self {
+        Self { space, table }
+    }
+}
+
+impl<'a, T, Space, QTable> QuadratureTable<T, U2> for AxisymmetricQuadratureTable<'a, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T, GeometryDim = U2, ReferenceDim = U2>,
+    QTable: ?Sized + QuadratureTable<T, U2>,
+{
+    type Data = QTable::Data;
+
+    fn element_quadrature_size(&self, element_index: usize) -> usize {
+        self.table.element_quadrature_size(element_index)
+    }
+
+    fn populate_element_data(&self, element_index: usize, data: &mut [Self::Data]) {
+        self.table.populate_element_data(element_index, data)
+    }
+
+    fn populate_element_quadrature(&self, element_index: usize, points: &mut [OPoint<T, U2>], weights: &mut [T]) {
+        self.table
+            .populate_element_quadrature(element_index, points, weights);
+        for (point, weight) in points.iter().zip(weights) {
+            let r = self
+                .space
+                .map_element_reference_coords(element_index, point)
+                .x;
+            *weight *= T::two_pi() * r;
+        }
+    }
+
+    fn element_basis_cache(&self, element_index: usize) -> Option<BasisCacheView<T, U2>> {
+        self.table.element_basis_cache(element_index)
+    }
+}
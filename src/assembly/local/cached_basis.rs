@@ -0,0 +1,262 @@
+use crate::allocators::DimAllocator;
+use crate::assembly::local::{QuadratureTable, UniformQuadratureTable};
+use crate::element::{FiniteElement, ReferenceFiniteElement};
+use crate::nalgebra::{DefaultAllocator, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint, Scalar};
+use crate::{Real, SmallDim};
+use std::marker::PhantomData;
+
+/// Basis function values and reference gradients evaluated at the points of a quadrature rule.
+///
+/// Obtained from [`QuadratureTable::element_basis_cache`].
+#[derive(Debug, Clone, Copy)]
+pub struct BasisCacheView<'a, T: Scalar, D: SmallDim> {
+    num_nodes: usize,
+    basis_values: &'a [T],
+    basis_gradients: &'a [T],
+    dim: PhantomData<D>,
+}
+
+impl<'a, T: Scalar, D: SmallDim> BasisCacheView<'a, T, D> {
+    /// The number of nodes (basis functions) of the element.
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// The number of quadrature points.
+    pub fn num_points(&self) -> usize {
+        self.basis_values.len() / self.num_nodes.max(1)
+    }
+
+    /// The values of all basis functions at the quadrature point with the given index.
+    pub fn basis_values(&self, quadrature_index: usize) -> &'a [T] {
+        let n = self.num_nodes;
+        &self.basis_values[n * quadrature_index..n * (quadrature_index + 1)]
+    }
+
+    /// The gradients of all basis functions with respect to reference coordinates at the quadrature
+    /// point with the given index, stored column-wise.
+    pub fn basis_gradients(&self, quadrature_index: usize) -> MatrixView<'a, T, D, Dyn> {
+        let n = self.num_nodes;
+        let d = D::dim();
+        let gradients = &self.basis_gradients[d * n * quadrature_index..d * n * (quadrature_index + 1)];
+        MatrixView::from_slice_generic(gradients, D::name(), Dyn(n))
+    }
+}
+
+/// A uniform quadrature table with basis functions precomputed at its quadrature points.
+///
+/// Element assemblers evaluate the basis functions and their gradients at the same reference
+/// quadrature points for every element. For a mesh in which every element has the same
+/// reference element, for example a mesh with a single connectivity type, and a uniform quadrature rule,
+/// this work is redundant. [`CachedBasisQuadrature`] evaluates the basis functions once for a given
+/// reference element, and supplies the cached values to the element assemblers through
+/// [`QuadratureTable::element_basis_cache`]. Only the geometric Jacobians are then computed per element,
+/// and isoparametric elements form them from the cached reference gradients.
+///
+/// Currently, [`ElementEllipticAssembler`](crate::assembly::local::ElementEllipticAssembler) and
+/// [`ElementMassAssembler`](crate::assembly::local::ElementMassAssembler) make use of the cache.
+/// Other assemblers treat the table like the underlying [`UniformQuadratureTable`].
+///
+/// The cache is only valid if the reference basis functions of every element in the space coincide
+/// with those of the reference element used to construct the table. This holds for isoparametric
+/// Lagrange elements, whose basis functions in reference coordinates do not depend on the geometry
+/// of the element. Assemblers panic if the number of nodes of an element does not match the cache.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedBasisQuadrature<T, D, Data = ()>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    table: UniformQuadratureTable<T, D, Data>,
+    num_nodes: usize,
+    basis_values: Vec<T>,
+    basis_gradients: Vec<T>,
+    dim: PhantomData<D>,
+}
+
+impl<T, D, Data> CachedBasisQuadrature<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    Data: Clone + Default,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// Precomputes the basis functions of the given reference element at the points of the table.
+    pub fn new<Element>(table: UniformQuadratureTable<T, D, Data>, reference_element: &Element) -> Self
+    where
+        Element: ReferenceFiniteElement<T, ReferenceDim = D>,
+    {
+        let n = reference_element.num_nodes();
+        let num_points = table.element_quadrature_size(0);
+        let mut points = vec![OPoint::origin(); num_points];
+        let mut weights = vec![T::zero(); num_points];
+        table.populate_element_quadrature(0, &mut points, &mut weights);
+
+        let d = D::dim();
+        let mut basis_values = vec![T::zero(); n * num_points];
+        let mut basis_gradients = vec![T::zero(); d * n * num_points];
+        for (q, point) in points.iter().enumerate() {
+            reference_element.populate_basis(&mut basis_values[n * q..n * (q + 1)], point);
+            let gradients = &mut basis_gradients[d * n * q..d * n * (q + 1)];
+            let gradients = MatrixViewMut::from_slice_generic(gradients, D::name(), Dyn(n));
+            reference_element.populate_basis_gradients(gradients, point);
+        }
+
+        Self {
+            table,
+            num_nodes: n,
+            basis_values,
+            basis_gradients,
+            dim: PhantomData,
+        }
+    }
+
+    /// The underlying quadrature table.
+    pub fn table(&self) -> &UniformQuadratureTable<T, D, Data> {
+        &self.table
+    }
+
+    /// Returns a view of the cached basis values and gradients.
+    pub fn basis_cache(&self) -> BasisCacheView<T, D> {
+        BasisCacheView {
+            num_nodes: self.num_nodes,
+            basis_values: &self.basis_values,
+            basis_gradients: &self.basis_gradients,
+            dim: PhantomData,
+        }
+    }
+}
+
+impl<T, D, Data> QuadratureTable<T, D> for CachedBasisQuadrature<T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    Data: Clone + Default,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type Data = Data;
+
+    fn element_quadrature_size(&self, element_index: usize) -> usize {
+        self.table.element_quadrature_size(element_index)
+    }
+
+    fn populate_element_data(&self, element_index: usize, data: &mut [Self::Data]) {
+        self.table.populate_element_data(element_index, data)
+    }
+
+    fn populate_element_quadrature(&self, element_index: usize, points: &mut [OPoint<T, D>], weights: &mut [T]) {
+        self.table
+            .populate_element_quadrature(element_index, points, weights)
+    }
+
+    fn element_basis_cache(&self, _element_index: usize) -> Option<BasisCacheView<T, D>> {
+        Some(self.basis_cache())
+    }
+}
+
+fn check_cache_dimensions<T: Scalar, D: SmallDim>(cache: &BasisCacheView<T, D>, num_nodes: usize, num_points: usize) {
+    assert_eq!(
+        cache.num_nodes(),
+        num_nodes,
+        "Number of nodes in basis cache must match the number of nodes in the element"
+    );
+    assert_eq!(
+        cache.num_points(),
+        num_points,
+        "Number of points in basis cache must match the number of quadrature points"
+    );
+}
+
+/// The quadrature rule of a single element, along with the basis cache for its points, if any.
+///
+/// Bundles the per-element quadrature arguments of the element assembly kernels.
+pub(crate) struct ElementQuadrature<'a, T, D, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub(crate) weights: &'a [T],
+    pub(crate) points: &'a [OPoint<T, D>],
+    pub(crate) data: &'a [Data],
+    pub(crate) basis_cache: Option<BasisCacheView<'a, T, D>>,
+}
+
+impl<'a, T, D, Data> ElementQuadrature<'a, T, D, Data>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// # Panics
+    ///
+    /// Panics if the quadrature arrays do not have the same lengths.
+    pub(crate) fn new(
+        weights: &'a [T],
+        points: &'a [OPoint<T, D>],
+        data: &'a [Data],
+        basis_cache: Option<BasisCacheView<'a, T, D>>,
+    ) -> Self {
+        assert_eq!(weights.len(), points.len());
+        assert_eq!(points.len(), data.len());
+        Self {
+            weights,
+            points,
+            data,
+            basis_cache,
+        }
+    }
+
+    /// Populates the reference gradients of the element at the quadrature point with the given
+    /// index, using the cache if available.
+    pub(crate) fn populate_basis_gradients<Element>(
+        &self,
+        element: &Element,
+        quadrature_index: usize,
+        mut basis_gradients: MatrixViewMut<T, D, Dyn>,
+    ) where
+        Element: ReferenceFiniteElement<T, ReferenceDim = D>,
+    {
+        match &self.basis_cache {
+            Some(cache) => {
+                check_cache_dimensions(cache, element.num_nodes(), self.points.len());
+                basis_gradients.copy_from(&cache.basis_gradients(quadrature_index));
+            }
+            None => element.populate_basis_gradients(basis_gradients, &self.points[quadrature_index]),
+        }
+    }
+
+    /// Populates the basis values of the element at the quadrature point with the given index,
+    /// using the cache if available.
+    pub(crate) fn populate_basis<Element>(&self, element: &Element, quadrature_index: usize, basis_values: &mut [T])
+    where
+        Element: ReferenceFiniteElement<T, ReferenceDim = D>,
+    {
+        match &self.basis_cache {
+            Some(cache) => {
+                check_cache_dimensions(cache, element.num_nodes(), self.points.len());
+                basis_values.copy_from_slice(cache.basis_values(quadrature_index));
+            }
+            None => element.populate_basis(basis_values, &self.points[quadrature_index]),
+        }
+    }
+
+    /// Computes the reference Jacobian of the (volumetric) element at the quadrature point with the
+    /// given index.
+    ///
+    /// With a basis cache, the Jacobian is formed from the cached reference gradients.
+    pub(crate) fn reference_jacobian<Element>(&self, element: &Element, quadrature_index: usize) -> OMatrix<T, D, D>
+    where
+        Element: FiniteElement<T, GeometryDim = D, ReferenceDim = D>,
+    {
+        let point = &self.points[quadrature_index];
+        match &self.basis_cache {
+            Some(cache) => {
+                check_cache_dimensions(cache, element.num_nodes(), self.points.len());
+                element.reference_jacobian_from_basis_gradients(point, cache.basis_gradients(quadrature_index))
+            }
+            None => element.reference_jacobian(point),
+        }
+    }
+}
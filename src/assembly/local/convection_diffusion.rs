@@ -0,0 +1,339 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::local::jacobian::JacobianOrientation;
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use crate::error::AssemblyError;
+use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, Dyn, OMatrix, OPoint, OVector, Scalar};
+use crate::space::{FiniteElementConnectivity, VolumetricFiniteElementSpace};
+use crate::{Real, SmallDim, Symmetry};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use numeric_literals::replace_float_literals;
+
+/// A velocity field $\vec b$ used for the convective term of a convection-diffusion problem.
+///
+/// The velocity is evaluated at quadrature points, for which both the element and the reference
+/// coordinates are known, in addition to the physical coordinates. This allows velocities
+/// defined by finite element functions to be evaluated without locating the point in the mesh.
+///
+/// The trait is implemented for closures `Fn(&OPoint<T, D>) -> OVector<T, D>` of the physical
+/// position and for [`InterpolatedVelocityField`].
+pub trait VelocityField<T, D>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn evaluate_velocity(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, D>,
+        coords: &OPoint<T, D>,
+    ) -> OVector<T, D>;
+}
+
+impl<T, D, F> VelocityField<T, D> for F
+where
+    T: Scalar,
+    D: SmallDim,
+    F: Fn(&OPoint<T, D>) -> OVector<T, D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn evaluate_velocity(
+        &self,
+        _element_index: usize,
+        _reference_coords: &OPoint<T, D>,
+        coords: &OPoint<T, D>,
+    ) -> OVector<T, D> {
+        self(coords)
+    }
+}
+
+/// A velocity field given by the nodal values of a finite element function.
+///
+/// The nodal velocities are stored as a vector of length $d n$ for $n$ nodes, with the $d$
+/// components of each node stored contiguously. The space must be the same space as the one used
+/// by the assembler, since the velocity is evaluated by element index and reference coordinates.
+#[derive(Debug, Clone)]
+pub struct InterpolatedVelocityField<'a, T: Scalar, Space> {
+    space: &'a Space,
+    velocity: DVectorView<'a, T>,
+}
+
+impl<'a, T: Scalar, Space> InterpolatedVelocityField<'a, T, Space> {
+    pub fn new(space: &'a Space, velocity: impl Into<DVectorView<'a, T>>) -> Self {
+        Self {
+            space,
+            velocity: velocity.into(),
+        }
+    }
+}
+
+define_thread_local_workspace!(VELOCITY_WORKSPACE);
+
+impl<'a, T, Space> VelocityField<T, Space::GeometryDim> for InterpolatedVelocityField<'a, T, Space>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn evaluate_velocity(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Space::GeometryDim>,
+        _coords: &OPoint<T, Space::GeometryDim>,
+    ) -> OVector<T, Space::GeometryDim> {
+        let d = Space::GeometryDim::dim();
+        let n = self.space.element_node_count(element_index);
+        assert_eq!(
+            self.velocity.len(),
+            d * self.space.num_nodes(),
+            "Length of velocity vector must be consistent with number of nodes and dimension"
+        );
+        with_thread_local_workspace(&VELOCITY_WORKSPACE, |buffer: &mut BasisFunctionBuffer<T>| {
+            buffer.resize(n, d);
+            buffer.populate_element_nodes_from_space(element_index, self.space);
+            buffer.populate_element_basis_values_from_space(element_index, self.space, reference_coords);
+            let mut velocity = OVector::<T, Space::GeometryDim>::zeros();
+            for (&node, &phi) in buffer
+                .element_nodes()
+                .iter()
+                .zip(buffer.element_basis_values())
+            {
+                velocity += self
+                    .velocity
+                    .rows_generic(d * node, Space::GeometryDim::name())
+                    * phi;
+            }
+            velocity
+        })
+    }
+}
+
+/// Computes the SUPG stabilization parameter $\tau$ for an element with diameter $h$.
+///
+/// The parameter is given by
+/// <div>$$
+/// \tau = \frac{h}{2 |\vec b|} \left( \coth(\mathrm{Pe}) - \frac{1}{\mathrm{Pe}} \right),
+/// \qquad \mathrm{Pe} = \frac{|\vec b| h}{2 \epsilon},
+/// $$</div>
+/// where $\mathrm{Pe}$ is the element Péclet number. The parameter vanishes as
+/// $\mathrm{Pe} \rightarrow 0$ and tends to $h / (2 |\vec b|)$ for convection-dominated problems.
+/// For linear elements in one dimension, this choice gives nodally exact solutions.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn supg_stabilization_parameter<T: Real>(diameter: T, velocity_norm: T, diffusivity: T) -> T {
+    if velocity_norm <= 0.0 {
+        return 0.0;
+    }
+    let h = diameter;
+    if diffusivity <= 0.0 {
+        return h / (2.0 * velocity_norm);
+    }
+    let peclet = velocity_norm * h / (2.0 * diffusivity);
+    // coth(Pe) - 1 / Pe suffers from cancellation for small Pe, where it is approximately Pe / 3
+    let xi = if peclet < 1e-3 {
+        peclet / 3.0
+    } else {
+        1.0 / peclet.tanh() - 1.0 / peclet
+    };
+    h / (2.0 * velocity_norm) * xi
+}
+
+/// An element assembler for the steady convection-diffusion operator
+/// $- \epsilon \Delta u + \vec b \cdot \nabla u$.
+///
+/// The element matrix associated with the weak form is
+/// <div>$$
+///   \vec A^K_{IJ} = \int_K \epsilon \\, \nabla \varphi_I \cdot \nabla \varphi_J
+///     + \varphi_I \\, (\vec b \cdot \nabla \varphi_J) \\, \mathrm{d}x,
+/// $$</div>
+/// where the diffusivity $\epsilon$ is constant and the velocity $\vec b$ is a
+/// [`VelocityField`]. Since the convective term is not symmetric, neither is the element matrix.
+/// Any data associated with the quadrature table is ignored.
+///
+/// For convection-dominated problems, in which the element Péclet number exceeds one,
+/// the Galerkin discretization produces spurious oscillations. The streamline upwind
+/// Petrov-Galerkin (SUPG) method, enabled with [`with_supg`](Self::with_supg), adds the term
+/// <div>$$
+///   \int_K \tau \\, (\vec b \cdot \nabla \varphi_I) (\vec b \cdot \nabla \varphi_J) \\, \mathrm{d}x
+/// $$</div>
+/// with $\tau$ given by [`supg_stabilization_parameter`] with the element diameter and the magnitude of
+/// the velocity at each quadrature point. The term $- \epsilon \Delta \varphi_J$ of the residual is
+/// neglected, which is exact for linear simplex elements. For a consistent method, the load vector must
+/// also include the term $\int_K \tau f \\, (\vec b \cdot \nabla \varphi_I) \\, \mathrm{d}x$.
+#[derive(Debug, Clone)]
+pub struct ConvectionDiffusionAssembler<'a, T, Space, QTable: ?Sized, Velocity> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    diffusivity: T,
+    velocity: Velocity,
+    supg: bool,
+}
+
+impl<'a, T, Space, QTable: ?Sized, Velocity> ConvectionDiffusionAssembler<'a, T, Space, QTable, Velocity> {
+    pub fn new(space: &'a Space, qtable: &'a QTable, diffusivity: T, velocity: Velocity) -> Self {
+        Self {
+            space,
+            qtable,
+            diffusivity,
+            velocity,
+            supg: false,
+        }
+    }
+
+    /// Enables or disables SUPG stabilization. Disabled by default.
+    pub fn with_supg(self, supg: bool) -> Self {
+        Self { supg, ..self }
+    }
+
+    pub fn supg(&self) -> bool {
+        self.supg
+    }
+
+    pub fn diffusivity(&self) -> &T {
+        &self.diffusivity
+    }
+
+    pub fn velocity(&self) -> &Velocity {
+        &self.velocity
+    }
+}
+
+impl<'a, T, Space, QTable, Velocity> ElementConnectivityAssembler
+    for ConvectionDiffusionAssembler<'a, T, Space, QTable, Velocity>
+where
+    Space: FiniteElementConnectivity,
+    QTable: ?Sized,
+{
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+struct ConvectionDiffusionWorkspace<T, D>
+where
+    T: Scalar,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D>,
+    basis_buffer: BasisFunctionBuffer<T>,
+    /// Physical gradients of the basis functions.
+    gradients: OMatrix<T, D, Dyn>,
+    /// The streamline derivatives $\vec b \cdot \nabla \varphi_J$.
+    streamline_derivatives: Vec<T>,
+}
+
+impl<T, D> Default for ConvectionDiffusionWorkspace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: QuadratureBuffer::default(),
+            basis_buffer: BasisFunctionBuffer::default(),
+            gradients: OMatrix::<T, D, Dyn>::zeros(0),
+            streamline_derivatives: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, Space, QTable, Velocity> ElementMatrixAssembler<T>
+    for ConvectionDiffusionAssembler<'a, T, Space, QTable, Velocity>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: ?Sized + QuadratureTable<T, Space::ReferenceDim>,
+    Velocity: VelocityField<T, Space::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let n = self.space.element_node_count(element_index);
+        assert_eq!(output.nrows(), n, "Output matrix dimension mismatch");
+        assert_eq!(output.ncols(), n, "Output matrix dimension mismatch");
+        let h = self.space.diameter(element_index);
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut ConvectionDiffusionWorkspace<T, Space::ReferenceDim>| {
+                ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+                ws.quadrature_buffer
+                    .populate_element_weights_and_points_from_table(element_index, self.qtable);
+                ws.gradients.resize_horizontally_mut(n, T::zero());
+                ws.streamline_derivatives.resize(n, T::zero());
+                output.fill(T::zero());
+
+                let mut orientation = JacobianOrientation::new();
+                let (weights, points) = ws.quadrature_buffer.weights_and_points();
+                for (&w, xi) in weights.iter().zip(points) {
+                    ws.basis_buffer
+                        .populate_element_basis_values_from_space(element_index, self.space, xi);
+                    ws.basis_buffer
+                        .populate_element_basis_gradients_from_space(element_index, self.space, xi);
+                    let j = self.space.element_reference_jacobian(element_index, xi);
+                    let j_det = j.determinant();
+                    orientation.record(j_det);
+                    let j_inv = j
+                        .try_inverse()
+                        .ok_or(AssemblyError::SingularJacobian { element_index })?;
+                    let scale = w * j_det.abs();
+
+                    // Physical gradients are given by G = J^{-T} G_ref
+                    ws.gradients.gemm_tr(
+                        T::one(),
+                        &j_inv,
+                        &ws.basis_buffer.element_gradients::<Space::ReferenceDim>(),
+                        T::zero(),
+                    );
+                    let x = self.space.map_element_reference_coords(element_index, xi);
+                    let b = self.velocity.evaluate_velocity(element_index, xi, &x);
+                    for (b_dot_grad, grad) in ws
+                        .streamline_derivatives
+                        .iter_mut()
+                        .zip(ws.gradients.column_iter())
+                    {
+                        *b_dot_grad = b.dot(&grad);
+                    }
+                    let phi = DVectorView::from_slice(ws.basis_buffer.element_basis_values(), n);
+                    let b_dot_grad = DVectorView::from_slice(&ws.streamline_derivatives, n);
+
+                    output.gemm_tr(scale * self.diffusivity, &ws.gradients, &ws.gradients, T::one());
+                    output.ger(scale, &phi, &b_dot_grad, T::one());
+                    if self.supg {
+                        let tau = supg_stabilization_parameter(h, b.norm(), self.diffusivity);
+                        output.ger(scale * tau, &b_dot_grad, &b_dot_grad, T::one());
+                    }
+                }
+
+                orientation
+                    .check()
+                    .map_err(|failure| failure.into_assembly_error(element_index))?;
+
+                Ok(())
+            },
+        )
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::NonSymmetric
+    }
+}
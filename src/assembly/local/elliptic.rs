@@ -1,12 +1,15 @@
 use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
 use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
 use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::cached_basis::ElementQuadrature;
+use crate::assembly::local::jacobian::{try_invert_jacobian_with_policy, JacobianFailure, JacobianOrientation};
 use crate::assembly::local::{
     ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
-    QuadratureTable,
+    JacobianConditioningPolicy, QuadratureTable,
 };
 use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
-use crate::element::VolumetricFiniteElement;
+use crate::element::{ElementGeometryDim, ReferenceFiniteElement, VolumetricFiniteElement};
+use crate::error::AssemblyError;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{
     DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, Dim, DimName, Dyn, MatrixView,
@@ -17,7 +20,6 @@ use crate::util::{clone_upper_to_lower, reshape_to_slice};
 use crate::Real;
 use crate::Symmetry;
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
-use eyre::eyre;
 use itertools::izip;
 
 // TODO: Move this to the right spot and don't make it pub(crate)
@@ -58,6 +60,14 @@ where
     jacobian_inv_t * u_grad
 }
 
+/// The quadrature of an element, with quadrature data holding the parameters of the operator `Op`.
+type OperatorQuadrature<'a, T, Element, Op> = ElementQuadrature<
+    'a,
+    T,
+    <Element as ReferenceFiniteElement<T>>::ReferenceDim,
+    <Op as Operator<T, ElementGeometryDim<T, Element>>>::Parameters,
+>;
+
 /// TODO: The builder here is pretty complex. Is it possible to simplify without losing too
 /// much type safety?
 pub struct ElementEllipticAssemblerBuilder<Space, Op, QTable, U> {
@@ -145,6 +155,7 @@ where
             op: self.op,
             qtable: self.qtable,
             u: self.u,
+            jacobian_policy: JacobianConditioningPolicy::Ignore,
         }
     }
 }
@@ -155,6 +166,40 @@ pub struct ElementEllipticAssembler<'a, T: Scalar, Space, Op, QTable: ?Sized> {
     op: &'a Op,
     qtable: &'a QTable,
     u: DVectorView<'a, T>,
+    jacobian_policy: JacobianConditioningPolicy<T>,
+}
+
+impl<'a, T: Scalar, Space, Op, QTable: ?Sized> ElementEllipticAssembler<'a, T, Space, Op, QTable> {
+    /// Sets the policy for treating ill-conditioned element Jacobians.
+    ///
+    /// The default policy is [`JacobianConditioningPolicy::Ignore`]. Errors raised by the policy
+    /// report the index of the offending element.
+    pub fn with_jacobian_policy(mut self, policy: JacobianConditioningPolicy<T>) -> Self {
+        self.jacobian_policy = policy;
+        self
+    }
+}
+
+impl<'a, T, Space, Op, QTable> ElementEllipticAssembler<'a, T, Space, Op, QTable>
+where
+    T: Scalar,
+    Space: VolumetricFiniteElementSpace<T>,
+    Op: Operator<T, Space::GeometryDim>,
+    QTable: ?Sized,
+    DefaultAllocator: DimAllocator<T, Space::GeometryDim>,
+{
+    fn check_u_dimensions(&self, element_index: usize) -> eyre::Result<()> {
+        let expected = self.solution_dim() * self.num_nodes();
+        if self.u.len() != expected {
+            return Err(AssemblyError::IncompatibleDimensions {
+                element_index,
+                expected,
+                actual: self.u.len(),
+            }
+            .into());
+        }
+        Ok(())
+    }
 }
 
 impl<'a, T, Space, Op, QTable> ElementConnectivityAssembler for ElementEllipticAssembler<'a, T, Space, Op, QTable>
@@ -227,6 +272,7 @@ where
         let s = self.solution_dim();
         let n = self.element_node_count(element_index);
 
+        self.check_u_dimensions(element_index)?;
         with_thread_local_workspace(
             &WORKSPACE,
             |ws: &mut EllipticAssemblerWorkspace<T, Space::ReferenceDim, Op::Parameters>| {
@@ -240,15 +286,20 @@ where
                     .populate_element_quadrature_from_table(element_index, self.qtable);
 
                 let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
-                compute_element_elliptic_energy(
+                compute_element_elliptic_energy_impl(
                     &element,
                     self.op,
                     DVectorView::from(&ws.u_element),
-                    ws.quadrature_buffer.weights(),
-                    ws.quadrature_buffer.points(),
-                    ws.quadrature_buffer.data(),
+                    ElementQuadrature::new(
+                        ws.quadrature_buffer.weights(),
+                        ws.quadrature_buffer.points(),
+                        ws.quadrature_buffer.data(),
+                        self.qtable.element_basis_cache(element_index),
+                    ),
                     ws.basis_buffer.element_gradients_mut(),
+                    &self.jacobian_policy,
                 )
+                .map_err(|failure| eyre::Report::new(failure.into_assembly_error(element_index)))
             },
         )
     }
@@ -268,6 +319,7 @@ where
         let n = self.element_node_count(element_index);
         assert_eq!(output.len(), s * n, "Output vector dimension mismatch");
 
+        self.check_u_dimensions(element_index)?;
         with_thread_local_workspace(
             &WORKSPACE,
             |ws: &mut EllipticAssemblerWorkspace<T, Space::ReferenceDim, Op::Parameters>| {
@@ -281,16 +333,21 @@ where
                     .populate_element_quadrature_from_table(element_index, self.qtable);
 
                 let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
-                assemble_element_elliptic_vector(
+                assemble_element_elliptic_vector_impl(
                     output,
                     &element,
                     self.op,
                     DVectorView::from(&ws.u_element),
-                    ws.quadrature_buffer.weights(),
-                    ws.quadrature_buffer.points(),
-                    ws.quadrature_buffer.data(),
+                    ElementQuadrature::new(
+                        ws.quadrature_buffer.weights(),
+                        ws.quadrature_buffer.points(),
+                        ws.quadrature_buffer.data(),
+                        self.qtable.element_basis_cache(element_index),
+                    ),
                     ws.basis_buffer.element_gradients_mut(),
+                    &self.jacobian_policy,
                 )
+                .map_err(|failure| eyre::Report::new(failure.into_assembly_error(element_index)))
             },
         )
     }
@@ -311,6 +368,7 @@ where
         assert_eq!(output.nrows(), s * n, "Output matrix dimension mismatch");
         assert_eq!(output.ncols(), s * n, "Output matrix dimension mismatch");
 
+        self.check_u_dimensions(element_index)?;
         with_thread_local_workspace(
             &WORKSPACE,
             |ws: &mut EllipticAssemblerWorkspace<T, Space::ReferenceDim, Op::Parameters>| {
@@ -324,19 +382,28 @@ where
                     .populate_element_quadrature_from_table(element_index, self.qtable);
 
                 let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
-                assemble_element_elliptic_matrix(
+                assemble_element_elliptic_matrix_impl(
                     output,
                     &element,
                     self.op,
                     DVectorView::from(&ws.u_element),
-                    ws.quadrature_buffer.weights(),
-                    ws.quadrature_buffer.points(),
-                    ws.quadrature_buffer.data(),
+                    ElementQuadrature::new(
+                        ws.quadrature_buffer.weights(),
+                        ws.quadrature_buffer.points(),
+                        ws.quadrature_buffer.data(),
+                        self.qtable.element_basis_cache(element_index),
+                    ),
                     ws.basis_buffer.element_gradients_mut(),
+                    &self.jacobian_policy,
                 )
+                .map_err(|failure| eyre::Report::new(failure.into_assembly_error(element_index)))
             },
         )
     }
+
+    fn symmetry(&self) -> Symmetry {
+        self.op.symmetry()
+    }
 }
 
 /// Assembles the element (derivative) matrix associated with the given elliptic operator.
@@ -359,7 +426,7 @@ where
 /// Panics if the number of columns in the gradient buffer is not equal to the number of nodes
 /// in the element.
 pub fn assemble_element_elliptic_matrix<T, Element, Contraction>(
-    mut output: DMatrixViewMut<T>,
+    output: DMatrixViewMut<T>,
     element: &Element,
     operator: &Contraction,
     u_element: DVectorView<T>,
@@ -375,8 +442,34 @@ where
     Contraction: EllipticContraction<T, Element::GeometryDim>,
     DefaultAllocator: BiDimAllocator<T, Contraction::SolutionDim, Element::GeometryDim>,
 {
-    assert_eq!(quadrature_weights.len(), quadrature_points.len());
-    assert_eq!(quadrature_points.len(), quadrature_data.len());
+    assemble_element_elliptic_matrix_impl(
+        output,
+        element,
+        operator,
+        u_element,
+        ElementQuadrature::new(quadrature_weights, quadrature_points, quadrature_data, None),
+        basis_gradients_buffer,
+        &JacobianConditioningPolicy::Ignore,
+    )
+    .map_err(JacobianFailure::into_report)
+}
+
+fn assemble_element_elliptic_matrix_impl<T, Element, Contraction>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    operator: &Contraction,
+    u_element: DVectorView<T>,
+    quadrature: OperatorQuadrature<T, Element, Contraction>,
+    basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+    jacobian_policy: &JacobianConditioningPolicy<T>,
+) -> Result<(), JacobianFailure<T>>
+where
+    T: Real,
+    // We only support volumetric elements atm
+    Element: VolumetricFiniteElement<T>,
+    Contraction: EllipticContraction<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Contraction::SolutionDim, Element::GeometryDim>,
+{
     assert_eq!(basis_gradients_buffer.ncols(), element.num_nodes());
 
     let d = Element::GeometryDim::dim();
@@ -394,19 +487,18 @@ where
 
     let mut phi_grad = basis_gradients_buffer;
 
-    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
-    for (&weight, point, data) in quadrature_iter {
-        let j = element.reference_jacobian(point);
+    let mut orientation = JacobianOrientation::new();
+    let quadrature_iter = izip!(quadrature.weights, quadrature.data);
+    for (q, (&weight, data)) in quadrature_iter.enumerate() {
+        // First populate gradients with respect to reference coords
+        quadrature.populate_basis_gradients(element, q, MatrixViewMut::from(&mut phi_grad));
+
+        let j = element.reference_jacobian_from_basis_gradients(&quadrature.points[q], MatrixView::from(&phi_grad));
         let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
+        orientation.record(j_det);
+        let j_inv = try_invert_jacobian_with_policy(&j, jacobian_policy)?;
         let j_inv_t = j_inv.transpose();
 
-        // First populate gradients with respect to reference coords
-        element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad), &point);
-
         // We currently have to compute u_grad by providing reference gradients
         let u_element = reshape_to_slice(&u_element, (s, Dyn(n)));
         let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad, u_element);
@@ -431,6 +523,8 @@ where
         );
     }
 
+    orientation.check()?;
+
     if matches!(operator.symmetry(), Symmetry::Symmetric) {
         clone_upper_to_lower(&mut output);
     }
@@ -455,7 +549,7 @@ where
 /// Panics if the number of columns in the gradient buffer is not equal to the number of nodes
 /// in the element.
 pub fn assemble_element_elliptic_vector<T, Element, Operator>(
-    mut output: DVectorViewMut<T>,
+    output: DVectorViewMut<T>,
     element: &Element,
     operator: &Operator,
     u_element: DVectorView<T>,
@@ -471,8 +565,34 @@ where
     Operator: EllipticOperator<T, Element::GeometryDim>,
     DefaultAllocator: BiDimAllocator<T, Operator::SolutionDim, Element::GeometryDim>,
 {
-    assert_eq!(quadrature_weights.len(), quadrature_points.len());
-    assert_eq!(quadrature_points.len(), quadrature_data.len());
+    assemble_element_elliptic_vector_impl(
+        output,
+        element,
+        operator,
+        u_element,
+        ElementQuadrature::new(quadrature_weights, quadrature_points, quadrature_data, None),
+        basis_gradients_buffer,
+        &JacobianConditioningPolicy::Ignore,
+    )
+    .map_err(JacobianFailure::into_report)
+}
+
+fn assemble_element_elliptic_vector_impl<T, Element, Operator>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    operator: &Operator,
+    u_element: DVectorView<T>,
+    quadrature: OperatorQuadrature<T, Element, Operator>,
+    basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+    jacobian_policy: &JacobianConditioningPolicy<T>,
+) -> Result<(), JacobianFailure<T>>
+where
+    T: Real,
+    // We only support volumetric elements atm
+    Element: VolumetricFiniteElement<T>,
+    Operator: EllipticOperator<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Operator::SolutionDim, Element::GeometryDim>,
+{
     assert_eq!(basis_gradients_buffer.ncols(), element.num_nodes());
 
     let s = Operator::SolutionDim::dim();
@@ -488,19 +608,18 @@ where
 
     let mut phi_grad_ref = basis_gradients_buffer;
 
-    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
-    for (&weight, point, data) in quadrature_iter {
-        let j = element.reference_jacobian(point);
+    let mut orientation = JacobianOrientation::new();
+    let quadrature_iter = izip!(quadrature.weights, quadrature.data);
+    for (q, (&weight, data)) in quadrature_iter.enumerate() {
+        // First populate gradients with respect to reference coords
+        quadrature.populate_basis_gradients(element, q, MatrixViewMut::from(&mut phi_grad_ref));
+
+        let j = element.reference_jacobian_from_basis_gradients(&quadrature.points[q], MatrixView::from(&phi_grad_ref));
         let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
+        orientation.record(j_det);
+        let j_inv = try_invert_jacobian_with_policy(&j, jacobian_policy)?;
         let j_inv_t = j_inv.transpose();
 
-        // First populate gradients with respect to reference coords
-        element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad_ref), &point);
-
         let u_element = MatrixView::from_slice_generic(u_element.as_slice(), Operator::SolutionDim::name(), Dyn(n));
         let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad_ref, u_element);
 
@@ -527,6 +646,7 @@ where
         output.gemm(weight * j_det.abs(), &g_t_j_inv_t, &phi_grad_ref, T::one());
     }
 
+    orientation.check()?;
     Ok(())
 }
 
@@ -557,6 +677,32 @@ pub fn compute_element_elliptic_energy<T, Element, Operator>(
     quadrature_data: &[Operator::Parameters],
     basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
 ) -> eyre::Result<T>
+where
+    T: Real,
+    // We only support volumetric elements atm
+    Element: VolumetricFiniteElement<T>,
+    Operator: EllipticEnergy<T, Element::GeometryDim>,
+    DefaultAllocator: BiDimAllocator<T, Operator::SolutionDim, Element::GeometryDim>,
+{
+    compute_element_elliptic_energy_impl(
+        element,
+        operator,
+        u_element,
+        ElementQuadrature::new(quadrature_weights, quadrature_points, quadrature_data, None),
+        basis_gradients_buffer,
+        &JacobianConditioningPolicy::Ignore,
+    )
+    .map_err(JacobianFailure::into_report)
+}
+
+fn compute_element_elliptic_energy_impl<T, Element, Operator>(
+    element: &Element,
+    operator: &Operator,
+    u_element: DVectorView<T>,
+    quadrature: OperatorQuadrature<T, Element, Operator>,
+    basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+    jacobian_policy: &JacobianConditioningPolicy<T>,
+) -> Result<T, JacobianFailure<T>>
 where
     T: Real,
     // We only support volumetric elements atm
@@ -566,8 +712,6 @@ where
 {
     let s = Operator::SolutionDim::dim();
     let n = element.num_nodes();
-    assert_eq!(quadrature_weights.len(), quadrature_points.len());
-    assert_eq!(quadrature_points.len(), quadrature_data.len());
     assert_eq!(basis_gradients_buffer.ncols(), n);
     assert_eq!(
         u_element.len(),
@@ -578,21 +722,20 @@ where
     let mut phi_grad_ref = basis_gradients_buffer;
 
     let mut integral = T::zero();
-    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_data);
-    for (&weight, point, data) in quadrature_iter {
+    let mut orientation = JacobianOrientation::new();
+    let quadrature_iter = izip!(quadrature.weights, quadrature.data);
+    for (q, (&weight, data)) in quadrature_iter.enumerate() {
         // All this stuff is basically the same for energy, vector and matrix. TODO: Consolidate?
 
-        let j = element.reference_jacobian(point);
+        // First populate gradients with respect to reference coords
+        quadrature.populate_basis_gradients(element, q, MatrixViewMut::from(&mut phi_grad_ref));
+
+        let j = element.reference_jacobian_from_basis_gradients(&quadrature.points[q], MatrixView::from(&phi_grad_ref));
         let j_det = j.determinant();
-        let j_inv = j
-            .try_inverse()
-            // TODO: Return a "proper" error instead of using eyre
-            .ok_or_else(|| eyre!("Singular element Jacobian encountered"))?;
+        orientation.record(j_det);
+        let j_inv = try_invert_jacobian_with_policy(&j, jacobian_policy)?;
         let j_inv_t = j_inv.transpose();
 
-        // First populate gradients with respect to reference coords
-        element.populate_basis_gradients(MatrixViewMut::from(&mut phi_grad_ref), &point);
-
         let u_element = MatrixView::from_slice_generic(u_element.as_slice(), Operator::SolutionDim::name(), Dyn(n));
         let u_grad = compute_volume_u_grad(&j_inv_t, &phi_grad_ref, u_element);
 
@@ -601,5 +744,6 @@ where
         integral += weight * j_det.abs() * psi;
     }
 
+    orientation.check()?;
     Ok(integral)
 }
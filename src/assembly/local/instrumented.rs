@@ -0,0 +1,337 @@
+use crate::assembly::local::{
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
+};
+use crate::nalgebra::{DMatrixViewMut, DVectorViewMut, Scalar};
+use crate::Symmetry;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// The assembly stages recorded by an [`InstrumentedAssembler`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AssemblyStage {
+    Scalar,
+    Vector,
+    Matrix,
+}
+
+impl AssemblyStage {
+    pub fn all() -> [AssemblyStage; 3] {
+        [AssemblyStage::Scalar, AssemblyStage::Vector, AssemblyStage::Matrix]
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            AssemblyStage::Scalar => 0,
+            AssemblyStage::Vector => 1,
+            AssemblyStage::Matrix => 2,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ElementCounters {
+    nanos: [AtomicU64; 3],
+    calls: [AtomicU64; 3],
+}
+
+/// An element assembler that records per-element wall time and call counts.
+///
+/// The instrumented assembler wraps another element assembler and implements all element assembler
+/// traits implemented by the wrapped assembler by delegation. Every call to assemble an element
+/// scalar, vector or matrix is timed, and the elapsed time is accumulated per element and
+/// per [`AssemblyStage`]. The accumulators are atomic, so the instrumented assembler can be
+/// used with parallel assembly.
+///
+/// Instrumentation is opt-in: assemblers that are not wrapped incur no overhead.
+///
+/// The per-element times can be visualized by exporting them as a cell attribute, for example:
+///
+/// ```ignore
+/// FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+///     .with_cell_scalar_attributes("assembly_time", 1, &instrumented.element_times_in_seconds())
+///     .try_export("assembly_time.vtu")?;
+/// ```
+#[derive(Debug)]
+pub struct InstrumentedAssembler<Assembler> {
+    assembler: Assembler,
+    counters: Vec<ElementCounters>,
+}
+
+impl<Assembler> InstrumentedAssembler<Assembler>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    pub fn new(assembler: Assembler) -> Self {
+        let counters = (0..assembler.num_elements())
+            .map(|_| ElementCounters::default())
+            .collect();
+        Self { assembler, counters }
+    }
+}
+
+impl<Assembler> InstrumentedAssembler<Assembler> {
+    pub fn inner(&self) -> &Assembler {
+        &self.assembler
+    }
+
+    pub fn into_inner(self) -> Assembler {
+        self.assembler
+    }
+
+    /// Resets all recorded times and call counts.
+    pub fn reset(&self) {
+        for counters in &self.counters {
+            for (nanos, calls) in counters.nanos.iter().zip(&counters.calls) {
+                nanos.store(0, Ordering::Relaxed);
+                calls.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns the total number of calls for the given stage, summed over all elements.
+    pub fn stage_call_count(&self, stage: AssemblyStage) -> u64 {
+        self.counters
+            .iter()
+            .map(|counters| counters.calls[stage.index()].load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns the number of calls for the given element, summed over all stages.
+    pub fn element_call_count(&self, element_index: usize) -> u64 {
+        self.counters[element_index]
+            .calls
+            .iter()
+            .map(|calls| calls.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns the accumulated time for the given element and stage.
+    pub fn element_stage_time(&self, element_index: usize, stage: AssemblyStage) -> Duration {
+        Duration::from_nanos(self.counters[element_index].nanos[stage.index()].load(Ordering::Relaxed))
+    }
+
+    /// Returns the accumulated time for the given element, summed over all stages.
+    pub fn element_time(&self, element_index: usize) -> Duration {
+        AssemblyStage::all()
+            .iter()
+            .map(|&stage| self.element_stage_time(element_index, stage))
+            .sum()
+    }
+
+    /// Returns the accumulated time for each element, summed over all stages.
+    pub fn element_times(&self) -> Vec<Duration> {
+        (0..self.counters.len())
+            .map(|element_index| self.element_time(element_index))
+            .collect()
+    }
+
+    /// Returns the accumulated time in seconds for each element, summed over all stages.
+    ///
+    /// This is convenient for exporting the times as a cell attribute for visualization.
+    pub fn element_times_in_seconds(&self) -> Vec<f64> {
+        self.element_times()
+            .iter()
+            .map(Duration::as_secs_f64)
+            .collect()
+    }
+
+    /// Produces a summary report of the recorded timings.
+    ///
+    /// The report lists the `num_worst_elements` elements with the largest accumulated time.
+    /// Only elements that have been assembled at least once are taken into account for
+    /// the statistics.
+    pub fn report(&self, num_worst_elements: usize) -> AssemblyTimingReport {
+        let mut assembled_elements: Vec<(usize, Duration)> = (0..self.counters.len())
+            .filter(|&element_index| self.element_call_count(element_index) > 0)
+            .map(|element_index| (element_index, self.element_time(element_index)))
+            .collect();
+
+        let num_assembled_elements = assembled_elements.len();
+        let total_time: Duration = assembled_elements.iter().map(|(_, time)| *time).sum();
+        let mean_time = if num_assembled_elements > 0 {
+            total_time / num_assembled_elements as u32
+        } else {
+            Duration::ZERO
+        };
+
+        // Sort by decreasing time, breaking ties by element index
+        assembled_elements.sort_by(|(idx_a, time_a), (idx_b, time_b)| time_b.cmp(time_a).then(idx_a.cmp(idx_b)));
+        // Nearest-rank percentile
+        let p99_time = if num_assembled_elements > 0 {
+            let rank = (0.99 * num_assembled_elements as f64).ceil() as usize;
+            assembled_elements[num_assembled_elements - rank.max(1)].1
+        } else {
+            Duration::ZERO
+        };
+        let max_time = assembled_elements
+            .first()
+            .map(|(_, time)| *time)
+            .unwrap_or(Duration::ZERO);
+
+        let stages = AssemblyStage::all()
+            .iter()
+            .map(|&stage| StageTiming {
+                stage,
+                num_calls: self.stage_call_count(stage),
+                total_time: (0..self.counters.len())
+                    .map(|element_index| self.element_stage_time(element_index, stage))
+                    .sum(),
+            })
+            .collect();
+
+        assembled_elements.truncate(num_worst_elements);
+
+        AssemblyTimingReport {
+            num_elements: self.counters.len(),
+            num_assembled_elements,
+            num_calls: AssemblyStage::all()
+                .iter()
+                .map(|&stage| self.stage_call_count(stage))
+                .sum(),
+            total_time,
+            mean_time,
+            p99_time,
+            max_time,
+            stages,
+            worst_elements: assembled_elements,
+        }
+    }
+
+    fn record<R>(&self, element_index: usize, stage: AssemblyStage, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        if let Some(counters) = self.counters.get(element_index) {
+            let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+            counters.nanos[stage.index()].fetch_add(nanos, Ordering::Relaxed);
+            counters.calls[stage.index()].fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+/// Accumulated timings for a single assembly stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: AssemblyStage,
+    pub num_calls: u64,
+    pub total_time: Duration,
+}
+
+/// Summary of the timings recorded by an [`InstrumentedAssembler`].
+///
+/// Element times refer to the accumulated time for each element, summed over all stages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyTimingReport {
+    pub num_elements: usize,
+    /// The number of elements that have been assembled at least once.
+    pub num_assembled_elements: usize,
+    pub num_calls: u64,
+    pub total_time: Duration,
+    pub mean_time: Duration,
+    pub p99_time: Duration,
+    pub max_time: Duration,
+    pub stages: Vec<StageTiming>,
+    /// The indices and times of the most expensive elements, sorted by decreasing time.
+    pub worst_elements: Vec<(usize, Duration)>,
+}
+
+impl Display for AssemblyTimingReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Assembled {} of {} elements in {} calls.",
+            self.num_assembled_elements, self.num_elements, self.num_calls
+        )?;
+        writeln!(
+            f,
+            "Element time: total {:?}, mean {:?}, p99 {:?}, max {:?}",
+            self.total_time, self.mean_time, self.p99_time, self.max_time
+        )?;
+        for stage in &self.stages {
+            if stage.num_calls > 0 {
+                writeln!(
+                    f,
+                    "  {:?}: {} calls, {:?}",
+                    stage.stage, stage.num_calls, stage.total_time
+                )?;
+            }
+        }
+        if !self.worst_elements.is_empty() {
+            writeln!(f, "Most expensive elements:")?;
+            for (element_index, time) in &self.worst_elements {
+                writeln!(f, "  {}: {:?}", element_index, time)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<Assembler> ElementConnectivityAssembler for InstrumentedAssembler<Assembler>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<T, Assembler> ElementScalarAssembler<T> for InstrumentedAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementScalarAssembler<T>,
+{
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<T> {
+        self.record(element_index, AssemblyStage::Scalar, || {
+            self.assembler.assemble_element_scalar(element_index)
+        })
+    }
+}
+
+impl<T, Assembler> ElementVectorAssembler<T> for InstrumentedAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementVectorAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        self.record(element_index, AssemblyStage::Vector, || {
+            self.assembler
+                .assemble_element_vector_into(element_index, output)
+        })
+    }
+}
+
+impl<T, Assembler> ElementMatrixAssembler<T> for InstrumentedAssembler<Assembler>
+where
+    T: Scalar,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.record(element_index, AssemblyStage::Matrix, || {
+            self.assembler
+                .assemble_element_matrix_into(element_index, output)
+        })
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        self.assembler.symmetry()
+    }
+}
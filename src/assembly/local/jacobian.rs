@@ -0,0 +1,208 @@
+use crate::allocators::DimAllocator;
+use crate::error::AssemblyError;
+use crate::nalgebra::{DMatrix, DefaultAllocator, OMatrix};
+use crate::{Real, SmallDim};
+use eyre::eyre;
+
+/// Determines how ill-conditioned element Jacobians are treated when computing gradients with
+/// respect to physical coordinates.
+///
+/// Physical gradients are obtained by transforming reference gradients with $\vec J^{-T}$, where
+/// $\vec J$ is the Jacobian of the reference-to-physical map. For nearly degenerate elements,
+/// $\vec J$ is nearly singular and its inverse amplifies round-off errors catastrophically.
+/// The conditioning is measured by the condition number
+/// $\kappa_1(\vec J) = \| \vec J \|_1 \| \vec J^{-1} \|_1$, see [`jacobian_condition_number`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JacobianConditioningPolicy<T> {
+    /// Invert the Jacobian without checking its conditioning. Only exactly singular Jacobians
+    /// are rejected.
+    Ignore,
+    /// Fail with an error if the condition number exceeds `max_condition_number`.
+    Error { max_condition_number: T },
+    /// If the condition number exceeds `max_condition_number`, use a truncated pseudo-inverse
+    /// that discards singular values smaller than $\sigma_{\max}$ / `max_condition_number`.
+    PseudoInverse { max_condition_number: T },
+}
+
+impl<T> Default for JacobianConditioningPolicy<T> {
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
+/// Computes the condition number $\kappa_1(\vec J) = \| \vec J \|_1 \| \vec J^{-1} \|_1$
+/// of the given (small) Jacobian matrix.
+///
+/// Returns infinity if the matrix is singular.
+pub fn jacobian_condition_number<T, D>(jacobian: &OMatrix<T, D, D>) -> T
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let norm_1 = |matrix: &OMatrix<T, D, D>| {
+        matrix
+            .column_iter()
+            .map(|column| column.lp_norm(1))
+            .fold(T::zero(), |a, b| a.max(b))
+    };
+    match jacobian.clone().try_inverse() {
+        Some(inverse) => norm_1(jacobian) * norm_1(&inverse),
+        None => T::from_f64(f64::INFINITY).unwrap(),
+    }
+}
+
+/// Inverts the given element Jacobian subject to the given conditioning policy.
+///
+/// With [`JacobianConditioningPolicy::Ignore`], the result is identical to
+/// [`try_inverse`](OMatrix::try_inverse). Returns an error if the Jacobian is singular and no
+/// pseudo-inverse is requested, or if the policy is [`JacobianConditioningPolicy::Error`] and the
+/// condition number exceeds the bound.
+pub fn invert_jacobian_with_policy<T, D>(
+    jacobian: &OMatrix<T, D, D>,
+    policy: &JacobianConditioningPolicy<T>,
+) -> eyre::Result<OMatrix<T, D, D>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    try_invert_jacobian_with_policy(jacobian, policy).map_err(JacobianFailure::into_report)
+}
+
+/// The ways in which the Jacobian of an element can be unsuitable for assembly.
+///
+/// Low-level routines that operate on a single element do not know the index of the element.
+/// Assemblers convert failures to [`AssemblyError`] with
+/// [`into_assembly_error`](Self::into_assembly_error), while the public low-level routines
+/// report them as plain [`eyre::Report`]s.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum JacobianFailure<T> {
+    Singular,
+    IllConditioned {
+        condition_number: T,
+        max_condition_number: T,
+    },
+    Inverted {
+        min_determinant: T,
+    },
+}
+
+impl<T: Real> JacobianFailure<T> {
+    pub(crate) fn into_assembly_error(self, element_index: usize) -> AssemblyError {
+        let to_f64 = |x: T| x.to_subset().unwrap_or(f64::NAN);
+        match self {
+            Self::Singular => AssemblyError::SingularJacobian { element_index },
+            Self::IllConditioned {
+                condition_number,
+                max_condition_number,
+            } => AssemblyError::IllConditionedJacobian {
+                element_index,
+                condition_number: to_f64(condition_number),
+                max_condition_number: to_f64(max_condition_number),
+            },
+            Self::Inverted { min_determinant } => AssemblyError::ElementInverted {
+                element_index,
+                min_jacobian_determinant: to_f64(min_determinant),
+            },
+        }
+    }
+
+    pub(crate) fn into_report(self) -> eyre::Report {
+        match self {
+            Self::Singular => eyre!("Singular element Jacobian encountered"),
+            Self::IllConditioned {
+                condition_number,
+                max_condition_number,
+            } => eyre!(
+                "Ill-conditioned element Jacobian: condition number {} exceeds the maximum {}",
+                condition_number,
+                max_condition_number
+            ),
+            Self::Inverted { min_determinant } => eyre!(
+                "Inverted element encountered: Jacobian determinant changes sign (minimum {})",
+                min_determinant
+            ),
+        }
+    }
+}
+
+pub(crate) fn try_invert_jacobian_with_policy<T, D>(
+    jacobian: &OMatrix<T, D, D>,
+    policy: &JacobianConditioningPolicy<T>,
+) -> Result<OMatrix<T, D, D>, JacobianFailure<T>>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let max_condition_number = match *policy {
+        JacobianConditioningPolicy::Ignore => {
+            return jacobian
+                .clone()
+                .try_inverse()
+                .ok_or(JacobianFailure::Singular);
+        }
+        JacobianConditioningPolicy::Error { max_condition_number }
+        | JacobianConditioningPolicy::PseudoInverse { max_condition_number } => max_condition_number,
+    };
+
+    let condition_number = jacobian_condition_number(jacobian);
+    if condition_number <= max_condition_number {
+        return jacobian
+            .clone()
+            .try_inverse()
+            .ok_or(JacobianFailure::Singular);
+    }
+
+    match policy {
+        JacobianConditioningPolicy::PseudoInverse { .. } => {
+            let d = D::dim();
+            let svd = DMatrix::from_fn(d, d, |i, j| jacobian[(i, j)]).svd(true, true);
+            let tolerance = svd.singular_values.max() / max_condition_number;
+            // The pseudo-inverse only fails for negative tolerances, i.e. if the Jacobian is zero
+            let pseudo_inverse = svd
+                .pseudo_inverse(tolerance)
+                .map_err(|_| JacobianFailure::Singular)?;
+            Ok(OMatrix::<T, D, D>::from_fn(|i, j| pseudo_inverse[(i, j)]))
+        }
+        _ => Err(JacobianFailure::IllConditioned {
+            condition_number,
+            max_condition_number,
+        }),
+    }
+}
+
+/// Tracks the sign of the Jacobian determinant over the quadrature points of an element.
+///
+/// Elements may be consistently oriented either way, but an element whose determinant
+/// changes sign is folded over itself.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct JacobianOrientation<T> {
+    min_determinant: T,
+    max_determinant: T,
+}
+
+impl<T: Real> JacobianOrientation<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            min_determinant: T::max_value().unwrap(),
+            max_determinant: T::min_value().unwrap(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, determinant: T) {
+        self.min_determinant = self.min_determinant.min(determinant);
+        self.max_determinant = self.max_determinant.max(determinant);
+    }
+
+    pub(crate) fn check(&self) -> Result<(), JacobianFailure<T>> {
+        if self.min_determinant < T::zero() && self.max_determinant > T::zero() {
+            Err(JacobianFailure::Inverted {
+                min_determinant: self.min_determinant,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use crate::allocators::DimAllocator;
 use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
-use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler, QuadratureTable};
+use crate::assembly::local::cached_basis::ElementQuadrature;
+use crate::assembly::local::{
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementVectorAssembler, QuadratureTable,
+};
 use crate::element::{ReferenceFiniteElement, VolumetricFiniteElement};
-use crate::nalgebra::{DMatrixViewMut, DefaultAllocator, DimName, OPoint};
+use crate::nalgebra::{DMatrixViewMut, DVectorViewMut, DefaultAllocator, DimName, OPoint};
 use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
 use crate::util::clone_upper_to_lower;
-use crate::Real;
+use crate::{Real, Symmetry};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
 use itertools::izip;
 use nalgebra::Scalar;
@@ -145,17 +148,24 @@ where
             ws.quadrature_buffer
                 .populate_element_quadrature_from_table(element_index, self.qtable);
 
-            assemble_element_mass_matrix(
+            assemble_element_mass_matrix_(
                 output,
                 &element,
-                ws.quadrature_buffer.weights(),
-                ws.quadrature_buffer.points(),
-                Density::as_inner_slice(ws.quadrature_buffer.data()),
+                ElementQuadrature::new(
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    Density::as_inner_slice(ws.quadrature_buffer.data()),
+                    self.qtable.element_basis_cache(element_index),
+                ),
                 self.solution_dim,
                 ws.basis_buffer.element_basis_values_mut(),
             )
         })
     }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
 }
 
 /// Assembles the element mass matrix using the provided quadrature.
@@ -206,9 +216,7 @@ where
     assemble_element_mass_matrix_(
         output.into(),
         element,
-        quadrature_weights,
-        quadrature_points,
-        quadrature_density,
+        ElementQuadrature::new(quadrature_weights, quadrature_points, quadrature_density, None),
         solution_dim,
         basis_values_buffer,
     )
@@ -218,9 +226,7 @@ where
 fn assemble_element_mass_matrix_<T, Element>(
     mut output: DMatrixViewMut<T>,
     element: &Element,
-    quadrature_weights: &[T],
-    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
-    quadrature_density: &[T],
+    quadrature: ElementQuadrature<T, Element::ReferenceDim, T>,
     solution_dim: usize,
     basis_values_buffer: &mut [T],
 ) -> eyre::Result<()>
@@ -230,8 +236,6 @@ where
     Element: VolumetricFiniteElement<T>,
     DefaultAllocator: DimAllocator<T, Element::GeometryDim>,
 {
-    assert_eq!(quadrature_weights.len(), quadrature_points.len());
-    assert_eq!(quadrature_points.len(), quadrature_density.len());
     assert_eq!(basis_values_buffer.len(), element.num_nodes());
 
     let s = solution_dim;
@@ -243,12 +247,12 @@ where
 
     let phi = basis_values_buffer;
 
-    let quadrature_iter = izip!(quadrature_weights, quadrature_points, quadrature_density);
-    for (&weight, point, density) in quadrature_iter {
-        let j_det = element.reference_jacobian(point).determinant();
+    let quadrature_iter = izip!(quadrature.weights, quadrature.data);
+    for (q, (&weight, density)) in quadrature_iter.enumerate() {
+        let j_det = quadrature.reference_jacobian(element, q).determinant();
 
         // First populate basis values with respect to reference coords
-        element.populate_basis(phi, &point);
+        quadrature.populate_basis(element, q, phi);
 
         let scale = weight * j_det.abs() * *density;
 
@@ -284,3 +288,133 @@ where
 
     Ok(())
 }
+
+/// Strategy for lumping a consistent (element) mass matrix into a diagonal matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LumpingStrategy {
+    /// Replaces each diagonal entry by the sum of the entries in its row.
+    ///
+    /// Row-sum lumping preserves the total mass exactly. For some higher-order elements,
+    /// such as quadratic triangles and tetrahedra, the lumped masses of the vertex nodes
+    /// are zero or negative.
+    #[default]
+    RowSum,
+    /// The diagonal scaling of Hinton, Rock and Zienkiewicz (HRZ).
+    ///
+    /// The diagonal entries of the consistent matrix are scaled by a common factor so that
+    /// their sum equals the sum of all entries, which preserves the total mass. The lumped
+    /// masses are positive whenever the diagonal entries of the consistent matrix are positive.
+    Hrz,
+}
+
+/// Lumps the given (square) element matrix in place according to the lumping strategy.
+///
+/// All off-diagonal entries are set to zero.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square.
+pub fn lump_element_matrix<T: Real>(mut matrix: DMatrixViewMut<T>, strategy: LumpingStrategy) {
+    assert_eq!(matrix.nrows(), matrix.ncols(), "Element matrix must be square");
+    let n = matrix.nrows();
+    let lumped: Vec<T> = match strategy {
+        LumpingStrategy::RowSum => matrix.row_iter().map(|row| row.sum()).collect(),
+        LumpingStrategy::Hrz => {
+            let total = matrix.sum();
+            let diagonal_sum = matrix.diagonal().sum();
+            let scale = if diagonal_sum != T::zero() {
+                total / diagonal_sum
+            } else {
+                T::zero()
+            };
+            (0..n).map(|i| scale * matrix[(i, i)]).collect()
+        }
+    };
+    matrix.fill(T::zero());
+    for (i, m_ii) in lumped.into_iter().enumerate() {
+        matrix[(i, i)] = m_ii;
+    }
+}
+
+/// An adapter that lumps the element matrices produced by another assembler.
+///
+/// The element matrices are lumped with [`lump_element_matrix`]. In addition to producing the
+/// (diagonal) lumped element matrices, the adapter implements [`ElementVectorAssembler`], whose
+/// element vectors are the diagonals of the lumped element matrices. Assembling the vector
+/// therefore produces the diagonal of the global lumped matrix directly, without storing
+/// a sparse matrix, which is the typical use case in explicit dynamics.
+#[derive(Debug, Clone)]
+pub struct LumpedElementAssembler<Assembler> {
+    assembler: Assembler,
+    strategy: LumpingStrategy,
+}
+
+impl<Assembler> LumpedElementAssembler<Assembler> {
+    pub fn new(assembler: Assembler, strategy: LumpingStrategy) -> Self {
+        Self { assembler, strategy }
+    }
+
+    pub fn strategy(&self) -> LumpingStrategy {
+        self.strategy
+    }
+
+    pub fn inner(&self) -> &Assembler {
+        &self.assembler
+    }
+}
+
+impl<Assembler> ElementConnectivityAssembler for LumpedElementAssembler<Assembler>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<T, Assembler> ElementMatrixAssembler<T> for LumpedElementAssembler<Assembler>
+where
+    T: Real,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.assembler
+            .assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
+        lump_element_matrix(output, self.strategy);
+        Ok(())
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        // Lumped matrices are diagonal
+        Symmetry::Symmetric
+    }
+}
+
+impl<T, Assembler> ElementVectorAssembler<T> for LumpedElementAssembler<Assembler>
+where
+    T: Real,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let mut matrix = self.assembler.assemble_element_matrix(element_index)?;
+        lump_element_matrix(DMatrixViewMut::from(&mut matrix), self.strategy);
+        output.copy_from(&matrix.diagonal());
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler};
+use crate::nalgebra::DMatrixViewMut;
+use crate::util::small_eig::generalized_symmetric_eigenvalues;
+use crate::{Real, Symmetry};
+use eyre::eyre;
+
+/// Estimates the critical time step of each element for explicit (central difference) time integration.
+///
+/// The critical time step of element $e$ is given by $\Delta t^e_{\text{crit}} = 2 / \omega_e$,
+/// where $\omega_e^2$ is the largest eigenvalue of the generalized eigenvalue problem
+/// $\vec K_e \vec x = \omega^2 \vec M_e \vec x$. By the element eigenvalue inequality, the smallest
+/// element critical time step is a lower bound for the critical time step of the assembled system.
+///
+/// For explicit time integration with a lumped mass matrix, the critical time steps must be
+/// computed with the lumped element mass matrices, i.e. by passing the mass assembler wrapped in a
+/// [`LumpedElementAssembler`](crate::assembly::local::LumpedElementAssembler).
+///
+/// # Errors
+///
+/// Returns an error if an element mass matrix is not positive definite, or if an element
+/// stiffness matrix has no positive eigenvalues.
+///
+/// # Panics
+///
+/// Panics if the mass and stiffness assemblers do not have the same number of elements.
+pub fn compute_element_critical_time_steps<T: Real>(
+    mass_assembler: &impl ElementMatrixAssembler<T>,
+    stiffness_assembler: &impl ElementMatrixAssembler<T>,
+) -> eyre::Result<Vec<T>> {
+    assert_eq!(
+        mass_assembler.num_elements(),
+        stiffness_assembler.num_elements(),
+        "Mass and stiffness assemblers must have the same number of elements"
+    );
+    let two = T::from_f64(2.0).unwrap();
+    (0..mass_assembler.num_elements())
+        .map(|element_index| {
+            let m_e = mass_assembler.assemble_element_matrix(element_index)?;
+            let k_e = stiffness_assembler.assemble_element_matrix(element_index)?;
+            let omega_squared = generalized_symmetric_eigenvalues(&k_e, &m_e)?
+                .iter()
+                .fold(T::zero(), |max, &lambda| T::max(max, lambda));
+            if omega_squared > T::zero() {
+                Ok(two / omega_squared.sqrt())
+            } else {
+                Err(eyre!("Element stiffness matrix has no positive eigenvalues"))
+            }
+        })
+        .collect()
+}
+
+/// Computes element mass scale factors for *conventional* mass scaling.
+///
+/// The critical time step of an element scales with the square root of its mass, so
+/// scaling the element mass by $s_e = (\Delta t / \Delta t^e_{\text{crit}})^2$ raises the critical
+/// time step of the element to the target time step $\Delta t$. Elements whose critical time step
+/// already exceeds the target time step are left untouched, i.e. $s_e = 1$.
+///
+/// The scale factors can be used with [`ScaledElementMassAssembler`].
+///
+/// # Panics
+///
+/// Panics if the target time step or any of the critical time steps are not positive.
+pub fn compute_conventional_mass_scale_factors<T: Real>(target_dt: T, critical_dts: &[T]) -> Vec<T> {
+    assert!(target_dt > T::zero(), "Target time step must be positive");
+    critical_dts
+        .iter()
+        .map(|&dt_crit| {
+            assert!(dt_crit > T::zero(), "Critical time steps must be positive");
+            T::max(T::one(), (target_dt / dt_crit).powi(2))
+        })
+        .collect()
+}
+
+/// Computes per-element coefficients for *selective* (stiffness-proportional) mass scaling.
+///
+/// Selective mass scaling replaces the element mass matrix $\vec M_e$ by $\vec M_e + \beta_e \vec K_e$,
+/// where $\vec K_e$ is the element stiffness matrix. Since rigid-body modes are in the null space of
+/// $\vec K_e$, they are not affected by the added mass. For an element with highest eigenfrequency
+/// $\omega_e = 2 / \Delta t^e_{\text{crit}}$, the highest eigenfrequency of the scaled element is
+/// $\omega_e / \sqrt{1 + \beta_e \omega_e^2}$, and so choosing
+/// $$
+/// \beta_e = \frac{1}{4} \left( \Delta t^2 - (\Delta t^e_{\text{crit}})^2 \right)
+/// $$
+/// raises the critical time step of the element to the target time step $\Delta t$. Elements
+/// whose critical time step already exceeds the target time step get $\beta_e = 0$.
+///
+/// The coefficients can be used with [`SelectiveMassScalingAssembler`].
+///
+/// # Panics
+///
+/// Panics if the target time step or any of the critical time steps are not positive.
+pub fn compute_selective_mass_scaling_coefficients<T: Real>(target_dt: T, critical_dts: &[T]) -> Vec<T> {
+    assert!(target_dt > T::zero(), "Target time step must be positive");
+    let quarter = T::from_f64(0.25).unwrap();
+    critical_dts
+        .iter()
+        .map(|&dt_crit| {
+            assert!(dt_crit > T::zero(), "Critical time steps must be positive");
+            T::max(T::zero(), quarter * (target_dt * target_dt - dt_crit * dt_crit))
+        })
+        .collect()
+}
+
+/// A summary of the mass added by mass scaling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MassScalingReport<T> {
+    /// The mass added to each element.
+    ///
+    /// This is suitable for export as a cell field in order to visualize where mass was added,
+    /// see [`VtkExporter::with_mass_scaling_report`](crate::io::vtk::VtkExporter::with_mass_scaling_report).
+    pub element_added_mass: Vec<T>,
+    /// The total mass of the system before scaling.
+    pub total_mass: T,
+    /// The total mass added to the system by scaling.
+    pub total_added_mass: T,
+}
+
+impl<T: Real> MassScalingReport<T> {
+    /// The added mass relative to the original mass of the system.
+    pub fn added_mass_fraction(&self) -> T {
+        self.total_added_mass / self.total_mass
+    }
+}
+
+/// Computes the (translational) mass of each element in the provided mass assembler.
+///
+/// The element mass is computed as $\frac{1}{s} \vec 1^T \vec M_e \vec 1$, where $s$ is the solution
+/// dimension. This is the mass associated with a rigid translation of the element.
+pub fn compute_element_masses<T: Real>(mass_assembler: &impl ElementMatrixAssembler<T>) -> eyre::Result<Vec<T>> {
+    let s = T::from_usize(mass_assembler.solution_dim()).unwrap();
+    (0..mass_assembler.num_elements())
+        .map(|element_index| {
+            let m_e = mass_assembler.assemble_element_matrix(element_index)?;
+            Ok(m_e.sum() / s)
+        })
+        .collect()
+}
+
+/// Computes a report of the mass added by conventional mass scaling with the given scale factors.
+///
+/// # Panics
+///
+/// Panics if the number of scale factors does not match the number of elements.
+pub fn compute_mass_scaling_report<T: Real>(
+    mass_assembler: &impl ElementMatrixAssembler<T>,
+    scale_factors: &[T],
+) -> eyre::Result<MassScalingReport<T>> {
+    assert_eq!(
+        scale_factors.len(),
+        mass_assembler.num_elements(),
+        "Number of scale factors must match number of elements"
+    );
+    let element_masses = compute_element_masses(mass_assembler)?;
+    let element_added_mass: Vec<_> = element_masses
+        .iter()
+        .zip(scale_factors)
+        .map(|(&m_e, &s_e)| (s_e - T::one()) * m_e)
+        .collect();
+    let total_mass = element_masses.iter().fold(T::zero(), |acc, &m_e| acc + m_e);
+    let total_added_mass = element_added_mass
+        .iter()
+        .fold(T::zero(), |acc, &m_e| acc + m_e);
+    Ok(MassScalingReport {
+        element_added_mass,
+        total_mass,
+        total_added_mass,
+    })
+}
+
+/// An element assembler that scales the element matrices of a mass assembler by per-element factors.
+///
+/// This implements conventional mass scaling, see [`compute_conventional_mass_scale_factors`].
+/// Since the scaling is applied at the element level, the assembler can be combined with any
+/// global assembly routine. In particular, wrapping it in a
+/// [`LumpedElementAssembler`](crate::assembly::local::LumpedElementAssembler) gives the scaled
+/// lumped mass required by explicit integrators. Since every element matrix is scaled uniformly,
+/// scaling and lumping commute.
+#[derive(Debug, Clone)]
+pub struct ScaledElementMassAssembler<'a, T, MassAssembler> {
+    mass_assembler: MassAssembler,
+    scale_factors: &'a [T],
+}
+
+impl<'a, T, MassAssembler> ScaledElementMassAssembler<'a, T, MassAssembler>
+where
+    MassAssembler: ElementConnectivityAssembler,
+{
+    /// Constructs a new scaled mass assembler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of scale factors does not match the number of elements.
+    pub fn from_assembler_and_scale_factors(mass_assembler: MassAssembler, scale_factors: &'a [T]) -> Self {
+        assert_eq!(
+            scale_factors.len(),
+            mass_assembler.num_elements(),
+            "Number of scale factors must match number of elements"
+        );
+        Self {
+            mass_assembler,
+            scale_factors,
+        }
+    }
+}
+
+impl<'a, T, MassAssembler> ElementConnectivityAssembler for ScaledElementMassAssembler<'a, T, MassAssembler>
+where
+    MassAssembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.mass_assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.mass_assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.mass_assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.mass_assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.mass_assembler
+            .populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, MassAssembler> ElementMatrixAssembler<T> for ScaledElementMassAssembler<'a, T, MassAssembler>
+where
+    T: Real,
+    MassAssembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.mass_assembler
+            .assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
+        output *= self.scale_factors[element_index];
+        Ok(())
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        self.mass_assembler.symmetry()
+    }
+}
+
+/// An element assembler that implements selective (stiffness-proportional) mass scaling.
+///
+/// The element matrices are given by $\vec M_e + \beta_e \vec K_e$, where $\vec M_e$ and $\vec K_e$
+/// are the element mass and stiffness matrices. Rigid-body modes are not affected by the
+/// added mass, see [`compute_selective_mass_scaling_coefficients`] for more information.
+///
+/// Note that row-sum lumping of the scaled mass matrix eliminates the added mass entirely,
+/// since the rows of the stiffness matrix sum to zero. Selective mass scaling therefore
+/// requires a consistent mass matrix.
+#[derive(Debug, Clone)]
+pub struct SelectiveMassScalingAssembler<'a, T, MassAssembler, StiffnessAssembler> {
+    mass_assembler: MassAssembler,
+    stiffness_assembler: StiffnessAssembler,
+    coefficients: &'a [T],
+}
+
+impl<'a, T, MassAssembler, StiffnessAssembler> SelectiveMassScalingAssembler<'a, T, MassAssembler, StiffnessAssembler>
+where
+    MassAssembler: ElementConnectivityAssembler,
+    StiffnessAssembler: ElementConnectivityAssembler,
+{
+    /// Constructs a new selective mass scaling assembler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mass and stiffness assemblers are not defined on the same elements and nodes,
+    /// or if the number of coefficients does not match the number of elements.
+    pub fn from_assemblers_and_coefficients(
+        mass_assembler: MassAssembler,
+        stiffness_assembler: StiffnessAssembler,
+        coefficients: &'a [T],
+    ) -> Self {
+        assert_eq!(
+            mass_assembler.num_elements(),
+            stiffness_assembler.num_elements(),
+            "Mass and stiffness assemblers must have the same number of elements"
+        );
+        assert_eq!(
+            mass_assembler.num_nodes(),
+            stiffness_assembler.num_nodes(),
+            "Mass and stiffness assemblers must have the same number of nodes"
+        );
+        assert_eq!(
+            mass_assembler.solution_dim(),
+            stiffness_assembler.solution_dim(),
+            "Mass and stiffness assemblers must have the same solution dimension"
+        );
+        assert_eq!(
+            coefficients.len(),
+            mass_assembler.num_elements(),
+            "Number of coefficients must match number of elements"
+        );
+        Self {
+            mass_assembler,
+            stiffness_assembler,
+            coefficients,
+        }
+    }
+}
+
+impl<'a, T, MassAssembler, StiffnessAssembler> ElementConnectivityAssembler
+    for SelectiveMassScalingAssembler<'a, T, MassAssembler, StiffnessAssembler>
+where
+    MassAssembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.mass_assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.mass_assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.mass_assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.mass_assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.mass_assembler
+            .populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, MassAssembler, StiffnessAssembler> ElementMatrixAssembler<T>
+    for SelectiveMassScalingAssembler<'a, T, MassAssembler, StiffnessAssembler>
+where
+    T: Real,
+    MassAssembler: ElementMatrixAssembler<T>,
+    StiffnessAssembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.mass_assembler
+            .assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
+        let beta = self.coefficients[element_index];
+        if beta != T::zero() {
+            let k_e = self
+                .stiffness_assembler
+                .assemble_element_matrix(element_index)?;
+            output.zip_apply(&k_e, |m, k| *m += beta * k);
+        }
+        Ok(())
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        let symmetric = self.mass_assembler.symmetry() == Symmetry::Symmetric
+            && self.stiffness_assembler.symmetry() == Symmetry::Symmetric;
+        if symmetric {
+            Symmetry::Symmetric
+        } else {
+            Symmetry::NonSymmetric
+        }
+    }
+}
@@ -0,0 +1,476 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
+use crate::assembly::global::gather_global_to_local;
+use crate::assembly::local::{
+    ElementConnectivityAssembler, ElementMatrixAssembler, ElementVectorAssembler, QuadratureTable,
+};
+use crate::element::FiniteElement;
+use crate::nalgebra::{
+    DMatrixViewMut, DVector, DVectorView, DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut,
+    OMatrix, OPoint, OVector, Scalar,
+};
+use crate::space::{ElementInSpace, FiniteElementSpace};
+use crate::Real;
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use itertools::izip;
+
+/// An element assembler for pressure loads acting on a deforming surface.
+///
+/// Pressure loads are *follower loads*: the force acting on the surface is always directed
+/// along the *current* normal of the surface, and it scales with the *current* area.
+/// Given a pressure $p$ and the displacement $\vec u$, the external force associated with node $I$ is
+/// $$
+/// \vec f_I(\vec u) := - \int_{\Gamma} p \\, \phi_I \\, \vec n \\, \mathrm{d}a,
+/// $$
+/// where $\Gamma$ and $\vec n$ denote the surface and its (outward) normal in the *deformed*
+/// configuration. A positive pressure therefore pushes against the surface.
+///
+/// Since the force depends on the displacement, Newton's method requires the corresponding
+/// *load stiffness* in order to converge quadratically. The element matrix assembled by
+/// this assembler is the load stiffness matrix
+/// $$
+/// \vec K^{\text{load}}_{IJ} := - \pd{\vec f_I}{\vec u_J},
+/// $$
+/// which can be directly added to the tangent stiffness matrix of the (internal) elastic forces.
+/// Note that the load stiffness matrix is in general **not** symmetric.
+///
+/// The space must be a surface space, i.e. its reference dimension must be one less than
+/// its geometry dimension, and only two- and three-dimensional geometries are supported.
+/// The orientation of each surface element determines the normal, which is required to point
+/// outwards. The node indices of the surface space must refer to the nodes of the *volume* space,
+/// so that the assembled quantities can be directly combined with quantities assembled
+/// from the volume space. A suitable surface space can be obtained with
+/// [`Mesh::extract_boundary_face_mesh`](crate::mesh::Mesh::extract_boundary_face_mesh).
+///
+/// For time-dependent pressure loads, the pressure can be updated in-between time steps
+/// with [`set_pressure`](Self::set_pressure).
+#[derive(Debug, Clone)]
+pub struct ElementPressureAssembler<'a, T: Scalar, Space, QTable: ?Sized> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: DVectorView<'a, T>,
+    pressure: T,
+}
+
+impl<'a, T: Scalar> ElementPressureAssembler<'a, T, (), ()> {
+    pub fn with_pressure(pressure: T) -> Self {
+        Self {
+            space: &(),
+            qtable: &(),
+            u: DVectorView::from_slice(&[], 0),
+            pressure,
+        }
+    }
+}
+
+impl<'a, T: Scalar, QTable: ?Sized> ElementPressureAssembler<'a, T, (), QTable> {
+    /// Sets the surface space whose elements the pressure acts on.
+    pub fn with_space<Space>(self, space: &'a Space) -> ElementPressureAssembler<'a, T, Space, QTable> {
+        ElementPressureAssembler {
+            space,
+            qtable: self.qtable,
+            u: self.u,
+            pressure: self.pressure,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space> ElementPressureAssembler<'a, T, Space, ()> {
+    pub fn with_quadrature_table<QTable: ?Sized>(
+        self,
+        qtable: &'a QTable,
+    ) -> ElementPressureAssembler<'a, T, Space, QTable> {
+        ElementPressureAssembler {
+            space: self.space,
+            qtable,
+            u: self.u,
+            pressure: self.pressure,
+        }
+    }
+}
+
+impl<'a, T: Scalar, Space, QTable: ?Sized> ElementPressureAssembler<'a, T, Space, QTable> {
+    /// Sets the displacement (associated with the nodes of the volume space) that
+    /// determines the deformed configuration.
+    pub fn with_u(self, u: impl Into<DVectorView<'a, T>>) -> Self {
+        Self { u: u.into(), ..self }
+    }
+
+    pub fn pressure(&self) -> &T {
+        &self.pressure
+    }
+
+    pub fn set_pressure(&mut self, pressure: T) {
+        self.pressure = pressure;
+    }
+}
+
+impl<'a, T, Space, QTable> ElementConnectivityAssembler for ElementPressureAssembler<'a, T, Space, QTable>
+where
+    T: Scalar,
+    Space: FiniteElementSpace<T>,
+    QTable: ?Sized,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        Space::GeometryDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+#[derive(Debug)]
+struct PressureAssemblerWorkspace<T, ReferenceDim>
+where
+    T: Scalar,
+    ReferenceDim: DimName,
+    DefaultAllocator: DimAllocator<T, ReferenceDim>,
+{
+    u_element: DVector<T>,
+    quadrature_buffer: QuadratureBuffer<T, ReferenceDim>,
+    basis_buffer: BasisFunctionBuffer<T>,
+}
+
+impl<T, ReferenceDim> Default for PressureAssemblerWorkspace<T, ReferenceDim>
+where
+    T: Real,
+    ReferenceDim: DimName,
+    DefaultAllocator: DimAllocator<T, ReferenceDim>,
+{
+    fn default() -> Self {
+        Self {
+            u_element: DVector::zeros(0),
+            quadrature_buffer: Default::default(),
+            basis_buffer: Default::default(),
+        }
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+impl<'a, T, Space, QTable> ElementPressureAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + ?Sized,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn prepare_workspace(&self, ws: &mut PressureAssemblerWorkspace<T, Space::ReferenceDim>, element_index: usize) {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index);
+        ws.basis_buffer.resize(n, Space::ReferenceDim::dim());
+        ws.basis_buffer
+            .populate_element_nodes_from_space(element_index, self.space);
+        ws.u_element.resize_vertically_mut(s * n, T::zero());
+        gather_global_to_local(self.u, &mut ws.u_element, ws.basis_buffer.element_nodes(), s);
+        ws.quadrature_buffer
+            .populate_element_weights_and_points_from_table(element_index, self.qtable);
+    }
+}
+
+impl<'a, T, Space, QTable> ElementVectorAssembler<T> for ElementPressureAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + ?Sized,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index);
+        assert_eq!(output.len(), s * n, "Output vector dimension mismatch");
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut PressureAssemblerWorkspace<T, Space::ReferenceDim>| {
+                self.prepare_workspace(ws, element_index);
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                let (basis_values, basis_gradients) = ws.basis_buffer.element_values_gradients_mut();
+                assemble_element_pressure_vector(
+                    output,
+                    &element,
+                    self.pressure,
+                    DVectorView::from(&ws.u_element),
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    basis_values,
+                    basis_gradients,
+                );
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<'a, T, Space, QTable> ElementMatrixAssembler<T> for ElementPressureAssembler<'a, T, Space, QTable>
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + ?Sized,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        let s = self.solution_dim();
+        let n = self.element_node_count(element_index);
+        assert_eq!(output.nrows(), s * n, "Output matrix dimension mismatch");
+        assert_eq!(output.ncols(), s * n, "Output matrix dimension mismatch");
+
+        with_thread_local_workspace(
+            &WORKSPACE,
+            |ws: &mut PressureAssemblerWorkspace<T, Space::ReferenceDim>| {
+                self.prepare_workspace(ws, element_index);
+                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
+                let (basis_values, basis_gradients) = ws.basis_buffer.element_values_gradients_mut();
+                assemble_element_pressure_load_stiffness(
+                    output,
+                    &element,
+                    self.pressure,
+                    DVectorView::from(&ws.u_element),
+                    ws.quadrature_buffer.weights(),
+                    ws.quadrature_buffer.points(),
+                    basis_values,
+                    basis_gradients,
+                );
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Computes the area-weighted normal $\vec n \\, \mathrm{d}a$ of a surface element from the
+/// Jacobian of the map from reference coordinates.
+///
+/// In 2D, the normal is the tangent rotated clockwise by 90 degrees, and in 3D the normal is the cross
+/// product of the two columns of the Jacobian. This is consistent with the orientation of the
+/// faces of volumetric elements, so that the normal points outwards.
+///
+/// # Panics
+///
+/// Panics unless the dimensions of the Jacobian are either $2 \times 1$ or $3 \times 2$.
+pub fn compute_area_weighted_normal<T, GeometryDim, ReferenceDim>(
+    jacobian: &OMatrix<T, GeometryDim, ReferenceDim>,
+) -> OVector<T, GeometryDim>
+where
+    T: Real,
+    GeometryDim: DimName,
+    ReferenceDim: DimName,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    let mut normal = OVector::<T, GeometryDim>::zeros();
+    match (GeometryDim::dim(), ReferenceDim::dim()) {
+        (2, 1) => {
+            normal[0] = jacobian[(1, 0)];
+            normal[1] = -jacobian[(0, 0)];
+        }
+        (3, 2) => {
+            let (a, b) = (jacobian.column(0), jacobian.column(1));
+            normal[0] = a[1] * b[2] - a[2] * b[1];
+            normal[1] = a[2] * b[0] - a[0] * b[2];
+            normal[2] = a[0] * b[1] - a[1] * b[0];
+        }
+        _ => panic!("Area-weighted normals are only supported for surfaces in 2D and 3D"),
+    }
+    normal
+}
+
+/// Computes the derivative of the area-weighted normal with respect to the displacement
+/// of a single node.
+///
+/// The node is identified by the gradient of its basis function with respect to reference coordinates.
+fn compute_area_weighted_normal_derivative<T, GeometryDim, ReferenceDim>(
+    jacobian: &OMatrix<T, GeometryDim, ReferenceDim>,
+    phi_grad_ref: &OVector<T, ReferenceDim>,
+) -> OMatrix<T, GeometryDim, GeometryDim>
+where
+    T: Real,
+    GeometryDim: DimName,
+    ReferenceDim: DimName,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, ReferenceDim>,
+{
+    let mut derivative = OMatrix::<T, GeometryDim, GeometryDim>::zeros();
+    match (GeometryDim::dim(), ReferenceDim::dim()) {
+        (2, 1) => {
+            let g = phi_grad_ref[0];
+            derivative[(0, 1)] = g;
+            derivative[(1, 0)] = -g;
+        }
+        (3, 2) => {
+            // With a = J_1 x J_2 and dJ_k = dphi/dxi_k * du, we have
+            //  da = g_1 du x J_2 + g_2 J_1 x du = (g_2 J_1 - g_1 J_2) x du,
+            // so the derivative is the cross product matrix of w = g_2 J_1 - g_1 J_2
+            let w = jacobian.column(0) * phi_grad_ref[1] - jacobian.column(1) * phi_grad_ref[0];
+            derivative[(0, 1)] = -w[2];
+            derivative[(0, 2)] = w[1];
+            derivative[(1, 0)] = w[2];
+            derivative[(1, 2)] = -w[0];
+            derivative[(2, 0)] = -w[1];
+            derivative[(2, 1)] = w[0];
+        }
+        _ => panic!("Area-weighted normals are only supported for surfaces in 2D and 3D"),
+    }
+    derivative
+}
+
+/// Computes the reference Jacobian of the deformed surface element at the given point.
+#[allow(non_snake_case)]
+fn compute_deformed_reference_jacobian<T, Element>(
+    element: &Element,
+    u: &MatrixView<T, Element::GeometryDim, Dyn>,
+    phi_grad_ref: &MatrixView<T, Element::ReferenceDim, Dyn>,
+    reference_coords: &OPoint<T, Element::ReferenceDim>,
+) -> OMatrix<T, Element::GeometryDim, Element::ReferenceDim>
+where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    // x = X + u, so dx/dxi = dX/dxi + sum_I u_I (dphi_I/dxi)^T
+    let mut jacobian = element.reference_jacobian(reference_coords);
+    for (u_I, phi_I_grad_ref) in u.column_iter().zip(phi_grad_ref.column_iter()) {
+        jacobian.ger(T::one(), &u_I, &phi_I_grad_ref, T::one());
+    }
+    jacobian
+}
+
+/// Assembles the external force vector associated with a pressure load on a deforming surface element.
+///
+/// See [`ElementPressureAssembler`] for the definition of the force. The displacement `u_element`
+/// contains the displacements of the element nodes.
+///
+/// **This is a low-level routine**. Most users will want to use [`ElementPressureAssembler`] instead.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the output vector, the element displacement or the buffers are not
+/// consistent with the number of nodes in the element.
+///
+/// Panics if the element is not a surface element in 2D or 3D.
+#[allow(non_snake_case)]
+pub fn assemble_element_pressure_vector<T, Element>(
+    mut output: DVectorViewMut<T>,
+    element: &Element,
+    pressure: T,
+    u_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    basis_values_buffer: &mut [T],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let n = element.num_nodes();
+    let d = Element::GeometryDim::dim();
+    assert_eq!(output.len(), d * n, "Output vector dimension mismatch");
+    assert_eq!(u_element.len(), d * n, "Element displacement dimension mismatch");
+    assert_eq!(basis_values_buffer.len(), n, "Basis values buffer dimension mismatch");
+    assert_eq!(
+        basis_gradients_buffer.ncols(),
+        n,
+        "Basis gradients buffer dimension mismatch"
+    );
+    assert_eq!(
+        quadrature_weights.len(),
+        quadrature_points.len(),
+        "Number of quadrature weights must be equal to number of points."
+    );
+
+    let u = MatrixView::from_slice_generic(u_element.as_slice(), Element::GeometryDim::name(), Dyn(n));
+    let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), Element::GeometryDim::name(), Dyn(n));
+    output.fill(T::zero());
+
+    for (&weight, point) in izip!(quadrature_weights, quadrature_points) {
+        element.populate_basis(&mut *basis_values_buffer, point);
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+        let phi_grad_ref = MatrixView::from(&basis_gradients_buffer);
+        let jacobian = compute_deformed_reference_jacobian(element, &u, &phi_grad_ref, point);
+        let normal = compute_area_weighted_normal(&jacobian);
+
+        for (mut f_I, &phi_I) in output.column_iter_mut().zip(basis_values_buffer.iter()) {
+            f_I.axpy(-pressure * weight * phi_I, &normal, T::one());
+        }
+    }
+}
+
+/// Assembles the load stiffness matrix associated with a pressure load on a deforming surface element.
+///
+/// See [`ElementPressureAssembler`] for the definition of the load stiffness matrix.
+/// The displacement `u_element` contains the displacements of the element nodes.
+///
+/// **This is a low-level routine**. Most users will want to use [`ElementPressureAssembler`] instead.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the output matrix, the element displacement or the buffers are not
+/// consistent with the number of nodes in the element.
+///
+/// Panics if the element is not a surface element in 2D or 3D.
+#[allow(non_snake_case)]
+pub fn assemble_element_pressure_load_stiffness<T, Element>(
+    mut output: DMatrixViewMut<T>,
+    element: &Element,
+    pressure: T,
+    u_element: DVectorView<T>,
+    quadrature_weights: &[T],
+    quadrature_points: &[OPoint<T, Element::ReferenceDim>],
+    basis_values_buffer: &mut [T],
+    mut basis_gradients_buffer: MatrixViewMut<T, Element::ReferenceDim, Dyn>,
+) where
+    T: Real,
+    Element: FiniteElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let n = element.num_nodes();
+    let d = Element::GeometryDim::dim();
+    assert_eq!(output.nrows(), d * n, "Output matrix dimension mismatch");
+    assert_eq!(output.ncols(), d * n, "Output matrix dimension mismatch");
+    assert_eq!(u_element.len(), d * n, "Element displacement dimension mismatch");
+    assert_eq!(basis_values_buffer.len(), n, "Basis values buffer dimension mismatch");
+    assert_eq!(
+        basis_gradients_buffer.ncols(),
+        n,
+        "Basis gradients buffer dimension mismatch"
+    );
+    assert_eq!(
+        quadrature_weights.len(),
+        quadrature_points.len(),
+        "Number of quadrature weights must be equal to number of points."
+    );
+
+    let u = MatrixView::from_slice_generic(u_element.as_slice(), Element::GeometryDim::name(), Dyn(n));
+    let d_times_d = (Element::GeometryDim::name(), Element::GeometryDim::name());
+    output.fill(T::zero());
+
+    for (&weight, point) in izip!(quadrature_weights, quadrature_points) {
+        element.populate_basis(&mut *basis_values_buffer, point);
+        element.populate_basis_gradients(MatrixViewMut::from(&mut basis_gradients_buffer), point);
+        let phi_grad_ref = MatrixView::from(&basis_gradients_buffer);
+        let jacobian = compute_deformed_reference_jacobian(element, &u, &phi_grad_ref, point);
+
+        // K_IJ = - d f_I / d u_J = p * int phi_I (d a / d u_J) dxi,
+        // where a is the area-weighted normal
+        for J in 0..n {
+            let phi_J_grad_ref = phi_grad_ref.column(J).clone_owned();
+            let normal_derivative = compute_area_weighted_normal_derivative(&jacobian, &phi_J_grad_ref);
+            for (I, &phi_I) in basis_values_buffer.iter().enumerate() {
+                let mut k_IJ = output.generic_view_mut((d * I, d * J), d_times_d);
+                k_IJ += &normal_derivative * (pressure * weight * phi_I);
+            }
+        }
+    }
+}
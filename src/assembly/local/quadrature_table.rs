@@ -1,3 +1,5 @@
+use crate::assembly::local::BasisCacheView;
+use crate::assembly::QuadraturePointData;
 use crate::nalgebra::allocator::Allocator;
 use crate::nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
 use crate::quadrature::QuadraturePair;
@@ -37,6 +39,16 @@ where
         self.populate_element_quadrature(element_index, points, weights);
         self.populate_element_data(element_index, data);
     }
+
+    /// Returns basis function values and gradients precomputed at the quadrature points of the
+    /// given element, if available.
+    ///
+    /// Element assemblers that support caching use these values instead of evaluating the basis
+    /// functions of each element. The default implementation returns `None`.
+    /// See [`CachedBasisQuadrature`](crate::assembly::local::CachedBasisQuadrature) for more information.
+    fn element_basis_cache(&self, _element_index: usize) -> Option<BasisCacheView<T, GeometryDim>> {
+        None
+    }
 }
 
 /// Trait alias for a one-dimensional quadrature table.
@@ -53,6 +65,10 @@ impl<T: Scalar, Table: QuadratureTable<T, U2>> QuadratureTable2d<T> for Table {}
 impl<T: Scalar, Table: QuadratureTable<T, U3>> QuadratureTable3d<T> for Table {}
 
 /// A quadrature table that keeps a separate quadrature rule per element.
+///
+/// The data associated with the quadrature points is stored as [`QuadraturePointData`], which can
+/// be accessed with [`data`](Self::data) and [`data_mut`](Self::data_mut), for example to update
+/// per-point material parameters in place.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GeneralQuadratureTable<T, GeometryDim, Data = ()>
 where
@@ -64,7 +80,7 @@ where
     #[serde(bound(deserialize = "OPoint<T, GeometryDim>: Deserialize<'de>"))]
     points: NestedVec<OPoint<T, GeometryDim>>,
     weights: NestedVec<T>,
-    data: NestedVec<Data>,
+    data: QuadraturePointData<Data>,
 }
 
 fn unit_data_table_for_weights<T>(points: &NestedVec<T>) -> NestedVec<()> {
@@ -82,15 +98,53 @@ where
     DefaultAllocator: Allocator<T, GeometryDim>,
 {
     pub fn from_points_and_weights(points: NestedVec<OPoint<T, GeometryDim>>, weights: NestedVec<T>) -> Self {
-        let data = unit_data_table_for_weights(&weights);
-        Self::from_points_weights_and_data(points, weights, data)
+        let data = QuadraturePointData::from_element_sizes_and_value(weights.iter().map(|weights| weights.len()), ());
+        Self::from_points_weights_and_quadrature_point_data(points, weights, data)
+    }
+
+    /// Constructs a table with a separate quadrature rule for each element.
+    ///
+    /// The rule at index `i` is used for the element with index `i`, and the rules may have
+    /// different numbers of points. This is for example useful for $p$-adaptive assembly, or
+    /// for elements with curved geometry that require stronger quadrature rules.
+    pub fn from_element_quadratures(quadratures: &[QuadraturePair<T, GeometryDim>]) -> Self {
+        Self::from_element_quadrature_fn(quadratures.len(), |element_index| &quadratures[element_index])
+    }
+
+    /// Constructs a table with a separate quadrature rule for each of the `num_elements` elements,
+    /// given by `quadrature_fn(element_index)`.
+    ///
+    /// Since the closure returns a reference, the same rule can be shared among many elements,
+    /// for example by selecting from a small set of rules based on the polynomial degree of each element.
+    /// The points and weights are stored contiguously in the table regardless.
+    pub fn from_element_quadrature_fn<'a>(
+        num_elements: usize,
+        mut quadrature_fn: impl FnMut(usize) -> &'a QuadraturePair<T, GeometryDim>,
+    ) -> Self
+    where
+        T: 'a,
+    {
+        let mut points_table = NestedVec::new();
+        let mut weights_table = NestedVec::new();
+        for element_index in 0..num_elements {
+            let (weights, points) = quadrature_fn(element_index);
+            assert_eq!(
+                points.len(),
+                weights.len(),
+                "Quadrature for element {} must have the same number of points and weights.",
+                element_index
+            );
+            points_table.push(points);
+            weights_table.push(weights);
+        }
+        Self::from_points_and_weights(points_table, weights_table)
     }
 }
 
 /// Checks that the provided quadrature rules are consistent, in the sense that
 /// the number of elements for each table is identical, and that each rule has
 /// consistent numbers of points, weights and data entries.
-fn check_rules_consistency<T, D, Data>(points: &NestedVec<OPoint<T, D>>, weights: &NestedVec<T>, data: &NestedVec<Data>)
+fn check_rules_consistency<T, D>(points: &NestedVec<OPoint<T, D>>, weights: &NestedVec<T>, data_sizes: &[usize])
 where
     T: Scalar,
     D: DimName,
@@ -103,13 +157,13 @@ where
     );
     assert_eq!(
         points.len(),
-        data.len(),
+        data_sizes.len(),
         "Quadrature point and data tables must have the same number of rules."
     );
 
     // Ensure that each element has a consistent quadrature rule
-    let iter = izip!(points.iter(), weights.iter(), data.iter());
-    for (element_index, (element_points, element_weights, element_data)) in iter.enumerate() {
+    let iter = izip!(points.iter(), weights.iter(), data_sizes.iter().copied());
+    for (element_index, (element_points, element_weights, element_data_size)) in iter.enumerate() {
         assert_eq!(
             element_points.len(),
             element_weights.len(),
@@ -118,7 +172,7 @@ where
         );
         assert_eq!(
             element_points.len(),
-            element_data.len(),
+            element_data_size,
             "Element {} has mismatched number of points and data.",
             element_index
         );
@@ -135,11 +189,105 @@ where
         points: NestedVec<OPoint<T, GeometryDim>>,
         weights: NestedVec<T>,
         data: NestedVec<Data>,
+    ) -> Self
+    where
+        Data: Clone,
+    {
+        let data = QuadraturePointData::from_element_sizes_and_fn(data.iter().map(|data| data.len()), |i, k| {
+            data.get(i).unwrap()[k].clone()
+        });
+        Self::from_points_weights_and_quadrature_point_data(points, weights, data)
+    }
+
+    /// Constructs a table from the quadrature points and weights of each element, and the data
+    /// associated with each quadrature point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements or the number of quadrature points of an element differ
+    /// between the points, weights and data.
+    pub fn from_points_weights_and_quadrature_point_data(
+        points: NestedVec<OPoint<T, GeometryDim>>,
+        weights: NestedVec<T>,
+        data: QuadraturePointData<Data>,
     ) -> Self {
-        check_rules_consistency(&points, &weights, &data);
+        let data_sizes: Vec<_> = data.iter().map(|data| data.len()).collect();
+        check_rules_consistency(&points, &weights, &data_sizes);
         Self { points, weights, data }
     }
 
+    /// Constructs a table that uses the same quadrature rule for every element, with the given data
+    /// associated with all quadrature points of each element.
+    ///
+    /// This is for example useful for assigning material parameters per element. The number of elements
+    /// in the table is given by the length of `element_data`.
+    pub fn from_uniform_quadrature_and_element_data(
+        quadrature: QuadraturePair<T, GeometryDim>,
+        element_data: &[Data],
+    ) -> Self
+    where
+        Data: Clone,
+    {
+        Self::from_uniform_quadrature_and_fn(quadrature, element_data.len(), |element_index, _| {
+            element_data[element_index].clone()
+        })
+    }
+
+    /// Constructs a table that uses the same quadrature rule for `num_elements` elements, with data
+    /// given by `data_fn(element_index, point)` for each quadrature point.
+    ///
+    /// The point passed to `data_fn` is the quadrature point in *reference* coordinates. The corresponding
+    /// physical point can be obtained with
+    /// [`map_element_reference_coords`](crate::space::FiniteElementSpace::map_element_reference_coords)
+    /// on the finite element space.
+    pub fn from_uniform_quadrature_and_fn(
+        quadrature: QuadraturePair<T, GeometryDim>,
+        num_elements: usize,
+        mut data_fn: impl FnMut(usize, &OPoint<T, GeometryDim>) -> Data,
+    ) -> Self {
+        let (weights, points) = quadrature;
+        assert_eq!(
+            points.len(),
+            weights.len(),
+            "Quadrature must have the same number of points and weights."
+        );
+        let mut points_table = NestedVec::new();
+        let mut weights_table = NestedVec::new();
+        for _ in 0..num_elements {
+            points_table.push(&points);
+            weights_table.push(&weights);
+        }
+        let element_sizes = (0..num_elements).map(|_| points.len());
+        let data_table = QuadraturePointData::from_element_sizes_and_fn(element_sizes, |i, k| data_fn(i, &points[k]));
+        Self::from_points_weights_and_quadrature_point_data(points_table, weights_table, data_table)
+    }
+
+    /// Replaces the data associated with each quadrature point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of entries for each element does not match the number of quadrature
+    /// points of the element.
+    pub fn with_quadrature_point_data<Data2: Clone>(
+        self,
+        data: &QuadraturePointData<Data2>,
+    ) -> GeneralQuadratureTable<T, GeometryDim, Data2> {
+        GeneralQuadratureTable::from_points_weights_and_quadrature_point_data(self.points, self.weights, data.clone())
+    }
+
+    /// The data associated with each quadrature point.
+    pub fn data(&self) -> &QuadraturePointData<Data> {
+        &self.data
+    }
+
+    /// The mutable data associated with each quadrature point.
+    ///
+    /// The layout of the data is fixed by the quadrature rules, so that only the values
+    /// can be modified.
+    pub fn data_mut(&mut self) -> &mut [Data] {
+        self.data.as_mut_slice()
+    }
+
     pub fn into_parts(self) -> GeneralQuadratureParts<T, GeometryDim, Data> {
         GeneralQuadratureParts {
             points: self.points,
@@ -157,7 +305,7 @@ where
 {
     pub points: NestedVec<OPoint<T, GeometryDim>>,
     pub weights: NestedVec<T>,
-    pub data: NestedVec<Data>,
+    pub data: QuadraturePointData<Data>,
 }
 
 impl<T, GeometryDim, Data> QuadratureTable<T, GeometryDim> for GeneralQuadratureTable<T, GeometryDim, Data>
@@ -180,12 +328,10 @@ where
     }
 
     fn populate_element_data(&self, element_index: usize, data: &mut [Self::Data]) {
-        let data_for_element = self
-            .data
-            .get(element_index)
-            .expect("Element index out of bounds");
+        assert!(element_index < self.data.num_elements(), "Element index out of bounds");
+        let data_for_element = self.data.element_data(element_index);
         assert_eq!(data_for_element.len(), data.len());
-        data.clone_from_slice(&data_for_element);
+        data.clone_from_slice(data_for_element);
     }
 
     fn populate_element_quadrature(
@@ -360,7 +506,8 @@ where
         data: NestedVec<Data>,
         element_to_rule_map: Vec<usize>,
     ) -> Self {
-        check_rules_consistency(&points, &weights, &data);
+        let data_sizes: Vec<_> = data.iter().map(|data| data.len()).collect();
+        check_rules_consistency(&points, &weights, &data_sizes);
         let num_rules = points.len();
         let rule_indices_in_bounds = element_to_rule_map
             .iter()
@@ -6,7 +6,7 @@ use crate::element::{ReferenceFiniteElement, VolumetricFiniteElement};
 use crate::nalgebra::{
     DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint, OVector, Scalar, U1,
 };
-use crate::space::{ElementInSpace, VolumetricFiniteElementSpace};
+use crate::space::{ElementInSpace, FiniteElementConnectivity, VolumetricFiniteElementSpace};
 use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
 use itertools::izip;
@@ -165,30 +165,257 @@ where
     DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Source::SolutionDim>,
 {
     fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
-        with_thread_local_workspace(
-            &SOURCE_WORKSPACE,
-            |ws: &mut SourceTermWorkspace<T, Space::ReferenceDim, Source::Parameters>| {
-                let element = ElementInSpace::from_space_and_element_index(self.space, element_index);
-                ws.basis_buffer
-                    .resize(element.num_nodes(), Space::ReferenceDim::dim());
-                ws.basis_buffer
-                    .populate_element_nodes_from_space(element_index, self.space);
-                ws.quadrature_buffer
-                    .populate_element_quadrature_from_table(element_index, self.qtable);
-
-                assemble_element_source_vector(
-                    output,
-                    &element,
-                    self.source,
-                    ws.quadrature_buffer.weights(),
-                    ws.quadrature_buffer.points(),
-                    ws.quadrature_buffer.data(),
-                    ws.basis_buffer.element_basis_values_mut(),
-                );
-
-                Ok(())
-            },
-        )
+        assemble_element_source_vector_in_space(output, self.space, self.qtable, self.source, element_index);
+        Ok(())
+    }
+}
+
+fn assemble_element_source_vector_in_space<T, Space, Source, QTable>(
+    output: DVectorViewMut<T>,
+    space: &Space,
+    qtable: &QTable,
+    source: &Source,
+    element_index: usize,
+) where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    Source: SourceFunction<T, Space::ReferenceDim>,
+    QTable: QuadratureTable<T, Space::ReferenceDim, Data = Source::Parameters>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, Source::SolutionDim>,
+{
+    with_thread_local_workspace(
+        &SOURCE_WORKSPACE,
+        |ws: &mut SourceTermWorkspace<T, Space::ReferenceDim, Source::Parameters>| {
+            let element = ElementInSpace::from_space_and_element_index(space, element_index);
+            ws.basis_buffer
+                .resize(element.num_nodes(), Space::ReferenceDim::dim());
+            ws.basis_buffer
+                .populate_element_nodes_from_space(element_index, space);
+            ws.quadrature_buffer
+                .populate_element_quadrature_from_table(element_index, qtable);
+
+            assemble_element_source_vector(
+                output,
+                &element,
+                source,
+                ws.quadrature_buffer.weights(),
+                ws.quadrature_buffer.points(),
+                ws.quadrature_buffer.data(),
+                ws.basis_buffer.element_basis_values_mut(),
+            );
+        },
+    )
+}
+
+/// A source function given by a function of the physical position only.
+///
+/// The quadrature data is passed through as the parameters of the source, but otherwise ignored.
+struct PositionSourceFunction<'f, F, SolutionDim, Data> {
+    function: &'f F,
+    marker: PhantomData<(SolutionDim, Data)>,
+}
+
+impl<'f, F, SolutionDim, Data> PositionSourceFunction<'f, F, SolutionDim, Data> {
+    fn new(function: &'f F) -> Self {
+        Self {
+            function,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'f, T, D, F, SolutionDim, Data> Operator<T, D> for PositionSourceFunction<'f, F, SolutionDim, Data>
+where
+    SolutionDim: SmallDim,
+    Data: Default + Clone + 'static,
+{
+    type SolutionDim = SolutionDim;
+    type Parameters = Data;
+}
+
+impl<'f, T, D, F, SolutionDim, Data> SourceFunction<T, D> for PositionSourceFunction<'f, F, SolutionDim, Data>
+where
+    T: Scalar,
+    D: SmallDim,
+    SolutionDim: SmallDim,
+    Data: Default + Clone + 'static,
+    F: Fn(&OPoint<T, D>) -> OVector<T, SolutionDim>,
+    DefaultAllocator: BiDimAllocator<T, D, SolutionDim>,
+{
+    fn evaluate(&self, coords: &OPoint<T, D>, _data: &Self::Parameters) -> OVector<T, SolutionDim> {
+        (self.function)(coords)
+    }
+}
+
+/// An element assembler for source terms given by a closure of the physical position.
+///
+/// This is a convenience alternative to [`ElementSourceAssembler`] for the common case where
+/// the source $f = f(x)$ does not depend on any quadrature data, so that it is not necessary
+/// to implement [`SourceFunction`]. The closure is evaluated at the quadrature points
+/// mapped to physical space. Any data associated with the quadrature table is ignored.
+///
+/// See [`TimeDependentSourceAssembler`] for sources that also depend on time.
+#[derive(Debug, Clone)]
+pub struct FunctionSourceAssembler<'a, Space, QTable, SolutionDim, F> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    function: F,
+    marker: PhantomData<SolutionDim>,
+}
+
+impl<'a, Space, QTable, SolutionDim, F> FunctionSourceAssembler<'a, Space, QTable, SolutionDim, F> {
+    /// Creates a new assembler for the source given by `function`.
+    ///
+    /// The solution dimension is given by a type-level dimension, such as `U1` for scalar
+    /// problems, and must match the dimension of the vectors returned by the function.
+    pub fn new(space: &'a Space, qtable: &'a QTable, _solution_dim: SolutionDim, function: F) -> Self {
+        Self {
+            space,
+            qtable,
+            function,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn function(&self) -> &F {
+        &self.function
+    }
+}
+
+impl<'a, Space, QTable, SolutionDim, F> ElementConnectivityAssembler
+    for FunctionSourceAssembler<'a, Space, QTable, SolutionDim, F>
+where
+    Space: FiniteElementConnectivity,
+    SolutionDim: SmallDim,
+{
+    fn solution_dim(&self) -> usize {
+        SolutionDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, Space, QTable, SolutionDim, F> ElementVectorAssembler<T>
+    for FunctionSourceAssembler<'a, Space, QTable, SolutionDim, F>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    QTable::Data: Default + Clone + 'static,
+    SolutionDim: SmallDim,
+    F: Fn(&OPoint<T, Space::GeometryDim>) -> OVector<T, SolutionDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let source = PositionSourceFunction::<_, SolutionDim, QTable::Data>::new(&self.function);
+        assemble_element_source_vector_in_space(output, self.space, self.qtable, &source, element_index);
+        Ok(())
+    }
+}
+
+/// An element assembler for time-dependent source terms given by a closure $f = f(x, t)$.
+///
+/// The assembler stores the current time $t$, which can be updated with
+/// [`set_time`](Self::set_time) in between time steps. This way, the same assembler can be
+/// reused throughout a transient simulation. Apart from the time, the assembler behaves
+/// like [`FunctionSourceAssembler`].
+#[derive(Debug, Clone)]
+pub struct TimeDependentSourceAssembler<'a, T, Space, QTable, SolutionDim, F> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    function: F,
+    time: T,
+    marker: PhantomData<SolutionDim>,
+}
+
+impl<'a, T, Space, QTable, SolutionDim, F> TimeDependentSourceAssembler<'a, T, Space, QTable, SolutionDim, F>
+where
+    T: Scalar,
+{
+    /// Creates a new assembler for the source given by `function`, evaluated at time `time`.
+    ///
+    /// See [`FunctionSourceAssembler::new`] for the meaning of the solution dimension.
+    pub fn new(space: &'a Space, qtable: &'a QTable, _solution_dim: SolutionDim, function: F, time: T) -> Self {
+        Self {
+            space,
+            qtable,
+            function,
+            time,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn time(&self) -> T {
+        self.time.clone()
+    }
+
+    pub fn set_time(&mut self, time: T) {
+        self.time = time;
+    }
+
+    pub fn function(&self) -> &F {
+        &self.function
+    }
+}
+
+impl<'a, T, Space, QTable, SolutionDim, F> ElementConnectivityAssembler
+    for TimeDependentSourceAssembler<'a, T, Space, QTable, SolutionDim, F>
+where
+    Space: FiniteElementConnectivity,
+    SolutionDim: SmallDim,
+{
+    fn solution_dim(&self) -> usize {
+        SolutionDim::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+impl<'a, T, Space, QTable, SolutionDim, F> ElementVectorAssembler<T>
+    for TimeDependentSourceAssembler<'a, T, Space, QTable, SolutionDim, F>
+where
+    T: Real,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    QTable::Data: Default + Clone + 'static,
+    SolutionDim: SmallDim,
+    F: Fn(&OPoint<T, Space::GeometryDim>, T) -> OVector<T, SolutionDim>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, output: DVectorViewMut<T>) -> eyre::Result<()> {
+        let t = self.time;
+        let function = |x: &OPoint<T, Space::GeometryDim>| (self.function)(x, t);
+        let source = PositionSourceFunction::<_, SolutionDim, QTable::Data>::new(&function);
+        assemble_element_source_vector_in_space(output, self.space, self.qtable, &source, element_index);
+        Ok(())
     }
 }
 
@@ -0,0 +1,119 @@
+use crate::assembly::local::{ElementConnectivityAssembler, ElementMatrixAssembler};
+use crate::nalgebra::{DMatrix, DMatrixViewMut};
+use crate::util::small_eig::project_symmetric_onto_min_eigenvalue_with_workspace;
+use crate::{Real, Symmetry};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+
+/// An adapter that projects the element matrices produced by another assembler onto the set of
+/// symmetric positive semi-definite matrices.
+///
+/// The stiffness matrices of non-convex energies, such as those of hyperelastic materials
+/// away from the rest configuration, are generally indefinite. This makes Newton's method
+/// unreliable and prevents the use of solvers such as the conjugate gradient method.
+/// A common remedy is to replace each element matrix by its projection
+/// (see [`project_symmetric_onto_min_eigenvalue`](crate::util::small_eig::project_symmetric_onto_min_eigenvalue)),
+/// in which all eigenvalues below `min_eigenvalue` are clamped to `min_eigenvalue`.
+/// The assembled global matrix is then positive semi-definite (or definite, if `min_eigenvalue`
+/// is positive and the matrix has no null space). Element matrices whose eigenvalues all exceed
+/// the threshold are left unchanged.
+///
+/// Only the upper triangular part of the element matrices of the inner assembler is used,
+/// which is compatible with symmetric assemblers that only fill the upper triangle.
+///
+/// The projection of the element matrix does not preserve the structure of the
+/// integrand at individual quadrature points. Alternatively, the tangent of the operator can be
+/// projected at each quadrature point with [`SpdProjectedContraction`](crate::assembly::operators::SpdProjectedContraction),
+/// which is cheaper for elements with many nodes, but generally more conservative.
+#[derive(Debug, Clone)]
+pub struct SpdProjectedAssembler<Assembler, T> {
+    assembler: Assembler,
+    min_eigenvalue: T,
+}
+
+impl<Assembler, T> SpdProjectedAssembler<Assembler, T> {
+    /// Wraps the given assembler, clamping eigenvalues of its element matrices to `min_eigenvalue`.
+    ///
+    /// A threshold of zero gives the nearest positive semi-definite element matrices.
+    pub fn new(assembler: Assembler, min_eigenvalue: T) -> Self {
+        Self {
+            assembler,
+            min_eigenvalue,
+        }
+    }
+
+    pub fn min_eigenvalue(&self) -> &T {
+        &self.min_eigenvalue
+    }
+
+    pub fn inner(&self) -> &Assembler {
+        &self.assembler
+    }
+}
+
+impl<Assembler, T> ElementConnectivityAssembler for SpdProjectedAssembler<Assembler, T>
+where
+    Assembler: ElementConnectivityAssembler,
+{
+    fn solution_dim(&self) -> usize {
+        self.assembler.solution_dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.assembler.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.assembler.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.assembler.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.assembler.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+struct SpdProjectionWorkspace<T: Real> {
+    decomposition: DMatrix<T>,
+    eigenvectors: DMatrix<T>,
+}
+
+impl<T: Real> Default for SpdProjectionWorkspace<T> {
+    fn default() -> Self {
+        Self {
+            decomposition: DMatrix::zeros(0, 0),
+            eigenvectors: DMatrix::zeros(0, 0),
+        }
+    }
+}
+
+impl<T, Assembler> ElementMatrixAssembler<T> for SpdProjectedAssembler<Assembler, T>
+where
+    T: Real,
+    Assembler: ElementMatrixAssembler<T>,
+{
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<T>) -> eyre::Result<()> {
+        self.assembler
+            .assemble_element_matrix_into(element_index, DMatrixViewMut::from(&mut output))?;
+        let n = output.nrows();
+        with_thread_local_workspace(&WORKSPACE, |ws: &mut SpdProjectionWorkspace<T>| {
+            ws.decomposition.resize_mut(n, n, T::zero());
+            ws.eigenvectors.resize_mut(n, n, T::zero());
+            project_symmetric_onto_min_eigenvalue_with_workspace(
+                output,
+                self.min_eigenvalue,
+                &mut ws.decomposition,
+                &mut ws.eigenvectors,
+            );
+            Ok(())
+        })
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
@@ -0,0 +1,171 @@
+use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
+use crate::assembly::buffers::QuadratureBuffer;
+use crate::assembly::local::{
+    compute_area_weighted_normal, ElementConnectivityAssembler, ElementVectorAssembler, QuadratureTable,
+};
+use crate::error::AssemblyError;
+use crate::nalgebra::{DVectorViewMut, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OPoint, OVector, U1};
+use crate::space::FiniteElementSpace;
+use crate::{Real, SmallDim};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use std::marker::PhantomData;
+
+/// An element assembler for sources on surfaces, such as tractions and other Neumann boundary data.
+///
+/// Given a surface finite element space, for example the faces of a volume mesh obtained with
+/// [`Mesh::extract_surface_mesh`](crate::mesh::Mesh::extract_surface_mesh), and boundary data
+/// $g(\vec x, \vec n)$ that may depend on the position $\vec x$ and the unit normal $\vec n$,
+/// the assembler computes the element vectors associated with the term
+/// $$ \int_{\Gamma} g(\vec x, \vec n) \cdot v \enspace \mathrm{d}s $$
+/// in the weak form. For example, a pressure $p$ corresponds to $g(\vec x, \vec n) = - p \vec n$.
+///
+/// The normal and the area element $\mathrm{d}s$ are computed from the Jacobian of the
+/// reference-to-physical map of each surface element, see [`compute_area_weighted_normal`] for
+/// the orientation of the normal. Only surfaces in 2D and 3D are supported.
+///
+/// The element nodes are the nodes of the surface space. If the surface space uses a different
+/// node numbering than the volume space, the element vectors can be assembled into the volume
+/// system by mapping the nodes with
+/// [`map_element_nodes`](ElementConnectivityAssembler::map_element_nodes).
+#[derive(Debug, Clone)]
+pub struct SurfaceSourceAssembler<'a, T, S, Space, QTable, Source> {
+    space: &'a Space,
+    qtable: &'a QTable,
+    source: Source,
+    marker: PhantomData<(T, S)>,
+}
+
+impl<'a, T, S, Space, QTable, Source> SurfaceSourceAssembler<'a, T, S, Space, QTable, Source>
+where
+    T: Real,
+    S: SmallDim,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    Source: Fn(&OPoint<T, Space::GeometryDim>, &OVector<T, Space::GeometryDim>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    /// Constructs a new assembler for the given surface space, quadrature table and source.
+    ///
+    /// The source is called as `source(x, n)`, where `x` is the physical position and `n` is the
+    /// unit normal of the surface.
+    pub fn new(space: &'a Space, qtable: &'a QTable, source: Source) -> Self {
+        Self {
+            space,
+            qtable,
+            source,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, S, Space, QTable, Source> ElementConnectivityAssembler
+    for SurfaceSourceAssembler<'a, T, S, Space, QTable, Source>
+where
+    T: Real,
+    S: DimName,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn solution_dim(&self) -> usize {
+        S::dim()
+    }
+
+    fn num_elements(&self) -> usize {
+        self.space.num_elements()
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.space.element_node_count(element_index)
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        self.space.populate_element_nodes(output, element_index)
+    }
+}
+
+define_thread_local_workspace!(SURFACE_SOURCE_WORKSPACE);
+
+struct SurfaceSourceWorkspace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    quadrature_buffer: QuadratureBuffer<T, D, ()>,
+    basis_values: Vec<T>,
+}
+
+impl<T, D> Default for SurfaceSourceWorkspace<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            quadrature_buffer: QuadratureBuffer::default(),
+            basis_values: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T, S, Space, QTable, Source> ElementVectorAssembler<T>
+    for SurfaceSourceAssembler<'a, T, S, Space, QTable, Source>
+where
+    T: Real,
+    S: SmallDim,
+    Space: FiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    Source: Fn(&OPoint<T, Space::GeometryDim>, &OVector<T, Space::GeometryDim>) -> OVector<T, S>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, S>,
+{
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<T>) -> eyre::Result<()> {
+        with_thread_local_workspace(
+            &SURFACE_SOURCE_WORKSPACE,
+            |ws: &mut SurfaceSourceWorkspace<T, Space::ReferenceDim>| {
+                let n = self.space.element_node_count(element_index);
+                assert_eq!(
+                    output.len(),
+                    n * S::dim(),
+                    "Length of output vector must be consistent with number of nodes and solution dim"
+                );
+                // Reshape output into an `s x n` matrix, so that each column corresponds to the
+                // output associated with a node
+                let mut output = MatrixViewMut::from_slice_generic(output.as_mut_slice(), S::name(), Dyn(n));
+                output.fill(T::zero());
+
+                ws.quadrature_buffer
+                    .populate_element_weights_and_points_from_table(element_index, self.qtable);
+                ws.basis_values.resize(n, T::zero());
+
+                let (weights, points) = ws.quadrature_buffer.weights_and_points();
+                for (&w, xi) in weights.iter().zip(points) {
+                    self.space
+                        .populate_element_basis(element_index, &mut ws.basis_values, xi);
+                    let x = self.space.map_element_reference_coords(element_index, xi);
+                    let j = self.space.element_reference_jacobian(element_index, xi);
+
+                    // The norm of the area-weighted normal is the area element ds
+                    let area_weighted_normal = compute_area_weighted_normal(&j);
+                    let ds = area_weighted_normal.norm();
+                    if ds == T::zero() {
+                        return Err(AssemblyError::SingularJacobian { element_index }.into());
+                    }
+                    let normal = area_weighted_normal / ds;
+                    let g = (self.source)(&x, &normal);
+
+                    // The contribution is w * ds * [ g * phi_1, g * phi_2, ... ] = w * ds * g * phi,
+                    // where phi is a row vector of basis values
+                    let phi = MatrixView::from_slice_generic(&ws.basis_values, U1::name(), Dyn(n));
+                    output.gemm(w * ds, &g, &phi, T::one());
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -2,9 +2,13 @@ use crate::allocators::BiDimAllocator;
 use crate::nalgebra::{DMatrixViewMut, DVectorView, DefaultAllocator, DimName, OMatrix, OVector, Scalar};
 use crate::{Real, SmallDim, Symmetry};
 
+mod diffusion;
 mod laplace;
+mod spd_projection;
+pub use diffusion::*;
 pub use laplace::*;
 use nalgebra::min;
+pub use spd_projection::*;
 
 pub trait Operator<T, GeometryDim> {
     type SolutionDim: SmallDim;
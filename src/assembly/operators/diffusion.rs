@@ -0,0 +1,121 @@
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::nalgebra::{DefaultAllocator, DimName, OMatrix, OVector, U1};
+use crate::{Real, SmallDim, Symmetry};
+use numeric_literals::replace_float_literals;
+
+/// The symmetric diffusion tensor $\vec K$ associated with a quadrature point.
+///
+/// The default tensor is the identity, for which [`AnisotropicDiffusionOperator`] reduces to the
+/// [Laplace operator](crate::assembly::operators::LaplaceOperator).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffusionTensor<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    /// The diffusion tensor. Must be symmetric.
+    pub tensor: OMatrix<T, D, D>,
+}
+
+impl<T, D> DiffusionTensor<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    pub fn new(tensor: OMatrix<T, D, D>) -> Self {
+        Self { tensor }
+    }
+
+    /// An isotropic diffusion tensor $\vec K = k \vec I$.
+    pub fn isotropic(k: T) -> Self {
+        Self::new(OMatrix::<T, D, D>::identity() * k)
+    }
+}
+
+impl<T, D> Default for DiffusionTensor<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    fn default() -> Self {
+        Self::new(OMatrix::<T, D, D>::identity())
+    }
+}
+
+/// The anisotropic diffusion operator $- \nabla \cdot (\vec K \nabla u)$.
+///
+/// The diffusion tensor $\vec K = \vec K(x)$ is a symmetric $d \times d$ matrix given by the
+/// [`DiffusionTensor`] associated with each quadrature point, so that spatially varying tensors
+/// are provided through the data of the quadrature table. The elliptic operator is
+/// $g(\nabla u) = \vec K \nabla u$, and the contraction is given by
+/// $\mathcal{C}_g(\nabla u, a, b) = a^T \vec K b$. The operator derives from the energy density
+/// $\psi(\nabla u) = \frac{1}{2} \nabla u^T \vec K \nabla u$.
+///
+/// The weak form of $- \nabla \cdot (\vec K \nabla u) = f$ with homogeneous Dirichlet boundary
+/// conditions is then assembled in the same way as for the
+/// [Laplace operator](crate::assembly::operators::LaplaceOperator), which corresponds to
+/// $\vec K = \vec I$.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnisotropicDiffusionOperator;
+
+impl<T, D> Operator<T, D> for AnisotropicDiffusionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    type SolutionDim = U1;
+    type Parameters = DiffusionTensor<T, D>;
+}
+
+impl<T, D> EllipticEnergy<T, D> for AnisotropicDiffusionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, Self::SolutionDim>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn compute_energy(&self, gradient: &OMatrix<T, D, Self::SolutionDim>, parameters: &Self::Parameters) -> T {
+        0.5 * gradient.dot(&(&parameters.tensor * gradient))
+    }
+}
+
+impl<T, D> EllipticOperator<T, D> for AnisotropicDiffusionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, Self::SolutionDim>,
+{
+    fn compute_elliptic_operator(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, Self::SolutionDim> {
+        &parameters.tensor * gradient
+    }
+}
+
+impl<T, D> EllipticContraction<T, D> for AnisotropicDiffusionOperator
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, Self::SolutionDim>,
+{
+    fn contract(
+        &self,
+        _gradient: &OMatrix<T, D, Self::SolutionDim>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
+        OVector::<T, U1>::from_element(a.dot(&(&parameters.tensor * b)))
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
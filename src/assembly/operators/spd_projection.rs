@@ -0,0 +1,257 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
+use crate::nalgebra::{DMatrix, DMatrixViewMut, DVector, DVectorView, DefaultAllocator, DimName, OMatrix, OVector};
+use crate::util::small_eig::project_symmetric_onto_min_eigenvalue_with_workspace;
+use crate::{Real, SmallDim, Symmetry};
+use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+
+/// An operator adapter whose contraction is projected onto the set of positive semi-definite
+/// tangents at each quadrature point.
+///
+/// The contraction $\mathcal{C}_g (\nabla u, a, b)$ is bilinear in $a$ and $b$ and is therefore
+/// determined by the $ds \times ds$ *tangent matrix* $\vec A$ whose $s \times s$ blocks are
+/// $\vec A_{km} = \mathcal{C}_g(\nabla u, e_k, e_m)$. For an operator derived from an
+/// [elliptic energy](EllipticEnergy), $\vec A$ is the Hessian of the energy density $\psi$ with
+/// respect to $\nabla u$. This adapter replaces $\vec A$ by its projection onto the symmetric
+/// matrices whose eigenvalues are at least `min_eigenvalue`
+/// (see [`project_symmetric_onto_min_eigenvalue`](crate::util::small_eig::project_symmetric_onto_min_eigenvalue)).
+/// Since each quadrature point then contributes a positive semi-definite term, element
+/// matrices assembled with the adapted operator are positive semi-definite.
+/// For non-symmetric contractions, the symmetric part of the tangent is projected.
+///
+/// The operator itself, as well as its energy, is forwarded unchanged, so that the adapter can be
+/// used in place of the original operator when assembling the system for a Newton-type method.
+/// See also [`SpdProjectedAssembler`](crate::assembly::local::SpdProjectedAssembler),
+/// which instead projects the assembled element matrices.
+#[derive(Debug, Clone)]
+pub struct SpdProjectedContraction<Op, T> {
+    operator: Op,
+    min_eigenvalue: T,
+}
+
+impl<Op, T> SpdProjectedContraction<Op, T> {
+    /// Wraps the given operator, clamping eigenvalues of its tangent to `min_eigenvalue`.
+    ///
+    /// A threshold of zero gives the nearest positive semi-definite tangent.
+    pub fn new(operator: Op, min_eigenvalue: T) -> Self {
+        Self {
+            operator,
+            min_eigenvalue,
+        }
+    }
+
+    pub fn min_eigenvalue(&self) -> &T {
+        &self.min_eigenvalue
+    }
+
+    pub fn inner(&self) -> &Op {
+        &self.operator
+    }
+}
+
+impl<T, D, Op> Operator<T, D> for SpdProjectedContraction<Op, T>
+where
+    Op: Operator<T, D>,
+{
+    type SolutionDim = Op::SolutionDim;
+    type Parameters = Op::Parameters;
+}
+
+impl<T, D, Op> EllipticOperator<T, D> for SpdProjectedContraction<Op, T>
+where
+    T: Real,
+    D: SmallDim,
+    Op: EllipticOperator<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn compute_elliptic_operator(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, D, Self::SolutionDim> {
+        self.operator
+            .compute_elliptic_operator(gradient, parameters)
+    }
+
+    fn compute_elliptic_operator_transpose(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, D> {
+        self.operator
+            .compute_elliptic_operator_transpose(gradient, parameters)
+    }
+}
+
+impl<T, D, Op> EllipticEnergy<T, D> for SpdProjectedContraction<Op, T>
+where
+    T: Real,
+    D: SmallDim,
+    Op: EllipticEnergy<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn compute_energy(&self, gradient: &OMatrix<T, D, Self::SolutionDim>, parameters: &Self::Parameters) -> T {
+        self.operator.compute_energy(gradient, parameters)
+    }
+}
+
+define_thread_local_workspace!(WORKSPACE);
+
+struct ProjectedTangentWorkspace<T: Real> {
+    /// The stacked unit vectors $e_1, \dots, e_d$.
+    unit_vectors: DVector<T>,
+    tangent: DMatrix<T>,
+    decomposition: DMatrix<T>,
+    eigenvectors: DMatrix<T>,
+    /// The $ds \times s$ product of the tangent with a single vector $b_J$.
+    tangent_b: DMatrix<T>,
+}
+
+impl<T: Real> Default for ProjectedTangentWorkspace<T> {
+    fn default() -> Self {
+        Self {
+            unit_vectors: DVector::zeros(0),
+            tangent: DMatrix::zeros(0, 0),
+            decomposition: DMatrix::zeros(0, 0),
+            eigenvectors: DMatrix::zeros(0, 0),
+            tangent_b: DMatrix::zeros(0, 0),
+        }
+    }
+}
+
+impl<T: Real> ProjectedTangentWorkspace<T> {
+    fn compute_projected_tangent<D, Op>(
+        &mut self,
+        operator: &Op,
+        min_eigenvalue: T,
+        gradient: &OMatrix<T, D, Op::SolutionDim>,
+        parameters: &Op::Parameters,
+    ) where
+        D: SmallDim,
+        Op: EllipticContraction<T, D>,
+        DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+    {
+        let d = D::dim();
+        let n = d * Op::SolutionDim::dim();
+        self.unit_vectors.resize_vertically_mut(d * d, T::zero());
+        self.unit_vectors.fill(T::zero());
+        for k in 0..d {
+            self.unit_vectors[d * k + k] = T::one();
+        }
+        self.tangent.resize_mut(n, n, T::zero());
+        self.tangent.fill(T::zero());
+        operator.accumulate_contractions_into(
+            DMatrixViewMut::from(&mut self.tangent),
+            T::one(),
+            gradient,
+            DVectorView::from(&self.unit_vectors),
+            DVectorView::from(&self.unit_vectors),
+            parameters,
+        );
+
+        if operator.symmetry() == Symmetry::NonSymmetric {
+            let half = T::from_f64(0.5).unwrap();
+            for j in 0..n {
+                for i in 0..j {
+                    self.tangent[(i, j)] = half * (self.tangent[(i, j)] + self.tangent[(j, i)]);
+                }
+            }
+        }
+
+        self.decomposition.resize_mut(n, n, T::zero());
+        self.eigenvectors.resize_mut(n, n, T::zero());
+        project_symmetric_onto_min_eigenvalue_with_workspace(
+            &mut self.tangent,
+            min_eigenvalue,
+            &mut self.decomposition,
+            &mut self.eigenvectors,
+        );
+    }
+}
+
+impl<T, D, Op> EllipticContraction<T, D> for SpdProjectedContraction<Op, T>
+where
+    T: Real,
+    D: SmallDim,
+    Op: EllipticContraction<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, Op::SolutionDim>,
+{
+    fn contract(
+        &self,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        a: &OVector<T, D>,
+        b: &OVector<T, D>,
+        parameters: &Self::Parameters,
+    ) -> OMatrix<T, Self::SolutionDim, Self::SolutionDim> {
+        let s = Self::SolutionDim::dim();
+        let s_times_s = (Self::SolutionDim::name(), Self::SolutionDim::name());
+        with_thread_local_workspace(&WORKSPACE, |ws: &mut ProjectedTangentWorkspace<T>| {
+            ws.compute_projected_tangent(&self.operator, self.min_eigenvalue, gradient, parameters);
+            let mut output = OMatrix::<T, Self::SolutionDim, Self::SolutionDim>::zeros();
+            for (m, b_m) in b.iter().enumerate() {
+                for (k, a_k) in a.iter().enumerate() {
+                    let a_k_b_m = *a_k * *b_m;
+                    output.zip_apply(&ws.tangent.generic_view((s * k, s * m), s_times_s), |y, x| {
+                        *y += a_k_b_m * x
+                    });
+                }
+            }
+            output
+        })
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+
+    #[allow(non_snake_case)]
+    fn accumulate_contractions_into(
+        &self,
+        mut output: DMatrixViewMut<T>,
+        alpha: T,
+        gradient: &OMatrix<T, D, Self::SolutionDim>,
+        a: DVectorView<T>,
+        b: DVectorView<T>,
+        parameters: &Self::Parameters,
+    ) {
+        let d = D::dim();
+        let s = Self::SolutionDim::dim();
+        assert!(a.len() % d == 0, "Dimension of a must be divisible by d (GeometryDim)");
+        assert!(b.len() % d == 0, "Dimension of b must be divisible by d (GeometryDim)");
+        let M = a.len() / d;
+        let N = b.len() / d;
+        assert_eq!(
+            output.nrows(),
+            s * M,
+            "Number of rows in output matrix is not consistent with a"
+        );
+        assert_eq!(
+            output.ncols(),
+            s * N,
+            "Number of columns in output matrix is not consistent with b"
+        );
+
+        with_thread_local_workspace(&WORKSPACE, |ws: &mut ProjectedTangentWorkspace<T>| {
+            ws.compute_projected_tangent(&self.operator, self.min_eigenvalue, gradient, parameters);
+            ws.tangent_b.resize_mut(d * s, s, T::zero());
+
+            // C_IJ = sum_{k, m} (a_I)_k (b_J)_m A_km, where only the block upper triangle
+            // (I <= J) needs to be filled, since the projected tangent is symmetric
+            for J in 0..N {
+                ws.tangent_b.fill(T::zero());
+                for m in 0..d {
+                    let b_Jm = b[d * J + m];
+                    ws.tangent_b
+                        .zip_apply(&ws.tangent.columns(s * m, s), |y, x| *y += b_Jm * x);
+                }
+                for I in 0..usize::min(J + 1, M) {
+                    let mut c_IJ = output.view_mut((s * I, s * J), (s, s));
+                    for k in 0..d {
+                        let a_Ik = alpha * a[d * I + k];
+                        c_IJ.zip_apply(&ws.tangent_b.rows(s * k, s), |y, x| *y += a_Ik * x);
+                    }
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,283 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::local::QuadratureTable;
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DefaultAllocator, OPoint, Scalar};
+use crate::space::FiniteElementSpace;
+use crate::{Real, SmallDim};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// Storage for one value per quadrature point of every element.
+///
+/// The values for all elements are stored contiguously in a single flat buffer, with
+/// the values of each element stored in the same order as the quadrature points of the
+/// associated quadrature table. The layout is therefore stable: the value associated with
+/// quadrature point `k` of element `i` is always found at
+/// `offsets[i] + k` in the flat buffer, regardless of how the data was constructed.
+///
+/// This is typically used for history-dependent material state, per-point material parameters
+/// or for storing quantities such as stresses evaluated at quadrature points.
+///
+/// Deserialization validates the offsets in the same way as
+/// [`from_data_and_offsets`](Self::from_data_and_offsets), and fails with an error for
+/// inconsistent data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "QuadraturePointDataParts<D>")]
+pub struct QuadraturePointData<D> {
+    data: Vec<D>,
+    // Offsets into the data buffer, with one entry per element plus a final entry
+    // equal to the total number of quadrature points
+    offsets: Vec<usize>,
+}
+
+/// The serialized representation of [`QuadraturePointData`], validated on deserialization.
+#[derive(Deserialize)]
+struct QuadraturePointDataParts<D> {
+    data: Vec<D>,
+    offsets: Vec<usize>,
+}
+
+impl<D> TryFrom<QuadraturePointDataParts<D>> for QuadraturePointData<D> {
+    type Error = eyre::Report;
+
+    fn try_from(parts: QuadraturePointDataParts<D>) -> eyre::Result<Self> {
+        Self::from_data_and_offsets(parts.data, parts.offsets)
+    }
+}
+
+impl<D> QuadraturePointData<D> {
+    /// Constructs quadrature point data from a flat data buffer and per-element offsets.
+    ///
+    /// The offsets must contain one entry per element, followed by the total number of quadrature
+    /// points. See [`offsets`](Self::offsets).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the offsets are empty, do not start at zero, are not monotonically
+    /// increasing or do not end at the length of the data buffer.
+    pub fn from_data_and_offsets(data: Vec<D>, offsets: Vec<usize>) -> eyre::Result<Self> {
+        match (offsets.first(), offsets.last()) {
+            (Some(&first), Some(&last)) => {
+                if first != 0 {
+                    return Err(eyre!("The first offset is {}, but must be zero.", first));
+                }
+                if last != data.len() {
+                    return Err(eyre!(
+                        "The last offset is {}, but must be equal to the number of entries {}.",
+                        last,
+                        data.len()
+                    ));
+                }
+            }
+            _ => return Err(eyre!("Offsets must contain at least one entry.")),
+        }
+        if let Some(element_index) = offsets.windows(2).position(|range| range[0] > range[1]) {
+            return Err(eyre!(
+                "Offsets must be monotonically increasing, but the offset of element {} is {} and the next offset is {}.",
+                element_index,
+                offsets[element_index],
+                offsets[element_index + 1]
+            ));
+        }
+        Ok(Self { data, offsets })
+    }
+
+    /// Constructs quadrature point data from the number of quadrature points in each element,
+    /// with every entry initialized by the given function.
+    ///
+    /// The function receives the element index and the index of the quadrature point
+    /// within the element.
+    pub fn from_element_sizes_and_fn(
+        element_sizes: impl IntoIterator<Item = usize>,
+        mut f: impl FnMut(usize, usize) -> D,
+    ) -> Self {
+        let mut offsets = vec![0];
+        let mut data = Vec::new();
+        for (element_index, element_size) in element_sizes.into_iter().enumerate() {
+            data.extend((0..element_size).map(|point_index| f(element_index, point_index)));
+            offsets.push(data.len());
+        }
+        Self { data, offsets }
+    }
+
+    /// Constructs quadrature point data from the number of quadrature points in each element,
+    /// with every entry set to the given value.
+    pub fn from_element_sizes_and_value(element_sizes: impl IntoIterator<Item = usize>, value: D) -> Self
+    where
+        D: Clone,
+    {
+        Self::from_element_sizes_and_fn(element_sizes, |_, _| value.clone())
+    }
+
+    /// Constructs quadrature point data with the same number of quadrature points in every element,
+    /// with every entry set to the given value.
+    pub fn from_uniform_size_and_value(num_elements: usize, points_per_element: usize, value: D) -> Self
+    where
+        D: Clone,
+    {
+        Self::from_element_sizes_and_value((0..num_elements).map(|_| points_per_element), value)
+    }
+
+    /// Constructs quadrature point data with one entry per quadrature point in the table for each
+    /// of the first `num_elements` elements, with every entry set to the given value.
+    pub fn from_quadrature_table_and_value<T, GeometryDim>(
+        table: &impl QuadratureTable<T, GeometryDim>,
+        num_elements: usize,
+        value: D,
+    ) -> Self
+    where
+        T: Scalar,
+        GeometryDim: SmallDim,
+        D: Clone,
+        DefaultAllocator: Allocator<T, GeometryDim>,
+    {
+        let element_sizes = (0..num_elements).map(|i| table.element_quadrature_size(i));
+        Self::from_element_sizes_and_value(element_sizes, value)
+    }
+
+    /// Constructs quadrature point data by evaluating the given function at the physical
+    /// coordinates of every quadrature point of each element in the space.
+    ///
+    /// The function receives the element index and the physical coordinates of the
+    /// quadrature point.
+    pub fn map<T, Space>(
+        space: &Space,
+        table: &impl QuadratureTable<T, Space::ReferenceDim>,
+        mut f: impl FnMut(usize, &OPoint<T, Space::GeometryDim>) -> D,
+    ) -> Self
+    where
+        T: Real,
+        Space: FiniteElementSpace<T>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let mut offsets = vec![0];
+        let mut data = Vec::new();
+        let mut points = Vec::new();
+        let mut weights = Vec::new();
+        for element_index in 0..space.num_elements() {
+            let element_size = table.element_quadrature_size(element_index);
+            points.resize(element_size, OPoint::origin());
+            weights.resize(element_size, T::zero());
+            table.populate_element_quadrature(element_index, &mut points, &mut weights);
+            for xi in &points {
+                let x = space.map_element_reference_coords(element_index, xi);
+                data.push(f(element_index, &x));
+            }
+            offsets.push(data.len());
+        }
+        Self { data, offsets }
+    }
+
+    /// The number of elements.
+    pub fn num_elements(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// The total number of quadrature points across all elements.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the data associated with the quadrature points of the given element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_data(&self, element_index: usize) -> &[D] {
+        let begin = self.offsets[element_index];
+        let end = self.offsets[element_index + 1];
+        &self.data[begin..end]
+    }
+
+    /// Returns the mutable data associated with the quadrature points of the given element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_data_mut(&mut self, element_index: usize) -> &mut [D] {
+        let begin = self.offsets[element_index];
+        let end = self.offsets[element_index + 1];
+        &mut self.data[begin..end]
+    }
+
+    /// Returns an iterator over the data of each element.
+    pub fn iter(&self) -> impl '_ + ExactSizeIterator<Item = &[D]> {
+        self.offsets
+            .windows(2)
+            .map(move |range| &self.data[range[0]..range[1]])
+    }
+
+    /// Returns an iterator over the mutable data of each element.
+    pub fn iter_mut(&mut self) -> impl '_ + ExactSizeIterator<Item = &mut [D]> {
+        let mut remaining = self.data.as_mut_slice();
+        self.offsets.windows(2).map(move |range| {
+            let (element_data, rest) = std::mem::take(&mut remaining).split_at_mut(range[1] - range[0]);
+            remaining = rest;
+            element_data
+        })
+    }
+
+    /// Returns the data of all elements as a flat slice.
+    pub fn as_slice(&self) -> &[D] {
+        &self.data
+    }
+
+    /// Returns the data of all elements as a flat mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [D] {
+        &mut self.data
+    }
+
+    /// Returns the offsets of each element into the flat data buffer.
+    ///
+    /// The offsets contain one entry per element, followed by the total number of
+    /// quadrature points.
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Maps each entry to a new value, preserving the layout.
+    pub fn map_values<D2>(&self, f: impl FnMut(&D) -> D2) -> QuadraturePointData<D2> {
+        QuadraturePointData {
+            data: self.data.iter().map(f).collect(),
+            offsets: self.offsets.clone(),
+        }
+    }
+
+    /// Checks that the number of entries for each element matches the number of quadrature points
+    /// in the given quadrature table.
+    pub fn check_consistency_with_table<T, GeometryDim>(
+        &self,
+        table: &impl QuadratureTable<T, GeometryDim>,
+    ) -> eyre::Result<()>
+    where
+        T: Scalar,
+        GeometryDim: SmallDim,
+        DefaultAllocator: Allocator<T, GeometryDim>,
+    {
+        for (element_index, element_data) in self.iter().enumerate() {
+            let expected_size = table.element_quadrature_size(element_index);
+            if element_data.len() != expected_size {
+                return Err(eyre!(
+                    "Element {} has {} quadrature point entries, but the quadrature table has {} points.",
+                    element_index,
+                    element_data.len(),
+                    expected_size
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D> Default for QuadraturePointData<D> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+}
@@ -126,6 +126,35 @@ impl ConnectivityMut for Segment2d1Connectivity {
     }
 }
 
+/// Connectivity for a 1D segment element of polynomial degree 2.
+///
+/// The first and last nodes are the end points of the segment, and the second node is the
+/// midpoint of the segment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Segment3d1Connectivity(pub [usize; 3]);
+
+impl Connectivity for Segment3d1Connectivity {
+    type FaceConnectivity = ();
+
+    fn num_faces(&self) -> usize {
+        0
+    }
+
+    fn get_face_connectivity(&self, _index: usize) -> Option<Self::FaceConnectivity> {
+        None
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl ConnectivityMut for Segment3d1Connectivity {
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Segment2d2Connectivity(pub [usize; 2]);
 
@@ -376,10 +405,74 @@ where
     }
 }
 
+/// Connectivity for a two-dimensional Tri10 element.
+///
+/// The node ordering follows Gmsh, which coincides with the ordering of the cubic
+/// `VTK_LAGRANGE_TRIANGLE`. Each edge has two interior nodes, ordered along the edge
+/// in the direction from its first to its last vertex, and node 9 is the interior node.
+/// The schematic below demonstrates the node numbering.
+///
+/// ```text
+/// 2
+/// |`\
+/// 7  `6
+/// |    `\
+/// 8  9   `5
+/// |        `\
+/// 0---3---4--1
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Tri10d2Connectivity(pub [usize; 10]);
+
+impl<'a> From<&'a Tri10d2Connectivity> for Tri3d2Connectivity {
+    fn from(tri10: &'a Tri10d2Connectivity) -> Self {
+        let Tri10d2Connectivity(indices) = tri10;
+        Tri3d2Connectivity([indices[0], indices[1], indices[2]])
+    }
+}
+
+impl Connectivity for Tri10d2Connectivity {
+    // TODO: Use a cubic segment connectivity for the faces
+    type FaceConnectivity = ();
+
+    fn num_faces(&self) -> usize {
+        0
+    }
+
+    fn get_face_connectivity(&self, _index: usize) -> Option<Self::FaceConnectivity> {
+        None
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl ConnectivityMut for Tri10d2Connectivity {
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
+impl<T> CellConnectivity<T, U2> for Tri10d2Connectivity
+where
+    T: Scalar,
+{
+    type Cell = Triangle2d<T>;
+
+    fn cell(&self, vertices: &[Point2<T>]) -> Option<Self::Cell> {
+        Some(Triangle([
+            vertices.get(self.0[0]).cloned()?,
+            vertices.get(self.0[1]).cloned()?,
+            vertices.get(self.0[2]).cloned()?,
+        ]))
+    }
+}
+
 /// Connectivity for a 2D segment element of polynomial degree 2.
 ///
 /// This connectivity is used e.g. to represent the faces of a Quad9 element.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Segment3d2Connectivity(pub [usize; 3]);
 
 impl Connectivity for Segment3d2Connectivity {
@@ -568,7 +661,7 @@ where
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Quad4d3Connectivity(pub [usize; 4]);
 
 impl Connectivity for Quad4d3Connectivity {
@@ -659,6 +752,190 @@ where
     }
 }
 
+/// Connectivity for a 3D linear prism (wedge) element.
+///
+/// The first three vertices form the bottom triangle, ordered counter-clockwise when viewed
+/// from the top triangle, and vertices `3`, `4` and `5` are the corresponding vertices of the top
+/// triangle. This matches the reference prism (see [`ReferenceShape`](crate::element::ReferenceShape)).
+///
+/// Since the faces of a prism are a mix of triangles and quadrilaterals, which cannot be represented
+/// by a single face connectivity type, face connectivities are currently not provided.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Prism6Connectivity(pub [usize; 6]);
+
+impl Connectivity for Prism6Connectivity {
+    type FaceConnectivity = ();
+
+    fn num_faces(&self) -> usize {
+        0
+    }
+
+    fn get_face_connectivity(&self, _index: usize) -> Option<Self::FaceConnectivity> {
+        None
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl ConnectivityMut for Prism6Connectivity {
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
+/// Connectivity for the faces of a cell whose faces are a mix of triangles and quadrilaterals,
+/// such as a prism or a pyramid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MixedFaceConnectivity<Tri, Quad> {
+    Tri(Tri),
+    Quad(Quad),
+}
+
+impl<Tri, Quad> Connectivity for MixedFaceConnectivity<Tri, Quad>
+where
+    Tri: Connectivity,
+    Quad: Connectivity<FaceConnectivity = Tri::FaceConnectivity>,
+{
+    type FaceConnectivity = Tri::FaceConnectivity;
+
+    fn num_faces(&self) -> usize {
+        match self {
+            Self::Tri(tri) => tri.num_faces(),
+            Self::Quad(quad) => quad.num_faces(),
+        }
+    }
+
+    fn get_face_connectivity(&self, index: usize) -> Option<Self::FaceConnectivity> {
+        match self {
+            Self::Tri(tri) => tri.get_face_connectivity(index),
+            Self::Quad(quad) => quad.get_face_connectivity(index),
+        }
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        match self {
+            Self::Tri(tri) => tri.vertex_indices(),
+            Self::Quad(quad) => quad.vertex_indices(),
+        }
+    }
+}
+
+impl<Tri, Quad> ConnectivityMut for MixedFaceConnectivity<Tri, Quad>
+where
+    Tri: ConnectivityMut,
+    Quad: ConnectivityMut<FaceConnectivity = Tri::FaceConnectivity>,
+{
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        match self {
+            Self::Tri(tri) => tri.vertex_indices_mut(),
+            Self::Quad(quad) => quad.vertex_indices_mut(),
+        }
+    }
+}
+
+/// Connectivity for a 3D quadratic (serendipity) prism (wedge) element.
+///
+/// The node ordering is the same as for `VTK_QUADRATIC_WEDGE`. The first six nodes are ordered as
+/// for [`Prism6Connectivity`], followed by the midpoints of the edges `(0, 1)`, `(1, 2)`, `(2, 0)`
+/// of the bottom triangle, the edges `(3, 4)`, `(4, 5)`, `(5, 3)` of the top triangle and
+/// the edges `(0, 3)`, `(1, 4)`, `(2, 5)` between the triangles. Note that this differs
+/// from the Gmsh ordering of the 15-node prism.
+///
+/// The two triangular faces are [`Tri6d3Connectivity`] and the three quadrilateral faces
+/// are [`Quad8d3Connectivity`], all oriented so that their normals point out of the element.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Prism15Connectivity(pub [usize; 15]);
+
+impl<'a> From<&'a Prism15Connectivity> for Prism6Connectivity {
+    fn from(prism15: &'a Prism15Connectivity) -> Self {
+        let Prism15Connectivity(indices) = prism15;
+        let mut prism6_indices = [0; 6];
+        prism6_indices.copy_from_slice(&indices[0..6]);
+        Prism6Connectivity(prism6_indices)
+    }
+}
+
+impl Connectivity for Prism15Connectivity {
+    type FaceConnectivity = MixedFaceConnectivity<Tri6d3Connectivity, Quad8d3Connectivity>;
+
+    fn num_faces(&self) -> usize {
+        5
+    }
+
+    fn get_face_connectivity(&self, index: usize) -> Option<Self::FaceConnectivity> {
+        let v = &self.0;
+        let tri6 = |indices: [usize; 6]| Some(MixedFaceConnectivity::Tri(Tri6d3Connectivity(indices.map(|i| v[i]))));
+        let quad8 = |indices: [usize; 8]| Some(MixedFaceConnectivity::Quad(Quad8d3Connectivity(indices.map(|i| v[i]))));
+
+        // The faces are the same as for the reference prism, see
+        // `reference_face_embeddings_3d`, and must point towards the exterior
+        match index {
+            0 => tri6([0, 2, 1, 8, 7, 6]),
+            1 => tri6([3, 4, 5, 9, 10, 11]),
+            2 => quad8([0, 1, 4, 3, 6, 13, 9, 12]),
+            3 => quad8([1, 2, 5, 4, 7, 14, 10, 13]),
+            4 => quad8([2, 0, 3, 5, 8, 12, 11, 14]),
+            _ => None,
+        }
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl ConnectivityMut for Prism15Connectivity {
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
+/// Connectivity for a 3D linear pyramid element.
+///
+/// The first four vertices form the quadrilateral base, ordered counter-clockwise when viewed from
+/// the apex, which is the last vertex. This is the same ordering as used by Gmsh and `VTK_PYRAMID`.
+///
+/// The base face is a [`Quad4d3Connectivity`] and the four side faces are [`Tri3d3Connectivity`],
+/// all oriented so that their normals point out of the element.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Pyramid5Connectivity(pub [usize; 5]);
+
+impl Connectivity for Pyramid5Connectivity {
+    type FaceConnectivity = MixedFaceConnectivity<Tri3d3Connectivity, Quad4d3Connectivity>;
+
+    fn num_faces(&self) -> usize {
+        5
+    }
+
+    fn get_face_connectivity(&self, index: usize) -> Option<Self::FaceConnectivity> {
+        let v = &self.0;
+        let tri3 = |indices: [usize; 3]| Some(MixedFaceConnectivity::Tri(Tri3d3Connectivity(indices.map(|i| v[i]))));
+
+        match index {
+            0 => Some(MixedFaceConnectivity::Quad(Quad4d3Connectivity([
+                v[0], v[3], v[2], v[1],
+            ]))),
+            1 => tri3([0, 1, 4]),
+            2 => tri3([1, 2, 4]),
+            3 => tri3([2, 3, 4]),
+            4 => tri3([3, 0, 4]),
+            _ => None,
+        }
+    }
+
+    fn vertex_indices(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl ConnectivityMut for Pyramid5Connectivity {
+    fn vertex_indices_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
 /// Connectivity for a 3D tri-quadratic Hex element.
 ///
 /// The node ordering is the same as defined by gmsh, see
@@ -666,6 +943,15 @@ where
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Hex27Connectivity(pub [usize; 27]);
 
+impl<'a> From<&'a Hex27Connectivity> for Hex8Connectivity {
+    fn from(hex27: &'a Hex27Connectivity) -> Self {
+        let Hex27Connectivity(indices) = hex27;
+        let mut hex8_indices = [0; 8];
+        hex8_indices.copy_from_slice(&indices[0..8]);
+        Hex8Connectivity(hex8_indices)
+    }
+}
+
 impl Connectivity for Hex27Connectivity {
     type FaceConnectivity = Quad9d3Connectivity;
 
@@ -729,6 +1015,15 @@ where
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Hex20Connectivity(pub [usize; 20]);
 
+impl<'a> From<&'a Hex20Connectivity> for Hex8Connectivity {
+    fn from(hex20: &'a Hex20Connectivity) -> Self {
+        let Hex20Connectivity(indices) = hex20;
+        let mut hex8_indices = [0; 8];
+        hex8_indices.copy_from_slice(&indices[0..8]);
+        Hex8Connectivity(hex8_indices)
+    }
+}
+
 impl Connectivity for Hex20Connectivity {
     // TODO: Implement FaceConnectivity for Hex27
     type FaceConnectivity = Quad8d3Connectivity;
@@ -1,6 +1,6 @@
 use crate::allocators::{BiDimAllocator, DimAllocator};
 use crate::connectivity::Connectivity;
-use crate::nalgebra::MatrixViewMut;
+use crate::nalgebra::{MatrixView, MatrixViewMut};
 use crate::{Real, SmallDim};
 use fenris_geometry::AxisAlignedBoundingBox;
 use fenris_optimize::newton::NewtonSettings;
@@ -14,15 +14,23 @@ use std::error::Error;
 use std::fmt::Debug;
 
 mod hexahedron;
+mod prism;
+mod pyramid;
 mod quadrilateral;
+mod reference_domain;
 mod segment;
 mod tetrahedron;
 mod triangle;
+mod validity;
 pub use hexahedron::*;
+pub use prism::*;
+pub use pyramid::*;
 pub use quadrilateral::*;
+pub use reference_domain::*;
 pub use segment::*;
 pub use tetrahedron::*;
 pub use triangle::*;
+pub use validity::*;
 
 pub trait ReferenceFiniteElement<T>
 where
@@ -131,14 +139,21 @@ macro_rules! impl_reference_finite_element_for_fixed {
 
 impl_reference_finite_element_for_fixed!(Tri3d2Element<T>);
 impl_reference_finite_element_for_fixed!(Tri6d2Element<T>);
+impl_reference_finite_element_for_fixed!(Tri10d2Element<T>);
 impl_reference_finite_element_for_fixed!(Quad4d2Element<T>);
+impl_reference_finite_element_for_fixed!(Quad4d3Element<T>);
 impl_reference_finite_element_for_fixed!(Quad9d2Element<T>);
 impl_reference_finite_element_for_fixed!(Segment2d1Element<T>);
+impl_reference_finite_element_for_fixed!(Segment3d1Element<T>);
 impl_reference_finite_element_for_fixed!(Segment2d2Element<T>);
+impl_reference_finite_element_for_fixed!(Segment3d2Element<T>);
 impl_reference_finite_element_for_fixed!(Tet4Element<T>);
 impl_reference_finite_element_for_fixed!(Hex8Element<T>);
 impl_reference_finite_element_for_fixed!(Hex27Element<T>);
 impl_reference_finite_element_for_fixed!(Hex20Element<T>);
+impl_reference_finite_element_for_fixed!(Prism6Element<T>);
+impl_reference_finite_element_for_fixed!(Prism15Element<T>);
+impl_reference_finite_element_for_fixed!(Pyramid5Element<T>);
 impl_reference_finite_element_for_fixed!(Tri3d3Element<T>);
 impl_reference_finite_element_for_fixed!(Tet10Element<T>);
 impl_reference_finite_element_for_fixed!(Tet20Element<T>);
@@ -157,6 +172,21 @@ where
         reference_coords: &OPoint<T, Self::ReferenceDim>,
     ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim>;
 
+    /// Compute the reference Jacobian at the given reference coordinates, given the reference
+    /// gradients of the basis functions at the same coordinates.
+    ///
+    /// The gradients are stored column-wise, as populated by
+    /// [`ReferenceFiniteElement::populate_basis_gradients`]. Isoparametric elements can form the
+    /// Jacobian directly from the gradients instead of evaluating them a second time. The default
+    /// implementation ignores the gradients and calls [`reference_jacobian`](Self::reference_jacobian).
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+        _basis_gradients: MatrixView<T, Self::ReferenceDim, Dyn>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.reference_jacobian(reference_coords)
+    }
+
     /// Maps reference coordinates to physical coordinates in the element.
     fn map_reference_coords(&self, reference_coords: &OPoint<T, Self::ReferenceDim>) -> OPoint<T, Self::GeometryDim>;
 
@@ -457,6 +487,184 @@ where
     Ok(OPoint::from(xi))
 }
 
+/// Settings for [`closest_point_in_element`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClosestPointSettings<T> {
+    /// The query point is considered to be in the element if its distance to the mapped reference
+    /// coordinates is at most `tolerance` times the diameter of the element.
+    pub tolerance: T,
+    /// The maximum number of damped Gauss-Newton iterations in each run.
+    pub max_iterations: usize,
+    /// The number of samples along each reference axis used to find a new starting point if the
+    /// iteration started from the centroid of the reference domain does not converge.
+    ///
+    /// Sampling is disabled if the number is smaller than two.
+    pub samples_per_dim: usize,
+}
+
+impl<T: Real> Default for ClosestPointSettings<T> {
+    fn default() -> Self {
+        Self {
+            tolerance: T::from_f64(1e-12).unwrap(),
+            max_iterations: 100,
+            samples_per_dim: 5,
+        }
+    }
+}
+
+/// Computes the closest point in an element to the given point, in reference coordinates.
+///
+/// This inverts the reference-to-physical map of the element. The closest point is found by
+/// solving the constrained least-squares problem
+///  min_xi 1/2 || x(xi) - p ||^2   subject to   xi in the reference domain
+/// with a damped Gauss-Newton method, in which each step is projected onto the reference domain
+/// and shortened until the distance decreases. When the Gauss-Newton step leaves the reference
+/// domain, the Gauss-Newton step restricted to the face of the domain it is projected onto is
+/// attempted as well, so that minimizers on the boundary are found efficiently. If no Gauss-Newton
+/// step decreases the distance, a projected steepest descent step is attempted instead.
+///
+/// The iteration starts from the centroid of the reference domain. If it does not converge, which
+/// may happen for strongly distorted or curved elements, the reference domain is sampled on a
+/// regular grid (see [`ClosestPointSettings::samples_per_dim`]) and the iteration is restarted from
+/// the sample closest to `p`.
+///
+/// The result is [`ClosestPoint::InElement`] if the point is inside the element up to the
+/// tolerance, and otherwise [`ClosestPoint::ClosestPoint`], in which case the reference coordinates
+/// lie on the boundary of the reference domain. Since the problem is in general not convex,
+/// only a local minimizer of the distance is guaranteed to be found.
+#[allow(non_snake_case)]
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+pub fn closest_point_in_element<T, Element>(
+    element: &Element,
+    p: &OPoint<T, Element::GeometryDim>,
+    settings: &ClosestPointSettings<T>,
+) -> ClosestPoint<T, Element::ReferenceDim>
+where
+    T: Real,
+    Element: FiniteElement<T> + ReferenceDomain<T>,
+    Element::ReferenceDim: DimMin<Element::ReferenceDim, Output = Element::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<T, Element::GeometryDim, Element::ReferenceDim>,
+{
+    let tolerance = settings.tolerance * element.diameter();
+    let dist2 = |xi: &OPoint<T, Element::ReferenceDim>| (element.map_reference_coords(xi) - p).norm_squared();
+
+    // Returns the final reference coordinates, the squared distance to p and whether the
+    // iteration converged to a (local) minimizer within the maximum number of iterations
+    let gauss_newton = |mut xi: OPoint<T, Element::ReferenceDim>| {
+        let mut r = element.map_reference_coords(&xi) - p;
+        let mut r2 = r.norm_squared();
+
+        for _ in 0..settings.max_iterations {
+            if r2.sqrt() <= tolerance {
+                return (xi, r2, true);
+            }
+
+            let J = element.reference_jacobian(&xi);
+            let g = J.transpose() * &r;
+            let JTJ = J.transpose() * &J;
+            let steepest_descent = -&g / J.norm_squared().max(T::default_epsilon());
+            let gauss_newton_direction = JTJ.clone().lu().solve(&-&g);
+            // If the Gauss-Newton step leaves the reference domain, projecting it onto the domain
+            // discards most of its progress along the boundary. We therefore also consider the
+            // Gauss-Newton step restricted to the tangent space of the face it is clamped against,
+            // whose (outward) normal is given by the difference between the step and its projection.
+            let face_direction = gauss_newton_direction.as_ref().and_then(|direction| {
+                let xi_unclamped = &xi + direction;
+                let n = &xi_unclamped - element.clamp_to_reference_domain(&xi_unclamped);
+                let n_norm = n.norm();
+                if n_norm <= T::default_epsilon() {
+                    return None;
+                }
+                let n = n / n_norm;
+                let nnT = &n * n.transpose();
+                let P = OMatrix::<T, Element::ReferenceDim, Element::ReferenceDim>::identity() - &nnT;
+                let A = &P * &JTJ * &P + nnT;
+                A.lu().solve(&-(&P * &g))
+            });
+
+            let line_search = |direction: &OVector<T, Element::ReferenceDim>| {
+                let mut alpha = 1.0;
+                for _ in 0..30 {
+                    let xi_trial = element.clamp_to_reference_domain(&(&xi + direction * alpha));
+                    // Since the reference domain is convex, shorter steps cannot move away from
+                    // xi if the projected step does not
+                    if xi_trial == xi {
+                        return None;
+                    }
+                    let r_trial = element.map_reference_coords(&xi_trial) - p;
+                    let r2_trial = r_trial.norm_squared();
+                    if r2_trial < r2 {
+                        return Some((xi_trial, r_trial, r2_trial));
+                    }
+                    alpha *= 0.5;
+                }
+                None
+            };
+
+            let accepted = [gauss_newton_direction.as_ref(), face_direction.as_ref()]
+                .into_iter()
+                .flatten()
+                .filter_map(&line_search)
+                .min_by(|(_, _, r2_a), (_, _, r2_b)| r2_a.partial_cmp(r2_b).unwrap_or(std::cmp::Ordering::Less))
+                .or_else(|| line_search(&steepest_descent));
+
+            match accepted {
+                Some((xi_new, r_new, r2_new)) => {
+                    let step_norm = (&xi_new - &xi).norm();
+                    xi = xi_new;
+                    r = r_new;
+                    r2 = r2_new;
+                    if step_norm <= 1e-14 {
+                        return (xi, r2, true);
+                    }
+                }
+                // Neither direction decreases the distance, so we are at a (local) minimizer
+                None => return (xi, r2, true),
+            }
+        }
+
+        let converged = r2.sqrt() <= tolerance;
+        (xi, r2, converged)
+    };
+
+    let (mut xi, mut r2, converged) = gauss_newton(element.reference_shape().centroid());
+
+    let n = settings.samples_per_dim;
+    if !converged && n >= 2 {
+        let dim = Element::ReferenceDim::dim();
+        let shape = element.reference_shape();
+        let best_sample = (0..n.pow(dim as u32))
+            .map(|index| {
+                OPoint::from(OVector::<T, Element::ReferenceDim>::from_fn(|i, _| {
+                    let k = (index / n.pow(i as u32)) % n;
+                    -1.0 + 2.0 * T::from_usize(k).unwrap() / T::from_usize(n - 1).unwrap()
+                }))
+            })
+            .filter(|xi_sample| shape.contains(xi_sample, T::zero()))
+            .map(|xi_sample| {
+                let d2 = dist2(&xi_sample);
+                (xi_sample, d2)
+            })
+            .min_by(|(_, d2_a), (_, d2_b)| d2_a.partial_cmp(d2_b).unwrap_or(std::cmp::Ordering::Less));
+
+        if let Some((xi_sample, d2_sample)) = best_sample {
+            if d2_sample < r2 {
+                let (xi_restarted, r2_restarted, _) = gauss_newton(xi_sample);
+                if r2_restarted < r2 {
+                    xi = xi_restarted;
+                    r2 = r2_restarted;
+                }
+            }
+        }
+    }
+
+    if r2.sqrt() <= tolerance {
+        ClosestPoint::InElement(xi)
+    } else {
+        ClosestPoint::ClosestPoint(xi)
+    }
+}
+
 /// The result of a [`ClosestPointInElement`] query.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ClosestPoint<T, D>
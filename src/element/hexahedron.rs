@@ -5,8 +5,14 @@ use numeric_literals::replace_float_literals;
 
 use crate::connectivity::{Hex20Connectivity, Hex27Connectivity, Hex8Connectivity};
 use crate::element;
-use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement};
-use crate::nalgebra::{distance, Matrix3, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U20, U27, U3, U8};
+use crate::element::{
+    closest_point_in_element, BoundsForElement, ClosestPoint, ClosestPointInElement, ClosestPointSettings,
+    ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement,
+};
+use crate::geometry::AxisAlignedBoundingBox;
+use crate::nalgebra::{
+    distance, Dyn, Matrix3, MatrixView, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U20, U27, U3, U8,
+};
 use crate::Real;
 
 impl<T> ElementConnectivity<T> for Hex8Connectivity
@@ -106,6 +112,17 @@ where
         X * G.transpose()
     }
 
+    #[allow(non_snake_case)]
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        _xi: &Point3<T>,
+        basis_gradients: MatrixView<T, U3, Dyn>,
+    ) -> Matrix3<T> {
+        let X = OMatrix::<_, U3, U8>::from_fn(|i, j| self.vertices[j][i]);
+        let G = basis_gradients.fixed_columns::<8>(0);
+        X * G.transpose()
+    }
+
     // TODO: Write tests for diameter
     fn diameter(&self) -> T {
         self.vertices
@@ -153,6 +170,19 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Hex8Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        closest_point_in_element(self, p, &ClosestPointSettings::default())
+    }
+}
+
+impl<T: Real> BoundsForElement<T> for Hex8Element<T> {
+    fn element_bounds(&self) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        // The trilinear map is a convex combination of the vertices
+        AxisAlignedBoundingBox::from_points(self.vertices()).expect("Never fails since we always have > 0 vertices")
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Hex27Element<T: Scalar> {
     // Store a hex8 element for trilinear transformations from reference element
@@ -334,6 +364,12 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Hex27Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        self.hex8.closest_point(p)
+    }
+}
+
 impl<T> ElementConnectivity<T> for Hex27Connectivity
 where
     T: Real,
@@ -562,6 +598,12 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Hex20Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        self.hex8.closest_point(p)
+    }
+}
+
 impl<T> ElementConnectivity<T> for Hex20Connectivity
 where
     T: Real,
@@ -0,0 +1,360 @@
+use std::convert::TryInto;
+
+use itertools::Itertools;
+use numeric_literals::replace_float_literals;
+
+use crate::connectivity::{Prism15Connectivity, Prism6Connectivity};
+use crate::element::{
+    closest_point_in_element, ClosestPoint, ClosestPointInElement, ClosestPointSettings, ElementConnectivity,
+    FiniteElement, FixedNodesReferenceFiniteElement,
+};
+use crate::nalgebra::{distance, Matrix3, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U15, U3, U6};
+use crate::Real;
+
+impl<T> ElementConnectivity<T> for Prism6Connectivity
+where
+    T: Real,
+{
+    type Element = Prism6Element<T>;
+    type GeometryDim = U3;
+    type ReferenceDim = U3;
+
+    fn element(&self, vertices: &[OPoint<T, Self::GeometryDim>]) -> Option<Self::Element> {
+        Some(Prism6Element::from_vertices([
+            *vertices.get(self.0[0])?,
+            *vertices.get(self.0[1])?,
+            *vertices.get(self.0[2])?,
+            *vertices.get(self.0[3])?,
+            *vertices.get(self.0[4])?,
+            *vertices.get(self.0[5])?,
+        ]))
+    }
+}
+
+/// A linear prism (wedge) element.
+///
+/// The reference domain is the reference triangle in $(\xi, \eta)$ times $[-1, 1]$ in $\zeta$,
+/// and the basis functions are products of the linear triangle basis functions and the
+/// linear basis functions on $[-1, 1]$. See [`Prism6Connectivity`] for the vertex ordering.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Prism6Element<T: Scalar> {
+    vertices: [Point3<T>; 6],
+}
+
+impl<T> Prism6Element<T>
+where
+    T: Scalar,
+{
+    pub fn from_vertices(vertices: [Point3<T>; 6]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 6] {
+        &self.vertices
+    }
+}
+
+impl<T> Prism6Element<T>
+where
+    T: Real,
+{
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    pub fn reference() -> Self {
+        Self::from_vertices([
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+            Point3::new(1.0, -1.0, 1.0),
+            Point3::new(-1.0, 1.0, 1.0),
+        ])
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Prism6Element<T>
+where
+    T: Real,
+{
+    type ReferenceDim = U3;
+    type NodalDim = U6;
+
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn evaluate_basis(&self, xi: &Point3<T>) -> OMatrix<T, U1, U6> {
+        let triangle = [-(xi[0] + xi[1]) / 2.0, (1.0 + xi[0]) / 2.0, (1.0 + xi[1]) / 2.0];
+        let bottom = (1.0 - xi[2]) / 2.0;
+        let top = (1.0 + xi[2]) / 2.0;
+        OMatrix::<_, U1, U6>::from_row_slice(&[
+            triangle[0] * bottom,
+            triangle[1] * bottom,
+            triangle[2] * bottom,
+            triangle[0] * top,
+            triangle[1] * top,
+            triangle[2] * top,
+        ])
+    }
+
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn gradients(&self, xi: &Point3<T>) -> OMatrix<T, U3, U6> {
+        let triangle = [-(xi[0] + xi[1]) / 2.0, (1.0 + xi[0]) / 2.0, (1.0 + xi[1]) / 2.0];
+        let triangle_grad = [(-0.5, -0.5), (0.5, 0.0), (0.0, 0.5)];
+        let bottom = (1.0 - xi[2]) / 2.0;
+        let top = (1.0 + xi[2]) / 2.0;
+        let grad = |i: usize, z: T, z_grad: T| {
+            let (dxi, deta) = triangle_grad[i];
+            Vector3::new(dxi * z, deta * z, triangle[i] * z_grad)
+        };
+        OMatrix::from_columns(&[
+            grad(0, bottom, -0.5),
+            grad(1, bottom, -0.5),
+            grad(2, bottom, -0.5),
+            grad(0, top, 0.5),
+            grad(1, top, 0.5),
+            grad(2, top, 0.5),
+        ])
+    }
+}
+
+impl<T> FiniteElement<T> for Prism6Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point3<T>) -> Point3<T> {
+        let X = OMatrix::<_, U3, U6>::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(X * N.transpose())
+    }
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point3<T>) -> Matrix3<T> {
+        let X = OMatrix::<_, U3, U6>::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Prism6Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        closest_point_in_element(self, p, &ClosestPointSettings::default())
+    }
+}
+
+impl<T> ElementConnectivity<T> for Prism15Connectivity
+where
+    T: Real,
+{
+    type Element = Prism15Element<T>;
+    type GeometryDim = U3;
+    type ReferenceDim = U3;
+
+    fn element(&self, global_vertices: &[Point3<T>]) -> Option<Self::Element> {
+        let mut prism_vertices = [OPoint::origin(); 15];
+
+        for (local_idx, global_idx) in self.0.iter().enumerate() {
+            prism_vertices[local_idx] = *global_vertices.get(*global_idx)?;
+        }
+
+        Some(Prism15Element::from_vertices(prism_vertices))
+    }
+}
+
+/// A quadratic (serendipity) prism (wedge) element.
+///
+/// The reference domain is the same as for [`Prism6Element`]. The basis spans the quadratic
+/// polynomials, but not the full product of the quadratic triangle basis and the quadratic
+/// basis on $[-1, 1]$. See [`Prism15Connectivity`] for the node ordering.
+///
+/// The geometry is represented by the [`Prism6Element`] given by the first six vertices,
+/// so the edge nodes are assumed to be located at the midpoints of the edges.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Prism15Element<T: Scalar> {
+    // Store a prism6 element for the transformation from the reference element
+    prism6: Prism6Element<T>,
+    vertices: [Point3<T>; 15],
+}
+
+impl<T: Scalar + Copy> Prism15Element<T> {
+    pub fn from_vertices(vertices: [Point3<T>; 15]) -> Self {
+        Self {
+            prism6: Prism6Element::from_vertices(vertices[0..6].try_into().unwrap()),
+            vertices,
+        }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 15] {
+        &self.vertices
+    }
+}
+
+impl<T: Real> Prism15Element<T> {
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    pub fn reference() -> Self {
+        Self::from_vertices([
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+            Point3::new(1.0, -1.0, 1.0),
+            Point3::new(-1.0, 1.0, 1.0),
+            // Edge nodes of the bottom triangle
+            Point3::new(0.0, -1.0, -1.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Point3::new(-1.0, 0.0, -1.0),
+            // Edge nodes of the top triangle
+            Point3::new(0.0, -1.0, 1.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(-1.0, 0.0, 1.0),
+            // Edge nodes between the triangles
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(-1.0, 1.0, 0.0),
+        ])
+    }
+}
+
+impl<'a, T: Real> From<&'a Prism6Element<T>> for Prism15Element<T> {
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn from(prism6: &'a Prism6Element<T>) -> Self {
+        let v = prism6.vertices();
+        let midpoint = |a: usize, b: usize| OPoint::from((v[a].coords + v[b].coords) * 0.5);
+        Self::from_vertices([
+            v[0],
+            v[1],
+            v[2],
+            v[3],
+            v[4],
+            v[5],
+            midpoint(0, 1),
+            midpoint(1, 2),
+            midpoint(2, 0),
+            midpoint(3, 4),
+            midpoint(4, 5),
+            midpoint(5, 3),
+            midpoint(0, 3),
+            midpoint(1, 4),
+            midpoint(2, 5),
+        ])
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Prism15Element<T>
+where
+    T: Real,
+{
+    type ReferenceDim = U3;
+    type NodalDim = U15;
+
+    #[rustfmt::skip]
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn evaluate_basis(&self, xi: &Point3<T>) -> OMatrix<T, U1, U15> {
+        // Barycentric coordinates of the reference triangle
+        let l = [-(xi[0] + xi[1]) / 2.0, (1.0 + xi[0]) / 2.0, (1.0 + xi[1]) / 2.0];
+        let z = xi[2];
+        // s = -1 for the bottom triangle and s = 1 for the top triangle
+        let phi_corner = |i: usize, s: T|
+            0.5 * l[i] * (2.0 * l[i] - 1.0) * (1.0 + s * z) - 0.5 * l[i] * (1.0 - z * z);
+        let phi_triangle_edge = |i: usize, j: usize, s: T| 2.0 * l[i] * l[j] * (1.0 + s * z);
+        let phi_vertical_edge = |i: usize| l[i] * (1.0 - z * z);
+
+        OMatrix::<_, U1, U15>::from_row_slice(&[
+            phi_corner(0, -1.0),
+            phi_corner(1, -1.0),
+            phi_corner(2, -1.0),
+            phi_corner(0, 1.0),
+            phi_corner(1, 1.0),
+            phi_corner(2, 1.0),
+            phi_triangle_edge(0, 1, -1.0),
+            phi_triangle_edge(1, 2, -1.0),
+            phi_triangle_edge(2, 0, -1.0),
+            phi_triangle_edge(0, 1, 1.0),
+            phi_triangle_edge(1, 2, 1.0),
+            phi_triangle_edge(2, 0, 1.0),
+            phi_vertical_edge(0),
+            phi_vertical_edge(1),
+            phi_vertical_edge(2),
+        ])
+    }
+
+    #[rustfmt::skip]
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn gradients(&self, xi: &Point3<T>) -> OMatrix<T, U3, U15> {
+        let l = [-(xi[0] + xi[1]) / 2.0, (1.0 + xi[0]) / 2.0, (1.0 + xi[1]) / 2.0];
+        // Gradients of the barycentric coordinates with respect to (xi, eta)
+        let l_grad = [(-0.5, -0.5), (0.5, 0.0), (0.0, 0.5)];
+        let z = xi[2];
+
+        let phi_grad_corner = |i: usize, s: T| {
+            let (dxi, deta) = l_grad[i];
+            let dl = 0.5 * (4.0 * l[i] - 1.0) * (1.0 + s * z) - 0.5 * (1.0 - z * z);
+            let dz = 0.5 * l[i] * (2.0 * l[i] - 1.0) * s + l[i] * z;
+            Vector3::new(dl * dxi, dl * deta, dz)
+        };
+        let phi_grad_triangle_edge = |i: usize, j: usize, s: T| {
+            let ((dxi_i, deta_i), (dxi_j, deta_j)) = (l_grad[i], l_grad[j]);
+            let w = 2.0 * (1.0 + s * z);
+            Vector3::new(
+                w * (dxi_i * l[j] + l[i] * dxi_j),
+                w * (deta_i * l[j] + l[i] * deta_j),
+                2.0 * l[i] * l[j] * s,
+            )
+        };
+        let phi_grad_vertical_edge = |i: usize| {
+            let (dxi, deta) = l_grad[i];
+            let w = 1.0 - z * z;
+            Vector3::new(w * dxi, w * deta, -2.0 * l[i] * z)
+        };
+
+        OMatrix::from_columns(&[
+            phi_grad_corner(0, -1.0),
+            phi_grad_corner(1, -1.0),
+            phi_grad_corner(2, -1.0),
+            phi_grad_corner(0, 1.0),
+            phi_grad_corner(1, 1.0),
+            phi_grad_corner(2, 1.0),
+            phi_grad_triangle_edge(0, 1, -1.0),
+            phi_grad_triangle_edge(1, 2, -1.0),
+            phi_grad_triangle_edge(2, 0, -1.0),
+            phi_grad_triangle_edge(0, 1, 1.0),
+            phi_grad_triangle_edge(1, 2, 1.0),
+            phi_grad_triangle_edge(2, 0, 1.0),
+            phi_grad_vertical_edge(0),
+            phi_grad_vertical_edge(1),
+            phi_grad_vertical_edge(2),
+        ])
+    }
+}
+
+impl<T> FiniteElement<T> for Prism15Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    fn reference_jacobian(&self, reference_coords: &Point3<T>) -> Matrix3<T> {
+        self.prism6.reference_jacobian(reference_coords)
+    }
+
+    fn map_reference_coords(&self, reference_coords: &Point3<T>) -> Point3<T> {
+        self.prism6.map_reference_coords(reference_coords)
+    }
+
+    fn diameter(&self) -> T {
+        self.prism6.diameter()
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Prism15Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        self.prism6.closest_point(p)
+    }
+}
@@ -0,0 +1,174 @@
+use itertools::Itertools;
+use numeric_literals::replace_float_literals;
+
+use crate::connectivity::Pyramid5Connectivity;
+use crate::element::{
+    closest_point_in_element, ClosestPoint, ClosestPointInElement, ClosestPointSettings, ElementConnectivity,
+    FiniteElement, FixedNodesReferenceFiniteElement,
+};
+use crate::nalgebra::{distance, Matrix3, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U3, U5};
+use crate::Real;
+
+impl<T> ElementConnectivity<T> for Pyramid5Connectivity
+where
+    T: Real,
+{
+    type Element = Pyramid5Element<T>;
+    type GeometryDim = U3;
+    type ReferenceDim = U3;
+
+    fn element(&self, vertices: &[OPoint<T, Self::GeometryDim>]) -> Option<Self::Element> {
+        Some(Pyramid5Element::from_vertices([
+            *vertices.get(self.0[0])?,
+            *vertices.get(self.0[1])?,
+            *vertices.get(self.0[2])?,
+            *vertices.get(self.0[3])?,
+            *vertices.get(self.0[4])?,
+        ]))
+    }
+}
+
+/// A linear pyramid element.
+///
+/// The reference domain is the pyramid with base $[-1, 1]^2$ at $\zeta = -1$ and apex $(0, 0, 1)$.
+/// See [`Pyramid5Connectivity`] for the vertex ordering.
+///
+/// With $s = (1 - \zeta) / 2$, the basis functions associated with the base vertices
+/// $(\xi_i, \eta_i, -1)$ are the rational functions
+/// <div>$$
+///   N_i = \frac{(s + \xi_i \xi)(s + \eta_i \eta)}{4 s},
+/// $$</div>
+/// and the basis function associated with the apex is $N_4 = 1 - s$. The basis functions are
+/// continuous, but their gradients have no limit at the apex. At the apex, the gradients are
+/// evaluated as the limit along the axis of the pyramid, $\xi = \eta = 0$.
+///
+/// Since the basis is not polynomial, quadrature rules for polynomials on the pyramid
+/// (such as [`total_order::pyramid`](crate::quadrature::total_order::pyramid)) do not in general
+/// integrate products of basis functions exactly. The rational terms vanish for pyramids with a
+/// parallelogram base, however, so that the geometry map is affine in this case.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pyramid5Element<T: Scalar> {
+    vertices: [Point3<T>; 5],
+}
+
+impl<T> Pyramid5Element<T>
+where
+    T: Scalar,
+{
+    pub fn from_vertices(vertices: [Point3<T>; 5]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 5] {
+        &self.vertices
+    }
+}
+
+impl<T> Pyramid5Element<T>
+where
+    T: Real,
+{
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    pub fn reference() -> Self {
+        Self::from_vertices([
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ])
+    }
+}
+
+/// Returns $s = (1 - \zeta) / 2$ together with $\xi / s$ and $\eta / s$.
+///
+/// At the apex, where $s = 0$, the ratios are replaced by their limits along the axis of the pyramid.
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+fn pyramid_collapsed_coords<T: Real>(xi: &Point3<T>) -> (T, T, T) {
+    let s = (1.0 - xi[2]) / 2.0;
+    if s.abs() <= T::default_epsilon() {
+        (s, 0.0, 0.0)
+    } else {
+        (s, xi[0] / s, xi[1] / s)
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Pyramid5Element<T>
+where
+    T: Real,
+{
+    type ReferenceDim = U3;
+    type NodalDim = U5;
+
+    #[rustfmt::skip]
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn evaluate_basis(&self, xi: &Point3<T>) -> OMatrix<T, U1, U5> {
+        let (s, _, b) = pyramid_collapsed_coords(xi);
+        // Expanding the product gives (s + alpha xi + beta eta + alpha beta xi eta / s) / 4,
+        // where we write xi eta / s = xi * b to avoid dividing by zero at the apex
+        let phi_base = |alpha: T, beta: T|
+            0.25 * (s + alpha * xi[0] + beta * xi[1] + alpha * beta * xi[0] * b);
+        let phi_apex = 1.0 - s;
+        OMatrix::<_, U1, U5>::from_row_slice(&[
+            phi_base(-1.0, -1.0),
+            phi_base( 1.0, -1.0),
+            phi_base( 1.0,  1.0),
+            phi_base(-1.0,  1.0),
+            phi_apex,
+        ])
+    }
+
+    #[rustfmt::skip]
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn gradients(&self, xi: &Point3<T>) -> OMatrix<T, U3, U5> {
+        let (_, a, b) = pyramid_collapsed_coords(xi);
+        let phi_grad_base = |alpha: T, beta: T|
+            Vector3::new(
+                0.25 * (alpha + alpha * beta * b),
+                0.25 * (beta + alpha * beta * a),
+                0.25 * (-0.5 + 0.5 * alpha * beta * a * b),
+            );
+        OMatrix::from_columns(&[
+            phi_grad_base(-1.0, -1.0),
+            phi_grad_base( 1.0, -1.0),
+            phi_grad_base( 1.0,  1.0),
+            phi_grad_base(-1.0,  1.0),
+            Vector3::new(0.0, 0.0, 0.5),
+        ])
+    }
+}
+
+impl<T> FiniteElement<T> for Pyramid5Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point3<T>) -> Point3<T> {
+        let X = OMatrix::<_, U3, U5>::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(X * N.transpose())
+    }
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point3<T>) -> Matrix3<T> {
+        let X = OMatrix::<_, U3, U5>::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(T::zero(), |a, b| a.max(b))
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Pyramid5Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        closest_point_in_element(self, p, &ClosestPointSettings::default())
+    }
+}
@@ -3,11 +3,15 @@ use std::convert::TryFrom;
 use itertools::Itertools;
 use numeric_literals::replace_float_literals;
 
-use crate::connectivity::{Quad4d2Connectivity, Quad9d2Connectivity};
-use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement};
-use crate::geometry::{ConcavePolygonError, ConvexPolygon, LineSegment2d, Quad2d};
+use crate::connectivity::{Quad4d2Connectivity, Quad4d3Connectivity, Quad9d2Connectivity};
+use crate::element::{
+    closest_point_in_element, BoundsForElement, ClosestPoint, ClosestPointInElement, ClosestPointSettings,
+    ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement, SurfaceFiniteElement,
+};
+use crate::geometry::{AxisAlignedBoundingBox, ConcavePolygonError, ConvexPolygon, LineSegment2d, Quad2d};
 use crate::nalgebra::{
-    distance, Matrix1x4, Matrix2, Matrix2x4, OMatrix, OPoint, Point2, Scalar, Vector2, U1, U2, U4, U9,
+    distance, Dyn, Matrix1x4, Matrix2, Matrix2x4, Matrix3x2, Matrix3x4, MatrixView, OMatrix, OPoint, Point2, Point3,
+    Scalar, Vector2, Vector3, U1, U2, U3, U4, U9,
 };
 use crate::Real;
 
@@ -131,6 +135,17 @@ where
         X * G.transpose()
     }
 
+    #[allow(non_snake_case)]
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        _xi: &Point2<T>,
+        basis_gradients: MatrixView<T, U2, Dyn>,
+    ) -> Matrix2<T> {
+        let X: Matrix2x4<T> = Matrix2x4::from_fn(|i, j| self.vertices[j][i]);
+        let G = basis_gradients.fixed_columns::<4>(0);
+        X * G.transpose()
+    }
+
     // TODO: Write tests for diameter
     fn diameter(&self) -> T {
         self.vertices
@@ -141,6 +156,102 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Quad4d2Element<T> {
+    fn closest_point(&self, p: &Point2<T>) -> ClosestPoint<T, U2> {
+        closest_point_in_element(self, p, &ClosestPointSettings::default())
+    }
+}
+
+impl<T: Real> BoundsForElement<T> for Quad4d2Element<T> {
+    fn element_bounds(&self) -> AxisAlignedBoundingBox<T, Self::GeometryDim> {
+        // The bilinear map is a convex combination of the vertices
+        AxisAlignedBoundingBox::from_points(self.vertices()).expect("Never fails since we always have > 0 vertices")
+    }
+}
+
+/// A (surface) finite element representing bilinear basis functions on a quad, in three dimensions.
+///
+/// The reference element and the basis functions are the same as for [`Quad4d2Element`].
+/// This is for example the element associated with the faces of a [`Hex8Element`](crate::element::Hex8Element).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Quad4d3Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point3<T>; 4],
+}
+
+impl<T> Quad4d3Element<T>
+where
+    T: Scalar,
+{
+    pub fn from_vertices(vertices: [Point3<T>; 4]) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point3<T>; 4] {
+        &self.vertices
+    }
+}
+
+impl<T> FixedNodesReferenceFiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U4;
+    type ReferenceDim = U2;
+
+    fn evaluate_basis(&self, xi: &Point2<T>) -> Matrix1x4<T> {
+        Quad4d2Element::reference().evaluate_basis(xi)
+    }
+
+    fn gradients(&self, xi: &Point2<T>) -> Matrix2x4<T> {
+        Quad4d2Element::reference().gradients(xi)
+    }
+}
+
+impl<T> FiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U3;
+
+    #[allow(non_snake_case)]
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point3<T> {
+        let X: Matrix3x4<T> = Matrix3x4::from_fn(|i, j| self.vertices[j][i]);
+        let N = self.evaluate_basis(xi);
+        OPoint::from(&X * &N.transpose())
+    }
+
+    #[allow(non_snake_case)]
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix3x2<T> {
+        let X: Matrix3x4<T> = Matrix3x4::from_fn(|i, j| self.vertices[j][i]);
+        let G = self.gradients(xi);
+        X * G.transpose()
+    }
+
+    fn diameter(&self) -> T {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(T::zero(), |a, b| a.max(b.clone()))
+    }
+}
+
+impl<T> SurfaceFiniteElement<T> for Quad4d3Element<T>
+where
+    T: Real,
+{
+    /// The unit normal, given by the cross product of the columns of the Jacobian.
+    ///
+    /// For the faces of hexahedral elements, the normal points outwards.
+    fn normal(&self, xi: &Point2<T>) -> Vector3<T> {
+        let jacobian = self.reference_jacobian(xi);
+        jacobian.column(0).cross(&jacobian.column(1)).normalize()
+    }
+}
+
 /// A finite element representing quadratic basis functions on a quad, in two dimensions.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Quad9d2Element<T>
@@ -328,6 +439,12 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Quad9d2Element<T> {
+    fn closest_point(&self, p: &Point2<T>) -> ClosestPoint<T, U2> {
+        self.quad.closest_point(p)
+    }
+}
+
 impl<T> TryFrom<Quad9d2Element<T>> for ConvexPolygon<T>
 where
     T: Real,
@@ -360,6 +477,27 @@ where
     }
 }
 
+impl<T> ElementConnectivity<T> for Quad4d3Connectivity
+where
+    T: Real,
+{
+    type Element = Quad4d3Element<T>;
+    type ReferenceDim = U2;
+    type GeometryDim = U3;
+
+    fn element(&self, vertices: &[Point3<T>]) -> Option<Self::Element> {
+        let Self(indices) = self;
+        let lookup_vertex = |local_index| vertices.get(indices[local_index]).cloned();
+
+        Some(Quad4d3Element::from_vertices([
+            lookup_vertex(0)?,
+            lookup_vertex(1)?,
+            lookup_vertex(2)?,
+            lookup_vertex(3)?,
+        ]))
+    }
+}
+
 impl<T> ElementConnectivity<T> for Quad9d2Connectivity
 where
     T: Real,
@@ -0,0 +1,374 @@
+use crate::allocators::DimAllocator;
+use crate::element::{
+    Hex20Element, Hex27Element, Hex8Element, Prism15Element, Prism6Element, Pyramid5Element, Quad4d2Element,
+    Quad9d2Element, ReferenceFiniteElement, Segment2d1Element, Segment2d2Element, Segment3d1Element, Segment3d2Element,
+    Tet10Element, Tet20Element, Tet4Element, Tri10d2Element, Tri3d2Element, Tri3d3Element, Tri6d2Element,
+};
+use crate::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, OPoint, Vector3};
+use crate::Real;
+use numeric_literals::replace_float_literals;
+use std::cmp::Ordering;
+
+/// The shape of the reference domain of a finite element.
+///
+/// | Shape           | Reference domain                                                    |
+/// |-----------------|---------------------------------------------------------------------|
+/// | `Segment`       | $[-1, 1]$                                                           |
+/// | `Triangle`      | triangle with vertices $(-1, -1)$, $(1, -1)$ and $(-1, 1)$          |
+/// | `Quadrilateral` | $[-1, 1]^2$                                                         |
+/// | `Tetrahedron`   | tetrahedron with vertices $(-1, -1, -1)$ and $-\vec 1 + 2 \vec e_i$ |
+/// | `Hexahedron`    | $[-1, 1]^3$                                                         |
+/// | `Prism`         | reference triangle in $(\xi, \eta)$ times $[-1, 1]$ in $\zeta$      |
+/// | `Pyramid`       | square base $[-1, 1]^2$ at $\zeta = -1$ with apex $(0, 0, 1)$       |
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceShape {
+    Segment,
+    Triangle,
+    Quadrilateral,
+    Tetrahedron,
+    Hexahedron,
+    Prism,
+    Pyramid,
+}
+
+impl ReferenceShape {
+    /// The dimension of the reference domain.
+    pub fn dim(&self) -> usize {
+        use ReferenceShape::*;
+        match self {
+            Segment => 1,
+            Triangle | Quadrilateral => 2,
+            Tetrahedron | Hexahedron | Prism | Pyramid => 3,
+        }
+    }
+
+    /// Determines whether the point lies within distance `tol` of the reference domain.
+    ///
+    /// Points in the reference domain are always contained, also when `tol` is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the point does not match the dimension of the shape.
+    pub fn contains<T, D>(&self, xi: &OPoint<T, D>, tol: T) -> bool
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        self.assert_dim_matches::<D>();
+        let xi = xi.coords.as_slice();
+        if self.contains_exactly(xi) {
+            true
+        } else {
+            let mut projected = [T::zero(); 3];
+            let projected = &mut projected[..xi.len()];
+            projected.copy_from_slice(xi);
+            self.project_in_place(projected);
+            let dist2 = xi
+                .iter()
+                .zip(projected.iter())
+                .fold(T::zero(), |sum, (&x, &p)| sum + (x - p) * (x - p));
+            dist2 <= tol * tol
+        }
+    }
+
+    /// Computes the closest point in the reference domain to the given point.
+    ///
+    /// The result is the exact Euclidean projection onto the reference domain. In particular,
+    /// points in the reference domain are returned unchanged. Note that per-coordinate
+    /// clamping only gives the closest point for the box-shaped domains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the point does not match the dimension of the shape.
+    pub fn project<T, D>(&self, xi: &OPoint<T, D>) -> OPoint<T, D>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        self.assert_dim_matches::<D>();
+        let mut projected = xi.clone();
+        if !self.contains_exactly(xi.coords.as_slice()) {
+            self.project_in_place(projected.coords.as_mut_slice());
+        }
+        projected
+    }
+
+    /// The centroid of the reference domain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension `D` does not match the dimension of the shape.
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn centroid<T, D>(&self) -> OPoint<T, D>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        use ReferenceShape::*;
+        self.assert_dim_matches::<D>();
+        let centroid = match self {
+            Segment | Quadrilateral | Hexahedron => [0.0, 0.0, 0.0],
+            Triangle | Prism => [-1.0 / 3.0, -1.0 / 3.0, 0.0],
+            Tetrahedron => [-0.5, -0.5, -0.5],
+            Pyramid => [0.0, 0.0, -0.5],
+        };
+        OPoint::from_slice(&centroid[..D::dim()])
+    }
+
+    /// The vertices of the reference domain.
+    ///
+    /// The vertices are ordered consistently with the vertices of the corresponding linear elements,
+    /// e.g. [`Quad4d2Element`] or [`Pyramid5Element`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension `D` does not match the dimension of the shape.
+    #[rustfmt::skip]
+    pub fn vertices<T, D>(&self) -> Vec<OPoint<T, D>>
+    where
+        T: Real,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        use ReferenceShape::*;
+        self.assert_dim_matches::<D>();
+        let vertices: &[[f64; 3]] = match self {
+            Segment => &[[-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+            Triangle => &[[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, 1.0, 0.0]],
+            Quadrilateral => &[[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0]],
+            Tetrahedron => &[[-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]],
+            Hexahedron => &[
+                [-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [1.0, 1.0, -1.0], [-1.0, 1.0, -1.0],
+                [-1.0, -1.0, 1.0], [1.0, -1.0, 1.0], [1.0, 1.0, 1.0], [-1.0, 1.0, 1.0],
+            ],
+            Prism => &[
+                [-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0],
+                [-1.0, -1.0, 1.0], [1.0, -1.0, 1.0], [-1.0, 1.0, 1.0],
+            ],
+            Pyramid => &[
+                [-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [1.0, 1.0, -1.0], [-1.0, 1.0, -1.0], [0.0, 0.0, 1.0],
+            ],
+        };
+        vertices
+            .iter()
+            .map(|v| OPoint::from_slice(&v.map(|v_i| T::from_f64(v_i).unwrap())[..D::dim()]))
+            .collect()
+    }
+
+    fn assert_dim_matches<D: DimName>(&self) {
+        assert_eq!(
+            D::dim(),
+            self.dim(),
+            "Dimension of point must match dimension of reference shape {:?}",
+            self
+        );
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn contains_exactly<T: Real>(&self, xi: &[T]) -> bool {
+        use ReferenceShape::*;
+        let in_interval = |x: T| x >= -1.0 && x <= 1.0;
+        match self {
+            Segment | Quadrilateral | Hexahedron => xi.iter().copied().all(in_interval),
+            Triangle | Tetrahedron => simplex_contains_exactly(xi),
+            Prism => simplex_contains_exactly(&xi[0..2]) && in_interval(xi[2]),
+            Pyramid => {
+                let (x, y, z) = (xi[0], xi[1], xi[2]);
+                z >= -1.0 && 2.0 * x.abs() + z <= 1.0 && 2.0 * y.abs() + z <= 1.0
+            }
+        }
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn project_in_place<T: Real>(&self, xi: &mut [T]) {
+        use ReferenceShape::*;
+        let clamp_to_interval = |x: &mut T| *x = x.max(-1.0).min(1.0);
+        match self {
+            Segment | Quadrilateral | Hexahedron => xi.iter_mut().for_each(clamp_to_interval),
+            Triangle | Tetrahedron => project_onto_simplex(xi),
+            // The prism is the Cartesian product of a triangle and an interval,
+            // so the projection is the product of the individual projections
+            Prism => {
+                project_onto_simplex(&mut xi[0..2]);
+                clamp_to_interval(&mut xi[2]);
+            }
+            Pyramid => {
+                let x = Vector3::new(xi[0], xi[1], xi[2]);
+                let halfspaces = [
+                    (Vector3::new(0.0, 0.0, -1.0), 1.0),
+                    (Vector3::new(2.0, 0.0, 1.0), 1.0),
+                    (Vector3::new(-2.0, 0.0, 1.0), 1.0),
+                    (Vector3::new(0.0, 2.0, 1.0), 1.0),
+                    (Vector3::new(0.0, -2.0, 1.0), 1.0),
+                ];
+                let projected = project_onto_polytope(&x, &halfspaces);
+                xi.copy_from_slice(projected.as_slice());
+            }
+        }
+    }
+}
+
+/// Checks if the point is in the reference simplex, given by $\xi_i \geq -1$ and
+/// $\sum_i (\xi_i + 1) \leq 2$.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn simplex_contains_exactly<T: Real>(xi: &[T]) -> bool {
+    xi.iter().all(|&x| x >= -1.0) && xi.iter().fold(T::zero(), |sum, &x| sum + x + 1.0) <= 2.0
+}
+
+/// Computes the Euclidean projection onto the reference simplex.
+///
+/// In terms of $\lambda_i = (\xi_i + 1) / 2$, the reference simplex is the standard simplex
+/// $\lambda_i \geq 0$, $\sum_i \lambda_i \leq 1$. Since the change of variables is a uniform
+/// scaling and a translation, projections are preserved. If the positive part of
+/// $\lambda$ is in the standard simplex, it is the projection. Otherwise, the constraint
+/// $\sum_i \lambda_i \leq 1$ is active, and we use the sort-based projection onto the
+/// probability simplex (see e.g. Duchi et al., "Efficient projections onto the l1-ball
+/// for learning in high dimensions", 2008).
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn project_onto_simplex<T: Real>(xi: &mut [T]) {
+    let n = xi.len();
+    let mut lambda = [T::zero(); 3];
+    let lambda = &mut lambda[..n];
+    for (lambda_i, &xi_i) in lambda.iter_mut().zip(xi.iter()) {
+        *lambda_i = (xi_i + 1.0) / 2.0;
+    }
+
+    let positive_sum = lambda.iter().fold(T::zero(), |sum, &l| sum + l.max(0.0));
+    let theta = if positive_sum <= 1.0 {
+        T::zero()
+    } else {
+        let mut sorted = [T::zero(); 3];
+        let sorted = &mut sorted[..n];
+        sorted.copy_from_slice(lambda);
+        sorted.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        let mut cumulative_sum = T::zero();
+        let mut theta = T::zero();
+        for (j, &u_j) in sorted.iter().enumerate() {
+            cumulative_sum += u_j;
+            let t = (cumulative_sum - 1.0) / T::from_usize(j + 1).unwrap();
+            if u_j - t > T::zero() {
+                theta = t;
+            }
+        }
+        theta
+    };
+
+    for (xi_i, &lambda_i) in xi.iter_mut().zip(lambda.iter()) {
+        *xi_i = 2.0 * (lambda_i - theta).max(0.0) - 1.0;
+    }
+}
+
+/// Computes the Euclidean projection onto the (bounded) polytope given by the half-spaces
+/// $\vec a_i \cdot \vec x \leq b_i$.
+///
+/// The projection is the projection onto the intersection of the hyperplanes of some set of
+/// at most three active constraints. We enumerate all such sets and pick the closest feasible
+/// candidate, which is only feasible for polytopes with few faces.
+fn project_onto_polytope<T: Real>(x: &Vector3<T>, halfspaces: &[(Vector3<T>, T)]) -> Vector3<T> {
+    let eps = T::default_epsilon();
+    let feasibility_tol = T::from_f64(64.0).unwrap() * eps * (T::one() + x.amax());
+    let is_feasible = |p: &Vector3<T>| {
+        halfspaces
+            .iter()
+            .all(|(a, b)| a.dot(p) <= *b + feasibility_tol)
+    };
+
+    let mut best: Option<(Vector3<T>, T)> = None;
+    for active_set in 1..(1usize << halfspaces.len()) {
+        let active: Vec<_> = (0..halfspaces.len())
+            .filter(|i| active_set & (1 << i) != 0)
+            .map(|i| &halfspaces[i])
+            .collect();
+        if active.len() > 3 {
+            continue;
+        }
+
+        // The projection onto the intersection of the hyperplanes is p = x - A^T mu,
+        // with mu given by (A A^T) mu = A x - b
+        let a = DMatrix::from_fn(active.len(), 3, |i, j| active[i].0[j]);
+        let residual = DVector::from_fn(active.len(), |i, _| active[i].0.dot(x) - active[i].1);
+        let gram = &a * a.transpose();
+        if gram.determinant() <= eps.sqrt() {
+            // The active normals are (close to) linearly dependent
+            continue;
+        }
+        let mu = match gram.lu().solve(&residual) {
+            Some(mu) => mu,
+            None => continue,
+        };
+        let correction = a.transpose() * mu;
+        let p = x - Vector3::new(correction[0], correction[1], correction[2]);
+
+        if is_feasible(&p) {
+            let dist2 = (x - p).norm_squared();
+            if best
+                .map(|(_, best_dist2)| dist2 < best_dist2)
+                .unwrap_or(true)
+            {
+                best = Some((p, dist2));
+            }
+        }
+    }
+
+    best.map(|(p, _)| p)
+        .expect("The projection onto a bounded polytope always exists")
+}
+
+/// The tolerance used to decide whether reference coordinates computed by inverting the
+/// reference-to-physical map lie in the reference domain.
+pub(crate) fn reference_domain_tolerance<T: Real>() -> T {
+    T::from_f64(4.0).unwrap() * T::default_epsilon()
+}
+
+/// Finite elements whose reference domain is one of the standard [reference shapes](ReferenceShape).
+///
+/// This is the single API for testing whether reference coordinates lie in the reference
+/// domain of an element, and for mapping reference coordinates to the closest point in the
+/// reference domain.
+pub trait ReferenceDomain<T>: ReferenceFiniteElement<T>
+where
+    T: Real,
+    DefaultAllocator: DimAllocator<T, Self::ReferenceDim>,
+{
+    /// The shape of the reference domain.
+    fn reference_shape(&self) -> ReferenceShape;
+
+    /// Determines whether the reference coordinates lie within distance `tol`
+    /// of the reference domain.
+    ///
+    /// See [`ReferenceShape::contains`].
+    fn reference_domain_contains(&self, xi: &OPoint<T, Self::ReferenceDim>, tol: T) -> bool {
+        self.reference_shape().contains(xi, tol)
+    }
+
+    /// Computes the closest point in the reference domain to the given reference coordinates.
+    ///
+    /// See [`ReferenceShape::project`].
+    fn clamp_to_reference_domain(&self, xi: &OPoint<T, Self::ReferenceDim>) -> OPoint<T, Self::ReferenceDim> {
+        self.reference_shape().project(xi)
+    }
+}
+
+macro_rules! impl_reference_domain {
+    ($shape:ident => $($element:ident),*) => {
+        $(
+            impl<T: Real> ReferenceDomain<T> for $element<T> {
+                fn reference_shape(&self) -> ReferenceShape {
+                    ReferenceShape::$shape
+                }
+            }
+        )*
+    };
+}
+
+impl_reference_domain!(Segment => Segment2d1Element, Segment3d1Element, Segment2d2Element, Segment3d2Element);
+impl_reference_domain!(Triangle => Tri3d2Element, Tri6d2Element, Tri10d2Element, Tri3d3Element);
+impl_reference_domain!(Quadrilateral => Quad4d2Element, Quad9d2Element);
+impl_reference_domain!(Tetrahedron => Tet4Element, Tet10Element, Tet20Element);
+impl_reference_domain!(Hexahedron => Hex8Element, Hex20Element, Hex27Element);
+impl_reference_domain!(Prism => Prism6Element, Prism15Element);
+impl_reference_domain!(Pyramid => Pyramid5Element);
@@ -1,8 +1,14 @@
-use crate::connectivity::{Segment2d1Connectivity, Segment2d2Connectivity};
-use crate::element::{ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement, SurfaceFiniteElement};
+use crate::connectivity::{
+    Segment2d1Connectivity, Segment2d2Connectivity, Segment3d1Connectivity, Segment3d2Connectivity,
+};
+use crate::element::{
+    BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement,
+    FixedNodesReferenceFiniteElement, SurfaceFiniteElement,
+};
 use crate::geometry::LineSegment2d;
-use crate::nalgebra::{OMatrix, OPoint, Point1, Point2, Scalar, Vector2, U1, U2};
+use crate::nalgebra::{OMatrix, OPoint, Point1, Point2, Scalar, Vector2, U1, U2, U3};
 use crate::Real;
+use fenris_geometry::AxisAlignedBoundingBox;
 use nalgebra::{point, Vector1};
 use numeric_literals::replace_float_literals;
 
@@ -29,6 +35,45 @@ impl<T: Scalar> Segment2d1Element<T> {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A quadratic segment in one dimension.
+///
+/// The nodes are ordered such that the first and last nodes are the end points of the segment,
+/// and the second node is the midpoint of the segment. The geometry of the element is given by
+/// the interval between the end points.
+pub struct Segment3d1Element<T>
+where
+    T: Scalar,
+{
+    segment: Segment2d1Element<T>,
+    vertices: [Point1<T>; 3],
+}
+
+impl<T: Scalar> Segment3d1Element<T> {
+    pub fn from_vertices(vertices: [Point1<T>; 3]) -> Self {
+        let segment = Segment2d1Element::from_vertices([vertices[0].clone(), vertices[2].clone()]);
+        Self { segment, vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point1<T>; 3] {
+        &self.vertices
+    }
+}
+
+impl<'a, T: Real> From<&'a Segment2d1Element<T>> for Segment3d1Element<T> {
+    fn from(segment: &'a Segment2d1Element<T>) -> Self {
+        let [a, b] = segment.vertices().clone();
+        let midpoint = a.coords.lerp(&b.coords, T::from_f64(0.5).unwrap());
+        Self::from_vertices([a, midpoint.into(), b])
+    }
+}
+
+impl<T: Real> From<Segment2d1Element<T>> for Segment3d1Element<T> {
+    fn from(segment: Segment2d1Element<T>) -> Self {
+        Self::from(&segment)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// A surface element embedded in two dimensions.
 pub struct Segment2d2Element<T>
@@ -78,6 +123,45 @@ impl<'a, T: Scalar> From<&'a Segment2d2Element<T>> for LineSegment2d<T> {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A quadratic surface element embedded in two dimensions.
+///
+/// The nodes are ordered such that the first and last nodes are the end points of the segment,
+/// and the second node is the midpoint of the segment. The geometry of the element is given by
+/// the line segment between the end points.
+pub struct Segment3d2Element<T>
+where
+    T: Scalar,
+{
+    segment: Segment2d2Element<T>,
+    vertices: [Point2<T>; 3],
+}
+
+impl<T: Scalar> Segment3d2Element<T> {
+    pub fn from_vertices(vertices: [Point2<T>; 3]) -> Self {
+        let segment = Segment2d2Element::from_vertices([vertices[0].clone(), vertices[2].clone()]);
+        Self { segment, vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point2<T>; 3] {
+        &self.vertices
+    }
+}
+
+impl<'a, T: Real> From<&'a Segment2d2Element<T>> for Segment3d2Element<T> {
+    fn from(segment: &'a Segment2d2Element<T>) -> Self {
+        let [a, b] = segment.vertices().clone();
+        let midpoint = segment.to_line_segment().midpoint();
+        Self::from_vertices([a, midpoint, b])
+    }
+}
+
+impl<T: Real> From<Segment2d2Element<T>> for Segment3d2Element<T> {
+    fn from(segment: Segment2d2Element<T>) -> Self {
+        Self::from(&segment)
+    }
+}
+
 #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
 fn segment2_basis<T: Real>(xi: T) -> OMatrix<T, U1, U2> {
     let phi_1 = (1.0 - xi) / 2.0;
@@ -90,6 +174,19 @@ fn segment2_gradients<T: Real>() -> OMatrix<T, U1, U2> {
     OMatrix::<_, U1, U2>::new(-0.5, 0.5)
 }
 
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+fn segment3_basis<T: Real>(xi: T) -> OMatrix<T, U1, U3> {
+    let phi_1 = xi * (xi - 1.0) / 2.0;
+    let phi_2 = 1.0 - xi * xi;
+    let phi_3 = xi * (xi + 1.0) / 2.0;
+    OMatrix::<_, U1, U3>::new(phi_1, phi_2, phi_3)
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+fn segment3_gradients<T: Real>(xi: T) -> OMatrix<T, U1, U3> {
+    OMatrix::<_, U1, U3>::new(xi - 0.5, -2.0 * xi, xi + 0.5)
+}
+
 impl<T> FixedNodesReferenceFiniteElement<T> for Segment2d1Element<T>
 where
     T: Real,
@@ -108,6 +205,22 @@ where
     }
 }
 
+impl<T> FixedNodesReferenceFiniteElement<T> for Segment3d1Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U3;
+    type ReferenceDim = U1;
+
+    fn evaluate_basis(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        segment3_basis(xi[0])
+    }
+
+    fn gradients(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        segment3_gradients(xi[0])
+    }
+}
+
 impl<T> FixedNodesReferenceFiniteElement<T> for Segment2d2Element<T>
 where
     T: Real,
@@ -124,6 +237,22 @@ where
     }
 }
 
+impl<T> FixedNodesReferenceFiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U3;
+    type ReferenceDim = U1;
+
+    fn evaluate_basis(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        segment3_basis(xi[0])
+    }
+
+    fn gradients(&self, xi: &Point1<T>) -> OMatrix<T, U1, U3> {
+        segment3_gradients(xi[0])
+    }
+}
+
 impl<T> FiniteElement<T> for Segment2d1Element<T>
 where
     T: Real,
@@ -152,6 +281,58 @@ where
     }
 }
 
+impl<T> FiniteElement<T> for Segment3d1Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U1;
+
+    fn reference_jacobian(&self, xi: &Point1<T>) -> Vector1<T> {
+        self.segment.reference_jacobian(xi)
+    }
+
+    fn map_reference_coords(&self, xi: &Point1<T>) -> Point1<T> {
+        self.segment.map_reference_coords(xi)
+    }
+
+    fn diameter(&self) -> T {
+        self.segment.diameter()
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Segment2d1Element<T> {
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn closest_point(&self, p: &Point1<T>) -> ClosestPoint<T, U1> {
+        // The map from reference coordinates is affine, so we can invert it directly
+        // and clamp the result to the reference interval [-1, 1]
+        let [a, b] = &self.vertices;
+        let xi = 2.0 * (p.x - a.x) / (b.x - a.x) - 1.0;
+        if xi.abs() <= 1.0 {
+            ClosestPoint::InElement(Point1::new(xi))
+        } else {
+            ClosestPoint::ClosestPoint(Point1::new(xi.max(-1.0).min(1.0)))
+        }
+    }
+}
+
+impl<T: Real> BoundsForElement<T> for Segment2d1Element<T> {
+    fn element_bounds(&self) -> AxisAlignedBoundingBox<T, U1> {
+        AxisAlignedBoundingBox::from_points(&self.vertices).expect("Never fails since we always have > 0 vertices")
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Segment3d1Element<T> {
+    fn closest_point(&self, p: &Point1<T>) -> ClosestPoint<T, U1> {
+        self.segment.closest_point(p)
+    }
+}
+
+impl<T: Real> BoundsForElement<T> for Segment3d1Element<T> {
+    fn element_bounds(&self) -> AxisAlignedBoundingBox<T, U1> {
+        self.segment.element_bounds()
+    }
+}
+
 impl<T> FiniteElement<T> for Segment2d2Element<T>
 where
     T: Real,
@@ -191,6 +372,34 @@ where
     }
 }
 
+impl<T> FiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U2;
+
+    fn reference_jacobian(&self, xi: &Point1<T>) -> Vector2<T> {
+        self.segment.reference_jacobian(xi)
+    }
+
+    fn map_reference_coords(&self, xi: &Point1<T>) -> Point2<T> {
+        self.segment.map_reference_coords(xi)
+    }
+
+    fn diameter(&self) -> T {
+        self.segment.diameter()
+    }
+}
+
+impl<T> SurfaceFiniteElement<T> for Segment3d2Element<T>
+where
+    T: Real,
+{
+    fn normal(&self, xi: &Point1<T>) -> Vector2<T> {
+        self.segment.normal(xi)
+    }
+}
+
 impl<T> ElementConnectivity<T> for Segment2d2Connectivity
 where
     T: Real,
@@ -221,3 +430,31 @@ where
         Some(Segment2d1Element::from_vertices([a, b]))
     }
 }
+
+impl<T> ElementConnectivity<T> for Segment3d1Connectivity
+where
+    T: Real,
+{
+    type Element = Segment3d1Element<T>;
+    type GeometryDim = U1;
+    type ReferenceDim = U1;
+
+    fn element(&self, vertices: &[Point1<T>]) -> Option<Self::Element> {
+        let v = |i: usize| -> Point1<T> { vertices[self.0[i]].clone() };
+        Some(Segment3d1Element::from_vertices([v(0), v(1), v(2)]))
+    }
+}
+
+impl<T> ElementConnectivity<T> for Segment3d2Connectivity
+where
+    T: Real,
+{
+    type Element = Segment3d2Element<T>;
+    type GeometryDim = U2;
+    type ReferenceDim = U1;
+
+    fn element(&self, vertices: &[Point2<T>]) -> Option<Self::Element> {
+        let v = |i: usize| -> Point2<T> { vertices[self.0[i]].clone() };
+        Some(Segment3d2Element::from_vertices([v(0), v(1), v(2)]))
+    }
+}
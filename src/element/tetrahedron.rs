@@ -2,12 +2,14 @@ use numeric_literals::replace_float_literals;
 use std::cmp::Ordering;
 
 use crate::connectivity::{Connectivity, Tet10Connectivity, Tet20Connectivity, Tet4Connectivity};
+use crate::element::reference_domain::reference_domain_tolerance;
 use crate::element::{
     BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement,
-    FixedNodesReferenceFiniteElement,
+    FixedNodesReferenceFiniteElement, ReferenceDomain,
 };
 use crate::nalgebra::{
-    distance, Matrix1x4, Matrix3, Matrix3x4, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U10, U20, U3, U4,
+    distance, Dyn, Matrix1x4, Matrix3, Matrix3x4, MatrixView, OMatrix, OPoint, Point3, Scalar, Vector3, U1, U10, U20,
+    U3, U4,
 };
 use crate::Real;
 use fenris_geometry::AxisAlignedBoundingBox;
@@ -245,6 +247,12 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Tet10Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        self.tet4.closest_point(p)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Tet20Element<T>
 where
@@ -491,6 +499,12 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Tet20Element<T> {
+    fn closest_point(&self, p: &Point3<T>) -> ClosestPoint<T, U3> {
+        self.tet4.closest_point(p)
+    }
+}
+
 impl<'a, T> From<&'a Tet4Element<T>> for Tet20Element<T>
 where
     T: Real,
@@ -568,12 +582,6 @@ where
     }
 }
 
-#[replace_float_literals(T::from_f64(literal).unwrap())]
-fn is_likely_in_tet_ref_interior<T: Real>(xi: &Point3<T>) -> bool {
-    let eps = 4.0 * T::default_epsilon();
-    xi.x >= -1.0 - eps && xi.y >= -1.0 - eps && xi.z >= -1.0 - eps && xi.x + xi.y + xi.z <= -1.0 + eps
-}
-
 impl<T> FiniteElement<T> for Tet4Element<T>
 where
     T: Real,
@@ -589,6 +597,17 @@ where
         X * G.transpose()
     }
 
+    #[allow(non_snake_case)]
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        _xi: &Point3<T>,
+        basis_gradients: MatrixView<T, U3, Dyn>,
+    ) -> Matrix3<T> {
+        let X = Matrix3x4::from_fn(|i, j| self.vertices[j][i]);
+        let G = basis_gradients.fixed_columns::<4>(0);
+        X * G.transpose()
+    }
+
     #[allow(non_snake_case)]
     fn map_reference_coords(&self, xi: &Point3<T>) -> Point3<T> {
         // TODO: Store this X matrix directly in Self...?
@@ -628,7 +647,8 @@ impl<T: Real> ClosestPointInElement<T> for Tet4Element<T> {
                 })
                 // If the inverse transformation doesn't lead to a point clearly inside
                 // the reference domain, we assume that the closest point is on the boundary
-                .filter(is_likely_in_tet_ref_interior)
+                .filter(|xi| self.reference_domain_contains(xi, reference_domain_tolerance()))
+                .map(|xi| self.clamp_to_reference_domain(&xi))
         };
 
         let conn = Tet4Connectivity([0, 1, 2, 3]);
@@ -1,12 +1,13 @@
-use crate::connectivity::{Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity};
+use crate::connectivity::{Tri10d2Connectivity, Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity};
+use crate::element::reference_domain::reference_domain_tolerance;
 use crate::element::{
     BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement,
-    FixedNodesReferenceFiniteElement, SurfaceFiniteElement,
+    FixedNodesReferenceFiniteElement, ReferenceDomain, SurfaceFiniteElement,
 };
 use crate::geometry::{LineSegment2d, Triangle, Triangle2d, Triangle3d};
 use crate::nalgebra::{
-    distance, Matrix1x3, Matrix1x6, Matrix2, Matrix2x3, Matrix2x6, Matrix3, Matrix3x2, OPoint, Point2, Point3, Scalar,
-    Vector2, Vector3, U2, U3, U6,
+    distance, Dyn, Matrix1x3, Matrix1x6, Matrix2, Matrix2x3, Matrix2x6, Matrix3, Matrix3x2, MatrixView, OMatrix,
+    OPoint, Point2, Point3, Scalar, Vector2, Vector3, U1, U10, U2, U3, U6,
 };
 use crate::Real;
 use fenris_geometry::{AxisAlignedBoundingBox, LineSegment3d};
@@ -102,6 +103,17 @@ where
         X * G.transpose()
     }
 
+    #[allow(non_snake_case)]
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        _xi: &Point2<T>,
+        basis_gradients: MatrixView<T, U2, Dyn>,
+    ) -> Matrix2<T> {
+        let X: Matrix2x3<T> = Matrix2x3::from_fn(|i, j| self.vertices[j][i]);
+        let G = basis_gradients.fixed_columns::<3>(0);
+        X * G.transpose()
+    }
+
     #[allow(non_snake_case)]
     fn map_reference_coords(&self, xi: &Point2<T>) -> Point2<T> {
         // TODO: Store this X matrix directly in Self...?
@@ -268,6 +280,199 @@ where
     }
 }
 
+impl<T: Real> ClosestPointInElement<T> for Tri6d2Element<T> {
+    fn closest_point(&self, p: &Point2<T>) -> ClosestPoint<T, U2> {
+        self.tri3.closest_point(p)
+    }
+}
+
+/// A finite element representing cubic basis functions on a triangle, in two dimensions.
+///
+/// The reference element is the same as for [`Tri3d2Element`], and the nodes are ordered as
+/// described in the documentation of [`Tri10d2Connectivity`]. As for [`Tri6d2Element`],
+/// the geometry of the element is assumed to be affine.
+///
+/// On an affine element, the entries of the mass matrix are polynomials of degree 6 and the
+/// entries of the stiffness matrix for the Laplace operator are polynomials of degree 4.
+/// They are therefore integrated exactly by
+/// [`total_order::triangle(6)`](crate::quadrature::total_order::triangle) and
+/// [`total_order::triangle(4)`](crate::quadrature::total_order::triangle), respectively.
+/// Source terms and non-linear operators generally require higher strength.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tri10d2Element<T>
+where
+    T: Scalar,
+{
+    vertices: [Point2<T>; 10],
+    tri3: Tri3d2Element<T>,
+}
+
+impl<T> Tri10d2Element<T>
+where
+    T: Scalar,
+{
+    pub fn from_vertices(vertices: [Point2<T>; 10]) -> Self {
+        let v = &vertices;
+        let tri = [v[0].clone(), v[1].clone(), v[2].clone()];
+        Self {
+            vertices,
+            tri3: Tri3d2Element::from_vertices(tri),
+        }
+    }
+
+    pub fn vertices(&self) -> &[Point2<T>; 10] {
+        &self.vertices
+    }
+}
+
+impl<'a, T> From<&'a Tri3d2Element<T>> for Tri10d2Element<T>
+where
+    T: Real,
+{
+    fn from(tri3: &'a Tri3d2Element<T>) -> Self {
+        // Obtain the physical node positions by mapping the nodes of the reference element
+        let mut vertices = [Point2::origin(); 10];
+        for (v_ref, v_physical) in Self::reference().vertices().iter().zip(&mut vertices) {
+            *v_physical = tri3.map_reference_coords(v_ref);
+        }
+        Self::from_vertices(vertices)
+    }
+}
+
+impl<T> From<Tri3d2Element<T>> for Tri10d2Element<T>
+where
+    T: Real,
+{
+    fn from(tri3: Tri3d2Element<T>) -> Self {
+        Self::from(&tri3)
+    }
+}
+
+impl<T> Tri10d2Element<T>
+where
+    T: Real,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    pub fn reference() -> Self {
+        Self {
+            vertices: [
+                // Vertex nodes
+                Point2::new(-1.0, -1.0),
+                Point2::new(1.0, -1.0),
+                Point2::new(-1.0, 1.0),
+                // Between node 0 and 1
+                Point2::new(-1.0 / 3.0, -1.0),
+                Point2::new(1.0 / 3.0, -1.0),
+                // Between node 1 and 2
+                Point2::new(1.0 / 3.0, -1.0 / 3.0),
+                Point2::new(-1.0 / 3.0, 1.0 / 3.0),
+                // Between node 2 and 0
+                Point2::new(-1.0, 1.0 / 3.0),
+                Point2::new(-1.0, -1.0 / 3.0),
+                // Interior node
+                Point2::new(-1.0 / 3.0, -1.0 / 3.0),
+            ],
+            tri3: Tri3d2Element::reference(),
+        }
+    }
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+impl<T> FixedNodesReferenceFiniteElement<T> for Tri10d2Element<T>
+where
+    T: Real,
+{
+    type NodalDim = U10;
+    type ReferenceDim = U2;
+
+    #[rustfmt::skip]
+    fn evaluate_basis(&self, xi: &Point2<T>) -> OMatrix<T, U1, U10> {
+        // We express the basis functions of Tri10 as products of
+        // the Tri3 basis functions, analogously to Tet20
+        let psi = self.tri3.evaluate_basis(xi);
+
+        // Corner node i
+        let phi_vertex = |i: usize| 0.5 * psi[i] * (3.0 * psi[i] - 1.0) * (3.0 * psi[i] - 2.0);
+        // Edge node on the edge between vertex a and b that is closest to vertex a
+        let phi_edge = |closest: usize, other: usize|
+            (9.0 / 2.0) * psi[closest] * psi[other] * (3.0 * psi[closest] - 1.0);
+
+        OMatrix::<_, U1, U10>::from_row_slice(&[
+            phi_vertex(0),
+            phi_vertex(1),
+            phi_vertex(2),
+            phi_edge(0, 1),
+            phi_edge(1, 0),
+            phi_edge(1, 2),
+            phi_edge(2, 1),
+            phi_edge(2, 0),
+            phi_edge(0, 2),
+            27.0 * psi[0] * psi[1] * psi[2],
+        ])
+    }
+
+    #[rustfmt::skip]
+    fn gradients(&self, xi: &Point2<T>) -> OMatrix<T, U2, U10> {
+        let psi = self.tri3.evaluate_basis(xi);
+        let tri3_gradients = self.tri3.gradients(xi);
+        let g = |i| tri3_gradients.index((.., i));
+
+        // Gradient of vertex node i
+        let vertex_gradient = |i| -> Vector2<T> {
+            let p = psi[i];
+            g(i) * 0.5 * (27.0 * p * p - 18.0 * p + 2.0)
+        };
+
+        // Gradient of the edge node on the edge between vertex a and b that is closest to a
+        let edge_gradient = |a, b| -> Vector2<T> {
+            let pa = psi[a];
+            let pb = psi[b];
+            (g(a) * (pb * (6.0 * pa - 1.0)) + g(b) * (pa * (3.0 * pa - 1.0))) * (9.0 / 2.0)
+        };
+
+        let interior_gradient =
+            (g(0) * psi[1] * psi[2] + g(1) * psi[0] * psi[2] + g(2) * psi[0] * psi[1]) * 27.0;
+
+        OMatrix::from_columns(&[
+            vertex_gradient(0),
+            vertex_gradient(1),
+            vertex_gradient(2),
+            edge_gradient(0, 1),
+            edge_gradient(1, 0),
+            edge_gradient(1, 2),
+            edge_gradient(2, 1),
+            edge_gradient(2, 0),
+            edge_gradient(0, 2),
+            interior_gradient,
+        ])
+    }
+}
+
+impl<T> FiniteElement<T> for Tri10d2Element<T>
+where
+    T: Real,
+{
+    type GeometryDim = U2;
+
+    fn reference_jacobian(&self, xi: &Point2<T>) -> Matrix2<T> {
+        self.tri3.reference_jacobian(xi)
+    }
+
+    fn map_reference_coords(&self, xi: &Point2<T>) -> Point2<T> {
+        self.tri3.map_reference_coords(xi)
+    }
+
+    fn diameter(&self) -> T {
+        self.tri3.diameter()
+    }
+}
+
+impl<T: Real> ClosestPointInElement<T> for Tri10d2Element<T> {
+    fn closest_point(&self, p: &Point2<T>) -> ClosestPoint<T, U2> {
+        self.tri3.closest_point(p)
+    }
+}
+
 impl<T> ElementConnectivity<T> for Tri3d2Connectivity
 where
     T: Real,
@@ -311,6 +516,23 @@ where
     }
 }
 
+impl<T> ElementConnectivity<T> for Tri10d2Connectivity
+where
+    T: Real,
+{
+    type Element = Tri10d2Element<T>;
+    type ReferenceDim = U2;
+    type GeometryDim = U2;
+
+    fn element(&self, vertices: &[Point2<T>]) -> Option<Self::Element> {
+        let mut tri10_vertices = [Point2::origin(); 10];
+        for (v, &index) in tri10_vertices.iter_mut().zip(&self.0) {
+            *v = vertices.get(index)?.clone();
+        }
+        Some(Tri10d2Element::from_vertices(tri10_vertices))
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 /// A (surface) finite element representing linear basis functions on a triangle,
 /// in three dimensions.
@@ -441,12 +663,6 @@ where
     }
 }
 
-#[replace_float_literals(T::from_f64(literal).unwrap())]
-fn is_likely_in_tri_ref_interior<T: Real>(xi: &Point2<T>) -> bool {
-    let eps = 4.0 * T::default_epsilon();
-    xi.x >= -1.0 - eps && xi.y >= -1.0 - eps && xi.x + xi.y <= eps
-}
-
 impl<T: Real> ClosestPointInElement<T> for Tri3d2Element<T> {
     #[allow(non_snake_case)]
     fn closest_point(&self, p: &Point2<T>) -> ClosestPoint<T, U2> {
@@ -455,7 +671,7 @@ impl<T: Real> ClosestPointInElement<T> for Tri3d2Element<T> {
         // This implementation needs to work with (nearly) degenerate triangles. To do so
         // robustly, we therefore *always* compute the distance to all edges and
         // try to compute an interior point by inverting the affine map. We always project
-        // (coordinate-wise in this case) the interior point to the reference domain,
+        // the interior point onto the reference domain,
         // since we may otherwise obtain a point arbitrarily far outside the reference domain.
         // This ensures that, if the affine mapping is ill-conditioned due to near degeneracy,
         // we always obtain some point on the reference domain.
@@ -480,7 +696,8 @@ impl<T: Real> ClosestPointInElement<T> for Tri3d2Element<T> {
                 })
                 // If the inverse transformation doesn't lead to a point clearly inside
                 // the reference domain, we assume that the closest point is on the boundary
-                .filter(is_likely_in_tri_ref_interior)
+                .filter(|xi| self.reference_domain_contains(xi, reference_domain_tolerance()))
+                .map(|xi| self.clamp_to_reference_domain(&xi))
         };
 
         // Compute the closest point on each edge and take the point corresponding to the
@@ -549,7 +766,8 @@ impl<T: Real> ClosestPointInElement<T> for Tri3d3Element<T> {
             ATA.try_inverse()
                 .map(|ATA_inv| ATA_inv * (A.transpose() * (p - p0)))
                 .map(Point2::from)
-                .filter(is_likely_in_tri_ref_interior)
+                .filter(|xi| self.reference_domain_contains(xi, reference_domain_tolerance()))
+                .map(|xi| self.clamp_to_reference_domain(&xi))
         };
 
         // Compute the closest point on each edge and take the point corresponding to the
@@ -0,0 +1,138 @@
+use crate::allocators::BiDimAllocator;
+use crate::assembly::local::QuadratureTable;
+use crate::mesh::default_degeneracy_tolerance;
+use crate::nalgebra::{DefaultAllocator, DimMin, OPoint};
+use crate::space::ReferenceDomainInSpace;
+use crate::{Real, SmallDim};
+
+/// Classification of an element according to the sign of its Jacobian determinant.
+///
+/// See [`check_element_validity`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ElementValidityStatus {
+    /// The Jacobian determinant is positive at all evaluated points.
+    Valid,
+    /// The Jacobian determinant is (nearly) zero at some point, but not negative beyond the tolerance.
+    Degenerate,
+    /// The Jacobian determinant is negative at some point.
+    Inverted,
+}
+
+/// The result of checking the Jacobian of a single element.
+///
+/// See [`check_element_validity`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementValidity<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    pub status: ElementValidityStatus,
+    /// The minimum Jacobian determinant over all evaluated points.
+    pub min_jacobian_det: T,
+    /// The reference coordinates at which the minimum Jacobian determinant is attained.
+    pub min_reference_coords: OPoint<T, D>,
+}
+
+impl<T, D> ElementValidity<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    pub fn is_valid(&self) -> bool {
+        self.status == ElementValidityStatus::Valid
+    }
+}
+
+/// Checks the Jacobian determinants of all elements in the space, using the
+/// [default degeneracy tolerance](default_degeneracy_tolerance).
+///
+/// See [`check_element_validity_with_tolerance`].
+pub fn check_element_validity<T, D, Space, QTable>(
+    space: &Space,
+    quadrature_table: &QTable,
+) -> Vec<ElementValidity<T, D>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    Space: ReferenceDomainInSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: ?Sized + QuadratureTable<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    check_element_validity_with_tolerance(space, quadrature_table, default_degeneracy_tolerance())
+}
+
+/// Checks the Jacobian determinants of all elements in the space with the given degeneracy tolerance.
+///
+/// The determinant of the Jacobian of the reference-to-physical map is evaluated at the
+/// quadrature points given by the quadrature table and at the vertices of the reference domain.
+/// An element is considered degenerate if its minimum determinant is at most `tolerance` $\cdot h^d$,
+/// where $h$ is the diameter of the element and $d$ the dimension, and inverted if the minimum
+/// determinant is less than $-$`tolerance` $\cdot h^d$.
+///
+/// This is intended as a check before assembly, since higher-order elements, for example
+/// in meshes imported from external mesh generators, may be inverted only at some points.
+/// The check is complementary to the [mesh validation](crate::mesh::validate), which
+/// considers the connectivity and the orientation of linear elements.
+pub fn check_element_validity_with_tolerance<T, D, Space, QTable>(
+    space: &Space,
+    quadrature_table: &QTable,
+    tolerance: T,
+) -> Vec<ElementValidity<T, D>>
+where
+    T: Real,
+    D: SmallDim + DimMin<D, Output = D>,
+    Space: ReferenceDomainInSpace<T, GeometryDim = D, ReferenceDim = D>,
+    QTable: ?Sized + QuadratureTable<T, D>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let dim = i32::try_from(D::dim()).unwrap();
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    (0..space.num_elements())
+        .map(|element_index| {
+            let quadrature_size = quadrature_table.element_quadrature_size(element_index);
+            points.resize(quadrature_size, OPoint::origin());
+            weights.resize(quadrature_size, T::zero());
+            quadrature_table.populate_element_quadrature(element_index, &mut points, &mut weights);
+            let vertices = space
+                .element_reference_shape(element_index)
+                .vertices::<T, D>();
+
+            let (min_jacobian_det, min_reference_coords) = points
+                .iter()
+                .chain(vertices.iter())
+                .map(|xi| {
+                    (
+                        space
+                            .element_reference_jacobian(element_index, xi)
+                            .determinant(),
+                        xi,
+                    )
+                })
+                .fold(None, |min: Option<(T, &OPoint<T, D>)>, (det, xi)| match min {
+                    Some((min_det, _)) if min_det <= det => min,
+                    _ => Some((det, xi)),
+                })
+                .map(|(det, xi)| (det, xi.clone()))
+                .expect("The reference domain always has vertices");
+
+            let threshold = tolerance * space.diameter(element_index).powi(dim);
+            let status = if min_jacobian_det > threshold {
+                ElementValidityStatus::Valid
+            } else if min_jacobian_det < -threshold {
+                ElementValidityStatus::Inverted
+            } else {
+                ElementValidityStatus::Degenerate
+            };
+
+            ElementValidity {
+                status,
+                min_jacobian_det,
+                min_reference_coords,
+            }
+        })
+        .collect()
+}
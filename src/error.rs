@@ -1,6 +1,6 @@
-//! Functionality for error estimation.
+//! Functionality for error estimation, and typed errors for assembly failures.
 use crate::allocators::{BiDimAllocator, TriDimAllocator};
-use crate::assembly::global::assemble_scalar;
+use crate::assembly::global::{assemble_scalar, par_assemble_scalar};
 use crate::assembly::local::QuadratureTable;
 use crate::element::VolumetricFiniteElement;
 use crate::integrate::dependency::DependsOnGrad;
@@ -13,6 +13,9 @@ use crate::nalgebra::{DefaultAllocator, OPoint, OVector};
 use crate::space::{InterpolateGradientInSpace, InterpolateInSpace, VolumetricFiniteElementSpace};
 use crate::{Real, SmallDim};
 use nalgebra::{OMatrix, Scalar, Vector1, U1};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 
 /// A function $u: \mathbb{R}^d \rightarrow \mathbb{R}^s$ of the form $u(x)$ used to represent a reference solution.
 ///
@@ -370,3 +373,231 @@ where
 {
     estimate_H1_seminorm_error_squared(space, u_grad, u_h, qtable).map(|err2| err2.sqrt())
 }
+
+/// Estimate the squared $L^2$ error $\norm{u_h - u}^2_{L^2}$ on the given finite element space
+/// with the given solution weights and quadrature table, integrating elements in parallel.
+///
+/// See [`estimate_L2_error_squared`].
+#[allow(non_snake_case)]
+pub fn par_estimate_L2_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync + ?Sized),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_L2_error_squared_integrand(u))
+        .build_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $L^2$ error $\norm{u_h - u}_{L^2}$ on the given finite element space
+/// with the given solution weights and quadrature table, integrating elements in parallel.
+///
+/// See [`estimate_L2_error`].
+#[allow(non_snake_case)]
+pub fn par_estimate_L2_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u: &(impl SolutionFunction<T, Space::GeometryDim, SolutionDim> + Sync + ?Sized),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    Ok(par_estimate_L2_error_squared(space, u, u_h, qtable)?.sqrt())
+}
+
+/// Estimate the squared $H^1$ *seminorm* error $\| u_h - u \|^2_{H^1}$ on the given finite element space
+/// with the given solution weights and quadrature table, integrating elements in parallel.
+///
+/// See [`estimate_H1_seminorm_error_squared`].
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_seminorm_error_squared<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let assembler = ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u_h.into())
+        .with_integrand(make_H1_seminorm_error_squared_integrand(u_grad))
+        .build_volume_integrator();
+
+    par_assemble_scalar(&assembler)
+}
+
+/// Estimate the $H^1$ *seminorm* error $\|u_h - u \|_{H^1}$ on the given finite element space
+/// with the given solution weights and quadrature table, integrating elements in parallel.
+///
+/// See [`estimate_H1_seminorm_error`].
+#[allow(non_snake_case)]
+pub fn par_estimate_H1_seminorm_error<'a, T, SolutionDim, Space, QTable>(
+    space: &Space,
+    u_grad: &(impl SolutionGradient<T, Space::GeometryDim, SolutionDim> + Sync),
+    u_h: impl Into<DVectorView<'a, T>>,
+    qtable: &QTable,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T> + Sync,
+    QTable: QuadratureTable<T, Space::ReferenceDim> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_estimate_H1_seminorm_error_squared(space, u_grad, u_h, qtable).map(|err2| err2.sqrt())
+}
+
+/// Errors that can occur when assembling a single element.
+///
+/// Built-in element assemblers return their errors as [`eyre::Report`] for compatibility with the
+/// assembler traits, but construct the report from an `AssemblyError` when the failure falls
+/// into one of the categories below. A driver can therefore react to specific failures by
+/// downcasting the report, for example to retry a load step with a smaller increment if an element
+/// was inverted:
+///
+/// ```ignore
+/// match report.downcast_ref::<AssemblyError>() {
+///     Some(AssemblyError::ElementInverted { element_index, .. }) => { /* reduce step size */ }
+///     _ => return Err(report),
+/// }
+/// ```
+///
+/// Downcasting also works for reports to which context has been added with
+/// [`wrap_err`](eyre::WrapErr::wrap_err). Every variant contains the index of the element that
+/// failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AssemblyError {
+    /// The determinant of the element Jacobian changes sign inside the element, so that the
+    /// element is folded over itself. `min_jacobian_determinant` is the smallest determinant
+    /// encountered at the quadrature points. Elements with a consistently negative determinant are
+    /// not considered inverted, since the orientation of the reference element is a matter of
+    /// convention.
+    ElementInverted {
+        element_index: usize,
+        min_jacobian_determinant: f64,
+    },
+    /// The element Jacobian is singular at a quadrature point.
+    SingularJacobian { element_index: usize },
+    /// The element Jacobian is too ill-conditioned according to the
+    /// [conditioning policy](crate::assembly::local::JacobianConditioningPolicy) in use.
+    IllConditionedJacobian {
+        element_index: usize,
+        condition_number: f64,
+        max_condition_number: f64,
+    },
+    /// The material or operator failed to evaluate for the element.
+    MaterialFailure {
+        element_index: usize,
+        source: Box<dyn Error + Send + Sync + 'static>,
+    },
+    /// The data provided for the element has incompatible dimensions.
+    IncompatibleDimensions {
+        element_index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The element contributes to an entry that is not present in the sparsity pattern of the
+    /// output matrix.
+    MissingSparsityEntry {
+        element_index: usize,
+        row: usize,
+        col: usize,
+    },
+}
+
+impl AssemblyError {
+    /// The index of the element that failed to assemble.
+    pub fn element_index(&self) -> usize {
+        match *self {
+            Self::ElementInverted { element_index, .. }
+            | Self::SingularJacobian { element_index }
+            | Self::IllConditionedJacobian { element_index, .. }
+            | Self::MaterialFailure { element_index, .. }
+            | Self::IncompatibleDimensions { element_index, .. }
+            | Self::MissingSparsityEntry { element_index, .. } => element_index,
+        }
+    }
+}
+
+impl Display for AssemblyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ElementInverted {
+                element_index,
+                min_jacobian_determinant,
+            } => write!(
+                f,
+                "Element {} is inverted (minimum Jacobian determinant {})",
+                element_index, min_jacobian_determinant
+            ),
+            Self::SingularJacobian { element_index } => {
+                write!(f, "Singular Jacobian encountered in element {}", element_index)
+            }
+            Self::IllConditionedJacobian {
+                element_index,
+                condition_number,
+                max_condition_number,
+            } => write!(
+                f,
+                "Ill-conditioned element Jacobian: condition number {} exceeds the maximum {} in element {}",
+                condition_number, max_condition_number, element_index
+            ),
+            Self::MaterialFailure { element_index, source } => {
+                write!(f, "Material failure in element {}: {}", element_index, source)
+            }
+            Self::IncompatibleDimensions {
+                element_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Incompatible dimensions for element {}: expected {}, got {}",
+                element_index, expected, actual
+            ),
+            Self::MissingSparsityEntry {
+                element_index,
+                row,
+                col,
+            } => write!(
+                f,
+                "Element {} contributes to entry ({}, {}), which is not present in the sparsity pattern",
+                element_index, row, col
+            ),
+        }
+    }
+}
+
+impl Error for AssemblyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::MaterialFailure { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
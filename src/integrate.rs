@@ -1,17 +1,21 @@
 //! Tools for integrating functions on finite element spaces.
-use crate::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
+use crate::allocators::{BiDimAllocator, DimAllocator, ElementConnectivityAllocator, TriDimAllocator};
 use crate::assembly::buffers::{BasisFunctionBuffer, QuadratureBuffer};
-use crate::assembly::global::gather_global_to_local;
+use crate::assembly::global::{assemble_scalar, gather_global_to_local, par_assemble_scalar};
 use crate::assembly::local::{ElementConnectivityAssembler, ElementScalarAssembler, QuadratureTable};
-use crate::element::{FiniteElement, VolumetricFiniteElement};
+use crate::element::{ElementConnectivity, FiniteElement, VolumetricFiniteElement};
+use crate::mesh::topology::MeshTopology;
+use crate::mesh::Mesh;
 use crate::nalgebra::{DVector, DefaultAllocator, DimName, OMatrix, OPoint, Scalar, U1};
-use crate::quadrature::Quadrature;
+use crate::quadrature::faces::FaceEmbedding;
+use crate::quadrature::{Quadrature, QuadraturePair};
 use crate::space::{ElementInSpace, FiniteElementSpace, VolumetricFiniteElementSpace};
 use crate::util::{reshape_to_slice, try_transmute_ref};
 use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
-use eyre::eyre;
-use nalgebra::{DVectorView, Dyn, MatrixViewMut, OVector};
+use eyre::{bail, eyre};
+use nalgebra::{DVectorView, Dyn, MatrixViewMut, OVector, Vector1};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::marker::PhantomData;
 
 /// Computes the Riemannian volume form for the given dimensions.
@@ -1028,3 +1032,501 @@ where
         Ok(integral[0])
     }
 }
+
+/// A scalar integrand $f(x, u, \nabla u)$ given by a closure.
+struct ScalarIntegrand<F>(F);
+
+impl<T, F, GeometryDim, SolutionDim> UGradFunction<T, GeometryDim, SolutionDim> for ScalarIntegrand<F>
+where
+    T: Scalar,
+    F: Fn(&OPoint<T, GeometryDim>, &OVector<T, SolutionDim>, &OMatrix<T, GeometryDim, SolutionDim>) -> T,
+    GeometryDim: SmallDim,
+    SolutionDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, GeometryDim, SolutionDim>,
+{
+    type OutputDim = U1;
+
+    fn evaluate(
+        &self,
+        x: &OPoint<T, GeometryDim>,
+        u: impl FnOnce() -> OVector<T, SolutionDim>,
+        u_grad: impl FnOnce() -> OMatrix<T, GeometryDim, SolutionDim>,
+    ) -> OVector<T, U1> {
+        Vector1::new((self.0)(x, &u(), &u_grad()))
+    }
+}
+
+fn build_scalar_volume_integrator<'a, T, Space, QTable, SolutionDim, F>(
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    f: F,
+) -> ElementIntegralVolumeAssembler<'a, T, ScalarIntegrand<F>, SolutionDim, Space, QTable>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    F: Fn(
+        &OPoint<T, Space::ReferenceDim>,
+        &OVector<T, SolutionDim>,
+        &OMatrix<T, Space::ReferenceDim, SolutionDim>,
+    ) -> T,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    ElementIntegralAssemblerBuilder::new()
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_interpolation_weights(u)
+        .with_integrand(ScalarIntegrand(f))
+        .build_volume_integrator()
+}
+
+/// Integrates a scalar functional of the finite element solution over the domain of the space.
+///
+/// Computes
+/// <div>$$
+///   \int_\Omega f(x, u_h(x), \nabla u_h(x)) \\, \mathrm{d}x,
+/// $$</div>
+/// where $u_h: \Omega \rightarrow \mathbb{R}^s$ is the finite element function with interpolation
+/// weights `u`, and the integral over each element is approximated by the quadrature given by the
+/// quadrature table. The gradient $\nabla u_h$ is a $d \times s$ matrix whose columns are the
+/// gradients of the components of $u_h$. Since the solution dimension $s$ is deduced from the
+/// closure, its parameters generally need to be annotated.
+///
+/// Functionals that do not depend on $u_h$ can be used to compute e.g. the volume of the domain.
+/// Note that quadrature data stored in the table is not made available to the integrand.
+/// For integrands that depend on such data, implement an
+/// [`ElementScalarAssembler`] and use [`assemble_scalar`] instead.
+///
+/// # Errors
+///
+/// Returns an error if the Jacobian of an element is singular at a quadrature point.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not equal to $s$ times the number of nodes in the space.
+///
+/// # Examples
+///
+/// ```rust
+/// use fenris::integrate::integrate_over_space;
+/// use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+/// use fenris::mesh::QuadMesh2d;
+/// use fenris::nalgebra::{DVector, Matrix2x1, Point2, Vector1};
+/// use fenris::quadrature::CanonicalStiffnessQuadrature;
+///
+/// let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+/// let qtable = mesh.canonical_stiffness_quadrature();
+/// let u = DVector::zeros(mesh.vertices().len());
+/// let area = integrate_over_space(&mesh, &qtable, &u, |_: &Point2<f64>, _: &Vector1<f64>, _: &Matrix2x1<f64>| 1.0)
+///     .unwrap();
+/// assert!((area - 1.0).abs() < 1e-12);
+/// ```
+pub fn integrate_over_space<'a, T, Space, QTable, SolutionDim, F>(
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    f: F,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    F: Fn(
+        &OPoint<T, Space::ReferenceDim>,
+        &OVector<T, SolutionDim>,
+        &OMatrix<T, Space::ReferenceDim, SolutionDim>,
+    ) -> T,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    assemble_scalar(&build_scalar_volume_integrator(space, qtable, u, f))
+}
+
+/// Integrates a scalar functional of the finite element solution over the domain of the space
+/// in parallel.
+///
+/// See [`integrate_over_space`] for details.
+pub fn par_integrate_over_space<'a, T, Space, QTable, SolutionDim, F>(
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    f: F,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: Sync + VolumetricFiniteElementSpace<T>,
+    QTable: Sync + QuadratureTable<T, Space::ReferenceDim>,
+    F: Sync
+        + Fn(
+            &OPoint<T, Space::ReferenceDim>,
+            &OVector<T, SolutionDim>,
+            &OMatrix<T, Space::ReferenceDim, SolutionDim>,
+        ) -> T,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    par_assemble_scalar(&build_scalar_volume_integrator(space, qtable, u, f))
+}
+
+/// Integrates a scalar functional of the finite element solution over the subdomain
+/// given by the union of the elements with the given indices.
+///
+/// Each element index is expected to appear at most once, as elements are otherwise
+/// counted multiple times. See [`integrate_over_space`] for details.
+pub fn integrate_over_subset<'a, T, Space, QTable, SolutionDim, F>(
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    element_indices: &[usize],
+    f: F,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: VolumetricFiniteElementSpace<T>,
+    QTable: QuadratureTable<T, Space::ReferenceDim>,
+    F: Fn(
+        &OPoint<T, Space::ReferenceDim>,
+        &OVector<T, SolutionDim>,
+        &OMatrix<T, Space::ReferenceDim, SolutionDim>,
+    ) -> T,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let integrator = build_scalar_volume_integrator(space, qtable, u, f);
+    let mut integral = T::zero();
+    for &i in element_indices {
+        integral += integrator
+            .assemble_element_scalar(i)
+            .map_err(|error| error.wrap_err(format!("Integration failed for element {}", i)))?;
+    }
+    Ok(integral)
+}
+
+/// Integrates a scalar functional of the finite element solution over the subdomain
+/// given by the union of the elements with the given indices in parallel.
+///
+/// See [`integrate_over_subset`] for details.
+pub fn par_integrate_over_subset<'a, T, Space, QTable, SolutionDim, F>(
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: impl Into<DVectorView<'a, T>>,
+    element_indices: &[usize],
+    f: F,
+) -> eyre::Result<T>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: Sync + VolumetricFiniteElementSpace<T>,
+    QTable: Sync + QuadratureTable<T, Space::ReferenceDim>,
+    F: Sync
+        + Fn(
+            &OPoint<T, Space::ReferenceDim>,
+            &OVector<T, SolutionDim>,
+            &OMatrix<T, Space::ReferenceDim, SolutionDim>,
+        ) -> T,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let integrator = build_scalar_volume_integrator(space, qtable, u, f);
+    element_indices
+        .par_iter()
+        .map(|&i| {
+            integrator
+                .assemble_element_scalar(i)
+                .map_err(|error| error.wrap_err(format!("Integration failed for element {}", i)))
+        })
+        .try_reduce(|| T::zero(), |a, b| Ok(a + b))
+}
+
+/// Computes the normal of the hyperplane spanned by the columns of `tangents`, scaled by the
+/// measure of the parallelotope spanned by the columns.
+///
+/// The normal $n$ is defined by $n \cdot v = \det \begin{bmatrix} v & t_1 & \dots & t_{d - 1} \end{bmatrix}$,
+/// which in 2D gives $n = (t_y, -t_x)$ and in 3D gives $n = t_1 \times t_2$.
+fn scaled_hyperplane_normal<T, D, FaceDim>(tangents: &OMatrix<T, D, FaceDim>) -> OVector<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    FaceDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, FaceDim>,
+{
+    assert_eq!(
+        FaceDim::dim() + 1,
+        D::dim(),
+        "Face dimension must be one less than the geometry dimension"
+    );
+    let mut matrix = OMatrix::<T, D, D>::zeros();
+    matrix
+        .generic_view_mut((0, 1), (D::name(), FaceDim::name()))
+        .copy_from(tangents);
+    let mut normal = OVector::<T, D>::zeros();
+    for i in 0..D::dim() {
+        matrix.column_mut(0).fill(T::zero());
+        matrix[(i, 0)] = T::one();
+        normal[i] = matrix.determinant();
+    }
+    normal
+}
+
+/// Computes the normal of a face of an element, scaled by the surface measure of the face relative
+/// to its reference domain, pointing out of the element.
+///
+/// The normal computed from the tangents of the face points out of the element if the element is
+/// positively oriented. For inverted elements the orientation is reversed, which is corrected for
+/// by the sign of the determinant of the Jacobian of the element.
+fn outward_scaled_face_normal<T, D, FaceDim>(
+    jacobian: &OMatrix<T, D, D>,
+    face_jacobian: &OMatrix<T, D, FaceDim>,
+) -> OVector<T, D>
+where
+    T: Real,
+    D: SmallDim,
+    FaceDim: SmallDim,
+    DefaultAllocator: BiDimAllocator<T, D, FaceDim>,
+{
+    let scaled_normal = scaled_hyperplane_normal(&(jacobian * face_jacobian));
+    if jacobian.determinant() < T::zero() {
+        -scaled_normal
+    } else {
+        scaled_normal
+    }
+}
+
+/// Integrates a scalar functional of the finite element solution over a set of boundary faces.
+///
+/// Computes
+/// <div>$$
+///   \int_\Gamma f(x, n(x), u_h(x), \nabla u_h(x)) \\, \mathrm{d}s,
+/// $$</div>
+/// where $\Gamma$ is the union of the given faces and $n$ is the outward unit normal.
+/// This can be used to compute e.g. the flux $\int_\Gamma \nabla u_h \cdot n \\, \mathrm{d}s$
+/// through part of the boundary, or the mean value of the solution over an outlet.
+///
+/// Each face is given as a pair `(element_index, local_face_index)`, as returned by
+/// [`Mesh::find_boundary_faces`](crate::mesh::Mesh::find_boundary_faces) for meshes.
+/// The local face index is used to look up the face quadrature in `face_quadratures`,
+/// which associates each face of the reference element with its embedding into the reference
+/// element and a quadrature rule on the reference domain of the face, as returned by
+/// [`reference_face_quadratures_2d`](crate::quadrature::faces::reference_face_quadratures_2d)
+/// and [`reference_face_quadratures_3d`](crate::quadrature::faces::reference_face_quadratures_3d).
+/// The solution, its gradient, the normal and the surface measure are all computed from the mapping of
+/// the volume element, restricted to the face. Since the solution dimension $s$ is deduced from the
+/// closure, its parameters generally need to be annotated.
+///
+/// The normal is determined from the mapping of the element, and points out of the element
+/// regardless of whether the element is positively oriented.
+///
+/// # Errors
+///
+/// Returns an error if an element or local face index is out of bounds, or if the Jacobian of
+/// an element is singular at a quadrature point.
+///
+/// # Panics
+///
+/// Panics if the length of `u` is not equal to $s$ times the number of nodes in the space.
+pub fn integrate_over_boundary<'a, T, D, FaceDim, SolutionDim, Space, F>(
+    space: &Space,
+    boundary_faces: &[(usize, usize)],
+    face_quadratures: &[(FaceEmbedding<T, FaceDim, D>, QuadraturePair<T, FaceDim>)],
+    u: impl Into<DVectorView<'a, T>>,
+    f: F,
+) -> eyre::Result<T>
+where
+    T: Real,
+    D: SmallDim,
+    FaceDim: SmallDim,
+    SolutionDim: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    F: Fn(&OPoint<T, D>, &OVector<T, D>, &OVector<T, SolutionDim>, &OMatrix<T, D, SolutionDim>) -> T,
+    DefaultAllocator: TriDimAllocator<T, D, FaceDim, SolutionDim>,
+{
+    let u = u.into();
+    let s = SolutionDim::dim();
+    assert_eq!(
+        u.len(),
+        s * space.num_nodes(),
+        "Size of interpolation weight vector does not match expected number of DOFs ( {} x {} )",
+        s,
+        space.num_nodes()
+    );
+
+    let mut nodes = Vec::new();
+    let mut u_local = DVector::zeros(0);
+    let mut basis_values = Vec::new();
+    let mut ref_gradients = OMatrix::<T, D, Dyn>::zeros(0);
+    let mut integral = T::zero();
+    for &(element_index, face_index) in boundary_faces {
+        if element_index >= space.num_elements() {
+            bail!(
+                "Element index {} is out of bounds for space with {} elements",
+                element_index,
+                space.num_elements()
+            );
+        }
+        let (embedding, (weights, points)) = face_quadratures.get(face_index).ok_or_else(|| {
+            eyre!(
+                "Local face index {} of element {} is out of bounds for {} face quadratures",
+                face_index,
+                element_index,
+                face_quadratures.len()
+            )
+        })?;
+
+        let n = space.element_node_count(element_index);
+        nodes.resize(n, usize::MAX);
+        space.populate_element_nodes(&mut nodes, element_index);
+        u_local.resize_vertically_mut(s * n, T::zero());
+        gather_global_to_local(&u, &mut u_local, &nodes, s);
+        basis_values.resize(n, T::zero());
+        ref_gradients.resize_horizontally_mut(n, T::zero());
+
+        for (&w, eta) in weights.iter().zip(points) {
+            let xi = embedding.map_reference_coords(eta);
+            let jacobian = space.element_reference_jacobian(element_index, &xi);
+            let jacobian_inv_t = jacobian
+                .clone()
+                .try_inverse()
+                .ok_or_else(|| {
+                    eyre!(
+                        "Jacobian of element {} is singular on local face {}",
+                        element_index,
+                        face_index
+                    )
+                })?
+                .transpose();
+
+            // The tangents of the face in physical space span the face, and the norm of the
+            // scaled normal is the surface measure relative to the face reference domain
+            let scaled_normal = outward_scaled_face_normal(&jacobian, embedding.jacobian());
+            let ds = scaled_normal.norm();
+            let normal = scaled_normal / ds;
+
+            let x = space.map_element_reference_coords(element_index, &xi);
+            space.populate_element_basis(element_index, &mut basis_values, &xi);
+            space.populate_element_gradients(element_index, ref_gradients.as_view_mut(), &xi);
+            let u_h = crate::util::compute_interpolation(&u_local, DVectorView::from_slice(&basis_values, n));
+            let u_h_ref_grad: OMatrix<T, D, SolutionDim> = crate::util::compute_interpolation_gradient(
+                &u_local,
+                DVectorView::from_slice(ref_gradients.as_slice(), D::dim() * n),
+            );
+            let u_h_grad = jacobian_inv_t * u_h_ref_grad;
+
+            integral += f(&x, &normal, &u_h, &u_h_grad) * w * ds;
+        }
+    }
+
+    Ok(integral)
+}
+
+/// Computes the outward unit normal and the area of a face of an element.
+///
+/// The face is given by the element index and the local face index, which is used to look up the
+/// face quadrature in `face_quadratures` in the same way as for [`integrate_over_boundary`].
+/// The area (the length for faces of 2D elements) is computed by integrating the surface measure
+/// of the face over its reference domain, and the normal is the average of the unit normal over
+/// the face, normalized to unit length. For faces of affine elements, the surface measure and the
+/// normal are constant, so that the results are exact for any quadrature rule. For curved faces,
+/// such as the faces of higher-order isoparametric elements, their accuracy is determined by the
+/// face quadrature.
+///
+/// The normal is determined from the mapping of the element, and points out of the element
+/// regardless of whether the element is positively oriented.
+///
+/// # Errors
+///
+/// Returns an error if the element or local face index is out of bounds, or if the Jacobian of
+/// the element is singular at a quadrature point.
+pub fn compute_face_normal_and_area<T, D, FaceDim, Space>(
+    space: &Space,
+    element_index: usize,
+    local_face_index: usize,
+    face_quadratures: &[(FaceEmbedding<T, FaceDim, D>, QuadraturePair<T, FaceDim>)],
+) -> eyre::Result<(OVector<T, D>, T)>
+where
+    T: Real,
+    D: SmallDim,
+    FaceDim: SmallDim,
+    Space: FiniteElementSpace<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, FaceDim>,
+{
+    if element_index >= space.num_elements() {
+        bail!(
+            "Element index {} is out of bounds for space with {} elements",
+            element_index,
+            space.num_elements()
+        );
+    }
+    let (embedding, (weights, points)) = face_quadratures.get(local_face_index).ok_or_else(|| {
+        eyre!(
+            "Local face index {} of element {} is out of bounds for {} face quadratures",
+            local_face_index,
+            element_index,
+            face_quadratures.len()
+        )
+    })?;
+
+    let mut area = T::zero();
+    let mut weighted_normal = OVector::<T, D>::zeros();
+    for (&w, eta) in weights.iter().zip(points) {
+        let xi = embedding.map_reference_coords(eta);
+        let jacobian = space.element_reference_jacobian(element_index, &xi);
+        if jacobian.determinant() == T::zero() {
+            bail!(
+                "Jacobian of element {} is singular on local face {}",
+                element_index,
+                local_face_index
+            );
+        }
+        // Since n ds is the scaled normal, its integral is the area-weighted sum of the normals
+        let scaled_normal = outward_scaled_face_normal(&jacobian, embedding.jacobian());
+        area += w * scaled_normal.norm();
+        weighted_normal += scaled_normal * w;
+    }
+
+    let normal_norm = weighted_normal.norm();
+    if normal_norm == T::zero() {
+        bail!(
+            "Local face {} of element {} is degenerate and has no well-defined normal",
+            local_face_index,
+            element_index
+        );
+    }
+    Ok((weighted_normal / normal_norm, area))
+}
+
+/// Computes the outward unit normal and the area of each boundary face of a mesh.
+///
+/// The boundary faces are given as pairs `(element_index, local_face_index)` in the order of
+/// [`MeshTopology::boundary_faces`](crate::mesh::topology::MeshTopology::boundary_faces),
+/// and the normal and area of each face are computed with [`compute_face_normal_and_area`].
+///
+/// # Errors
+///
+/// Returns an error if a local face index is out of bounds for the face quadratures, or if the
+/// Jacobian of an element is singular on one of its boundary faces.
+pub fn compute_boundary_normals<T, D, C, FaceDim>(
+    mesh: &Mesh<T, D, C>,
+    face_quadratures: &[(FaceEmbedding<T, FaceDim, D>, QuadraturePair<T, FaceDim>)],
+) -> eyre::Result<Vec<((usize, usize), OVector<T, D>, T)>>
+where
+    T: Real,
+    D: SmallDim,
+    FaceDim: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C> + BiDimAllocator<T, D, FaceDim>,
+{
+    MeshTopology::from_mesh(mesh)
+        .boundary_faces()
+        .iter()
+        .map(|&(element_index, local_face_index)| {
+            let (normal, area) = compute_face_normal_and_area::<T, D, FaceDim, _>(
+                mesh,
+                element_index,
+                local_face_index,
+                face_quadratures,
+            )?;
+            Ok(((element_index, local_face_index), normal, area))
+        })
+        .collect()
+}
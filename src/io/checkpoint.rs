@@ -0,0 +1,174 @@
+//! Binary checkpoints of a mesh together with named solution vectors, for restarting
+//! long-running simulations.
+//!
+//! Requires the `checkpoint` feature.
+//!
+//! A checkpoint file starts with a header consisting of the magic bytes `FENRISCP`, the format
+//! version as a little-endian `u32`, the size in bytes of the scalar type and the geometric
+//! dimension of the mesh, each as a single byte. The header is followed by the mesh and the state
+//! vectors, encoded with [`bincode`]. Loading a checkpoint with a different scalar type width or
+//! dimension than it was saved with fails with an error instead of producing garbage.
+//!
+//! ```no_run
+//! # use fenris::io::checkpoint::{load_checkpoint, save_checkpoint};
+//! # use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+//! # use fenris::mesh::HexMesh;
+//! # use fenris::nalgebra::{DVector, DVectorView};
+//! # fn main() -> eyre::Result<()> {
+//! let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(4);
+//! let u = DVector::zeros(3 * mesh.vertices().len());
+//! let v = DVector::zeros(3 * mesh.vertices().len());
+//! save_checkpoint(
+//!     "checkpoint.bin",
+//!     &mesh,
+//!     &[("u", DVectorView::from(&u)), ("v", DVectorView::from(&v))],
+//! )?;
+//!
+//! let (mesh, state): (HexMesh<f64>, _) = load_checkpoint("checkpoint.bin")?;
+//! assert_eq!(state[0].0, "u");
+//! # Ok(())
+//! # }
+//! ```
+use crate::mesh::Mesh;
+use crate::nalgebra::allocator::Allocator;
+use crate::nalgebra::{DVector, DVectorView, DefaultAllocator, DimName, Scalar};
+use eyre::{bail, eyre, Context};
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+use std::fs::{create_dir_all, read, File};
+use std::io::{BufWriter, Write};
+use std::mem::size_of;
+use std::path::Path;
+
+/// The magic bytes at the start of every checkpoint file.
+const CHECKPOINT_MAGIC: &[u8; 8] = b"FENRISCP";
+
+/// The current version of the checkpoint format.
+pub const CHECKPOINT_FORMAT_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = CHECKPOINT_MAGIC.len() + 4 + 1 + 1;
+
+/// Serializes a vector view as a sequence, so that it can be deserialized as a `Vec<T>`
+/// without first copying the view.
+struct StateView<'a, 'b, T: Scalar>(&'a DVectorView<'b, T>);
+
+impl<'a, 'b, T: Scalar + Serialize> Serialize for StateView<'a, 'b, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// Saves a checkpoint of the mesh and the named state vectors to the given path.
+///
+/// The state vectors are stored in the given order. Parent directories are created if they do
+/// not already exist. See the [module-level documentation](self) for the file format.
+pub fn save_checkpoint<T, D, C>(
+    file_path: impl AsRef<Path>,
+    mesh: &Mesh<T, D, C>,
+    named_state: &[(&str, DVectorView<T>)],
+) -> eyre::Result<()>
+where
+    T: Scalar + Serialize,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+    Mesh<T, D, C>: Serialize,
+{
+    let file_path = file_path.as_ref();
+    if let Some(parent) = file_path.parent() {
+        create_dir_all(parent)?;
+    }
+    let file = File::create(file_path).wrap_err_with(|| format!("failed to create file {:?}", file_path))?;
+    let mut writer = BufWriter::new(file);
+
+    writer.write_all(CHECKPOINT_MAGIC)?;
+    writer.write_all(&CHECKPOINT_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[scalar_width::<T>()?, D::dim().try_into()?])?;
+
+    let state: Vec<_> = named_state
+        .iter()
+        .map(|(name, values)| (*name, StateView(values)))
+        .collect();
+    bincode::serialize_into(&mut writer, &(mesh, state))
+        .wrap_err_with(|| format!("failed to write checkpoint to file {:?}", file_path))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Loads a checkpoint saved with [`save_checkpoint`].
+///
+/// Returns the mesh and the named state vectors in the order in which they were saved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, is not a checkpoint, is truncated or otherwise
+/// corrupt, or if it was saved with a different format version, scalar type width or
+/// dimension. Note that the connectivity type is not recorded in the file, so loading with
+/// a different connectivity type than the checkpoint was saved with is only detected if the
+/// data cannot be decoded.
+pub fn load_checkpoint<T, D, C>(file_path: impl AsRef<Path>) -> eyre::Result<(Mesh<T, D, C>, Vec<(String, DVector<T>)>)>
+where
+    T: Scalar + DeserializeOwned,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+    Mesh<T, D, C>: DeserializeOwned,
+{
+    let file_path = file_path.as_ref();
+    let bytes = read(file_path).wrap_err_with(|| format!("failed to read file {:?}", file_path))?;
+    checkpoint_from_bytes(&bytes).wrap_err_with(|| format!("failed to load checkpoint from file {:?}", file_path))
+}
+
+fn checkpoint_from_bytes<T, D, C>(bytes: &[u8]) -> eyre::Result<(Mesh<T, D, C>, Vec<(String, DVector<T>)>)>
+where
+    T: Scalar + DeserializeOwned,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+    Mesh<T, D, C>: DeserializeOwned,
+{
+    if bytes.len() < HEADER_LEN {
+        bail!("file is too short to contain a checkpoint header");
+    }
+    let (header, body) = bytes.split_at(HEADER_LEN);
+    let (magic, header) = header.split_at(CHECKPOINT_MAGIC.len());
+    if magic != CHECKPOINT_MAGIC {
+        bail!("file is not a checkpoint");
+    }
+    let (version, header) = header.split_at(4);
+    let version = u32::from_le_bytes(version.try_into().unwrap());
+    if version != CHECKPOINT_FORMAT_VERSION {
+        bail!(
+            "unsupported checkpoint format version {}, expected {}",
+            version,
+            CHECKPOINT_FORMAT_VERSION
+        );
+    }
+    let (width, dim) = (header[0], header[1]);
+    let expected_width = scalar_width::<T>()?;
+    if width != expected_width {
+        bail!(
+            "checkpoint was saved with a scalar type of {} bytes, but is loaded with a scalar type of {} bytes",
+            width,
+            expected_width
+        );
+    }
+    if usize::from(dim) != D::dim() {
+        bail!(
+            "checkpoint contains a mesh of dimension {}, but is loaded as dimension {}",
+            dim,
+            D::dim()
+        );
+    }
+
+    let (mesh, state): (Mesh<T, D, C>, Vec<(String, Vec<T>)>) =
+        bincode::deserialize(body).map_err(|err| eyre!("failed to decode checkpoint data: {}", err))?;
+    let state = state
+        .into_iter()
+        .map(|(name, values)| (name, DVector::from_vec(values)))
+        .collect();
+    Ok((mesh, state))
+}
+
+fn scalar_width<T>() -> eyre::Result<u8> {
+    size_of::<T>()
+        .try_into()
+        .map_err(|_| eyre!("scalar type is too large for checkpoints"))
+}
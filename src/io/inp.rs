@@ -0,0 +1,549 @@
+//! Support for loading Abaqus input (`.inp`) files as [`Mesh`]es.
+//!
+//! Only the mesh definition is read: nodes (`*NODE`), elements (`*ELEMENT`), node sets (`*NSET`)
+//! and element sets (`*ELSET`), including sets defined with the `GENERATE` parameter and sets
+//! defined through the `NSET` and `ELSET` parameters of `*NODE` and `*ELEMENT`. All other keywords,
+//! such as materials, steps or boundary conditions, are skipped and reported as warnings.
+//! Models organized into parts and instances are not supported beyond the plain nodes and elements
+//! that they define. Keywords, parameters and element types are case-insensitive.
+//!
+//! All element types that implement [`InpConnectivity`] are supported. Elements whose type
+//! cannot be represented by the requested connectivity are skipped with a warning, and
+//! the node ordering of quadratic elements is converted to the node ordering used in `fenris`.
+//!
+//! ```
+//! use fenris::connectivity::Quad4d2Connectivity;
+//! use fenris::io::inp::load_inp_from_str;
+//! use nalgebra::U2;
+//!
+//! let inp = "
+//! *HEADING
+//! Two quads
+//! *NODE
+//! 1, 0.0, 0.0
+//! 2, 1.0, 0.0
+//! 3, 2.0, 0.0
+//! 4, 0.0, 1.0
+//! 5, 1.0, 1.0
+//! 6, 2.0, 1.0
+//! *ELEMENT, TYPE=CPS4, ELSET=PLATE
+//! 1, 1, 2, 5, 4
+//! 2, 2, 3, 6, 5
+//! *NSET, NSET=LEFT
+//! 1, 4
+//! ";
+//! let inp_mesh = load_inp_from_str::<f64, U2, Quad4d2Connectivity>(inp).unwrap();
+//! assert_eq!(inp_mesh.mesh.connectivity().len(), 2);
+//! assert_eq!(inp_mesh.named_sets.node_set("LEFT"), Some([0, 3].as_slice()));
+//! assert_eq!(inp_mesh.warnings.len(), 1);
+//! ```
+use crate::connectivity::{
+    Hex20Connectivity, Hex8Connectivity, Quad4d2Connectivity, Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity,
+};
+use crate::mesh::{Mesh, NamedSets};
+use crate::Real;
+use eyre::{bail, eyre, Context};
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// A mesh loaded from an Abaqus input file, together with its sets and the warnings
+/// produced while parsing.
+#[derive(Debug, Clone)]
+pub struct InpMesh<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// The mesh, with vertices and elements in the order in which they appear in the file.
+    pub mesh: Mesh<T, D, C>,
+    /// Node sets (`*NSET`) and element sets (`*ELSET`), given as indices into the mesh.
+    pub named_sets: NamedSets,
+    /// Descriptions of skipped keywords and elements, prefixed by their (1-based) line number.
+    pub warnings: Vec<String>,
+}
+
+/// Loads a mesh and its node and element sets from an Abaqus input file at the given path.
+///
+/// See [`load_inp_from_str`] for details.
+pub fn load_inp<T, D, C>(file_path: impl AsRef<Path>) -> eyre::Result<InpMesh<T, D, C>>
+where
+    T: Real,
+    D: DimName,
+    C: InpConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let file_path = file_path.as_ref();
+    let inp = std::fs::read_to_string(file_path).wrap_err_with(|| format!("failed to read file {:?}", file_path))?;
+    load_inp_from_str(&inp).wrap_err_with(|| format!("failed to load mesh from inp file {:?}", file_path))
+}
+
+/// Loads a mesh and its node and element sets by parsing the given string as an Abaqus input file.
+///
+/// Nodes and elements are numbered consecutively in the order in which they appear in the file,
+/// regardless of their ids. Nodes that are only referenced by skipped elements remain in the mesh.
+/// Sets may refer to nodes and elements by id or by the name of a previously defined set of the
+/// same kind, and members that are skipped elements are ignored.
+///
+/// # Errors
+///
+/// Returns an error, reporting the (1-based) line number where applicable, if the file contains
+/// no elements compatible with `C`, if node or element definitions are malformed or refer to
+/// undefined nodes, if a node or element id is defined twice, if a node has nonzero coordinates
+/// beyond the dimension `D`, or if a set refers to undefined nodes, elements or sets.
+pub fn load_inp_from_str<T, D, C>(inp: &str) -> eyre::Result<InpMesh<T, D, C>>
+where
+    T: Real,
+    D: DimName,
+    C: InpConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let mut parser = InpParser::<T, D>::default();
+    let mut section = Section::None;
+    // Tokens of an element definition that continues on the next line
+    let mut pending_element: Vec<&str> = Vec::new();
+    let mut pending_line_number = 0;
+    let mut connectivity = Vec::new();
+
+    for (line_index, line) in inp.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("**") {
+            continue;
+        }
+
+        if let Some(keyword_line) = line.strip_prefix('*') {
+            if !pending_element.is_empty() {
+                bail!("line {}: incomplete element definition", pending_line_number);
+            }
+            let keyword = Keyword::parse(keyword_line);
+            section = parser
+                .begin_section::<C>(&keyword, line_number)
+                .wrap_err_with(|| format!("line {}: invalid *{} keyword", line_number, keyword.name))?;
+            continue;
+        }
+
+        let mut tokens = line
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty());
+        match &mut section {
+            Section::None => bail!("line {}: data line outside of any keyword", line_number),
+            Section::Skipped => {}
+            Section::Node { node_set } => {
+                let tokens: Vec<_> = tokens.collect();
+                parser
+                    .parse_node(&tokens, node_set.as_deref())
+                    .wrap_err_with(|| format!("line {}: invalid node", line_number))?;
+            }
+            Section::Element { element_set } => {
+                if pending_element.is_empty() {
+                    pending_line_number = line_number;
+                }
+                pending_element.extend(tokens);
+                if pending_element.len() >= C::num_inp_nodes() + 1 {
+                    let element = parser
+                        .parse_element::<C>(&pending_element, element_set.as_deref())
+                        .wrap_err_with(|| format!("line {}: invalid element", pending_line_number))?;
+                    connectivity.push(element);
+                    pending_element.clear();
+                }
+            }
+            Section::SkippedElement { continued } => {
+                if !*continued {
+                    // The first entry of an element definition is its id
+                    if let Some(token) = tokens.next() {
+                        let id = parse_id(token).wrap_err_with(|| format!("line {}: invalid element", line_number))?;
+                        parser.skipped_elements.insert(id);
+                    }
+                }
+                *continued = line.ends_with(',');
+            }
+            Section::Set { set, generate } => {
+                parser
+                    .parse_set_members(set, *generate, tokens)
+                    .wrap_err_with(|| format!("line {}: invalid set definition", line_number))?;
+            }
+        }
+    }
+    if !pending_element.is_empty() {
+        bail!("line {}: incomplete element definition", pending_line_number);
+    }
+    if connectivity.is_empty() {
+        bail!(
+            "inp file does not contain elements of the requested types {:?}",
+            C::inp_element_types()
+        );
+    }
+
+    let named_sets = parser.resolve_sets()?;
+    Ok(InpMesh {
+        mesh: Mesh::from_vertices_and_connectivity(parser.vertices, connectivity),
+        named_sets,
+        warnings: parser.warnings,
+    })
+}
+
+/// Allows construction of `fenris` connectivity from Abaqus element definitions.
+pub trait InpConnectivity: Sized {
+    /// The Abaqus element types, in upper case, that can be represented by this connectivity.
+    fn inp_element_types() -> &'static [&'static str];
+
+    /// The number of nodes of an Abaqus element of the supported types.
+    fn num_inp_nodes() -> usize;
+
+    /// Constructs the connectivity from mesh vertex indices given in Abaqus node order.
+    ///
+    /// Panics if the number of indices differs from [`num_inp_nodes`](Self::num_inp_nodes).
+    fn from_inp_nodes(nodes: &[usize]) -> Self;
+}
+
+macro_rules! impl_inp_connectivity {
+    ($connectivity:ident, [$($inp_type:literal),+], num_nodes = $num_nodes:literal) => {
+        impl_inp_connectivity!(
+            $connectivity,
+            [$($inp_type),+],
+            num_nodes = $num_nodes,
+            ordering = core::array::from_fn::<usize, $num_nodes, _>(|i| i)
+        );
+    };
+    ($connectivity:ident, [$($inp_type:literal),+], num_nodes = $num_nodes:literal, ordering = $ordering:expr) => {
+        impl InpConnectivity for $connectivity {
+            fn inp_element_types() -> &'static [&'static str] {
+                &[$($inp_type),+]
+            }
+
+            fn num_inp_nodes() -> usize {
+                $num_nodes
+            }
+
+            fn from_inp_nodes(nodes: &[usize]) -> Self {
+                assert_eq!(
+                    nodes.len(),
+                    $num_nodes,
+                    "number of inp element nodes must match the connectivity"
+                );
+                // Node i in fenris ordering is node ordering[i] in Abaqus ordering
+                let ordering: [usize; $num_nodes] = $ordering;
+                Self(ordering.map(|i| nodes[i]))
+            }
+        }
+    };
+}
+
+impl_inp_connectivity!(Tri3d2Connectivity, ["CPS3", "CPE3"], num_nodes = 3);
+impl_inp_connectivity!(Quad4d2Connectivity, ["CPS4", "CPS4R", "CPE4", "CPE4R"], num_nodes = 4);
+impl_inp_connectivity!(Tet4Connectivity, ["C3D4"], num_nodes = 4);
+// Abaqus places the midpoints of the edges 1-3 and 2-3 in the opposite order of Gmsh
+impl_inp_connectivity!(
+    Tet10Connectivity,
+    ["C3D10"],
+    num_nodes = 10,
+    ordering = [0, 1, 2, 3, 4, 5, 6, 7, 9, 8]
+);
+impl_inp_connectivity!(Hex8Connectivity, ["C3D8", "C3D8R", "C3D8I"], num_nodes = 8);
+// Abaqus orders edge midpoints by the bottom face, top face and vertical edges,
+// whereas Gmsh orders them lexicographically by their vertices
+impl_inp_connectivity!(
+    Hex20Connectivity,
+    ["C3D20", "C3D20R"],
+    num_nodes = 20,
+    ordering = [0, 1, 2, 3, 4, 5, 6, 7, 8, 11, 16, 9, 17, 10, 18, 19, 12, 15, 13, 14]
+);
+
+/// A keyword line such as `*ELEMENT, TYPE=C3D8, ELSET=BODY`, without the leading `*`.
+struct Keyword {
+    /// Upper case name of the keyword.
+    name: String,
+    /// Upper case parameter names with their values, if any.
+    parameters: Vec<(String, Option<String>)>,
+}
+
+impl Keyword {
+    fn parse(line: &str) -> Self {
+        let mut entries = line.split(',').map(str::trim);
+        let name = entries.next().unwrap_or_default().to_ascii_uppercase();
+        let parameters = entries
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) => (key.trim().to_ascii_uppercase(), Some(value.trim().to_string())),
+                None => (entry.to_ascii_uppercase(), None),
+            })
+            .collect();
+        Self { name, parameters }
+    }
+
+    fn value(&self, parameter: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key == parameter)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn has_flag(&self, parameter: &str) -> bool {
+        self.parameters.iter().any(|(key, _)| key == parameter)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetKind {
+    Node,
+    Element,
+}
+
+/// The keyword section that subsequent data lines belong to.
+enum Section {
+    None,
+    Skipped,
+    Node { node_set: Option<String> },
+    Element { element_set: Option<String> },
+    SkippedElement { continued: bool },
+    Set { set: (SetKind, String), generate: bool },
+}
+
+struct InpParser<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    vertices: Vec<OPoint<T, D>>,
+    node_indices: HashMap<u64, usize>,
+    element_indices: HashMap<u64, usize>,
+    skipped_elements: HashSet<u64>,
+    // Sets are stored by ids, since they may refer to nodes and elements that are defined later
+    node_sets: BTreeMap<String, Vec<u64>>,
+    element_sets: BTreeMap<String, Vec<u64>>,
+    warnings: Vec<String>,
+}
+
+impl<T, D> Default for InpParser<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn default() -> Self {
+        Self {
+            vertices: Vec::new(),
+            node_indices: HashMap::new(),
+            element_indices: HashMap::new(),
+            skipped_elements: HashSet::new(),
+            node_sets: BTreeMap::new(),
+            element_sets: BTreeMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl<T, D> InpParser<T, D>
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    fn begin_section<C: InpConnectivity>(&mut self, keyword: &Keyword, line_number: usize) -> eyre::Result<Section> {
+        let section = match keyword.name.as_str() {
+            "NODE" => {
+                let node_set = keyword.value("NSET").map(str::to_string);
+                if let Some(name) = &node_set {
+                    self.node_sets.entry(name.clone()).or_default();
+                }
+                Section::Node { node_set }
+            }
+            "ELEMENT" => {
+                let element_type = keyword
+                    .value("TYPE")
+                    .ok_or_else(|| eyre!("missing TYPE parameter"))?
+                    .to_ascii_uppercase();
+                if C::inp_element_types().contains(&element_type.as_str()) {
+                    let element_set = keyword.value("ELSET").map(str::to_string);
+                    if let Some(name) = &element_set {
+                        self.element_sets.entry(name.clone()).or_default();
+                    }
+                    Section::Element { element_set }
+                } else {
+                    self.warnings.push(format!(
+                        "line {}: skipping elements of type {}, which cannot be represented by the requested \
+                         connectivity",
+                        line_number, element_type
+                    ));
+                    Section::SkippedElement { continued: false }
+                }
+            }
+            "NSET" | "ELSET" => {
+                let kind = if keyword.name == "NSET" {
+                    SetKind::Node
+                } else {
+                    SetKind::Element
+                };
+                let name = keyword
+                    .value(&keyword.name)
+                    .ok_or_else(|| eyre!("missing {} parameter", keyword.name))?
+                    .to_string();
+                self.sets_mut(kind).entry(name.clone()).or_default();
+                Section::Set {
+                    set: (kind, name),
+                    generate: keyword.has_flag("GENERATE"),
+                }
+            }
+            name => {
+                self.warnings
+                    .push(format!("line {}: skipping unsupported keyword *{}", line_number, name));
+                Section::Skipped
+            }
+        };
+        Ok(section)
+    }
+
+    fn sets_mut(&mut self, kind: SetKind) -> &mut BTreeMap<String, Vec<u64>> {
+        match kind {
+            SetKind::Node => &mut self.node_sets,
+            SetKind::Element => &mut self.element_sets,
+        }
+    }
+
+    fn parse_node(&mut self, tokens: &[&str], node_set: Option<&str>) -> eyre::Result<()> {
+        let (id, coords) = tokens
+            .split_first()
+            .ok_or_else(|| eyre!("missing node id"))?;
+        let id = parse_id(id)?;
+        if coords.is_empty() || coords.len() > 3 {
+            bail!("expected between 1 and 3 coordinates, found {}", coords.len());
+        }
+        let mut point = OPoint::<T, D>::origin();
+        for (i, token) in coords.iter().enumerate() {
+            let coord: f64 = token
+                .parse()
+                .map_err(|_| eyre!("failed to parse coordinate \"{}\"", token))?;
+            if i < D::dim() {
+                point[i] = T::from_f64(coord)
+                    .ok_or_else(|| eyre!("failed to convert coordinate {} to the target real type", coord))?;
+            } else if coord != 0.0 {
+                bail!("node {} has nonzero coordinates beyond dimension {}", id, D::dim());
+            }
+        }
+
+        if self.node_indices.insert(id, self.vertices.len()).is_some() {
+            bail!("node {} is defined more than once", id);
+        }
+        self.vertices.push(point);
+        if let Some(name) = node_set {
+            self.node_sets.entry(name.to_string()).or_default().push(id);
+        }
+        Ok(())
+    }
+
+    fn parse_element<C: InpConnectivity>(&mut self, tokens: &[&str], element_set: Option<&str>) -> eyre::Result<C> {
+        if tokens.len() != C::num_inp_nodes() + 1 {
+            bail!(
+                "expected element id and {} nodes, found {} entries",
+                C::num_inp_nodes(),
+                tokens.len()
+            );
+        }
+        let id = parse_id(tokens[0])?;
+        let nodes = tokens[1..]
+            .iter()
+            .map(|token| {
+                let node_id = parse_id(token)?;
+                self.node_indices
+                    .get(&node_id)
+                    .copied()
+                    .ok_or_else(|| eyre!("element {} refers to undefined node {}", id, node_id))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        if self
+            .element_indices
+            .insert(id, self.element_indices.len())
+            .is_some()
+        {
+            bail!("element {} is defined more than once", id);
+        }
+        if let Some(name) = element_set {
+            self.element_sets
+                .entry(name.to_string())
+                .or_default()
+                .push(id);
+        }
+        Ok(C::from_inp_nodes(&nodes))
+    }
+
+    fn parse_set_members<'a>(
+        &mut self,
+        (kind, name): &(SetKind, String),
+        generate: bool,
+        tokens: impl Iterator<Item = &'a str>,
+    ) -> eyre::Result<()> {
+        let tokens: Vec<_> = tokens.collect();
+        let mut ids = Vec::new();
+        if generate {
+            let (start, end, step) = match tokens.as_slice() {
+                [start, end] => (parse_id(start)?, parse_id(end)?, 1),
+                [start, end, step] => (parse_id(start)?, parse_id(end)?, parse_id(step)?),
+                _ => bail!("expected start, end and optional step, found {} entries", tokens.len()),
+            };
+            if step == 0 || end < start {
+                bail!("invalid range {} to {} with step {}", start, end, step);
+            }
+            ids.extend((start..=end).step_by(step as usize));
+        } else {
+            let sets = self.sets_mut(*kind);
+            for token in tokens {
+                match token.parse::<u64>() {
+                    Ok(id) => ids.push(id),
+                    // Abaqus allows sets to be defined in terms of previously defined sets
+                    Err(_) => {
+                        let (_, members) = sets
+                            .iter()
+                            .find(|(set_name, _)| set_name.eq_ignore_ascii_case(token))
+                            .ok_or_else(|| eyre!("\"{}\" is neither an id nor the name of a previous set", token))?;
+                        ids.extend_from_slice(members);
+                    }
+                }
+            }
+        }
+        self.sets_mut(*kind)
+            .get_mut(name)
+            .expect("Set is created when its keyword is parsed")
+            .extend(ids);
+        Ok(())
+    }
+
+    fn resolve_sets(&self) -> eyre::Result<NamedSets> {
+        let mut named_sets = NamedSets::new();
+        for (name, ids) in &self.node_sets {
+            let indices = ids
+                .iter()
+                .map(|id| {
+                    self.node_indices
+                        .get(id)
+                        .copied()
+                        .ok_or_else(|| eyre!("node set {} refers to undefined node {}", name, id))
+                })
+                .collect::<eyre::Result<Vec<_>>>()?;
+            named_sets.insert_node_set(name.clone(), indices);
+        }
+        for (name, ids) in &self.element_sets {
+            let mut indices = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(&index) = self.element_indices.get(id) {
+                    indices.push(index);
+                } else if !self.skipped_elements.contains(id) {
+                    bail!("element set {} refers to undefined element {}", name, id);
+                }
+            }
+            named_sets.insert_element_set(name.clone(), indices);
+        }
+        Ok(named_sets)
+    }
+}
+
+fn parse_id(token: &str) -> eyre::Result<u64> {
+    token
+        .parse()
+        .map_err(|_| eyre!("failed to parse id \"{}\"", token))
+}
@@ -1,2 +1,6 @@
+#[cfg(feature = "checkpoint")]
+pub mod checkpoint;
+pub mod inp;
 pub mod msh;
+pub mod obj;
 pub mod vtk;
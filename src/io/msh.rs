@@ -2,7 +2,8 @@
 //!
 //! This module supports Gmsh MSH files with the file format version 4.1 as specified in the
 //! [Gmsh documentation](https://gmsh.info/doc/texinfo/gmsh.html#MSH-file-format). All element types
-//! that implement the [`MshConnectivity`] trait are supported.
+//! that implement the [`MshConnectivity`] trait are supported. Physical groups can be loaded
+//! alongside the mesh with [`load_msh_with_groups_from_file`].
 //!
 //! Example usage:
 //! ```
@@ -18,17 +19,21 @@
 //! ```
 
 use crate::connectivity::{
-    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Pyramid5Connectivity, Quad4d2Connectivity,
+    Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity, Tri10d2Connectivity, Tri3d2Connectivity,
+    Tri3d3Connectivity, Tri6d2Connectivity,
 };
-use crate::mesh::Mesh;
+use crate::mesh::{Mesh, NamedSets};
 use eyre::{eyre, Context};
 use nalgebra::allocator::Allocator;
 use nalgebra::{DefaultAllocator, DimName, OPoint, RealField};
 use num::ToPrimitive;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 /// Loads a [`Mesh`] from a Gmsh MSH file at the given path.
+///
+/// See [`load_msh_with_groups_from_bytes`] for details.
 pub fn load_msh_from_file<T, D, C, P: AsRef<Path>>(file_path: P) -> eyre::Result<Mesh<T, D, C>>
 where
     T: RealField,
@@ -41,7 +46,46 @@ where
 }
 
 /// Loads a [`Mesh`] by parsing the given bytes as a Gmsh MSH file.
+///
+/// See [`load_msh_with_groups_from_bytes`] for details.
 pub fn load_msh_from_bytes<T, D, C>(bytes: &[u8]) -> eyre::Result<Mesh<T, D, C>>
+where
+    T: RealField,
+    D: DimName,
+    C: MshConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    load_msh_with_groups_from_bytes(bytes).map(|(mesh, _)| mesh)
+}
+
+/// Loads a [`Mesh`] and its physical groups from a Gmsh MSH file at the given path.
+///
+/// See [`load_msh_with_groups_from_bytes`] for details.
+pub fn load_msh_with_groups_from_file<T, D, C, P: AsRef<Path>>(file_path: P) -> eyre::Result<(Mesh<T, D, C>, NamedSets)>
+where
+    T: RealField,
+    D: DimName,
+    C: MshConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let msh_bytes = std::fs::read(file_path).wrap_err("failed to read file")?;
+    load_msh_with_groups_from_bytes(&msh_bytes).wrap_err("failed to load mesh from msh file")
+}
+
+/// Loads a [`Mesh`] and its physical groups by parsing the given bytes as a Gmsh MSH file.
+///
+/// The elements of the mesh are the elements of the MSH file whose entity dimension matches the
+/// reference dimension of the connectivity `C`. Lower-dimensional elements, such as the boundary
+/// curves of a 2D mesh, are not part of the mesh, but are used to determine the nodes of physical groups.
+/// Mixed-element meshes are not supported: if the file contains elements of the same dimension
+/// as `C` but of a different element type, an error listing the offending element types is returned.
+///
+/// Physical groups are returned as [`NamedSets`]. Every physical group gives rise to a node set containing
+/// the vertices of all elements of any dimension that belong to the group. Physical groups with the same
+/// dimension as the mesh additionally give rise to an element set containing the indices of the mesh
+/// elements in the group. Groups are named by their names in the `$PhysicalNames` section of the file,
+/// or by their numeric tags if they are unnamed. Groups that share the same name are merged.
+pub fn load_msh_with_groups_from_bytes<T, D, C>(bytes: &[u8]) -> eyre::Result<(Mesh<T, D, C>, NamedSets)>
 where
     T: RealField,
     D: DimName,
@@ -77,6 +121,26 @@ where
         ));
     }
 
+    // Ensure that no elements of the mesh dimension would be silently dropped
+    let mut mismatched_types = Vec::new();
+    for block in &msh_elements.element_blocks {
+        if block_entity_dim(block) == C::reference_dim()
+            && block.element_type != C::msh_element_type()
+            && !mismatched_types.contains(&block.element_type)
+        {
+            mismatched_types.push(block.element_type);
+        }
+    }
+    if !mismatched_types.is_empty() {
+        return Err(eyre!(
+            "MSH file contains elements of types {:?} with entity dim {} in addition to the requested type {:?} \
+            (mixed-element meshes are not supported)",
+            mismatched_types,
+            C::reference_dim(),
+            C::msh_element_type()
+        ));
+    }
+
     // Collect all mesh vertices
     for node_block in &msh_nodes.node_blocks {
         let block_vertices = vertices_from_node_block(node_block)?;
@@ -96,13 +160,61 @@ where
         ));
     }
 
-    // Collect all connectivity matching the target connectivity
+    let entity_physical_tags = match &msh_file.data.entities {
+        Some(entities) => physical_tags_from_entities(entities)?,
+        None => HashMap::new(),
+    };
+    let physical_names = parse_physical_names(bytes)?;
+    let group_name = |dim: usize, tag: i64| {
+        physical_names
+            .get(&(dim, tag))
+            .cloned()
+            .unwrap_or_else(|| tag.to_string())
+    };
+
+    let mut node_sets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut element_sets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    // Collect all connectivity matching the target connectivity, along with the physical groups
+    // that the elements belong to
     for element_block in &msh_elements.element_blocks {
+        let element_offset = connectivity.len();
         let block_connectivity = connectivity_from_element_block(element_block)?;
         connectivity.extend(block_connectivity);
+
+        let dim = block_entity_dim(element_block);
+        let entity_tag = element_block
+            .entity_tag
+            .to_i64()
+            .ok_or_else(|| eyre!("failed to convert element block entity tag to i64"))?;
+        let physical_tags = entity_physical_tags
+            .get(&(dim, entity_tag))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for &physical_tag in physical_tags {
+            let name = group_name(dim, physical_tag);
+            let node_set = node_sets.entry(name.clone()).or_default();
+            for element in &element_block.elements {
+                node_set.extend(element.nodes.iter().map(|&node| node as usize - 1));
+            }
+            if connectivity.len() > element_offset {
+                element_sets
+                    .entry(name)
+                    .or_default()
+                    .extend(element_offset..connectivity.len());
+            }
+        }
+    }
+
+    let mut named_sets = NamedSets::new();
+    for (name, node_indices) in node_sets {
+        named_sets.insert_node_set(name, node_indices);
+    }
+    for (name, element_indices) in element_sets {
+        named_sets.insert_element_set(name, element_indices);
     }
 
-    Ok(Mesh::from_vertices_and_connectivity(vertices, connectivity))
+    Ok((Mesh::from_vertices_and_connectivity(vertices, connectivity), named_sets))
 }
 
 /// Tries to convert a `mshio::NodeBlock` to a `Vec<OPoint<T, D>>`.
@@ -192,12 +304,88 @@ where
     C: MshConnectivity,
     I: mshio::MshIntT,
 {
-    element_block.element_type == C::msh_element_type()
-        && element_block
-            .entity_dim
-            .to_usize()
-            .expect("failed to convert element block dimension to usize")
-            == C::reference_dim()
+    element_block.element_type == C::msh_element_type() && block_entity_dim(element_block) == C::reference_dim()
+}
+
+fn block_entity_dim<I: mshio::MshIntT>(element_block: &mshio::ElementBlock<u64, I>) -> usize {
+    element_block
+        .entity_dim
+        .to_usize()
+        .expect("failed to convert element block dimension to usize")
+}
+
+/// Collects the physical tags associated with each (dimension, entity tag) pair.
+fn physical_tags_from_entities<I, F>(entities: &mshio::Entities<I, F>) -> eyre::Result<HashMap<(usize, i64), Vec<i64>>>
+where
+    I: mshio::MshIntT,
+    F: mshio::MshFloatT,
+{
+    let to_i64 = |tag: &I| {
+        tag.to_i64()
+            .ok_or_else(|| eyre!("failed to convert entity tag to i64"))
+    };
+    let mut physical_tags = HashMap::new();
+    let mut insert = |dim: usize, tag: &I, tags: &[I]| -> eyre::Result<()> {
+        let tags = tags.iter().map(to_i64).collect::<eyre::Result<_>>()?;
+        physical_tags.insert((dim, to_i64(tag)?), tags);
+        Ok(())
+    };
+    for point in &entities.points {
+        insert(0, &point.tag, &point.physical_tags)?;
+    }
+    for curve in &entities.curves {
+        insert(1, &curve.tag, &curve.physical_tags)?;
+    }
+    for surface in &entities.surfaces {
+        insert(2, &surface.tag, &surface.physical_tags)?;
+    }
+    for volume in &entities.volumes {
+        insert(3, &volume.tag, &volume.physical_tags)?;
+    }
+    Ok(physical_tags)
+}
+
+/// Parses the `$PhysicalNames` section of an MSH file, which is always stored as ASCII text.
+///
+/// Returns an empty map if the file has no such section.
+fn parse_physical_names(bytes: &[u8]) -> eyre::Result<HashMap<(usize, i64), String>> {
+    let mut names = HashMap::new();
+    let begin = b"$PhysicalNames";
+    let end = b"$EndPhysicalNames";
+    let Some(start) = bytes.windows(begin.len()).position(|w| w == begin) else {
+        return Ok(names);
+    };
+    let section = &bytes[start + begin.len()..];
+    let section_len = section
+        .windows(end.len())
+        .position(|w| w == end)
+        .ok_or_else(|| eyre!("unterminated $PhysicalNames section in msh file"))?;
+    let section = std::str::from_utf8(&section[..section_len]).wrap_err("invalid $PhysicalNames section")?;
+
+    // The first line contains the number of names, and the rest are of the form `dim tag "name"`
+    for line in section
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .skip(1)
+    {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let mut next = || {
+            parts
+                .next()
+                .ok_or_else(|| eyre!("invalid line in $PhysicalNames section: {}", line))
+        };
+        let dim = next()?
+            .parse()
+            .wrap_err("invalid physical group dimension")?;
+        let tag = next()?.parse().wrap_err("invalid physical group tag")?;
+        let name = next()?.trim().trim_matches('"').to_string();
+        names.insert((dim, tag), name);
+    }
+    Ok(names)
 }
 
 macro_rules! f_to_t {
@@ -275,12 +463,15 @@ macro_rules! impl_msh_connectivity {
 impl_msh_connectivity!(Tri3d2Connectivity, Tri3, num_nodes = 3);
 impl_msh_connectivity!(Tri3d3Connectivity, Tri3, num_nodes = 3);
 impl_msh_connectivity!(Tri6d2Connectivity, Tri6, num_nodes = 6);
+impl_msh_connectivity!(Tri10d2Connectivity, Tri10, num_nodes = 10);
 impl_msh_connectivity!(Quad4d2Connectivity, Qua4, num_nodes = 4);
 impl_msh_connectivity!(Quad9d2Connectivity, Qua9, num_nodes = 9);
 impl_msh_connectivity!(Tet4Connectivity, Tet4, num_nodes = 4);
 impl_msh_connectivity!(Tet10Connectivity, Tet10, num_nodes = 10);
 impl_msh_connectivity!(Hex8Connectivity, Hex8, num_nodes = 8);
+impl_msh_connectivity!(Hex20Connectivity, Hex20, num_nodes = 20);
 impl_msh_connectivity!(Hex27Connectivity, Hex27, num_nodes = 27);
+impl_msh_connectivity!(Pyramid5Connectivity, Pyr5, num_nodes = 5);
 
 // The following connectivities do not implement ElementConnectivity yet
 //impl_msh_connectivity!(Tri6d3Connectivity, Tri6, num_nodes = 6);
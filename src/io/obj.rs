@@ -0,0 +1,165 @@
+//! Import and export of triangle surface meshes in the Wavefront OBJ format.
+//!
+//! Only the geometry of the mesh is supported: vertex positions (`v`) and faces (`f`).
+//! Polygonal faces are triangulated by a fan around their first vertex, and vertex normals,
+//! texture coordinates, groups, materials and other statements are ignored. Face indices may be
+//! positive (1-based) or negative (relative to the most recently defined vertex).
+//!
+//! ```
+//! use fenris::io::obj::{load_obj_from_str, write_obj};
+//! use fenris::mesh::TriangleMesh3d;
+//!
+//! let obj = "
+//! # A unit square split into two triangles
+//! v 0 0 0
+//! v 1 0 0
+//! v 1 1 0
+//! v 0 1 0
+//! f 1 2 3 4
+//! ";
+//! let mesh: TriangleMesh3d<f64> = load_obj_from_str(obj).unwrap();
+//! assert_eq!(mesh.vertices().len(), 4);
+//! assert_eq!(mesh.connectivity().len(), 2);
+//!
+//! let mut output = Vec::new();
+//! write_obj(&mesh, &mut output).unwrap();
+//! ```
+use crate::connectivity::Tri3d3Connectivity;
+use crate::mesh::TriangleMesh3d;
+use crate::nalgebra::Point3;
+use crate::Real;
+use eyre::{bail, eyre, Context};
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Loads a triangle surface mesh from an OBJ file at the given path.
+///
+/// See the [module-level documentation](self) for the supported subset of the format.
+pub fn load_obj_from_file<T: Real>(file_path: impl AsRef<Path>) -> eyre::Result<TriangleMesh3d<T>> {
+    let file_path = file_path.as_ref();
+    let obj = std::fs::read_to_string(file_path).wrap_err_with(|| format!("failed to read file {:?}", file_path))?;
+    load_obj_from_str(&obj).wrap_err_with(|| format!("failed to load mesh from obj file {:?}", file_path))
+}
+
+/// Loads a triangle surface mesh by parsing the given string as an OBJ file.
+///
+/// See the [module-level documentation](self) for the supported subset of the format.
+/// Errors report the (1-based) line number at which parsing failed.
+pub fn load_obj_from_str<T: Real>(obj: &str) -> eyre::Result<TriangleMesh3d<T>> {
+    let mut vertices = Vec::new();
+    // Faces are stored with their line number, so that indices can be validated after all vertices are read
+    let mut faces = Vec::new();
+
+    for (line_index, line) in obj.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        match keyword {
+            "v" => {
+                let vertex = parse_vertex(tokens).wrap_err_with(|| format!("line {}: invalid vertex", line_number))?;
+                vertices.push(vertex);
+            }
+            "f" => {
+                let face = parse_face(tokens, vertices.len())
+                    .wrap_err_with(|| format!("line {}: invalid face", line_number))?;
+                faces.push((line_number, face));
+            }
+            // Normals, texture coordinates, groups, materials etc. do not affect the mesh geometry
+            _ => {}
+        }
+    }
+
+    let mut connectivity = Vec::new();
+    for (line_number, face) in faces {
+        if let Some(&index) = face.iter().find(|&&index| index >= vertices.len()) {
+            bail!(
+                "line {}: face refers to vertex {}, but there are only {} vertices",
+                line_number,
+                index + 1,
+                vertices.len()
+            );
+        }
+        connectivity.extend((1..face.len() - 1).map(|i| Tri3d3Connectivity([face[0], face[i], face[i + 1]])));
+    }
+
+    Ok(TriangleMesh3d::from_vertices_and_connectivity(vertices, connectivity))
+}
+
+fn parse_vertex<'a, T: Real>(tokens: impl Iterator<Item = &'a str>) -> eyre::Result<Point3<T>> {
+    let coords = tokens
+        .map(|token| {
+            let coord: f64 = token
+                .parse()
+                .map_err(|_| eyre!("failed to parse coordinate \"{}\"", token))?;
+            T::from_f64(coord).ok_or_else(|| eyre!("failed to convert coordinate {} to the target real type", coord))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    // An optional fourth (weight) coordinate may be present
+    match coords.as_slice() {
+        [x, y, z] | [x, y, z, _] => Ok(Point3::new(*x, *y, *z)),
+        _ => bail!("expected 3 or 4 coordinates, found {}", coords.len()),
+    }
+}
+
+/// Parses the zero-based vertex indices of a face, given the number of vertices defined so far.
+fn parse_face<'a>(tokens: impl Iterator<Item = &'a str>, num_vertices: usize) -> eyre::Result<Vec<usize>> {
+    let indices = tokens
+        .map(|token| {
+            // Each vertex is of the form v, v/vt, v//vn or v/vt/vn, and only v is relevant
+            let vertex_token = token.split('/').next().unwrap_or_default();
+            let index: i64 = vertex_token
+                .parse()
+                .map_err(|_| eyre!("failed to parse vertex index \"{}\"", token))?;
+            if index > 0 {
+                Ok(index as usize - 1)
+            } else if index < 0 && index.unsigned_abs() as usize <= num_vertices {
+                Ok(num_vertices - index.unsigned_abs() as usize)
+            } else if index < 0 {
+                bail!(
+                    "relative vertex index {} refers to a vertex before the first, since only {} vertices are defined",
+                    index,
+                    num_vertices
+                )
+            } else {
+                bail!("vertex index must be nonzero")
+            }
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    if indices.len() < 3 {
+        bail!("a face must have at least 3 vertices, found {}", indices.len());
+    }
+    Ok(indices)
+}
+
+/// Writes a triangle surface mesh in the OBJ format.
+///
+/// The output consists of one `v` statement per vertex followed by one `f` statement per triangle.
+pub fn write_obj<T: Real>(mesh: &TriangleMesh3d<T>, mut writer: impl Write) -> eyre::Result<()> {
+    for v in mesh.vertices() {
+        writeln!(writer, "v {} {} {}", v.x, v.y, v.z)?;
+    }
+    for Tri3d3Connectivity([a, b, c]) in mesh.connectivity() {
+        writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+    }
+    Ok(())
+}
+
+/// Saves a triangle surface mesh as an OBJ file at the given path.
+///
+/// Parent directories are created if they do not already exist. See [`write_obj`].
+pub fn save_obj<T: Real>(mesh: &TriangleMesh3d<T>, file_path: impl AsRef<Path>) -> eyre::Result<()> {
+    let file_path = file_path.as_ref();
+    if let Some(parent) = file_path.parent() {
+        create_dir_all(parent)?;
+    }
+    let file = File::create(file_path).wrap_err_with(|| format!("failed to create file {:?}", file_path))?;
+    let mut writer = BufWriter::new(file);
+    write_obj(mesh, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -1,16 +1,33 @@
-use crate::mesh::Mesh;
+//! Export and import of finite element meshes in the VTK format.
+//!
+//! # Named sets
+//!
+//! Named node and element sets (see [`NamedSets`]) are stored as ordinary VTK attributes, so that
+//! they can be inspected and visualized in e.g. ParaView. A node set with name `<name>` is stored
+//! as an integer point data array called `fenris_node_set:<name>`, and an element set is stored as
+//! an integer cell data array called `fenris_element_set:<name>`. The value of the array is `1`
+//! for each node (element) in the set and `0` otherwise. Since legacy VTK files do not support
+//! whitespace in attribute names, set names must not contain whitespace.
+//!
+//! When importing, any attribute whose name starts with one of these prefixes is interpreted as
+//! a named set, where every nonzero entry denotes membership. All other attributes are ignored.
+use crate::assembly::local::MassScalingReport;
+use crate::mesh::{Mesh, NamedSets};
 use crate::Real;
-use nalgebra::{DefaultAllocator, DimName, Scalar};
-use vtkio::model::{Attribute, CellType, Cells, DataSet, UnstructuredGridPiece, VertexNumbers};
+use eyre::{bail, eyre, Context};
+use nalgebra::{DVector, DefaultAllocator, DimName, OPoint, OVector, Scalar};
+use vtkio::model::{Attribute, CellType, Cells, DataSet, IOBuffer, UnstructuredGridPiece, VertexNumbers};
 
 use crate::connectivity::{
-    Connectivity, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
-    Segment2d2Connectivity, Segment2d3Connectivity, Tet10Connectivity, Tet20Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Connectivity, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Prism15Connectivity, Pyramid5Connectivity,
+    Quad4d2Connectivity, Quad9d2Connectivity, Segment2d2Connectivity, Segment2d3Connectivity, Tet10Connectivity,
+    Tet20Connectivity, Tet4Connectivity, Tri10d2Connectivity, Tri3d2Connectivity, Tri3d3Connectivity,
+    Tri6d2Connectivity,
 };
 
 use nalgebra::allocator::Allocator;
 
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
 // TODO: This is kind of a dirty hack to get around the fact that some VTK things are in
@@ -19,8 +36,9 @@ use crate::vtkio::model::{Attributes, ByteOrder, DataArray, Piece, Version, Vtk}
 // TODO: We've currently disabled all vtkio impls, might have to re-enable/re-implement some of them in the future
 //pub use fenris_geometry::vtkio::*;
 use num::{ToPrimitive, Zero};
-use std::fs::create_dir_all;
-use std::path::Path;
+use std::fs::{create_dir_all, read_to_string, rename, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 /// Represents connectivity that is supported by VTK.
 pub trait VtkCellConnectivity: Connectivity {
@@ -63,6 +81,13 @@ impl VtkCellConnectivity for Tri6d2Connectivity {
     }
 }
 
+// The node ordering of the cubic Lagrange triangle in VTK coincides with the Gmsh ordering
+impl VtkCellConnectivity for Tri10d2Connectivity {
+    fn cell_type(&self) -> CellType {
+        CellType::LagrangeTriangle
+    }
+}
+
 impl VtkCellConnectivity for Quad4d2Connectivity {
     fn cell_type(&self) -> CellType {
         CellType::Quad
@@ -152,37 +177,139 @@ impl VtkCellConnectivity for Hex20Connectivity {
     }
 }
 
-impl VtkCellConnectivity for Hex27Connectivity {
-    fn num_nodes(&self) -> usize {
-        20
+impl VtkCellConnectivity for Pyramid5Connectivity {
+    fn cell_type(&self) -> CellType {
+        CellType::Pyramid
     }
+}
 
-    // There is no tri-quadratic Hex in legacy VTK, so use Hex20 instead
+impl VtkCellConnectivity for Prism15Connectivity {
     fn cell_type(&self) -> CellType {
-        CellType::QuadraticHexahedron
+        CellType::QuadraticWedge
+    }
+}
+
+/// The Gmsh node index of each node of a `VTK_TRIQUADRATIC_HEXAHEDRON`, in VTK order.
+///
+/// The corner and edge nodes are ordered as for the quadratic hexahedron. VTK then orders the face
+/// nodes as $x^-, x^+, y^-, y^+, z^-, z^+$, whereas Gmsh uses $z^-, y^-, x^-, x^+, y^+, z^+$.
+const HEX27_VTK_NODE_ORDER: [usize; 27] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 11, 13, 9, 16, 18, 19, 17, 10, 12, 14, 15, 22, 23, 21, 24, 20, 25, 26,
+];
+
+impl VtkCellConnectivity for Hex27Connectivity {
+    fn cell_type(&self) -> CellType {
+        CellType::TriquadraticHexahedron
     }
 
     fn write_vtk_connectivity(&self, connectivity: &mut [usize]) {
         assert_eq!(connectivity.len(), self.num_nodes());
 
         let v = self.vertex_indices();
-        // The first 8 entries are the same
-        connectivity[0..8].clone_from_slice(&v[0..8]);
-        connectivity[8] = v[8];
-        connectivity[9] = v[11];
-        connectivity[10] = v[13];
-        connectivity[11] = v[9];
-        connectivity[12] = v[16];
-        connectivity[13] = v[18];
-        connectivity[14] = v[19];
-        connectivity[15] = v[17];
-        connectivity[16] = v[10];
-        connectivity[17] = v[12];
-        connectivity[18] = v[14];
-        connectivity[19] = v[15];
+        for (vtk_index, &gmsh_index) in connectivity.iter_mut().zip(HEX27_VTK_NODE_ORDER.iter()) {
+            *vtk_index = v[gmsh_index];
+        }
+    }
+}
+
+/// Represents connectivity that can be constructed from VTK cells.
+pub trait FromVtkCellConnectivity: VtkCellConnectivity + Sized {
+    /// Constructs the connectivity from a VTK cell of the given type and its vertex indices,
+    /// given in VTK node order.
+    ///
+    /// Returns `None` if the cell type or the number of vertices is not compatible with
+    /// the connectivity.
+    fn from_vtk_connectivity(cell_type: CellType, connectivity: &[usize]) -> Option<Self>;
+}
+
+macro_rules! impl_from_vtk_cell_connectivity {
+    ($($connectivity:ident => $cell_type:ident),* $(,)?) => {
+        $(
+            impl FromVtkCellConnectivity for $connectivity {
+                fn from_vtk_connectivity(cell_type: CellType, connectivity: &[usize]) -> Option<Self> {
+                    if matches!(cell_type, CellType::$cell_type) {
+                        connectivity.try_into().ok().map(Self)
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_vtk_cell_connectivity!(
+    Segment2d2Connectivity => Line,
+    Segment2d3Connectivity => Line,
+    Tri3d2Connectivity => Triangle,
+    Tri3d3Connectivity => Triangle,
+    Tri6d2Connectivity => QuadraticTriangle,
+    Tri10d2Connectivity => LagrangeTriangle,
+    Quad4d2Connectivity => Quad,
+    Quad9d2Connectivity => QuadraticQuad,
+    Tet4Connectivity => Tetra,
+    Hex8Connectivity => Hexahedron,
+    Pyramid5Connectivity => Pyramid,
+    Prism15Connectivity => QuadraticWedge,
+);
+
+impl FromVtkCellConnectivity for Tet10Connectivity {
+    fn from_vtk_connectivity(cell_type: CellType, connectivity: &[usize]) -> Option<Self> {
+        if !matches!(cell_type, CellType::QuadraticTetra) {
+            return None;
+        }
+        let mut vertex_indices: [usize; 10] = connectivity.try_into().ok()?;
+        // Undo the swap performed on export, see VtkCellConnectivity impl
+        vertex_indices.swap(8, 9);
+        Some(Self(vertex_indices))
+    }
+}
+
+impl FromVtkCellConnectivity for Hex20Connectivity {
+    fn from_vtk_connectivity(cell_type: CellType, connectivity: &[usize]) -> Option<Self> {
+        if !matches!(cell_type, CellType::QuadraticHexahedron) {
+            return None;
+        }
+        let vtk: [usize; 20] = connectivity.try_into().ok()?;
+        // Inverse of the permutation used on export, see VtkCellConnectivity impl
+        let mut v = [0; 20];
+        v[0..8].copy_from_slice(&vtk[0..8]);
+        v[8] = vtk[8];
+        v[11] = vtk[9];
+        v[13] = vtk[10];
+        v[9] = vtk[11];
+        v[16] = vtk[12];
+        v[18] = vtk[13];
+        v[19] = vtk[14];
+        v[17] = vtk[15];
+        v[10] = vtk[16];
+        v[12] = vtk[17];
+        v[14] = vtk[18];
+        v[15] = vtk[19];
+        Some(Self(v))
     }
 }
 
+impl FromVtkCellConnectivity for Hex27Connectivity {
+    fn from_vtk_connectivity(cell_type: CellType, connectivity: &[usize]) -> Option<Self> {
+        if !matches!(cell_type, CellType::TriquadraticHexahedron) {
+            return None;
+        }
+        let vtk: [usize; 27] = connectivity.try_into().ok()?;
+        let mut v = [0; 27];
+        for (&vtk_index, &gmsh_index) in vtk.iter().zip(HEX27_VTK_NODE_ORDER.iter()) {
+            v[gmsh_index] = vtk_index;
+        }
+        Some(Self(v))
+    }
+}
+
+/// Prefix of the names of point data arrays that encode named node sets.
+pub const VTK_NODE_SET_PREFIX: &str = "fenris_node_set:";
+
+/// Prefix of the names of cell data arrays that encode named element sets.
+pub const VTK_ELEMENT_SET_PREFIX: &str = "fenris_element_set:";
+
 // impl<'a, T, D, C> From<&'a Mesh<T, D, C>> for DataSet
 // where
 //     T: Scalar + Zero,
@@ -466,6 +593,28 @@ where
         }
     }
 
+    /// Adds the given named node and element sets as integer point and cell attributes.
+    ///
+    /// See the [module-level documentation](self) for the naming convention.
+    ///
+    /// # Panics
+    /// Panics if a set name is empty or contains whitespace, or if a set contains an index that
+    /// is out of bounds for the mesh.
+    pub fn with_named_sets(self, named_sets: &NamedSets) -> Self {
+        let num_points = self.mesh.vertices().len();
+        let num_cells = self.mesh.connectivity().len();
+        let mut builder = self;
+        for (name, node_indices) in named_sets.node_sets() {
+            let mask = named_set_mask(name, node_indices, num_points);
+            builder = builder.with_point_scalar_attributes(format!("{VTK_NODE_SET_PREFIX}{name}"), 1, &mask);
+        }
+        for (name, element_indices) in named_sets.element_sets() {
+            let mask = named_set_mask(name, element_indices, num_cells);
+            builder = builder.with_cell_scalar_attributes(format!("{VTK_ELEMENT_SET_PREFIX}{name}"), 1, &mask);
+        }
+        builder
+    }
+
     // TODO: Different error type
     pub fn try_build(&self) -> eyre::Result<DataSet>
     where
@@ -486,36 +635,9 @@ where
             points
         };
 
-        // Vertices is laid out as follows: N, i_1, i_2, ... i_N,
-        // so for e.g. quads this becomes 4 followed by the four indices making up the quad
-        let mut vertices = Vec::new();
-        let mut cell_types = Vec::new();
-        let mut vertex_indices = Vec::new();
-        for cell in self.mesh.connectivity() {
-            // TODO: Return better error
-            vertices.push(cell.num_nodes().try_into()?);
-
-            vertex_indices.clear();
-            vertex_indices.resize(cell.num_nodes(), 0);
-            cell.write_vtk_connectivity(&mut vertex_indices);
-
-            for &idx in &vertex_indices {
-                // TODO: Return better error
-                vertices.push(idx.try_into()?);
-            }
-            cell_types.push(cell.cell_type());
-        }
-
         let piece = UnstructuredGridPiece {
             points: points.into(),
-            cells: Cells {
-                // TODO: Use XML instead of Legacy?
-                cell_verts: VertexNumbers::Legacy {
-                    num_cells: self.mesh.connectivity().len() as u32,
-                    vertices,
-                },
-                types: cell_types,
-            },
+            cells: vtk_cells(self.mesh.connectivity())?,
             data: self.attributes.clone(),
         };
 
@@ -530,36 +652,935 @@ where
     where
         C: VtkCellConnectivity,
     {
-        let filepath = filename.as_ref();
-        let fallback_title = filepath
-            .file_stem()
-            .map(|os_str| os_str.to_string_lossy().to_string())
-            .unwrap_or_else(|| "untitled".to_string());
         let dataset = self.try_build()?;
-        if let Some(parent) = filepath.parent() {
-            create_dir_all(parent)?;
-        }
-
-        // Set VTK format version depending on detected file extension
-        // Workaround for vtkio not setting version number automatically depending on format
-        // Issue: https://github.com/elrnv/vtkio/issues/12
-        let extension = filepath
-            .extension()
-            .map(|os_str| os_str.to_string_lossy().to_ascii_lowercase());
-        let version = match extension.as_ref().map(|s| s.as_str()) {
-            Some("vtu") => Version { major: 1, minor: 0 },
-            Some("vtk") | _ => Version { major: 4, minor: 1 },
+        export_dataset(dataset, self.title.clone(), filename.as_ref())
+    }
+}
+
+/// Constructs the VTK cells for the given connectivity.
+fn vtk_cells<C: VtkCellConnectivity>(connectivity: &[C]) -> eyre::Result<Cells> {
+    // Vertices is laid out as follows: N, i_1, i_2, ... i_N,
+    // so for e.g. quads this becomes 4 followed by the four indices making up the quad
+    let mut vertices = Vec::new();
+    let mut cell_types = Vec::new();
+    let mut vertex_indices = Vec::new();
+    for cell in connectivity {
+        // TODO: Return better error
+        vertices.push(cell.num_nodes().try_into()?);
+
+        vertex_indices.clear();
+        vertex_indices.resize(cell.num_nodes(), 0);
+        cell.write_vtk_connectivity(&mut vertex_indices);
+
+        for &idx in &vertex_indices {
+            // TODO: Return better error
+            vertices.push(idx.try_into()?);
+        }
+        cell_types.push(cell.cell_type());
+    }
+
+    Ok(Cells {
+        // TODO: Use XML instead of Legacy?
+        cell_verts: VertexNumbers::Legacy {
+            num_cells: connectivity.len() as u32,
+            vertices,
+        },
+        types: cell_types,
+    })
+}
+
+/// Exports the dataset to the given file, creating parent directories as necessary.
+///
+/// If no title is given, the file stem is used as the title.
+fn export_dataset(dataset: DataSet, title: Option<String>, filepath: &Path) -> eyre::Result<()> {
+    let fallback_title = filepath
+        .file_stem()
+        .map(|os_str| os_str.to_string_lossy().to_string())
+        .unwrap_or_else(|| "untitled".to_string());
+    if let Some(parent) = filepath.parent() {
+        create_dir_all(parent)?;
+    }
+
+    // Set VTK format version depending on detected file extension
+    // Workaround for vtkio not setting version number automatically depending on format
+    // Issue: https://github.com/elrnv/vtkio/issues/12
+    let extension = filepath
+        .extension()
+        .map(|os_str| os_str.to_string_lossy().to_ascii_lowercase());
+    let version = match extension.as_ref().map(|s| s.as_str()) {
+        Some("vtu") => Version { major: 1, minor: 0 },
+        Some("vtk") | _ => Version { major: 4, minor: 1 },
+    };
+
+    Vtk {
+        version,
+        // If we don't have a title then just make the filepath the title
+        title: title.unwrap_or(fallback_title),
+        byte_order: ByteOrder::BigEndian,
+        data: dataset,
+        file_path: None,
+    }
+    .export(filepath)?;
+    Ok(())
+}
+
+/// The floating-point precision of the data written by [`VtkExporter`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum VtkPrecision {
+    /// Single precision.
+    F32,
+    /// Double precision.
+    #[default]
+    F64,
+}
+
+#[derive(Debug, Clone)]
+struct VtkField<'a, T> {
+    name: String,
+    values: &'a [T],
+    num_components: usize,
+    is_vector: bool,
+}
+
+/// Builder-style exporter of a mesh with named point and cell fields to VTK files.
+///
+/// Vector fields are stored with their components interleaved, i.e. the components of the first
+/// node (or cell) are followed by the components of the second node (or cell) and so on, which is
+/// also the layout of nodal solution vectors in `fenris`. Since VTK vectors always have three
+/// components, vectors with fewer components are padded with zeros.
+///
+/// Field lengths are validated against the mesh when the dataset is built or exported.
+///
+/// ```no_run
+/// # use fenris::io::vtk::{VtkExporter, VtkPrecision};
+/// # use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+/// # fn main() -> eyre::Result<()> {
+/// let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+/// let u = vec![0.0; 3 * mesh.vertices().len()];
+/// let p = vec![1.0; mesh.vertices().len()];
+/// let sigma = vec![2.0; mesh.connectivity().len()];
+/// VtkExporter::new(&mesh)
+///     .with_point_vector_field("displacement", &u, 3)
+///     .with_point_scalar_field("pressure", &p)
+///     .with_cell_scalar_field("von_mises", &sigma)
+///     .with_precision(VtkPrecision::F32)
+///     .export("output/solution.vtu")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct VtkExporter<'a, T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    mesh: &'a Mesh<T, D, C>,
+    point_fields: Vec<VtkField<'a, T>>,
+    cell_fields: Vec<VtkField<'a, T>>,
+    precision: VtkPrecision,
+    title: Option<String>,
+}
+
+impl<'a, T, D, C> VtkExporter<'a, T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: VtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Creates an exporter for the given mesh without any fields.
+    pub fn new(mesh: &'a Mesh<T, D, C>) -> Self {
+        Self {
+            mesh,
+            point_fields: Vec::new(),
+            cell_fields: Vec::new(),
+            precision: VtkPrecision::default(),
+            title: None,
+        }
+    }
+
+    /// Sets the title of the exported file. By default, the file stem is used.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the precision of the exported point coordinates and fields. Defaults to double precision.
+    pub fn with_precision(mut self, precision: VtkPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Adds a scalar field with one value per node.
+    pub fn with_point_scalar_field(mut self, name: impl Into<String>, values: &'a [T]) -> Self {
+        self.point_fields
+            .push(VtkField::new(name.into(), values, 1, false));
+        self
+    }
+
+    /// Adds a vector field with `dim` interleaved components per node.
+    pub fn with_point_vector_field(mut self, name: impl Into<String>, values: &'a [T], dim: usize) -> Self {
+        self.point_fields
+            .push(VtkField::new(name.into(), values, dim, true));
+        self
+    }
+
+    /// Adds a scalar field with one value per element.
+    pub fn with_cell_scalar_field(mut self, name: impl Into<String>, values: &'a [T]) -> Self {
+        self.cell_fields
+            .push(VtkField::new(name.into(), values, 1, false));
+        self
+    }
+
+    /// Adds a vector field with `dim` interleaved components per element.
+    pub fn with_cell_vector_field(mut self, name: impl Into<String>, values: &'a [T], dim: usize) -> Self {
+        self.cell_fields
+            .push(VtkField::new(name.into(), values, dim, true));
+        self
+    }
+
+    /// Adds the mass added to each element by mass scaling as the scalar cell field `added_mass`.
+    pub fn with_mass_scaling_report(self, report: &'a MassScalingReport<T>) -> Self {
+        self.with_cell_scalar_field("added_mass", &report.element_added_mass)
+    }
+
+    /// Builds the VTK dataset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field name is empty, contains whitespace or is used by several fields of
+    /// the same kind, if a vector field does not have between one and three components, or if the
+    /// number of values in a field does not match the number of nodes or elements in the mesh
+    /// times the number of components.
+    pub fn build(&self) -> eyre::Result<DataSet> {
+        assert!(D::dim() <= 3, "Unable to support dimensions larger than 3.");
+        let num_points = self.mesh.vertices().len();
+        let num_cells = self.mesh.connectivity().len();
+
+        let mut attributes = Attributes::new();
+        for field in &self.point_fields {
+            field.validate("point", num_points, &self.point_fields)?;
+            attributes.point.push(field.to_attribute(self.precision));
+        }
+        for field in &self.cell_fields {
+            field.validate("cell", num_cells, &self.cell_fields)?;
+            attributes.cell.push(field.to_attribute(self.precision));
+        }
+
+        let coords = self
+            .mesh
+            .vertices()
+            .iter()
+            .flat_map(|v| (0..3).map(move |i| if i < D::dim() { v[i] } else { T::zero() }));
+        let points = convert_to_precision(coords, self.precision);
+
+        let piece = UnstructuredGridPiece {
+            points,
+            cells: vtk_cells(self.mesh.connectivity())?,
+            data: attributes,
         };
 
-        Vtk {
-            version,
-            // If we don't have a title then just make the filepath the title
-            title: self.title.clone().unwrap_or(fallback_title),
-            byte_order: ByteOrder::BigEndian,
-            data: dataset,
-            file_path: None,
+        Ok(DataSet::UnstructuredGrid {
+            meta: None,
+            pieces: vec![Piece::Inline(Box::new(piece))],
+        })
+    }
+
+    /// Exports the mesh and its fields to the given file.
+    ///
+    /// The format is determined by the file extension: `.vtu` files are written in the XML
+    /// format, and all other files in the legacy format. Parent directories are created
+    /// if they do not already exist. See [`build`](Self::build) for possible errors.
+    pub fn export(&self, file_path: impl AsRef<Path>) -> eyre::Result<()> {
+        let dataset = self.build()?;
+        export_dataset(dataset, self.title.clone(), file_path.as_ref())
+    }
+}
+
+impl<'a, T: Real> VtkField<'a, T> {
+    fn new(name: String, values: &'a [T], num_components: usize, is_vector: bool) -> Self {
+        Self {
+            name,
+            values,
+            num_components,
+            is_vector,
+        }
+    }
+
+    fn validate(&self, kind: &str, num_entities: usize, fields: &[Self]) -> eyre::Result<()> {
+        if self.name.is_empty() || self.name.contains(char::is_whitespace) {
+            bail!("{} field name \"{}\" is empty or contains whitespace", kind, self.name);
+        }
+        if fields
+            .iter()
+            .filter(|field| field.name == self.name)
+            .count()
+            > 1
+        {
+            bail!("there are several {} fields with name \"{}\"", kind, self.name);
+        }
+        if self.is_vector && !(1..=3).contains(&self.num_components) {
+            bail!(
+                "{} vector field \"{}\" has {} components, but must have between 1 and 3",
+                kind,
+                self.name,
+                self.num_components
+            );
+        }
+        let expected_len = self.num_components * num_entities;
+        if self.values.len() != expected_len {
+            bail!(
+                "{} field \"{}\" has {} values, expected {} ({} {}s with {} components)",
+                kind,
+                self.name,
+                self.values.len(),
+                expected_len,
+                num_entities,
+                kind,
+                self.num_components
+            );
         }
-        .export(filepath)?;
         Ok(())
     }
+
+    fn to_attribute(&self, precision: VtkPrecision) -> Attribute {
+        let data_array = if self.is_vector {
+            // Vectors are always 3-dimensional in VTK, so we pad with zeros
+            let dim = self.num_components;
+            let values = self
+                .values
+                .chunks_exact(dim)
+                .flat_map(|vector| (0..3).map(move |i| vector.get(i).copied().unwrap_or_else(T::zero)));
+            DataArray::vectors(&self.name).with_data(convert_to_precision(values, precision))
+        } else {
+            DataArray::scalars(&self.name, 1).with_data(convert_to_precision(self.values.iter().copied(), precision))
+        };
+        Attribute::DataArray(data_array)
+    }
+}
+
+fn convert_to_precision<T: Real>(values: impl Iterator<Item = T>, precision: VtkPrecision) -> IOBuffer {
+    let values_f64 = values.map(|x| x.to_subset().expect("Values must be representable as f64"));
+    match precision {
+        VtkPrecision::F32 => values_f64.map(|x| x as f32).collect::<Vec<_>>().into(),
+        VtkPrecision::F64 => values_f64.collect::<Vec<_>>().into(),
+    }
+}
+
+fn named_set_mask(name: &str, indices: &[usize], len: usize) -> Vec<i32> {
+    assert!(
+        !name.is_empty() && !name.contains(char::is_whitespace),
+        "Set name \"{}\" is empty or contains whitespace.",
+        name
+    );
+    let mut mask = vec![0; len];
+    for &index in indices {
+        assert!(index < len, "Index {} in set \"{}\" is out of bounds.", index, name);
+        mask[index] = 1;
+    }
+    mask
+}
+
+/// Loads a mesh and its named node and element sets from a VTK file.
+///
+/// See [`mesh_and_named_sets_from_vtk_dataset`].
+pub fn load_vtk_mesh_and_named_sets_from_file<T, D, C>(
+    file_path: impl AsRef<Path>,
+) -> eyre::Result<(Mesh<T, D, C>, NamedSets)>
+where
+    T: Real,
+    D: DimName,
+    C: FromVtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let vtk = Vtk::import(file_path.as_ref())?;
+    mesh_and_named_sets_from_vtk_dataset(vtk.data)
+}
+
+/// Constructs a mesh and its named node and element sets from a VTK dataset.
+///
+/// Only unstructured grids consisting of a single inline piece are supported. All cells must be
+/// compatible with the connectivity type `C`, and point coordinates beyond the dimension `D` must
+/// be zero. Named sets are reconstructed from attributes following the convention described in
+/// the [module-level documentation](self). If there are no such attributes, the returned
+/// named sets are empty.
+pub fn mesh_and_named_sets_from_vtk_dataset<T, D, C>(dataset: DataSet) -> eyre::Result<(Mesh<T, D, C>, NamedSets)>
+where
+    T: Real,
+    D: DimName,
+    C: FromVtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let (mesh, data) = mesh_and_attributes_from_vtk_dataset(dataset)?;
+
+    let mut named_sets = NamedSets::new();
+    for attribute in &data.point {
+        if let Some((name, indices)) = named_set_from_attribute(attribute, VTK_NODE_SET_PREFIX, mesh.vertices().len())?
+        {
+            named_sets.insert_node_set(name, indices);
+        }
+    }
+    for attribute in &data.cell {
+        if let Some((name, indices)) =
+            named_set_from_attribute(attribute, VTK_ELEMENT_SET_PREFIX, mesh.connectivity().len())?
+        {
+            named_sets.insert_element_set(name, indices);
+        }
+    }
+
+    Ok((mesh, named_sets))
+}
+
+fn mesh_and_attributes_from_vtk_dataset<T, D, C>(dataset: DataSet) -> eyre::Result<(Mesh<T, D, C>, Attributes)>
+where
+    T: Real,
+    D: DimName,
+    C: FromVtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    assert!(D::dim() <= 3, "Unable to support dimensions larger than 3.");
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { pieces, .. } => {
+            let mut pieces = pieces.into_iter();
+            match (pieces.next(), pieces.next()) {
+                (Some(Piece::Inline(piece)), None) => *piece,
+                (Some(_), None) => bail!("only inline unstructured grid pieces are supported"),
+                _ => bail!("expected exactly one unstructured grid piece"),
+            }
+        }
+        _ => bail!("only unstructured grid datasets are supported"),
+    };
+    let UnstructuredGridPiece { points, cells, data } = piece;
+
+    let coords = points
+        .cast_into::<f64>()
+        .ok_or_else(|| eyre!("failed to convert point coordinates to f64"))?;
+    if coords.len() % 3 != 0 {
+        bail!("number of point coordinates ({}) is not divisible by 3", coords.len());
+    }
+    let vertices = coords
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, xyz)| {
+            let (x, dropped) = xyz.split_at(D::dim());
+            if dropped.iter().any(|&x_k| x_k != 0.0) {
+                bail!("point {} has nonzero coordinates beyond dimension {}", i, D::dim());
+            }
+            let x = OVector::<T, D>::from_iterator(x.iter().map(|&x_k| T::from_f64(x_k).unwrap()));
+            Ok(OPoint::from(x))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let cell_vertices: Vec<Vec<usize>> = match cells.cell_verts {
+        VertexNumbers::Legacy { num_cells, vertices } => {
+            let mut cell_vertices = Vec::with_capacity(num_cells as usize);
+            let mut remaining = vertices.as_slice();
+            while let Some((&n, rest)) = remaining.split_first() {
+                let n = n as usize;
+                if rest.len() < n {
+                    bail!("invalid legacy cell vertex data");
+                }
+                cell_vertices.push(rest[..n].iter().map(|&v| v as usize).collect());
+                remaining = &rest[n..];
+            }
+            cell_vertices
+        }
+        VertexNumbers::XML { connectivity, offsets } => {
+            let mut begin = 0;
+            let mut cell_vertices = Vec::with_capacity(offsets.len());
+            for &end in &offsets {
+                let end = end as usize;
+                if end < begin || end > connectivity.len() {
+                    bail!("invalid cell offsets");
+                }
+                cell_vertices.push(
+                    connectivity[begin..end]
+                        .iter()
+                        .map(|&v| v as usize)
+                        .collect(),
+                );
+                begin = end;
+            }
+            cell_vertices
+        }
+    };
+    if cell_vertices.len() != cells.types.len() {
+        bail!(
+            "number of cells ({}) does not match number of cell types ({})",
+            cell_vertices.len(),
+            cells.types.len()
+        );
+    }
+    if let Some(&first_type) = cells.types.first() {
+        if let Some((i, cell_type)) = cells
+            .types
+            .iter()
+            .enumerate()
+            .find(|(_, &cell_type)| cell_type != first_type)
+        {
+            bail!(
+                "meshes with mixed cell types are not supported: cell 0 has type {:?}, but cell {} has type {:?}",
+                first_type,
+                i,
+                cell_type
+            );
+        }
+    }
+    let connectivity = cell_vertices
+        .iter()
+        .zip(&cells.types)
+        .enumerate()
+        .map(|(i, (vertex_indices, &cell_type))| {
+            if let Some(&v) = vertex_indices.iter().find(|&&v| v >= vertices.len()) {
+                bail!("cell {} references vertex {}, which does not exist", i, v);
+            }
+            C::from_vtk_connectivity(cell_type, vertex_indices).ok_or_else(|| {
+                eyre!(
+                    "cell {} of type {:?} is not compatible with the connectivity",
+                    i,
+                    cell_type
+                )
+            })
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    Ok((Mesh::from_vertices_and_connectivity(vertices, connectivity), data))
+}
+
+/// A named data array with a fixed number of components per point or cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldArray<T: Scalar> {
+    /// The number of components per point or cell.
+    pub num_components: usize,
+    /// The values, with the components of each point or cell stored contiguously.
+    pub values: DVector<T>,
+}
+
+/// Named point and cell data arrays loaded from a VTK file, see [`load_vtu`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldData<T: Scalar> {
+    point_fields: BTreeMap<String, FieldArray<T>>,
+    cell_fields: BTreeMap<String, FieldArray<T>>,
+}
+
+impl<T: Scalar> Default for FieldData<T> {
+    fn default() -> Self {
+        Self {
+            point_fields: BTreeMap::new(),
+            cell_fields: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Scalar> FieldData<T> {
+    /// Creates an empty collection of fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the point field with the given name, if it exists.
+    pub fn point_field(&self, name: &str) -> Option<&FieldArray<T>> {
+        self.point_fields.get(name)
+    }
+
+    /// Returns the cell field with the given name, if it exists.
+    pub fn cell_field(&self, name: &str) -> Option<&FieldArray<T>> {
+        self.cell_fields.get(name)
+    }
+
+    /// Iterates over all point fields in lexicographical order by name.
+    pub fn point_fields(&self) -> impl '_ + Iterator<Item = (&str, &FieldArray<T>)> {
+        self.point_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+    }
+
+    /// Iterates over all cell fields in lexicographical order by name.
+    pub fn cell_fields(&self) -> impl '_ + Iterator<Item = (&str, &FieldArray<T>)> {
+        self.cell_fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+    }
+
+    /// Returns `true` if there are no point or cell fields.
+    pub fn is_empty(&self) -> bool {
+        self.point_fields.is_empty() && self.cell_fields.is_empty()
+    }
+}
+
+/// Loads a mesh and its point and cell data from a VTK file, typically a `.vtu` file written by
+/// [`VtkExporter`] or [`FiniteElementMeshDataSetBuilder`].
+///
+/// All cells must have the same type, and the type must be compatible with the connectivity `C`.
+/// Node orderings that differ between VTK and `fenris`, such as for quadratic tetrahedra and
+/// hexahedra, are converted to the `fenris` ordering. See
+/// [`mesh_and_named_sets_from_vtk_dataset`] for further requirements on the dataset.
+///
+/// Every data array is returned as a field with the number of components stored in the file.
+/// In particular, vectors always have three components, since vectors in VTK files are padded
+/// with zeros. Arrays that encode named sets (see the [module-level documentation](self)) are
+/// not included, use [`load_vtk_mesh_and_named_sets_from_file`] to load them.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, if the mesh cannot be represented with
+/// the connectivity `C`, or if the length of a data array does not match the number of points
+/// or cells times its number of components.
+pub fn load_vtu<T, D, C>(file_path: impl AsRef<Path>) -> eyre::Result<(Mesh<T, D, C>, FieldData<T>)>
+where
+    T: Real,
+    D: DimName,
+    C: FromVtkCellConnectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let file_path = file_path.as_ref();
+    let vtk = Vtk::import(file_path).wrap_err_with(|| format!("failed to import VTK file {:?}", file_path))?;
+    let (mesh, data) = mesh_and_attributes_from_vtk_dataset(vtk.data)
+        .wrap_err_with(|| format!("failed to load mesh from VTK file {:?}", file_path))?;
+
+    let mut field_data = FieldData::new();
+    for attribute in &data.point {
+        if let Some((name, field)) = field_from_attribute(attribute, VTK_NODE_SET_PREFIX, mesh.vertices().len())? {
+            field_data.point_fields.insert(name, field);
+        }
+    }
+    for attribute in &data.cell {
+        if let Some((name, field)) = field_from_attribute(attribute, VTK_ELEMENT_SET_PREFIX, mesh.connectivity().len())?
+        {
+            field_data.cell_fields.insert(name, field);
+        }
+    }
+    Ok((mesh, field_data))
+}
+
+fn field_from_attribute<T: Real>(
+    attribute: &Attribute,
+    named_set_prefix: &str,
+    len: usize,
+) -> eyre::Result<Option<(String, FieldArray<T>)>> {
+    let array = match attribute {
+        Attribute::DataArray(array) if !array.name.starts_with(named_set_prefix) => array,
+        _ => return Ok(None),
+    };
+    let num_components = array.elem.num_comp() as usize;
+    let values = array
+        .data
+        .clone()
+        .cast_into::<f64>()
+        .ok_or_else(|| eyre!("failed to read values of attribute {}", array.name))?;
+    if values.len() != num_components * len {
+        bail!(
+            "attribute {} has {} values, expected {} ({} entries with {} components)",
+            array.name,
+            values.len(),
+            num_components * len,
+            len,
+            num_components
+        );
+    }
+    let values = DVector::from_iterator(values.len(), values.into_iter().map(|x| T::from_f64(x).unwrap()));
+    let field = FieldArray { num_components, values };
+    Ok(Some((array.name.clone(), field)))
+}
+
+fn named_set_from_attribute<'a>(
+    attribute: &'a Attribute,
+    prefix: &str,
+    len: usize,
+) -> eyre::Result<Option<(&'a str, Vec<usize>)>> {
+    let array = match attribute {
+        Attribute::DataArray(array) => array,
+        _ => return Ok(None),
+    };
+    let name = match array.name.strip_prefix(prefix) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let values = array
+        .data
+        .clone()
+        .cast_into::<f64>()
+        .ok_or_else(|| eyre!("failed to read values of named set attribute {}", array.name))?;
+    if values.len() != len {
+        bail!(
+            "named set attribute {} has {} entries, expected {}",
+            array.name,
+            values.len(),
+            len
+        );
+    }
+    let indices = values
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value != 0.0)
+        .map(|(i, _)| i)
+        .collect();
+    Ok(Some((name, indices)))
+}
+
+/// Writes the time steps of a transient simulation as a series of VTU files, together with a
+/// ParaView collection (`.pvd`) file that references each file with its physical time.
+///
+/// Time step `i` is written to `<base_name>_<i>.vtu`, with `i` padded to six digits, and the
+/// collection is written to `<base_name>.pvd`, all in the given directory. The collection is
+/// rewritten after every time step by writing to a temporary file which then replaces the
+/// previous collection, so that the collection always refers to completely written time steps
+/// and remains loadable if the simulation is interrupted.
+///
+/// ```no_run
+/// # use fenris::io::vtk::PvdTimeSeriesWriter;
+/// # use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+/// # fn main() -> eyre::Result<()> {
+/// let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(4);
+/// let mut writer = PvdTimeSeriesWriter::new("output", "heat")?;
+/// for step in 0..10 {
+///     let temperature = vec![step as f64; mesh.vertices().len()];
+///     writer.write_timestep(0.1 * step as f64, &mesh, &[("temperature", 1, temperature.as_slice())], &[])?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PvdTimeSeriesWriter {
+    directory: PathBuf,
+    base_name: String,
+    // Pairs of (time, file name relative to the directory)
+    timesteps: Vec<(f64, String)>,
+}
+
+impl PvdTimeSeriesWriter {
+    /// Creates a writer for a new time series.
+    ///
+    /// The directory is created if it does not already exist. An existing collection with the
+    /// same base name is replaced once the first time step is written. Use [`resume`](Self::resume)
+    /// to append to an existing series instead.
+    pub fn new(directory: impl AsRef<Path>, base_name: impl Into<String>) -> eyre::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            base_name: base_name.into(),
+            timesteps: Vec::new(),
+        })
+    }
+
+    /// Creates a writer that appends to an existing time series, for example when restarting
+    /// a simulation.
+    ///
+    /// The time steps referenced by the existing collection are kept, and new time steps are
+    /// numbered after them. If there is no existing collection, a new series is started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing collection cannot be read or parsed.
+    pub fn resume(directory: impl AsRef<Path>, base_name: impl Into<String>) -> eyre::Result<Self> {
+        let mut writer = Self::new(directory, base_name)?;
+        let pvd_path = writer.pvd_path();
+        if pvd_path.exists() {
+            let pvd = read_to_string(&pvd_path).wrap_err_with(|| format!("failed to read file {:?}", pvd_path))?;
+            writer.timesteps =
+                parse_pvd_timesteps(&pvd).wrap_err_with(|| format!("failed to parse collection {:?}", pvd_path))?;
+        }
+        Ok(writer)
+    }
+
+    /// The number of time steps in the series.
+    pub fn num_timesteps(&self) -> usize {
+        self.timesteps.len()
+    }
+
+    /// The path of the collection (`.pvd`) file.
+    pub fn pvd_path(&self) -> PathBuf {
+        self.directory.join(format!("{}.pvd", self.base_name))
+    }
+
+    /// Writes the mesh and its attributes for the given time as the next time step in the series,
+    /// and updates the collection.
+    ///
+    /// Point and cell data are given as `(name, num_components, values)`, see
+    /// [`FiniteElementMeshDataSetBuilder::with_point_scalar_attributes`] and
+    /// [`FiniteElementMeshDataSetBuilder::with_cell_scalar_attributes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time is not finite or not larger than the time of the previous
+    /// time step, or if writing any of the files fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values in an attribute is incompatible with the mesh.
+    pub fn write_timestep<T, D, C>(
+        &mut self,
+        time: f64,
+        mesh: &Mesh<T, D, C>,
+        point_data: &[(&str, usize, &[T])],
+        cell_data: &[(&str, usize, &[T])],
+    ) -> eyre::Result<()>
+    where
+        T: Real + ToPrimitive,
+        D: DimName,
+        C: VtkCellConnectivity,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        if !time.is_finite() {
+            bail!("time {} of time step is not finite", time);
+        }
+        if let Some(&(previous_time, _)) = self.timesteps.last() {
+            if time <= previous_time {
+                bail!(
+                    "time {} of time step must be larger than the time {} of the previous time step",
+                    time,
+                    previous_time
+                );
+            }
+        }
+
+        let file_name = format!("{}_{:06}.vtu", self.base_name, self.timesteps.len());
+        let mut builder = FiniteElementMeshDataSetBuilder::from_mesh(mesh).with_title(&self.base_name);
+        for &(name, num_components, values) in point_data {
+            builder = builder.with_point_scalar_attributes(name, num_components, values);
+        }
+        for &(name, num_components, values) in cell_data {
+            builder = builder.with_cell_scalar_attributes(name, num_components, values);
+        }
+        builder.try_export(self.directory.join(&file_name))?;
+
+        self.timesteps.push((time, file_name));
+        if let Err(err) = self.write_pvd() {
+            // The time step is not part of the series unless it is referenced by the collection
+            self.timesteps.pop();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn write_pvd(&self) -> eyre::Result<()> {
+        let pvd_path = self.pvd_path();
+        let tmp_path = self.directory.join(format!("{}.pvd.tmp", self.base_name));
+        {
+            let file = File::create(&tmp_path).wrap_err_with(|| format!("failed to create file {:?}", tmp_path))?;
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, r#"<?xml version="1.0"?>"#)?;
+            writeln!(
+                writer,
+                r#"<VTKFile type="Collection" version="0.1" byte_order="LittleEndian">"#
+            )?;
+            writeln!(writer, "  <Collection>")?;
+            for (time, file_name) in &self.timesteps {
+                // Rust's formatting of f64 is the shortest representation that round-trips exactly
+                writeln!(
+                    writer,
+                    r#"    <DataSet timestep="{}" group="" part="0" file="{}"/>"#,
+                    time,
+                    xml_escape(file_name)
+                )?;
+            }
+            writeln!(writer, "  </Collection>")?;
+            writeln!(writer, "</VTKFile>")?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        rename(&tmp_path, &pvd_path).wrap_err_with(|| format!("failed to replace collection {:?}", pvd_path))?;
+        Ok(())
+    }
+}
+
+/// Parses the `(timestep, file)` attributes of the `DataSet` entries of a ParaView collection.
+fn parse_pvd_timesteps(pvd: &str) -> eyre::Result<Vec<(f64, String)>> {
+    pvd.split("<DataSet")
+        .skip(1)
+        .map(|entry| {
+            let attributes = parse_xml_attributes(entry)?;
+            let attribute = |name: &str| {
+                attributes
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.as_str())
+            };
+            let time_str = attribute("timestep").ok_or_else(|| eyre!("DataSet entry without timestep"))?;
+            let time = time_str
+                .parse()
+                .map_err(|_| eyre!("failed to parse timestep \"{}\"", time_str))?;
+            let file = attribute("file").ok_or_else(|| eyre!("DataSet entry without file"))?;
+            Ok((time, file.to_string()))
+        })
+        .collect()
+}
+
+/// Parses the `name="value"` attributes of an XML tag, starting right after the tag name and
+/// ending at the closing `>` or `/>`. Attribute values are unescaped.
+fn parse_xml_attributes(tag: &str) -> eyre::Result<Vec<(&str, String)>> {
+    let mut attributes = Vec::new();
+    let mut remaining = tag.trim_start();
+    while !(remaining.starts_with('>') || remaining.starts_with("/>")) {
+        if remaining.is_empty() {
+            bail!("unterminated XML tag");
+        }
+        let (name, rest) = remaining
+            .split_once('=')
+            .ok_or_else(|| eyre!("XML attribute without value"))?;
+        let name = name.trim_end();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("invalid XML attribute name \"{}\"", name);
+        }
+        let rest = rest.trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| eyre!("value of XML attribute \"{}\" is not quoted", name))?;
+        let (value, rest) = rest[1..]
+            .split_once(quote)
+            .ok_or_else(|| eyre!("unterminated value of XML attribute \"{}\"", name))?;
+        attributes.push((name, xml_unescape(value)?));
+        remaining = rest.trim_start();
+    }
+    Ok(attributes)
+}
+
+/// Escapes a string for use as a (double-quoted) XML attribute value.
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Replaces the predefined and numeric character references in an XML attribute value.
+fn xml_unescape(value: &str) -> eyre::Result<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut remaining = value;
+    while let Some(start) = remaining.find('&') {
+        unescaped.push_str(&remaining[..start]);
+        let (reference, rest) = remaining[start + 1..]
+            .split_once(';')
+            .ok_or_else(|| eyre!("unterminated character reference in \"{}\"", value))?;
+        let c = match reference {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32)
+                .ok_or_else(|| eyre!("invalid character reference \"&{};\"", reference))?,
+        };
+        unescaped.push(c);
+        remaining = rest;
+    }
+    unescaped.push_str(remaining);
+    Ok(unescaped)
 }
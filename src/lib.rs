@@ -42,7 +42,8 @@ pub trait SmallDim: DimName + DimMin<Self, Output = Self> {}
 
 impl<D> SmallDim for D where D: DimName + DimMin<Self, Output = Self> {}
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// Describes whether a matrix (or an operator producing matrices) is symmetric.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Symmetry {
     NonSymmetric,
     Symmetric,
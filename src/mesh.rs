@@ -1,7 +1,8 @@
 use crate::connectivity::{
     CellConnectivity, Connectivity, ConnectivityMut, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity,
-    Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet20Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Prism15Connectivity, Prism6Connectivity, Pyramid5Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet20Connectivity, Tet4Connectivity, Tri10d2Connectivity, Tri3d2Connectivity,
+    Tri3d3Connectivity, Tri6d2Connectivity,
 };
 use crate::geometry::{AxisAlignedBoundingBox, BoundedGeometry, GeometryCollection};
 use crate::Real;
@@ -12,9 +13,24 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::iter::once;
 
+pub mod distance;
 pub mod procedural;
+pub mod quality;
 pub mod refinement;
 pub mod reorder;
+pub mod topology;
+
+mod boundary_patches;
+mod extrude;
+mod named_sets;
+mod validation;
+pub use crate::mesh_convert::{p_coarsen_mesh, p_refine_mesh, NodeOrigin, RefineFrom};
+pub use boundary_patches::BoundaryPatches;
+pub use extrude::{extrude, ExtrudeConnectivity};
+pub use named_sets::NamedSets;
+pub use validation::{
+    default_degeneracy_tolerance, fix_orientation, validate, MeshValidationReport, ReverseOrientation, ValidateElement,
+};
 
 /// Index-based data structure for conforming meshes (i.e. no hanging nodes).
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -45,6 +61,7 @@ pub type Mesh3d<T, Connectivity> = Mesh<T, U3, Connectivity>;
 
 pub type TriangleMesh2d<T> = Mesh2d<T, Tri3d2Connectivity>;
 pub type Tri6Mesh2d<T> = Mesh2d<T, Tri6d2Connectivity>;
+pub type Tri10Mesh2d<T> = Mesh2d<T, Tri10d2Connectivity>;
 pub type QuadMesh2d<T> = Mesh2d<T, Quad4d2Connectivity>;
 pub type Quad9Mesh2d<T> = Mesh2d<T, Quad9d2Connectivity>;
 pub type TriangleMesh3d<T> = Mesh3d<T, Tri3d3Connectivity>;
@@ -53,6 +70,9 @@ pub type HexMesh<T> = Mesh3d<T, Hex8Connectivity>;
 pub type Hex20Mesh<T> = Mesh3d<T, Hex20Connectivity>;
 pub type Hex27Mesh<T> = Mesh3d<T, Hex27Connectivity>;
 pub type Tet4Mesh<T> = Mesh3d<T, Tet4Connectivity>;
+pub type Prism6Mesh<T> = Mesh3d<T, Prism6Connectivity>;
+pub type Prism15Mesh<T> = Mesh3d<T, Prism15Connectivity>;
+pub type Pyramid5Mesh<T> = Mesh3d<T, Pyramid5Connectivity>;
 pub type Tet10Mesh<T> = Mesh3d<T, Tet10Connectivity>;
 pub type Tet20Mesh<T> = Mesh3d<T, Tet20Connectivity>;
 
@@ -354,6 +374,109 @@ where
     }
 }
 
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Real,
+    D: DimName,
+    C: ConnectivityMut,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Merges several meshes into a single mesh, welding vertices that are closer than the
+    /// given tolerance.
+    ///
+    /// The vertices and connectivities of the meshes are concatenated in order. A vertex within
+    /// distance `tolerance` of a previously added vertex is replaced by the previous vertex.
+    /// This also merges nearly coincident vertices within a single mesh. Vertices are located
+    /// with a spatial hash, so the cost is roughly linear in the total number of vertices.
+    /// A tolerance of zero means that no vertices are merged.
+    ///
+    /// Returns the merged mesh together with, for each input mesh, a map from its vertex indices
+    /// to vertex indices in the merged mesh.
+    ///
+    /// The tolerance should be small compared to the size of the elements, otherwise distinct vertices of
+    /// the same element may be merged, producing degenerate elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tolerance is negative.
+    pub fn merge(meshes: &[Self], tolerance: T) -> (Self, Vec<Vec<usize>>) {
+        assert!(tolerance >= T::zero(), "Tolerance must be non-negative");
+        let merge_vertices = tolerance > T::zero();
+
+        // Merged vertices are bucketed into a grid of cells with size equal to the tolerance,
+        // so that any vertex within the tolerance of a given vertex is found in the neighboring cells
+        let grid_cell = |p: &OPoint<T, D>| -> Vec<i64> {
+            p.coords
+                .iter()
+                .map(|&x_i| {
+                    let cell: f64 = (x_i / tolerance).floor().to_subset().unwrap();
+                    cell as i64
+                })
+                .collect()
+        };
+        let neighbor_offsets: Vec<Vec<i64>> = (0..D::dim()).fold(vec![Vec::new()], |offsets, _| {
+            offsets
+                .iter()
+                .flat_map(|offset| {
+                    [-1, 0, 1].map(|delta| {
+                        let mut offset = offset.clone();
+                        offset.push(delta);
+                        offset
+                    })
+                })
+                .collect()
+        });
+
+        let mut grid: HashMap<Vec<i64>, Vec<usize>> = HashMap::new();
+        let mut vertices: Vec<OPoint<T, D>> = Vec::new();
+        let mut connectivity = Vec::new();
+        let mut vertex_maps = Vec::with_capacity(meshes.len());
+        for mesh in meshes {
+            let mut vertex_map = Vec::with_capacity(mesh.vertices().len());
+            for vertex in mesh.vertices() {
+                let existing_index = if merge_vertices {
+                    let cell = grid_cell(vertex);
+                    let existing_index = neighbor_offsets
+                        .iter()
+                        .filter_map(|offset| {
+                            let neighbor: Vec<i64> = cell.iter().zip(offset).map(|(c, o)| c + o).collect();
+                            grid.get(&neighbor)
+                        })
+                        .flatten()
+                        .copied()
+                        .find(|&index| (&vertices[index] - vertex).norm() < tolerance);
+                    if existing_index.is_none() {
+                        grid.entry(cell).or_default().push(vertices.len());
+                    }
+                    existing_index
+                } else {
+                    None
+                };
+
+                let index = existing_index.unwrap_or_else(|| {
+                    vertices.push(vertex.clone());
+                    vertices.len() - 1
+                });
+                vertex_map.push(index);
+            }
+
+            connectivity.extend(mesh.connectivity().iter().map(|cell| {
+                let mut cell = cell.clone();
+                for index in cell.vertex_indices_mut() {
+                    *index = vertex_map[*index];
+                }
+                cell
+            }));
+            vertex_maps.push(vertex_map);
+        }
+
+        (
+            Self::from_vertices_and_connectivity(vertices, connectivity),
+            vertex_maps,
+        )
+    }
+}
+
 // impl<T, Cell> Mesh2d<T, Cell>
 // where
 //     T: Real,
@@ -514,6 +637,21 @@ where
         let cells_to_keep: Vec<_> = (0..new_mesh.connectivity().len()).collect();
         new_mesh.keep_cells(&cells_to_keep)
     }
+
+    /// Constructs a mesh consisting of the boundary faces of the mesh, sharing the vertices of this mesh.
+    ///
+    /// Unlike [`extract_surface_mesh`](Self::extract_surface_mesh), the vertices are not relabeled,
+    /// so that the node indices of the face mesh coincide with the node indices of this mesh.
+    /// This makes the face mesh suitable for assembling boundary terms directly into the degrees of
+    /// freedom associated with this mesh. The orientation of the faces are preserved.
+    pub fn extract_boundary_face_mesh(&self) -> Mesh<T, D, C::FaceConnectivity> {
+        let connectivity = self
+            .find_boundary_faces()
+            .into_iter()
+            .map(|(face, _, _)| face)
+            .collect();
+        Mesh::from_vertices_and_connectivity(self.vertices.clone(), connectivity)
+    }
 }
 
 impl<'a, T, D, C> GeometryCollection<'a> for Mesh<T, D, C>
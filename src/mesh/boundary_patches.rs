@@ -0,0 +1,161 @@
+use crate::connectivity::{Connectivity, ConnectivityMut};
+use crate::mesh::Mesh;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
+use std::ops::Range;
+
+/// A collection of named patches on the boundary of a volume mesh that share a single boundary
+/// node numbering.
+///
+/// The faces of all patches are stored in a single surface mesh, in which the faces of each patch
+/// occupy a contiguous range of elements. The vertices of the surface mesh are the volume mesh
+/// vertices that belong to at least one patch, and nodes that are shared between patches
+/// (for example along edges or at corners) appear only once. The map from boundary nodes to volume
+/// nodes makes it possible to assemble terms associated with different patches into the same
+/// volume system.
+///
+/// Boundary patches are usually obtained with [`Mesh::extract_boundary_patches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundaryPatches<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    DefaultAllocator: Allocator<T, D>,
+{
+    surface_mesh: Mesh<T, D, C>,
+    patch_names: Vec<String>,
+    element_ranges: Vec<Range<usize>>,
+    boundary_to_volume: Vec<usize>,
+    num_volume_nodes: usize,
+}
+
+impl<T, D, C> BoundaryPatches<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// The surface mesh consisting of the faces of all patches, in the boundary node numbering.
+    pub fn surface_mesh(&self) -> &Mesh<T, D, C> {
+        &self.surface_mesh
+    }
+
+    pub fn num_patches(&self) -> usize {
+        self.patch_names.len()
+    }
+
+    pub fn patch_names(&self) -> &[String] {
+        &self.patch_names
+    }
+
+    /// Returns the index of the first patch with the given name, if any.
+    pub fn find_patch(&self, name: &str) -> Option<usize> {
+        self.patch_names.iter().position(|patch| patch == name)
+    }
+
+    /// The range of elements in the surface mesh that belong to the given patch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patch index is out of bounds.
+    pub fn element_range(&self, patch_index: usize) -> Range<usize> {
+        self.element_ranges[patch_index].clone()
+    }
+
+    /// The connectivity of the faces of the given patch, in the boundary node numbering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patch index is out of bounds.
+    pub fn patch_connectivity(&self, patch_index: usize) -> &[C] {
+        &self.surface_mesh.connectivity()[self.element_range(patch_index)]
+    }
+
+    /// The volume node associated with each boundary node.
+    ///
+    /// The volume nodes are sorted in ascending order, so the boundary numbering preserves
+    /// the relative order of the volume numbering.
+    pub fn boundary_to_volume(&self) -> &[usize] {
+        &self.boundary_to_volume
+    }
+
+    /// Returns the boundary node associated with the given volume node, if any.
+    pub fn volume_to_boundary(&self, volume_node: usize) -> Option<usize> {
+        self.boundary_to_volume.binary_search(&volume_node).ok()
+    }
+
+    /// The number of nodes in the volume mesh from which the patches were extracted.
+    pub fn num_volume_nodes(&self) -> usize {
+        self.num_volume_nodes
+    }
+}
+
+impl<T, D, C> Mesh<T, D, C>
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    C::FaceConnectivity: ConnectivityMut,
+    DefaultAllocator: Allocator<T, D>,
+{
+    /// Extracts several named patches from the boundary of the mesh, sharing a single boundary
+    /// node numbering.
+    ///
+    /// A boundary face belongs to a patch if the predicate of the patch holds for all of its
+    /// vertices. A face may belong to several patches, in which case it is included once in each
+    /// of them. Like [`extract_surface_mesh`](Self::extract_surface_mesh), the orientation of the
+    /// faces is preserved.
+    pub fn extract_boundary_patches(
+        &self,
+        patches: &[(&str, &dyn Fn(&OPoint<T, D>) -> bool)],
+    ) -> BoundaryPatches<T, D, C::FaceConnectivity> {
+        let boundary_faces = self.find_boundary_faces();
+
+        let mut faces = Vec::new();
+        let mut element_ranges = Vec::with_capacity(patches.len());
+        for (_, predicate) in patches {
+            let start = faces.len();
+            faces.extend(
+                boundary_faces
+                    .iter()
+                    .map(|(face, _, _)| face)
+                    .filter(|face| {
+                        face.vertex_indices()
+                            .iter()
+                            .all(|&v| predicate(&self.vertices()[v]))
+                    })
+                    .cloned(),
+            );
+            element_ranges.push(start..faces.len());
+        }
+
+        let mut boundary_to_volume: Vec<_> = faces
+            .iter()
+            .flat_map(|face| face.vertex_indices().iter().copied())
+            .collect();
+        boundary_to_volume.sort_unstable();
+        boundary_to_volume.dedup();
+
+        for face in &mut faces {
+            for index in face.vertex_indices_mut() {
+                *index = boundary_to_volume
+                    .binary_search(index)
+                    .expect("Every face vertex is a boundary node");
+            }
+        }
+
+        let vertices = boundary_to_volume
+            .iter()
+            .map(|&v| self.vertices()[v].clone())
+            .collect();
+
+        BoundaryPatches {
+            surface_mesh: Mesh::from_vertices_and_connectivity(vertices, faces),
+            patch_names: patches.iter().map(|(name, _)| name.to_string()).collect(),
+            element_ranges,
+            boundary_to_volume,
+            num_volume_nodes: self.vertices().len(),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Closest-point and signed distance queries for triangulated surface meshes.
+use crate::geometry::{AxisAlignedBoundingBox, SignedDistance, SignedDistanceResult};
+use crate::mesh::TriangleMesh3d;
+use crate::space::RTreeAccelerationStructure;
+use crate::Real;
+use eyre::bail;
+use nalgebra::{Point3, Vector3, U3};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The feature of a triangle that contains the closest point to a query point.
+///
+/// Edge $i$ goes from vertex $i$ to vertex $i + 1$ (modulo 3) of the triangle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TriangleFeature {
+    Vertex(usize),
+    Edge(usize),
+    Face,
+}
+
+/// Accelerated closest-point and signed distance queries for a triangulated surface mesh.
+///
+/// The triangles are indexed by an R-tree of their bounding boxes, so that a query only needs to
+/// examine the triangles whose bounding boxes are closer to the query point than the closest
+/// triangle found so far. Closest-point queries are valid for any collection of triangles.
+///
+/// Signed distances are negative inside and positive outside the surface. The sign is determined
+/// with the angle-weighted pseudo-normals of Bærentzen and Aanæs, "Signed distance computation
+/// using the angle weighted pseudonormal" (2005): depending on whether the closest point lies in
+/// the interior of a triangle, on an edge or at a vertex, the vector from the closest point to the
+/// query point is compared with the normal of the triangle, the sum of the normals of the two
+/// triangles sharing the edge, or the sum of the normals of the triangles incident to the vertex,
+/// weighted by their angles at the vertex. Unlike the normal of any single triangle, this gives the
+/// correct sign also when the closest point lies on an edge or at a vertex. Signed distances
+/// require the surface mesh to be closed (watertight) and its triangles to be oriented
+/// consistently, with normals pointing out of the enclosed volume. This is the case for
+/// the [surface mesh](crate::mesh::Mesh::extract_surface_mesh) of a positively oriented
+/// tetrahedral mesh.
+#[derive(Debug, Clone)]
+pub struct SurfaceMeshDistanceQuery<T: Real> {
+    vertices: Vec<Point3<T>>,
+    triangles: Vec<[usize; 3]>,
+    face_normals: Vec<Vector3<T>>,
+    edge_normals: Vec<[Vector3<T>; 3]>,
+    vertex_normals: Vec<Vector3<T>>,
+    tree: RTreeAccelerationStructure<U3>,
+}
+
+impl<T: Real> SurfaceMeshDistanceQuery<T> {
+    /// Builds the query structure for the given surface mesh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mesh has no triangles, or if a triangle refers to a vertex that is
+    /// out of bounds.
+    pub fn from_mesh(mesh: &TriangleMesh3d<T>) -> eyre::Result<Self> {
+        let vertices = mesh.vertices().to_vec();
+        let triangles: Vec<[usize; 3]> = mesh.connectivity().iter().map(|conn| conn.0).collect();
+        if triangles.is_empty() {
+            bail!("Cannot build distance query for surface mesh without triangles");
+        }
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            if let Some(&v) = triangle.iter().find(|&&v| v >= vertices.len()) {
+                bail!(
+                    "Vertex index {} of triangle {} is out of bounds for mesh with {} vertices",
+                    v,
+                    triangle_index,
+                    vertices.len()
+                );
+            }
+        }
+
+        let mut face_normals = Vec::with_capacity(triangles.len());
+        let mut vertex_normals = vec![Vector3::zeros(); vertices.len()];
+        let mut edge_normal_sums = HashMap::new();
+        for triangle in &triangles {
+            let [a, b, c] = triangle.map(|v| vertices[v]);
+            let normal = (b - a)
+                .cross(&(c - a))
+                .try_normalize(T::zero())
+                .unwrap_or_else(Vector3::zeros);
+            face_normals.push(normal);
+
+            for i in 0..3 {
+                let (v0, v1, v2) = (triangle[i], triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+                let e1 = vertices[v1] - vertices[v0];
+                let e2 = vertices[v2] - vertices[v0];
+                let angle = e1.cross(&e2).norm().atan2(e1.dot(&e2));
+                vertex_normals[v0] += normal * angle;
+
+                let edge_key = [v0.min(v1), v0.max(v1)];
+                *edge_normal_sums
+                    .entry(edge_key)
+                    .or_insert_with(Vector3::zeros) += normal;
+            }
+        }
+
+        let edge_normals = triangles
+            .iter()
+            .map(|triangle| {
+                [0, 1, 2].map(|i| {
+                    let (v0, v1) = (triangle[i], triangle[(i + 1) % 3]);
+                    edge_normal_sums[&[v0.min(v1), v0.max(v1)]]
+                })
+            })
+            .collect();
+
+        let bounding_boxes: Vec<_> = triangles
+            .iter()
+            .map(|triangle| {
+                AxisAlignedBoundingBox::from_points(triangle.iter().map(|&v| &vertices[v]))
+                    .expect("A triangle has vertices")
+            })
+            .collect();
+        let tree = RTreeAccelerationStructure::from_bounding_boxes(&bounding_boxes);
+
+        Ok(Self {
+            vertices,
+            triangles,
+            face_normals,
+            edge_normals,
+            vertex_normals,
+            tree,
+        })
+    }
+
+    /// The number of triangles in the surface mesh.
+    pub fn num_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Computes the closest point on the surface mesh to the given point.
+    ///
+    /// Returns the closest point, the index of the triangle that contains it and the (unsigned)
+    /// distance to the point. If several triangles contain the closest point, any of them may be
+    /// returned.
+    pub fn closest_point(&self, point: &Point3<T>) -> (Point3<T>, usize, T) {
+        let (closest_point, triangle_index, _, dist2) = self.find_closest_feature(point);
+        (closest_point, triangle_index, dist2.sqrt())
+    }
+
+    /// Computes the signed distance from the given point to the surface mesh.
+    ///
+    /// The result also contains the closest point on the surface, and the index of the triangle
+    /// that contains it as the feature id.
+    pub fn signed_distance(&self, point: &Point3<T>) -> SignedDistanceResult<T, U3> {
+        let (closest_point, triangle_index, feature, dist2) = self.find_closest_feature(point);
+        let pseudonormal = match feature {
+            TriangleFeature::Face => &self.face_normals[triangle_index],
+            TriangleFeature::Edge(i) => &self.edge_normals[triangle_index][i],
+            TriangleFeature::Vertex(i) => &self.vertex_normals[self.triangles[triangle_index][i]],
+        };
+        let distance = dist2.sqrt();
+        let signed_distance = if (point - closest_point).dot(pseudonormal) < T::zero() {
+            -distance
+        } else {
+            distance
+        };
+        SignedDistanceResult {
+            feature_id: triangle_index,
+            point: closest_point,
+            signed_distance,
+        }
+    }
+
+    /// Computes the closest points on the surface mesh to the given points in parallel.
+    ///
+    /// See [`closest_point`](Self::closest_point).
+    pub fn closest_points_par(&self, points: &[Point3<T>]) -> Vec<(Point3<T>, usize, T)> {
+        points
+            .par_iter()
+            .map(|point| self.closest_point(point))
+            .collect()
+    }
+
+    /// Computes the signed distances from the given points to the surface mesh in parallel.
+    ///
+    /// See [`signed_distance`](Self::signed_distance).
+    pub fn signed_distances_par(&self, points: &[Point3<T>]) -> Vec<SignedDistanceResult<T, U3>> {
+        points
+            .par_iter()
+            .map(|point| self.signed_distance(point))
+            .collect()
+    }
+
+    /// Finds the closest point, the triangle and feature that contain it, and the squared distance
+    /// to the query point.
+    fn find_closest_feature(&self, point: &Point3<T>) -> (Point3<T>, usize, TriangleFeature, T) {
+        let mut closest: Option<(Point3<T>, usize, TriangleFeature, T)> = None;
+        for (box_dist2, triangle_index) in self.tree.cells_by_distance(point) {
+            // Bounding boxes are visited in order of increasing distance, so no remaining
+            // triangle can be closer than the closest triangle found so far
+            if let Some((_, _, _, closest_dist2)) = closest {
+                let closest_dist2: f64 = closest_dist2.to_subset().unwrap();
+                if box_dist2 > closest_dist2 {
+                    break;
+                }
+            }
+
+            let vertices = self.triangles[triangle_index].map(|v| self.vertices[v]);
+            let (closest_point, feature) = closest_point_on_triangle(&vertices, point);
+            let dist2 = (point - closest_point).norm_squared();
+            let is_closer = match closest {
+                Some((_, _, _, closest_dist2)) => dist2 < closest_dist2,
+                None => true,
+            };
+            if is_closer {
+                closest = Some((closest_point, triangle_index, feature, dist2));
+            }
+        }
+        closest.expect("Query structure always contains at least one triangle")
+    }
+}
+
+impl<T: Real> SignedDistance<T, U3> for SurfaceMeshDistanceQuery<T> {
+    fn query_signed_distance(&self, point: &Point3<T>) -> Option<SignedDistanceResult<T, U3>> {
+        Some(self.signed_distance(point))
+    }
+}
+
+/// Computes the closest point on a triangle and the feature of the triangle that contains it.
+///
+/// The point is located by determining which of the Voronoi regions of the vertices, edges and
+/// interior of the triangle contains the query point, following Section 5.1.5 of
+/// Ericson, "Real-Time Collision Detection" (2004).
+fn closest_point_on_triangle<T: Real>([a, b, c]: &[Point3<T>; 3], p: &Point3<T>) -> (Point3<T>, TriangleFeature) {
+    let zero = T::zero();
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= zero && d2 <= zero {
+        return (*a, TriangleFeature::Vertex(0));
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= zero && d4 <= d3 {
+        return (*b, TriangleFeature::Vertex(1));
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= zero && d1 >= zero && d3 <= zero {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, TriangleFeature::Edge(0));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= zero && d5 <= d6 {
+        return (*c, TriangleFeature::Vertex(2));
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= zero && d2 >= zero && d6 <= zero {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, TriangleFeature::Edge(2));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= zero && d4 - d3 >= zero && d5 - d6 >= zero {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, TriangleFeature::Edge(1));
+    }
+
+    let denom = T::one() / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+    (a + ab * v + ac * w, TriangleFeature::Face)
+}
@@ -0,0 +1,104 @@
+use crate::connectivity::{
+    Connectivity, Hex8Connectivity, Prism6Connectivity, Quad4d2Connectivity, Tri3d2Connectivity,
+};
+use crate::mesh::{Mesh2d, Mesh3d};
+use crate::nalgebra::Point3;
+use crate::Real;
+
+/// A 2D connectivity that can be swept along the $z$-axis to form a 3D connectivity.
+pub trait ExtrudeConnectivity: Connectivity {
+    /// The connectivity of the 3D element swept out by the 2D element.
+    type Extruded: Connectivity;
+
+    /// Constructs the connectivity of the element between two layers, given the offsets
+    /// of the vertex indices in the bottom and top layers.
+    fn extrude(&self, bottom_offset: usize, top_offset: usize) -> Self::Extruded;
+}
+
+impl ExtrudeConnectivity for Tri3d2Connectivity {
+    type Extruded = Prism6Connectivity;
+
+    fn extrude(&self, bottom_offset: usize, top_offset: usize) -> Self::Extruded {
+        let [a, b, c] = self.0;
+        Prism6Connectivity([
+            bottom_offset + a,
+            bottom_offset + b,
+            bottom_offset + c,
+            top_offset + a,
+            top_offset + b,
+            top_offset + c,
+        ])
+    }
+}
+
+impl ExtrudeConnectivity for Quad4d2Connectivity {
+    type Extruded = Hex8Connectivity;
+
+    fn extrude(&self, bottom_offset: usize, top_offset: usize) -> Self::Extruded {
+        let [a, b, c, d] = self.0;
+        Hex8Connectivity([
+            bottom_offset + a,
+            bottom_offset + b,
+            bottom_offset + c,
+            bottom_offset + d,
+            top_offset + a,
+            top_offset + b,
+            top_offset + c,
+            top_offset + d,
+        ])
+    }
+}
+
+/// Extrudes a 2D mesh into a 3D mesh by sweeping it along the $z$-axis.
+///
+/// Each vertex of the 2D mesh is copied to every layer, with the $z$-coordinates given by
+/// `layers`, and each element is swept between consecutive layers. Triangle meshes produce prism meshes
+/// and quadrilateral meshes produce hexahedral meshes. Vertex $i$ of the 2D mesh in layer $k$
+/// is given the index $k n + i$ in the extruded mesh, where $n$ is the number of vertices
+/// of the 2D mesh.
+///
+/// Elements with counter-clockwise oriented vertices in the 2D mesh produce 3D elements with
+/// positive Jacobian determinants.
+///
+/// Returns the extruded mesh together with the indices of the vertices in the bottom and top layers.
+///
+/// # Panics
+///
+/// Panics if fewer than two layers are given or the layer coordinates are not strictly increasing.
+pub fn extrude<T, C>(mesh: &Mesh2d<T, C>, layers: &[T]) -> (Mesh3d<T, C::Extruded>, Vec<usize>, Vec<usize>)
+where
+    T: Real,
+    C: ExtrudeConnectivity,
+{
+    assert!(layers.len() >= 2, "Extrusion requires at least two layers");
+    assert!(
+        layers.windows(2).all(|pair| pair[0] < pair[1]),
+        "Layer coordinates must be strictly increasing"
+    );
+
+    let n = mesh.vertices().len();
+    let num_layers = layers.len();
+    let vertices = layers
+        .iter()
+        .flat_map(|&z| {
+            mesh.vertices()
+                .iter()
+                .map(move |v| Point3::new(v.x, v.y, z))
+        })
+        .collect();
+    let connectivity = (0..num_layers - 1)
+        .flat_map(|layer| {
+            mesh.connectivity()
+                .iter()
+                .map(move |cell| cell.extrude(layer * n, (layer + 1) * n))
+        })
+        .collect();
+
+    let bottom_vertices = (0..n).collect();
+    let top_vertices = ((num_layers - 1) * n..num_layers * n).collect();
+    (
+        Mesh3d::from_vertices_and_connectivity(vertices, connectivity),
+        bottom_vertices,
+        top_vertices,
+    )
+}
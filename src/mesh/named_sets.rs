@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Named sets of nodes and elements associated with a mesh.
+///
+/// Named sets are typically used to tag parts of a mesh, for example the nodes on which a
+/// boundary condition is applied or the elements that make up a particular material region.
+/// Each set is stored as a sorted list of unique node or element indices.
+///
+/// Node sets and element sets live in separate namespaces, so a node set and an element set
+/// may share the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedSets {
+    node_sets: BTreeMap<String, Vec<usize>>,
+    element_sets: BTreeMap<String, Vec<usize>>,
+}
+
+fn sorted_unique_indices(indices: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let mut indices: Vec<_> = indices.into_iter().collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+impl NamedSets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a node set with the given name, replacing any existing node set with the same name.
+    ///
+    /// The indices are sorted and duplicates are removed. Returns the previous node set
+    /// with the same name, if any.
+    pub fn insert_node_set(
+        &mut self,
+        name: impl Into<String>,
+        node_indices: impl IntoIterator<Item = usize>,
+    ) -> Option<Vec<usize>> {
+        self.node_sets
+            .insert(name.into(), sorted_unique_indices(node_indices))
+    }
+
+    /// Inserts an element set with the given name, replacing any existing element set with the same name.
+    ///
+    /// The indices are sorted and duplicates are removed. Returns the previous element set
+    /// with the same name, if any.
+    pub fn insert_element_set(
+        &mut self,
+        name: impl Into<String>,
+        element_indices: impl IntoIterator<Item = usize>,
+    ) -> Option<Vec<usize>> {
+        self.element_sets
+            .insert(name.into(), sorted_unique_indices(element_indices))
+    }
+
+    /// Inserts a node set with the given name.
+    ///
+    /// See [`insert_node_set`](Self::insert_node_set).
+    pub fn with_node_set(mut self, name: impl Into<String>, node_indices: impl IntoIterator<Item = usize>) -> Self {
+        self.insert_node_set(name, node_indices);
+        self
+    }
+
+    /// Inserts an element set with the given name.
+    ///
+    /// See [`insert_element_set`](Self::insert_element_set).
+    pub fn with_element_set(
+        mut self,
+        name: impl Into<String>,
+        element_indices: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        self.insert_element_set(name, element_indices);
+        self
+    }
+
+    /// The sorted node indices of the node set with the given name.
+    pub fn node_set(&self, name: &str) -> Option<&[usize]> {
+        self.node_sets.get(name).map(Vec::as_slice)
+    }
+
+    /// The sorted element indices of the element set with the given name.
+    pub fn element_set(&self, name: &str) -> Option<&[usize]> {
+        self.element_sets.get(name).map(Vec::as_slice)
+    }
+
+    /// All node sets, ordered by name.
+    pub fn node_sets(&self) -> impl '_ + Iterator<Item = (&str, &[usize])> {
+        self.node_sets
+            .iter()
+            .map(|(name, indices)| (name.as_str(), indices.as_slice()))
+    }
+
+    /// All element sets, ordered by name.
+    pub fn element_sets(&self) -> impl '_ + Iterator<Item = (&str, &[usize])> {
+        self.element_sets
+            .iter()
+            .map(|(name, indices)| (name.as_str(), indices.as_slice()))
+    }
+
+    /// Returns `true` if there are neither node sets nor element sets.
+    pub fn is_empty(&self) -> bool {
+        self.node_sets.is_empty() && self.element_sets.is_empty()
+    }
+}
@@ -276,6 +276,192 @@ where
     }
 }
 
+/// The index layout of the vertices of a structured (tensor-product) grid.
+///
+/// The vertices of meshes created by [`create_graded_quad_mesh_2d`], [`create_graded_hex_mesh_3d`],
+/// [`create_box_uniform_quad_mesh_2d`] and [`create_box_uniform_hex_mesh_3d`] are ordered
+/// lexicographically with the $x$-index varying fastest, followed by the $y$-index and the $z$-index.
+/// Elements are ordered in the same way. `StructuredGrid` computes vertex indices for this layout,
+/// for example to find the vertices on the sides of the box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuredGrid<const D: usize> {
+    num_vertices: [usize; D],
+}
+
+impl<const D: usize> StructuredGrid<D> {
+    /// Creates the layout for a grid with the given number of cells along each axis.
+    pub fn from_num_cells(num_cells: [usize; D]) -> Self {
+        Self {
+            num_vertices: num_cells.map(|n| n + 1),
+        }
+    }
+
+    /// The number of vertices along each axis.
+    pub fn num_vertices_per_axis(&self) -> [usize; D] {
+        self.num_vertices
+    }
+
+    /// The total number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.num_vertices.iter().product()
+    }
+
+    /// The index of the vertex with the given multi-index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multi-index is out of bounds.
+    pub fn vertex_index(&self, multi_index: [usize; D]) -> usize {
+        let mut index = 0;
+        let mut stride = 1;
+        for (i, n) in multi_index.into_iter().zip(self.num_vertices) {
+            assert!(i < n, "Multi-index must be in bounds");
+            index += stride * i;
+            stride *= n;
+        }
+        index
+    }
+
+    /// The indices of the vertices on the side of the box where the coordinate along the given
+    /// axis is minimal, in increasing order.
+    pub fn min_side_vertices(&self, axis: usize) -> Vec<usize> {
+        self.side_vertices(axis, 0)
+    }
+
+    /// The indices of the vertices on the side of the box where the coordinate along the given
+    /// axis is maximal, in increasing order.
+    pub fn max_side_vertices(&self, axis: usize) -> Vec<usize> {
+        self.side_vertices(axis, self.num_vertices[axis] - 1)
+    }
+
+    fn side_vertices(&self, axis: usize, axis_index: usize) -> Vec<usize> {
+        assert!(axis < D, "Axis must be smaller than the dimension of the grid");
+        let stride: usize = self.num_vertices[..axis].iter().product();
+        (0..self.num_vertices())
+            .filter(|&index| (index / stride) % self.num_vertices[axis] == axis_index)
+            .collect()
+    }
+}
+
+fn assert_valid_grading<T: Real>(coords: &[T]) {
+    assert!(coords.len() >= 2, "Each axis must have at least two coordinates");
+    assert!(
+        coords.windows(2).all(|pair| pair[0] < pair[1]),
+        "Coordinates must be strictly increasing"
+    );
+}
+
+fn uniform_coordinates<T: Real>(extent: T, num_cells: usize) -> Vec<T> {
+    assert!(extent > T::zero(), "Extents must be positive");
+    assert!(num_cells > 0, "Number of cells must be positive");
+    let n = T::from_usize(num_cells).unwrap();
+    (0..=num_cells)
+        .map(|i| extent * T::from_usize(i).unwrap() / n)
+        .collect()
+}
+
+/// Creates a structured quadrilateral mesh of the rectangle $[0, e_x] \times [0, e_y]$ with extents
+/// $(e_x, e_y)$ and the given number of cells along each axis.
+///
+/// See [`StructuredGrid`] for the ordering of vertices and elements.
+///
+/// # Panics
+///
+/// Panics if the extents or the number of cells are not positive.
+pub fn create_box_uniform_quad_mesh_2d<T>(extents: &Vector2<T>, num_cells: [usize; 2]) -> QuadMesh2d<T>
+where
+    T: Real,
+{
+    let [nx, ny] = num_cells;
+    create_graded_quad_mesh_2d(&uniform_coordinates(extents.x, nx), &uniform_coordinates(extents.y, ny))
+}
+
+/// Creates a structured hexahedral mesh of the box $[0, e_x] \times [0, e_y] \times [0, e_z]$ with extents
+/// $(e_x, e_y, e_z)$ and the given number of cells along each axis.
+///
+/// See [`StructuredGrid`] for the ordering of vertices and elements.
+///
+/// # Panics
+///
+/// Panics if the extents or the number of cells are not positive.
+pub fn create_box_uniform_hex_mesh_3d<T>(extents: &Vector3<T>, num_cells: [usize; 3]) -> HexMesh<T>
+where
+    T: Real,
+{
+    let [nx, ny, nz] = num_cells;
+    create_graded_hex_mesh_3d(
+        &uniform_coordinates(extents.x, nx),
+        &uniform_coordinates(extents.y, ny),
+        &uniform_coordinates(extents.z, nz),
+    )
+}
+
+/// Creates a structured quadrilateral mesh from the given vertex coordinates along each axis.
+///
+/// The vertices of the mesh are all points $(x_i, y_j)$, so that non-uniform coordinates can be used
+/// to grade the mesh, for example to cluster cells near a boundary layer. See [`StructuredGrid`] for the
+/// ordering of vertices and elements.
+///
+/// # Panics
+///
+/// Panics if any axis has fewer than two coordinates, or the coordinates are not strictly increasing.
+pub fn create_graded_quad_mesh_2d<T>(x_coords: &[T], y_coords: &[T]) -> QuadMesh2d<T>
+where
+    T: Real,
+{
+    assert_valid_grading(x_coords);
+    assert_valid_grading(y_coords);
+    let grid = StructuredGrid::from_num_cells([x_coords.len() - 1, y_coords.len() - 1]);
+    let idx = |i, j| grid.vertex_index([i, j]);
+
+    let vertices = iproduct!(y_coords, x_coords)
+        .map(|(&y, &x)| Point2::new(x, y))
+        .collect();
+    let cells = iproduct!(0..y_coords.len() - 1, 0..x_coords.len() - 1)
+        .map(|(j, i)| Quad4d2Connectivity([idx(i, j), idx(i + 1, j), idx(i + 1, j + 1), idx(i, j + 1)]))
+        .collect();
+    Mesh::from_vertices_and_connectivity(vertices, cells)
+}
+
+/// Creates a structured hexahedral mesh from the given vertex coordinates along each axis.
+///
+/// The vertices of the mesh are all points $(x_i, y_j, z_k)$, so that non-uniform coordinates can be used
+/// to grade the mesh, for example to cluster cells near a boundary layer. See [`StructuredGrid`] for the
+/// ordering of vertices and elements.
+///
+/// # Panics
+///
+/// Panics if any axis has fewer than two coordinates, or the coordinates are not strictly increasing.
+pub fn create_graded_hex_mesh_3d<T>(x_coords: &[T], y_coords: &[T], z_coords: &[T]) -> HexMesh<T>
+where
+    T: Real,
+{
+    assert_valid_grading(x_coords);
+    assert_valid_grading(y_coords);
+    assert_valid_grading(z_coords);
+    let grid = StructuredGrid::from_num_cells([x_coords.len() - 1, y_coords.len() - 1, z_coords.len() - 1]);
+    let idx = |i, j, k| grid.vertex_index([i, j, k]);
+
+    let vertices = iproduct!(z_coords, y_coords, x_coords)
+        .map(|(&z, &y, &x)| Point3::new(x, y, z))
+        .collect();
+    let cells = iproduct!(0..z_coords.len() - 1, 0..y_coords.len() - 1, 0..x_coords.len() - 1)
+        .map(|(k, j, i)| {
+            Hex8Connectivity([
+                idx(i, j, k),
+                idx(i + 1, j, k),
+                idx(i + 1, j + 1, k),
+                idx(i, j + 1, k),
+                idx(i, j, k + 1),
+                idx(i + 1, j, k + 1),
+                idx(i + 1, j + 1, k + 1),
+                idx(i, j + 1, k + 1),
+            ])
+        })
+        .collect();
+    Mesh::from_vertices_and_connectivity(vertices, cells)
+}
+
 /// Creates a rectangular uniform tetrahedral mesh.
 ///
 /// The implementation uses a BCC lattice, where each pair of adjacent cell centers
@@ -0,0 +1,412 @@
+//! Quality metrics for mesh elements.
+//!
+//! Poorly shaped elements degrade the accuracy of finite element approximations and the
+//! conditioning of the resulting linear systems, and inverted or degenerate elements produce
+//! singular Jacobians during assembly. The metrics in this module help to identify such elements,
+//! for example in meshes imported from external mesh generators.
+//!
+//! Metrics for individual elements are provided by [`ElementQuality`], implemented for
+//! the linear triangle, quadrilateral, tetrahedron and hexahedron elements, and by [`SimplexQuality`]
+//! for simplices. [`min_scaled_jacobian`] computes the scaled Jacobian for any element whose
+//! reference and geometry dimensions coincide. All metrics are normalized so that they evaluate to 1
+//! for the ideal element shape.
+//!
+//! Metrics for all elements of a mesh can be computed with [`compute_element_metric`]
+//! and summarized with [`QualitySummary`]:
+//!
+//! ```
+//! # use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+//! use fenris::mesh::quality::{compute_element_metric, ElementQuality, QualitySummary};
+//! let mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+//! let scaled_jacobians = compute_element_metric(&mesh, |element| element.scaled_jacobian());
+//! let summary = QualitySummary::from_values(&scaled_jacobians, 10).unwrap();
+//! assert!(summary.min > 0.0);
+//! ```
+use crate::allocators::{BiDimAllocator, DimAllocator};
+use crate::element::{ElementConnectivity, FiniteElement, Hex8Element, Quad4d2Element, Tet4Element, Tri3d2Element};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DefaultAllocator, DimMin, DimName, OPoint, Point2, Point3, Vector2, Vector3};
+use crate::{Real, SmallDim};
+use numeric_literals::replace_float_literals;
+
+/// Quality metrics for individual elements.
+pub trait ElementQuality<T: Real> {
+    /// The aspect ratio of the element.
+    ///
+    /// For simplices, this is the ratio of the longest edge to the inradius, normalized so that
+    /// the equilateral triangle and the regular tetrahedron have aspect ratio 1.
+    /// For quadrilaterals and hexahedra, this is the ratio of the longest edge to the shortest edge.
+    /// The aspect ratio is always at least 1, and unbounded for degenerate elements.
+    fn aspect_ratio(&self) -> T;
+
+    /// The scaled Jacobian of the element.
+    ///
+    /// The scaled Jacobian is the minimum over the corners of the element of the Jacobian determinant
+    /// at the corner, divided by the product of the lengths of the edges meeting at the corner.
+    /// Simplices are additionally normalized so that equilateral elements have scaled Jacobian 1.
+    /// The scaled Jacobian is at most 1, zero for degenerate elements and negative for
+    /// inverted elements.
+    fn scaled_jacobian(&self) -> T;
+}
+
+/// Quality metrics specific to simplices.
+pub trait SimplexQuality<T: Real> {
+    /// The radius ratio $d \, r / R$ of the simplex, where $d$ is the dimension and $r$ and $R$ are the radii
+    /// of the inscribed and circumscribed spheres.
+    ///
+    /// The radius ratio lies in $[0, 1]$. It is 1 for equilateral simplices and 0 for degenerate simplices.
+    fn radius_ratio(&self) -> T;
+}
+
+/// Computes the minimum scaled Jacobian of the element over the given reference points.
+///
+/// The scaled Jacobian at a point is the determinant of the reference Jacobian divided by the
+/// product of the norms of its columns. When evaluated at the reference vertices of quadrilateral
+/// and hexahedral elements, this coincides with the standard corner-based scaled Jacobian.
+/// Returns zero if any column of the Jacobian vanishes.
+///
+/// # Panics
+///
+/// Panics if no points are given.
+pub fn min_scaled_jacobian<T, D, Element>(element: &Element, reference_points: &[OPoint<T, D>]) -> T
+where
+    T: Real,
+    D: DimName + DimMin<D, Output = D>,
+    Element: FiniteElement<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    assert!(
+        !reference_points.is_empty(),
+        "Must provide at least one reference point"
+    );
+    reference_points
+        .iter()
+        .map(|xi| {
+            let jacobian = element.reference_jacobian(xi);
+            let column_norm_product = jacobian
+                .column_iter()
+                .map(|column| column.norm())
+                .fold(T::one(), |product, norm| product * norm);
+            if column_norm_product == T::zero() {
+                T::zero()
+            } else {
+                jacobian.determinant() / column_norm_product
+            }
+        })
+        .fold(T::max_value().unwrap(), |a, b| a.min(b))
+}
+
+/// The dihedral angles of a tetrahedron, in radians.
+///
+/// The angles are associated with the edges $(0, 1)$, $(0, 2)$, $(0, 3)$, $(1, 2)$, $(1, 3)$ and $(2, 3)$,
+/// in that order. The dihedral angles of the regular tetrahedron are all $\arccos(1/3) \approx 70.53°$,
+/// while slivers have dihedral angles close to both $0$ and $\pi$.
+pub fn tetrahedron_dihedral_angles<T: Real>(element: &Tet4Element<T>) -> [T; 6] {
+    let x = element.vertices();
+    // For each edge (i, j), the remaining two vertices (k, l) span the two faces meeting at the edge
+    let edges = [
+        (0, 1, 2, 3),
+        (0, 2, 1, 3),
+        (0, 3, 1, 2),
+        (1, 2, 0, 3),
+        (1, 3, 0, 2),
+        (2, 3, 0, 1),
+    ];
+    edges.map(|(i, j, k, l)| {
+        let e = x[j] - x[i];
+        let n1 = e.cross(&(x[k] - x[i]));
+        let n2 = e.cross(&(x[l] - x[i]));
+        n1.cross(&n2).norm().atan2(n1.dot(&n2))
+    })
+}
+
+/// The minimum and maximum dihedral angles of a tetrahedron, in radians.
+///
+/// See [`tetrahedron_dihedral_angles`].
+pub fn tetrahedron_min_max_dihedral_angles<T: Real>(element: &Tet4Element<T>) -> (T, T) {
+    let angles = tetrahedron_dihedral_angles(element);
+    let min = angles.iter().fold(angles[0], |a, &b| a.min(b));
+    let max = angles.iter().fold(angles[0], |a, &b| a.max(b));
+    (min, max)
+}
+
+/// Computes a metric for every element in the mesh.
+///
+/// # Panics
+///
+/// Panics if an element refers to vertices that are not in the mesh.
+pub fn compute_element_metric<T, D, C, F>(mesh: &Mesh<T, D, C>, metric: F) -> Vec<T>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    F: Fn(&C::Element) -> T,
+    DefaultAllocator: BiDimAllocator<T, D, C::ReferenceDim>,
+{
+    mesh.connectivity()
+        .iter()
+        .map(|connectivity| {
+            let element = connectivity
+                .element(mesh.vertices())
+                .expect("Connectivity must refer to vertices in the mesh");
+            metric(&element)
+        })
+        .collect()
+}
+
+/// Summary statistics for a quality metric over a collection of elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualitySummary<T> {
+    /// The minimum value.
+    pub min: T,
+    /// The maximum value.
+    pub max: T,
+    /// The mean value.
+    pub mean: T,
+    /// The index of the (first) element attaining the minimum value.
+    pub min_index: usize,
+    /// The index of the (first) element attaining the maximum value.
+    pub max_index: usize,
+    /// The number of values in each of the equally sized bins spanning `[min, max]`.
+    ///
+    /// See [`QualitySummary::bin_edges`].
+    pub histogram: Vec<usize>,
+}
+
+impl<T: Real> QualitySummary<T> {
+    /// Summarizes the given values, with a histogram consisting of the given number of bins.
+    ///
+    /// Returns `None` if there are no values. Infinite or NaN values, such as the aspect ratio of
+    /// degenerate elements, are accounted for in the minimum and maximum, but render the
+    /// mean and histogram meaningless.
+    pub fn from_values(values: &[T], num_bins: usize) -> Option<Self> {
+        let first = *values.first()?;
+        let mut summary = Self {
+            min: first,
+            max: first,
+            mean: T::zero(),
+            min_index: 0,
+            max_index: 0,
+            histogram: vec![0; num_bins],
+        };
+
+        let mut sum = T::zero();
+        for (i, &value) in values.iter().enumerate() {
+            if value < summary.min {
+                summary.min = value;
+                summary.min_index = i;
+            }
+            if value > summary.max {
+                summary.max = value;
+                summary.max_index = i;
+            }
+            sum += value;
+        }
+        summary.mean = sum / T::from_usize(values.len()).unwrap();
+
+        if num_bins > 0 {
+            let range = summary.max - summary.min;
+            let num_bins_t = T::from_usize(num_bins).unwrap();
+            for &value in values {
+                let bin = if range > T::zero() {
+                    let relative = (value - summary.min) / range;
+                    let bin: f64 = (relative * num_bins_t).floor().to_subset().unwrap_or(0.0);
+                    (bin.max(0.0) as usize).min(num_bins - 1)
+                } else {
+                    0
+                };
+                summary.histogram[bin] += 1;
+            }
+        }
+
+        Some(summary)
+    }
+
+    /// The edges of the histogram bins.
+    ///
+    /// Bin `i` contains values in the half-open interval `[edges[i], edges[i + 1])`,
+    /// except for the last bin, which also includes the maximum value.
+    pub fn bin_edges(&self) -> Vec<T> {
+        let num_bins = self.histogram.len();
+        if num_bins == 0 {
+            return Vec::new();
+        }
+        let range = self.max - self.min;
+        (0..=num_bins)
+            .map(|i| {
+                let fraction = T::from_usize(i).unwrap() / T::from_usize(num_bins).unwrap();
+                self.min + fraction * range
+            })
+            .collect()
+    }
+}
+
+fn edge_length_ratio<T, D>(vertices: &[OPoint<T, D>], edges: &[(usize, usize)]) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let lengths = edges
+        .iter()
+        .map(|&(i, j)| (&vertices[i].coords - &vertices[j].coords).norm());
+    let (min, max) = lengths.fold((T::max_value().unwrap(), T::zero()), |(min, max), length| {
+        (min.min(length), max.max(length))
+    });
+    max / min
+}
+
+fn longest_edge<T, D>(vertices: &[OPoint<T, D>]) -> T
+where
+    T: Real,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let n = vertices.len();
+    (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .map(|(i, j)| (&vertices[i].coords - &vertices[j].coords).norm())
+        .fold(T::zero(), |a, b| a.max(b))
+}
+
+fn perp_dot<T: Real>(a: &Vector2<T>, b: &Vector2<T>) -> T {
+    a.x * b.y - a.y * b.x
+}
+
+fn triangle_area_and_perimeter<T: Real>(x: &[Point2<T>; 3]) -> (T, T) {
+    let area = perp_dot(&(x[1] - x[0]), &(x[2] - x[0])).abs() / T::from_f64(2.0).unwrap();
+    let perimeter = (x[0] - x[1]).norm() + (x[1] - x[2]).norm() + (x[2] - x[0]).norm();
+    (area, perimeter)
+}
+
+impl<T: Real> ElementQuality<T> for Tri3d2Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn aspect_ratio(&self) -> T {
+        // The inradius is r = 2 A / P for area A and perimeter P, and the ratio L / r
+        // of the equilateral triangle is 2 sqrt(3)
+        let (area, perimeter) = triangle_area_and_perimeter(self.vertices());
+        longest_edge(self.vertices()) * perimeter / (4.0 * T::sqrt(3.0) * area)
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn scaled_jacobian(&self) -> T {
+        let x = self.vertices();
+        let min = (0..3)
+            .map(|i| {
+                let a = x[(i + 1) % 3] - x[i];
+                let b = x[(i + 2) % 3] - x[i];
+                let norm_product = a.norm() * b.norm();
+                if norm_product == 0.0 {
+                    0.0
+                } else {
+                    perp_dot(&a, &b) / norm_product
+                }
+            })
+            .fold(T::max_value().unwrap(), |a, b| a.min(b));
+        2.0 / T::sqrt(3.0) * min
+    }
+}
+
+impl<T: Real> SimplexQuality<T> for Tri3d2Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn radius_ratio(&self) -> T {
+        // With r = 2 A / P and R = abc / (4 A), we have 2 r / R = 16 A^2 / (P abc)
+        let x = self.vertices();
+        let (area, perimeter) = triangle_area_and_perimeter(x);
+        if area == 0.0 {
+            return 0.0;
+        }
+        let edge_product = (x[0] - x[1]).norm() * (x[1] - x[2]).norm() * (x[2] - x[0]).norm();
+        16.0 * area * area / (perimeter * edge_product)
+    }
+}
+
+impl<T: Real> ElementQuality<T> for Quad4d2Element<T> {
+    fn aspect_ratio(&self) -> T {
+        edge_length_ratio(self.vertices(), &[(0, 1), (1, 2), (2, 3), (3, 0)])
+    }
+
+    fn scaled_jacobian(&self) -> T {
+        min_scaled_jacobian(self, Quad4d2Element::<T>::reference().vertices())
+    }
+}
+
+/// Returns the volume and total surface area of the tetrahedron.
+fn tetrahedron_volume_and_surface_area<T: Real>(x: &[Point3<T>; 4]) -> (T, T) {
+    let six = T::from_f64(6.0).unwrap();
+    let two = T::from_f64(2.0).unwrap();
+    let volume = (x[1] - x[0])
+        .dot(&(x[2] - x[0]).cross(&(x[3] - x[0])))
+        .abs()
+        / six;
+    let faces = [(0, 2, 1), (0, 1, 3), (1, 2, 3), (0, 3, 2)];
+    let area = faces
+        .iter()
+        .map(|&(i, j, k)| (x[j] - x[i]).cross(&(x[k] - x[i])).norm() / two)
+        .fold(T::zero(), |a, b| a + b);
+    (volume, area)
+}
+
+impl<T: Real> ElementQuality<T> for Tet4Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn aspect_ratio(&self) -> T {
+        // The inradius is r = 3 V / S for volume V and surface area S, and the ratio L / r
+        // of the regular tetrahedron is 2 sqrt(6)
+        let (volume, area) = tetrahedron_volume_and_surface_area(self.vertices());
+        longest_edge(self.vertices()) * area / (6.0 * T::sqrt(6.0) * volume)
+    }
+
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn scaled_jacobian(&self) -> T {
+        let x = self.vertices();
+        // Each corner with its neighboring vertices in an order consistent with the orientation
+        // of the tetrahedron
+        let corners = [(0, 1, 2, 3), (1, 0, 3, 2), (2, 0, 1, 3), (3, 0, 2, 1)];
+        let min = corners
+            .iter()
+            .map(|&(i, j, k, l)| {
+                let (a, b, c): (Vector3<T>, Vector3<T>, Vector3<T>) = (x[j] - x[i], x[k] - x[i], x[l] - x[i]);
+                let norm_product = a.norm() * b.norm() * c.norm();
+                if norm_product == 0.0 {
+                    0.0
+                } else {
+                    a.dot(&b.cross(&c)) / norm_product
+                }
+            })
+            .fold(T::max_value().unwrap(), |a, b| a.min(b));
+        T::sqrt(2.0) * min
+    }
+}
+
+impl<T: Real> SimplexQuality<T> for Tet4Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn radius_ratio(&self) -> T {
+        // With r = 3 V / S and R = |N| / (12 V), where
+        //  N = |a|^2 (b x c) + |b|^2 (c x a) + |c|^2 (a x b)
+        // for the edges a, b, c emanating from a vertex, we have 3 r / R = 108 V^2 / (S |N|)
+        let x = self.vertices();
+        let (volume, area) = tetrahedron_volume_and_surface_area(x);
+        if volume == 0.0 {
+            return 0.0;
+        }
+        let (a, b, c) = (x[1] - x[0], x[2] - x[0], x[3] - x[0]);
+        let n = b.cross(&c) * a.norm_squared() + c.cross(&a) * b.norm_squared() + a.cross(&b) * c.norm_squared();
+        108.0 * volume * volume / (area * n.norm())
+    }
+}
+
+impl<T: Real> ElementQuality<T> for Hex8Element<T> {
+    fn aspect_ratio(&self) -> T {
+        #[rustfmt::skip]
+        let edges = [
+            (0, 1), (1, 2), (2, 3), (3, 0),
+            (4, 5), (5, 6), (6, 7), (7, 4),
+            (0, 4), (1, 5), (2, 6), (3, 7),
+        ];
+        edge_length_ratio(self.vertices(), &edges)
+    }
+
+    fn scaled_jacobian(&self) -> T {
+        min_scaled_jacobian(self, Hex8Element::<T>::reference().vertices())
+    }
+}
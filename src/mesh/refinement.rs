@@ -2,10 +2,26 @@
 //!
 //! Currently we only provide uniform refinement for select element types through
 //! [`refine_mesh`] and [`UniformRefinement`].
-use crate::allocators::DimAllocator;
+//!
+//! [`refine_mesh_with_parents`] and [`refine_uniformly_with_parents`] additionally return the index
+//! of the original element that each refined element was created from, which can be used to transfer
+//! per-element data to the refined mesh.
+//!
+//! # Inter-grid transfer
+//!
+//! [`refine_mesh_with_prolongation`] additionally computes the *prolongation* operator
+//! $\vec P$, which maps nodal values of a finite element function on the original mesh to nodal
+//! values of the same function on the refined mesh. The transpose $\vec P^T$ serves as the
+//! corresponding restriction operator, for example for geometric multigrid, in which the
+//! coarse operator $\vec P^T \vec A \vec P$ is obtained from the fine operator $\vec A$.
+use crate::allocators::{BiDimAllocator, DimAllocator};
 use crate::connectivity::Connectivity;
+use crate::element::{map_physical_coordinates, ElementConnectivity, ReferenceFiniteElement};
 use crate::mesh::Mesh;
+use crate::{Real, SmallDim};
+use eyre::eyre;
 use nalgebra::{DefaultAllocator, DimName, OPoint, RealField};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
 use std::collections::HashMap;
 use std::hash::Hash;
 
@@ -56,6 +72,13 @@ pub trait RefineConnectivity<Connectivity> {
     ) -> Result<Self::OutputConnectivity, InvalidVertexCount>;
 }
 
+/// Uniform refinement, in which each element is split into $2^d$ geometrically similar children,
+/// where $d$ is the dimension of the element.
+///
+/// Uniform refinement is available for `Tri3`, `Quad4`, `Tet4` and `Hex8` elements, as well as
+/// their quadratic counterparts `Tri6`, `Quad9`, `Tet10` and `Hex27`. New nodes are placed at
+/// the midpoints of edges and the centroids of faces and cells of the element or its children,
+/// so for quadratic elements, curved geometry is only approximated by the refined mesh.
 pub struct UniformRefinement;
 
 /// Refine a mesh with the provided refinement scheme.
@@ -63,6 +86,53 @@ pub fn refine_mesh<T, D, C, Refinement>(
     mesh: &Mesh<T, D, C>,
     refinement_scheme: Refinement,
 ) -> Mesh<T, D, Refinement::OutputConnectivity>
+where
+    T: RealField,
+    D: DimName,
+    Refinement: RefineConnectivity<C>,
+    Refinement::VertexLabel: Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    refine_mesh_with_vertex_parents(mesh, refinement_scheme).mesh
+}
+
+/// Refine a mesh with the provided refinement scheme, and additionally return the index of the
+/// element in the original mesh that each element in the refined mesh was created from.
+///
+/// The parent map can be used to transfer per-element data, such as material parameters or
+/// quadrature tables, from the original mesh to the refined mesh.
+pub fn refine_mesh_with_parents<T, D, C, Refinement>(
+    mesh: &Mesh<T, D, C>,
+    refinement_scheme: Refinement,
+) -> (Mesh<T, D, Refinement::OutputConnectivity>, Vec<usize>)
+where
+    T: RealField,
+    D: DimName,
+    Refinement: RefineConnectivity<C>,
+    Refinement::VertexLabel: Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    let refined = refine_mesh_with_vertex_parents(mesh, refinement_scheme);
+    (refined.mesh, refined.element_parents)
+}
+
+struct RefinedMesh<T, D, C>
+where
+    T: RealField,
+    D: DimName,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    mesh: Mesh<T, D, C>,
+    vertex_parents: Vec<usize>,
+    element_parents: Vec<usize>,
+}
+
+/// Refine a mesh with the provided refinement scheme, and additionally return the index of the
+/// element in the original mesh that each vertex and each element in the refined mesh was created from.
+fn refine_mesh_with_vertex_parents<T, D, C, Refinement>(
+    mesh: &Mesh<T, D, C>,
+    refinement_scheme: Refinement,
+) -> RefinedMesh<T, D, Refinement::OutputConnectivity>
 where
     T: RealField,
     D: DimName,
@@ -72,6 +142,8 @@ where
 {
     let mut label_to_idx_map = HashMap::new();
     let mut next_vertex_idx = 0;
+    let mut vertex_parents = Vec::new();
+    let mut element_parents = Vec::new();
 
     let mut new_connectivity = Vec::new();
 
@@ -79,7 +151,7 @@ where
     let mut intermediates = Vec::new();
     let mut vertex_labels = Vec::new();
     let mut new_vertex_indices = Vec::new();
-    for connectivity in mesh.connectivity() {
+    for (element_idx, connectivity) in mesh.connectivity().iter().enumerate() {
         new_vertex_indices.clear();
         intermediates.clear();
         refinement_scheme.populate_refined_connectivity(&connectivity, &mut intermediates);
@@ -91,6 +163,7 @@ where
                 let idx = label_to_idx_map.entry(label.clone()).or_insert_with(|| {
                     let idx = next_vertex_idx;
                     next_vertex_idx += 1;
+                    vertex_parents.push(element_idx);
                     idx
                 });
                 new_vertex_indices.push(*idx);
@@ -99,6 +172,7 @@ where
                 .construct_output_connectivity(&intermediate, &new_vertex_indices)
                 .expect("Must succeed since vertex label count is consistent with vertex index count");
             new_connectivity.push(new_cell_connectivity);
+            element_parents.push(element_idx);
         }
     }
 
@@ -107,7 +181,87 @@ where
         let vertex = label.construct_vertex(mesh.vertices());
         new_vertices[index] = vertex;
     }
-    Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity)
+    RefinedMesh {
+        mesh: Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity),
+        vertex_parents,
+        element_parents,
+    }
+}
+
+/// Refine a mesh with the provided refinement scheme, and compute the prolongation operator
+/// from the original mesh to the refined mesh.
+///
+/// The row of the prolongation matrix $\vec P$ associated with a node in the refined mesh
+/// contains the basis functions of the element it was created from, evaluated at the node.
+/// Consequently, if the finite element space on the refined mesh contains the space on the
+/// original mesh, as is the case for the schemes provided here, $\vec P$ exactly reproduces
+/// finite element functions on the original mesh. Nodal values are interleaved with
+/// `solution_dim` components per node, and $\vec P$ is expanded accordingly.
+///
+/// See the [module-level documentation](self) for how to use $\vec P$ for inter-grid transfer.
+///
+/// # Errors
+///
+/// Returns an error if the nodes of the refined mesh cannot be mapped to reference coordinates
+/// of the element they were created from, which may happen for degenerate elements.
+pub fn refine_mesh_with_prolongation<T, D, C, Refinement>(
+    mesh: &Mesh<T, D, C>,
+    refinement_scheme: Refinement,
+    solution_dim: usize,
+) -> eyre::Result<(Mesh<T, D, Refinement::OutputConnectivity>, CsrMatrix<T>)>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    Refinement: RefineConnectivity<C>,
+    Refinement::VertexLabel: Eq + Hash,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let RefinedMesh {
+        mesh: refined_mesh,
+        vertex_parents,
+        ..
+    } = refine_mesh_with_vertex_parents(mesh, refinement_scheme);
+    let mut prolongation = CooMatrix::new(
+        solution_dim * refined_mesh.vertices().len(),
+        solution_dim * mesh.vertices().len(),
+    );
+
+    // Basis functions that vanish at a node only evaluate to zero up to round-off errors in the
+    // reference coordinates, so we drop values that are negligible
+    let threshold = T::default_epsilon().sqrt();
+    let mut basis_values = Vec::new();
+    for (vertex_idx, (vertex, &parent_idx)) in refined_mesh
+        .vertices()
+        .iter()
+        .zip(&vertex_parents)
+        .enumerate()
+    {
+        let parent = &mesh.connectivity()[parent_idx];
+        let element = parent
+            .element(mesh.vertices())
+            .ok_or_else(|| eyre!("failed to construct element {}", parent_idx))?;
+        let xi = map_physical_coordinates(&element, vertex).map_err(|err| {
+            eyre!(
+                "failed to map vertex {} to reference coordinates of element {}: {}",
+                vertex_idx,
+                parent_idx,
+                err
+            )
+        })?;
+        basis_values.resize(element.num_nodes(), T::zero());
+        element.populate_basis(&mut basis_values, &xi);
+
+        for (&node_idx, &phi) in parent.vertex_indices().iter().zip(&basis_values) {
+            if phi.abs() > threshold {
+                for i in 0..solution_dim {
+                    prolongation.push(solution_dim * vertex_idx + i, solution_dim * node_idx + i, phi);
+                }
+            }
+        }
+    }
+
+    Ok((refined_mesh, CsrMatrix::from(&prolongation)))
 }
 
 /// Apply one round of uniform mesh refinement.
@@ -124,6 +278,42 @@ where
     refine_mesh(mesh, UniformRefinement)
 }
 
+/// Apply one round of uniform mesh refinement, and return the index of the original element
+/// that each refined element was created from.
+///
+/// This is a convenience function for `refine_mesh_with_parents(mesh, UniformRefinement)`.
+/// Since each element is split into $2^d$ children, the parent map contains each original element
+/// index exactly $2^d$ times.
+pub fn refine_uniformly_with_parents<T, D, C>(mesh: &Mesh<T, D, C>) -> (Mesh<T, D, C>, Vec<usize>)
+where
+    T: RealField,
+    D: DimName,
+    UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+    <UniformRefinement as RefineConnectivity<C>>::VertexLabel: Eq + Hash,
+    DefaultAllocator: DimAllocator<T, D>,
+{
+    refine_mesh_with_parents(mesh, UniformRefinement)
+}
+
+/// Apply one round of uniform mesh refinement, and compute the prolongation operator.
+///
+/// This is a convenience function for
+/// `refine_mesh_with_prolongation(mesh, UniformRefinement, solution_dim)`.
+pub fn refine_uniformly_with_prolongation<T, D, C>(
+    mesh: &Mesh<T, D, C>,
+    solution_dim: usize,
+) -> eyre::Result<(Mesh<T, D, C>, CsrMatrix<T>)>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+    <UniformRefinement as RefineConnectivity<C>>::VertexLabel: Eq + Hash,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    refine_mesh_with_prolongation(mesh, UniformRefinement, solution_dim)
+}
+
 /// Repeatedly applies uniform mesh refinement to the given mesh.
 pub fn refine_uniformly_repeat<T, D, C>(mesh: &Mesh<T, D, C>, repeat_times: usize) -> Mesh<T, D, C>
 where
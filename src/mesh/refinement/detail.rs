@@ -1,7 +1,10 @@
 //! Lower level details for refinement abstractions.
 
 use crate::allocators::DimAllocator;
-use crate::connectivity::Tri3d2Connectivity;
+use crate::connectivity::{
+    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity, Tri6d2Connectivity,
+};
 use crate::mesh::refinement::{InvalidVertexCount, RefineConnectivity, UniformRefinement, VertexRepresentation};
 use core::cmp::{max, min};
 use core::hash::{Hash, Hasher};
@@ -141,3 +144,271 @@ impl RefineConnectivity<Tri3d2Connectivity> for UniformRefinement {
         ))
     }
 }
+
+/// A label for a vertex defined as the centroid of `N` existing vertices.
+#[derive(Debug, Copy, Clone, Eq)]
+pub struct CentroidLabel<const N: usize>(pub [usize; N]);
+
+impl<const N: usize> CentroidLabel<N> {
+    fn canonical_vertex_indices(&self) -> [usize; N] {
+        let mut indices = self.0;
+        indices.sort_unstable();
+        indices
+    }
+}
+
+impl<const N: usize> VertexRepresentation for CentroidLabel<N> {
+    fn construct_vertex<T, D>(&self, all_vertices: &[OPoint<T, D>]) -> OPoint<T, D>
+    where
+        T: RealField,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let sum = self
+            .0
+            .iter()
+            .fold(OPoint::origin().coords, |sum, &idx| sum + &all_vertices[idx].coords);
+        OPoint::from(sum / T::from_subset(&(N as f64)))
+    }
+}
+
+impl<const N: usize> PartialEq for CentroidLabel<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical_vertex_indices() == other.canonical_vertex_indices()
+    }
+}
+
+impl<const N: usize> Hash for CentroidLabel<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_vertex_indices().hash(state)
+    }
+}
+
+/// A vertex label for the refinement of general (linear or quadratic) elements.
+///
+/// New vertices are placed at the centroid of two (edge midpoints), four (face centroids)
+/// or eight (cell centroids) existing vertices.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CentroidVertexLabel {
+    Vertex(VertexLabel),
+    EdgeMidpoint(EdgeMidpointLabel),
+    FaceCentroid(CentroidLabel<4>),
+    CellCentroid(CentroidLabel<8>),
+}
+
+impl CentroidVertexLabel {
+    /// Constructs a label for the centroid of the given (global) vertices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of vertices is not 1, 2, 4 or 8.
+    fn from_vertices(vertices: &[usize]) -> Self {
+        match *vertices {
+            [a] => Self::Vertex(vertex(a)),
+            [a, b] => Self::EdgeMidpoint(edge_midpoint([a, b])),
+            [a, b, c, d] => Self::FaceCentroid(CentroidLabel([a, b, c, d])),
+            _ => Self::CellCentroid(CentroidLabel(
+                vertices
+                    .try_into()
+                    .expect("Centroid must be defined by 1, 2, 4 or 8 vertices"),
+            )),
+        }
+    }
+}
+
+impl VertexRepresentation for CentroidVertexLabel {
+    fn construct_vertex<T, D>(&self, all_vertices: &[OPoint<T, D>]) -> OPoint<T, D>
+    where
+        T: RealField,
+        D: DimName,
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        match self {
+            Self::Vertex(label) => label.construct_vertex(all_vertices),
+            Self::EdgeMidpoint(label) => label.construct_vertex(all_vertices),
+            Self::FaceCentroid(label) => label.construct_vertex(all_vertices),
+            Self::CellCentroid(label) => label.construct_vertex(all_vertices),
+        }
+    }
+}
+
+/// An intermediate connectivity with `N` nodes, each given by a [`CentroidVertexLabel`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IntermediateConnectivity<const N: usize>([CentroidVertexLabel; N]);
+
+// Uniform refinement of linear and quadratic elements is described in terms of the nodes of the
+// quadratic element in each element family: for each quadratic node, we list the vertices of the
+// linear element that the node is the centroid of, and the children in the refinement are given
+// by their vertices in terms of quadratic nodes. This way, the children of a linear element
+// are defined in terms of centroids of the vertices of the parent element, whereas the
+// vertices of the children of a quadratic element are precisely the nodes of the parent element.
+
+const TRI6_NODE_VERTICES: [&[usize]; 6] = [&[0], &[1], &[2], &[0, 1], &[1, 2], &[0, 2]];
+const TRI_CHILDREN: [[usize; 3]; 4] = [[0, 3, 5], [3, 1, 4], [5, 4, 2], [3, 4, 5]];
+
+const QUAD9_NODE_VERTICES: [&[usize]; 9] = [
+    &[0],
+    &[1],
+    &[2],
+    &[3],
+    &[0, 1],
+    &[1, 2],
+    &[2, 3],
+    &[3, 0],
+    &[0, 1, 2, 3],
+];
+const QUAD_CHILDREN: [[usize; 4]; 4] = [[0, 4, 8, 7], [4, 1, 5, 8], [8, 5, 2, 6], [7, 8, 6, 3]];
+
+const TET10_NODE_VERTICES: [&[usize]; 10] = [
+    &[0],
+    &[1],
+    &[2],
+    &[3],
+    &[0, 1],
+    &[1, 2],
+    &[0, 2],
+    &[0, 3],
+    &[2, 3],
+    &[1, 3],
+];
+// The four corner tetrahedra, followed by the four tetrahedra obtained by splitting the
+// interior octahedron along the diagonal between the midpoints of edges (0, 2) and (1, 3)
+const TET_CHILDREN: [[usize; 4]; 8] = [
+    [0, 4, 6, 7],
+    [4, 1, 5, 9],
+    [6, 5, 2, 8],
+    [7, 9, 8, 3],
+    [6, 9, 4, 5],
+    [6, 9, 5, 8],
+    [6, 9, 8, 7],
+    [6, 9, 7, 4],
+];
+
+const HEX27_NODE_VERTICES: [&[usize]; 27] = [
+    &[0],
+    &[1],
+    &[2],
+    &[3],
+    &[4],
+    &[5],
+    &[6],
+    &[7],
+    &[0, 1],
+    &[0, 3],
+    &[0, 4],
+    &[1, 2],
+    &[1, 5],
+    &[2, 3],
+    &[2, 6],
+    &[3, 7],
+    &[4, 5],
+    &[4, 7],
+    &[5, 6],
+    &[6, 7],
+    &[0, 1, 2, 3],
+    &[0, 1, 4, 5],
+    &[0, 3, 4, 7],
+    &[1, 2, 5, 6],
+    &[2, 3, 6, 7],
+    &[4, 5, 6, 7],
+    &[0, 1, 2, 3, 4, 5, 6, 7],
+];
+const HEX_CHILDREN: [[usize; 8]; 8] = [
+    [0, 8, 20, 9, 10, 21, 26, 22],
+    [8, 1, 11, 20, 21, 12, 23, 26],
+    [9, 20, 13, 3, 22, 26, 24, 15],
+    [20, 11, 2, 13, 26, 23, 14, 24],
+    [10, 21, 26, 22, 4, 16, 25, 17],
+    [21, 12, 23, 26, 16, 5, 18, 25],
+    [22, 26, 24, 15, 17, 25, 19, 7],
+    [26, 23, 14, 24, 25, 18, 6, 19],
+];
+
+/// Constructs the children of a linear element with the given (global) vertices.
+fn populate_linear_children<const N: usize>(
+    vertices: &[usize; N],
+    quadratic_node_vertices: &[&[usize]],
+    children: &[[usize; N]],
+    intermediates: &mut Vec<IntermediateConnectivity<N>>,
+) {
+    let mut global_vertices = Vec::new();
+    intermediates.extend(children.iter().map(|child| {
+        IntermediateConnectivity(child.map(|node| {
+            global_vertices.clear();
+            global_vertices.extend(quadratic_node_vertices[node].iter().map(|&v| vertices[v]));
+            CentroidVertexLabel::from_vertices(&global_vertices)
+        }))
+    }));
+}
+
+/// Constructs the children of a quadratic element with the given (global) nodes.
+fn populate_quadratic_children<const NV: usize, const N: usize>(
+    nodes: &[usize; N],
+    quadratic_node_vertices: &[&[usize]; N],
+    children: &[[usize; NV]],
+    intermediates: &mut Vec<IntermediateConnectivity<N>>,
+) {
+    let mut global_vertices = Vec::new();
+    intermediates.extend(children.iter().map(|child| {
+        IntermediateConnectivity(quadratic_node_vertices.map(|child_vertices| {
+            global_vertices.clear();
+            global_vertices.extend(child_vertices.iter().map(|&v| nodes[child[v]]));
+            CentroidVertexLabel::from_vertices(&global_vertices)
+        }))
+    }));
+}
+
+macro_rules! impl_uniform_refinement {
+    ($connectivity:ident, $num_nodes:expr, |$nodes:ident, $intermediates:ident| $populate:expr) => {
+        impl RefineConnectivity<$connectivity> for UniformRefinement {
+            type Intermediate = IntermediateConnectivity<$num_nodes>;
+            type OutputConnectivity = $connectivity;
+            type VertexLabel = CentroidVertexLabel;
+
+            fn populate_refined_connectivity(
+                &self,
+                connectivity: &$connectivity,
+                $intermediates: &mut Vec<Self::Intermediate>,
+            ) {
+                let $nodes = &connectivity.0;
+                $populate
+            }
+
+            fn populate_vertex_labels(&self, intermediate: &Self::Intermediate, labels: &mut Vec<Self::VertexLabel>) {
+                labels.extend_from_slice(&intermediate.0);
+            }
+
+            fn construct_output_connectivity(
+                &self,
+                _intermediate: &Self::Intermediate,
+                vertex_indices: &[usize],
+            ) -> Result<Self::OutputConnectivity, InvalidVertexCount> {
+                Ok($connectivity(
+                    vertex_indices.try_into().map_err(|_| InvalidVertexCount)?,
+                ))
+            }
+        }
+    };
+}
+
+impl_uniform_refinement!(Quad4d2Connectivity, 4, |nodes, intermediates| {
+    populate_linear_children(nodes, &QUAD9_NODE_VERTICES, &QUAD_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Tet4Connectivity, 4, |nodes, intermediates| {
+    populate_linear_children(nodes, &TET10_NODE_VERTICES, &TET_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Hex8Connectivity, 8, |nodes, intermediates| {
+    populate_linear_children(nodes, &HEX27_NODE_VERTICES, &HEX_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Tri6d2Connectivity, 6, |nodes, intermediates| {
+    populate_quadratic_children(nodes, &TRI6_NODE_VERTICES, &TRI_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Quad9d2Connectivity, 9, |nodes, intermediates| {
+    populate_quadratic_children(nodes, &QUAD9_NODE_VERTICES, &QUAD_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Tet10Connectivity, 10, |nodes, intermediates| {
+    populate_quadratic_children(nodes, &TET10_NODE_VERTICES, &TET_CHILDREN, intermediates)
+});
+impl_uniform_refinement!(Hex27Connectivity, 27, |nodes, intermediates| {
+    populate_quadratic_children(nodes, &HEX27_NODE_VERTICES, &HEX_CHILDREN, intermediates)
+});
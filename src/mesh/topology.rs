@@ -0,0 +1,168 @@
+//! Face topology and element adjacency of meshes.
+use crate::connectivity::Connectivity;
+use crate::mesh::Mesh;
+use fenris_nested_vec::NestedVec;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, Scalar};
+use std::collections::HashMap;
+
+/// The faces of a mesh and the adjacency relations between its elements.
+///
+/// The local faces of each element are given by the
+/// [face connectivity](Connectivity::get_face_connectivity) of the element, and two local faces
+/// are considered to be the same face if they refer to the same set of vertices. Each unique
+/// face is represented by its vertex indices in ascending order, and faces are numbered in the
+/// order in which they first appear as a local face, in order of increasing element index.
+///
+/// A face shared by exactly two elements is an interior face, and the two elements are
+/// neighbors across the face. A face that belongs to a single element is a boundary face.
+/// For non-manifold meshes, faces may belong to more than two elements. Such faces are neither
+/// interior nor boundary faces, and the elements do not have a neighbor across them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshTopology {
+    faces: NestedVec<usize>,
+    face_incidences: NestedVec<(usize, usize)>,
+    element_faces: NestedVec<usize>,
+    element_neighbors: NestedVec<Option<usize>>,
+    boundary_faces: Vec<(usize, usize)>,
+}
+
+impl MeshTopology {
+    /// Computes the face topology of the given mesh.
+    pub fn from_mesh<T, D, C>(mesh: &Mesh<T, D, C>) -> Self
+    where
+        T: Scalar,
+        D: DimName,
+        C: Connectivity,
+        DefaultAllocator: Allocator<T, D>,
+    {
+        let mut faces = NestedVec::new();
+        let mut incidences: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut element_faces = NestedVec::new();
+        let mut face_indices = HashMap::new();
+        let mut face_vertices = Vec::new();
+
+        for (element_index, element) in mesh.connectivity().iter().enumerate() {
+            let mut element_face_appender = element_faces.begin_array();
+            for local_index in 0..element.num_faces() {
+                let face = element
+                    .get_face_connectivity(local_index)
+                    .expect("Local face index is in bounds");
+                face_vertices.clear();
+                face_vertices.extend_from_slice(face.vertex_indices());
+                face_vertices.sort_unstable();
+
+                let face_index = *face_indices
+                    .entry(face_vertices.clone())
+                    .or_insert_with(|| {
+                        faces.push(&face_vertices);
+                        incidences.push(Vec::new());
+                        incidences.len() - 1
+                    });
+                incidences[face_index].push((element_index, local_index));
+                element_face_appender.push_single(face_index);
+            }
+        }
+
+        let mut element_neighbors = NestedVec::new();
+        for (element_index, element_face_indices) in element_faces.iter().enumerate() {
+            let mut appender = element_neighbors.begin_array();
+            for &face_index in element_face_indices {
+                let neighbor = match incidences[face_index].as_slice() {
+                    &[(a, _), (b, _)] => Some(if a == element_index { b } else { a }),
+                    _ => None,
+                };
+                appender.push_single(neighbor);
+            }
+        }
+
+        let boundary_faces = incidences
+            .iter()
+            .filter_map(|incidence| match incidence.as_slice() {
+                &[element_and_local_face] => Some(element_and_local_face),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            faces,
+            face_incidences: NestedVec::from(incidences),
+            element_faces,
+            element_neighbors,
+            boundary_faces,
+        }
+    }
+
+    /// The number of elements in the mesh.
+    pub fn num_elements(&self) -> usize {
+        self.element_faces.len()
+    }
+
+    /// The number of unique faces in the mesh.
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// The vertex indices of the given face, sorted in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the face index is out of bounds.
+    pub fn face_vertices(&self, face_index: usize) -> &[usize] {
+        self.faces
+            .get(face_index)
+            .expect("Face index out of bounds")
+    }
+
+    /// The elements incident to the given face, as pairs of element index and local face index,
+    /// in order of increasing element index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the face index is out of bounds.
+    pub fn face_elements(&self, face_index: usize) -> &[(usize, usize)] {
+        self.face_incidences
+            .get(face_index)
+            .expect("Face index out of bounds")
+    }
+
+    /// Returns `true` if the given face belongs to a single element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the face index is out of bounds.
+    pub fn is_boundary_face(&self, face_index: usize) -> bool {
+        self.face_elements(face_index).len() == 1
+    }
+
+    /// The face indices of the local faces of the given element, ordered by local face index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_faces(&self, element_index: usize) -> &[usize] {
+        self.element_faces
+            .get(element_index)
+            .expect("Element index out of bounds")
+    }
+
+    /// The neighbors of the given element across each of its local faces, ordered by local face
+    /// index.
+    ///
+    /// The neighbor is `None` for boundary faces and for faces shared by more than two elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element index is out of bounds.
+    pub fn element_neighbors(&self, element_index: usize) -> &[Option<usize>] {
+        self.element_neighbors
+            .get(element_index)
+            .expect("Element index out of bounds")
+    }
+
+    /// The boundary faces of the mesh, as pairs of element index and local face index,
+    /// in order of increasing element index and local face index.
+    pub fn boundary_faces(&self) -> &[(usize, usize)] {
+        &self.boundary_faces
+    }
+}
@@ -0,0 +1,331 @@
+//! Validation of mesh connectivity and element geometry.
+//!
+//! Problems in meshes, such as connectivity referring to vertices that do not exist or inverted
+//! elements, otherwise often only surface as panics or NaNs deep inside assembly.
+use crate::allocators::BiDimAllocator;
+use crate::connectivity::{ConnectivityMut, Tet4Connectivity, Tri3d2Connectivity};
+use crate::element::{ElementConnectivity, FiniteElement, Hex8Element, Quad4d2Element, Tet4Element, Tri3d2Element};
+use crate::mesh::Mesh;
+use crate::nalgebra::{DefaultAllocator, OPoint, OVector, Point2, Point3};
+use crate::{Real, SmallDim};
+use itertools::Itertools;
+use numeric_literals::replace_float_literals;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Elements whose orientation and volume can be validated.
+pub trait ValidateElement<T: Real> {
+    /// The signed volume (area in 2D) of the element.
+    ///
+    /// The signed volume is negative for inverted simplices.
+    fn signed_volume(&self) -> T;
+
+    /// Determines whether the element is positively oriented.
+    ///
+    /// Simplices are positively oriented if their signed volume is positive, and tensor-product
+    /// elements are positively oriented if the Jacobian determinant is positive at every corner.
+    fn is_positively_oriented(&self) -> bool;
+}
+
+/// Simplex connectivities whose orientation can be reversed by permuting their vertices.
+pub trait ReverseOrientation: ConnectivityMut {
+    /// Reverses the orientation of the simplex.
+    fn reverse_orientation(&mut self);
+}
+
+/// A report of the problems found by [`validate`].
+///
+/// Every field lists the indices of the offending elements or vertices in increasing order.
+/// All problems except unreferenced vertices are considered errors, see [`is_valid`](Self::is_valid).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MeshValidationReport {
+    /// Elements that refer to vertices that are not in the mesh.
+    pub out_of_bounds_elements: Vec<usize>,
+    /// Elements with the same set of vertices as an element with lower index.
+    pub duplicate_elements: Vec<usize>,
+    /// Elements that are not positively oriented.
+    pub inverted_elements: Vec<usize>,
+    /// Elements whose volume is (nearly) zero.
+    pub degenerate_elements: Vec<usize>,
+    /// Vertices that are not referenced by any element.
+    pub unreferenced_vertices: Vec<usize>,
+}
+
+impl MeshValidationReport {
+    /// Computes a report of all problems in the mesh, using the
+    /// [default degeneracy tolerance](default_degeneracy_tolerance).
+    ///
+    /// See [`from_mesh_with_tolerance`](Self::from_mesh_with_tolerance).
+    pub fn from_mesh<T, D, C>(mesh: &Mesh<T, D, C>) -> Self
+    where
+        T: Real,
+        D: SmallDim,
+        C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+        C::Element: ValidateElement<T>,
+        DefaultAllocator: BiDimAllocator<T, D, D>,
+    {
+        Self::from_mesh_with_tolerance(mesh, default_degeneracy_tolerance())
+    }
+
+    /// Computes a report of all problems in the mesh with the given degeneracy tolerance.
+    ///
+    /// The following problems are detected:
+    ///
+    /// - elements that refer to vertices that are not in the mesh,
+    /// - elements with the same set of vertices as a previous element,
+    /// - elements that are not positively oriented,
+    /// - degenerate elements, whose absolute volume is at most `degeneracy_tolerance` $\cdot h^d$,
+    ///   where $h$ is the diameter of the element and $d$ the dimension,
+    /// - vertices that are not referenced by any element.
+    ///
+    /// Elements with out-of-bounds vertex indices are not checked for further geometric problems,
+    /// and degenerate elements are not additionally reported as inverted.
+    pub fn from_mesh_with_tolerance<T, D, C>(mesh: &Mesh<T, D, C>, degeneracy_tolerance: T) -> Self
+    where
+        T: Real,
+        D: SmallDim,
+        C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+        C::Element: ValidateElement<T>,
+        DefaultAllocator: BiDimAllocator<T, D, D>,
+    {
+        let num_vertices = mesh.vertices().len();
+        let mut report = MeshValidationReport::default();
+        let mut referenced = vec![false; num_vertices];
+        let mut seen_vertex_sets = HashSet::new();
+
+        for (element_index, connectivity) in mesh.connectivity().iter().enumerate() {
+            let indices = connectivity.vertex_indices();
+            if indices.iter().any(|&index| index >= num_vertices) {
+                report.out_of_bounds_elements.push(element_index);
+                continue;
+            }
+            for &index in indices {
+                referenced[index] = true;
+            }
+
+            let key = indices.iter().copied().sorted_unstable().collect_vec();
+            if !seen_vertex_sets.insert(key) {
+                report.duplicate_elements.push(element_index);
+            }
+
+            let element = connectivity
+                .element(mesh.vertices())
+                .expect("Vertex indices are in bounds");
+            let threshold = degeneracy_tolerance * element.diameter().powi(D::dim() as i32);
+            if element.signed_volume().abs() <= threshold {
+                report.degenerate_elements.push(element_index);
+            } else if !element.is_positively_oriented() {
+                report.inverted_elements.push(element_index);
+            }
+        }
+
+        report.unreferenced_vertices = referenced
+            .iter()
+            .positions(|&is_referenced| !is_referenced)
+            .collect();
+        report
+    }
+
+    /// Determines whether the report contains any errors.
+    ///
+    /// Unreferenced vertices are not considered errors.
+    pub fn is_valid(&self) -> bool {
+        self.out_of_bounds_elements.is_empty()
+            && self.duplicate_elements.is_empty()
+            && self.inverted_elements.is_empty()
+            && self.degenerate_elements.is_empty()
+    }
+
+    /// Determines whether no problems at all were found, including unreferenced vertices.
+    pub fn is_empty(&self) -> bool {
+        self.is_valid() && self.unreferenced_vertices.is_empty()
+    }
+}
+
+impl Display for MeshValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "mesh validation found no problems");
+        }
+        write!(f, "mesh validation found problems:")?;
+        let problems = [
+            (
+                "elements with out-of-bounds vertex indices",
+                &self.out_of_bounds_elements,
+            ),
+            ("duplicate elements", &self.duplicate_elements),
+            ("inverted elements", &self.inverted_elements),
+            ("degenerate elements", &self.degenerate_elements),
+            ("unreferenced vertices, not an error", &self.unreferenced_vertices),
+        ];
+        for (description, indices) in problems {
+            if !indices.is_empty() {
+                write!(
+                    f,
+                    "\n  {} ({}): [{}]",
+                    description,
+                    indices.len(),
+                    indices.iter().join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for MeshValidationReport {}
+
+/// The default relative volume threshold below which elements are considered degenerate.
+///
+/// See [`MeshValidationReport::from_mesh_with_tolerance`].
+pub fn default_degeneracy_tolerance<T: Real>() -> T {
+    T::default_epsilon().sqrt()
+}
+
+/// Validates the connectivity and element geometry of the mesh.
+///
+/// Returns an error with a report of all problems, including unreferenced vertices, if any problem
+/// other than unreferenced vertices is found. Use [`MeshValidationReport::from_mesh`] to obtain
+/// the report unconditionally. See [`MeshValidationReport::from_mesh_with_tolerance`] for
+/// the problems that are detected.
+pub fn validate<T, D, C>(mesh: &Mesh<T, D, C>) -> Result<(), MeshValidationReport>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D>,
+    C::Element: ValidateElement<T>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let report = MeshValidationReport::from_mesh(mesh);
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Reverses the orientation of every simplex in the mesh with negative signed volume.
+///
+/// Returns the indices of the reoriented elements.
+///
+/// # Panics
+///
+/// Panics if an element refers to vertices that are not in the mesh.
+pub fn fix_orientation<T, D, C>(mesh: &mut Mesh<T, D, C>) -> Vec<usize>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D, ReferenceDim = D> + ReverseOrientation,
+    C::Element: ValidateElement<T>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let mut reoriented = Vec::new();
+    for (element_index, connectivity) in mesh.connectivity.iter_mut().enumerate() {
+        let element = connectivity
+            .element(&mesh.vertices)
+            .expect("Connectivity must refer to vertices in the mesh");
+        if element.signed_volume() < T::zero() {
+            connectivity.reverse_orientation();
+            reoriented.push(element_index);
+        }
+    }
+    reoriented
+}
+
+impl ReverseOrientation for Tri3d2Connectivity {
+    fn reverse_orientation(&mut self) {
+        self.0.swap(1, 2);
+    }
+}
+
+impl ReverseOrientation for Tet4Connectivity {
+    fn reverse_orientation(&mut self) {
+        self.0.swap(1, 2);
+    }
+}
+
+/// Computes the volume of a tensor-product element with the 2-point Gauss rule in each direction,
+/// which is exact for bilinear and trilinear elements.
+fn tensor_product_volume<T, D, Element>(element: &Element) -> T
+where
+    T: Real,
+    D: SmallDim,
+    Element: FiniteElement<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    let a = T::one() / T::from_f64(3.0).unwrap().sqrt();
+    let num_points = 1 << D::dim();
+    (0..num_points)
+        .map(|point| {
+            let xi = OPoint::from(OVector::<T, D>::from_fn(
+                |i, _| {
+                    if point & (1 << i) == 0 {
+                        -a
+                    } else {
+                        a
+                    }
+                },
+            ));
+            element.reference_jacobian(&xi).determinant()
+        })
+        .fold(T::zero(), |sum, det| sum + det)
+}
+
+/// Determines whether the Jacobian determinant of the element is positive at all its corners.
+fn positive_at_corners<T, D, Element>(element: &Element, corners: &[OPoint<T, D>]) -> bool
+where
+    T: Real,
+    D: SmallDim,
+    Element: FiniteElement<T, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<T, D, D>,
+{
+    corners
+        .iter()
+        .all(|xi| element.reference_jacobian(xi).determinant() > T::zero())
+}
+
+impl<T: Real> ValidateElement<T> for Tri3d2Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn signed_volume(&self) -> T {
+        // The Jacobian is constant and the reference triangle has area 2
+        2.0 * self.reference_jacobian(&Point2::origin()).determinant()
+    }
+
+    fn is_positively_oriented(&self) -> bool {
+        self.signed_volume() > T::zero()
+    }
+}
+
+impl<T: Real> ValidateElement<T> for Tet4Element<T> {
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn signed_volume(&self) -> T {
+        // The Jacobian is constant and the reference tetrahedron has volume 4/3
+        (4.0 / 3.0) * self.reference_jacobian(&Point3::origin()).determinant()
+    }
+
+    fn is_positively_oriented(&self) -> bool {
+        self.signed_volume() > T::zero()
+    }
+}
+
+impl<T: Real> ValidateElement<T> for Quad4d2Element<T> {
+    fn signed_volume(&self) -> T {
+        tensor_product_volume(self)
+    }
+
+    fn is_positively_oriented(&self) -> bool {
+        positive_at_corners(self, Quad4d2Element::<T>::reference().vertices())
+    }
+}
+
+impl<T: Real> ValidateElement<T> for Hex8Element<T> {
+    fn signed_volume(&self) -> T {
+        tensor_product_volume(self)
+    }
+
+    fn is_positively_oriented(&self) -> bool {
+        positive_at_corners(self, Hex8Element::<T>::reference().vertices())
+    }
+}
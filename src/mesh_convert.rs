@@ -1,20 +1,19 @@
 use crate::connectivity::{
     Connectivity, ConnectivityMut, Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity,
-    Quad9d2Connectivity, Tet10Connectivity, Tet20Connectivity, Tet4Connectivity, Tri3d2Connectivity,
-    Tri6d2Connectivity,
+    Quad9d2Connectivity, Tet10Connectivity, Tet20Connectivity, Tet4Connectivity, Tri10d2Connectivity,
+    Tri3d2Connectivity, Tri6d2Connectivity,
 };
 use crate::element::{ElementConnectivity, FiniteElement};
-use crate::mesh::{HexMesh, Mesh, Mesh2d, Mesh3d, Tet20Mesh, Tet4Mesh};
+use crate::mesh::{HexMesh, Mesh, Mesh2d, Mesh3d, Tet20Mesh, Tet4Mesh, Tri10Mesh2d, TriangleMesh2d};
 use nalgebra::allocator::Allocator;
-use nalgebra::{DefaultAllocator, DimName, OPoint, Point2, Point3, Scalar, U3};
+use nalgebra::{DefaultAllocator, DimName, OPoint, Point2, Point3, Scalar, U2, U3};
 
 use crate::geometry::polymesh::{PolyMesh, PolyMesh3d};
 use crate::geometry::{OrientationTestResult, Triangle};
 use crate::Real;
 use fenris_nested_vec::NestedVec;
-use itertools::{izip, Itertools};
+use itertools::Itertools;
 use numeric_literals::replace_float_literals;
-use rustc_hash::FxHashMap;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::error::Error;
@@ -39,6 +38,97 @@ where
     ) -> Self;
 }
 
+impl<T> RefineFrom<T, U2, Tri3d2Connectivity> for Tri6d2Connectivity
+where
+    T: Real,
+    DefaultAllocator: Allocator<T, U2>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn refine(
+        connectivity: &Tri3d2Connectivity,
+        mesh_vertices: &[Point2<T>],
+        vertices: &mut Vec<Point2<T>>,
+        child_indices: &mut Vec<usize>,
+        parents: &mut NestedVec<usize>,
+    ) -> Self {
+        let global_indices = connectivity.vertex_indices();
+
+        // Add vertex nodes
+        for v_idx in global_indices {
+            parents.push(&[*v_idx]);
+            child_indices.push(0);
+            vertices.push(mesh_vertices[*v_idx].clone());
+        }
+
+        let mut add_edge_node = |v_local_begin, v_local_end| {
+            let v_global_begin = global_indices[v_local_begin];
+            let v_global_end = global_indices[v_local_end];
+            parents.push(&[v_global_begin, v_global_end]);
+            child_indices.push(0);
+            let (v_a, v_b) = (
+                mesh_vertices[v_global_begin].clone(),
+                mesh_vertices[v_global_end].clone(),
+            );
+            vertices.push(Point2::from((v_a.coords + v_b.coords) / 2.0));
+        };
+
+        add_edge_node(0, 1);
+        add_edge_node(1, 2);
+        add_edge_node(2, 0);
+
+        Tri6d2Connectivity([0, 1, 2, 3, 4, 5])
+    }
+}
+
+impl<T> RefineFrom<T, U2, Quad4d2Connectivity> for Quad9d2Connectivity
+where
+    T: Real,
+    DefaultAllocator: Allocator<T, U2>,
+{
+    #[replace_float_literals(T::from_f64(literal).unwrap())]
+    fn refine(
+        connectivity: &Quad4d2Connectivity,
+        mesh_vertices: &[Point2<T>],
+        vertices: &mut Vec<Point2<T>>,
+        child_indices: &mut Vec<usize>,
+        parents: &mut NestedVec<usize>,
+    ) -> Self {
+        let global_indices = connectivity.vertex_indices();
+
+        // Add vertex nodes
+        for v_idx in global_indices {
+            parents.push(&[*v_idx]);
+            child_indices.push(0);
+            vertices.push(mesh_vertices[*v_idx].clone());
+        }
+
+        let mut add_edge_node = |v_local_begin, v_local_end| {
+            let v_global_begin = global_indices[v_local_begin];
+            let v_global_end = global_indices[v_local_end];
+            parents.push(&[v_global_begin, v_global_end]);
+            child_indices.push(0);
+            let (v_a, v_b) = (
+                mesh_vertices[v_global_begin].clone(),
+                mesh_vertices[v_global_end].clone(),
+            );
+            vertices.push(Point2::from((v_a.coords + v_b.coords) / 2.0));
+        };
+
+        add_edge_node(0, 1);
+        add_edge_node(1, 2);
+        add_edge_node(2, 3);
+        add_edge_node(3, 0);
+
+        // Add center node
+        let element = connectivity.element(mesh_vertices).unwrap();
+        parents.push(global_indices);
+        child_indices.push(0);
+        vertices.push(element.map_reference_coords(&Point2::origin()));
+
+        Quad9d2Connectivity([0, 1, 2, 3, 4, 5, 6, 7, 8])
+    }
+}
+
 impl<T> RefineFrom<T, U3, Tet4Connectivity> for Tet10Connectivity
 where
     T: Real,
@@ -216,15 +306,49 @@ where
     }
 }
 
-/// Stop-gap solution for generalizing mesh conversion.
-///
-/// TODO: Remove this trait and use RefineFrom directly? Though this is not directly possible
-/// due to conflicting impls. At least find a better name though!
-pub trait FromTemp<T> {
-    fn from(obj: T) -> Self;
+/// Describes how a node in a mesh produced by [`p_refine_mesh`] relates to the vertices of the
+/// original mesh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeOrigin {
+    /// The node is the vertex with the given index in the original mesh.
+    Vertex(usize),
+    /// The node is the midpoint of the edge between the two given vertices.
+    EdgeMidpoint([usize; 2]),
+    /// The node is the centroid of the given vertices, which make up a face or cell.
+    Centroid(Vec<usize>),
+}
+
+impl NodeOrigin {
+    /// The vertices of the original mesh whose average gives the node.
+    ///
+    /// Since all nodes are placed at the average of their parent vertices, linear interpolation
+    /// of values at the parent vertices transfers a solution on the original mesh to the
+    /// refined mesh.
+    pub fn parent_vertices(&self) -> &[usize] {
+        match self {
+            Self::Vertex(vertex) => std::slice::from_ref(vertex),
+            Self::EdgeMidpoint(vertices) => vertices,
+            Self::Centroid(vertices) => vertices,
+        }
+    }
 }
 
-impl<'a, T, D, C, CNew> FromTemp<&'a Mesh<T, D, C>> for Mesh<T, D, CNew>
+/// Converts a mesh to a mesh of higher polynomial order (p-refinement).
+///
+/// Supported conversions are `Tri3 -> Tri6`, `Quad4 -> Quad9`, `Tet4 -> Tet10`, `Hex8 -> Hex20`
+/// and `Hex8 -> Hex27`. The vertices of the original mesh keep their indices, and new nodes are
+/// appended after them. Nodes on edges and faces shared between elements are only created once.
+/// New nodes are placed at the midpoints of edges and the centroids of faces and cells,
+/// so that the converted mesh has straight sides.
+///
+/// Returns the converted mesh together with the [origin](NodeOrigin) of every node in the
+/// converted mesh.
+///
+/// # Panics
+///
+/// Panics if the conversion produces more than one node for the same edge, face or cell,
+/// as is the case for e.g. `Tet4 -> Tet20`.
+pub fn p_refine_mesh<T, D, C, CNew>(mesh: &Mesh<T, D, C>) -> (Mesh<T, D, CNew>, Vec<NodeOrigin>)
 where
     T: Real,
     D: DimName,
@@ -232,153 +356,119 @@ where
     CNew: RefineFrom<T, D, C>,
     DefaultAllocator: Allocator<T, D>,
 {
-    fn from(mesh: &'a Mesh<T, D, C>) -> Self {
-        // Workspaces are used to hold only per-connectivity information,
-        // which is later transformed to global data
-        let mut child_indices_workspace = Vec::new();
-        let mut parents_workspace = NestedVec::new();
-        let mut vertices_workspace = Vec::new();
-
-        // Global intermediate data
-        let mut child_indices = Vec::new();
-        let mut parents = NestedVec::new();
-        let mut intermediate_vertices = Vec::new();
-
-        let mut new_connectivity = Vec::new();
-
-        // First construct intermediate data. This means that we basically just lay out the
-        // data from each local connectivity refinement linearly. Then in the next step,
-        // we label vertices, making sure to label equivalent vertices with the same index,
-        // before finally reconstructing connectivity.
-        for conn in mesh.connectivity() {
-            child_indices_workspace.clear();
-            parents_workspace.clear();
-            vertices_workspace.clear();
-
-            let mut new_conn = CNew::refine(
-                conn,
-                mesh.vertices(),
-                &mut vertices_workspace,
-                &mut child_indices_workspace,
-                &mut parents_workspace,
-            );
-
+    let mut vertices = mesh.vertices().to_vec();
+    let mut origins: Vec<_> = (0..vertices.len()).map(NodeOrigin::Vertex).collect();
+    let mut new_connectivity = Vec::with_capacity(mesh.connectivity().len());
+
+    // Maps the sorted parent vertices of edge, face and cell nodes to their index
+    let mut node_index_map = HashMap::new();
+
+    let mut child_indices_workspace = Vec::new();
+    let mut parents_workspace = NestedVec::new();
+    let mut vertices_workspace = Vec::new();
+
+    for conn in mesh.connectivity() {
+        child_indices_workspace.clear();
+        parents_workspace.clear();
+        vertices_workspace.clear();
+
+        let mut new_conn = CNew::refine(
+            conn,
+            mesh.vertices(),
+            &mut vertices_workspace,
+            &mut child_indices_workspace,
+            &mut parents_workspace,
+        );
+
+        for vertex_index in new_conn.vertex_indices_mut() {
+            let local_index = *vertex_index;
             assert_eq!(
-                child_indices_workspace.len(),
-                parents_workspace.len(),
-                "Invalid RefineFrom implementation: \
-                       Number of child indices and parent groups must be equal."
-            );
-            assert_eq!(
-                child_indices_workspace.len(),
-                vertices_workspace.len(),
-                "Invalid RefineFrom implementation: \
-                       Number of child indices and vertices must be equal."
+                child_indices_workspace[local_index], 0,
+                "p-refinement only supports a single node per edge, face or cell"
             );
+            let mut vertex_parents = parents_workspace.get(local_index).unwrap().to_vec();
+            vertex_parents.sort_unstable();
+
+            *vertex_index = match vertex_parents.as_slice() {
+                &[vertex] => vertex,
+                _ => *node_index_map
+                    .entry(vertex_parents)
+                    .or_insert_with_key(|vertex_parents| {
+                        let index = vertices.len();
+                        vertices.push(vertices_workspace[local_index].clone());
+                        origins.push(match vertex_parents.as_slice() {
+                            &[a, b] => NodeOrigin::EdgeMidpoint([a, b]),
+                            _ => NodeOrigin::Centroid(vertex_parents.clone()),
+                        });
+                        index
+                    }),
+            };
+        }
 
-            let intermediate_vertex_index_offset = child_indices.len();
+        new_connectivity.push(new_conn);
+    }
 
-            intermediate_vertices.extend_from_slice(&vertices_workspace);
-            child_indices.extend_from_slice(&child_indices_workspace);
-            for parent_group in parents_workspace.iter() {
-                parents.push(parent_group);
-                // TODO: Sort here or in impl? Might as well do it here I guess?
-                parents.last_mut().unwrap().sort_unstable();
-            }
+    (
+        Mesh::from_vertices_and_connectivity(vertices, new_connectivity),
+        origins,
+    )
+}
 
-            // Vertex indices are local with respect to the returned new vertices.
-            // By adding the offset, the connectivity holds global intermediate indices
-            for v_idx in new_conn.vertex_indices_mut() {
-                *v_idx += intermediate_vertex_index_offset;
-            }
+/// Converts a mesh to a mesh of lower polynomial order by dropping higher-order nodes.
+///
+/// This is the inverse of [`p_refine_mesh`], and supports `Tri6 -> Tri3`, `Tri10 -> Tri3`,
+/// `Quad9 -> Quad4`, `Tet10 -> Tet4`, `Tet20 -> Tet4`, `Hex20 -> Hex8` and `Hex27 -> Hex8`. Nodes that are no longer referenced
+/// by any element are removed, and the remaining nodes keep their relative order.
+///
+/// Returns the converted mesh together with the index of the node in the original mesh
+/// for every vertex of the converted mesh.
+pub fn p_coarsen_mesh<T, D, C, CNew>(mesh: &Mesh<T, D, C>) -> (Mesh<T, D, CNew>, Vec<usize>)
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    CNew: ConnectivityMut + for<'a> From<&'a C>,
+    DefaultAllocator: Allocator<T, D>,
+{
+    let mut new_connectivity: Vec<_> = mesh.connectivity().iter().map(CNew::from).collect();
 
-            new_connectivity.push(new_conn);
+    let mut is_referenced = vec![false; mesh.vertices().len()];
+    for conn in &new_connectivity {
+        for &index in conn.vertex_indices() {
+            is_referenced[index] = true;
         }
-
-        // Map (child index, parents) to final vertex index
-        let mut vertex_label_map = FxHashMap::default();
-        let mut final_vertices = Vec::new();
-        let mut next_available_vertex_index = 0;
-
-        // Rewrite connectivity and label vertices, making sure to collect
-        // vertices with the same child index and parents under the same label
-        for conn in &mut new_connectivity {
-            for vertex_index in conn.vertex_indices_mut() {
-                let vertex_parents = parents.get(*vertex_index).unwrap();
-                let vertex_child_index = child_indices[*vertex_index];
-
-                // TODO: Avoid double lookup
-                let key = (vertex_child_index, vertex_parents);
-                let final_vertex_index = if vertex_label_map.contains_key(&key) {
-                    *vertex_label_map.get(&key).unwrap()
-                } else {
-                    let vertex = intermediate_vertices[*vertex_index].clone();
-                    final_vertices.push(vertex);
-
-                    let final_index = next_available_vertex_index;
-                    vertex_label_map.insert((vertex_child_index, vertex_parents), final_index);
-                    next_available_vertex_index += 1;
-                    final_index
-                };
-
-                *vertex_index = final_vertex_index;
-            }
+    }
+    let original_indices: Vec<_> = is_referenced
+        .iter()
+        .positions(|&referenced| referenced)
+        .collect();
+
+    let mut new_indices = vec![usize::MAX; mesh.vertices().len()];
+    for (new_index, &original_index) in original_indices.iter().enumerate() {
+        new_indices[original_index] = new_index;
+    }
+    for conn in &mut new_connectivity {
+        for index in conn.vertex_indices_mut() {
+            *index = new_indices[*index];
         }
-
-        Mesh::from_vertices_and_connectivity(final_vertices, new_connectivity)
     }
+
+    let vertices = original_indices
+        .iter()
+        .map(|&index| mesh.vertices()[index].clone())
+        .collect();
+    (
+        Mesh::from_vertices_and_connectivity(vertices, new_connectivity),
+        original_indices,
+    )
 }
 
 impl<T> From<Mesh2d<T, Tri3d2Connectivity>> for Mesh2d<T, Tri6d2Connectivity>
 where
     T: Real,
 {
-    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
     fn from(initial_mesh: Mesh2d<T, Tri3d2Connectivity>) -> Self {
-        let mut vertices = initial_mesh.vertices().to_vec();
-
-        // Holds edges on which vertices should be inserted
-        let mut edge_vertex_index_map = HashMap::new();
-
-        let mut new_connectivity = Vec::new();
-
-        for connectivity in initial_mesh.connectivity() {
-            // TODO: Find a nicer way to write this
-            let vertex_indices = connectivity.vertex_indices();
-            let num_vertices = vertex_indices.len();
-            let edges = vertex_indices
-                .iter()
-                .cycle()
-                .take(num_vertices + 1)
-                .tuple_windows();
-
-            // Add nodal vertices
-            let mut tri6_vertex_indices = [0usize; 6];
-            for (i, index) in vertex_indices.iter().enumerate() {
-                tri6_vertex_indices[i] = *index;
-            }
-
-            // Add vertices that are midpoints on edges
-            for ((a, b), vertex_index) in izip!(edges, &mut tri6_vertex_indices[3..]) {
-                // Sort the tuple so that edges are uniquely described
-                let edge = (a.min(b), a.max(b));
-
-                let index = edge_vertex_index_map.entry(edge).or_insert_with(|| {
-                    let new_vertex_index = vertices.len();
-                    let (v_a, v_b) = (vertices[*a], vertices[*b]);
-                    let midpoint = Point2::from((v_a.coords + v_b.coords) / 2.0);
-                    vertices.push(midpoint);
-                    new_vertex_index
-                });
-
-                *vertex_index = *index;
-            }
-
-            // Finally add the new p-refined connectivity
-            new_connectivity.push(Tri6d2Connectivity(tri6_vertex_indices));
-        }
-
-        Mesh2d::from_vertices_and_connectivity(vertices, new_connectivity)
+        p_refine_mesh(&initial_mesh).0
     }
 }
 
@@ -386,58 +476,8 @@ impl<T> From<Mesh2d<T, Quad4d2Connectivity>> for Mesh2d<T, Quad9d2Connectivity>
 where
     T: Real,
 {
-    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
     fn from(initial_mesh: Mesh2d<T, Quad4d2Connectivity>) -> Self {
-        let mut vertices = initial_mesh.vertices().to_vec();
-
-        // Holds edges on which vertices should be inserted
-        let mut edge_vertex_index_map = HashMap::new();
-
-        let mut new_connectivity = Vec::new();
-
-        for connectivity in initial_mesh.connectivity() {
-            // TODO: Find a nicer way to write this
-            let vertex_indices = connectivity.vertex_indices();
-            let num_vertices = vertex_indices.len();
-            let edges = vertex_indices
-                .iter()
-                .cycle()
-                .take(num_vertices + 1)
-                .tuple_windows();
-
-            // Add nodal vertices
-            let mut quad9_vertex_indices = [0usize; 9];
-            for (i, index) in vertex_indices.iter().enumerate() {
-                quad9_vertex_indices[i] = *index;
-            }
-
-            // Add vertices that are midpoints on edges
-            for ((a, b), vertex_index) in izip!(edges, &mut quad9_vertex_indices[4..]) {
-                // Sort the tuple so that edges are uniquely described
-                let edge = (a.min(b), a.max(b));
-
-                let index = edge_vertex_index_map.entry(edge).or_insert_with(|| {
-                    let new_vertex_index = vertices.len();
-                    let (v_a, v_b) = (vertices[*a], vertices[*b]);
-                    let midpoint = Point2::from((v_a.coords + v_b.coords) / 2.0);
-                    vertices.push(midpoint);
-                    new_vertex_index
-                });
-
-                *vertex_index = *index;
-            }
-
-            // Add the midpoint of the cell
-            let element = connectivity.element(initial_mesh.vertices()).unwrap();
-            let midpoint = Point2::from(element.map_reference_coords(&Point2::origin()));
-            quad9_vertex_indices[8] = vertices.len();
-            vertices.push(midpoint);
-
-            // Finally add the new p-refined connectivity
-            new_connectivity.push(Quad9d2Connectivity(quad9_vertex_indices));
-        }
-
-        Mesh2d::from_vertices_and_connectivity(vertices, new_connectivity)
+        p_refine_mesh(&initial_mesh).0
     }
 }
 
@@ -445,9 +485,8 @@ impl<'a, T> From<&'a Mesh3d<T, Tet4Connectivity>> for Mesh3d<T, Tet10Connectivit
 where
     T: Real,
 {
-    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
     fn from(initial_mesh: &'a Mesh3d<T, Tet4Connectivity>) -> Self {
-        <Self as FromTemp<_>>::from(initial_mesh)
+        p_refine_mesh(initial_mesh).0
     }
 }
 
@@ -474,7 +513,7 @@ where
     T: Real,
 {
     fn from(initial_mesh: &'a Mesh3d<T, Hex8Connectivity>) -> Self {
-        <Self as FromTemp<_>>::from(initial_mesh)
+        p_refine_mesh(initial_mesh).0
     }
 }
 
@@ -483,7 +522,7 @@ where
     T: Real,
 {
     fn from(initial_mesh: &'a Mesh3d<T, Hex8Connectivity>) -> Self {
-        <Self as FromTemp<_>>::from(initial_mesh)
+        p_refine_mesh(initial_mesh).0
     }
 }
 
@@ -773,3 +812,100 @@ impl<'a, T: Real> From<&'a Tet4Mesh<T>> for Tet20Mesh<T> {
         Tet20Mesh::from_vertices_and_connectivity(new_vertices, new_connectivity)
     }
 }
+
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+impl<'a, T: Real> From<&'a TriangleMesh2d<T>> for Tri10Mesh2d<T> {
+    fn from(tri3_mesh: &'a TriangleMesh2d<T>) -> Self {
+        // Similar to the conversion from Tet4 to Tet20, we represent each new vertex
+        // with 3 integers as follows:
+        //
+        // # A vertex in the original mesh
+        //  [VERTEX, idx, 0],
+        //  where idx is the index of the vertex.
+        //
+        // # Internal point on edge
+        // Tri10 elements have 2 points per edge. We represent such a vertex as
+        //  [EDGE + local_idx, min_idx, max_idx]
+        // where min_idx is the smallest of the two vertex indices, max_idx is the largest
+        // and local_idx is either 0 or 1, indicating which of the two vertices on the edge
+        // is represented, from the direction of min_idx
+        //
+        // # Interior point
+        //  [INTERIOR, element_idx, 0]
+        // since interior points are never shared between elements.
+        //
+        // Since the kind comes first, the vertices of the original mesh come first in the new mesh.
+        const VERTEX: usize = 0;
+        const EDGE: usize = 1;
+        const INTERIOR: usize = 3;
+
+        let normalized_vertex = |vertex_idx| [VERTEX, vertex_idx, 0];
+        let normalized_edge = |[mut start, mut end, mut local_idx]: [usize; 3]| {
+            if start > end {
+                (start, end) = (end, start);
+                // "Flip" local idx, i.e. 0 -> 1, 1 -> 0
+                local_idx = (local_idx + 1) % 2;
+            }
+            [EDGE + local_idx, start, end]
+        };
+
+        let unlabeled_vertices_for_tri3_element =
+            |(element_idx, &Tri3d2Connectivity([v0, v1, v2])): (usize, &Tri3d2Connectivity)| {
+                [
+                    normalized_vertex(v0),
+                    normalized_vertex(v1),
+                    normalized_vertex(v2),
+                    normalized_edge([v0, v1, 0]),
+                    normalized_edge([v0, v1, 1]),
+                    normalized_edge([v1, v2, 0]),
+                    normalized_edge([v1, v2, 1]),
+                    normalized_edge([v2, v0, 0]),
+                    normalized_edge([v2, v0, 1]),
+                    [INTERIOR, element_idx, 0],
+                ]
+            };
+
+        let mut unlabeled_vertices: Vec<_> = tri3_mesh
+            .connectivity()
+            .iter()
+            .enumerate()
+            .flat_map(unlabeled_vertices_for_tri3_element)
+            .collect();
+        unlabeled_vertices.sort_unstable();
+        unlabeled_vertices.dedup();
+
+        let new_connectivity = tri3_mesh
+            .connectivity()
+            .iter()
+            .enumerate()
+            .map(unlabeled_vertices_for_tri3_element)
+            .map(|element_unlabeled_vertices| {
+                element_unlabeled_vertices.map(|unlabeled_vertex| {
+                    unlabeled_vertices
+                        .binary_search(&unlabeled_vertex)
+                        .expect("all vertices exist by construction")
+                })
+            })
+            .map(Tri10d2Connectivity)
+            .collect();
+
+        let vertex = |idx: usize| tri3_mesh.vertices()[idx].coords.clone();
+        let new_vertices = unlabeled_vertices
+            .into_iter()
+            .map(|[kind, a, b]| match kind {
+                VERTEX => tri3_mesh.vertices()[a].clone(),
+                INTERIOR => {
+                    let Tri3d2Connectivity([v0, v1, v2]) = tri3_mesh.connectivity()[a];
+                    Point2::from((vertex(v0) + vertex(v1) + vertex(v2)) / 3.0)
+                }
+                _ => {
+                    let local_idx = kind - EDGE;
+                    let alpha = T::from_usize(local_idx + 1).unwrap() / 3.0;
+                    Point2::from(vertex(a) + (vertex(b) - vertex(a)) * alpha)
+                }
+            })
+            .collect();
+
+        Tri10Mesh2d::from_vertices_and_connectivity(new_vertices, new_connectivity)
+    }
+}
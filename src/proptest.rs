@@ -1,11 +1,16 @@
-use crate::element::{Tet4Element, Tri3d2Element, Tri3d3Element};
+use crate::element::{Hex8Element, Quad4d2Element, Tet4Element, Tri3d2Element, Tri3d3Element};
 use crate::geometry::Orientation::Counterclockwise;
-use crate::mesh::procedural::create_rectangular_uniform_quad_mesh_2d;
-use crate::mesh::QuadMesh2d;
+use crate::mesh::procedural::{
+    create_rectangular_uniform_quad_mesh_2d, create_unit_box_uniform_hex_mesh_3d,
+    create_unit_square_uniform_quad_mesh_2d,
+};
+use crate::mesh::{HexMesh, Mesh, QuadMesh2d};
+use ::proptest::collection::vec;
 use ::proptest::prelude::*;
 use fenris_geometry::proptest::Triangle2dParams;
 use fenris_geometry::Triangle2d;
-use nalgebra::{Point2, Point3, Vector2};
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Point2, Point3, Vector2};
 use std::cmp::max;
 
 pub fn point2() -> impl Strategy<Value = Point2<f64>> {
@@ -95,14 +100,213 @@ pub fn rectangular_uniform_mesh_strategy(unit_length: f64, max_cells: usize) ->
     )
 }
 
+/// The maximum perturbation of each vertex coordinate of the reference element in the
+/// `valid_*_element` strategies.
+///
+/// The reference elements have edge length $2$, and a perturbation of at most $0.25$ in each
+/// coordinate keeps the Jacobian determinant of linear triangles, quadrilaterals, tetrahedra and
+/// hexahedra bounded away from zero everywhere in the element.
+pub const MAX_REFERENCE_VERTEX_PERTURBATION: f64 = 0.25;
+
+/// Returns a strategy that perturbs each coordinate of the given points by at most `max_perturbation`.
+///
+/// The perturbations shrink towards zero, so that the points shrink towards the given points.
+fn perturbed_points<D, const N: usize>(
+    points: [OPoint<f64, D>; N],
+    max_perturbation: f64,
+) -> impl Strategy<Value = [OPoint<f64, D>; N]>
+where
+    D: DimName,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    vec(-max_perturbation..=max_perturbation, N * D::dim()).prop_map(move |perturbations| {
+        let mut points = points.clone();
+        for (point, perturbation) in points.iter_mut().zip(perturbations.chunks_exact(D::dim())) {
+            for (x_i, dx_i) in point.coords.iter_mut().zip(perturbation) {
+                *x_i += *dx_i;
+            }
+        }
+        points
+    })
+}
+
+/// A strategy for non-degenerate [`Tri3d2Element`]s, obtained by perturbing the vertices of the
+/// reference element by at most [`MAX_REFERENCE_VERTEX_PERTURBATION`].
+///
+/// Elements shrink towards the reference element.
+pub fn valid_tri3_element() -> impl Strategy<Value = Tri3d2Element<f64>> {
+    perturbed_points(
+        *Tri3d2Element::reference().vertices(),
+        MAX_REFERENCE_VERTEX_PERTURBATION,
+    )
+    .prop_map(Tri3d2Element::from_vertices)
+}
+
+/// A strategy for non-degenerate [`Quad4d2Element`]s, obtained by perturbing the vertices of the
+/// reference element by at most [`MAX_REFERENCE_VERTEX_PERTURBATION`].
+///
+/// Elements shrink towards the reference element.
+pub fn valid_quad4_element() -> impl Strategy<Value = Quad4d2Element<f64>> {
+    perturbed_points(
+        *Quad4d2Element::reference().vertices(),
+        MAX_REFERENCE_VERTEX_PERTURBATION,
+    )
+    .prop_map(Quad4d2Element::from_vertices)
+}
+
+/// A strategy for non-degenerate [`Tet4Element`]s, obtained by perturbing the vertices of the
+/// reference element by at most [`MAX_REFERENCE_VERTEX_PERTURBATION`].
+///
+/// Elements shrink towards the reference element.
+pub fn valid_tet4_element() -> impl Strategy<Value = Tet4Element<f64>> {
+    perturbed_points(*Tet4Element::reference().vertices(), MAX_REFERENCE_VERTEX_PERTURBATION)
+        .prop_map(Tet4Element::from_vertices)
+}
+
+/// A strategy for non-degenerate [`Hex8Element`]s, obtained by perturbing the vertices of the
+/// reference element by at most [`MAX_REFERENCE_VERTEX_PERTURBATION`].
+///
+/// Elements shrink towards the reference element.
+pub fn valid_hex8_element() -> impl Strategy<Value = Hex8Element<f64>> {
+    perturbed_points(*Hex8Element::reference().vertices(), MAX_REFERENCE_VERTEX_PERTURBATION)
+        .prop_map(Hex8Element::from_vertices)
+}
+
+/// Perturbs the interior vertices of a uniform mesh of the unit square or cube with cell size `h`.
+fn jitter_interior_vertices<D, C>(
+    mesh: Mesh<f64, D, C>,
+    h: f64,
+    max_jitter: f64,
+) -> impl Strategy<Value = Mesh<f64, D, C>>
+where
+    D: DimName,
+    C: Clone + std::fmt::Debug,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    let num_coords = mesh.vertices().len() * D::dim();
+    vec(-max_jitter..=max_jitter, num_coords).prop_map(move |jitter| {
+        let mut mesh = mesh.clone();
+        for (vertex, jitter) in mesh
+            .vertices_mut()
+            .iter_mut()
+            .zip(jitter.chunks_exact(D::dim()))
+        {
+            let is_interior = vertex
+                .coords
+                .iter()
+                .all(|&x_i| x_i > 0.5 * h && x_i < 1.0 - 0.5 * h);
+            if is_interior {
+                for (x_i, dx_i) in vertex.coords.iter_mut().zip(jitter) {
+                    *x_i += h * dx_i;
+                }
+            }
+        }
+        mesh
+    })
+}
+
+/// A strategy for uniform quadrilateral meshes of the unit square with `resolution` cells
+/// per dimension, in which each coordinate of each interior vertex is perturbed by at most
+/// `max_jitter` times the cell size.
+///
+/// Boundary vertices are not perturbed, so that the mesh always covers the unit square.
+/// The meshes shrink towards the unperturbed uniform mesh.
+///
+/// # Panics
+///
+/// Panics if `max_jitter` is not in the interval $[0, 1/4)$, which guarantees that all elements
+/// have a positive Jacobian determinant.
+pub fn jittered_quad_mesh(resolution: usize, max_jitter: f64) -> impl Strategy<Value = QuadMesh2d<f64>> {
+    assert!(
+        (0.0..0.25).contains(&max_jitter),
+        "max_jitter must be in [0, 1/4) for the mesh to remain valid"
+    );
+    let mesh = create_unit_square_uniform_quad_mesh_2d(resolution);
+    jitter_interior_vertices(mesh, 1.0 / resolution as f64, max_jitter)
+}
+
+/// A strategy for uniform hexahedral meshes of the unit cube with `resolution` cells
+/// per dimension, in which each coordinate of each interior vertex is perturbed by at most
+/// `max_jitter` times the cell size.
+///
+/// Boundary vertices are not perturbed, so that the mesh always covers the unit cube.
+/// The meshes shrink towards the unperturbed uniform mesh.
+///
+/// # Panics
+///
+/// Panics if `max_jitter` is not in the interval $[0, 1/6)$, which guarantees that all elements
+/// have a positive Jacobian determinant.
+pub fn jittered_hex_mesh(resolution: usize, max_jitter: f64) -> impl Strategy<Value = HexMesh<f64>> {
+    assert!(
+        (0.0..1.0 / 6.0).contains(&max_jitter),
+        "max_jitter must be in [0, 1/6) for the mesh to remain valid"
+    );
+    let mesh = create_unit_box_uniform_hex_mesh_3d(resolution);
+    jitter_interior_vertices(mesh, 1.0 / resolution as f64, max_jitter)
+}
+
+/// A strategy for points in the reference triangle with vertices $(-1, -1)$, $(1, -1)$ and $(-1, 1)$.
+pub fn point_in_reference_tri() -> impl Strategy<Value = Point2<f64>> {
+    [0.0..=1.0, 0.0..=1.0].prop_map(|[s, t]| {
+        // Reflect points in the upper half of the unit square into the lower half
+        let (s, t) = if s + t > 1.0 { (1.0 - s, 1.0 - t) } else { (s, t) };
+        Point2::new(2.0 * s - 1.0, 2.0 * t - 1.0)
+    })
+}
+
+/// A strategy for points in the reference quadrilateral $[-1, 1]^2$.
+pub fn point_in_reference_quad() -> impl Strategy<Value = Point2<f64>> {
+    [-1.0..=1.0, -1.0..=1.0].prop_map(|[x, y]| Point2::new(x, y))
+}
+
+/// A strategy for points in the reference tetrahedron with vertices $(-1, -1, -1)$, $(1, -1, -1)$,
+/// $(-1, 1, -1)$ and $(-1, -1, 1)$.
+pub fn point_in_reference_tet() -> impl Strategy<Value = Point3<f64>> {
+    [0.0..=1.0, 0.0..=1.0, 0.0..=1.0].prop_map(|mut u| {
+        // The gaps between sorted numbers in [0, 1] are the coordinates of a point in the unit simplex
+        u.sort_by(|a: &f64, b| a.partial_cmp(b).unwrap());
+        let [s, t, r] = [u[0], u[1] - u[0], u[2] - u[1]];
+        Point3::new(2.0 * s - 1.0, 2.0 * t - 1.0, 2.0 * r - 1.0)
+    })
+}
+
+/// A strategy for points in the reference hexahedron $[-1, 1]^3$.
+pub fn point_in_reference_hex() -> impl Strategy<Value = Point3<f64>> {
+    [-1.0..=1.0, -1.0..=1.0, -1.0..=1.0].prop_map(|[x, y, z]| Point3::new(x, y, z))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::rectangular_uniform_mesh_cell_distribution_strategy;
+    use super::*;
+    use crate::allocators::BiDimAllocator;
+    use crate::element::{ElementConnectivity, FiniteElement};
     use crate::geometry::proptest::{
         convex_quad2d_strategy_f64, nondegenerate_convex_quad2d_strategy_f64, nondegenerate_triangle2d_strategy_f64,
     };
     use crate::geometry::Orientation;
-    use proptest::prelude::*;
+    use crate::SmallDim;
+
+    fn assert_partition_of_unity_and_positive_jacobian<D, Element>(
+        element: &Element,
+        xi: &OPoint<f64, D>,
+    ) -> Result<(), TestCaseError>
+    where
+        D: SmallDim,
+        Element: FiniteElement<f64, GeometryDim = D, ReferenceDim = D>,
+        DefaultAllocator: BiDimAllocator<f64, D, D>,
+    {
+        let mut basis_values = vec![0.0; element.num_nodes()];
+        element.populate_basis(&mut basis_values, xi);
+        let basis_sum: f64 = basis_values.iter().sum();
+        prop_assert!(
+            (basis_sum - 1.0).abs() <= 1e-12,
+            "sum of basis functions: {}",
+            basis_sum
+        );
+        let jacobian_det = element.reference_jacobian(xi).determinant();
+        prop_assert!(jacobian_det > 0.0, "Jacobian determinant: {}", jacobian_det);
+        Ok(())
+    }
 
     proptest! {
         #[test]
@@ -120,6 +324,70 @@ mod tests {
             prop_assert!(cells_per_unit * cells_per_unit * units_x * units_y <= max_cells);
         }
 
+        #[test]
+        fn valid_tri3_elements_have_partition_of_unity(
+            element in valid_tri3_element(),
+            xi in point_in_reference_tri()
+        ) {
+            assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+        }
+
+        #[test]
+        fn valid_quad4_elements_have_partition_of_unity(
+            element in valid_quad4_element(),
+            xi in point_in_reference_quad()
+        ) {
+            assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+        }
+
+        #[test]
+        fn valid_tet4_elements_have_partition_of_unity(
+            element in valid_tet4_element(),
+            xi in point_in_reference_tet()
+        ) {
+            assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+        }
+
+        #[test]
+        fn valid_hex8_elements_have_partition_of_unity(
+            element in valid_hex8_element(),
+            xi in point_in_reference_hex()
+        ) {
+            assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+        }
+
+        #[test]
+        fn reference_tri_and_tet_points_are_in_reference_domain(
+            xi_tri in point_in_reference_tri(),
+            xi_tet in point_in_reference_tet()
+        ) {
+            prop_assert!(xi_tri.x >= -1.0 && xi_tri.y >= -1.0 && xi_tri.x + xi_tri.y <= 1e-12);
+            prop_assert!(xi_tet.iter().all(|&x_i| x_i >= -1.0));
+            prop_assert!(xi_tet.x + xi_tet.y + xi_tet.z <= -1.0 + 1e-12);
+        }
+
+        #[test]
+        fn jittered_quad_meshes_are_valid(
+            mesh in jittered_quad_mesh(3, 0.2),
+            xi in point_in_reference_quad()
+        ) {
+            for connectivity in mesh.connectivity() {
+                let element = connectivity.element(mesh.vertices()).unwrap();
+                assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+            }
+        }
+
+        #[test]
+        fn jittered_hex_meshes_are_valid(
+            mesh in jittered_hex_mesh(2, 0.15),
+            xi in point_in_reference_hex()
+        ) {
+            for connectivity in mesh.connectivity() {
+                let element = connectivity.element(mesh.vertices()).unwrap();
+                assert_partition_of_unity_and_positive_jacobian(&element, &xi)?;
+            }
+        }
+
         #[test]
         fn convex_quads_are_convex(quad in convex_quad2d_strategy_f64()) {
             prop_assert!(quad.concave_corner().is_none());
@@ -13,6 +13,7 @@ pub use canonical::*;
 /// TODO: How to prevent collapse?
 pub use fenris_quadrature::Error as QuadratureError;
 
+pub mod faces;
 pub mod subdivide;
 pub mod tensor;
 pub mod total_order;
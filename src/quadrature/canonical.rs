@@ -86,8 +86,18 @@ macro_rules! impl_canonical_stiffness_for_element {
 // Triangular elements
 impl_canonical_mass_for_element!(Tri3d2Connectivity, Tri3d2Element<T>, total_order::triangle(2).unwrap());
 impl_canonical_mass_for_element!(Tri6d2Connectivity, Tri6d2Element<T>, total_order::triangle(4).unwrap());
+impl_canonical_mass_for_element!(
+    Tri10d2Connectivity,
+    Tri10d2Element<T>,
+    total_order::triangle(6).unwrap()
+);
 impl_canonical_stiffness_for_element!(Tri3d2Connectivity, Tri3d2Element<T>, total_order::triangle(1).unwrap());
 impl_canonical_stiffness_for_element!(Tri6d2Connectivity, Tri6d2Element<T>, total_order::triangle(2).unwrap());
+impl_canonical_stiffness_for_element!(
+    Tri10d2Connectivity,
+    Tri10d2Element<T>,
+    total_order::triangle(4).unwrap()
+);
 
 // Quadrilateral elements
 impl_canonical_mass_for_element!(Quad4d2Connectivity, Quad4d2Element<T>, tensor::quadrilateral_gauss(2));
@@ -110,3 +120,20 @@ impl_canonical_mass_for_element!(Hex27Connectivity, Hex27Element<T>, tensor::hex
 impl_canonical_stiffness_for_element!(Hex8Connectivity, Hex8Element<T>, tensor::hexahedron_gauss(2));
 impl_canonical_stiffness_for_element!(Hex20Connectivity, Hex20Element<T>, tensor::hexahedron_gauss(3));
 impl_canonical_stiffness_for_element!(Hex27Connectivity, Hex27Element<T>, tensor::hexahedron_gauss(3));
+
+// Prismatic elements
+impl_canonical_mass_for_element!(Prism15Connectivity, Prism15Element<T>, total_order::prism(6).unwrap());
+impl_canonical_stiffness_for_element!(Prism15Connectivity, Prism15Element<T>, total_order::prism(4).unwrap());
+
+// Pyramidal elements
+// The basis functions are rational, so these rules are not exact in general, see Pyramid5Element
+impl_canonical_mass_for_element!(
+    Pyramid5Connectivity,
+    Pyramid5Element<T>,
+    total_order::pyramid(4).unwrap()
+);
+impl_canonical_stiffness_for_element!(
+    Pyramid5Connectivity,
+    Pyramid5Element<T>,
+    total_order::pyramid(4).unwrap()
+);
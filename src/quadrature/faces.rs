@@ -0,0 +1,309 @@
+//! Quadrature rules for the faces of reference elements.
+//!
+//! Surface integrals over the boundary of an element are most conveniently computed by integrating
+//! over the faces of its reference element. Each face is itself parametrized by a reference domain
+//! (a segment for 2D elements, a triangle or quadrilateral for 3D elements), and a quadrature rule on
+//! this face reference domain is mapped to the reference domain of the parent element by an affine
+//! [`FaceEmbedding`].
+//!
+//! The faces are numbered and oriented in the same way as the face connectivities returned by
+//! [`Connectivity::get_face_connectivity`] for the corresponding lowest-order connectivities
+//! ([`Tri3d2Connectivity`], [`Quad4d2Connectivity`], [`Tet4Connectivity`], [`Hex8Connectivity`] and
+//! [`Pyramid5Connectivity`]).
+//! In particular, vertex $k$ of the face reference domain maps to vertex $k$ of the face connectivity,
+//! so that the embedding is consistent with the face connectivity and the face normals point
+//! out of the element. For prisms, whose linear connectivity has no faces, the faces are documented
+//! in [`reference_face_embeddings_3d`].
+use crate::allocators::BiDimAllocator;
+use crate::connectivity::{
+    Connectivity, Hex8Connectivity, Pyramid5Connectivity, Quad4d2Connectivity, Tet4Connectivity, Tri3d2Connectivity,
+};
+use crate::element::ReferenceShape;
+use crate::nalgebra::{
+    DefaultAllocator, DimName, Matrix2x1, Matrix3x2, OMatrix, OPoint, Point2, Point3, Vector2, Vector3, U1, U2, U3,
+};
+use crate::quadrature::univariate::gauss;
+use crate::quadrature::{total_order, QuadratureError, QuadraturePair, QuadraturePair1d, QuadraturePair2d};
+use crate::Real;
+use numeric_literals::replace_float_literals;
+
+/// An affine map from the reference domain of a face to the reference domain of its parent element.
+///
+/// The map is given by $\vec \xi = \vec \xi_0 + \vec J \vec \eta$, where $\vec \eta$ are the
+/// reference coordinates of the face and $\vec \xi$ the reference coordinates of the parent element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceEmbedding<T, FaceDim, ElementDim>
+where
+    T: Real,
+    FaceDim: DimName,
+    ElementDim: DimName,
+    DefaultAllocator: BiDimAllocator<T, FaceDim, ElementDim>,
+{
+    shape: ReferenceShape,
+    origin: OPoint<T, ElementDim>,
+    jacobian: OMatrix<T, ElementDim, FaceDim>,
+    measure_scale: T,
+}
+
+/// An embedding of an edge of a 2D reference element.
+pub type FaceEmbedding2d<T> = FaceEmbedding<T, U1, U2>;
+/// An embedding of a face of a 3D reference element.
+pub type FaceEmbedding3d<T> = FaceEmbedding<T, U2, U3>;
+
+impl<T, FaceDim, ElementDim> FaceEmbedding<T, FaceDim, ElementDim>
+where
+    T: Real,
+    FaceDim: DimName,
+    ElementDim: DimName,
+    DefaultAllocator: BiDimAllocator<T, FaceDim, ElementDim>,
+{
+    /// The shape of the reference domain of the face.
+    pub fn shape(&self) -> ReferenceShape {
+        self.shape
+    }
+
+    /// The image of the origin of the face reference domain in the parent reference domain.
+    pub fn origin(&self) -> &OPoint<T, ElementDim> {
+        &self.origin
+    }
+
+    /// The (constant) Jacobian of the embedding.
+    pub fn jacobian(&self) -> &OMatrix<T, ElementDim, FaceDim> {
+        &self.jacobian
+    }
+
+    /// The ratio between the measure of the face in the parent reference domain and the measure of
+    /// the face reference domain, i.e. $\sqrt{\det(\vec J^T \vec J)}$.
+    pub fn measure_scale(&self) -> T {
+        self.measure_scale
+    }
+
+    /// Maps reference coordinates of the face to reference coordinates of the parent element.
+    pub fn map_reference_coords(&self, face_coords: &OPoint<T, FaceDim>) -> OPoint<T, ElementDim> {
+        &self.origin + &self.jacobian * &face_coords.coords
+    }
+
+    /// Maps a quadrature rule on the face reference domain to the parent reference domain.
+    ///
+    /// The weights are scaled by [`measure_scale`](Self::measure_scale), so that the resulting rule
+    /// approximates surface integrals over the face in the parent reference domain.
+    pub fn map_quadrature(&self, quadrature: &QuadraturePair<T, FaceDim>) -> QuadraturePair<T, ElementDim> {
+        let (weights, points) = quadrature;
+        let weights = weights.iter().map(|w| *w * self.measure_scale).collect();
+        let points = points
+            .iter()
+            .map(|p| self.map_reference_coords(p))
+            .collect();
+        (weights, points)
+    }
+}
+
+impl<T: Real> FaceEmbedding2d<T> {
+    /// Embedding of the reference segment $[-1, 1]$ onto the segment from `a` to `b`.
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn segment(a: &Point2<T>, b: &Point2<T>) -> Self {
+        let half_tangent: Vector2<T> = (b - a) * 0.5;
+        Self {
+            shape: ReferenceShape::Segment,
+            origin: a + half_tangent,
+            jacobian: Matrix2x1::from_column_slice(half_tangent.as_slice()),
+            measure_scale: half_tangent.norm(),
+        }
+    }
+}
+
+impl<T: Real> FaceEmbedding3d<T> {
+    /// Embedding of the reference triangle or quadrilateral such that $(-1, -1)$ maps to `a`,
+    /// $(1, -1)$ maps to `b` and $(-1, 1)$ maps to `c`.
+    #[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+    fn planar(shape: ReferenceShape, a: &Point3<T>, b: &Point3<T>, c: &Point3<T>) -> Self {
+        let e1: Vector3<T> = (b - a) * 0.5;
+        let e2: Vector3<T> = (c - a) * 0.5;
+        Self {
+            shape,
+            origin: a + e1 + e2,
+            jacobian: Matrix3x2::from_columns(&[e1, e2]),
+            measure_scale: e1.cross(&e2).norm(),
+        }
+    }
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+fn reference_vertices_2d<T: Real>(shape: ReferenceShape) -> Vec<Point2<T>> {
+    match shape {
+        ReferenceShape::Triangle => vec![Point2::new(-1.0, -1.0), Point2::new(1.0, -1.0), Point2::new(-1.0, 1.0)],
+        ReferenceShape::Quadrilateral => vec![
+            Point2::new(-1.0, -1.0),
+            Point2::new(1.0, -1.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(-1.0, 1.0),
+        ],
+        _ => panic!("{:?} is not a 2D reference shape", shape),
+    }
+}
+
+#[replace_float_literals(T::from_f64(literal).expect("Literal must fit in T"))]
+fn reference_vertices_3d<T: Real>(shape: ReferenceShape) -> Vec<Point3<T>> {
+    match shape {
+        ReferenceShape::Tetrahedron => vec![
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+        ],
+        ReferenceShape::Hexahedron => vec![
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+            Point3::new(1.0, -1.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(-1.0, 1.0, 1.0),
+        ],
+        ReferenceShape::Prism => vec![
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, 1.0),
+            Point3::new(1.0, -1.0, 1.0),
+            Point3::new(-1.0, 1.0, 1.0),
+        ],
+        ReferenceShape::Pyramid => vec![
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, -1.0),
+            Point3::new(-1.0, 1.0, -1.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ],
+        _ => panic!("{:?} is not a 3D reference shape", shape),
+    }
+}
+
+fn face_vertex_indices<C: Connectivity>(connectivity: &C) -> Vec<Vec<usize>> {
+    (0..connectivity.num_faces())
+        .map(|i| {
+            connectivity
+                .get_face_connectivity(i)
+                .expect("Face index is in bounds")
+                .vertex_indices()
+                .to_vec()
+        })
+        .collect()
+}
+
+/// Returns the embeddings of the edges of a 2D reference element.
+///
+/// The edges are ordered and oriented consistently with the face connectivities of
+/// [`Tri3d2Connectivity`] and [`Quad4d2Connectivity`], with edge $i$ going from vertex $i$ to vertex $i + 1$
+/// of the element. The reference coordinate $-1$ of the edge maps to the first vertex of the edge.
+///
+/// # Panics
+///
+/// Panics if the shape is not [`Triangle`](ReferenceShape::Triangle) or
+/// [`Quadrilateral`](ReferenceShape::Quadrilateral).
+pub fn reference_face_embeddings_2d<T: Real>(shape: ReferenceShape) -> Vec<FaceEmbedding2d<T>> {
+    let vertices = reference_vertices_2d::<T>(shape);
+    let faces = match shape {
+        ReferenceShape::Triangle => face_vertex_indices(&Tri3d2Connectivity([0, 1, 2])),
+        ReferenceShape::Quadrilateral => face_vertex_indices(&Quad4d2Connectivity([0, 1, 2, 3])),
+        _ => unreachable!(),
+    };
+    faces
+        .iter()
+        .map(|face| FaceEmbedding2d::segment(&vertices[face[0]], &vertices[face[1]]))
+        .collect()
+}
+
+/// Returns the embeddings of the faces of a 3D reference element.
+///
+/// For tetrahedra, hexahedra and pyramids, the faces are ordered and oriented consistently with the
+/// face connectivities of [`Tet4Connectivity`], [`Hex8Connectivity`] and [`Pyramid5Connectivity`].
+/// Prisms have vertices $(-1, -1, -1)$, $(1, -1, -1)$, $(-1, 1, -1)$ followed by the same vertices at
+/// $\zeta = 1$, and the faces
+/// `[0, 2, 1]`, `[3, 4, 5]`, `[0, 1, 4, 3]`, `[1, 2, 5, 4]` and `[2, 0, 3, 5]`. Pyramids have the base
+/// vertices $(-1, -1, -1)$, $(1, -1, -1)$, $(1, 1, -1)$, $(-1, 1, -1)$ followed by the apex $(0, 0, 1)$,
+/// and the faces `[0, 3, 2, 1]`, `[0, 1, 4]`, `[1, 2, 4]`, `[2, 3, 4]` and `[3, 0, 4]`.
+///
+/// For every face, the vertices of the face reference domain map to the face vertices in the given
+/// order, so that all face normals point out of the element.
+///
+/// # Panics
+///
+/// Panics if the shape is not a 3D reference shape.
+pub fn reference_face_embeddings_3d<T: Real>(shape: ReferenceShape) -> Vec<FaceEmbedding3d<T>> {
+    let vertices = reference_vertices_3d::<T>(shape);
+    let faces = match shape {
+        ReferenceShape::Tetrahedron => face_vertex_indices(&Tet4Connectivity([0, 1, 2, 3])),
+        ReferenceShape::Hexahedron => face_vertex_indices(&Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7])),
+        ReferenceShape::Prism => vec![
+            vec![0, 2, 1],
+            vec![3, 4, 5],
+            vec![0, 1, 4, 3],
+            vec![1, 2, 5, 4],
+            vec![2, 0, 3, 5],
+        ],
+        ReferenceShape::Pyramid => face_vertex_indices(&Pyramid5Connectivity([0, 1, 2, 3, 4])),
+        _ => unreachable!(),
+    };
+    faces
+        .iter()
+        .map(|face| {
+            let v = |i: usize| &vertices[face[i]];
+            match face.len() {
+                3 => FaceEmbedding3d::planar(ReferenceShape::Triangle, v(0), v(1), v(2)),
+                // The reference quadrilateral vertex (-1, 1) is the last vertex of the face
+                4 => FaceEmbedding3d::planar(ReferenceShape::Quadrilateral, v(0), v(1), v(3)),
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
+
+/// Returns edge quadrature rules for each edge of a 2D reference element.
+///
+/// Each quadrature rule is a Gauss rule on the reference segment $[-1, 1]$ that integrates polynomials of
+/// total degree up to `strength` exactly, paired with the embedding of the edge into the parent element.
+/// Use [`FaceEmbedding::map_quadrature`] to obtain a rule in the reference coordinates of the parent element.
+///
+/// # Panics
+///
+/// Panics if the shape is not [`Triangle`](ReferenceShape::Triangle) or
+/// [`Quadrilateral`](ReferenceShape::Quadrilateral).
+pub fn reference_face_quadratures_2d<T: Real>(
+    shape: ReferenceShape,
+    strength: usize,
+) -> Vec<(FaceEmbedding2d<T>, QuadraturePair1d<T>)> {
+    // An n-point Gauss rule integrates polynomials of degree up to 2n - 1 exactly
+    let num_points = strength / 2 + 1;
+    reference_face_embeddings_2d(shape)
+        .into_iter()
+        .map(|embedding| (embedding, gauss(num_points)))
+        .collect()
+}
+
+/// Returns face quadrature rules for each face of a 3D reference element.
+///
+/// Triangular faces use [`total_order::triangle`] and quadrilateral faces use
+/// [`total_order::quadrilateral`] with the given strength. Each rule is paired with the embedding of the face
+/// into the parent element, as returned by [`reference_face_embeddings_3d`].
+///
+/// # Panics
+///
+/// Panics if the shape is not a 3D reference shape.
+pub fn reference_face_quadratures_3d<T: Real>(
+    shape: ReferenceShape,
+    strength: usize,
+) -> Result<Vec<(FaceEmbedding3d<T>, QuadraturePair2d<T>)>, QuadratureError> {
+    reference_face_embeddings_3d(shape)
+        .into_iter()
+        .map(|embedding| {
+            let quadrature = match embedding.shape() {
+                ReferenceShape::Triangle => total_order::triangle(strength)?,
+                ReferenceShape::Quadrilateral => total_order::quadrilateral(strength)?,
+                _ => unreachable!(),
+            };
+            Ok((embedding, quadrature))
+        })
+        .collect()
+}
@@ -1,6 +1,11 @@
 //! Quadrature rules constructed from products of 1D quadrature rules.
+//!
+//! The points of the rules are ordered lexicographically by the indices of the points in the
+//! underlying rules, with the last coordinate varying fastest. See [`fenris_quadrature::tensor`]
+//! for details.
 use crate::quadrature::{
-    convert_quadrature_rule_from_2d_f64, convert_quadrature_rule_from_3d_f64, QuadraturePair2d, QuadraturePair3d,
+    convert_quadrature_rule_from_2d_f64, convert_quadrature_rule_from_3d_f64, QuadratureError, QuadraturePair2d,
+    QuadraturePair3d,
 };
 use crate::Real;
 use fenris_quadrature::tensor;
@@ -10,7 +15,42 @@ pub fn quadrilateral_gauss<T: Real>(num_points_per_dim: usize) -> QuadraturePair
     convert_quadrature_rule_from_2d_f64((weights, points))
 }
 
+/// Tensor-product Gauss rule for the reference quadrilateral with `nx` and `ny` points along the axes.
+pub fn quadrilateral_gauss_anisotropic<T: Real>(nx: usize, ny: usize) -> QuadraturePair2d<T> {
+    convert_quadrature_rule_from_2d_f64(tensor::quadrilateral_gauss_anisotropic(nx, ny))
+}
+
 pub fn hexahedron_gauss<T: Real>(num_points_per_dim: usize) -> QuadraturePair3d<T> {
     let (weights, points) = tensor::hexahedron_gauss(num_points_per_dim);
     convert_quadrature_rule_from_3d_f64((weights, points))
 }
+
+/// Tensor-product Gauss rule for the reference hexahedron with `nx`, `ny` and `nz` points along the axes.
+pub fn hexahedron_gauss_anisotropic<T: Real>(nx: usize, ny: usize, nz: usize) -> QuadraturePair3d<T> {
+    convert_quadrature_rule_from_3d_f64(tensor::hexahedron_gauss_anisotropic(nx, ny, nz))
+}
+
+/// Product of a triangle rule with the given strength and a 1D Gauss rule with `nz` points
+/// for the reference prism.
+pub fn prism_gauss<T: Real>(triangle_strength: usize, nz: usize) -> Result<QuadraturePair3d<T>, QuadratureError> {
+    let (weights, points) = tensor::prism_gauss(triangle_strength, nz)?;
+    Ok(convert_quadrature_rule_from_3d_f64((weights, points)))
+}
+
+/// Tensor-product Gauss-Lobatto rule for the reference quadrilateral with `n` points along each axis.
+///
+/// The points coincide with the nodes of the `Quad4` (`n = 2`) and `Quad9` (`n = 3`) elements,
+/// so that the mass matrices of these elements become diagonal when computed with the matching rule.
+/// Returns `None` if the underlying 1D rule is not available.
+pub fn try_quadrilateral_gauss_lobatto<T: Real>(n: usize) -> Option<QuadraturePair2d<T>> {
+    tensor::try_quadrilateral_gauss_lobatto(n).map(convert_quadrature_rule_from_2d_f64)
+}
+
+/// Tensor-product Gauss-Lobatto rule for the reference hexahedron with `n` points along each axis.
+///
+/// The points coincide with the nodes of the `Hex8` (`n = 2`) and `Hex27` (`n = 3`) elements,
+/// so that the mass matrices of these elements become diagonal when computed with the matching rule.
+/// Returns `None` if the underlying 1D rule is not available.
+pub fn try_hexahedron_gauss_lobatto<T: Real>(n: usize) -> Option<QuadraturePair3d<T>> {
+    tensor::try_hexahedron_gauss_lobatto(n).map(convert_quadrature_rule_from_3d_f64)
+}
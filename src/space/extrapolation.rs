@@ -0,0 +1,306 @@
+use crate::allocators::BiDimAllocator;
+use crate::space::{FindClosestElement, FiniteElementSpace};
+use crate::Real;
+use eyre::bail;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
+use std::fmt::Write;
+
+/// Determines how interpolation treats points that lie outside the domain of a finite element space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtrapolationPolicy {
+    /// Extrapolate from the element closest to the point.
+    #[default]
+    Extrapolate,
+    /// Leave the interpolated quantity undefined.
+    ///
+    /// Depending on the query, undefined quantities are reported as `None` or as NaN values.
+    Undefined,
+    /// Fail with an error that lists the points outside the domain.
+    Error,
+}
+
+/// Diagnostics collected while locating a set of points in a finite element space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtrapolationDiagnostics<T> {
+    /// The number of points inside the domain.
+    pub num_interior_points: usize,
+    /// The number of points outside the domain for which the quantity was extrapolated.
+    pub num_extrapolated_points: usize,
+    /// The number of points for which the quantity is undefined.
+    ///
+    /// This includes points outside the domain with the [`ExtrapolationPolicy::Undefined`] policy
+    /// and all points if the space has no elements.
+    pub num_undefined_points: usize,
+    /// The largest distance from a point outside the domain to the closest point in the domain,
+    /// or zero if all points are inside the domain.
+    pub max_exterior_distance: T,
+}
+
+impl<T: Real> ExtrapolationDiagnostics<T> {
+    fn new() -> Self {
+        Self {
+            num_interior_points: 0,
+            num_extrapolated_points: 0,
+            num_undefined_points: 0,
+            max_exterior_distance: T::zero(),
+        }
+    }
+
+    /// The number of points outside the domain.
+    pub fn num_exterior_points(&self) -> usize {
+        self.num_extrapolated_points + self.num_undefined_points
+    }
+}
+
+/// Determines whether a point at the given distance from an element is contained in the element,
+/// up to `tolerance` times the diameter of the element.
+pub(crate) fn is_within_element_tolerance<T, Space>(
+    space: &Space,
+    element_index: usize,
+    distance: T,
+    tolerance: T,
+) -> bool
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    distance <= tolerance * space.diameter(element_index)
+}
+
+/// Determines whether a point at the given distance from the closest element is inside the domain.
+///
+/// The distance is compared to $\sqrt{\epsilon}$ times the diameter of the element, so that
+/// round-off errors in the closest point computation do not cause interior points to be
+/// considered outside the domain.
+fn is_interior<T, Space>(space: &Space, element_index: usize, distance: T) -> bool
+where
+    T: Real,
+    Space: FiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    is_within_element_tolerance(space, element_index, distance, T::default_epsilon().sqrt())
+}
+
+/// The maximum number of offending points listed in the error returned for
+/// [`ExtrapolationPolicy::Error`].
+const MAX_REPORTED_EXTERIOR_POINTS: usize = 10;
+
+/// Finds the closest element and the associated reference coordinates for each point,
+/// subject to the given extrapolation policy.
+///
+/// A point is considered to be outside the domain if its distance to the closest point in the
+/// closest element exceeds $\sqrt{\epsilon}$ times the diameter of the element, where
+/// $\epsilon$ is the machine epsilon. For such points, the result is `None` if the policy is
+/// [`ExtrapolationPolicy::Undefined`]. The result is also `None` for every point
+/// if the space has no elements.
+///
+/// Returns an error if the policy is [`ExtrapolationPolicy::Error`] and any point is outside
+/// the domain, or the space has no elements.
+pub fn find_closest_elements_with_policy<T, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    policy: ExtrapolationPolicy,
+) -> eyre::Result<(
+    Vec<Option<(usize, OPoint<T, Space::ReferenceDim>)>>,
+    ExtrapolationDiagnostics<T>,
+)>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let mut diagnostics = ExtrapolationDiagnostics::new();
+    if space.num_elements() == 0 {
+        if policy == ExtrapolationPolicy::Error && !points.is_empty() {
+            bail!("cannot locate {} points in a space without elements", points.len());
+        }
+        diagnostics.num_undefined_points = points.len();
+        return Ok((vec![None; points.len()], diagnostics));
+    }
+
+    let mut exterior_points = Vec::new();
+    let located = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let (element_index, xi, closest_point) = space
+                .find_closest_point(point)
+                .expect("A space with elements must always have a closest element");
+            let distance = (closest_point - point).norm();
+            if is_interior(space, element_index, distance) {
+                diagnostics.num_interior_points += 1;
+                return Some((element_index, xi));
+            }
+
+            diagnostics.max_exterior_distance = diagnostics.max_exterior_distance.max(distance);
+            match policy {
+                ExtrapolationPolicy::Extrapolate => {
+                    diagnostics.num_extrapolated_points += 1;
+                    Some((element_index, xi))
+                }
+                ExtrapolationPolicy::Undefined => {
+                    diagnostics.num_undefined_points += 1;
+                    None
+                }
+                ExtrapolationPolicy::Error => {
+                    exterior_points.push((i, distance));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    if !exterior_points.is_empty() {
+        let mut message = format!("{} points are outside the domain:", exterior_points.len());
+        for &(i, distance) in exterior_points.iter().take(MAX_REPORTED_EXTERIOR_POINTS) {
+            write!(message, "\n  point {} at {} (distance {})", i, points[i], distance)?;
+        }
+        if exterior_points.len() > MAX_REPORTED_EXTERIOR_POINTS {
+            write!(
+                message,
+                "\n  ... and {} more",
+                exterior_points.len() - MAX_REPORTED_EXTERIOR_POINTS
+            )?;
+        }
+        bail!(message);
+    }
+
+    Ok((located, diagnostics))
+}
+
+/// The location of a point relative to the domain of a finite element space.
+///
+/// Each variant stores the index of the element closest to the point and the reference
+/// coordinates of the closest point in the element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointClassification<T, ReferenceDim>
+where
+    T: Scalar,
+    ReferenceDim: DimName,
+    DefaultAllocator: Allocator<T, ReferenceDim>,
+{
+    /// The point is inside the domain.
+    Inside {
+        element_index: usize,
+        xi: OPoint<T, ReferenceDim>,
+    },
+    /// The point is outside the domain, but within the boundary tolerance.
+    OnBoundary {
+        element_index: usize,
+        xi: OPoint<T, ReferenceDim>,
+        distance: T,
+    },
+    /// The point is outside the domain by more than the boundary tolerance.
+    Outside {
+        element_index: usize,
+        xi: OPoint<T, ReferenceDim>,
+        distance: T,
+    },
+}
+
+impl<T, ReferenceDim> PointClassification<T, ReferenceDim>
+where
+    T: Real,
+    ReferenceDim: DimName,
+    DefaultAllocator: Allocator<T, ReferenceDim>,
+{
+    /// The index of the element closest to the point.
+    pub fn element_index(&self) -> usize {
+        match self {
+            Self::Inside { element_index, .. }
+            | Self::OnBoundary { element_index, .. }
+            | Self::Outside { element_index, .. } => *element_index,
+        }
+    }
+
+    /// The reference coordinates of the closest point in the closest element.
+    pub fn reference_coords(&self) -> &OPoint<T, ReferenceDim> {
+        match self {
+            Self::Inside { xi, .. } | Self::OnBoundary { xi, .. } | Self::Outside { xi, .. } => xi,
+        }
+    }
+
+    /// The distance from the point to the closest point in the domain, which is zero for
+    /// points inside the domain.
+    pub fn distance(&self) -> T {
+        match self {
+            Self::Inside { .. } => T::zero(),
+            Self::OnBoundary { distance, .. } | Self::Outside { distance, .. } => *distance,
+        }
+    }
+
+    /// Returns `true` if the point is inside the domain.
+    pub fn is_inside(&self) -> bool {
+        matches!(self, Self::Inside { .. })
+    }
+}
+
+/// Classifies the location of the point relative to the domain of the space.
+///
+/// Points inside the domain are determined in the same way as in
+/// [`find_closest_elements_with_policy`]. Points outside the domain are classified as
+/// [`PointClassification::OnBoundary`] if the distance to the domain is at most `tolerance`,
+/// and as [`PointClassification::Outside`] otherwise. The boundary band therefore consists of
+/// the points outside the domain within distance `tolerance` of the boundary, which absorbs
+/// geometric discrepancies between e.g. an embedded geometry and the mesh.
+///
+/// Returns `None` if the space has no elements.
+///
+/// # Panics
+///
+/// Panics if `tolerance` is negative.
+pub fn classify_point<T, Space>(
+    space: &Space,
+    point: &OPoint<T, Space::GeometryDim>,
+    tolerance: T,
+) -> Option<PointClassification<T, Space::ReferenceDim>>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    assert!(tolerance >= T::zero(), "tolerance must be non-negative");
+    let (element_index, xi, closest_point) = space.find_closest_point(point)?;
+    let distance = (closest_point - point).norm();
+    let classification = if is_interior(space, element_index, distance) {
+        PointClassification::Inside { element_index, xi }
+    } else if distance <= tolerance {
+        PointClassification::OnBoundary {
+            element_index,
+            xi,
+            distance,
+        }
+    } else {
+        PointClassification::Outside {
+            element_index,
+            xi,
+            distance,
+        }
+    };
+    Some(classification)
+}
+
+/// Classifies the location of each point relative to the domain of the space.
+///
+/// See [`classify_point`] for details. Returns `None` if the space has no elements.
+///
+/// # Panics
+///
+/// Panics if `tolerance` is negative.
+pub fn classify_points<T, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    tolerance: T,
+) -> Option<Vec<PointClassification<T, Space::ReferenceDim>>>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    points
+        .iter()
+        .map(|point| classify_point(space, point, tolerance))
+        .collect()
+}
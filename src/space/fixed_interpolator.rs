@@ -1,9 +1,13 @@
-use crate::space::{FindClosestElement, VolumetricFiniteElementSpace};
+use crate::space::{
+    find_closest_elements_with_policy, ExtrapolationDiagnostics, ExtrapolationPolicy, FindClosestElement,
+    VolumetricFiniteElementSpace,
+};
 use fenris_traits::allocators::BiDimAllocator;
 use fenris_traits::Real;
 use itertools::izip;
 use nalgebra::allocator::Allocator;
 use nalgebra::{DVectorView, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint, OVector, U1};
+use nalgebra_sparse::{CooMatrix, CsrMatrix};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::iter::repeat;
@@ -35,6 +39,10 @@ pub struct FixedInterpolator<T> {
     node_values: Option<Vec<T>>,
     // Stored by flattening gradients in column-major ordering
     node_gradients: Option<Vec<T>>,
+    // Sorted indices of the points for which the interpolated quantity is undefined.
+    // Interpolation produces NaN values for these points.
+    #[serde(default)]
+    undefined_points: Vec<usize>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -108,6 +116,10 @@ where
         };
 
         for i in 0..result.len() {
+            if self.is_undefined(i) {
+                result[i] = OVector::repeat(T::from_f64(f64::NAN).unwrap());
+                continue;
+            }
             let i_support_start = self.supported_node_offsets[i];
             let i_support_end = self.supported_node_offsets[i + 1];
             let node_values = &node_values[i_support_start..i_support_end];
@@ -175,6 +187,11 @@ where
         let gradient_len = GeometryDim::dim();
 
         for i in 0..gradients.len() {
+            if self.is_undefined(i) {
+                let nan = T::from_f64(f64::NAN).unwrap();
+                gradients[i] = OMatrix::repeat_generic(GeometryDim::name(), SolutionDim::name(), nan);
+                continue;
+            }
             let idx_start = self.supported_node_offsets[i];
             let idx_end = self.supported_node_offsets[i + 1];
             let gradients_begin = gradient_len * idx_start;
@@ -195,9 +212,51 @@ where
             gradients[i] = interpolated;
         }
     }
+    /// Returns the interpolation matrix corresponding to the stored basis function values.
+    ///
+    /// The matrix has `solution_dim` rows per interpolation point and `solution_dim` columns per
+    /// node, with both rows and columns in the interleaved layout used throughout `fenris`.
+    /// Multiplying a vector of interpolation weights by the matrix therefore gives the
+    /// interpolated values, flattened into a single vector. Rows corresponding to points
+    /// without any supported nodes are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpolator does not store basis function values, or if it references
+    /// nodes whose index is not smaller than `num_nodes`.
+    pub fn to_csr(&self, num_nodes: usize, solution_dim: usize) -> CsrMatrix<T> {
+        assert!(
+            self.max_node_index.is_none() || self.max_node_index.unwrap() < num_nodes,
+            "Cannot reference nodes beyond the number of nodes"
+        );
+        let Some(node_values) = &self.node_values else {
+            panic!("cannot construct interpolation matrix without nodal values")
+        };
+
+        let s = solution_dim;
+        let num_points = self.supported_node_offsets.len().checked_sub(1).unwrap();
+        let mut coo = CooMatrix::new(s * num_points, s * num_nodes);
+        for (i, offsets) in self.supported_node_offsets.windows(2).enumerate() {
+            let support = offsets[0]..offsets[1];
+            for (v, j) in izip!(&node_values[support.clone()], &self.node_indices[support]) {
+                for k in 0..s {
+                    coo.push(s * i + k, s * j + k, v.clone());
+                }
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
 }
 
 impl<T> FixedInterpolator<T> {
+    /// Returns `true` if the interpolated quantity is undefined at the given point.
+    ///
+    /// This can only be the case for interpolators constructed with
+    /// [`from_space_and_points_with_policy`](Self::from_space_and_points_with_policy).
+    pub fn is_undefined(&self, point_index: usize) -> bool {
+        self.undefined_points.binary_search(&point_index).is_ok()
+    }
+
     pub fn from_compressed_values(
         node_values: Option<Vec<T>>,
         node_gradients: Option<Vec<T>>,
@@ -235,6 +294,7 @@ impl<T> FixedInterpolator<T> {
             supported_node_offsets,
             node_indices,
             node_gradients,
+            undefined_points: Vec::new(),
         }
     }
 }
@@ -242,8 +302,8 @@ impl<T> FixedInterpolator<T> {
 impl<T: Real> FixedInterpolator<T> {
     /// Creates a new fixed interpolator for the given space and point set.
     ///
-    /// Returns `None` if the space does not have any elements, in which case a meaningful
-    /// interpolator cannot be constructed.
+    /// If a point is outside the domain of the space, the closest element is used to
+    /// interpolate. If the space does not have any elements, the interpolated values are zero.
     pub fn from_space_and_points<Space>(
         space: &Space,
         points: &[OPoint<T, Space::GeometryDim>],
@@ -255,63 +315,10 @@ impl<T: Real> FixedInterpolator<T> {
         Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
         DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
     {
-        let mut supported_node_offsets = Vec::new();
-        let mut node_values = what_to_compute.compute_values().then(|| Vec::new());
-        let mut node_gradients = what_to_compute.compute_gradients().then(|| Vec::new());
-        let mut node_indices = Vec::new();
-
-        let mut ref_gradient_buffer_flat = Vec::new();
-
-        let d = Space::GeometryDim::dim();
-
-        for point in points {
-            let point_node_support_begin = node_indices.len();
-            supported_node_offsets.push(point_node_support_begin);
-
-            let Some((element_idx, xi)) = space.find_closest_element_and_reference_coords(point)
-            // break out of the loop if there are no elements in the space
-            else {
-                break;
-            };
-
-            let element_node_count = space.element_node_count(element_idx);
-            node_indices.extend(repeat(usize::MAX).take(element_node_count));
-            space.populate_element_nodes(&mut node_indices[point_node_support_begin..], element_idx);
-
-            if let Some(node_values) = &mut node_values {
-                debug_assert_eq!(point_node_support_begin, node_values.len());
-                node_values.extend(repeat(T::zero()).take(element_node_count));
-                space.populate_element_basis(element_idx, &mut node_values[point_node_support_begin..], &xi);
-            }
-
-            if let Some(node_gradients) = &mut node_gradients {
-                let point_node_gradients_begin = node_gradients.len();
-                // First store *reference* gradients in buffers
-                ref_gradient_buffer_flat.resize(d * element_node_count, T::zero());
-                let mut ref_gradients = MatrixViewMut::from_slice_generic(
-                    &mut ref_gradient_buffer_flat,
-                    Space::ReferenceDim::name(),
-                    Dyn(element_node_count),
-                );
-                space.populate_element_gradients(element_idx, ref_gradients.as_view_mut(), &xi);
-                // Next compute gradients in physical space by transforming by J^{-T}
-                // and storing directly in flat gradient storage
-                node_gradients.extend(repeat(T::zero()).take(d * element_node_count));
-                let mut gradient_buffer = MatrixViewMut::from_slice_generic(
-                    &mut node_gradients[point_node_gradients_begin..],
-                    Space::GeometryDim::name(),
-                    Dyn(element_node_count),
-                );
-                let jacobian = space.element_reference_jacobian(element_idx, &xi);
-                let j_inv_t = jacobian.try_inverse().unwrap().transpose();
-                gradient_buffer.gemm(T::one(), &j_inv_t, &ref_gradients, T::zero());
-            }
-        }
-
-        supported_node_offsets.push(node_indices.len());
-        assert_eq!(points.len() + 1, supported_node_offsets.len());
-
-        Self::from_compressed_values(node_values, node_gradients, node_indices, supported_node_offsets)
+        let closest_elements = points
+            .iter()
+            .map(|point| space.find_closest_element_and_reference_coords(point));
+        Self::from_closest_elements(space, closest_elements, what_to_compute, false)
     }
 
     /// Same as [`from_space_and_points`](Self::from_space_and_points),
@@ -328,15 +335,6 @@ impl<T: Real> FixedInterpolator<T> {
         DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
         OPoint<T, Space::GeometryDim>: Sync + Send,
     {
-        let mut supported_node_offsets = Vec::new();
-        let mut node_values = what_to_compute.compute_values().then(|| Vec::new());
-        let mut node_gradients = what_to_compute.compute_gradients().then(|| Vec::new());
-        let mut node_indices = Vec::new();
-
-        let mut ref_gradient_buffer_flat = Vec::new();
-
-        let d = Space::GeometryDim::dim();
-
         // This is by far the most expensive operation, hence we do this in parallel first
         let mut closest_elements_and_ref_coords = Vec::new();
         points
@@ -346,14 +344,67 @@ impl<T: Real> FixedInterpolator<T> {
 
         // The rest is sequential for now, it's a bit more cumbersome to parallelize
         // (needs some parallel prefix sum etc.)
-        for closest_element_and_ref_coord in closest_elements_and_ref_coords {
+        Self::from_closest_elements(space, closest_elements_and_ref_coords, what_to_compute, false)
+    }
+
+    /// Creates a new fixed interpolator for the given space and point set, subject to the given
+    /// extrapolation policy.
+    ///
+    /// Interpolated values and gradients are NaN for points where the interpolated quantity is
+    /// undefined, see [`find_closest_elements_with_policy`] for details. The returned diagnostics
+    /// report how the points outside the domain were treated.
+    ///
+    /// Returns an error if the policy is [`ExtrapolationPolicy::Error`] and any point is outside
+    /// the domain of the space.
+    pub fn from_space_and_points_with_policy<Space>(
+        space: &Space,
+        points: &[OPoint<T, Space::GeometryDim>],
+        what_to_compute: ValuesOrGradients,
+        policy: ExtrapolationPolicy,
+    ) -> eyre::Result<(Self, ExtrapolationDiagnostics<T>)>
+    where
+        Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let (closest_elements, diagnostics) = find_closest_elements_with_policy(space, points, policy)?;
+        let interpolator = Self::from_closest_elements(space, closest_elements, what_to_compute, true);
+        Ok((interpolator, diagnostics))
+    }
+
+    /// Constructs the interpolator from the closest element and reference coordinates of
+    /// each point.
+    ///
+    /// Points without a closest element have no supported nodes. If `undefined_if_missing` is
+    /// `true`, they are additionally marked as undefined, so that their interpolated values are NaN.
+    fn from_closest_elements<Space>(
+        space: &Space,
+        closest_elements: impl IntoIterator<Item = Option<(usize, OPoint<T, Space::ReferenceDim>)>>,
+        what_to_compute: ValuesOrGradients,
+        undefined_if_missing: bool,
+    ) -> Self
+    where
+        Space: VolumetricFiniteElementSpace<T>,
+        DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+    {
+        let mut supported_node_offsets = Vec::new();
+        let mut node_values = what_to_compute.compute_values().then(|| Vec::new());
+        let mut node_gradients = what_to_compute.compute_gradients().then(|| Vec::new());
+        let mut node_indices = Vec::new();
+        let mut undefined_points = Vec::new();
+
+        let mut ref_gradient_buffer_flat = Vec::new();
+
+        let d = Space::GeometryDim::dim();
+
+        for (point_index, closest_element_and_ref_coord) in closest_elements.into_iter().enumerate() {
             let point_node_support_begin = node_indices.len();
             supported_node_offsets.push(point_node_support_begin);
 
-            let Some((element_idx, xi)) = closest_element_and_ref_coord
-            // break out of the loop if there are no elements in the space
-            else {
-                break;
+            let Some((element_idx, xi)) = closest_element_and_ref_coord else {
+                if undefined_if_missing {
+                    undefined_points.push(point_index);
+                }
+                continue;
             };
 
             let element_node_count = space.element_node_count(element_idx);
@@ -391,8 +442,38 @@ impl<T: Real> FixedInterpolator<T> {
         }
 
         supported_node_offsets.push(node_indices.len());
-        assert_eq!(points.len() + 1, supported_node_offsets.len());
 
-        Self::from_compressed_values(node_values, node_gradients, node_indices, supported_node_offsets)
+        let mut interpolator =
+            Self::from_compressed_values(node_values, node_gradients, node_indices, supported_node_offsets);
+        interpolator.undefined_points = undefined_points;
+        interpolator
     }
 }
+
+/// Builds the sparse matrix that interpolates a finite element function in the given space at
+/// the target points.
+///
+/// Row `i` of the matrix (or rows `s * i .. s * (i + 1)` for solution dimension `s`) contains
+/// the values of the basis functions of the element containing the target point `i`, so that
+/// multiplying the matrix by a vector of interpolation weights in `space` gives the interpolated
+/// values at the target points. If a point is outside the domain of the space, the closest
+/// element is used to interpolate, as in [`FixedInterpolator::from_space_and_points`].
+///
+/// The matrix only depends on the geometry, and may be reused as long as neither the space nor
+/// the target points move. In order to transfer a function to another Lagrange finite element
+/// space, such as when transferring between levels in a multigrid hierarchy or between two
+/// meshes of the same domain, pass the vertices of the target mesh as the target points.
+/// See [`FixedInterpolator::to_csr`] for the layout of the matrix.
+pub fn build_interpolation_matrix<T, Space>(
+    space: &Space,
+    target_points: &[OPoint<T, Space::GeometryDim>],
+    solution_dim: usize,
+) -> CsrMatrix<T>
+where
+    T: Real,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    FixedInterpolator::from_space_and_points(space, target_points, ValuesOrGradients::OnlyValues)
+        .to_csr(space.num_nodes(), solution_dim)
+}
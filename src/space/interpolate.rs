@@ -1,10 +1,16 @@
-use crate::allocators::TriDimAllocator;
+use crate::allocators::{BiDimAllocator, TriDimAllocator};
 use crate::assembly::buffers::{BufferUpdate, InterpolationBuffer};
-use crate::space::{FindClosestElement, FiniteElementSpace, VolumetricFiniteElementSpace};
+use crate::assembly::local::{invert_jacobian_with_policy, JacobianConditioningPolicy};
+use crate::space::{
+    find_closest_elements_with_policy, ExtrapolationDiagnostics, ExtrapolationPolicy, FindClosestElement,
+    FiniteElementSpace, VolumetricFiniteElementSpace,
+};
 use crate::{Real, SmallDim};
 use davenport::{define_thread_local_workspace, with_thread_local_workspace};
+use eyre::WrapErr;
 use itertools::izip;
-use nalgebra::{DVectorView, DefaultAllocator, OMatrix, OPoint, OVector};
+use nalgebra::{DMatrix, DVectorView, DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, OVector};
+use rayon::prelude::*;
 use std::array;
 
 /// A finite element space that allows interpolation at arbitrary points.
@@ -191,6 +197,37 @@ pub fn interpolate_at_points<T, SolutionDim, Space>(
     })
 }
 
+/// The number of points processed by each parallel task in [`interpolate_at_points_par`] and
+/// [`interpolate_gradient_at_points_par`].
+const PAR_INTERPOLATION_CHUNK_SIZE: usize = 256;
+
+/// Same as [`interpolate_at_points`], but processes the points in parallel with `rayon`.
+///
+/// The points are split into chunks that are interpolated independently. Each thread uses its
+/// own workspace, so that the results are identical to the results of [`interpolate_at_points`].
+///
+/// # Panics
+/// Panics if the result buffer is not of the same length as the number of points.
+pub fn interpolate_at_points_par<T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    result_buffer: &mut [OVector<T, SolutionDim>],
+) where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+    OPoint<T, Space::GeometryDim>: Sync,
+    OVector<T, SolutionDim>: Send,
+{
+    assert_eq!(points.len(), result_buffer.len());
+    points
+        .par_chunks(PAR_INTERPOLATION_CHUNK_SIZE)
+        .zip(result_buffer.par_chunks_mut(PAR_INTERPOLATION_CHUNK_SIZE))
+        .for_each(|(points, results)| interpolate_at_points(space, points, interpolation_weights, results));
+}
+
 /// Interpolate the gradient of a quantity, defined by the global interpolation weights
 /// associated with the given finite element space, at a set of arbitrary points.
 ///
@@ -221,6 +258,40 @@ pub fn interpolate_gradient_at_points<T, SolutionDim, Space>(
     SolutionDim: SmallDim,
     Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
     DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    interpolate_gradient_at_points_with_jacobian_policy(
+        space,
+        points,
+        interpolation_weights,
+        result_buffer,
+        &JacobianConditioningPolicy::Ignore,
+    )
+    .expect("Reference Jacobian must be invertible")
+}
+
+/// Interpolate the gradient of a quantity at a set of arbitrary points, treating ill-conditioned
+/// element Jacobians according to the given policy.
+///
+/// This is the same as [`interpolate_gradient_at_points`], except that the inverse of the
+/// element Jacobian is computed with [`invert_jacobian_with_policy`].
+///
+/// Returns an error, which reports the index of the offending element, if the Jacobian of an
+/// element cannot be inverted subject to the policy.
+///
+/// # Panics
+/// Panics if the result buffer is not of the same length as the number of points.
+pub fn interpolate_gradient_at_points_with_jacobian_policy<T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    result_buffer: &mut [OMatrix<T, Space::GeometryDim, SolutionDim>],
+    jacobian_policy: &JacobianConditioningPolicy<T>,
+) -> eyre::Result<()>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
 {
     assert_eq!(points.len(), result_buffer.len());
     let u = interpolation_weights;
@@ -238,7 +309,9 @@ pub fn interpolate_gradient_at_points<T, SolutionDim, Space>(
                 // so need to transform it by inverse transpose Jacobian matrix
                 let ref_gradient = element_buf.interpolate_ref_gradient();
                 let j = element_buf.element_reference_jacobian();
-                let inv_j_t = j.try_inverse().expect("TODO: Fix this").transpose();
+                let inv_j_t = invert_jacobian_with_policy(&j, jacobian_policy)
+                    .wrap_err_with(|| format!("Failed to interpolate gradient in element {}", element))?
+                    .transpose();
                 *gradient = inv_j_t * ref_gradient;
             } else {
                 // If we can't even find a closest element, then there are no elements in
@@ -247,5 +320,270 @@ pub fn interpolate_gradient_at_points<T, SolutionDim, Space>(
                 *gradient = OMatrix::<T, Space::GeometryDim, SolutionDim>::zeros();
             }
         }
+        Ok(())
     })
 }
+
+/// Same as [`interpolate_gradient_at_points`], but processes the points in parallel with `rayon`.
+///
+/// The points are split into chunks that are interpolated independently. Each thread uses its
+/// own workspace, so that the results are identical to the results of
+/// [`interpolate_gradient_at_points`].
+///
+/// # Panics
+/// Panics if the result buffer is not of the same length as the number of points.
+pub fn interpolate_gradient_at_points_par<T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    result_buffer: &mut [OMatrix<T, Space::GeometryDim, SolutionDim>],
+) where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T> + Sync,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+    OPoint<T, Space::GeometryDim>: Sync,
+    OMatrix<T, Space::GeometryDim, SolutionDim>: Send,
+{
+    assert_eq!(points.len(), result_buffer.len());
+    points
+        .par_chunks(PAR_INTERPOLATION_CHUNK_SIZE)
+        .zip(result_buffer.par_chunks_mut(PAR_INTERPOLATION_CHUNK_SIZE))
+        .for_each(|(points, results)| interpolate_gradient_at_points(space, points, interpolation_weights, results));
+}
+
+/// Interpolate a quantity whose solution dimension is only known at runtime, defined by the
+/// global interpolation weights associated with the given finite element space, at a set of
+/// arbitrary points.
+///
+/// This is the same as [`interpolate_at_points`], except that the solution dimension $s$ is
+/// given at runtime. The result is an $s \times N$ matrix whose $i$-th column is the interpolated
+/// value $u_h(\vec x_i)$, where $N$ is the number of points.
+///
+/// If a point is outside the domain of the finite element space, the closest element is used to
+/// interpolate. If the space has no elements, the interpolated values are zero.
+///
+/// # Panics
+/// Panics if the length of the interpolation weights vector is not equal to $s n$, where
+/// $n$ is the number of nodes in the space.
+pub fn interpolate_at_points_dyn<T, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    solution_dim: usize,
+) -> DMatrix<T>
+where
+    T: Real,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u = interpolation_weights;
+    let s = solution_dim;
+    assert_eq!(
+        u.len(),
+        s * space.num_nodes(),
+        "Length of interpolation weights must be equal to solution dim times number of nodes."
+    );
+
+    let mut result = DMatrix::zeros(s, points.len());
+    let mut element_nodes = Vec::new();
+    let mut basis_values = Vec::new();
+    for (point, mut u_h) in izip!(points, result.column_iter_mut()) {
+        if let Some((element_index, xi)) = space.find_closest_element_and_reference_coords(point) {
+            let node_count = space.element_node_count(element_index);
+            element_nodes.resize(node_count, usize::MAX);
+            basis_values.resize(node_count, T::zero());
+            space.populate_element_nodes(&mut element_nodes, element_index);
+            space.populate_element_basis(element_index, &mut basis_values, &xi);
+            for (&node_index, &phi) in izip!(&element_nodes, &basis_values) {
+                u_h += u.rows(s * node_index, s) * phi;
+            }
+        }
+    }
+    result
+}
+
+/// Interpolate the gradient of a quantity whose solution dimension is only known at runtime,
+/// defined by the global interpolation weights associated with the given finite element space,
+/// at a set of arbitrary points.
+///
+/// This is the same as [`interpolate_gradient_at_points`], except that the solution dimension
+/// $s$ is given at runtime. The result contains the $d \times s$ gradient
+/// $\nabla u_h(\vec x_i)$ for each point $\vec x_i$, where $d$ is the geometry dimension.
+///
+/// If a point is outside the domain of the finite element space, the closest element is used to
+/// interpolate. If the space has no elements, the interpolated gradients are zero.
+///
+/// # Panics
+/// Panics if the length of the interpolation weights vector is not equal to $s n$, where
+/// $n$ is the number of nodes in the space.
+///
+/// Panics if the reference Jacobian of an element is singular at the reference coordinates
+/// of a point.
+pub fn interpolate_gradient_at_points_dyn<T, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    solution_dim: usize,
+) -> Vec<OMatrix<T, Space::GeometryDim, Dyn>>
+where
+    T: Real,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let u = interpolation_weights;
+    let s = solution_dim;
+    assert_eq!(
+        u.len(),
+        s * space.num_nodes(),
+        "Length of interpolation weights must be equal to solution dim times number of nodes."
+    );
+
+    let mut element_nodes = Vec::new();
+    let mut ref_basis_gradients =
+        OMatrix::<T, Space::ReferenceDim, Dyn>::zeros_generic(Space::ReferenceDim::name(), Dyn(0));
+    points
+        .iter()
+        .map(|point| {
+            let mut ref_gradient =
+                OMatrix::<T, Space::ReferenceDim, Dyn>::zeros_generic(Space::ReferenceDim::name(), Dyn(s));
+            if let Some((element_index, xi)) = space.find_closest_element_and_reference_coords(point) {
+                let node_count = space.element_node_count(element_index);
+                element_nodes.resize(node_count, usize::MAX);
+                ref_basis_gradients.resize_horizontally_mut(node_count, T::zero());
+                space.populate_element_nodes(&mut element_nodes, element_index);
+                space.populate_element_gradients(element_index, MatrixViewMut::from(&mut ref_basis_gradients), &xi);
+
+                // Gradient with respect to reference coordinates
+                //  grad_xi u_h = sum_I grad_xi N_I u_I^T
+                for (i, &node_index) in element_nodes.iter().enumerate() {
+                    ref_gradient += ref_basis_gradients.column(i) * u.rows(s * node_index, s).transpose();
+                }
+
+                // We need to compute the gradient with respect to physical coordinates,
+                // so need to transform it by inverse transpose Jacobian matrix
+                let j = space.element_reference_jacobian(element_index, &xi);
+                let inv_j_t = j
+                    .try_inverse()
+                    .expect("Reference Jacobian must be invertible")
+                    .transpose();
+                inv_j_t * ref_gradient
+            } else {
+                ref_gradient
+            }
+        })
+        .collect()
+}
+
+/// Interpolate a quantity, defined by the global interpolation weights associated with the
+/// given finite element space, at a set of arbitrary points, subject to the given extrapolation
+/// policy.
+///
+/// The interpolated value is `None` for points where the quantity is undefined, see
+/// [`find_closest_elements_with_policy`] for details. The returned diagnostics report how the
+/// points outside the domain were treated.
+///
+/// Returns an error if the policy is [`ExtrapolationPolicy::Error`] and any point is outside the
+/// domain of the space.
+///
+/// # Panics
+/// Panics if the length of the interpolation weights vector is not equal to $s n$, where
+/// $s$ is the solution dimension and $n$ is the number of nodes in the space.
+pub fn interpolate_at_points_with_policy<T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    policy: ExtrapolationPolicy,
+) -> eyre::Result<(Vec<Option<OVector<T, SolutionDim>>>, ExtrapolationDiagnostics<T>)>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let u = interpolation_weights;
+    let s = SolutionDim::dim();
+    assert_eq!(
+        u.len(),
+        s * space.num_nodes(),
+        "Length of interpolation weights must be equal to solution dim times number of nodes."
+    );
+
+    let (located, diagnostics) = find_closest_elements_with_policy(space, points, policy)?;
+    let values = with_thread_local_workspace(&INTERPOLATE_WORKSPACE, |buf: &mut InterpolationBuffer<T>| {
+        located
+            .iter()
+            .map(|closest| {
+                closest.as_ref().map(|(element, ref_coords)| {
+                    let mut element_buf = buf.prepare_element_in_space(*element, space, u, s);
+                    element_buf.update_reference_point(ref_coords, BufferUpdate::BasisValues);
+                    element_buf.interpolate()
+                })
+            })
+            .collect()
+    });
+    Ok((values, diagnostics))
+}
+
+/// Interpolate the gradient of a quantity, defined by the global interpolation weights
+/// associated with the given finite element space, at a set of arbitrary points, subject to the
+/// given extrapolation policy.
+///
+/// The interpolated gradient is `None` for points where the quantity is undefined, see
+/// [`find_closest_elements_with_policy`] for details. The returned diagnostics report how the
+/// points outside the domain were treated.
+///
+/// Returns an error if the policy is [`ExtrapolationPolicy::Error`] and any point is outside the
+/// domain of the space.
+///
+/// # Panics
+/// Panics if the length of the interpolation weights vector is not equal to $s n$, where
+/// $s$ is the solution dimension and $n$ is the number of nodes in the space.
+///
+/// Panics if the reference Jacobian of an element is singular at the reference coordinates
+/// of a point.
+pub fn interpolate_gradient_at_points_with_policy<T, SolutionDim, Space>(
+    space: &Space,
+    points: &[OPoint<T, Space::GeometryDim>],
+    interpolation_weights: DVectorView<T>,
+    policy: ExtrapolationPolicy,
+) -> eyre::Result<(
+    Vec<Option<OMatrix<T, Space::GeometryDim, SolutionDim>>>,
+    ExtrapolationDiagnostics<T>,
+)>
+where
+    T: Real,
+    SolutionDim: SmallDim,
+    Space: FindClosestElement<T> + VolumetricFiniteElementSpace<T>,
+    DefaultAllocator: TriDimAllocator<T, Space::GeometryDim, Space::ReferenceDim, SolutionDim>,
+{
+    let u = interpolation_weights;
+    let s = SolutionDim::dim();
+    assert_eq!(
+        u.len(),
+        s * space.num_nodes(),
+        "Length of interpolation weights must be equal to solution dim times number of nodes."
+    );
+
+    let (located, diagnostics) = find_closest_elements_with_policy(space, points, policy)?;
+    let gradients = with_thread_local_workspace(&INTERPOLATE_WORKSPACE, |buf: &mut InterpolationBuffer<T>| {
+        located
+            .iter()
+            .map(|closest| {
+                closest.as_ref().map(|(element, ref_coords)| {
+                    let mut element_buf = buf.prepare_element_in_space(*element, space, u, s);
+                    element_buf.update_reference_point(ref_coords, BufferUpdate::BasisGradients);
+                    let ref_gradient: OMatrix<T, Space::ReferenceDim, SolutionDim> =
+                        element_buf.interpolate_ref_gradient();
+                    let inv_j_t = element_buf
+                        .element_reference_jacobian()
+                        .try_inverse()
+                        .expect("Reference Jacobian must be invertible")
+                        .transpose();
+                    inv_j_t * ref_gradient
+                })
+            })
+            .collect()
+    });
+    Ok((gradients, diagnostics))
+}
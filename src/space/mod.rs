@@ -1,21 +1,30 @@
 //! Finite element spaces.
 
 use crate::allocators::BiDimAllocator;
-use crate::element::{ClosestPoint, FiniteElement, ReferenceFiniteElement};
+use crate::element::{ClosestPoint, FiniteElement, ReferenceFiniteElement, ReferenceShape};
 use crate::geometry::GeometryCollection;
-use crate::nalgebra::{Dyn, MatrixViewMut, OMatrix};
-use crate::SmallDim;
+use crate::nalgebra::{Dyn, MatrixView, MatrixViewMut, OMatrix};
+use crate::{Real, SmallDim};
 use fenris_geometry::AxisAlignedBoundingBox;
 use nalgebra::{DefaultAllocator, OPoint, Scalar};
 
+mod extrapolation;
 mod fixed_interpolator;
 mod interpolate;
 mod space_impl;
 mod spatially_indexed;
+mod vector_space;
 
-pub use fixed_interpolator::{FixedInterpolator, ValuesOrGradients};
+pub use extrapolation::{
+    classify_point, classify_points, find_closest_elements_with_policy, ExtrapolationDiagnostics, ExtrapolationPolicy,
+    PointClassification,
+};
+pub use fixed_interpolator::{build_interpolation_matrix, FixedInterpolator, ValuesOrGradients};
 pub use interpolate::*;
 pub use spatially_indexed::SpatiallyIndexed;
+pub use vector_space::{DofLayout, VectorSpace};
+
+pub(crate) use spatially_indexed::RTreeAccelerationStructure;
 
 /// Describes the connectivity of elements in a finite element space.
 pub trait FiniteElementConnectivity {
@@ -61,6 +70,21 @@ where
         reference_coords: &OPoint<T, Self::ReferenceDim>,
     ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim>;
 
+    /// Compute the reference Jacobian of the given element from the reference gradients of its
+    /// basis functions at the given reference coordinates.
+    ///
+    /// See [`FiniteElement::reference_jacobian_from_basis_gradients`]. The default implementation
+    /// ignores the gradients and calls
+    /// [`element_reference_jacobian`](Self::element_reference_jacobian).
+    fn element_reference_jacobian_from_basis_gradients(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+        _basis_gradients: MatrixView<T, Self::ReferenceDim, Dyn>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.element_reference_jacobian(element_index, reference_coords)
+    }
+
     /// Maps reference coordinates to physical coordinates in the element.
     fn map_element_reference_coords(
         &self,
@@ -163,6 +187,18 @@ where
             .element_reference_jacobian(self.element_index, reference_coords)
     }
 
+    fn reference_jacobian_from_basis_gradients(
+        &self,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+        basis_gradients: MatrixView<T, Self::ReferenceDim, Dyn>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.space.element_reference_jacobian_from_basis_gradients(
+            self.element_index,
+            reference_coords,
+            basis_gradients,
+        )
+    }
+
     fn map_reference_coords(&self, reference_coords: &OPoint<T, Self::ReferenceDim>) -> OPoint<T, Self::GeometryDim> {
         self.space
             .map_element_reference_coords(self.element_index, reference_coords)
@@ -206,6 +242,14 @@ where
     }
 }
 
+/// A finite element space whose elements have one of the standard [reference shapes](ReferenceShape).
+pub trait ReferenceDomainInSpace<T: Real>: FiniteElementSpace<T>
+where
+    DefaultAllocator: BiDimAllocator<T, Self::GeometryDim, Self::ReferenceDim>,
+{
+    fn element_reference_shape(&self, element_index: usize) -> ReferenceShape;
+}
+
 /// A finite element space which can be queried for the closest element to a given point in
 /// physical space.
 pub trait FindClosestElement<T: Scalar>: FiniteElementSpace<T>
@@ -218,4 +262,47 @@ where
         &self,
         point: &OPoint<T, Self::GeometryDim>,
     ) -> Option<(usize, OPoint<T, Self::ReferenceDim>)>;
+
+    /// Find the closest point on the mesh to the given point, represented as the index of the
+    /// closest element, the coordinates in the reference element and the closest point in
+    /// physical space.
+    ///
+    /// The closest point in physical space is the projection of the given point onto the
+    /// domain, and coincides with the given point if the point is inside the domain.
+    fn find_closest_point(
+        &self,
+        point: &OPoint<T, Self::GeometryDim>,
+    ) -> Option<(usize, OPoint<T, Self::ReferenceDim>, OPoint<T, Self::GeometryDim>)> {
+        self.find_closest_element_and_reference_coords(point)
+            .map(|(element_index, xi)| {
+                let closest_point = self.map_element_reference_coords(element_index, &xi);
+                (element_index, xi, closest_point)
+            })
+    }
+}
+
+/// A finite element space which can be queried for the element containing a given point in
+/// physical space.
+///
+/// Unlike [`FindClosestElement`], no element is returned for points outside the domain,
+/// which makes it possible to detect e.g. particles leaving the domain.
+pub trait FindContainingElement<T: Scalar>: FiniteElementSpace<T>
+where
+    DefaultAllocator: BiDimAllocator<T, Self::GeometryDim, Self::ReferenceDim>,
+{
+    /// Find the element containing the given point, represented as the index of the element and
+    /// the coordinates of the point in the reference element.
+    ///
+    /// A point is considered contained in an element if its distance to the element is at most
+    /// `tolerance` times the diameter of the element. The tolerance is intended to absorb
+    /// round-off errors and should be small compared to one.
+    ///
+    /// If the point is contained in several elements, for example because it lies on a face
+    /// or edge shared by multiple elements, the element with the lowest index is returned.
+    /// Returns `None` if no element contains the point.
+    fn find_containing_element(
+        &self,
+        point: &OPoint<T, Self::GeometryDim>,
+        tolerance: T,
+    ) -> Option<(usize, OPoint<T, Self::ReferenceDim>)>;
 }
@@ -1,15 +1,16 @@
 use crate::allocators::ElementConnectivityAllocator;
 use crate::connectivity::CellConnectivity;
 use crate::element::{
-    BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement, ReferenceFiniteElement,
+    BoundsForElement, ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement, ReferenceDomain,
+    ReferenceFiniteElement, ReferenceShape,
 };
 use crate::mesh::Mesh;
-use crate::nalgebra::{Dyn, MatrixViewMut, OMatrix};
+use crate::nalgebra::{Dyn, MatrixView, MatrixViewMut, OMatrix};
 use crate::space::{
     BoundsForElementInSpace, ClosestPointInElementInSpace, FiniteElementConnectivity, FiniteElementSpace,
-    GeometricFiniteElementSpace,
+    GeometricFiniteElementSpace, ReferenceDomainInSpace,
 };
-use crate::SmallDim;
+use crate::{Real, SmallDim};
 use fenris_geometry::AxisAlignedBoundingBox;
 use fenris_traits::allocators::BiDimAllocator;
 use nalgebra::{DefaultAllocator, DimName, OPoint, Scalar};
@@ -127,6 +128,21 @@ where
         element.reference_jacobian(&reference_coords)
     }
 
+    fn element_reference_jacobian_from_basis_gradients(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+        basis_gradients: MatrixView<T, Self::ReferenceDim, Dyn>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        let element = self
+            .connectivity()
+            .get(element_index)
+            .expect("Element index out of bounds")
+            .element(self.vertices())
+            .unwrap();
+        element.reference_jacobian_from_basis_gradients(reference_coords, basis_gradients)
+    }
+
     fn map_element_reference_coords(
         &self,
         element_index: usize,
@@ -183,3 +199,18 @@ where
         conn.element(self.vertices()).unwrap().element_bounds()
     }
 }
+
+impl<T, D, C> ReferenceDomainInSpace<T> for Mesh<T, D, C>
+where
+    T: Real,
+    D: SmallDim,
+    C: ElementConnectivity<T, GeometryDim = D>,
+    C::ReferenceDim: SmallDim,
+    C::Element: ReferenceDomain<T>,
+    DefaultAllocator: ElementConnectivityAllocator<T, C>,
+{
+    fn element_reference_shape(&self, element_index: usize) -> ReferenceShape {
+        let conn = &self.connectivity()[element_index];
+        conn.element(self.vertices()).unwrap().reference_shape()
+    }
+}
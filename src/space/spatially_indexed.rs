@@ -1,25 +1,31 @@
-use crate::element::ClosestPoint;
+use crate::element::{ClosestPoint, ReferenceShape};
+use crate::space::extrapolation::is_within_element_tolerance;
 use crate::space::{
     interpolate_at_points, interpolate_gradient_at_points, BoundsForElementInSpace, ClosestPointInElementInSpace,
-    FindClosestElement, FiniteElementConnectivity, FiniteElementSpace, InterpolateGradientInSpace, InterpolateInSpace,
-    VolumetricFiniteElementSpace,
+    FindClosestElement, FindContainingElement, FiniteElementConnectivity, FiniteElementSpace,
+    InterpolateGradientInSpace, InterpolateInSpace, ReferenceDomainInSpace, VolumetricFiniteElementSpace,
 };
 use crate::SmallDim;
 use fenris_geometry::AxisAlignedBoundingBox;
 use fenris_traits::allocators::{BiDimAllocator, DimAllocator, TriDimAllocator};
 use fenris_traits::Real;
+use itertools::Either;
 use nalgebra::allocator::Allocator;
-use nalgebra::{DVectorView, DefaultAllocator, DimName, Dyn, MatrixViewMut, OMatrix, OPoint, OVector, Scalar};
+use nalgebra::{
+    DVectorView, DefaultAllocator, DimName, Dyn, MatrixView, MatrixViewMut, OMatrix, OPoint, OVector, Scalar,
+};
 use rstar::primitives::GeomWithData;
 use rstar::{Envelope, PointDistance, RTree, RTreeObject, AABB};
 use std::marker::PhantomData;
 
 #[derive(Debug, Clone)]
-struct RTreeAccelerationStructure<D: DimName>
+pub(crate) struct RTreeAccelerationStructure<D: DimName>
 where
     DefaultAllocator: Allocator<f64, D>,
 {
     tree: RTree<GeomWithData<RTreeAABB<D>, usize>>,
+    // The largest diagonal of any bounding box in the tree
+    max_diameter: f64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -90,7 +96,7 @@ where
     where
         DefaultAllocator: DimAllocator<T, D>,
     {
-        let geometries = boxes
+        let geometries: Vec<_> = boxes
             .iter()
             .enumerate()
             .map(|(i, bounding_box)| {
@@ -109,8 +115,12 @@ where
                 GeomWithData::new(RTreeAABB(box_f64), i)
             })
             .collect();
+        let max_diameter = geometries
+            .iter()
+            .map(|geom: &GeomWithData<RTreeAABB<D>, usize>| geom.geom().0.extents().norm())
+            .fold(0.0, f64::max);
         let tree = RTree::bulk_load(geometries);
-        Self { tree }
+        Self { tree, max_diameter }
     }
 
     pub fn closest_cell_candidates<'a, T: Real>(&'a self, point: &OPoint<T, D>) -> impl 'a + Iterator<Item = usize>
@@ -135,18 +145,225 @@ where
             .take_while(move |&(aabb, _)| aabb.dist2_to(&point_f64) <= d2_max)
             .map(|(_, index)| index)
     }
+
+    /// Returns all cells whose bounding boxes, inflated by `tolerance` times the largest
+    /// bounding box diameter, contain the point.
+    pub fn containing_cell_candidates<'a, T: Real>(
+        &'a self,
+        point: &OPoint<T, D>,
+        tolerance: T,
+    ) -> impl 'a + Iterator<Item = usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let point_f64: OPoint<f64, D> = point.map(|x_i| x_i.to_subset().expect("TODO"));
+        let inflation: f64 = tolerance.to_subset().expect("TODO") * self.max_diameter;
+        let query = AxisAlignedBoundingBox::new(point_f64.clone(), point_f64).grow_uniformly(inflation);
+        let query = RTreeAABB(query).envelope();
+        self.tree
+            .locate_in_envelope_intersecting(&query)
+            .map(|geom| geom.data)
+    }
+
+    /// Returns all cells in order of increasing distance from the point to their bounding boxes,
+    /// together with the squared distance to the bounding box.
+    ///
+    /// Since the squared distance to the bounding box is a lower bound for the squared distance
+    /// to the cell, this can be used to terminate a search for the closest cell early.
+    pub fn cells_by_distance<'a, T: Real>(&'a self, point: &OPoint<T, D>) -> impl 'a + Iterator<Item = (f64, usize)>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let point_f64: OPoint<f64, D> = point.map(|x_i| x_i.to_subset().expect("TODO"));
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&RTreePoint(point_f64))
+            .map(|(geom, dist2)| (dist2, geom.data))
+    }
+}
+
+/// Acceleration structure for one-dimensional spaces, for which `rstar` is not applicable.
+///
+/// The bounding intervals of the elements are sorted by their lower bounds, so that the
+/// candidates for the closest element can be found by binary search.
+#[derive(Debug, Clone)]
+struct IntervalAccelerationStructure {
+    // Pairs of (bounding interval, element index), sorted by the lower bound of the interval
+    intervals: Vec<([f64; 2], usize)>,
+    // For each position i in `intervals`, the position j <= i whose interval has the largest
+    // upper bound among the intervals up to and including i
+    running_max_upper: Vec<usize>,
+    // The length of the longest interval
+    max_diameter: f64,
+}
+
+impl IntervalAccelerationStructure {
+    pub fn from_bounding_boxes<T: Real, D: DimName>(boxes: &[AxisAlignedBoundingBox<T, D>]) -> Self
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        assert_eq!(
+            D::dim(),
+            1,
+            "Interval acceleration structure requires one-dimensional bounding boxes"
+        );
+        let mut intervals: Vec<_> = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, bounding_box)| {
+                let lower: f64 = bounding_box.min()[0].to_subset().unwrap();
+                let upper: f64 = bounding_box.max()[0].to_subset().unwrap();
+                ([lower, upper], i)
+            })
+            .collect();
+        intervals.sort_by(|([a, _], _), ([b, _], _)| a.total_cmp(b));
+
+        let mut running_max_upper: Vec<usize> = Vec::with_capacity(intervals.len());
+        for (i, ([_, upper], _)) in intervals.iter().enumerate() {
+            let max_position = match running_max_upper.last() {
+                Some(&j) if intervals[j].0[1] >= *upper => j,
+                _ => i,
+            };
+            running_max_upper.push(max_position);
+        }
+
+        let max_diameter = intervals
+            .iter()
+            .map(|([lower, upper], _)| upper - lower)
+            .fold(0.0, f64::max);
+
+        Self {
+            intervals,
+            running_max_upper,
+            max_diameter,
+        }
+    }
+
+    pub fn closest_cell_candidates<'a, T: Real, D: DimName>(
+        &'a self,
+        point: &OPoint<T, D>,
+    ) -> impl 'a + Iterator<Item = usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let x: f64 = point[0].to_subset().expect("TODO");
+        let dist = move |[lower, upper]: [f64; 2]| (lower - x).max(x - upper).max(0.0);
+        let max_dist = move |[lower, upper]: [f64; 2]| (x - lower).abs().max((upper - x).abs());
+
+        // The closest interval is either the first interval that starts to the right of x,
+        // or the interval with the largest upper bound among the intervals that start at or
+        // to the left of x
+        let num_left = self
+            .intervals
+            .partition_point(|([lower, _], _)| *lower <= x);
+        let left = num_left
+            .checked_sub(1)
+            .map(|i| self.intervals[self.running_max_upper[i]].0);
+        let right = self.intervals.get(num_left).map(|(interval, _)| *interval);
+        let closest = match (left, right) {
+            (Some(left), Some(right)) => Some(if dist(left) <= dist(right) { left } else { right }),
+            (left, right) => left.or(right),
+        };
+
+        // Any interval can be excluded if its closest point is further away than the
+        // maximum possible distance to any point in the closest interval
+        let d_max = closest.map(max_dist).unwrap_or(f64::NAN);
+        let end = self
+            .intervals
+            .partition_point(|([lower, _], _)| *lower <= x + d_max);
+        (0..end)
+            .rev()
+            .take_while(move |&i| self.intervals[self.running_max_upper[i]].0[1] >= x - d_max)
+            .filter(move |&i| dist(self.intervals[i].0) <= d_max)
+            .map(move |i| self.intervals[i].1)
+    }
+
+    /// Returns all cells whose bounding intervals, inflated by `tolerance` times the length of the
+    /// longest interval, contain the point.
+    pub fn containing_cell_candidates<'a, T: Real, D: DimName>(
+        &'a self,
+        point: &OPoint<T, D>,
+        tolerance: T,
+    ) -> impl 'a + Iterator<Item = usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        let x: f64 = point[0].to_subset().expect("TODO");
+        let inflation: f64 = tolerance.to_subset().expect("TODO") * self.max_diameter;
+        let num_left = self
+            .intervals
+            .partition_point(|([lower, _], _)| *lower <= x + inflation);
+        (0..num_left)
+            .rev()
+            .take_while(move |&i| self.intervals[self.running_max_upper[i]].0[1] >= x - inflation)
+            .filter(move |&i| self.intervals[i].0[1] >= x - inflation)
+            .map(move |i| self.intervals[i].1)
+    }
+}
+
+/// Dispatches between the acceleration structures for different dimensions.
+#[derive(Debug, Clone)]
+enum AccelerationStructure<D: DimName>
+where
+    DefaultAllocator: Allocator<f64, D>,
+{
+    RTree(RTreeAccelerationStructure<D>),
+    Intervals(IntervalAccelerationStructure),
+}
+
+impl<D: DimName> AccelerationStructure<D>
+where
+    DefaultAllocator: Allocator<f64, D>,
+{
+    pub fn from_bounding_boxes<T: Real>(boxes: &[AxisAlignedBoundingBox<T, D>]) -> Self
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        // rstar does not support one-dimensional points
+        if D::dim() == 1 {
+            Self::Intervals(IntervalAccelerationStructure::from_bounding_boxes(boxes))
+        } else {
+            Self::RTree(RTreeAccelerationStructure::from_bounding_boxes(boxes))
+        }
+    }
+
+    pub fn closest_cell_candidates<'a, T: Real>(&'a self, point: &OPoint<T, D>) -> impl 'a + Iterator<Item = usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        match self {
+            Self::RTree(tree) => Either::Left(tree.closest_cell_candidates(point)),
+            Self::Intervals(intervals) => Either::Right(intervals.closest_cell_candidates(point)),
+        }
+    }
+
+    pub fn containing_cell_candidates<'a, T: Real>(
+        &'a self,
+        point: &OPoint<T, D>,
+        tolerance: T,
+    ) -> impl 'a + Iterator<Item = usize>
+    where
+        DefaultAllocator: DimAllocator<T, D>,
+    {
+        match self {
+            Self::RTree(tree) => Either::Left(tree.containing_cell_candidates(point, tolerance)),
+            Self::Intervals(intervals) => Either::Right(intervals.containing_cell_candidates(point, tolerance)),
+        }
+    }
 }
 
 /// Provides accelerated geometry queries for a
 /// [finite element space](crate::space::FiniteElementSpace).
 ///
 /// Specifically, given a space that implements [`BoundsForElementInSpace`] and [`ClosestPointInElementInSpace`],
-/// `SpatiallyIndexed` wraps the space and provides an implementation of
-/// [`FindClosestElement`] on top.
+/// `SpatiallyIndexed` wraps the space and provides implementations of
+/// [`FindClosestElement`] and [`FindContainingElement`] on top.
 ///
 /// In addition, `SpatiallyIndexed` provides interpolation of arbitrary points by implementing
 /// the [`InterpolateInSpace`] and [`InterpolateGradientInSpace`] finite element space
 /// traits.
+///
+/// Spaces in two and three dimensions are indexed by an R-tree of element bounding boxes,
+/// whereas one-dimensional spaces are indexed by the sorted bounding intervals of the elements.
 #[derive(Debug, Clone)]
 pub struct SpatiallyIndexed<T, Space>
 where
@@ -155,7 +372,7 @@ where
     DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
 {
     space: Space,
-    tree: RTreeAccelerationStructure<Space::GeometryDim>,
+    tree: AccelerationStructure<Space::GeometryDim>,
     marker: PhantomData<T>,
 }
 
@@ -166,14 +383,36 @@ where
     DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
 {
     pub fn from_space(space: Space) -> Self {
-        let bounding_boxes = space.bounds_for_all_elements();
-        let rtree = RTreeAccelerationStructure::from_bounding_boxes(&bounding_boxes);
+        let tree = Self::build_acceleration_structure(&space);
         Self {
             space,
-            tree: rtree,
+            tree,
             marker: Default::default(),
         }
     }
+
+    fn build_acceleration_structure(space: &Space) -> AccelerationStructure<Space::GeometryDim> {
+        let bounding_boxes = space.bounds_for_all_elements();
+        AccelerationStructure::from_bounding_boxes(&bounding_boxes)
+    }
+
+    /// Rebuilds the acceleration structure from the current geometry of the space.
+    ///
+    /// This is only necessary if the geometry of the space can change without going through
+    /// [`update_space`](Self::update_space), for example if the space has interior mutability.
+    pub fn rebuild(&mut self) {
+        self.tree = Self::build_acceleration_structure(&self.space);
+    }
+
+    /// Modifies the space with the given function and rebuilds the acceleration structure.
+    ///
+    /// This is useful when the geometry of the space changes over time, such as the vertices of a
+    /// mesh in a Lagrangian simulation. The acceleration structure is rebuilt from scratch, and
+    /// the number of elements may change.
+    pub fn update_space(&mut self, update: impl FnOnce(&mut Space)) {
+        update(&mut self.space);
+        self.rebuild();
+    }
 }
 
 impl<T, Space> SpatiallyIndexed<T, Space>
@@ -185,6 +424,10 @@ where
     pub fn space(&self) -> &Space {
         &self.space
     }
+
+    pub fn into_space(self) -> Space {
+        self.space
+    }
 }
 
 impl<T, Space> FiniteElementConnectivity for SpatiallyIndexed<T, Space>
@@ -248,6 +491,16 @@ where
             .element_reference_jacobian(element_index, reference_coords)
     }
 
+    fn element_reference_jacobian_from_basis_gradients(
+        &self,
+        element_index: usize,
+        reference_coords: &OPoint<T, Self::ReferenceDim>,
+        basis_gradients: MatrixView<T, Self::ReferenceDim, Dyn>,
+    ) -> OMatrix<T, Self::GeometryDim, Self::ReferenceDim> {
+        self.space
+            .element_reference_jacobian_from_basis_gradients(element_index, reference_coords, basis_gradients)
+    }
+
     fn map_element_reference_coords(
         &self,
         element_index: usize,
@@ -288,6 +541,17 @@ where
     }
 }
 
+impl<T, Space> ReferenceDomainInSpace<T> for SpatiallyIndexed<T, Space>
+where
+    T: Real,
+    Space: ReferenceDomainInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn element_reference_shape(&self, element_index: usize) -> ReferenceShape {
+        self.space.element_reference_shape(element_index)
+    }
+}
+
 impl<T, Space> FindClosestElement<T> for SpatiallyIndexed<T, Space>
 where
     T: Real,
@@ -330,6 +594,40 @@ where
     }
 }
 
+impl<T, Space> FindContainingElement<T> for SpatiallyIndexed<T, Space>
+where
+    T: Real,
+    Space: ClosestPointInElementInSpace<T>,
+    DefaultAllocator: BiDimAllocator<T, Space::GeometryDim, Space::ReferenceDim>,
+{
+    fn find_containing_element(
+        &self,
+        point: &OPoint<T, Self::GeometryDim>,
+        tolerance: T,
+    ) -> Option<(usize, OPoint<T, Self::ReferenceDim>)> {
+        // Candidates are visited in order of increasing element index, so that points on
+        // interfaces between elements are deterministically assigned to the lowest index
+        let mut candidates: Vec<_> = self
+            .tree
+            .containing_cell_candidates(point, tolerance)
+            .collect();
+        candidates.sort_unstable();
+        candidates.into_iter().find_map(
+            |element_idx| match self.space.closest_point_in_element(element_idx, point) {
+                ClosestPoint::InElement(ref_coords) => Some((element_idx, ref_coords)),
+                ClosestPoint::ClosestPoint(ref_coords) => {
+                    let x = self
+                        .space
+                        .map_element_reference_coords(element_idx, &ref_coords);
+                    let distance = (x - point).norm();
+                    is_within_element_tolerance(&self.space, element_idx, distance, tolerance)
+                        .then_some((element_idx, ref_coords))
+                }
+            },
+        )
+    }
+}
+
 impl<T, Space, SolutionDim> InterpolateInSpace<T, SolutionDim> for SpatiallyIndexed<T, Space>
 where
     T: Real,
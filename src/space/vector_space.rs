@@ -0,0 +1,208 @@
+use crate::nalgebra::{DVector, DVectorView, DimName, Scalar};
+use crate::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use crate::space::FiniteElementConnectivity;
+use crate::Real;
+use std::marker::PhantomData;
+
+/// The ordering of the degrees of freedom of a vector-valued quantity with $s$ components
+/// defined on $N$ nodes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DofLayout {
+    /// Component $i$ of node $I$ is stored at index $s I + i$.
+    ///
+    /// This is the layout used by all assemblers and boundary condition utilities in `fenris`.
+    #[default]
+    Interleaved,
+    /// Component $i$ of node $I$ is stored at index $N i + I$, i.e. all values of the first
+    /// component are followed by all values of the second component and so on.
+    Blocked,
+}
+
+/// A vector-valued finite element space, in which every component of the solution is
+/// discretized with the basis of a scalar finite element space.
+///
+/// The assemblers in `fenris` treat vector-valued problems by associating every node of a scalar
+/// space with `SolutionDim` consecutive degrees of freedom, i.e. with the
+/// [interleaved](DofLayout::Interleaved) layout. The same holds for
+/// [`DirichletBcs`](crate::assembly::DirichletBcs) and
+/// [`find_boundary_dofs`](crate::assembly::find_boundary_dofs). `VectorSpace` makes the layout
+/// explicit, and provides conversions from the interleaved layout used in assembly to any other
+/// layout, so that assembled systems, solution vectors and constrained degrees of freedom can be
+/// brought into a consistent ordering.
+#[derive(Debug, Clone)]
+pub struct VectorSpace<Space, SolutionDim> {
+    space: Space,
+    layout: DofLayout,
+    marker: PhantomData<SolutionDim>,
+}
+
+impl<Space, SolutionDim> VectorSpace<Space, SolutionDim>
+where
+    Space: FiniteElementConnectivity,
+    SolutionDim: DimName,
+{
+    /// Creates a vector-valued space with the [interleaved](DofLayout::Interleaved) layout.
+    pub fn new(space: Space) -> Self {
+        Self::with_layout(space, DofLayout::default())
+    }
+
+    /// Creates a vector-valued space with the given layout.
+    pub fn with_layout(space: Space, layout: DofLayout) -> Self {
+        Self {
+            space,
+            layout,
+            marker: PhantomData,
+        }
+    }
+
+    /// The underlying scalar space.
+    pub fn space(&self) -> &Space {
+        &self.space
+    }
+
+    /// Consumes the vector-valued space and returns the underlying scalar space.
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+
+    pub fn layout(&self) -> DofLayout {
+        self.layout
+    }
+
+    pub fn solution_dim(&self) -> usize {
+        SolutionDim::dim()
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.space.num_nodes()
+    }
+
+    /// The total number of degrees of freedom, i.e. the number of nodes times the solution dimension.
+    pub fn num_dofs(&self) -> usize {
+        self.solution_dim() * self.num_nodes()
+    }
+
+    /// The index of the degree of freedom associated with the given component of the given node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node or the component is out of bounds.
+    pub fn dof_index(&self, node: usize, component: usize) -> usize {
+        let (s, n) = (self.solution_dim(), self.num_nodes());
+        assert!(node < n, "Node index out of bounds");
+        assert!(component < s, "Component must be smaller than the solution dimension");
+        match self.layout {
+            DofLayout::Interleaved => s * node + component,
+            DofLayout::Blocked => n * component + node,
+        }
+    }
+
+    /// The node and component associated with the given degree of freedom.
+    ///
+    /// This is the inverse of [`dof_index`](Self::dof_index).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the degree of freedom is out of bounds.
+    pub fn node_and_component(&self, dof: usize) -> (usize, usize) {
+        let (s, n) = (self.solution_dim(), self.num_nodes());
+        assert!(dof < s * n, "Degree of freedom out of bounds");
+        match self.layout {
+            DofLayout::Interleaved => (dof / s, dof % s),
+            DofLayout::Blocked => (dof % n, dof / n),
+        }
+    }
+
+    /// Maps degrees of freedom in the interleaved layout to degrees of freedom in the layout
+    /// of this space.
+    ///
+    /// This can be used to convert the constrained degrees of freedom returned by
+    /// [`find_boundary_dofs`](crate::assembly::find_boundary_dofs) to the layout of this space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a degree of freedom is out of bounds.
+    pub fn dofs_from_interleaved(&self, interleaved_dofs: &[usize]) -> Vec<usize> {
+        let s = self.solution_dim();
+        interleaved_dofs
+            .iter()
+            .map(|&dof| {
+                assert!(dof < self.num_dofs(), "Degree of freedom out of bounds");
+                self.dof_index(dof / s, dof % s)
+            })
+            .collect()
+    }
+
+    /// Converts a vector in the interleaved layout, such as an assembled load vector,
+    /// to the layout of this space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector is not equal to the number of degrees of freedom.
+    pub fn vector_from_interleaved<'a, T: Scalar>(&self, interleaved: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let interleaved = interleaved.into();
+        assert_eq!(
+            interleaved.len(),
+            self.num_dofs(),
+            "Vector length must match the number of degrees of freedom"
+        );
+        let s = self.solution_dim();
+        DVector::from_fn(self.num_dofs(), |dof, _| {
+            let (node, component) = self.node_and_component(dof);
+            interleaved[s * node + component].clone()
+        })
+    }
+
+    /// Converts a vector in the layout of this space, such as a solution vector,
+    /// to the interleaved layout expected by the assemblers in `fenris`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the vector is not equal to the number of degrees of freedom.
+    pub fn vector_to_interleaved<'a, T: Scalar>(&self, vector: impl Into<DVectorView<'a, T>>) -> DVector<T> {
+        let vector = vector.into();
+        assert_eq!(
+            vector.len(),
+            self.num_dofs(),
+            "Vector length must match the number of degrees of freedom"
+        );
+        let s = self.solution_dim();
+        DVector::from_fn(self.num_dofs(), |interleaved_dof, _| {
+            vector[self.dof_index(interleaved_dof / s, interleaved_dof % s)].clone()
+        })
+    }
+
+    /// Converts a matrix in the interleaved layout, such as an assembled stiffness or mass matrix,
+    /// to the layout of this space.
+    ///
+    /// Rows and columns are permuted in the same way, so that the result is $P A P^T$ for
+    /// a permutation matrix $P$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square with as many rows as there are degrees of freedom.
+    pub fn csr_from_interleaved<T: Real>(&self, interleaved: &CsrMatrix<T>) -> CsrMatrix<T> {
+        let n = self.num_dofs();
+        assert_eq!(
+            interleaved.nrows(),
+            n,
+            "Number of rows must match the number of degrees of freedom"
+        );
+        assert_eq!(
+            interleaved.ncols(),
+            n,
+            "Number of columns must match the number of degrees of freedom"
+        );
+        if self.layout == DofLayout::Interleaved {
+            return interleaved.clone();
+        }
+
+        let s = self.solution_dim();
+        let permute = |dof: usize| self.dof_index(dof / s, dof % s);
+        let mut coo = CooMatrix::new(n, n);
+        for (i, j, v) in interleaved.triplet_iter() {
+            coo.push(permute(i), permute(j), *v);
+        }
+        CsrMatrix::from(&coo)
+    }
+}
@@ -31,6 +31,14 @@ use crate::mesh::Mesh;
 use crate::nalgebra::Dyn;
 use crate::SmallDim;
 
+pub mod small_eig;
+
+mod csr_blocks;
+pub use csr_blocks::*;
+
+mod const_dim;
+pub use const_dim::*;
+
 /// Clones the upper triangle entries into the lower triangle entries.
 ///
 /// The primary use case for this is to construct a full symmetric matrix from a symmetric
@@ -0,0 +1,163 @@
+use crate::util::try_transmute_ref;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, OMatrix, OVector, SMatrix, SVector, Scalar};
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+/// Error returned when a dimension is not among the dimensions supported by a dimension dispatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    dim: usize,
+    supported: RangeInclusive<usize>,
+}
+
+impl DimensionMismatch {
+    /// The dimension that was requested.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// The range of supported dimensions.
+    pub fn supported(&self) -> &RangeInclusive<usize> {
+        &self.supported
+    }
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Dimension {} is not supported (supported dimensions: {}..={})",
+            self.dim,
+            self.supported.start(),
+            self.supported.end()
+        )
+    }
+}
+
+impl Error for DimensionMismatch {}
+
+/// A witness that the generic dimension `D` is the fixed dimension `N`.
+///
+/// A witness can only be obtained through [`ConstDim::try_new`] or [`with_const_dim`], which check
+/// the dimension at runtime. Given a witness, values whose types are generic in `D` can be viewed as
+/// values with the fixed dimension `N`, so that dimension-specific code can be called from
+/// dimension-generic code.
+///
+/// Since the only dimension types of dimension `N` are `Const<N>`, the types involved in the
+/// conversion are in fact identical. The conversion methods nevertheless check this, and panic
+/// rather than reinterpret memory if this were ever not the case.
+#[derive(Debug)]
+pub struct ConstDim<D, const N: usize> {
+    marker: PhantomData<D>,
+}
+
+impl<D, const N: usize> Clone for ConstDim<D, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, const N: usize> Copy for ConstDim<D, N> {}
+
+impl<D: DimName, const N: usize> ConstDim<D, N> {
+    /// Returns a witness for `D == N`, or an error if the dimensions differ.
+    pub fn try_new() -> Result<Self, DimensionMismatch> {
+        if D::dim() == N {
+            Ok(Self { marker: PhantomData })
+        } else {
+            Err(DimensionMismatch {
+                dim: D::dim(),
+                supported: N..=N,
+            })
+        }
+    }
+
+    fn cast_ref<'a, X: 'static, Y: 'static>(&self, x: &'a X) -> &'a Y {
+        try_transmute_ref(x).unwrap_or_else(|| {
+            panic!(
+                "Dimension type {:?} has dimension {} but is not Const<{}>",
+                D::name(),
+                N,
+                N
+            )
+        })
+    }
+
+    /// Views a square matrix of generic dimension as a matrix of fixed dimension.
+    pub fn matrix<'a, T: Scalar>(&self, matrix: &'a OMatrix<T, D, D>) -> &'a SMatrix<T, N, N>
+    where
+        DefaultAllocator: Allocator<T, D, D>,
+    {
+        self.cast_ref(matrix)
+    }
+
+    /// Views a vector of generic dimension as a vector of fixed dimension.
+    pub fn vector<'a, T: Scalar>(&self, vector: &'a OVector<T, D>) -> &'a SVector<T, N>
+    where
+        DefaultAllocator: Allocator<T, D>,
+    {
+        self.cast_ref(vector)
+    }
+
+    /// Converts a square matrix of fixed dimension to a matrix of generic dimension.
+    pub fn generic_matrix<T: Scalar>(&self, matrix: SMatrix<T, N, N>) -> OMatrix<T, D, D>
+    where
+        DefaultAllocator: Allocator<T, D, D>,
+    {
+        OMatrix::from_column_slice(matrix.as_slice())
+    }
+
+    /// Converts a vector of fixed dimension to a vector of generic dimension.
+    pub fn generic_vector<T: Scalar>(&self, vector: SVector<T, N>) -> OVector<T, D>
+    where
+        DefaultAllocator: Allocator<T, D>,
+    {
+        OVector::from_column_slice(vector.as_slice())
+    }
+}
+
+/// Calls the function corresponding to the dimension `D`, which must be one of $1$, $2$ or $3$.
+///
+/// The selected function is given a [`ConstDim`] witness, with which values of generic dimension
+/// can be viewed as values of fixed dimension. This replaces the pattern of matching on `D::dim()`
+/// and reinterpreting the values in each branch. An error is returned if `D` is not between
+/// $1$ and $3$.
+///
+/// ```
+/// use fenris::nalgebra::{DefaultAllocator, DimName, OMatrix, U2};
+/// use fenris::nalgebra::allocator::Allocator;
+/// use fenris::util::with_const_dim;
+///
+/// fn trace<D: DimName>(matrix: &OMatrix<f64, D, D>) -> f64
+/// where
+///     DefaultAllocator: Allocator<f64, D, D>,
+/// {
+///     with_const_dim::<D, _>(
+///         |dim| dim.matrix(matrix)[(0, 0)],
+///         |dim| dim.matrix(matrix).trace(),
+///         |dim| dim.matrix(matrix).trace(),
+///     )
+///     .unwrap()
+/// }
+///
+/// assert_eq!(trace::<U2>(&OMatrix::<f64, U2, U2>::identity()), 2.0);
+/// ```
+pub fn with_const_dim<D, R>(
+    f1: impl FnOnce(ConstDim<D, 1>) -> R,
+    f2: impl FnOnce(ConstDim<D, 2>) -> R,
+    f3: impl FnOnce(ConstDim<D, 3>) -> R,
+) -> Result<R, DimensionMismatch>
+where
+    D: DimName,
+{
+    let marker = PhantomData;
+    match D::dim() {
+        1 => Ok(f1(ConstDim { marker })),
+        2 => Ok(f2(ConstDim { marker })),
+        3 => Ok(f3(ConstDim { marker })),
+        dim => Err(DimensionMismatch { dim, supported: 1..=3 }),
+    }
+}
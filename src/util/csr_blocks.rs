@@ -0,0 +1,219 @@
+use crate::nalgebra::{DVectorView, DVectorViewMut};
+use crate::Real;
+use nalgebra_sparse::CsrMatrix;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+use std::ops::Range;
+
+/// A contiguous block of rows in a CSR matrix, with direct access to the underlying
+/// index and value slices.
+///
+/// Iterating over rows through [`CsrMatrix::row_iter`] incurs a small overhead per row, which
+/// becomes significant for matrices with short rows, such as typical finite element matrices.
+/// A row block instead exposes the raw CSR data for a range of rows.
+#[derive(Debug)]
+pub struct CsrRowBlock<'a, T> {
+    first_row: usize,
+    // Offsets into the data of the full matrix for each row in the block, plus the end offset
+    row_offsets: &'a [usize],
+    col_indices: &'a [usize],
+    values: &'a [T],
+}
+
+impl<'a, T> Clone for CsrRowBlock<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for CsrRowBlock<'a, T> {}
+
+impl<'a, T> CsrRowBlock<'a, T> {
+    fn from_matrix(matrix: &'a CsrMatrix<T>, rows: Range<usize>) -> Self {
+        let row_offsets = &matrix.row_offsets()[rows.start..=rows.end];
+        let data_range = row_offsets[0]..row_offsets[row_offsets.len() - 1];
+        Self {
+            first_row: rows.start,
+            row_offsets,
+            col_indices: &matrix.col_indices()[data_range.clone()],
+            values: &matrix.values()[data_range],
+        }
+    }
+
+    /// The range of (global) row indices covered by this block.
+    pub fn row_range(&self) -> Range<usize> {
+        self.first_row..self.first_row + self.nrows()
+    }
+
+    /// The number of rows in the block.
+    pub fn nrows(&self) -> usize {
+        self.row_offsets.len() - 1
+    }
+
+    /// The number of explicitly stored entries in the block.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The column indices of all entries in the block, stored row by row.
+    pub fn col_indices(&self) -> &'a [usize] {
+        self.col_indices
+    }
+
+    /// The values of all entries in the block, stored row by row.
+    pub fn values(&self) -> &'a [T] {
+        self.values
+    }
+
+    /// The column indices and values of the row with the given *local* index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the local row index is out of bounds.
+    pub fn row(&self, local_index: usize) -> (&'a [usize], &'a [T]) {
+        let offset = self.row_offsets[0];
+        let range = self.row_offsets[local_index] - offset..self.row_offsets[local_index + 1] - offset;
+        (&self.col_indices[range.clone()], &self.values[range])
+    }
+
+    /// Iterates over the column indices and values of the rows in the block.
+    pub fn rows(&self) -> impl 'a + ExactSizeIterator<Item = (&'a [usize], &'a [T])> {
+        let block = *self;
+        (0..self.nrows()).map(move |i| block.row(i))
+    }
+}
+
+/// An iterator over the row blocks of a CSR matrix.
+///
+/// See [`csr_row_blocks`].
+#[derive(Debug)]
+pub struct CsrRowBlocks<'a, T> {
+    matrix: &'a CsrMatrix<T>,
+    block_size: usize,
+    next_row: usize,
+}
+
+impl<'a, T> Iterator for CsrRowBlocks<'a, T> {
+    type Item = CsrRowBlock<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let nrows = self.matrix.nrows();
+        if self.next_row < nrows {
+            let rows = self.next_row..usize::min(self.next_row + self.block_size, nrows);
+            self.next_row = rows.end;
+            Some(CsrRowBlock::from_matrix(self.matrix, rows))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining_rows = self.matrix.nrows() - self.next_row;
+        let remaining_blocks = remaining_rows.div_ceil(self.block_size);
+        (remaining_blocks, Some(remaining_blocks))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for CsrRowBlocks<'a, T> {}
+
+/// Iterates over contiguous blocks of at most `block_size` rows of a CSR matrix.
+///
+/// All blocks except possibly the last contain exactly `block_size` rows.
+///
+/// # Panics
+///
+/// Panics if `block_size` is zero.
+pub fn csr_row_blocks<T>(matrix: &CsrMatrix<T>, block_size: usize) -> CsrRowBlocks<T> {
+    assert!(block_size > 0, "Block size must be positive");
+    CsrRowBlocks {
+        matrix,
+        block_size,
+        next_row: 0,
+    }
+}
+
+/// Parallel version of [`csr_row_blocks`].
+///
+/// The blocks are identical to the blocks produced by [`csr_row_blocks`].
+///
+/// # Panics
+///
+/// Panics if `block_size` is zero.
+pub fn par_row_blocks<T: Sync>(
+    matrix: &CsrMatrix<T>,
+    block_size: usize,
+) -> impl '_ + IndexedParallelIterator<Item = CsrRowBlock<'_, T>> {
+    assert!(block_size > 0, "Block size must be positive");
+    let nrows = matrix.nrows();
+    let num_blocks = nrows.div_ceil(block_size);
+    (0..num_blocks).into_par_iter().map(move |block_index| {
+        let first_row = block_index * block_size;
+        let rows = first_row..usize::min(first_row + block_size, nrows);
+        CsrRowBlock::from_matrix(matrix, rows)
+    })
+}
+
+/// The default number of rows per block used by [`spmv_into`] and [`par_spmv_into`].
+const SPMV_BLOCK_SIZE: usize = 256;
+
+fn spmv_block<T: Real>(block: &CsrRowBlock<T>, x: &DVectorView<T>, y_block: &mut [T]) {
+    for (y_i, (col_indices, values)) in y_block.iter_mut().zip(block.rows()) {
+        *y_i = col_indices
+            .iter()
+            .zip(values)
+            .fold(T::zero(), |sum, (&j, &a_ij)| sum + a_ij * x[j]);
+    }
+}
+
+fn check_spmv_dimensions<T>(matrix: &CsrMatrix<T>, x: &DVectorView<T>, y: &DVectorViewMut<T>) {
+    assert_eq!(
+        matrix.ncols(),
+        x.len(),
+        "Number of matrix columns must match length of x"
+    );
+    assert_eq!(matrix.nrows(), y.len(), "Number of matrix rows must match length of y");
+}
+
+/// Computes the sparse matrix-vector product $\vec y = \vec A \vec x$.
+///
+/// The result is identical to the product computed by `nalgebra_sparse`, but the rows are
+/// processed in blocks with direct access to the CSR data.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the matrix and vectors are not compatible.
+pub fn spmv_into<'a, 'b, T: Real>(
+    matrix: &CsrMatrix<T>,
+    x: impl Into<DVectorView<'a, T>>,
+    y: impl Into<DVectorViewMut<'b, T>>,
+) {
+    let x = x.into();
+    let mut y = y.into();
+    check_spmv_dimensions(matrix, &x, &y);
+    let y = y.as_mut_slice();
+    for block in csr_row_blocks(matrix, SPMV_BLOCK_SIZE) {
+        spmv_block(&block, &x, &mut y[block.row_range()]);
+    }
+}
+
+/// Parallel version of [`spmv_into`].
+///
+/// Each entry of $\vec y$ is computed by a single thread in the same order as in
+/// [`spmv_into`], so the result is deterministic and identical to the serial result.
+///
+/// # Panics
+///
+/// Panics if the dimensions of the matrix and vectors are not compatible.
+pub fn par_spmv_into<'a, 'b, T: Real>(
+    matrix: &CsrMatrix<T>,
+    x: impl Into<DVectorView<'a, T>>,
+    y: impl Into<DVectorViewMut<'b, T>>,
+) {
+    let x = x.into();
+    let mut y = y.into();
+    check_spmv_dimensions(matrix, &x, &y);
+    y.as_mut_slice()
+        .par_chunks_mut(SPMV_BLOCK_SIZE)
+        .zip(par_row_blocks(matrix, SPMV_BLOCK_SIZE))
+        .for_each(|(y_block, block)| spmv_block(&block, &x, y_block));
+}
@@ -0,0 +1,345 @@
+//! Eigenvalue decompositions of small, dense symmetric matrices.
+//!
+//! Element-level analyses (critical time step estimation, projection of element matrices onto
+//! the cone of positive semi-definite matrices, inspection of hourglass modes) require
+//! eigen-decompositions of dense element matrices, which are typically no larger than
+//! about $100 \times 100$. This module provides a cyclic Jacobi eigensolver for such matrices.
+//! The Jacobi method is slower than tridiagonalization-based methods for large matrices,
+//! but it is very robust and computes small eigenvalues to high relative accuracy,
+//! which makes it well suited for this purpose.
+use crate::nalgebra::{DMatrix, DMatrixView, DMatrixViewMut, DVector};
+use crate::util::clone_upper_to_lower;
+use crate::Real;
+use eyre::eyre;
+use numeric_literals::replace_float_literals;
+
+/// The maximum number of sweeps performed by the Jacobi eigensolver.
+///
+/// The Jacobi method converges quadratically, and in practice only a handful of sweeps are
+/// needed for convergence to machine precision. The limit merely guards against
+/// infinite loops in the presence of non-finite input.
+const MAX_JACOBI_SWEEPS: usize = 100;
+
+/// The eigen-decomposition of a symmetric matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricEigen<T: Real> {
+    /// Eigenvalues in ascending order.
+    pub eigenvalues: DVector<T>,
+    /// Eigenvectors stored as columns, in the same order as the eigenvalues.
+    pub eigenvectors: DMatrix<T>,
+}
+
+impl<T: Real> SymmetricEigen<T> {
+    /// Recomposes the matrix $\vec V \vec \Lambda \vec V^T$ from the decomposition.
+    pub fn recompose(&self) -> DMatrix<T> {
+        let v = &self.eigenvectors;
+        let mut v_lambda = v.clone();
+        for (mut col, &lambda) in v_lambda.column_iter_mut().zip(self.eigenvalues.iter()) {
+            col *= lambda;
+        }
+        v_lambda * v.transpose()
+    }
+}
+
+/// Computes the eigen-decomposition of a symmetric matrix.
+///
+/// Only the upper triangular part of the matrix is accessed.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square.
+pub fn symmetric_eigen<'a, T: Real>(matrix: impl Into<DMatrixView<'a, T>>) -> SymmetricEigen<T> {
+    let mut a = matrix.into().clone_owned();
+    let n = a.nrows();
+    let mut eigenvectors = DMatrix::zeros(n, n);
+    symmetric_eigen_in_place(&mut a, Some(DMatrixViewMut::from(&mut eigenvectors)));
+    SymmetricEigen {
+        eigenvalues: a.diagonal(),
+        eigenvectors,
+    }
+}
+
+/// Computes the eigenvalues of a symmetric matrix in ascending order.
+///
+/// Only the upper triangular part of the matrix is accessed.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square.
+pub fn symmetric_eigenvalues<'a, T: Real>(matrix: impl Into<DMatrixView<'a, T>>) -> DVector<T> {
+    let mut a = matrix.into().clone_owned();
+    symmetric_eigen_in_place(&mut a, None);
+    a.diagonal()
+}
+
+/// Computes the eigen-decomposition of a symmetric matrix in place with the cyclic Jacobi method.
+///
+/// **This is a low-level routine** that avoids any allocation, which makes it suitable
+/// for computing eigen-decompositions of element matrices inside of assembly loops.
+/// Most users should prefer [`symmetric_eigen`] or [`symmetric_eigenvalues`].
+///
+/// Only the upper triangular part of the matrix is accessed on input. On return, the diagonal
+/// of the matrix holds the eigenvalues in ascending order, while the off-diagonal entries
+/// are unspecified. If an eigenvector matrix is provided, its columns are overwritten with
+/// the orthonormal eigenvectors corresponding to the eigenvalues.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square, or if the eigenvector matrix does not have the same
+/// dimensions as the matrix.
+pub fn symmetric_eigen_in_place<'a, 'b, T: Real>(
+    matrix: impl Into<DMatrixViewMut<'a, T>>,
+    eigenvectors: Option<DMatrixViewMut<'b, T>>,
+) {
+    symmetric_eigen_in_place_(matrix.into(), eigenvectors)
+}
+
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn symmetric_eigen_in_place_<T: Real>(mut a: DMatrixViewMut<T>, mut eigenvectors: Option<DMatrixViewMut<T>>) {
+    assert_eq!(a.nrows(), a.ncols(), "Matrix must be square");
+    let n = a.nrows();
+    if let Some(v) = &mut eigenvectors {
+        assert_eq!(
+            v.shape(),
+            (n, n),
+            "Eigenvector matrix must have the same dimensions as the matrix"
+        );
+        v.fill_with_identity();
+    }
+
+    // We only work with the upper triangular part of the matrix
+    for j in 0..n {
+        for i in (j + 1)..n {
+            a[(i, j)] = a[(j, i)];
+        }
+    }
+
+    let eps = T::default_epsilon();
+    for _ in 0..MAX_JACOBI_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in (p + 1)..n {
+                let a_pq = a[(p, q)];
+                let a_pp = a[(p, p)];
+                let a_qq = a[(q, q)];
+                // Off-diagonal entries that are negligible relative to the corresponding diagonal
+                // entries do not affect the eigenvalues to working precision. This threshold
+                // gives high relative accuracy also for the small eigenvalues
+                if a_pq.abs() <= eps * (a_pp * a_qq).abs().sqrt() {
+                    a[(p, q)] = T::zero();
+                    a[(q, p)] = T::zero();
+                    continue;
+                }
+                rotated = true;
+
+                // Determine the Jacobi rotation that annihilates a_pq, choosing the smaller
+                // of the two possible rotation angles for stability
+                let theta = (a_qq - a_pp) / (2.0 * a_pq);
+                let sign = if theta >= T::zero() { T::one() } else { -T::one() };
+                let t = sign / (theta.abs() + theta.hypot(T::one()));
+                let c = T::one() / t.hypot(T::one());
+                let s = t * c;
+
+                a[(p, p)] = a_pp - t * a_pq;
+                a[(q, q)] = a_qq + t * a_pq;
+                a[(p, q)] = T::zero();
+                a[(q, p)] = T::zero();
+                for r in 0..n {
+                    if r != p && r != q {
+                        let a_rp = a[(r, p)];
+                        let a_rq = a[(r, q)];
+                        a[(r, p)] = c * a_rp - s * a_rq;
+                        a[(r, q)] = s * a_rp + c * a_rq;
+                        a[(p, r)] = a[(r, p)];
+                        a[(q, r)] = a[(r, q)];
+                    }
+                }
+
+                if let Some(v) = &mut eigenvectors {
+                    for r in 0..n {
+                        let v_rp = v[(r, p)];
+                        let v_rq = v[(r, q)];
+                        v[(r, p)] = c * v_rp - s * v_rq;
+                        v[(r, q)] = s * v_rp + c * v_rq;
+                    }
+                }
+            }
+        }
+
+        if !rotated {
+            break;
+        }
+    }
+
+    // Sort eigenvalues (and eigenvectors) in ascending order with a selection sort,
+    // which requires at most n - 1 swaps
+    for i in 0..n {
+        let mut min_index = i;
+        for j in (i + 1)..n {
+            if a[(j, j)] < a[(min_index, min_index)] {
+                min_index = j;
+            }
+        }
+        if min_index != i {
+            a.swap((i, i), (min_index, min_index));
+            if let Some(v) = &mut eigenvectors {
+                v.swap_columns(i, min_index);
+            }
+        }
+    }
+}
+
+/// Computes the eigen-decomposition of the generalized symmetric-definite eigenvalue problem
+/// $\vec K \vec x = \lambda \vec M \vec x$.
+///
+/// The problem is reduced to a standard symmetric eigenvalue problem through the Cholesky
+/// factorization $\vec M = \vec L \vec L^T$. The eigenvalues are returned in ascending order,
+/// and the eigenvectors are normalized such that $\vec X^T \vec M \vec X = \vec I$.
+///
+/// # Errors
+///
+/// Returns an error if $\vec M$ is not (numerically) positive definite.
+///
+/// # Panics
+///
+/// Panics if the matrices are not square or do not have the same dimensions.
+pub fn generalized_symmetric_eigen<'a, 'b, T: Real>(
+    k: impl Into<DMatrixView<'a, T>>,
+    m: impl Into<DMatrixView<'b, T>>,
+) -> eyre::Result<SymmetricEigen<T>> {
+    let (l, mut a) = reduce_generalized_problem(k.into(), m.into())?;
+    let n = a.nrows();
+    let mut eigenvectors = DMatrix::zeros(n, n);
+    symmetric_eigen_in_place(&mut a, Some(DMatrixViewMut::from(&mut eigenvectors)));
+    // Transform the eigenvectors y of the reduced problem back with x = L^-T y
+    if !l.tr_solve_lower_triangular_mut(&mut eigenvectors) {
+        return Err(eyre!("Matrix M is singular"));
+    }
+    Ok(SymmetricEigen {
+        eigenvalues: a.diagonal(),
+        eigenvectors,
+    })
+}
+
+/// Computes the eigenvalues of the generalized symmetric-definite eigenvalue problem
+/// $\vec K \vec x = \lambda \vec M \vec x$ in ascending order.
+///
+/// See [`generalized_symmetric_eigen`] for details.
+///
+/// # Errors
+///
+/// Returns an error if $\vec M$ is not (numerically) positive definite.
+///
+/// # Panics
+///
+/// Panics if the matrices are not square or do not have the same dimensions.
+pub fn generalized_symmetric_eigenvalues<'a, 'b, T: Real>(
+    k: impl Into<DMatrixView<'a, T>>,
+    m: impl Into<DMatrixView<'b, T>>,
+) -> eyre::Result<DVector<T>> {
+    let (_, mut a) = reduce_generalized_problem(k.into(), m.into())?;
+    symmetric_eigen_in_place(&mut a, None);
+    Ok(a.diagonal())
+}
+
+/// Reduces the generalized problem to the standard problem $\vec L^{-1} \vec K \vec L^{-T}$,
+/// returning $\vec L$ and the reduced matrix.
+#[replace_float_literals(T::from_f64(literal).unwrap())]
+fn reduce_generalized_problem<T: Real>(k: DMatrixView<T>, m: DMatrixView<T>) -> eyre::Result<(DMatrix<T>, DMatrix<T>)> {
+    assert_eq!(k.nrows(), k.ncols(), "K must be square");
+    assert_eq!(m.nrows(), m.ncols(), "M must be square");
+    assert_eq!(k.shape(), m.shape(), "K and M must have the same dimensions");
+    let l = m
+        .clone_owned()
+        .cholesky()
+        .ok_or_else(|| eyre!("Matrix M is not positive definite"))?
+        .unpack();
+    // Since K is symmetric, L^-1 K L^-T = L^-1 (L^-1 K)^T
+    let mut l_inv_k = k.clone_owned();
+    if !l.solve_lower_triangular_mut(&mut l_inv_k) {
+        return Err(eyre!("Matrix M is singular"));
+    }
+    let mut a = l_inv_k.transpose();
+    if !l.solve_lower_triangular_mut(&mut a) {
+        return Err(eyre!("Matrix M is singular"));
+    }
+    // Remove the asymmetry introduced by round-off errors
+    let a = (&a + a.transpose()) * 0.5;
+    Ok((l, a))
+}
+
+/// Projects a symmetric matrix onto the set of symmetric matrices whose eigenvalues are all
+/// at least `min_eigenvalue`.
+///
+/// The projection (in the Frobenius norm) is obtained by clamping the eigenvalues of the matrix
+/// from below. With `min_eigenvalue` set to zero, this gives the nearest positive semi-definite
+/// matrix, which is commonly used to make element stiffness matrices of non-convex energies
+/// suitable for Newton-type methods.
+///
+/// Only the upper triangular part of the matrix is accessed on input. On return, the full
+/// matrix contains the (symmetric) projected matrix.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square.
+pub fn project_symmetric_onto_min_eigenvalue<'a, T: Real>(matrix: impl Into<DMatrixViewMut<'a, T>>, min_eigenvalue: T) {
+    let mut matrix = matrix.into();
+    let n = matrix.nrows();
+    let mut workspace = DMatrix::zeros(n, n);
+    let mut eigenvectors = DMatrix::zeros(n, n);
+    project_symmetric_onto_min_eigenvalue_with_workspace(
+        &mut matrix,
+        min_eigenvalue,
+        &mut workspace,
+        &mut eigenvectors,
+    );
+}
+
+/// Projects a symmetric matrix onto the set of symmetric matrices whose eigenvalues are all
+/// at least `min_eigenvalue`, using the provided buffers as workspace.
+///
+/// **This is a low-level routine** that avoids any allocation.
+/// See [`project_symmetric_onto_min_eigenvalue`] for details. The contents of the two
+/// workspace matrices are unspecified on return.
+///
+/// Returns `true` if any eigenvalue was clamped, in which case the matrix was modified beyond
+/// copying its upper triangle into the lower triangle.
+///
+/// # Panics
+///
+/// Panics if the matrix is not square, or if the workspace matrices do not have the same
+/// dimensions as the matrix.
+pub fn project_symmetric_onto_min_eigenvalue_with_workspace<'a, 'b, 'c, T: Real>(
+    matrix: impl Into<DMatrixViewMut<'a, T>>,
+    min_eigenvalue: T,
+    workspace: impl Into<DMatrixViewMut<'b, T>>,
+    eigenvectors: impl Into<DMatrixViewMut<'c, T>>,
+) -> bool {
+    let mut matrix = matrix.into();
+    let mut workspace = workspace.into();
+    let mut eigenvectors = eigenvectors.into();
+    assert_eq!(matrix.nrows(), matrix.ncols(), "Matrix must be square");
+    assert_eq!(
+        workspace.shape(),
+        matrix.shape(),
+        "Workspace must have same dimensions as matrix"
+    );
+
+    workspace.copy_from(&matrix);
+    symmetric_eigen_in_place(&mut workspace, Some(DMatrixViewMut::from(&mut eigenvectors)));
+
+    let n = matrix.nrows();
+    if (0..n).all(|k| workspace[(k, k)] >= min_eigenvalue) {
+        // Make sure the output is consistent regardless of whether any eigenvalues were clamped
+        clone_upper_to_lower(&mut matrix);
+        return false;
+    }
+
+    matrix.fill(T::zero());
+    for k in 0..n {
+        let lambda = T::max(workspace[(k, k)], min_eigenvalue);
+        let v_k = eigenvectors.column(k);
+        matrix.ger(lambda, &v_k, &v_k, T::one());
+    }
+    true
+}
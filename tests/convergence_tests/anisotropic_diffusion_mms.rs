@@ -0,0 +1,163 @@
+//! Use method of manufactured solutions to verify convergence for anisotropic diffusion.
+//!
+//! The problem is:
+//!   -div(K grad u) = f,
+//! where K = K(x) is a symmetric positive definite diffusion tensor.
+use crate::convergence_tests::poisson_mms_common::solve_linear_system;
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, VectorAssembler,
+};
+use fenris::assembly::local::{
+    ElementEllipticAssemblerBuilder, FunctionSourceAssembler, GeneralQuadratureTable, QuadratureTable,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::{AnisotropicDiffusionOperator, DiffusionTensor};
+use fenris::error::{estimate_H1_seminorm_error, estimate_L2_error};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::coordinates::XY;
+use fenris::nalgebra::{DVector, Matrix2, Point2, Vector1, Vector2, U1, U2};
+use fenris::quadrature;
+use fenris::space::FiniteElementSpace;
+use std::f64::consts::PI;
+use std::ops::Deref;
+
+fn sin(x: f64) -> f64 {
+    x.sin()
+}
+fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+// Exact solution
+fn u_exact(x: &Point2<f64>) -> f64 {
+    let &XY { x, y } = x.coords.deref();
+    sin(PI * x) * sin(PI * y)
+}
+
+fn u_exact_grad(x: &Point2<f64>) -> Vector2<f64> {
+    let &XY { x, y } = x.coords.deref();
+    let u_x = PI * cos(PI * x) * sin(PI * y);
+    let u_y = PI * sin(PI * x) * cos(PI * y);
+    Vector2::new(u_x, u_y)
+}
+
+fn u_exact_xy(x: &Point2<f64>) -> f64 {
+    let &XY { x, y } = x.coords.deref();
+    PI * PI * cos(PI * x) * cos(PI * y)
+}
+
+/// A spatially varying, symmetric positive definite diffusion tensor on the unit square.
+fn varying_tensor(x: &Point2<f64>) -> Matrix2<f64> {
+    let &XY { x, y } = x.coords.deref();
+    Matrix2::new(1.0 + x, 0.5, 0.5, 2.0 + y)
+}
+
+fn varying_tensor_source(x: &Point2<f64>) -> f64 {
+    // Derived from f = - div(K grad u) with K = [1 + x, 0.5; 0.5, 2 + y] and u_xx = u_yy = - pi^2 u, so that
+    //  f = - u_x - u_y - u_xy + pi^2 (3 + x + y) u
+    let &XY { x: x_, y: y_ } = x.coords.deref();
+    let grad = u_exact_grad(x);
+    -grad.x - grad.y - u_exact_xy(x) + PI * PI * (3.0 + x_ + y_) * u_exact(x)
+}
+
+#[allow(non_snake_case)]
+struct DiffusionSolveResult {
+    L2_error: f64,
+    H1_seminorm_error: f64,
+}
+
+#[allow(non_snake_case)]
+fn solve_diffusion<QTable>(
+    mesh: &QuadMesh2d<f64>,
+    qtable: &QTable,
+    f: impl Fn(&Point2<f64>) -> f64,
+) -> DiffusionSolveResult
+where
+    QTable: QuadratureTable<f64, U2, Data = DiffusionTensor<f64, U2>>,
+{
+    let u = DVector::<f64>::zeros(mesh.vertices().len());
+    let diffusion_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(mesh)
+        .with_operator(&AnisotropicDiffusionOperator)
+        .with_quadrature_table(qtable)
+        .with_u(&u)
+        .build();
+    let source_assembler = FunctionSourceAssembler::new(mesh, qtable, U1, |x: &Point2<f64>| Vector1::new(f(x)));
+
+    let mut a = CsrAssembler::default()
+        .assemble(&diffusion_assembler)
+        .unwrap();
+    let mut b = VectorAssembler::default()
+        .assemble_vector(&source_assembler)
+        .unwrap();
+
+    let dirichlet_nodes: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| (x.coords - Vector2::repeat(0.5)).amax() > 0.4999)
+        .map(|(idx, _)| idx)
+        .collect();
+    apply_homogeneous_dirichlet_bc_csr(&mut a, &dirichlet_nodes, 1);
+    apply_homogeneous_dirichlet_bc_rhs(&mut b, &dirichlet_nodes, 1);
+    let u_h = solve_linear_system(&a, &b).unwrap();
+
+    let error_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(6));
+    let L2_error = estimate_L2_error(mesh, &(|x: &Point2<f64>| Vector1::new(u_exact(x))), &u_h, &error_qtable).unwrap();
+    let H1_seminorm_error = estimate_H1_seminorm_error(mesh, &u_exact_grad, &u_h, &error_qtable).unwrap();
+    DiffusionSolveResult {
+        L2_error,
+        H1_seminorm_error,
+    }
+}
+
+#[allow(non_snake_case)]
+fn assert_optimal_quad4_convergence(results: &[DiffusionSolveResult]) {
+    for (coarse, fine) in results.iter().zip(results.iter().skip(1)) {
+        let L2_rate = (coarse.L2_error / fine.L2_error).log2();
+        let H1_rate = (coarse.H1_seminorm_error / fine.H1_seminorm_error).log2();
+        assert!(L2_rate > 1.9, "L2 convergence rate {L2_rate} is too low");
+        assert!(H1_rate > 0.95, "H1 seminorm convergence rate {H1_rate} is too low");
+    }
+}
+
+#[test]
+fn anisotropic_diffusion_2d_quad4_constant_tensor() {
+    // With K = diag(1, 100), we have f = - u_xx - 100 u_yy = 101 pi^2 u
+    let tensor = DiffusionTensor::new(Matrix2::new(1.0, 0.0, 0.0, 100.0));
+    let f = |x: &Point2<f64>| 101.0 * PI * PI * u_exact(x);
+    let results: Vec<_> = [8, 16, 32]
+        .iter()
+        .map(|&res| {
+            let mesh = create_unit_square_uniform_quad_mesh_2d(res);
+            let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+                quadrature::tensor::quadrilateral_gauss(2),
+                tensor.clone(),
+            );
+            solve_diffusion(&mesh, &qtable, f)
+        })
+        .collect();
+    assert_optimal_quad4_convergence(&results);
+}
+
+#[test]
+fn anisotropic_diffusion_2d_quad4_varying_tensor() {
+    let results: Vec<_> = [8, 16, 32]
+        .iter()
+        .map(|&res| {
+            let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(res);
+            // The tensor is evaluated at the physical location of each quadrature point
+            let qtable = GeneralQuadratureTable::from_uniform_quadrature_and_fn(
+                quadrature::tensor::quadrilateral_gauss(2),
+                mesh.connectivity().len(),
+                |element_index, xi| {
+                    let x = mesh.map_element_reference_coords(element_index, xi);
+                    DiffusionTensor::new(varying_tensor(&x))
+                },
+            );
+            solve_diffusion(&mesh, &qtable, varying_tensor_source)
+        })
+        .collect();
+    assert_optimal_quad4_convergence(&results);
+}
@@ -1,3 +1,4 @@
+mod anisotropic_diffusion_mms;
 mod poisson_2d_mms;
 mod poisson_3d_mms;
 mod poisson_mms_common;
@@ -8,7 +8,7 @@ use fenris::assembly::operators::Operator;
 use fenris::element::ElementConnectivity;
 use fenris::io::vtk::VtkCellConnectivity;
 use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
-use fenris::mesh::{Mesh2d, Quad9Mesh2d, Tri6Mesh2d};
+use fenris::mesh::{Mesh2d, Quad9Mesh2d, Tri10Mesh2d, Tri6Mesh2d};
 use fenris::nalgebra::coordinates::XY;
 use fenris::nalgebra::{OPoint, OVector, Point2, Vector1, Vector2, U1, U2};
 use fenris::quadrature;
@@ -112,3 +112,32 @@ fn poisson_2d_tri6() {
     let error_quadrature = quadrature::total_order::triangle(6).unwrap();
     solve_and_produce_output("Tri6", &resolutions, mesh_producer, quadrature, error_quadrature);
 }
+
+#[test]
+#[allow(non_snake_case)]
+fn poisson_2d_tri10() {
+    // Instead of comparing with stored reference values, we directly check that the errors
+    // converge with the optimal orders h^4 and h^3 in the L2 norm and H1 seminorm, respectively
+    let resolutions = [4, 8, 16];
+    let results: Vec<_> = resolutions
+        .iter()
+        .map(|&res| {
+            let mesh = Tri10Mesh2d::from(&create_unit_square_uniform_tri_mesh_2d(res));
+            crate::convergence_tests::poisson_mms_common::solve_poisson(
+                &mesh,
+                quadrature::total_order::triangle(6).unwrap(),
+                quadrature::total_order::triangle(8).unwrap(),
+                &PoissonProblemSourceFunction,
+                u_exact,
+                u_exact_grad,
+            )
+        })
+        .collect();
+
+    for (coarse, fine) in results.iter().zip(results.iter().skip(1)) {
+        let L2_rate = (coarse.L2_error / fine.L2_error).log2();
+        let H1_rate = (coarse.H1_seminorm_error / fine.H1_seminorm_error).log2();
+        assert!(L2_rate > 3.6, "L2 convergence rate {L2_rate} is too low");
+        assert!(H1_rate > 2.7, "H1 seminorm convergence rate {H1_rate} is too low");
+    }
+}
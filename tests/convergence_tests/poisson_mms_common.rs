@@ -103,6 +103,8 @@ where
     {
         let par_b_global = VectorParAssembler::default().assemble_vector(&colors, &source_assembler)?;
         assert_matrix_eq!(b_global, par_b_global, comp = float);
+        let reduced_b_global = VectorParAssembler::default().assemble_vector_by_reduction(&source_assembler)?;
+        assert_matrix_eq!(b_global, reduced_b_global, comp = abs, tol = 1e-12 * b_global.amax());
     }
 
     let laplace_assembler = ElementEllipticAssemblerBuilder::new()
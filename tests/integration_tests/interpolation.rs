@@ -1,24 +1,32 @@
 use crate::integration_tests::data_output_path;
 use fenris::assembly::buffers::{BufferUpdate, InterpolationBuffer};
+use fenris::assembly::local::JacobianConditioningPolicy;
 use fenris::connectivity::Tri3d2Connectivity;
+use fenris::io::msh::load_msh_from_file;
 use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
-use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::procedural::{
+    create_graded_quad_mesh_2d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
 use fenris::mesh::refinement::refine_uniformly_repeat;
-use fenris::mesh::{Mesh, Tet4Mesh, TriangleMesh2d};
+use fenris::mesh::{Mesh, QuadMesh2d, Tet4Mesh, TriangleMesh2d};
 use fenris::quadrature::Quadrature;
 use fenris::space::{
-    FindClosestElement, FiniteElementConnectivity, FiniteElementSpace, FixedInterpolator, InterpolateGradientInSpace,
-    InterpolateInSpace, SpatiallyIndexed, ValuesOrGradients,
+    build_interpolation_matrix, interpolate_at_points_dyn, interpolate_at_points_with_policy,
+    interpolate_gradient_at_points, interpolate_gradient_at_points_dyn,
+    interpolate_gradient_at_points_with_jacobian_policy, interpolate_gradient_at_points_with_policy,
+    ExtrapolationDiagnostics, ExtrapolationPolicy, FindClosestElement, FiniteElementConnectivity, FiniteElementSpace,
+    FixedInterpolator, InterpolateGradientInSpace, InterpolateInSpace, SpatiallyIndexed, ValuesOrGradients,
 };
 use fenris::util::global_vector_from_point_fn;
 use fenris::{quadrature, SmallDim};
 use fenris_traits::allocators::{BiDimAllocator, TriDimAllocator};
 use itertools::{izip, Itertools};
-use matrixcompare::{assert_matrix_eq, prop_assert_matrix_eq};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq, prop_assert_matrix_eq};
 use nalgebra::proptest::vector;
 use nalgebra::{
-    vector, DVectorView, DefaultAllocator, Matrix2x3, Matrix3, OMatrix, OPoint, OVector, Point2, Point3, Vector1,
-    Vector2, Vector3, U1, U2, U3,
+    vector, DVector, DVectorView, DefaultAllocator, Matrix2, Matrix2x3, Matrix3, Matrix3x2, OMatrix, OPoint, OVector,
+    Point2, Point3, Vector1, Vector2, Vector3, U1, U2, U3,
 };
 use proptest::array::{uniform2, uniform3};
 use proptest::collection::vec;
@@ -386,6 +394,282 @@ fn spatially_indexed_tet4_find_closest() {
     }
 }
 
+#[test]
+fn interpolate_gradient_with_jacobian_policy_for_sliver_element() {
+    // The second element is a near-degenerate sliver below the bottom edge of the unit square
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(0.0, 1.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(0.5, -1e-9),
+    ];
+    let connectivity = vec![
+        Tri3d2Connectivity([0, 1, 2]),
+        Tri3d2Connectivity([0, 4, 1]),
+        Tri3d2Connectivity([1, 3, 2]),
+    ];
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(vertices, connectivity);
+    let u_weights = global_vector_from_point_fn(mesh.vertices(), |p| Vector1::new(p.x + 2.0 * p.y));
+    let space = SpatiallyIndexed::from_space(mesh);
+    let error_policy = JacobianConditioningPolicy::Error {
+        max_condition_number: 1e6,
+    };
+    let pseudo_inverse_policy = JacobianConditioningPolicy::PseudoInverse {
+        max_condition_number: 1e6,
+    };
+
+    // Points in well-conditioned elements are unaffected by the policy
+    let points = [Point2::new(0.25, 0.25), Point2::new(0.75, 0.75)];
+    let mut expected = [Vector2::zeros(); 2];
+    interpolate_gradient_at_points(&space, &points, u_weights.as_view(), &mut expected);
+    for policy in [error_policy, pseudo_inverse_policy] {
+        let mut gradients = [Vector2::zeros(); 2];
+        interpolate_gradient_at_points_with_jacobian_policy(
+            &space,
+            &points,
+            u_weights.as_view(),
+            &mut gradients,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(gradients, expected);
+    }
+
+    // The point below the square is closest to the sliver
+    let points = [Point2::new(0.5, -0.5)];
+    let mut gradients = [Vector2::<f64>::zeros()];
+    let error = interpolate_gradient_at_points_with_jacobian_policy(
+        &space,
+        &points,
+        u_weights.as_view(),
+        &mut gradients,
+        &error_policy,
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Failed to interpolate gradient in element 1");
+
+    interpolate_gradient_at_points_with_jacobian_policy(
+        &space,
+        &points,
+        u_weights.as_view(),
+        &mut gradients,
+        &pseudo_inverse_policy,
+    )
+    .unwrap();
+    assert!(gradients[0].iter().all(|x| x.is_finite()));
+    assert!(gradients[0].norm() <= 10.0);
+}
+
+#[test]
+fn interpolate_linear_field_with_runtime_solution_dim_quad4() {
+    // Bilinear elements reproduce linear fields exactly, so values and gradients at arbitrary
+    // probe points must match the exact field
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let a = Matrix2x3::new(1.0, -2.0, 0.5, 3.0, 0.25, -1.5);
+    let b = vector![0.5, -1.0, 2.0];
+    let u_exact = |p: &Point2<f64>| a.transpose() * p.coords + b;
+    let u_weights = global_vector_from_point_fn(mesh.vertices(), u_exact);
+    let space = SpatiallyIndexed::from_space(mesh);
+
+    let probe_points: Vec<_> = (0..=10)
+        .cartesian_product(0..=10)
+        .map(|(i, j)| Point2::new(0.1 * i as f64, 0.1 * j as f64))
+        .collect();
+    let values = interpolate_at_points_dyn(&space, &probe_points, u_weights.as_view(), 3);
+    let gradients = interpolate_gradient_at_points_dyn(&space, &probe_points, u_weights.as_view(), 3);
+    assert_eq!(values.shape(), (3, probe_points.len()));
+    assert_eq!(gradients.len(), probe_points.len());
+    for (p, u, grad_u) in izip!(&probe_points, values.column_iter(), &gradients) {
+        assert_matrix_eq!(u, u_exact(p), comp = abs, tol = 1e-12);
+        assert_matrix_eq!(grad_u, a, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+#[should_panic]
+fn interpolate_at_points_dyn_rejects_incompatible_solution_dim() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let u_weights = DVector::zeros(2 * mesh.vertices().len());
+    let space = SpatiallyIndexed::from_space(mesh);
+    interpolate_at_points_dyn(&space, &[Point2::origin()], u_weights.as_view(), 3);
+}
+
+#[test]
+fn interpolation_matrix_transfers_linear_field_between_meshes_exactly() {
+    // The meshes cover the same domain, but their vertices are unrelated
+    let source_mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(7);
+    let target_mesh: QuadMesh2d<f64> = create_graded_quad_mesh_2d(&[0.0, 0.13, 0.4, 0.71, 1.0], &[0.0, 0.3, 0.55, 1.0]);
+    let a = Matrix2::new(1.0, -2.0, 0.5, 3.0);
+    let b = vector![0.5, -1.0];
+    let u_exact = |p: &Point2<f64>| a.transpose() * p.coords + b;
+    let u_source = global_vector_from_point_fn(source_mesh.vertices(), u_exact);
+    let space = SpatiallyIndexed::from_space(source_mesh);
+
+    let matrix = build_interpolation_matrix(&space, target_mesh.vertices(), 2);
+    assert_eq!(matrix.nrows(), 2 * target_mesh.vertices().len());
+    assert_eq!(matrix.ncols(), 2 * space.num_nodes());
+
+    let u_target = &matrix * &u_source;
+    let u_target_expected = global_vector_from_point_fn(target_mesh.vertices(), u_exact);
+    assert_matrix_eq!(u_target, u_target_expected, comp = abs, tol = 1e-12);
+
+    // Partition of unity
+    for row in matrix.row_iter() {
+        assert_scalar_eq!(row.values().iter().sum::<f64>(), 1.0, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn interpolation_matrix_matches_fixed_interpolator() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(3);
+    let u = global_vector_from_point_fn(mesh.vertices(), u_vector_3d);
+    let space = SpatiallyIndexed::from_space(mesh);
+    // Include points outside the domain, which are interpolated in the closest element
+    let points = [[0.1, 0.2, 0.3], [0.5, 0.5, 0.5], [0.9, 0.05, 0.7], [1.2, 0.5, -0.3]].map(Point3::from);
+
+    let matrix = build_interpolation_matrix(&space, &points, 3);
+    let interpolator = FixedInterpolator::from_space_and_points(&space, &points, ValuesOrGradients::OnlyValues);
+    let expected = flatten_vertically(&interpolator.interpolate::<U3>(&u)).unwrap();
+    assert_matrix_eq!(&matrix * &u, expected, comp = abs, tol = 1e-12);
+}
+
+/// Interior and exterior points for the ball of radius 0.5 centered at the origin.
+///
+/// The ball mesh has vertices at the poles (0, 0, +-0.5) and all of its vertices lie on or
+/// inside the sphere, so the distance from the exterior points on the z-axis to the mesh is
+/// known exactly. The remaining exterior points are closer to the mesh than 0.3.
+fn points_straddling_ball_boundary() -> (Vec<Point3<f64>>, Vec<usize>) {
+    let points = vec![
+        Point3::new(0.0, 0.0, 0.2),
+        Point3::new(0.0, 0.0, 0.8),
+        Point3::new(-0.1, 0.15, 0.05),
+        Point3::new(0.0, 0.0, -0.6),
+        Point3::new(0.12, -0.1, -0.1),
+        Point3::new(0.4, 0.4, 0.3),
+        Point3::new(0.0, 0.1, -0.2),
+        Point3::new(-0.5, -0.3, -0.2),
+    ];
+    let exterior_indices = vec![1, 3, 5, 7];
+    (points, exterior_indices)
+}
+
+#[test]
+fn extrapolation_policies_on_ball_mesh() {
+    use ExtrapolationPolicy::{Error, Extrapolate, Undefined};
+    let mesh: Tet4Mesh<f64> = load_msh_from_file("assets/meshes/sphere_tet4_593.msh").unwrap();
+    let u_exact = |p: &Point3<f64>| vector![p.x + 2.0 * p.y - p.z, 3.0 * p.z + 1.0];
+    let grad_u_exact = Matrix3x2::new(1.0, 0.0, 2.0, 0.0, -1.0, 3.0);
+    let u_weights = global_vector_from_point_fn(mesh.vertices(), u_exact);
+    let space = SpatiallyIndexed::from_space(mesh);
+    let (points, exterior_indices) = points_straddling_ball_boundary();
+    let is_exterior = |i: usize| exterior_indices.contains(&i);
+
+    let check_common_diagnostics = |diagnostics: &ExtrapolationDiagnostics<f64>| {
+        assert_eq!(diagnostics.num_interior_points, 4);
+        assert_eq!(diagnostics.num_exterior_points(), 4);
+        assert_scalar_eq!(diagnostics.max_exterior_distance, 0.3, comp = abs, tol = 1e-12);
+    };
+
+    {
+        let (values, diagnostics) =
+            interpolate_at_points_with_policy::<_, U2, _>(&space, &points, u_weights.as_view(), Extrapolate).unwrap();
+        check_common_diagnostics(&diagnostics);
+        assert_eq!(diagnostics.num_extrapolated_points, 4);
+        assert_eq!(diagnostics.num_undefined_points, 0);
+        // Linear functions are exactly represented by the linear elements, and the extrapolated
+        // values are those at the closest point in the mesh. At the poles, the closest point is
+        // the pole itself.
+        assert!(values.iter().all(Option::is_some));
+        assert_matrix_eq!(
+            values[1].unwrap(),
+            u_exact(&Point3::new(0.0, 0.0, 0.5)),
+            comp = abs,
+            tol = 1e-12
+        );
+        assert_matrix_eq!(
+            values[3].unwrap(),
+            u_exact(&Point3::new(0.0, 0.0, -0.5)),
+            comp = abs,
+            tol = 1e-12
+        );
+
+        let (interpolator, fixed_diagnostics) =
+            FixedInterpolator::from_space_and_points_with_policy(&space, &points, ValuesOrGradients::Both, Extrapolate)
+                .unwrap();
+        assert_eq!(fixed_diagnostics, diagnostics);
+        let fixed_values = interpolator.interpolate::<U2>(&u_weights);
+        for (value, fixed_value) in izip!(&values, &fixed_values) {
+            assert_matrix_eq!(value.unwrap(), fixed_value, comp = abs, tol = 1e-12);
+        }
+    }
+
+    {
+        let (values, diagnostics) =
+            interpolate_at_points_with_policy::<_, U2, _>(&space, &points, u_weights.as_view(), Undefined).unwrap();
+        let (gradients, gradient_diagnostics) =
+            interpolate_gradient_at_points_with_policy::<_, U2, _>(&space, &points, u_weights.as_view(), Undefined)
+                .unwrap();
+        check_common_diagnostics(&diagnostics);
+        assert_eq!(diagnostics.num_extrapolated_points, 0);
+        assert_eq!(diagnostics.num_undefined_points, 4);
+        assert_eq!(gradient_diagnostics, diagnostics);
+
+        let (interpolator, fixed_diagnostics) =
+            FixedInterpolator::from_space_and_points_with_policy(&space, &points, ValuesOrGradients::Both, Undefined)
+                .unwrap();
+        assert_eq!(fixed_diagnostics, diagnostics);
+        let fixed_values = interpolator.interpolate::<U2>(&u_weights);
+        let fixed_gradients = interpolator.interpolate_gradients::<U3, U2>(&u_weights);
+
+        for (i, p) in points.iter().enumerate() {
+            if is_exterior(i) {
+                assert!(values[i].is_none() && gradients[i].is_none());
+                assert!(interpolator.is_undefined(i));
+                assert!(fixed_values[i].iter().all(|x| x.is_nan()));
+                assert!(fixed_gradients[i].iter().all(|x| x.is_nan()));
+            } else {
+                assert_matrix_eq!(values[i].unwrap(), u_exact(p), comp = abs, tol = 1e-12);
+                assert_matrix_eq!(gradients[i].unwrap(), grad_u_exact, comp = abs, tol = 1e-10);
+                assert!(!interpolator.is_undefined(i));
+                assert_matrix_eq!(fixed_values[i], u_exact(p), comp = abs, tol = 1e-12);
+                assert_matrix_eq!(fixed_gradients[i], grad_u_exact, comp = abs, tol = 1e-10);
+            }
+        }
+    }
+
+    {
+        let error =
+            interpolate_at_points_with_policy::<_, U2, _>(&space, &points, u_weights.as_view(), Error).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("4 points are outside the domain"));
+        for i in 0..points.len() {
+            assert_eq!(message.contains(&format!("point {} at", i)), is_exterior(i));
+        }
+        assert!(
+            FixedInterpolator::from_space_and_points_with_policy(&space, &points, ValuesOrGradients::Both, Error)
+                .is_err()
+        );
+
+        // Without exterior points, the error policy behaves like the others
+        let interior_points: Vec<_> = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !is_exterior(*i))
+            .map(|(_, p)| *p)
+            .collect();
+        let (values, diagnostics) =
+            interpolate_at_points_with_policy::<_, U2, _>(&space, &interior_points, u_weights.as_view(), Error)
+                .unwrap();
+        assert_eq!(diagnostics.num_interior_points, 4);
+        assert_eq!(diagnostics.num_exterior_points(), 0);
+        assert_eq!(diagnostics.max_exterior_distance, 0.0);
+        for (value, p) in izip!(values, &interior_points) {
+            assert_matrix_eq!(value.unwrap(), u_exact(p), comp = abs, tol = 1e-12);
+        }
+    }
+}
+
 fn point_in_unit_square() -> impl Strategy<Value = Point2<f64>> {
     uniform2(0.0..=1.0).prop_map(Point2::from)
 }
@@ -427,6 +711,20 @@ proptest! {
                 }
             }
         }
+
+        // The runtime solution dimension variants must agree with the statically sized ones
+        let interpolated_indexed: Vec<Vector3<_>> = indexed.interpolate_at_points(&points, u.as_view());
+        let interpolated_dyn = interpolate_at_points_dyn(&indexed, &points, u.as_view(), 3);
+        prop_assert_eq!(interpolated_dyn.ncols(), points.len());
+        for (value_indexed, value_dyn) in izip!(&interpolated_indexed, interpolated_dyn.column_iter()) {
+            prop_assert_matrix_eq!(value_indexed, value_dyn, comp = abs, tol = 1e-12);
+        }
+        let gradients_indexed: Vec<Matrix2x3<_>> = indexed.interpolate_gradient_at_points(&points, u.as_view());
+        let gradients_dyn = interpolate_gradient_at_points_dyn(&indexed, &points, u.as_view(), 3);
+        prop_assert_eq!(gradients_dyn.len(), points.len());
+        for (gradient_indexed, gradient_dyn) in izip!(&gradients_indexed, &gradients_dyn) {
+            prop_assert_matrix_eq!(gradient_indexed, gradient_dyn, comp = abs, tol = 1e-9);
+        }
     }
 
     #[test]
@@ -460,5 +758,19 @@ proptest! {
                 }
             }
         }
+
+        // The runtime solution dimension variants must agree with the statically sized ones
+        let interpolated_indexed: Vec<Vector3<_>> = indexed.interpolate_at_points(&points, u.as_view());
+        let interpolated_dyn = interpolate_at_points_dyn(&indexed, &points, u.as_view(), 3);
+        prop_assert_eq!(interpolated_dyn.ncols(), points.len());
+        for (value_indexed, value_dyn) in izip!(&interpolated_indexed, interpolated_dyn.column_iter()) {
+            prop_assert_matrix_eq!(value_indexed, value_dyn, comp = abs, tol = 1e-12);
+        }
+        let gradients_indexed: Vec<Matrix3<_>> = indexed.interpolate_gradient_at_points(&points, u.as_view());
+        let gradients_dyn = interpolate_gradient_at_points_dyn(&indexed, &points, u.as_view(), 3);
+        prop_assert_eq!(gradients_dyn.len(), points.len());
+        for (gradient_indexed, gradient_dyn) in izip!(&gradients_indexed, &gradients_dyn) {
+            prop_assert_matrix_eq!(gradient_indexed, gradient_dyn, comp = abs, tol = 1e-9);
+        }
     }
 }
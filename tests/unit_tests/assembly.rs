@@ -3,8 +3,13 @@
 // use fenris_solid::ElasticMaterialModel;
 // use fenris_solid::ElasticityModel;
 
+mod boundary;
+mod coloring;
+mod constraints;
+mod dirichlet;
 mod global;
 mod local;
+mod quadrature_point_data;
 
 // TODO: Re-enable/rewrite tests here as appropriate when possible (most tests rely on some
 // solid mechanics stuff)
@@ -0,0 +1,300 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::AggregateElementAssembler;
+use fenris::assembly::{
+    compute_consistent_boundary_load_totals, compute_consistent_boundary_loads, BoundaryPatchAssembler,
+};
+use fenris::connectivity::{Connectivity, Quad9d2Connectivity, Tri3d3Connectivity};
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{BoundaryPatches, Mesh2d, QuadMesh2d, Tet4Mesh};
+use fenris::nalgebra::{vector, DMatrix, DVector, Point2, Point3, Vector1, Vector2, Vector3, U1, U3};
+use fenris::quadrature;
+use fenris::quadrature::QuadraturePair2d;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn is_on_right_face(x: &Point2<f64>) -> bool {
+    (x.x - 1.0).abs() < 1e-12
+}
+
+fn is_on_top_face(x: &Point3<f64>) -> bool {
+    (x.z - 1.0).abs() < 1e-12
+}
+
+/// Computes the expected nodal loads for a uniform traction on the face x = 1 of the unit square,
+/// given the nodal weights of each node on the face as a function of its y-coordinate.
+fn expected_loads_on_right_face(
+    vertices: &[Point2<f64>],
+    traction: &Vector2<f64>,
+    weight: impl Fn(f64) -> f64,
+) -> DVector<f64> {
+    let mut expected = DVector::zeros(2 * vertices.len());
+    for (i, v) in vertices.iter().enumerate() {
+        if is_on_right_face(v) {
+            expected
+                .fixed_rows_mut::<2>(2 * i)
+                .copy_from(&(weight(v.y) * traction));
+        }
+    }
+    expected
+}
+
+#[test]
+fn uniform_traction_on_linear_faces_2d() {
+    let n = 4;
+    let h = 1.0 / n as f64;
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(n);
+    let traction = vector![2.0, -3.0];
+    let loads =
+        compute_consistent_boundary_loads(&mesh, is_on_right_face, |_| traction, quadrature::univariate::gauss(2));
+
+    // Corner nodes are only connected to a single face, and therefore receive only half the load
+    // of the interior nodes
+    let expected = expected_loads_on_right_face(mesh.vertices(), &traction, |y| {
+        if y.abs() < 1e-12 || (y - 1.0).abs() < 1e-12 {
+            h / 2.0
+        } else {
+            h
+        }
+    });
+    assert_matrix_eq!(loads, expected, comp = abs, tol = 1e-12);
+
+    let all_nodes: Vec<_> = (0..mesh.vertices().len()).collect();
+    let totals = compute_consistent_boundary_load_totals(
+        &mesh,
+        is_on_right_face,
+        |_| traction,
+        quadrature::univariate::gauss(2),
+        &[all_nodes.as_slice()],
+    );
+    assert_eq!(totals.len(), 1);
+    assert_matrix_eq!(totals[0], traction, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn uniform_traction_on_quadratic_faces_2d() {
+    let n = 3;
+    let h = 1.0 / n as f64;
+    let mesh: Mesh2d<f64, Quad9d2Connectivity> = create_unit_square_uniform_quad_mesh_2d(n).into();
+    let traction = vector![2.0, -3.0];
+    let loads =
+        compute_consistent_boundary_loads(&mesh, is_on_right_face, |_| traction, quadrature::univariate::gauss(3));
+
+    // For quadratic segments, the consistent load is distributed as 1/6, 2/3, 1/6 among
+    // the end points and the midpoint of each segment
+    let expected = expected_loads_on_right_face(mesh.vertices(), &traction, |y| {
+        let is_midpoint = ((y / h).fract() - 0.5).abs() < 1e-6;
+        if is_midpoint {
+            2.0 * h / 3.0
+        } else if y.abs() < 1e-12 || (y - 1.0).abs() < 1e-12 {
+            h / 6.0
+        } else {
+            h / 3.0
+        }
+    });
+    assert_matrix_eq!(loads, expected, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn uniform_traction_on_linear_faces_3d() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(3);
+    let traction = vector![1.0, 2.0, 3.0];
+    let quadrature = quadrature::total_order::triangle(1).unwrap();
+    let loads = compute_consistent_boundary_loads(&mesh, is_on_top_face, |_| traction, &quadrature);
+
+    let (face_nodes, other_nodes): (Vec<_>, Vec<_>) =
+        (0..mesh.vertices().len()).partition(|&i| is_on_top_face(&mesh.vertices()[i]));
+    for &node in &face_nodes {
+        let node_load = loads.fixed_rows::<3>(3 * node);
+        // All nodes on the face receive a load parallel to the traction
+        assert!(node_load.dot(&traction) > 0.0);
+        assert_scalar_eq!(node_load.angle(&traction), 0.0, comp = abs, tol = 1e-6);
+    }
+    for &node in &other_nodes {
+        assert_eq!(loads.fixed_rows::<3>(3 * node).norm(), 0.0);
+    }
+
+    let totals = compute_consistent_boundary_load_totals(
+        &mesh,
+        is_on_top_face,
+        |_| traction,
+        &quadrature,
+        &[face_nodes.as_slice(), other_nodes.as_slice()],
+    );
+    // The unit box has a top face of unit area
+    assert_matrix_eq!(totals[0], traction, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(totals[1], vector![0.0, 0.0, 0.0], comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn scalar_flux_integrates_to_total_flux_3d() {
+    // The flux is linear, so the total flux is given by the integral of x + 2 y
+    // over the top face of the unit box, which is 1/2 + 1 = 3/2
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let all_nodes: Vec<_> = (0..mesh.vertices().len()).collect();
+    let totals = compute_consistent_boundary_load_totals(
+        &mesh,
+        is_on_top_face,
+        |x| Vector1::new(x.x + 2.0 * x.y),
+        quadrature::total_order::triangle(1).unwrap(),
+        &[all_nodes.as_slice()],
+    );
+    assert_scalar_eq!(totals[0][0], 1.5, comp = abs, tol = 1e-12);
+}
+
+fn is_on_x_max_face(x: &Point3<f64>) -> bool {
+    (x.x - 1.0).abs() < 1e-12
+}
+
+#[test]
+fn boundary_patches_share_node_numbering() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let patches = mesh.extract_boundary_patches(&[("top", &is_on_top_face), ("right", &is_on_x_max_face)]);
+    assert_eq!(patches.num_patches(), 2);
+    assert_eq!(patches.patch_names(), &["top".to_string(), "right".to_string()]);
+    assert_eq!(patches.find_patch("right"), Some(1));
+    assert_eq!(patches.find_patch("bottom"), None);
+
+    // Each face of the unit box consists of 2 x 2 squares, each split into two triangles
+    let num_faces = patches.surface_mesh().connectivity().len();
+    assert_eq!(patches.element_range(0), 0..8);
+    assert_eq!(patches.element_range(1), 8..num_faces);
+    assert_eq!(num_faces, 16);
+
+    // The 3 nodes on the shared edge appear only once in the boundary numbering
+    let expected_boundary_nodes: Vec<_> = (0..mesh.vertices().len())
+        .filter(|&i| is_on_top_face(&mesh.vertices()[i]) || is_on_x_max_face(&mesh.vertices()[i]))
+        .collect();
+    assert_eq!(expected_boundary_nodes.len(), 9 + 9 - 3);
+    assert_eq!(patches.boundary_to_volume(), expected_boundary_nodes.as_slice());
+    assert_eq!(patches.surface_mesh().vertices().len(), expected_boundary_nodes.len());
+    for (boundary_node, &volume_node) in patches.boundary_to_volume().iter().enumerate() {
+        assert_eq!(
+            patches.surface_mesh().vertices()[boundary_node],
+            mesh.vertices()[volume_node]
+        );
+        assert_eq!(patches.volume_to_boundary(volume_node), Some(boundary_node));
+    }
+
+    let patch_predicates: [(usize, fn(&Point3<f64>) -> bool); 2] = [(0, is_on_top_face), (1, is_on_x_max_face)];
+    for (patch_index, predicate) in patch_predicates {
+        for face in patches.patch_connectivity(patch_index) {
+            assert!(face
+                .vertex_indices()
+                .iter()
+                .all(|&v| predicate(&patches.surface_mesh().vertices()[v])));
+        }
+    }
+}
+
+type TetPatches = BoundaryPatches<f64, U3, Tri3d3Connectivity>;
+
+fn robin_assembler<'a>(
+    patches: &'a TetPatches,
+    patch_index: usize,
+    alpha: f64,
+    g: f64,
+) -> BoundaryPatchAssembler<
+    'a,
+    f64,
+    U3,
+    Tri3d3Connectivity,
+    U1,
+    QuadraturePair2d<f64>,
+    impl Fn(&Point3<f64>) -> f64,
+    impl Fn(&Point3<f64>) -> Vector1<f64>,
+> {
+    let quadrature = quadrature::total_order::triangle(2).unwrap();
+    BoundaryPatchAssembler::robin(
+        patches,
+        patch_index,
+        quadrature,
+        move |_| alpha,
+        move |_| Vector1::new(g),
+    )
+}
+
+#[test]
+fn robin_patch_assemblers_match_monolithic_assembly() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let patches = mesh.extract_boundary_patches(&[("top", &is_on_top_face), ("right", &is_on_x_max_face)]);
+    let (alpha_top, g_top) = (2.0, 1.0);
+    let (alpha_right, g_right) = (5.0, -3.0);
+    let assemblers = [
+        robin_assembler(&patches, 0, alpha_top, g_top),
+        robin_assembler(&patches, 1, alpha_right, g_right),
+    ];
+    let aggregate = AggregateElementAssembler::from_assemblers(&assemblers);
+    let matrix = DMatrix::from(&CsrAssembler::default().assemble(&aggregate).unwrap());
+    let vector = VectorAssembler::default()
+        .assemble_vector(&aggregate)
+        .unwrap();
+
+    // Monolithic computation directly on the boundary faces of the volume mesh, using the
+    // exact mass matrix |T| / 12 (1 + delta_ij) of linear triangles
+    let n = mesh.vertices().len();
+    let mut expected_matrix = DMatrix::zeros(n, n);
+    let mut expected_vector = DVector::zeros(n);
+    for (face, _, _) in mesh.find_boundary_faces() {
+        let nodes = face.vertex_indices();
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices()[nodes[i]]);
+        let area = 0.5 * (b - a).cross(&(c - a)).norm();
+        let patch_data: [(fn(&Point3<f64>) -> bool, f64, f64); 2] = [
+            (is_on_top_face, alpha_top, g_top),
+            (is_on_x_max_face, alpha_right, g_right),
+        ];
+        for (predicate, alpha, g) in patch_data {
+            if nodes.iter().all(|&v| predicate(&mesh.vertices()[v])) {
+                for i in 0..3 {
+                    for j in 0..3 {
+                        let m_ij = if i == j { 2.0 } else { 1.0 };
+                        expected_matrix[(nodes[i], nodes[j])] += alpha * area * m_ij / 12.0;
+                    }
+                    expected_vector[nodes[i]] += alpha * g * area / 3.0;
+                }
+            }
+        }
+    }
+    assert_matrix_eq!(matrix, expected_matrix, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(vector, expected_vector, comp = abs, tol = 1e-12);
+
+    // Each patch has unit area, so the shared edge must not be counted more than once per patch
+    assert_scalar_eq!(matrix.sum(), alpha_top + alpha_right, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(
+        vector.sum(),
+        alpha_top * g_top + alpha_right * g_right,
+        comp = abs,
+        tol = 1e-12
+    );
+    let edge_midpoint = (0..n)
+        .find(|&i| mesh.vertices()[i] == Point3::new(1.0, 0.5, 1.0))
+        .unwrap();
+    assert!(expected_vector[edge_midpoint] != 0.0);
+    assert_scalar_eq!(
+        vector[edge_midpoint],
+        expected_vector[edge_midpoint],
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn traction_patch_assembler_matches_consistent_boundary_loads() {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let patches = mesh.extract_boundary_patches(&[("top", &is_on_top_face), ("right", &is_on_x_max_face)]);
+    let traction = |x: &Point3<f64>| Vector3::new(x.x, -2.0 * x.y, 1.0);
+    let assembler = BoundaryPatchAssembler::traction(
+        &patches,
+        patches.find_patch("top").unwrap(),
+        quadrature::total_order::triangle(2).unwrap(),
+        traction,
+    );
+    let loads = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    let expected = compute_consistent_boundary_loads(
+        &mesh,
+        is_on_top_face,
+        traction,
+        quadrature::total_order::triangle(2).unwrap(),
+    );
+    assert_matrix_eq!(loads, expected, comp = abs, tol = 1e-12);
+}
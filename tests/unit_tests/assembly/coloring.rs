@@ -0,0 +1,209 @@
+use fenris::assembly::local::ElementConnectivityAssembler;
+use fenris::assembly::{color_elements, color_elements_with_strategy, ElementColoring, ElementColoringStrategy};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{HexMesh, QuadMesh2d, Tet4Mesh, TriangleMesh2d};
+use fenris_paradis::DisjointSubsets;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+const STRATEGIES: [ElementColoringStrategy; 2] = [
+    ElementColoringStrategy::Greedy,
+    ElementColoringStrategy::LargestDegreeFirst,
+];
+
+#[derive(Debug)]
+struct MockElementAssembler {
+    num_nodes: usize,
+    element_connectivities: Vec<Vec<usize>>,
+}
+
+#[rustfmt::skip]
+impl ElementConnectivityAssembler for MockElementAssembler {
+    fn solution_dim(&self) -> usize { 1 }
+    fn num_elements(&self) -> usize { self.element_connectivities.len() }
+    fn num_nodes(&self) -> usize { self.num_nodes }
+    fn element_node_count(&self, element_index: usize) -> usize { self.element_connectivities[element_index].len() }
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        output.copy_from_slice(&self.element_connectivities[element_index])
+    }
+}
+
+fn mock_element_assembler() -> impl Strategy<Value = MockElementAssembler> {
+    (1..30usize).prop_flat_map(|num_nodes| {
+        vec(vec(0..num_nodes, 0..6), 0..40).prop_map(move |element_connectivities| MockElementAssembler {
+            num_nodes,
+            element_connectivities,
+        })
+    })
+}
+
+/// Computes the maximum number of other elements that any element shares a node with.
+fn max_element_degree(assembler: &impl ElementConnectivityAssembler) -> usize {
+    let mut node_elements = vec![Vec::new(); assembler.num_nodes()];
+    let mut nodes = Vec::new();
+    for element_index in 0..assembler.num_elements() {
+        nodes.resize(assembler.element_node_count(element_index), 0);
+        assembler.populate_element_nodes(&mut nodes, element_index);
+        for &node in &nodes {
+            node_elements[node].push(element_index);
+        }
+    }
+
+    (0..assembler.num_elements())
+        .map(|element_index| {
+            nodes.resize(assembler.element_node_count(element_index), 0);
+            assembler.populate_element_nodes(&mut nodes, element_index);
+            let mut neighbors: Vec<_> = nodes
+                .iter()
+                .flat_map(|&node| node_elements[node].iter().copied())
+                .filter(|&neighbor| neighbor != element_index)
+                .collect();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors.len()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn assert_valid_coloring_within_bounds(coloring: &ElementColoring, assembler: &impl ElementConnectivityAssembler) {
+    coloring.verify(assembler).unwrap();
+    assert_eq!(coloring.num_elements(), assembler.num_elements());
+    assert_eq!(coloring.colors().count(), coloring.num_colors());
+    assert!(coloring.num_colors() <= max_element_degree(assembler) + 1);
+    for elements in coloring.colors() {
+        assert!(!elements.is_empty());
+        assert!(elements.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
+
+#[test]
+fn color_elements_empty_assembler() {
+    let assembler = MockElementAssembler {
+        num_nodes: 0,
+        element_connectivities: vec![],
+    };
+    let coloring = color_elements(&assembler);
+    assert_eq!(coloring.num_colors(), 0);
+    assert_eq!(coloring.num_elements(), 0);
+    coloring.verify(&assembler).unwrap();
+}
+
+#[test]
+fn color_elements_simple_example() {
+    let assembler = MockElementAssembler {
+        num_nodes: 6,
+        element_connectivities: vec![vec![0, 1, 2], vec![2, 3], vec![], vec![3, 4, 4, 4], vec![5], vec![1, 5]],
+    };
+
+    // Element 5 is visited last, at which point its neighbors 0 and 4 have been
+    // assigned different colors, which forces the introduction of a third color
+    let coloring = color_elements(&assembler);
+    assert_valid_coloring_within_bounds(&coloring, &assembler);
+    assert_eq!(coloring.num_colors(), 3);
+    assert_eq!(coloring.color(0), &[0, 2, 3]);
+    assert_eq!(coloring.color(1), &[1, 4]);
+    assert_eq!(coloring.color(2), &[5]);
+
+    // Elements 0, 1 and 5 have the largest degree and are colored first, which avoids the third color
+    let coloring = color_elements_with_strategy(&assembler, ElementColoringStrategy::LargestDegreeFirst);
+    assert_valid_coloring_within_bounds(&coloring, &assembler);
+    assert_eq!(coloring.num_colors(), 2);
+    assert_eq!(coloring.color(0), &[0, 3, 4]);
+    assert_eq!(coloring.color(1), &[1, 2, 5]);
+}
+
+#[test]
+fn verify_detects_invalid_colorings() {
+    let assembler = MockElementAssembler {
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2], vec![3]],
+    };
+    let coloring = color_elements(&assembler);
+    coloring.verify(&assembler).unwrap();
+
+    // The same coloring is not valid if elements 0 and 2 share a node
+    let conflicting_assembler = MockElementAssembler {
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2], vec![0, 3]],
+    };
+    assert!(coloring.verify(&conflicting_assembler).is_err());
+
+    // Nor is it valid for an assembler with a different number of elements
+    let larger_assembler = MockElementAssembler {
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2], vec![3], vec![3]],
+    };
+    assert!(coloring.verify(&larger_assembler).is_err());
+}
+
+#[test]
+fn color_elements_structured_meshes() {
+    for strategy in STRATEGIES {
+        // The elements sharing an interior node form a clique, so the number of elements around a
+        // node is a lower bound on the number of colors. For structured quad meshes, the
+        // bound is attained
+        for cells_per_dim in [2, 3, 5, 8, 16] {
+            let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(cells_per_dim);
+            let coloring = color_elements_with_strategy(&mesh, strategy);
+            assert_valid_coloring_within_bounds(&coloring, &mesh);
+            assert_eq!(coloring.num_colors(), 4);
+        }
+
+        for cells_per_dim in [2, 4, 6] {
+            let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(cells_per_dim);
+            let coloring = color_elements_with_strategy(&mesh, strategy);
+            assert_valid_coloring_within_bounds(&coloring, &mesh);
+            assert!(coloring.num_colors() >= 6);
+
+            let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(cells_per_dim);
+            let coloring = color_elements_with_strategy(&mesh, strategy);
+            assert_valid_coloring_within_bounds(&coloring, &mesh);
+            assert!(coloring.num_colors() >= 8);
+
+            let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(cells_per_dim);
+            let coloring = color_elements_with_strategy(&mesh, strategy);
+            assert_valid_coloring_within_bounds(&coloring, &mesh);
+        }
+    }
+
+    // Colors are perfectly balanced for a uniform quad mesh with an even number of cells per dimension
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(8);
+    let coloring = color_elements(&mesh);
+    assert!(coloring.colors().all(|elements| elements.len() == 16));
+}
+
+#[test]
+fn color_elements_to_disjoint_subsets() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(5);
+    let coloring = color_elements(&mesh);
+    let subsets = coloring.to_disjoint_subsets();
+    assert_eq!(subsets.len(), coloring.num_colors());
+    for (color, elements) in subsets.iter().zip(coloring.colors()) {
+        assert_eq!(color.labels(), elements);
+        for (&element_index, nodes) in color.labels().iter().zip(color.subsets().iter()) {
+            assert_eq!(nodes, &mesh.connectivity()[element_index].0);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn color_elements_produces_valid_and_deterministic_colorings(assembler in mock_element_assembler()) {
+        for strategy in STRATEGIES {
+            let coloring = color_elements_with_strategy(&assembler, strategy);
+            assert_valid_coloring_within_bounds(&coloring, &assembler);
+            prop_assert_eq!(&color_elements_with_strategy(&assembler, strategy), &coloring);
+
+            let subsets = coloring.to_disjoint_subsets();
+            prop_assert_eq!(subsets.len(), coloring.num_colors());
+            for (color, elements) in subsets.iter().zip(coloring.colors()) {
+                prop_assert_eq!(color.labels(), elements);
+            }
+            prop_assert_eq!(Vec::<DisjointSubsets>::from(coloring), subsets);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    ElementEllipticAssemblerBuilder, ElementSourceAssemblerBuilder, SourceFunction, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
+use fenris::assembly::{AffineConstraint, AffineConstraints};
+use fenris::connectivity::{Quad4d2Connectivity, Segment2d1Connectivity};
+use fenris::mesh::{Mesh, QuadMesh2d};
+use fenris::nalgebra::{DMatrix, DVector, Point1, Point2, Vector1, U1};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris::quadrature::CanonicalStiffnessQuadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+fn tridiagonal_example(n: usize) -> (CsrMatrix<f64>, DVector<f64>) {
+    let matrix = DMatrix::from_fn(n, n, |i, j| match i.abs_diff(j) {
+        0 => 4.0,
+        1 => -1.0,
+        _ => 0.0,
+    });
+    (CsrMatrix::from(&matrix), DVector::from_fn(n, |i, _| i as f64))
+}
+
+#[test]
+fn affine_constraints_resolve_chained_constraints() {
+    // u_3 = 2 u_1 + 1, u_1 = 0.5 u_0 + 0.5 u_2 - 1, u_4 = u_3 - u_0
+    let constraints = AffineConstraints::from_constraints([
+        AffineConstraint::new(4, vec![(3, 1.0), (0, -1.0)]),
+        AffineConstraint::new(3, vec![(1, 2.0)]).with_inhomogeneity(1.0),
+        AffineConstraint::new(1, vec![(0, 0.5), (2, 0.5)]).with_inhomogeneity(-1.0),
+    ])
+    .unwrap();
+    assert_eq!(constraints.dofs(), &[1, 3, 4]);
+    assert_eq!(constraints.len(), 3);
+    assert_eq!(constraints.constraint(0), None);
+    assert_eq!(constraints.constraint(1), Some((&[0, 2][..], &[0.5, 0.5][..], -1.0)));
+    assert_eq!(constraints.constraint(3), Some((&[0, 2][..], &[1.0, 1.0][..], -1.0)));
+    // The contributions of u_0 cancel
+    assert_eq!(constraints.constraint(4), Some((&[0, 2][..], &[0.0, 1.0][..], -1.0)));
+
+    let u = constraints
+        .expand_solution(&DVector::from_column_slice(&[2.0, 3.0, 7.0]))
+        .unwrap();
+    // The reduced DOFs are mapped to the unconstrained DOFs 0, 2 and 5
+    assert_matrix_eq!(u, DVector::from_column_slice(&[2.0, 1.5, 3.0, 4.0, 2.0, 7.0]));
+}
+
+#[test]
+fn affine_constraints_reject_invalid_constraints() {
+    let error = AffineConstraints::from_constraints([
+        AffineConstraint::new(1, vec![(0, 1.0)]),
+        AffineConstraint::fixed(1, 2.0),
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("more than once"));
+
+    let error = AffineConstraints::from_constraints([AffineConstraint::new(1, vec![(1, 1.0)])]).unwrap_err();
+    assert!(error.to_string().contains("cyclic"));
+
+    let error = AffineConstraints::from_constraints([
+        AffineConstraint::new(0, vec![(1, 1.0)]),
+        AffineConstraint::new(1, vec![(2, 0.5)]),
+        AffineConstraint::new(2, vec![(0, 2.0)]),
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("cyclic"));
+
+    let (matrix, rhs) = tridiagonal_example(3);
+    let constraints = AffineConstraints::from_constraints([AffineConstraint::new(1, vec![(3, 1.0)])]).unwrap();
+    let error = constraints.condense_system(&matrix, &rhs).unwrap_err();
+    assert!(error.to_string().contains("out of bounds"));
+    assert!(constraints
+        .condense_system(&matrix, &DVector::zeros(2))
+        .is_err());
+}
+
+#[test]
+fn affine_constraints_condense_system_matches_explicit_substitution() {
+    let (matrix, rhs) = tridiagonal_example(4);
+    // u_1 = 0.5 u_0 + 0.5 u_2 + 1, u_3 = 2
+    let constraints = AffineConstraints::from_constraints([
+        AffineConstraint::new(1, vec![(0, 0.5), (2, 0.5)]).with_inhomogeneity(1.0),
+        AffineConstraint::fixed(3, 2.0),
+    ])
+    .unwrap();
+
+    #[rustfmt::skip]
+    let c = DMatrix::from_row_slice(4, 2, &[
+        1.0, 0.0,
+        0.5, 0.5,
+        0.0, 1.0,
+        0.0, 0.0,
+    ]);
+    let g = DVector::from_column_slice(&[0.0, 1.0, 0.0, 2.0]);
+    assert_matrix_eq!(DMatrix::from(&constraints.expansion_matrix(4).unwrap()), c);
+
+    let a = DMatrix::from(&matrix);
+    let (reduced_matrix, reduced_rhs) = constraints.condense_system(&matrix, &rhs).unwrap();
+    let expected_matrix = c.transpose() * &a * &c;
+    let expected_rhs = c.transpose() * (&rhs - &a * &g);
+    assert_matrix_eq!(DMatrix::from(&reduced_matrix), expected_matrix, comp = float);
+    assert_matrix_eq!(reduced_rhs, expected_rhs, comp = float);
+
+    let reduced_solution = DMatrix::from(&reduced_matrix)
+        .lu()
+        .solve(&reduced_rhs)
+        .unwrap();
+    let u = constraints.expand_solution(&reduced_solution).unwrap();
+    assert_matrix_eq!(u, &c * &reduced_solution + &g, comp = float);
+}
+
+/// A quad mesh of $[0, 2] \times [0, 1]$ with cells of size $1/2$ in $[0, 1] \times [0, 1]$ and
+/// cells of size $1/4$ in $[1, 2] \times [0, 1]$.
+///
+/// Returns the mesh together with the hanging nodes on the interface $x = 1$ and the vertices
+/// of the coarse edges on which they lie.
+fn hanging_node_quad_mesh() -> (QuadMesh2d<f64>, Vec<(usize, [usize; 2])>) {
+    let mut vertices = Vec::new();
+    let mut vertex_indices = HashMap::new();
+    // Vertices are identified by their coordinates in units of 1/4
+    let mut vertex = |i: usize, j: usize| {
+        *vertex_indices.entry((i, j)).or_insert_with(|| {
+            vertices.push(Point2::new(i as f64 / 4.0, j as f64 / 4.0));
+            vertices.len() - 1
+        })
+    };
+
+    let mut cells = Vec::new();
+    for (x_range, h) in [(0..4, 2), (4..8, 1)] {
+        for j in (0..4).step_by(h) {
+            for i in x_range.clone().step_by(h) {
+                cells.push(Quad4d2Connectivity([
+                    vertex(i, j),
+                    vertex(i + h, j),
+                    vertex(i + h, j + h),
+                    vertex(i, j + h),
+                ]));
+            }
+        }
+    }
+    let hanging_nodes = [1, 3]
+        .map(|j| (vertex(4, j), [vertex(4, j - 1), vertex(4, j + 1)]))
+        .to_vec();
+
+    (Mesh::from_vertices_and_connectivity(vertices, cells), hanging_nodes)
+}
+
+#[test]
+fn affine_constraints_reproduce_linear_solution_on_mesh_with_hanging_nodes() {
+    // The bilinear basis reproduces linear functions exactly, so with the hanging nodes
+    // constrained to the coarse edges, the discrete solution of Laplace's equation with
+    // linear boundary data must coincide with the exact (and conforming) solution
+    let (mesh, hanging_nodes) = hanging_node_quad_mesh();
+    assert_eq!(mesh.connectivity().len(), 20);
+    let u_exact = |x: &Point2<f64>| 1.0 + 2.0 * x.x - 3.0 * x.y;
+    let u = DVector::zeros(mesh.vertices().len());
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .build();
+    let stiffness = CsrAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+
+    let is_boundary = |x: &Point2<f64>| x.x == 0.0 || x.x == 2.0 || x.y == 0.0 || x.y == 1.0;
+    let boundary_constraints = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| is_boundary(x))
+        .map(|(i, x)| AffineConstraint::fixed(i, u_exact(x)));
+    let hanging_node_constraints = hanging_nodes
+        .iter()
+        .map(|&(i, [a, b])| AffineConstraint::new(i, vec![(a, 0.5), (b, 0.5)]));
+    let constraints =
+        AffineConstraints::from_constraints(boundary_constraints.chain(hanging_node_constraints)).unwrap();
+
+    let rhs = DVector::zeros(mesh.vertices().len());
+    let (reduced_matrix, reduced_rhs) = constraints.condense_system(&stiffness, &rhs).unwrap();
+    assert_eq!(reduced_matrix.nrows(), mesh.vertices().len() - constraints.len());
+    let reduced_matrix = DMatrix::from(&reduced_matrix);
+    assert_matrix_eq!(reduced_matrix, reduced_matrix.transpose(), comp = float);
+
+    let reduced_solution = reduced_matrix.lu().solve(&reduced_rhs).unwrap();
+    let solution = constraints.expand_solution(&reduced_solution).unwrap();
+    for (x, u_h) in mesh.vertices().iter().zip(&solution) {
+        assert_scalar_eq!(*u_h, u_exact(x), comp = abs, tol = 1e-12);
+    }
+}
+
+struct PeriodicSource;
+
+impl Operator<f64, U1> for PeriodicSource {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U1> for PeriodicSource {
+    fn evaluate(&self, x: &Point1<f64>, _: &()) -> Vector1<f64> {
+        Vector1::new(4.0 * PI * PI * (2.0 * PI * x.x).sin())
+    }
+}
+
+#[test]
+fn affine_constraints_solve_periodic_1d_poisson_problem() {
+    // -u'' = 4 pi^2 sin(2 pi x) with periodic boundary conditions on [0, 1] has the solutions
+    // u = sin(2 pi x) + c, and we pin the constant with u(1/4) = 1. Linear elements in 1D
+    // are exact at the nodes, up to the error in the quadrature of the load
+    let n = 16;
+    let vertices = (0..=n).map(|i| Point1::new(i as f64 / n as f64)).collect();
+    let cells = (0..n).map(|i| Segment2d1Connectivity([i, i + 1])).collect();
+    let mesh = Mesh::<f64, U1, Segment2d1Connectivity>::from_vertices_and_connectivity(vertices, cells);
+
+    let u = DVector::zeros(mesh.vertices().len());
+    let stiffness_qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::univariate::gauss(1), ());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .build();
+    let stiffness = CsrAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+    let source_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::univariate::gauss(10), ());
+    let source_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&source_qtable)
+        .with_source(&PeriodicSource)
+        .build();
+    let load = VectorAssembler::default()
+        .assemble_vector(&source_assembler)
+        .unwrap();
+
+    let constraints = AffineConstraints::from_constraints([
+        AffineConstraint::new(n, vec![(0, 1.0)]),
+        AffineConstraint::fixed(n / 4, 1.0),
+    ])
+    .unwrap();
+    let (reduced_matrix, reduced_rhs) = constraints.condense_system(&stiffness, &load).unwrap();
+    let reduced_solution = DMatrix::from(&reduced_matrix)
+        .lu()
+        .solve(&reduced_rhs)
+        .unwrap();
+    let solution = constraints.expand_solution(&reduced_solution).unwrap();
+
+    assert_eq!(solution[n], solution[0]);
+    for (x, u_h) in mesh.vertices().iter().zip(&solution) {
+        assert_scalar_eq!(*u_h, (2.0 * PI * x.x).sin(), comp = abs, tol = 1e-10);
+    }
+}
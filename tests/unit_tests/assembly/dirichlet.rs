@@ -0,0 +1,224 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::ElementEllipticAssemblerBuilder;
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::assembly::{find_boundary_dofs, DirichletBcs, DirichletEliminationMode};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, Vector1, Vector2, U1, U2};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature::CanonicalStiffnessQuadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[rustfmt::skip]
+fn tridiagonal_example() -> (CsrMatrix<f64>, DVector<f64>) {
+    let matrix = DMatrix::from_row_slice(3, 3, &[
+        4.0, 1.0, 0.0,
+        1.0, 4.0, 1.0,
+        0.0, 1.0, 4.0,
+    ]);
+    (CsrMatrix::from(&matrix), DVector::repeat(3, 1.0))
+}
+
+#[test]
+fn dirichlet_bcs_symmetric_elimination_simple_example() {
+    let (mut matrix, mut rhs) = tridiagonal_example();
+    let bcs = DirichletBcs::from_dofs_and_values(vec![1], vec![2.0])
+        .unwrap()
+        .with_diagonal_value(3.0);
+    assert_eq!(bcs.mode(), DirichletEliminationMode::Symmetric);
+    bcs.apply_to_system(&mut matrix, &mut rhs).unwrap();
+
+    #[rustfmt::skip]
+    let expected_matrix = DMatrix::from_row_slice(3, 3, &[
+        4.0, 0.0, 0.0,
+        0.0, 3.0, 0.0,
+        0.0, 0.0, 4.0,
+    ]);
+    assert_matrix_eq!(DMatrix::from(&matrix), expected_matrix);
+    assert_matrix_eq!(rhs, DVector::from_column_slice(&[-1.0, 6.0, -1.0]));
+    // The pattern is left unchanged
+    assert_eq!(matrix.nnz(), 7);
+}
+
+#[test]
+fn dirichlet_bcs_row_only_elimination_simple_example() {
+    let (mut matrix, mut rhs) = tridiagonal_example();
+    let bcs = DirichletBcs::from_dofs_and_values(vec![1], vec![2.0])
+        .unwrap()
+        .with_diagonal_value(3.0)
+        .with_mode(DirichletEliminationMode::RowOnly);
+    bcs.apply_to_system(&mut matrix, &mut rhs).unwrap();
+
+    #[rustfmt::skip]
+    let expected_matrix = DMatrix::from_row_slice(3, 3, &[
+        4.0, 1.0, 0.0,
+        0.0, 3.0, 0.0,
+        0.0, 1.0, 4.0,
+    ]);
+    assert_matrix_eq!(DMatrix::from(&matrix), expected_matrix);
+    assert_matrix_eq!(rhs, DVector::from_column_slice(&[1.0, 6.0, 1.0]));
+}
+
+#[test]
+fn dirichlet_bcs_reproduce_linear_solution_of_laplace_problem() {
+    // The bilinear basis reproduces linear functions exactly, so the discrete solution of
+    // Laplace's equation with linear boundary data must coincide with the exact solution
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let u_exact = |x: &Point2<f64>| 1.0 + x.x - 2.0 * x.y;
+    let u = DVector::zeros(mesh.vertices().len());
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .build();
+    let stiffness = CsrAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+
+    let bcs = DirichletBcs::from_mesh_boundary(&mesh, 1, &[0], |_| true, |x, _| u_exact(x));
+    assert_eq!(bcs.dofs(), mesh.find_boundary_vertices().as_slice());
+
+    for mode in [DirichletEliminationMode::Symmetric, DirichletEliminationMode::RowOnly] {
+        let mut matrix = stiffness.clone();
+        let mut rhs = DVector::zeros(mesh.vertices().len());
+        bcs.clone()
+            .with_mode(mode)
+            .with_diagonal_value(2.0)
+            .apply_to_system(&mut matrix, &mut rhs)
+            .unwrap();
+
+        let dense = DMatrix::from(&matrix);
+        if mode == DirichletEliminationMode::Symmetric {
+            assert_matrix_eq!(dense, dense.transpose());
+        }
+        let solution = dense.lu().solve(&rhs).unwrap();
+        for (x, u_h) in mesh.vertices().iter().zip(&solution) {
+            assert_scalar_eq!(*u_h, u_exact(x), comp = abs, tol = 1e-12);
+        }
+    }
+}
+
+#[test]
+fn dirichlet_bcs_constrain_individual_components() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let is_bottom = |x: &Point2<f64>| x.y == 0.0;
+    let bottom_vertices: Vec<_> = (0..mesh.vertices().len())
+        .filter(|&i| is_bottom(&mesh.vertices()[i]))
+        .collect();
+    assert_eq!(bottom_vertices.len(), 3);
+
+    let dofs = find_boundary_dofs(&mesh, 2, &[1], is_bottom);
+    let expected_dofs: Vec<_> = bottom_vertices.iter().map(|&i| 2 * i + 1).collect();
+    assert_eq!(dofs, expected_dofs);
+
+    let dofs = find_boundary_dofs(&mesh, 2, &[1, 0, 1], is_bottom);
+    let expected_dofs: Vec<_> = bottom_vertices
+        .iter()
+        .flat_map(|&i| [2 * i, 2 * i + 1])
+        .collect();
+    assert_eq!(dofs, expected_dofs);
+
+    let bcs = DirichletBcs::from_mesh_boundary(&mesh, 2, &[1], is_bottom, |x, i| x.x + i as f64);
+    let expected_values: Vec<_> = bottom_vertices
+        .iter()
+        .map(|&i| mesh.vertices()[i].x + 1.0)
+        .collect();
+    assert_eq!(bcs.values(), expected_values.as_slice());
+
+    // Only the constrained component of each node is eliminated
+    let n = 2 * mesh.vertices().len();
+    let mut matrix = CsrMatrix::from(&DMatrix::repeat(n, n, 1.0));
+    let mut rhs = DVector::zeros(n);
+    bcs.apply_to_system(&mut matrix, &mut rhs).unwrap();
+    let dense = DMatrix::from(&matrix);
+    for i in 0..n {
+        let is_constrained = bcs.dofs().contains(&i);
+        assert_eq!(dense[(i, i)], 1.0);
+        assert_eq!(
+            dense.row(i).iter().filter(|&&a| a != 0.0).count(),
+            if is_constrained { 1 } else { n - bcs.len() }
+        );
+    }
+}
+
+#[test]
+fn dirichlet_bcs_handle_repeated_and_invalid_dofs() {
+    let bcs = DirichletBcs::from_dofs_and_values(vec![2, 0, 2], vec![1.0, 3.0, 1.0]).unwrap();
+    assert_eq!(bcs.dofs(), &[0, 2]);
+    assert_eq!(bcs.values(), &[3.0, 1.0]);
+
+    let bcs = DirichletBcs::<f64>::homogeneous(vec![1, 1]);
+    assert_eq!(bcs.dofs(), &[1]);
+    assert!(!bcs.is_empty());
+
+    assert!(DirichletBcs::from_dofs_and_values(vec![1, 1], vec![1.0, 2.0]).is_err());
+    assert!(DirichletBcs::from_dofs_and_values(vec![1, 2], vec![1.0]).is_err());
+
+    // The system is left untouched if a DOF is out of bounds
+    let (mut matrix, mut rhs) = tridiagonal_example();
+    let (original_matrix, original_rhs) = (matrix.clone(), rhs.clone());
+    let bcs = DirichletBcs::homogeneous(vec![0, 3]);
+    let error = bcs.apply_to_system(&mut matrix, &mut rhs).unwrap_err();
+    assert!(error.to_string().contains("out of bounds"));
+    assert_eq!(matrix, original_matrix);
+    assert_eq!(rhs, original_rhs);
+
+    // A DOF without a diagonal entry in the pattern can not be constrained
+    let mut matrix = CsrMatrix::try_from_csr_data(2, 2, vec![0, 1, 2], vec![1, 0], vec![1.0, 1.0]).unwrap();
+    let mut rhs = DVector::zeros(2);
+    let error = DirichletBcs::homogeneous(vec![1])
+        .apply_to_system(&mut matrix, &mut rhs)
+        .unwrap_err();
+    assert!(error.to_string().contains("diagonal entry"));
+
+    let mut rhs = DVector::zeros(3);
+    assert!(DirichletBcs::homogeneous(vec![0])
+        .apply_to_system(&mut matrix, &mut rhs)
+        .is_err());
+}
+
+#[test]
+fn dirichlet_bcs_reactions_from_constrained_rows() {
+    let (mut matrix, mut rhs) = tridiagonal_example();
+    let bcs = DirichletBcs::from_dofs_and_values(vec![2, 0], vec![1.0, -1.0]).unwrap();
+    let constrained_rows = bcs.extract_constrained_rows(&matrix, &rhs).unwrap();
+    assert_eq!(constrained_rows.dofs(), &[0, 2]);
+
+    bcs.apply_to_system(&mut matrix, &mut rhs).unwrap();
+    let u = DMatrix::from(&matrix).lu().solve(&rhs).unwrap();
+    // The unconstrained equation gives -1 + 4 u_1 + 1 = 1
+    assert_matrix_eq!(u, DVector::from_column_slice(&[-1.0, 0.25, 1.0]), comp = float);
+
+    let reactions = constrained_rows.compute_reactions(&u).unwrap();
+    assert_eq!(reactions.dofs(), &[0, 2]);
+    assert_matrix_eq!(
+        DVector::from_column_slice(reactions.values()),
+        DVector::from_column_slice(&[-4.0 + 0.25 - 1.0, 0.25 + 4.0 - 1.0]),
+        comp = float
+    );
+
+    // The residual of the unconstrained system gives the same reactions
+    let (matrix, rhs) = tridiagonal_example();
+    let residual = &matrix * &u - &rhs;
+    assert_eq!(bcs.reactions_from_residual(&residual).unwrap(), reactions);
+
+    // Nodes with solution dimension 1 correspond directly to DOFs
+    assert_matrix_eq!(
+        reactions.sum_reaction_force::<U1>(&[0, 1, 2]),
+        Vector1::new(reactions.values()[0] + reactions.values()[1]),
+        comp = float
+    );
+    // With solution dimension 2, DOFs 0 and 2 are the first components of nodes 0 and 1
+    assert_matrix_eq!(
+        reactions.sum_reaction_force::<U2>(&[1]),
+        Vector2::new(reactions.values()[1], 0.0),
+        comp = float
+    );
+
+    assert!(constrained_rows
+        .compute_reactions(&DVector::zeros(2))
+        .is_err());
+    assert!(bcs.reactions_from_residual(&DVector::zeros(2)).is_err());
+}
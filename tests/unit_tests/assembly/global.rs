@@ -5,15 +5,27 @@ use proptest::num::i32;
 use proptest::prelude::*;
 
 use eyre::eyre;
+use fenris::assembly::color_elements;
 use fenris::assembly::global::{
-    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_matrix, assemble_scalar, gather_global_to_local,
-    par_assemble_scalar, CsrAssembler, CsrParAssembler,
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_matrix, assemble_scalar, build_csr_pattern,
+    build_lower_triangular_csr_pattern, expand_lower_triangular_csr, gather_global_to_local, par_assemble_scalar,
+    CooAssembler, CsrAssembler, CsrParAssembler, VectorAssembler, VectorParAssembler,
 };
-use fenris::assembly::local::{ElementConnectivityAssembler, ElementScalarAssembler};
-use fenris::nalgebra::{DMatrix, DVector, U2};
+use fenris::assembly::local::{
+    Density, ElementConnectivityAssembler, ElementEllipticAssemblerBuilder, ElementMassAssembler,
+    ElementMatrixAssembler, ElementScalarAssembler, ElementSourceAssemblerBuilder, SourceFunction,
+};
+use fenris::assembly::operators::{LaplaceOperator, Operator};
+use fenris::mesh::procedural::{create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d};
+use fenris::mesh::{HexMesh, Tet4Mesh};
+use fenris::nalgebra::{DMatrix, DMatrixViewMut, DVector, Point3, Vector2, U2, U3};
 use fenris::nalgebra_sparse::pattern::SparsityPattern;
-use fenris::nalgebra_sparse::CsrMatrix;
-use matrixcompare::assert_scalar_eq;
+use fenris::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use fenris::quadrature::{CanonicalMassQuadrature, CanonicalStiffnessQuadrature};
+use fenris::Symmetry;
+use fenris_paradis::DisjointSubsets;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use rayon::ThreadPoolBuilder;
 
 #[test]
 fn apply_homogeneous_dirichlet_bc_matrix_simple_example() {
@@ -141,6 +153,409 @@ fn csr_assemble_mock_pattern() {
     // TODO: Would be good to have some property tests...
 }
 
+#[test]
+fn build_csr_pattern_matches_assembler_pattern() {
+    for solution_dim in [1, 2, 3] {
+        let element_assembler = MockElementAssembler {
+            solution_dim,
+            num_nodes: 6,
+            element_connectivities: vec![vec![0, 1, 2], vec![2, 3], vec![], vec![3, 4, 4, 4, 4, 4, 4]],
+        };
+        let pattern = build_csr_pattern(&element_assembler);
+        assert_eq!(pattern.major_dim(), 6 * solution_dim);
+        assert_eq!(
+            pattern,
+            CsrAssembler::<f64>::default().assemble_pattern(&element_assembler)
+        );
+    }
+}
+
+#[test]
+fn csr_assemble_into_preallocated_matches_from_scratch_assembly() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    let stiffness_qtable = mesh.canonical_stiffness_quadrature();
+    let mass_qtable = mesh
+        .canonical_mass_quadrature()
+        .with_uniform_data(Density(2.0));
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .build();
+    // The mass matrix with solution dim 3 exercises the block expansion of the pattern
+    let mass_assembler = ElementMassAssembler::with_solution_dim(3)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+
+    let csr_assembler = CsrAssembler::default();
+    let expected_laplace = csr_assembler.assemble(&laplace_assembler).unwrap();
+    let expected_mass = csr_assembler.assemble(&mass_assembler).unwrap();
+
+    // Previous values in the preallocated matrix must be overwritten, also on repeated assembly
+    let pattern = build_csr_pattern(&mass_assembler);
+    let mut mass = CsrMatrix::try_from_pattern_and_values(pattern.clone(), vec![1.0; pattern.nnz()]).unwrap();
+    for _ in 0..2 {
+        csr_assembler
+            .assemble_into_preallocated_csr(&mut mass, &mass_assembler)
+            .unwrap();
+        assert_eq!(mass, expected_mass);
+    }
+
+    let pattern = build_csr_pattern(&laplace_assembler);
+    let mut laplace = CsrMatrix::try_from_pattern_and_values(pattern.clone(), vec![-3.0; pattern.nnz()]).unwrap();
+    csr_assembler
+        .assemble_into_preallocated_csr(&mut laplace, &laplace_assembler)
+        .unwrap();
+    assert_eq!(laplace, expected_laplace);
+
+    // Entries that are not touched by any element are set to zero
+    let n = mesh.vertices().len();
+    let mut dense_pattern = CsrMatrix::from(&DMatrix::repeat(n, n, 1.0));
+    csr_assembler
+        .assemble_into_preallocated_csr(&mut dense_pattern, &laplace_assembler)
+        .unwrap();
+    assert_eq!(DMatrix::from(&dense_pattern), DMatrix::from(&expected_laplace));
+}
+
+#[test]
+fn csr_assemble_into_preallocated_rejects_incompatible_pattern() {
+    let element_assembler = MockElementAssembler {
+        solution_dim: 2,
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2, 3]],
+    };
+    let csr_assembler = CsrAssembler::default();
+
+    // The pattern of the first element alone is missing entries touched by the second element
+    let incomplete_assembler = MockElementAssembler {
+        element_connectivities: vec![vec![0, 1]],
+        ..element_assembler.clone()
+    };
+    let pattern = build_csr_pattern(&incomplete_assembler);
+    let mut matrix = CsrMatrix::try_from_pattern_and_values(pattern.clone(), vec![0.0; pattern.nnz()]).unwrap();
+    let error = csr_assembler
+        .assemble_into_preallocated_csr(&mut matrix, &element_assembler)
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Element 1 contributes to entry (2, 4), which is not present in the sparsity pattern"));
+
+    // Incompatible dimensions
+    let mut matrix = CsrMatrix::identity(6);
+    assert!(csr_assembler
+        .assemble_into_preallocated_csr(&mut matrix, &element_assembler)
+        .is_err());
+}
+
+#[test]
+fn csr_assemble_lower_triangle_of_symmetric_matrices() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    let stiffness_qtable = mesh.canonical_stiffness_quadrature();
+    let mass_qtable = mesh
+        .canonical_mass_quadrature()
+        .with_uniform_data(Density(2.0));
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .build();
+    let mass_assembler = ElementMassAssembler::with_solution_dim(3)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    assert_eq!(laplace_assembler.symmetry(), Symmetry::Symmetric);
+    assert_eq!(mass_assembler.symmetry(), Symmetry::Symmetric);
+
+    let csr_assembler = CsrAssembler::default();
+    for element_assembler in [&laplace_assembler as &dyn ElementMatrixAssembler<f64>, &mass_assembler] {
+        let full = csr_assembler.assemble(&element_assembler).unwrap();
+        let lower = csr_assembler
+            .assemble_with_symmetry(&element_assembler, Symmetry::Symmetric)
+            .unwrap();
+        assert_eq!(lower.pattern(), &build_lower_triangular_csr_pattern(&element_assembler));
+        assert!(lower.triplet_iter().all(|(i, j, _)| j <= i));
+        // Only the diagonal and the strictly lower triangular part are stored
+        assert_eq!(2 * lower.nnz(), full.nnz() + full.nrows());
+        assert_matrix_eq!(
+            DMatrix::from(&lower),
+            DMatrix::from(&full).lower_triangle(),
+            comp = abs,
+            tol = 1e-14
+        );
+
+        let expanded = expand_lower_triangular_csr(&lower);
+        assert_eq!(expanded.pattern(), full.pattern());
+        assert_matrix_eq!(expanded, full, comp = abs, tol = 1e-14);
+
+        let non_symmetric = csr_assembler
+            .assemble_with_symmetry(&element_assembler, Symmetry::NonSymmetric)
+            .unwrap();
+        assert_eq!(non_symmetric, full);
+    }
+}
+
+#[test]
+fn csr_assemble_with_symmetry_rejects_non_symmetric_assembler() {
+    let element_assembler = MockElementAssembler {
+        solution_dim: 2,
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2, 3]],
+    };
+    assert_eq!(element_assembler.symmetry(), Symmetry::NonSymmetric);
+    assert!(CsrAssembler::default()
+        .assemble_with_symmetry(&element_assembler, Symmetry::Symmetric)
+        .is_err());
+}
+
+#[test]
+fn scaled_add_assembly_matches_separate_assembly() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    let stiffness_qtable = mesh.canonical_stiffness_quadrature();
+    let mass_qtable = mesh
+        .canonical_mass_quadrature()
+        .with_uniform_data(Density(2.0));
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .build();
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+
+    // M + dt^2 K, as used by implicit time integrators
+    let dt: f64 = 0.1;
+    let csr_assembler = CsrAssembler::default();
+    let mass = csr_assembler.assemble(&mass_assembler).unwrap();
+    let stiffness = csr_assembler.assemble(&laplace_assembler).unwrap();
+    let expected = &mass + &(&stiffness * dt.powi(2));
+    let mut effective = mass.clone();
+    csr_assembler
+        .assemble_scaled_add_into(&mut effective, dt.powi(2), &laplace_assembler)
+        .unwrap();
+    assert_eq!(effective.pattern(), mass.pattern());
+    assert_matrix_eq!(effective, expected, comp = abs, tol = 1e-14);
+
+    // Existing values are accumulated into rather than overwritten
+    csr_assembler
+        .assemble_scaled_add_into(&mut effective, -1.0, &mass_assembler)
+        .unwrap();
+    assert_matrix_eq!(effective, &stiffness * dt.powi(2), comp = abs, tol = 1e-14);
+
+    // The vector associated with the Laplace assembler is K u
+    let vector_assembler = VectorAssembler::default();
+    let mut b = DVector::repeat(u.len(), 1.0);
+    vector_assembler
+        .assemble_scaled_add_into_vector(&mut b, -0.5, &laplace_assembler)
+        .unwrap();
+    let expected = DVector::repeat(u.len(), 1.0) - (&stiffness * &u) * 0.5;
+    assert_matrix_eq!(b, expected, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn scaled_add_assembly_rejects_incompatible_matrix() {
+    let element_assembler = MockElementAssembler {
+        solution_dim: 2,
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2, 3]],
+    };
+    let csr_assembler = CsrAssembler::default();
+
+    let incomplete_assembler = MockElementAssembler {
+        element_connectivities: vec![vec![0, 1]],
+        ..element_assembler.clone()
+    };
+    let mut matrix = csr_assembler.assemble(&incomplete_assembler).unwrap();
+    assert!(csr_assembler
+        .assemble_scaled_add_into(&mut matrix, 2.0, &element_assembler)
+        .is_err());
+
+    let mut matrix = CsrMatrix::identity(6);
+    assert!(csr_assembler
+        .assemble_scaled_add_into(&mut matrix, 2.0, &element_assembler)
+        .is_err());
+
+    // Entries of the pattern that are not touched by the assembler are left unchanged
+    let mut matrix = CsrMatrix::from(&DMatrix::repeat(8, 8, 1.0));
+    csr_assembler
+        .assemble_scaled_add_into(&mut matrix, 2.0, &incomplete_assembler)
+        .unwrap();
+    let mut expected = DMatrix::repeat(8, 8, 1.0);
+    expected.view_mut((0, 0), (4, 4)).fill(3.0);
+    assert_eq!(DMatrix::from(&matrix), expected);
+}
+
+struct SmoothSource;
+
+impl Operator<f64, U3> for SmoothSource {
+    type SolutionDim = U2;
+    type Parameters = ();
+}
+
+impl SourceFunction<f64, U3> for SmoothSource {
+    fn evaluate(&self, x: &Point3<f64>, _: &()) -> Vector2<f64> {
+        Vector2::new((x.x * x.y).sin() + x.z, (x.y - x.z).exp())
+    }
+}
+
+#[test]
+fn par_vector_assembly_matches_serial_assembly() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(4);
+    let qtable = mesh.canonical_mass_quadrature();
+    let source_assembler = ElementSourceAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_source(&SmoothSource)
+        .build();
+    let expected = VectorAssembler::default()
+        .assemble_vector(&source_assembler)
+        .unwrap();
+
+    let colors: Vec<DisjointSubsets> = color_elements(&source_assembler).into();
+    for num_threads in [1, 2, 4] {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let par_assembler = VectorParAssembler::default();
+            // The colored assembly sums element contributions in a fixed order
+            let colored = par_assembler
+                .assemble_vector(&colors, &source_assembler)
+                .unwrap();
+            assert_eq!(
+                colored,
+                par_assembler
+                    .assemble_vector(&colors, &source_assembler)
+                    .unwrap()
+            );
+            assert_matrix_eq!(colored, expected, comp = abs, tol = 1e-14);
+
+            let reduced = par_assembler
+                .assemble_vector_by_reduction(&source_assembler)
+                .unwrap();
+            assert_matrix_eq!(reduced, expected, comp = abs, tol = 1e-14);
+
+            let mut accumulated = DVector::repeat(expected.len(), 1.0);
+            par_assembler
+                .assemble_vector_into_by_reduction(&mut accumulated, &source_assembler)
+                .unwrap();
+            assert_matrix_eq!(accumulated, expected.add_scalar(1.0), comp = abs, tol = 1e-14);
+        });
+    }
+}
+
+#[test]
+fn coo_assemble_matches_csr_assembly() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    let stiffness_qtable = mesh.canonical_stiffness_quadrature();
+    let mass_qtable = mesh
+        .canonical_mass_quadrature()
+        .with_uniform_data(Density(2.0));
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .build();
+    let mass_assembler = ElementMassAssembler::with_solution_dim(3)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+
+    let csr_assembler = CsrAssembler::default();
+    let coo_assembler = CooAssembler::default();
+    for element_assembler in [&laplace_assembler as &dyn ElementMatrixAssembler<f64>, &mass_assembler] {
+        let full = csr_assembler.assemble(&element_assembler).unwrap();
+        let coo = coo_assembler.assemble(&element_assembler).unwrap();
+        let element_dim = 8 * element_assembler.solution_dim();
+        assert_eq!(coo.nnz(), element_assembler.num_elements() * element_dim * element_dim);
+        let from_coo = CsrMatrix::from(&coo);
+        assert_eq!(from_coo.pattern(), full.pattern());
+        assert_matrix_eq!(from_coo, full, comp = abs, tol = 1e-14);
+
+        let lower = csr_assembler
+            .assemble_with_symmetry(&element_assembler, Symmetry::Symmetric)
+            .unwrap();
+        let coo_lower = coo_assembler
+            .assemble_with_symmetry(&element_assembler, Symmetry::Symmetric)
+            .unwrap();
+        assert!(coo_lower.triplet_iter().all(|(i, j, _)| j <= i));
+        let from_coo_lower = CsrMatrix::from(&coo_lower);
+        assert_eq!(from_coo_lower.pattern(), lower.pattern());
+        assert_matrix_eq!(from_coo_lower, lower, comp = abs, tol = 1e-14);
+
+        // Assembling into an existing matrix appends the triplets
+        let mut appended = coo.clone();
+        coo_assembler
+            .assemble_into_coo(&mut appended, &element_assembler, Symmetry::NonSymmetric)
+            .unwrap();
+        assert_eq!(appended.nnz(), 2 * coo.nnz());
+        assert_matrix_eq!(CsrMatrix::from(&appended), &full * 2.0, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn coo_assemble_with_drop_tolerance() {
+    // Some entries of the element matrices of linear tetrahedra with right angles vanish
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .build();
+
+    let full = CsrAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+    let coo = CooAssembler::default()
+        .assemble(&laplace_assembler)
+        .unwrap();
+    let dropped = CooAssembler::default()
+        .with_drop_tolerance(1e-12)
+        .assemble(&laplace_assembler)
+        .unwrap();
+    assert!(dropped.nnz() < coo.nnz());
+    assert!(dropped.values().iter().all(|a_ij| a_ij.abs() > 1e-12));
+    assert_matrix_eq!(
+        DMatrix::from(&CsrMatrix::from(&dropped)),
+        DMatrix::from(&full),
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn coo_assemble_rejects_invalid_input() {
+    let element_assembler = MockElementAssembler {
+        solution_dim: 2,
+        num_nodes: 4,
+        element_connectivities: vec![vec![0, 1], vec![1, 2, 3]],
+    };
+    let coo_assembler = CooAssembler::default();
+    assert!(coo_assembler
+        .assemble_with_symmetry(&element_assembler, Symmetry::Symmetric)
+        .is_err());
+
+    let mut coo = CooMatrix::new(6, 6);
+    assert!(coo_assembler
+        .assemble_into_coo(&mut coo, &element_assembler, Symmetry::NonSymmetric)
+        .is_err());
+    let mut coo = CooMatrix::new(8, 8);
+    coo_assembler
+        .assemble_into_coo(&mut coo, &element_assembler, Symmetry::NonSymmetric)
+        .unwrap();
+    assert_eq!(coo.nnz(), 4 * 4 + 6 * 6);
+}
+
 #[test]
 fn csr_par_assemble_mock_pattern() {
     // Solution dim == 1
@@ -215,6 +630,56 @@ fn csr_par_assemble_mock_pattern() {
     // TODO: Would be good to have some property tests...
 }
 
+#[test]
+fn csr_par_assemble_with_element_coloring() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(4);
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::zeros(mesh.vertices().len());
+    let element_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_u(&u)
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .build();
+
+    let colors: Vec<DisjointSubsets> = color_elements(&element_assembler).into();
+    // Every element has exactly one color, and elements of the same color share no nodes
+    let mut labels: Vec<_> = colors
+        .iter()
+        .flat_map(|color| color.labels().iter().copied())
+        .collect();
+    labels.sort_unstable();
+    assert_eq!(labels, (0..element_assembler.num_elements()).collect::<Vec<_>>());
+    for color in &colors {
+        let mut nodes: Vec<_> = color.subsets().iter().flatten().copied().collect();
+        let num_nodes = nodes.len();
+        nodes.sort_unstable();
+        nodes.dedup();
+        assert_eq!(nodes.len(), num_nodes);
+    }
+
+    // The result must not depend on the number of threads
+    let assemble_with_threads = |num_threads| {
+        ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap()
+            .install(|| {
+                CsrParAssembler::default()
+                    .assemble(&colors, &element_assembler)
+                    .unwrap()
+            })
+    };
+    let matrix_single_thread = assemble_with_threads(1);
+    let matrix_multi_thread = assemble_with_threads(4);
+    assert_eq!(matrix_single_thread, matrix_multi_thread);
+
+    let matrix_serial = CsrAssembler::default()
+        .assemble(&element_assembler)
+        .unwrap();
+    assert_matrix_eq!(matrix_multi_thread, matrix_serial, comp = abs, tol = 1e-12);
+}
+
 fn gather_global_to_local_args() -> impl Strategy<Value = GatherGlobalToLocalArgs> {
     let sol_dim = 0..10usize;
     let num_nodes = 0..10usize;
@@ -235,6 +700,7 @@ fn gather_global_to_local_args() -> impl Strategy<Value = GatherGlobalToLocalArg
         })
 }
 
+#[derive(Clone)]
 struct MockElementAssembler {
     solution_dim: usize,
     num_nodes: usize,
@@ -263,6 +729,13 @@ impl ElementConnectivityAssembler for MockElementAssembler {
     }
 }
 
+impl ElementMatrixAssembler<f64> for MockElementAssembler {
+    fn assemble_element_matrix_into(&self, _element_index: usize, mut output: DMatrixViewMut<f64>) -> eyre::Result<()> {
+        output.fill(1.0);
+        Ok(())
+    }
+}
+
 struct MockScalarElementAssembler;
 
 #[rustfmt::skip]
@@ -1,16 +1,20 @@
 use fenris::allocators::{BiDimAllocator, DimAllocator};
 use fenris::assembly::global::{assemble_scalar, CsrAssembler, VectorAssembler};
 use fenris::assembly::local::{
-    assemble_element_mass_matrix, AggregateElementAssembler, ElementConnectivityAssembler,
-    ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
-    UniformQuadratureTable,
+    assemble_element_elliptic_matrix, assemble_element_mass_matrix, AggregateElementAssembler,
+    ElementConnectivityAssembler, ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementScalarAssembler,
+    ElementVectorAssembler, GeneralQuadratureTable, QuadratureTable, UniformQuadratureTable,
 };
 use fenris::assembly::operators::LaplaceOperator;
-use fenris::element::{Quad4d2Element, VolumetricFiniteElement};
+use fenris::connectivity::Connectivity;
+use fenris::element::{ElementConnectivity, Quad4d2Element, VolumetricFiniteElement};
 use fenris::geometry::Quad2d;
-use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::procedural::{create_rectangular_uniform_quad_mesh_2d, create_unit_square_uniform_quad_mesh_2d};
 use fenris::mesh::QuadMesh2d;
-use fenris::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, Matrix4, OPoint, OVector, Point2};
+use fenris::nalgebra::{
+    DMatrix, DVector, DVectorView, DefaultAllocator, DimName, Matrix2xX, Matrix4, MatrixViewMut, OPoint, OVector,
+    Point2, Vector2, U2,
+};
 use fenris::quadrature;
 use fenris::quadrature::QuadraturePair;
 use fenris::Real;
@@ -19,9 +23,17 @@ use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 use nalgebra::{DMatrixViewMut, Matrix2};
 use std::iter::repeat;
 
+mod axisymmetric;
+mod cached_basis;
+mod convection_diffusion;
 mod elliptic;
+mod instrumented;
 mod mass;
+mod mass_scaling;
+mod pressure;
 mod source;
+mod spd_projection;
+mod surface_source;
 
 fn reference_quad<T>() -> Quad2d<T>
 where
@@ -294,6 +306,139 @@ fn aggregate_element_assembler_multibody() {
     assert!(bottom_left_block.iter().all(|&x_i| x_i == 0.0));
 }
 
+/// A mock assembler on 4 nodes whose element matrices are filled with a constant value.
+struct ConstantElementMatrixAssembler {
+    value: f64,
+    element_connectivities: Vec<Vec<usize>>,
+}
+
+impl ElementConnectivityAssembler for ConstantElementMatrixAssembler {
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.element_connectivities.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        4
+    }
+
+    fn element_node_count(&self, element_index: usize) -> usize {
+        self.element_connectivities[element_index].len()
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        output.copy_from_slice(&self.element_connectivities[element_index])
+    }
+}
+
+impl ElementMatrixAssembler<f64> for ConstantElementMatrixAssembler {
+    fn assemble_element_matrix_into(&self, _element_index: usize, mut output: DMatrixViewMut<f64>) -> eyre::Result<()> {
+        output.fill(self.value);
+        Ok(())
+    }
+}
+
+#[test]
+fn aggregate_element_assembler_heterogeneous_assemblers() {
+    // A single quad with 4 nodes
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let first = ConstantElementMatrixAssembler {
+        value: 1.0,
+        element_connectivities: vec![vec![0, 1], vec![1, 2]],
+    };
+    let empty = ConstantElementMatrixAssembler {
+        value: 2.0,
+        element_connectivities: vec![],
+    };
+    let last = ConstantElementMatrixAssembler {
+        value: 3.0,
+        element_connectivities: vec![vec![3], vec![2, 3]],
+    };
+
+    // Element offsets are [0, 2, 2, 3], so the empty assembler shares its offset with the Laplace assembler
+    let assemblers: Vec<Box<dyn ElementMatrixAssembler<f64> + '_>> = vec![
+        Box::new(first),
+        Box::new(empty),
+        Box::new(laplace_assembler.clone()),
+        Box::new(last),
+    ];
+    let aggregate = AggregateElementAssembler::from_assemblers(&assemblers);
+    assert_eq!(aggregate.num_elements(), 5);
+    assert_eq!(aggregate.num_nodes(), 4);
+
+    let mut laplace_nodes = vec![0; 4];
+    laplace_assembler.populate_element_nodes(&mut laplace_nodes, 0);
+    let expected_nodes = [vec![0, 1], vec![1, 2], laplace_nodes, vec![3], vec![2, 3]];
+    for (element_index, expected_nodes) in expected_nodes.iter().enumerate() {
+        assert_eq!(aggregate.element_node_count(element_index), expected_nodes.len());
+        let mut nodes = vec![usize::MAX; expected_nodes.len()];
+        aggregate.populate_element_nodes(&mut nodes, element_index);
+        assert_eq!(&nodes, expected_nodes);
+    }
+
+    // Check dispatch at the boundaries between the offset ranges
+    assert_eq!(
+        aggregate.assemble_element_matrix(1).unwrap(),
+        DMatrix::repeat(2, 2, 1.0)
+    );
+    assert_eq!(
+        aggregate.assemble_element_matrix(2).unwrap(),
+        laplace_assembler.assemble_element_matrix(0).unwrap()
+    );
+    assert_eq!(
+        aggregate.assemble_element_matrix(3).unwrap(),
+        DMatrix::repeat(1, 1, 3.0)
+    );
+    assert_eq!(
+        aggregate.assemble_element_matrix(4).unwrap(),
+        DMatrix::repeat(2, 2, 3.0)
+    );
+
+    let global_matrix = DMatrix::from(&CsrAssembler::default().assemble(&aggregate).unwrap());
+    let mut expected_matrix = DMatrix::from(
+        &CsrAssembler::default()
+            .assemble(&laplace_assembler)
+            .unwrap(),
+    );
+    for (value, element_nodes) in [(1.0, &[0, 1][..]), (1.0, &[1, 2]), (3.0, &[3]), (3.0, &[2, 3])] {
+        for &i in element_nodes {
+            for &j in element_nodes {
+                expected_matrix[(i, j)] += value;
+            }
+        }
+    }
+    assert_matrix_eq!(global_matrix, expected_matrix, comp = float);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn aggregate_element_assembler_rejects_element_index_out_of_bounds() {
+    let assemblers = vec![
+        ConstantElementMatrixAssembler {
+            value: 1.0,
+            element_connectivities: vec![vec![0, 1]],
+        },
+        ConstantElementMatrixAssembler {
+            value: 2.0,
+            element_connectivities: vec![vec![2, 3]],
+        },
+    ];
+    let aggregate = AggregateElementAssembler::from_assemblers(&assemblers);
+    aggregate.element_node_count(2);
+}
+
 #[test]
 fn transform_element_scalar_vector_matrix() {
     let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
@@ -334,3 +479,61 @@ fn transform_element_scalar_vector_matrix() {
         assert_matrix_eq!(transformed_matrix, -6.0 * original_matrix);
     }
 }
+
+#[test]
+fn general_quadrature_table_with_different_rules_per_element() {
+    // Two neighboring elements that use quadrature rules of different sizes
+    let mesh: QuadMesh2d<f64> = create_rectangular_uniform_quad_mesh_2d(1.0, 2, 1, 1, &Vector2::new(0.0, 1.0));
+    let rules = vec![
+        quadrature::tensor::quadrilateral_gauss(1),
+        quadrature::tensor::quadrilateral_gauss(3),
+    ];
+    let qtable = GeneralQuadratureTable::from_element_quadratures(&rules);
+    assert_eq!(qtable.element_quadrature_size(0), 1);
+    assert_eq!(qtable.element_quadrature_size(1), 9);
+    assert_eq!(
+        qtable,
+        GeneralQuadratureTable::from_element_quadrature_fn(2, |element_index| &rules[element_index])
+    );
+
+    let json = serde_json::to_string(&qtable).unwrap();
+    let deserialized: GeneralQuadratureTable<f64, U2> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, qtable);
+
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let matrix = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+
+    // Compute the reference element by element with the rule of each element
+    let num_nodes = mesh.vertices().len();
+    let mut expected = DMatrix::zeros(num_nodes, num_nodes);
+    for (connectivity, (weights, points)) in mesh.connectivity().iter().zip(&rules) {
+        let element = connectivity.element(mesh.vertices()).unwrap();
+        let mut element_matrix = DMatrix::zeros(4, 4);
+        let mut gradients_buffer = Matrix2xX::zeros(4);
+        assemble_element_elliptic_matrix(
+            DMatrixViewMut::from(&mut element_matrix),
+            &element,
+            &LaplaceOperator,
+            DVectorView::from(&DVector::zeros(4)),
+            weights,
+            points,
+            &vec![(); weights.len()],
+            MatrixViewMut::from(&mut gradients_buffer),
+        )
+        .unwrap();
+        let nodes = connectivity.vertex_indices();
+        for (a, &i) in nodes.iter().enumerate() {
+            for (b, &j) in nodes.iter().enumerate() {
+                expected[(i, j)] += element_matrix[(a, b)];
+            }
+        }
+    }
+
+    assert_matrix_eq!(matrix, expected, comp = abs, tol = 1e-14);
+}
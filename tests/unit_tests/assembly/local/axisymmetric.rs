@@ -0,0 +1,31 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{AxisymmetricQuadratureTable, Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::mesh::procedural::create_graded_quad_mesh_2d;
+use fenris::quadrature;
+use matrixcompare::assert_scalar_eq;
+use std::f64::consts::PI;
+
+#[test]
+fn axisymmetric_mass_matrix_gives_mass_of_body_of_revolution() {
+    let density = 3.0;
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(density),
+    );
+
+    // A solid cylinder with radius 1 and height 2, whose elements touch the axis
+    let cylinder = create_graded_quad_mesh_2d(&[0.0, 0.25, 0.5, 1.0], &[0.0, 1.0, 2.0]);
+    // A hollow cylinder with inner radius 1, outer radius 2 and height 1
+    let tube = create_graded_quad_mesh_2d(&[1.0, 1.5, 2.0], &[0.0, 0.5, 1.0]);
+
+    for (mesh, expected_volume) in [(cylinder, 2.0 * PI), (tube, 3.0 * PI)] {
+        let axisymmetric_qtable = AxisymmetricQuadratureTable::new(&mesh, &qtable);
+        let assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(&mesh)
+            .with_quadrature_table(&axisymmetric_qtable);
+        let mass = CsrAssembler::default().assemble(&assembler).unwrap();
+        // The integrand 2 pi r is bilinear in the reference coordinates, so the quadrature is exact
+        let total_mass: f64 = mass.values().iter().sum();
+        assert_scalar_eq!(total_mass, density * expected_volume, comp = abs, tol = 1e-12);
+    }
+}
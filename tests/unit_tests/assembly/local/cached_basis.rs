@@ -0,0 +1,96 @@
+use fenris::assembly::global::{assemble_scalar, CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    CachedBasisQuadrature, Density, ElementEllipticAssemblerBuilder, ElementMassAssembler, QuadratureTable,
+    UniformQuadratureTable,
+};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::element::{Hex8Element, Tet4Element};
+use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::mesh::HexMesh;
+use fenris::nalgebra::{DVector, Vector3};
+use fenris::quadrature;
+
+/// A hex mesh with perturbed vertices, so that element Jacobians vary over the mesh.
+fn distorted_hex_mesh() -> HexMesh<f64> {
+    let mut mesh = create_unit_box_uniform_hex_mesh_3d(3);
+    for (i, v) in mesh.vertices_mut().iter_mut().enumerate() {
+        let i = i as f64;
+        v.coords += 0.05 * Vector3::new((1.3 * i).sin(), (2.1 * i).cos(), (0.7 * i).sin());
+    }
+    mesh
+}
+
+#[test]
+fn cached_basis_quadrature_elliptic_assembly_matches_uncached_hex8() {
+    let mesh = distorted_hex_mesh();
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::hexahedron_gauss(2));
+    let cached_table = CachedBasisQuadrature::new(table.clone(), &Hex8Element::reference());
+    assert_eq!(cached_table.basis_cache().num_nodes(), 8);
+    assert_eq!(cached_table.basis_cache().num_points(), 8);
+
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let uncached = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&table)
+        .with_u(&u)
+        .build();
+    let cached = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&cached_table)
+        .with_u(&u)
+        .build();
+
+    // The cached basis gradients are computed by exactly the same code, so results must agree bitwise
+    let assembler = CsrAssembler::default();
+    assert_eq!(
+        assembler.assemble(&cached).unwrap(),
+        assembler.assemble(&uncached).unwrap()
+    );
+    let vector_assembler = VectorAssembler::default();
+    assert_eq!(
+        vector_assembler.assemble_vector(&cached).unwrap(),
+        vector_assembler.assemble_vector(&uncached).unwrap()
+    );
+    assert_eq!(assemble_scalar(&cached).unwrap(), assemble_scalar(&uncached).unwrap());
+}
+
+#[test]
+fn cached_basis_quadrature_mass_assembly_matches_uncached_hex8() {
+    let mesh = distorted_hex_mesh();
+    let table =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::hexahedron_gauss(3), Density(2.0));
+    let cached_table = CachedBasisQuadrature::new(table.clone(), &Hex8Element::reference());
+    assert_eq!(cached_table.element_quadrature_size(0), 27);
+
+    let uncached = ElementMassAssembler::with_solution_dim(3)
+        .with_space(&mesh)
+        .with_quadrature_table(&table);
+    let cached = ElementMassAssembler::with_solution_dim(3)
+        .with_space(&mesh)
+        .with_quadrature_table(&cached_table);
+
+    let assembler = CsrAssembler::default();
+    assert_eq!(
+        assembler.assemble(&cached).unwrap(),
+        assembler.assemble(&uncached).unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Number of nodes in basis cache")]
+fn cached_basis_quadrature_panics_for_mismatched_element() {
+    let mesh = distorted_hex_mesh();
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::hexahedron_gauss(2));
+    let cached_table = CachedBasisQuadrature::new(table, &Tet4Element::reference());
+
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&cached_table)
+        .with_u(&u)
+        .build();
+    let _ = CsrAssembler::default().assemble(&assembler);
+}
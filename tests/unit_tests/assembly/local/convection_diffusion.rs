@@ -0,0 +1,158 @@
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_csr, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, VectorAssembler,
+};
+use fenris::assembly::local::{
+    supg_stabilization_parameter, ConvectionDiffusionAssembler, ElementMatrixAssembler, FunctionSourceAssembler,
+    InterpolatedVelocityField, UniformQuadratureTable,
+};
+use fenris::mesh::procedural::{create_rectangular_uniform_quad_mesh_2d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Point2, Vector1, Vector2, U1};
+use fenris::{quadrature, Symmetry};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// Solves $- \epsilon u'' + u' = 1$ on $[0, 1]$ with $u(0) = u(1) = 0$ on a strip of Quad4 elements,
+/// returning the solution at the nodes on the bottom of the strip, sorted by $x$.
+///
+/// The problem is invariant in $y$, so that the solution coincides with the solution of the
+/// one-dimensional problem with linear elements.
+fn solve_boundary_layer_problem(diffusivity: f64, cells: usize, supg: bool) -> Vec<(f64, f64)> {
+    let h = 1.0 / cells as f64;
+    let mesh: QuadMesh2d<f64> = create_rectangular_uniform_quad_mesh_2d(h, cells, 1, 1, &Vector2::new(0.0, h));
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let velocity = |_: &Point2<f64>| Vector2::new(1.0, 0.0);
+    let assembler = ConvectionDiffusionAssembler::new(&mesh, &qtable, diffusivity, velocity).with_supg(supg);
+    let source = FunctionSourceAssembler::new(&mesh, &qtable, U1, |_: &Point2<f64>| Vector1::new(1.0));
+
+    let mut a = CsrAssembler::default().assemble(&assembler).unwrap();
+    let mut b = VectorAssembler::default().assemble_vector(&source).unwrap();
+    let dirichlet_nodes: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.x.abs() < 1e-12 || (v.x - 1.0).abs() < 1e-12)
+        .map(|(i, _)| i)
+        .collect();
+    apply_homogeneous_dirichlet_bc_csr(&mut a, &dirichlet_nodes, 1);
+    apply_homogeneous_dirichlet_bc_rhs(&mut b, &dirichlet_nodes, 1);
+    let u = DMatrix::from(&a).lu().solve(&b).unwrap();
+
+    let mut bottom: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .zip(u.iter())
+        .filter(|(v, _)| v.y.abs() < 1e-12)
+        .map(|(v, &u)| (v.x, u))
+        .collect();
+    bottom.sort_by(|(x1, _), (x2, _)| x1.total_cmp(x2));
+    bottom
+}
+
+fn boundary_layer_exact_solution(x: f64, diffusivity: f64) -> f64 {
+    let eps = diffusivity;
+    let e = (-1.0 / eps).exp();
+    x - (((x - 1.0) / eps).exp() - e) / (1.0 - e)
+}
+
+fn count_local_extrema(values: &[(f64, f64)]) -> usize {
+    values
+        .windows(3)
+        .filter(|w| (w[1].1 - w[0].1) * (w[2].1 - w[1].1) < 0.0)
+        .count()
+}
+
+#[test]
+fn supg_removes_oscillations_in_boundary_layer_problem() {
+    // The element Péclet number is |b| h / (2 eps) = 10
+    let diffusivity = 0.005;
+    let galerkin = solve_boundary_layer_problem(diffusivity, 10, false);
+    let supg = solve_boundary_layer_problem(diffusivity, 10, true);
+    assert_eq!(galerkin.len(), 11);
+    assert_eq!(supg.len(), 11);
+
+    // The exact solution increases along the flow direction up to the boundary layer at x = 1,
+    // so it has a single maximum. The Galerkin solution oscillates, whereas SUPG does not
+    assert!(count_local_extrema(&galerkin) > 1, "Galerkin solution: {galerkin:?}");
+    assert_eq!(count_local_extrema(&supg), 1, "SUPG solution: {supg:?}");
+
+    // Away from the boundary layer, the SUPG solution is close to the exact solution
+    for &(x, u_h) in supg.iter().filter(|(x, _)| *x <= 0.8 + 1e-12) {
+        let u = boundary_layer_exact_solution(x, diffusivity);
+        assert!((u_h - u).abs() < 0.05, "x = {x}, u_h = {u_h}, u = {u}");
+    }
+}
+
+#[test]
+fn convection_diffusion_without_convection_is_symmetric_with_zero_row_sums() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let zero_velocity = |_: &Point2<f64>| Vector2::zeros();
+    let assembler = ConvectionDiffusionAssembler::new(&mesh, &qtable, 2.0, zero_velocity).with_supg(true);
+    assert_eq!(assembler.symmetry(), Symmetry::NonSymmetric);
+
+    // Without convection, the matrix is a scaled Laplacian, which is symmetric and has zero row sums
+    let a = CsrAssembler::default().assemble(&assembler).unwrap();
+    let a = DMatrix::from(&a);
+    assert_matrix_eq!(a, a.transpose(), comp = abs, tol = 1e-14);
+    let row_sums = &a * DVector::repeat(a.ncols(), 1.0);
+    assert_matrix_eq!(row_sums, DVector::zeros(a.nrows()), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn interpolated_velocity_field_matches_closure() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    // A linear velocity field is exactly reproduced by the bilinear interpolation
+    let velocity = |x: &Point2<f64>| Vector2::new(1.0 + x.y, 2.0 * x.x - 0.5);
+    let nodal_velocity = DVector::from_iterator(
+        2 * mesh.vertices().len(),
+        mesh.vertices().iter().flat_map(|v| {
+            let b = velocity(v);
+            [b.x, b.y]
+        }),
+    );
+    let interpolated = InterpolatedVelocityField::new(&mesh, &nodal_velocity);
+
+    for supg in [false, true] {
+        let closure_assembler = ConvectionDiffusionAssembler::new(&mesh, &qtable, 0.01, velocity).with_supg(supg);
+        let interpolated_assembler =
+            ConvectionDiffusionAssembler::new(&mesh, &qtable, 0.01, interpolated.clone()).with_supg(supg);
+        for element_index in 0..mesh.connectivity().len() {
+            let expected = closure_assembler
+                .assemble_element_matrix(element_index)
+                .unwrap();
+            let a = interpolated_assembler
+                .assemble_element_matrix(element_index)
+                .unwrap();
+            assert_matrix_eq!(a, expected, comp = abs, tol = 1e-13);
+        }
+    }
+}
+
+#[test]
+fn supg_stabilization_parameter_limits() {
+    let h = 0.1;
+    // No stabilization without convection
+    assert_eq!(supg_stabilization_parameter(h, 0.0, 1.0), 0.0);
+    // Convection-dominated limit
+    assert_scalar_eq!(
+        supg_stabilization_parameter(h, 2.0, 1e-12),
+        h / 4.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        supg_stabilization_parameter(h, 2.0, 0.0),
+        h / 4.0,
+        comp = abs,
+        tol = 1e-14
+    );
+    // Diffusion-dominated limit, where tau is approximately h^2 / (12 eps)
+    let eps = 1e3;
+    assert_scalar_eq!(
+        supg_stabilization_parameter(h, 2.0, eps),
+        h * h / (12.0 * eps),
+        comp = abs,
+        tol = 1e-15
+    );
+}
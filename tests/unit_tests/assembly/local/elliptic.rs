@@ -5,10 +5,13 @@ use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 use fenris::allocators::BiDimAllocator;
 use fenris::assembly::local::{
     assemble_element_elliptic_matrix, assemble_element_elliptic_vector, compute_element_elliptic_energy,
-    ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementScalarAssembler, ElementVectorAssembler,
-    GeneralQuadratureTable,
+    invert_jacobian_with_policy, jacobian_condition_number, ElementEllipticAssemblerBuilder, ElementMatrixAssembler,
+    ElementScalarAssembler, ElementVectorAssembler, GeneralQuadratureTable, JacobianConditioningPolicy,
+};
+use fenris::assembly::operators::{
+    AnisotropicDiffusionOperator, DiffusionTensor, EllipticContraction, EllipticEnergy, EllipticOperator,
+    LaplaceOperator, Operator,
 };
-use fenris::assembly::operators::{EllipticContraction, EllipticEnergy, EllipticOperator, Operator};
 use fenris::element::{
     ElementConnectivity, FiniteElement, Quad4d2Element, ReferenceFiniteElement, Tet10Element, Tet4Element,
     VolumetricFiniteElement,
@@ -24,10 +27,12 @@ use fenris_optimize::calculus::{approximate_gradient_fd, approximate_jacobian_fd
 
 use crate::unit_tests::assembly::local;
 use crate::unit_tests::assembly::local::density;
-use fenris::assembly::global::gather_global_to_local;
-use fenris::connectivity::Connectivity;
+use fenris::assembly::global::{assemble_scalar, gather_global_to_local, CsrAssembler, VectorAssembler};
+use fenris::connectivity::{Connectivity, Quad4d2Connectivity, Tri3d2Connectivity};
+use fenris::error::AssemblyError;
 use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
-use fenris::mesh::QuadMesh2d;
+use fenris::mesh::{QuadMesh2d, TriangleMesh2d};
+use fenris::quadrature::CanonicalStiffnessQuadrature;
 use fenris_nested_vec::NestedVec;
 use nalgebra::DMatrixViewMut;
 
@@ -602,6 +607,167 @@ fn elliptic_element_assembler_matches_individual_element_assembly() {
     }
 }
 
+/// A mesh of three triangles, where the second triangle is a near-degenerate sliver.
+fn triangle_mesh_with_sliver() -> TriangleMesh2d<f64> {
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(0.0, 1.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(0.5, -1e-9),
+    ];
+    let connectivity = vec![
+        Tri3d2Connectivity([0, 1, 2]),
+        Tri3d2Connectivity([0, 4, 1]),
+        Tri3d2Connectivity([1, 3, 2]),
+    ];
+    TriangleMesh2d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+#[test]
+fn jacobian_condition_number_and_pseudo_inverse() {
+    let j = Matrix2::new(2.0, 0.0, 0.0, 2e-3);
+    assert_scalar_eq!(jacobian_condition_number(&j), 1000.0, comp = float);
+    assert_eq!(jacobian_condition_number(&Matrix2::<f64>::zeros()), f64::INFINITY);
+
+    let ignore = JacobianConditioningPolicy::Ignore;
+    assert_eq!(
+        invert_jacobian_with_policy(&j, &ignore).unwrap(),
+        j.try_inverse().unwrap()
+    );
+    let error = JacobianConditioningPolicy::Error {
+        max_condition_number: 100.0,
+    };
+    assert!(invert_jacobian_with_policy(&j, &error).is_err());
+    // The smallest singular value is discarded by the pseudo-inverse
+    let pseudo_inverse = JacobianConditioningPolicy::PseudoInverse {
+        max_condition_number: 100.0,
+    };
+    assert_matrix_eq!(
+        invert_jacobian_with_policy(&j, &pseudo_inverse).unwrap(),
+        Matrix2::new(0.5, 0.0, 0.0, 0.0),
+        comp = abs,
+        tol = 1e-14
+    );
+    let relaxed = JacobianConditioningPolicy::PseudoInverse {
+        max_condition_number: 1e4,
+    };
+    assert_eq!(
+        invert_jacobian_with_policy(&j, &relaxed).unwrap(),
+        j.try_inverse().unwrap()
+    );
+}
+
+#[test]
+fn elliptic_assembler_jacobian_policies_are_bit_identical_for_well_conditioned_mesh() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::from_fn(mesh.vertices().len(), |i, _| (i as f64).sin());
+    let build = || {
+        ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&mesh)
+            .with_operator(&LaplaceOperator)
+            .with_quadrature_table(&qtable)
+            .with_u(&u)
+            .build()
+    };
+
+    let default_assembler = build();
+    let expected_matrix = CsrAssembler::default()
+        .assemble(&default_assembler)
+        .unwrap();
+    let expected_vector = VectorAssembler::default()
+        .assemble_vector(&default_assembler)
+        .unwrap();
+    let expected_scalar = assemble_scalar(&default_assembler).unwrap();
+
+    for policy in [
+        JacobianConditioningPolicy::Error {
+            max_condition_number: 1e6,
+        },
+        JacobianConditioningPolicy::PseudoInverse {
+            max_condition_number: 1e6,
+        },
+    ] {
+        let assembler = build().with_jacobian_policy(policy);
+        assert_eq!(CsrAssembler::default().assemble(&assembler).unwrap(), expected_matrix);
+        assert_eq!(
+            VectorAssembler::default()
+                .assemble_vector(&assembler)
+                .unwrap(),
+            expected_vector
+        );
+        assert_eq!(assemble_scalar(&assembler).unwrap(), expected_scalar);
+    }
+}
+
+#[test]
+fn elliptic_assembler_jacobian_policies_for_sliver_element() {
+    let mesh = triangle_mesh_with_sliver();
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::from_iterator(5, mesh.vertices().iter().map(|x| x.x + 2.0 * x.y));
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    let error_assembler = assembler
+        .clone()
+        .with_jacobian_policy(JacobianConditioningPolicy::Error {
+            max_condition_number: 1e6,
+        });
+    let error = CsrAssembler::default()
+        .assemble(&error_assembler)
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<AssemblyError>(),
+        Some(AssemblyError::IllConditionedJacobian { element_index: 1, .. })
+    ));
+    assert!(VectorAssembler::default()
+        .assemble_vector(&error_assembler)
+        .is_err());
+
+    // With the truncated pseudo-inverse, the contribution of the sliver is negligible,
+    // since its area is tiny and its gradients remain bounded
+    let pseudo_inverse_assembler = assembler.with_jacobian_policy(JacobianConditioningPolicy::PseudoInverse {
+        max_condition_number: 1e6,
+    });
+    let matrix = DMatrix::from(
+        &CsrAssembler::default()
+            .assemble(&pseudo_inverse_assembler)
+            .unwrap(),
+    );
+    let vector = VectorAssembler::default()
+        .assemble_vector(&pseudo_inverse_assembler)
+        .unwrap();
+    assert!(matrix.iter().chain(vector.iter()).all(|x| x.is_finite()));
+    assert!(matrix.amax() <= 10.0);
+
+    // The same mesh without the sliver, where vertex 4 is not referenced by any element
+    let mesh_without_sliver = TriangleMesh2d::from_vertices_and_connectivity(
+        mesh.vertices().to_vec(),
+        vec![mesh.connectivity()[0], mesh.connectivity()[2]],
+    );
+    let assembler_without_sliver = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh_without_sliver)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let expected_matrix = DMatrix::from(
+        &CsrAssembler::default()
+            .assemble(&assembler_without_sliver)
+            .unwrap(),
+    );
+    let expected_vector = VectorAssembler::default()
+        .assemble_vector(&assembler_without_sliver)
+        .unwrap();
+    assert_matrix_eq!(matrix, expected_matrix, comp = abs, tol = 1e-6);
+    assert_matrix_eq!(vector, expected_vector, comp = abs, tol = 1e-6);
+}
+
 fn compute_energy_integral<Element, Energy>(
     element: &Element,
     energy: &Energy,
@@ -656,3 +822,173 @@ where
     let integral_expected = quadrature_rule.integrate(f);
     integral_expected
 }
+
+#[test]
+fn anisotropic_diffusion_operator_matches_scaled_laplace_for_isotropic_tensor() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let quadrature = quadrature::tensor::quadrilateral_gauss(2);
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_qtable = GeneralQuadratureTable::from_uniform_quadrature_and_element_data(
+        quadrature.clone(),
+        &vec![(); mesh.connectivity().len()],
+    );
+    let diffusion_qtable = GeneralQuadratureTable::from_uniform_quadrature_and_element_data(
+        quadrature,
+        &vec![DiffusionTensor::isotropic(3.0); mesh.connectivity().len()],
+    );
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&laplace_qtable)
+        .with_u(&u)
+        .build();
+    let diffusion_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&AnisotropicDiffusionOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&diffusion_qtable)
+        .with_u(&u)
+        .build();
+    assert_eq!(diffusion_assembler.symmetry(), Symmetry::Symmetric);
+
+    for element_index in 0..mesh.connectivity().len() {
+        let laplace = laplace_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        let diffusion = diffusion_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        assert_matrix_eq!(diffusion, laplace * 3.0, comp = abs, tol = 1e-13);
+    }
+}
+
+#[test]
+fn anisotropic_diffusion_operator_contraction_is_consistent_with_operator() {
+    let k = DiffusionTensor::new(Matrix2::new(2.0, 0.5, 0.5, 4.0));
+    let gradient = Vector2::new(1.0, -3.0);
+    let a = Vector2::new(2.0, 3.0);
+    let b = Vector2::new(-1.0, 5.0);
+
+    let g = AnisotropicDiffusionOperator.compute_elliptic_operator(&gradient, &k);
+    assert_matrix_eq!(g, k.tensor * gradient, comp = abs, tol = 1e-14);
+
+    let psi = AnisotropicDiffusionOperator.compute_energy(&gradient, &k);
+    assert_scalar_eq!(psi, 0.5 * gradient.dot(&(k.tensor * gradient)), comp = abs, tol = 1e-14);
+
+    // Since the operator is linear in the gradient, C(a, b) = a^T K b and the operator is symmetric
+    let c_ab = AnisotropicDiffusionOperator.contract(&gradient, &a, &b, &k);
+    let c_ba = AnisotropicDiffusionOperator.contract(&gradient, &b, &a, &k);
+    assert_scalar_eq!(c_ab[0], a.dot(&(k.tensor * b)), comp = abs, tol = 1e-14);
+    assert_scalar_eq!(c_ab[0], c_ba[0], comp = abs, tol = 1e-14);
+    assert_eq!(
+        EllipticContraction::<f64, U2>::symmetry(&AnisotropicDiffusionOperator),
+        Symmetry::Symmetric
+    );
+}
+
+/// Two quadrilaterals, the second of which is folded over itself ("bow-tie").
+fn quad_mesh_with_inverted_element() -> QuadMesh2d<f64> {
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(0.0, 1.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(2.0, 1.0),
+    ];
+    let connectivity = vec![Quad4d2Connectivity([0, 1, 2, 3]), Quad4d2Connectivity([1, 4, 2, 5])];
+    QuadMesh2d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+#[test]
+fn elliptic_assembler_reports_inverted_element() {
+    let mesh = quad_mesh_with_inverted_element();
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::zeros(mesh.vertices().len());
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    assert!(assembler.assemble_element_matrix(0).is_ok());
+    let error = CsrAssembler::default().assemble(&assembler).unwrap_err();
+    match error.downcast_ref::<AssemblyError>() {
+        Some(AssemblyError::ElementInverted {
+            element_index,
+            min_jacobian_determinant,
+        }) => {
+            assert_eq!(*element_index, 1);
+            assert!(*min_jacobian_determinant < 0.0);
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+
+    let error = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap_err();
+    assert_eq!(
+        error
+            .downcast_ref::<AssemblyError>()
+            .unwrap()
+            .element_index(),
+        1
+    );
+}
+
+#[test]
+fn elliptic_assembler_accepts_consistently_negatively_oriented_element() {
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(0.0, 1.0),
+    ];
+    let clockwise =
+        QuadMesh2d::from_vertices_and_connectivity(vertices.clone(), vec![Quad4d2Connectivity([0, 3, 2, 1])]);
+    let counter_clockwise =
+        QuadMesh2d::from_vertices_and_connectivity(vertices, vec![Quad4d2Connectivity([0, 1, 2, 3])]);
+    let u = DVector::zeros(4);
+    let qtable = clockwise.canonical_stiffness_quadrature();
+    let assemble = |mesh: &QuadMesh2d<f64>| {
+        let assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(mesh)
+            .with_operator(&LaplaceOperator)
+            .with_quadrature_table(&qtable)
+            .with_u(&u)
+            .build();
+        DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap())
+    };
+    assert_matrix_eq!(
+        assemble(&clockwise),
+        assemble(&counter_clockwise),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+#[test]
+fn elliptic_assembler_reports_incompatible_solution_vector() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let u = DVector::zeros(mesh.vertices().len() + 1);
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    let error = assembler.assemble_element_matrix(0).unwrap_err();
+    match error.downcast_ref::<AssemblyError>() {
+        Some(AssemblyError::IncompatibleDimensions {
+            element_index: 0,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(*expected, mesh.vertices().len());
+            assert_eq!(*actual, mesh.vertices().len() + 1);
+        }
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
@@ -0,0 +1,174 @@
+use fenris::assembly::color_elements;
+use fenris::assembly::global::{
+    assemble_scalar, par_assemble_scalar, CsrAssembler, CsrParAssembler, VectorAssembler, VectorParAssembler,
+};
+use fenris::assembly::local::{
+    AssemblyStage, ElementConnectivityAssembler, ElementMatrixAssembler, ElementScalarAssembler,
+    ElementVectorAssembler, InstrumentedAssembler,
+};
+use fenris::nalgebra::{DMatrixViewMut, DVectorViewMut};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// A chain of two-node elements, where one element is artificially slow to assemble.
+struct MockChainAssembler {
+    num_elements: usize,
+    slow_element: Option<usize>,
+}
+
+impl MockChainAssembler {
+    fn maybe_sleep(&self, element_index: usize) {
+        if self.slow_element == Some(element_index) {
+            sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl ElementConnectivityAssembler for MockChainAssembler {
+    fn solution_dim(&self) -> usize {
+        1
+    }
+
+    fn num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.num_elements + 1
+    }
+
+    fn element_node_count(&self, _element_index: usize) -> usize {
+        2
+    }
+
+    fn populate_element_nodes(&self, output: &mut [usize], element_index: usize) {
+        output.copy_from_slice(&[element_index, element_index + 1]);
+    }
+}
+
+impl ElementScalarAssembler<f64> for MockChainAssembler {
+    fn assemble_element_scalar(&self, element_index: usize) -> eyre::Result<f64> {
+        self.maybe_sleep(element_index);
+        Ok(element_index as f64)
+    }
+}
+
+impl ElementVectorAssembler<f64> for MockChainAssembler {
+    fn assemble_element_vector_into(&self, element_index: usize, mut output: DVectorViewMut<f64>) -> eyre::Result<()> {
+        self.maybe_sleep(element_index);
+        output.fill(1.0);
+        Ok(())
+    }
+}
+
+impl ElementMatrixAssembler<f64> for MockChainAssembler {
+    fn assemble_element_matrix_into(&self, element_index: usize, mut output: DMatrixViewMut<f64>) -> eyre::Result<()> {
+        self.maybe_sleep(element_index);
+        output.fill(-1.0);
+        output.fill_diagonal(1.0);
+        Ok(())
+    }
+}
+
+#[test]
+fn instrumented_assembler_counts_match_assembled_elements() {
+    let n = 10;
+    let mock = MockChainAssembler {
+        num_elements: n,
+        slow_element: None,
+    };
+    let instrumented = InstrumentedAssembler::new(MockChainAssembler {
+        num_elements: n,
+        slow_element: None,
+    });
+
+    let matrix = CsrAssembler::default().assemble(&instrumented).unwrap();
+    assert_eq!(matrix, CsrAssembler::default().assemble(&mock).unwrap());
+    assert_eq!(instrumented.stage_call_count(AssemblyStage::Matrix), n as u64);
+    assert_eq!(instrumented.stage_call_count(AssemblyStage::Vector), 0);
+    assert_eq!(instrumented.stage_call_count(AssemblyStage::Scalar), 0);
+
+    let vector = VectorAssembler::default()
+        .assemble_vector(&instrumented)
+        .unwrap();
+    assert_eq!(vector, VectorAssembler::default().assemble_vector(&mock).unwrap());
+    assert_eq!(instrumented.stage_call_count(AssemblyStage::Vector), n as u64);
+
+    let scalar = assemble_scalar(&instrumented).unwrap();
+    assert_eq!(scalar, assemble_scalar(&mock).unwrap());
+    assert_eq!(instrumented.stage_call_count(AssemblyStage::Scalar), n as u64);
+
+    for element_index in 0..n {
+        assert_eq!(instrumented.element_call_count(element_index), 3);
+    }
+
+    let report = instrumented.report(3);
+    assert_eq!(report.num_elements, n);
+    assert_eq!(report.num_assembled_elements, n);
+    assert_eq!(report.num_calls, 3 * n as u64);
+    assert_eq!(report.worst_elements.len(), 3);
+    assert_eq!(report.stages.len(), 3);
+    assert!(report
+        .stages
+        .iter()
+        .all(|stage| stage.num_calls == n as u64));
+    assert_eq!(instrumented.element_times_in_seconds().len(), n);
+
+    instrumented.reset();
+    assert_eq!(instrumented.report(3).num_calls, 0);
+    assert_eq!(instrumented.report(3).num_assembled_elements, 0);
+    assert!(instrumented.report(3).worst_elements.is_empty());
+}
+
+#[test]
+fn instrumented_assembler_report_identifies_slow_element() {
+    let slow_element = 7;
+    let instrumented = InstrumentedAssembler::new(MockChainAssembler {
+        num_elements: 20,
+        slow_element: Some(slow_element),
+    });
+    assemble_scalar(&instrumented).unwrap();
+
+    let report = instrumented.report(3);
+    let slow_time = Duration::from_millis(20);
+    assert_eq!(report.worst_elements[0].0, slow_element);
+    assert!(report.worst_elements[0].1 >= slow_time);
+    assert_eq!(report.max_time, report.worst_elements[0].1);
+    assert!(report.total_time >= slow_time);
+    assert!(report.mean_time >= slow_time / 20);
+    assert!(report.p99_time <= report.max_time);
+    assert!(instrumented.element_time(slow_element) >= slow_time);
+    assert!(instrumented.element_times_in_seconds()[slow_element] >= 0.02);
+
+    // The report can be formatted for human consumption
+    let formatted = format!("{}", report);
+    assert!(formatted.contains("Most expensive elements"));
+}
+
+#[test]
+fn instrumented_assembler_accumulates_counts_in_parallel_assembly() {
+    let n = 100;
+    let instrumented = InstrumentedAssembler::new(MockChainAssembler {
+        num_elements: n,
+        slow_element: None,
+    });
+    let colors = color_elements(&instrumented).to_disjoint_subsets();
+
+    let num_repetitions = 3;
+    for _ in 0..num_repetitions {
+        CsrParAssembler::default()
+            .assemble(&colors, &instrumented)
+            .unwrap();
+        VectorParAssembler::default()
+            .assemble_vector(&colors, &instrumented)
+            .unwrap();
+        par_assemble_scalar(&instrumented).unwrap();
+    }
+
+    for stage in AssemblyStage::all() {
+        assert_eq!(instrumented.stage_call_count(stage), num_repetitions * n as u64);
+    }
+    for element_index in 0..n {
+        assert_eq!(instrumented.element_call_count(element_index), 3 * num_repetitions);
+    }
+}
@@ -1,13 +1,21 @@
-use fenris::assembly::global::CsrAssembler;
-use fenris::assembly::local::{assemble_element_mass_matrix, Density, ElementMassAssembler, GeneralQuadratureTable};
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    assemble_element_mass_matrix, lump_element_matrix, Density, ElementMassAssembler, ElementMatrixAssembler,
+    GeneralQuadratureTable, LumpedElementAssembler, LumpingStrategy, UniformQuadratureTable,
+};
 use fenris::element::{ElementConnectivity, FiniteElement, Tet20Element, Tet4Element};
 use fenris::error::{estimate_L2_error_squared, estimate_element_L2_error_squared};
 use fenris::integrate::IntegrationWorkspace;
-use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
-use fenris::mesh::Tet10Mesh;
-use fenris::nalgebra::{DMatrix, DVector, DVectorView};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{Hex27Mesh, Mesh, Quad9Mesh2d, Tet10Mesh};
+use fenris::nalgebra::{DMatrix, DMatrixViewMut, DVector, DVectorView, DefaultAllocator};
 use fenris::quadrature;
-use fenris::quadrature::Quadrature;
+use fenris::quadrature::{Quadrature, QuadraturePair};
+use fenris::SmallDim;
 use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
 use nalgebra::{Matrix2, Matrix3, MatrixViewMut, Point3, Vector1};
 use std::iter::repeat;
@@ -214,3 +222,165 @@ fn squared_norm_agrees_with_mass_matrix_quadratic_form_full_mesh_tet10() {
         assert_matrix_eq!(M3, DMatrix::from(&M).kronecker(&Matrix3::identity()));
     }
 }
+
+/// Constructs a quadrature table with the density rho(x) = 2 + sin(3 x_0) + |x|^2 evaluated at
+/// the quadrature points of each element.
+fn variable_density_table<D, C>(
+    mesh: &Mesh<f64, D, C>,
+    base_quadrature: QuadraturePair<f64, D>,
+) -> GeneralQuadratureTable<f64, D, Density<f64>>
+where
+    D: SmallDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let num_elements = mesh.connectivity().len();
+    let points: Vec<_> = repeat(base_quadrature.1.clone())
+        .take(num_elements)
+        .collect();
+    let weights: Vec<_> = repeat(base_quadrature.0.clone())
+        .take(num_elements)
+        .collect();
+    let density: Vec<_> = mesh
+        .connectivity()
+        .iter()
+        .map(|conn| {
+            let element = conn.element(mesh.vertices()).unwrap();
+            base_quadrature
+                .points()
+                .iter()
+                .map(|xi| {
+                    let x = element.map_reference_coords(xi);
+                    Density(2.0 + (3.0 * x[0]).sin() + x.coords.norm_squared())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    GeneralQuadratureTable::from_points_weights_and_data(points.into(), weights.into(), density.into())
+}
+
+fn assert_lumped_mass_preserves_total_mass(mass_assembler: &impl ElementMatrixAssembler<f64>) {
+    let consistent = CsrAssembler::default().assemble(mass_assembler).unwrap();
+    let total_mass: f64 = consistent.values().iter().sum();
+    assert!(total_mass > 0.0);
+
+    for strategy in [LumpingStrategy::RowSum, LumpingStrategy::Hrz] {
+        let lumped_assembler = LumpedElementAssembler::new(mass_assembler, strategy);
+        let lumped = VectorAssembler::default()
+            .assemble_vector(&lumped_assembler)
+            .unwrap();
+        assert_scalar_eq!(lumped.sum(), total_mass, comp = abs, tol = 1e-12 * total_mass);
+        assert!(lumped.iter().all(|&m| m > 0.0));
+
+        // The lumped matrix is diagonal, and its diagonal coincides with the lumped vector
+        let lumped_matrix = CsrAssembler::default().assemble(&lumped_assembler).unwrap();
+        let lumped_dense = DMatrix::from(&lumped_matrix);
+        assert_matrix_eq!(lumped_dense, DMatrix::from_diagonal(&lumped), comp = abs, tol = 1e-14);
+
+        if strategy == LumpingStrategy::RowSum {
+            // Since the element matrices are lumped individually, row-sum lumping of the
+            // element matrices coincides with row-sum lumping of the global matrix
+            let row_sums = DMatrix::from(&consistent) * DVector::repeat(lumped.len(), 1.0);
+            assert_matrix_eq!(lumped, row_sums, comp = abs, tol = 1e-14);
+        }
+    }
+}
+
+#[test]
+fn lumped_mass_preserves_total_mass_tri3() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d(4);
+    let qtable = variable_density_table(&mesh, quadrature::total_order::triangle(4).unwrap());
+    for solution_dim in [1, 2] {
+        let assembler = ElementMassAssembler::with_solution_dim(solution_dim)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        assert_lumped_mass_preserves_total_mass(&assembler);
+    }
+}
+
+#[test]
+fn lumped_mass_preserves_total_mass_tet4() {
+    let mesh = create_unit_box_uniform_tet_mesh_3d(2);
+    let qtable = variable_density_table(&mesh, quadrature::total_order::tetrahedron(4).unwrap());
+    for solution_dim in [1, 3] {
+        let assembler = ElementMassAssembler::with_solution_dim(solution_dim)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        assert_lumped_mass_preserves_total_mass(&assembler);
+    }
+}
+
+#[test]
+fn lumped_mass_preserves_total_mass_hex8() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    let qtable = variable_density_table(&mesh, quadrature::tensor::hexahedron_gauss(3));
+    for solution_dim in [1, 3] {
+        let assembler = ElementMassAssembler::with_solution_dim(solution_dim)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        assert_lumped_mass_preserves_total_mass(&assembler);
+    }
+}
+
+#[test]
+fn lump_element_matrix_simple_example() {
+    #[rustfmt::skip]
+    let consistent = DMatrix::from_row_slice(3, 3, &[
+        2.0, 1.0, 1.0,
+        1.0, 4.0, 1.0,
+        1.0, 1.0, 6.0,
+    ]);
+
+    let mut row_sum = consistent.clone();
+    lump_element_matrix(DMatrixViewMut::from(&mut row_sum), LumpingStrategy::RowSum);
+    assert_eq!(
+        row_sum,
+        DMatrix::from_diagonal(&DVector::from_column_slice(&[4.0, 6.0, 8.0]))
+    );
+
+    // The total of 18 is distributed in proportion to the diagonal entries 2, 4 and 6
+    let mut hrz = consistent.clone();
+    lump_element_matrix(DMatrixViewMut::from(&mut hrz), LumpingStrategy::Hrz);
+    assert_matrix_eq!(
+        hrz,
+        DMatrix::from_diagonal(&DVector::from_column_slice(&[3.0, 6.0, 9.0])),
+        comp = abs,
+        tol = 1e-14
+    );
+}
+
+/// Asserts that the mass matrix assembled with the given quadrature rule is diagonal and preserves the total mass.
+fn assert_mass_matrix_is_diagonal<D, C>(mesh: &Mesh<f64, D, C>, quadrature: QuadraturePair<f64, D>, volume: f64)
+where
+    D: SmallDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature, Density(1.0));
+    let assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(mesh)
+        .with_quadrature_table(&qtable);
+    let mass = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+    let diagonal = mass.diagonal();
+    assert!(diagonal.iter().all(|&m| m > 0.0));
+    assert_scalar_eq!(diagonal.sum(), volume, comp = abs, tol = 1e-12);
+
+    let mut off_diagonal = mass.clone();
+    off_diagonal.fill_diagonal(0.0);
+    assert!(off_diagonal.amax() <= 10.0 * f64::EPSILON * diagonal.max());
+}
+
+#[test]
+fn gauss_lobatto_mass_matrices_are_diagonal_for_tensor_product_lagrange_elements() {
+    let quad4_mesh = create_unit_square_uniform_quad_mesh_2d(3);
+    let quad9_mesh = Quad9Mesh2d::from(quad4_mesh.clone());
+    let hex8_mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    let hex27_mesh = Hex27Mesh::from(&hex8_mesh);
+
+    let quadrilateral_rule = |n| quadrature::tensor::try_quadrilateral_gauss_lobatto(n).unwrap();
+    let hexahedron_rule = |n| quadrature::tensor::try_hexahedron_gauss_lobatto(n).unwrap();
+    assert_mass_matrix_is_diagonal(&quad4_mesh, quadrilateral_rule(2), 1.0);
+    assert_mass_matrix_is_diagonal(&quad9_mesh, quadrilateral_rule(3), 1.0);
+    assert_mass_matrix_is_diagonal(&hex8_mesh, hexahedron_rule(2), 1.0);
+    assert_mass_matrix_is_diagonal(&hex27_mesh, hexahedron_rule(3), 1.0);
+}
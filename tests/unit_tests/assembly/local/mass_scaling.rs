@@ -0,0 +1,304 @@
+use fenris::assembly::global::{CsrAssembler, VectorAssembler};
+use fenris::assembly::local::{
+    compute_conventional_mass_scale_factors, compute_element_critical_time_steps, compute_mass_scaling_report,
+    compute_selective_mass_scaling_coefficients, Density, ElementEllipticAssemblerBuilder, ElementMassAssembler,
+    ElementMatrixAssembler, LumpedElementAssembler, LumpingStrategy, ScaledElementMassAssembler,
+    SelectiveMassScalingAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::assembly::DirichletBcs;
+use fenris::io::vtk::VtkExporter;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector};
+use fenris::nalgebra_sparse::CsrMatrix;
+use fenris::quadrature;
+use fenris::util::small_eig::generalized_symmetric_eigenvalues;
+use fenris::vtkio::model::{Attribute, DataSet, Piece};
+use fenris_solid::model::dynamics::{DynamicState, SecondOrderSystem, SymplecticEuler};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+/// A mesh of the unit square whose elements become progressively smaller towards the origin.
+fn graded_mesh() -> QuadMesh2d<f64> {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    mesh.transform_vertices(|v| {
+        v.x = v.x.powi(3);
+        v.y = v.y.powi(3);
+    });
+    mesh
+}
+
+fn assemble_dense(assembler: &impl ElementMatrixAssembler<f64>) -> DMatrix<f64> {
+    DMatrix::from(&CsrAssembler::default().assemble(assembler).unwrap())
+}
+
+/// Computes the critical time step of the system (K, M) by solving the generalized eigenvalue problem.
+fn compute_critical_time_step(stiffness: &DMatrix<f64>, mass: &DMatrix<f64>) -> f64 {
+    let omega_squared_max = generalized_symmetric_eigenvalues(stiffness, mass)
+        .unwrap()
+        .max();
+    2.0 / omega_squared_max.sqrt()
+}
+
+#[test]
+fn conventional_mass_scaling_meets_target_time_step() {
+    let mesh = graded_mesh();
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(3.0),
+    );
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::<f64>::zeros(mesh.vertices().len());
+
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .with_u(&u)
+        .build();
+
+    let critical_dts = compute_element_critical_time_steps(&mass_assembler, &stiffness_assembler).unwrap();
+    let min_dt = critical_dts.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_dt = critical_dts.iter().copied().fold(0.0, f64::max);
+    assert!(min_dt < max_dt);
+    let target_dt = 0.5 * (min_dt + max_dt);
+
+    let stiffness = assemble_dense(&stiffness_assembler);
+    let mass = assemble_dense(&mass_assembler);
+    // Sanity check: the unscaled system should not be stable at the target time step
+    assert!(compute_critical_time_step(&stiffness, &mass) < target_dt);
+
+    let scale_factors = compute_conventional_mass_scale_factors(target_dt, &critical_dts);
+    let scaled_mass_assembler =
+        ScaledElementMassAssembler::from_assembler_and_scale_factors(&mass_assembler, &scale_factors);
+    let scaled_mass = assemble_dense(&scaled_mass_assembler);
+
+    // Every element individually meets the target, and so does the assembled system
+    let scaled_critical_dts =
+        compute_element_critical_time_steps(&scaled_mass_assembler, &stiffness_assembler).unwrap();
+    for dt in scaled_critical_dts {
+        assert!(dt >= target_dt * (1.0 - 1e-10));
+    }
+    assert!(compute_critical_time_step(&stiffness, &scaled_mass) >= target_dt * (1.0 - 1e-10));
+
+    // The reported added mass must be consistent with the mass matrices
+    let report = compute_mass_scaling_report(&mass_assembler, &scale_factors).unwrap();
+    assert_scalar_eq!(report.total_mass, 3.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(report.total_mass, mass.sum(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(
+        report.total_added_mass,
+        scaled_mass.sum() - mass.sum(),
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        report.total_added_mass,
+        report.element_added_mass.iter().sum::<f64>(),
+        comp = abs,
+        tol = 1e-12
+    );
+    assert!(report.added_mass_fraction() > 0.0);
+    // Elements that already meet the target must not have any added mass
+    for (&dt, &added_mass) in critical_dts.iter().zip(&report.element_added_mass) {
+        if dt >= target_dt {
+            assert_eq!(added_mass, 0.0);
+        } else {
+            assert!(added_mass > 0.0);
+        }
+    }
+}
+
+#[test]
+fn selective_mass_scaling_preserves_rigid_translation() {
+    let mesh = graded_mesh();
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(3.0),
+    );
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let u = DVector::<f64>::zeros(mesh.vertices().len());
+
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .with_u(&u)
+        .build();
+
+    let critical_dts = compute_element_critical_time_steps(&mass_assembler, &stiffness_assembler).unwrap();
+    let min_dt = critical_dts.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_dt = critical_dts.iter().copied().fold(0.0, f64::max);
+    let target_dt = 0.5 * (min_dt + max_dt);
+
+    let coefficients = compute_selective_mass_scaling_coefficients(target_dt, &critical_dts);
+    let scaled_mass_assembler = SelectiveMassScalingAssembler::from_assemblers_and_coefficients(
+        &mass_assembler,
+        &stiffness_assembler,
+        &coefficients,
+    );
+
+    let stiffness = assemble_dense(&stiffness_assembler);
+    let mass = assemble_dense(&mass_assembler);
+    let scaled_mass = assemble_dense(&scaled_mass_assembler);
+
+    // Rigid translations (constant fields for the scalar problem) see exactly the same mass,
+    // so that the rigid-body modes and their (zero) frequencies are unchanged
+    let translation = DVector::repeat(mesh.vertices().len(), 1.0);
+    assert_matrix_eq!(
+        &scaled_mass * &translation,
+        &mass * &translation,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        translation.dot(&(&scaled_mass * &translation)),
+        3.0,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // The scaled system must be stable at the target time step
+    assert!(compute_critical_time_step(&stiffness, &mass) < target_dt);
+    assert!(compute_critical_time_step(&stiffness, &scaled_mass) >= target_dt * (1.0 - 1e-9));
+}
+
+/// The linear system $\vec M \ddot{\vec u} + \vec K \vec u = \vec 0$.
+struct LinearSystem {
+    stiffness: CsrMatrix<f64>,
+}
+
+impl SecondOrderSystem<f64> for LinearSystem {
+    fn num_dofs(&self) -> usize {
+        self.stiffness.nrows()
+    }
+
+    fn assemble_residual(&self, u: &DVector<f64>) -> eyre::Result<DVector<f64>> {
+        Ok(&self.stiffness * u)
+    }
+
+    fn assemble_tangent(&self, _u: &DVector<f64>) -> eyre::Result<CsrMatrix<f64>> {
+        Ok(self.stiffness.clone())
+    }
+}
+
+#[test]
+fn conventionally_scaled_lumped_system_runs_stably_at_target_time_step() {
+    let mesh = graded_mesh();
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(3.0),
+    );
+    let stiffness_qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let num_nodes = mesh.vertices().len();
+    let u = DVector::<f64>::zeros(num_nodes);
+
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let stiffness_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&stiffness_qtable)
+        .with_u(&u)
+        .build();
+
+    // The explicit integrator uses the lumped mass, so the critical time steps must be
+    // estimated with the lumped element matrices
+    let lumped_mass_assembler = LumpedElementAssembler::new(&mass_assembler, LumpingStrategy::RowSum);
+    let critical_dts = compute_element_critical_time_steps(&lumped_mass_assembler, &stiffness_assembler).unwrap();
+    let min_dt = critical_dts.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_dt = critical_dts.iter().copied().fold(0.0, f64::max);
+    let target_dt = 0.5 * (min_dt + max_dt);
+
+    let scale_factors = compute_conventional_mass_scale_factors(target_dt, &critical_dts);
+    let scaled_lumped_mass_assembler = LumpedElementAssembler::new(
+        ScaledElementMassAssembler::from_assembler_and_scale_factors(&mass_assembler, &scale_factors),
+        LumpingStrategy::RowSum,
+    );
+    let lumped_mass = VectorAssembler::default()
+        .assemble_vector(&lumped_mass_assembler)
+        .unwrap();
+    let scaled_lumped_mass = VectorAssembler::default()
+        .assemble_vector(&scaled_lumped_mass_assembler)
+        .unwrap();
+
+    let report = compute_mass_scaling_report(&lumped_mass_assembler, &scale_factors).unwrap();
+    assert_scalar_eq!(
+        report.total_added_mass,
+        scaled_lumped_mass.sum() - lumped_mass.sum(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let system = LinearSystem {
+        stiffness: CsrAssembler::default()
+            .assemble(&stiffness_assembler)
+            .unwrap(),
+    };
+    // An initial displacement that excites all modes, including the highest ones
+    let u0 = DVector::from_fn(num_nodes, |i, _| if i % 2 == 0 { 1.0 } else { -1.0 });
+    let max_displacement_norm = |lumped_mass: DVector<f64>| {
+        let integrator =
+            SymplecticEuler::from_lumped_mass(lumped_mass, DirichletBcs::homogeneous(vec![]), target_dt).unwrap();
+        let mut state = DynamicState::zeros(num_nodes);
+        state.u = u0.clone();
+        let mut max_norm: f64 = 0.0;
+        for _ in 0..1000 {
+            integrator.step(&system, &mut state).unwrap();
+            max_norm = max_norm.max(state.u.norm());
+        }
+        max_norm
+    };
+
+    // The scaled system remains bounded, whereas the unscaled system blows up
+    assert!(max_displacement_norm(scaled_lumped_mass) < 10.0 * u0.norm());
+    assert!(max_displacement_norm(lumped_mass) > 1e6 * u0.norm());
+}
+
+#[test]
+fn mass_scaling_report_is_exported_as_cell_field() {
+    let mesh = graded_mesh();
+    let mass_qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(3.0),
+    );
+    let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+        .with_space(&mesh)
+        .with_quadrature_table(&mass_qtable);
+    let scale_factors: Vec<_> = (0..mesh.connectivity().len())
+        .map(|i| 1.0 + (i % 3) as f64)
+        .collect();
+    let report = compute_mass_scaling_report(&mass_assembler, &scale_factors).unwrap();
+
+    let dataset = VtkExporter::new(&mesh)
+        .with_mass_scaling_report(&report)
+        .build()
+        .unwrap();
+    let piece = match dataset {
+        DataSet::UnstructuredGrid { mut pieces, .. } => match pieces.pop() {
+            Some(Piece::Inline(piece)) => piece,
+            _ => panic!("expected an inline piece"),
+        },
+        _ => panic!("expected an unstructured grid"),
+    };
+    let added_mass = piece
+        .data
+        .cell
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::DataArray(array) if array.name == "added_mass" => Some(array),
+            _ => None,
+        })
+        .expect("added_mass cell field must be present");
+    assert_eq!(added_mass.elem.num_comp(), 1);
+    assert_eq!(
+        added_mass.data.clone().cast_into::<f64>().unwrap(),
+        report.element_added_mass
+    );
+}
@@ -0,0 +1,311 @@
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::global::{
+    apply_homogeneous_dirichlet_bc_matrix, apply_homogeneous_dirichlet_bc_rhs, CsrAssembler, VectorAssembler,
+};
+use fenris::assembly::local::{
+    ElementConnectivityAssembler, ElementEllipticAssemblerBuilder, ElementMatrixAssembler, ElementPressureAssembler,
+    ElementVectorAssembler, QuadratureTable, UniformQuadratureTable,
+};
+use fenris::connectivity::{Quad4d2Connectivity, Segment2d2Connectivity, Tri3d3Connectivity};
+use fenris::mesh::procedural::{create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{Mesh, QuadMesh2d, Tet4Mesh};
+use fenris::nalgebra::{DMatrix, DVector, DefaultAllocator, DimName, Point2, U2, U3};
+use fenris::quadrature;
+use fenris::space::FiniteElementSpace;
+use fenris_solid::materials::{LameParameters, NeoHookeanMaterial};
+use fenris_solid::MaterialEllipticOperator;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::f64::consts::PI;
+
+/// Some arbitrary, but smooth displacement field.
+fn arbitrary_displacement(num_dofs: usize) -> DVector<f64> {
+    DVector::from_fn(num_dofs, |i, _| 0.1 * f64::sin(1.3 * i as f64))
+}
+
+fn surface_mesh_2d() -> Mesh<f64, U2, Segment2d2Connectivity> {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    mesh.extract_boundary_face_mesh()
+}
+
+fn surface_mesh_3d() -> Mesh<f64, U3, Tri3d3Connectivity> {
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    mesh.extract_boundary_face_mesh()
+}
+
+/// Sums the per-node forces of a global force vector.
+fn total_force(f: &DVector<f64>, dim: usize) -> DVector<f64> {
+    let mut total = DVector::zeros(dim);
+    for node_force in f.as_slice().chunks_exact(dim) {
+        total += DVector::from_column_slice(node_force);
+    }
+    total
+}
+
+fn pressure_assembler<'a, Space, QTable>(
+    pressure: f64,
+    space: &'a Space,
+    qtable: &'a QTable,
+    u: &'a DVector<f64>,
+) -> ElementPressureAssembler<'a, f64, Space, QTable> {
+    ElementPressureAssembler::with_pressure(pressure)
+        .with_space(space)
+        .with_quadrature_table(qtable)
+        .with_u(u)
+}
+
+#[allow(non_snake_case)]
+fn assert_load_stiffness_agrees_with_finite_differences<Space, QTable>(space: &Space, qtable: &QTable)
+where
+    Space: FiniteElementSpace<f64>,
+    QTable: QuadratureTable<f64, Space::ReferenceDim>,
+    DefaultAllocator: BiDimAllocator<f64, Space::GeometryDim, Space::ReferenceDim>,
+{
+    let pressure = 3.0;
+    let h = 1e-6;
+    let s = Space::GeometryDim::dim();
+    let u = arbitrary_displacement(s * space.num_nodes());
+
+    let assembler = pressure_assembler(pressure, space, qtable, &u);
+    for element_index in 0..assembler.num_elements() {
+        let n = assembler.element_node_count(element_index);
+        let mut nodes = vec![0; n];
+        assembler.populate_element_nodes(&mut nodes, element_index);
+
+        let k = assembler.assemble_element_matrix(element_index).unwrap();
+        let mut k_fd = DMatrix::zeros(s * n, s * n);
+        for (J, &node) in nodes.iter().enumerate() {
+            for i in 0..s {
+                let mut u_plus = u.clone();
+                let mut u_minus = u.clone();
+                u_plus[s * node + i] += h;
+                u_minus[s * node + i] -= h;
+                let f_plus = pressure_assembler(pressure, space, qtable, &u_plus)
+                    .assemble_element_vector(element_index)
+                    .unwrap();
+                let f_minus = pressure_assembler(pressure, space, qtable, &u_minus)
+                    .assemble_element_vector(element_index)
+                    .unwrap();
+                // The load stiffness is the *negative* derivative of the external force
+                k_fd.set_column(s * J + i, &(-(f_plus - f_minus) / (2.0 * h)));
+            }
+        }
+
+        assert_matrix_eq!(k, k_fd, comp = abs, tol = 1e-6);
+    }
+}
+
+#[test]
+fn pressure_load_on_closed_surface_has_zero_total_force() {
+    let pressure = 2.5;
+
+    {
+        let surface_mesh = surface_mesh_2d();
+        let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+        let u = arbitrary_displacement(2 * surface_mesh.vertices().len());
+        let assembler = pressure_assembler(pressure, &surface_mesh, &qtable, &u);
+        let f = VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap();
+        let total = total_force(&f, 2);
+        assert_matrix_eq!(total, DVector::zeros(2), comp = abs, tol = 1e-12);
+    }
+
+    {
+        let surface_mesh = surface_mesh_3d();
+        let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+        let u = arbitrary_displacement(3 * surface_mesh.vertices().len());
+        let assembler = pressure_assembler(pressure, &surface_mesh, &qtable, &u);
+        let f = VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap();
+        let total = total_force(&f, 3);
+        assert_matrix_eq!(total, DVector::zeros(3), comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn pressure_load_work_agrees_with_divergence_theorem() {
+    // For a linear position field x, we have sum_I f_I . x_I = -p int_Gamma x . n da = -p d |Omega|,
+    // by the divergence theorem. The undeformed domains are the unit square/cube.
+    let pressure = 2.5;
+
+    {
+        let surface_mesh = surface_mesh_2d();
+        let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+        let u = DVector::<f64>::zeros(2 * surface_mesh.vertices().len());
+        let assembler = pressure_assembler(pressure, &surface_mesh, &qtable, &u);
+        let f = VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap();
+        let x = DVector::from_iterator(
+            f.len(),
+            surface_mesh
+                .vertices()
+                .iter()
+                .flat_map(|v| v.coords.iter().copied()),
+        );
+        assert_scalar_eq!(f.dot(&x), -2.0 * pressure, comp = abs, tol = 1e-12);
+    }
+
+    {
+        let surface_mesh = surface_mesh_3d();
+        let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+        let u = DVector::<f64>::zeros(3 * surface_mesh.vertices().len());
+        let assembler = pressure_assembler(pressure, &surface_mesh, &qtable, &u);
+        let f = VectorAssembler::default()
+            .assemble_vector(&assembler)
+            .unwrap();
+        let x = DVector::from_iterator(
+            f.len(),
+            surface_mesh
+                .vertices()
+                .iter()
+                .flat_map(|v| v.coords.iter().copied()),
+        );
+        assert_scalar_eq!(f.dot(&x), -3.0 * pressure, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn pressure_load_stiffness_agrees_with_finite_differences_2d() {
+    let surface_mesh = surface_mesh_2d();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+    assert_load_stiffness_agrees_with_finite_differences(&surface_mesh, &qtable);
+}
+
+#[test]
+fn pressure_load_stiffness_agrees_with_finite_differences_3d() {
+    let surface_mesh = surface_mesh_3d();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::total_order::triangle(2).unwrap());
+    assert_load_stiffness_agrees_with_finite_differences(&surface_mesh, &qtable);
+}
+
+/// Creates a mesh of the cross-section of a thick-walled cylinder, along with the inner surface
+/// (oriented so that its normal points out of the domain, i.e. towards the axis of the cylinder)
+/// and the nodes on the outer surface.
+fn thick_walled_cylinder(
+    inner_radius: f64,
+    outer_radius: f64,
+    radial_cells: usize,
+    angular_cells: usize,
+) -> (QuadMesh2d<f64>, Mesh<f64, U2, Segment2d2Connectivity>, Vec<usize>) {
+    let node_index = |i: usize, j: usize| i * angular_cells + (j % angular_cells);
+
+    let mut vertices = Vec::new();
+    for i in 0..=radial_cells {
+        let r = inner_radius + (outer_radius - inner_radius) * (i as f64 / radial_cells as f64);
+        for j in 0..angular_cells {
+            let theta = 2.0 * PI * (j as f64 / angular_cells as f64);
+            vertices.push(Point2::new(r * theta.cos(), r * theta.sin()));
+        }
+    }
+
+    let mut cells = Vec::new();
+    for i in 0..radial_cells {
+        for j in 0..angular_cells {
+            cells.push(Quad4d2Connectivity([
+                node_index(i, j),
+                node_index(i + 1, j),
+                node_index(i + 1, j + 1),
+                node_index(i, j + 1),
+            ]));
+        }
+    }
+
+    // The inner surface must be traversed clockwise for its normal to point out of the domain
+    let inner_faces = (0..angular_cells)
+        .map(|j| Segment2d2Connectivity([node_index(0, j + 1), node_index(0, j)]))
+        .collect();
+    let outer_nodes = (0..angular_cells)
+        .map(|j| node_index(radial_cells, j))
+        .collect();
+
+    let volume_mesh = QuadMesh2d::from_vertices_and_connectivity(vertices.clone(), cells);
+    let inner_surface = Mesh::from_vertices_and_connectivity(vertices, inner_faces);
+    (volume_mesh, inner_surface, outer_nodes)
+}
+
+/// Inflates a thick-walled cylinder that is fixed at its outer surface by applying pressure
+/// to its inner surface, and returns the residual norms of each Newton iteration.
+///
+/// The load stiffness is only included in the Newton system if `include_load_stiffness` is `true`.
+fn inflate_thick_walled_cylinder(include_load_stiffness: bool, max_iterations: usize) -> Vec<f64> {
+    let pressure = 0.5;
+    let lame = LameParameters { mu: 1.0, lambda: 2.0 };
+    let (mesh, inner_surface, outer_nodes) = thick_walled_cylinder(1.0, 2.0, 4, 32);
+    let material = NeoHookeanMaterial;
+    let operator = MaterialEllipticOperator::new(&material);
+    let volume_qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), lame);
+    let surface_qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+
+    let mut u = DVector::zeros(2 * mesh.vertices().len());
+    let mut residual_norms = Vec::new();
+    for _ in 0..max_iterations {
+        let elastic_assembler = ElementEllipticAssemblerBuilder::new()
+            .with_finite_element_space(&mesh)
+            .with_operator(&operator)
+            .with_quadrature_table(&volume_qtable)
+            .with_u(&u)
+            .build();
+        let pressure_assembler = pressure_assembler(pressure, &inner_surface, &surface_qtable, &u);
+
+        // The residual is the difference between the internal elastic forces and the external pressure forces
+        let mut residual = VectorAssembler::default()
+            .assemble_vector(&elastic_assembler)
+            .unwrap()
+            - VectorAssembler::default()
+                .assemble_vector(&pressure_assembler)
+                .unwrap();
+        apply_homogeneous_dirichlet_bc_rhs(&mut residual, &outer_nodes, 2);
+        residual_norms.push(residual.norm());
+        if residual.norm() < 1e-12 {
+            break;
+        }
+
+        let mut jacobian = DMatrix::from(
+            &CsrAssembler::default()
+                .assemble(&elastic_assembler)
+                .unwrap(),
+        );
+        if include_load_stiffness {
+            jacobian += DMatrix::from(
+                &CsrAssembler::default()
+                    .assemble(&pressure_assembler)
+                    .unwrap(),
+            );
+        }
+        apply_homogeneous_dirichlet_bc_matrix::<_, U2>(&mut jacobian, &outer_nodes);
+        u -= jacobian.lu().solve(&residual).unwrap();
+    }
+    residual_norms
+}
+
+#[test]
+fn inflated_thick_walled_cylinder_newton_convergence_depends_on_load_stiffness() {
+    // Estimates the order of convergence from three consecutive residual norms
+    let convergence_order = |r: &[f64]| (r[2] / r[1]).ln() / (r[1] / r[0]).ln();
+
+    // With the load stiffness, Newton's method must converge quadratically
+    let newton_residual_norms = inflate_thick_walled_cylinder(true, 10);
+    let n = newton_residual_norms.len();
+    assert!(
+        *newton_residual_norms.last().unwrap() < 1e-12,
+        "{newton_residual_norms:?}"
+    );
+    // The last residual is typically already polluted by round-off errors, so we skip it
+    let order = convergence_order(&newton_residual_norms[n - 4..n - 1]);
+    assert!(order > 1.8, "order {order} for residuals {newton_residual_norms:?}");
+
+    // Without the load stiffness, the method degenerates to a fixed-point iteration
+    // that converges only linearly
+    let residual_norms = inflate_thick_walled_cylinder(false, 100);
+    let m = residual_norms.len();
+    assert!(*residual_norms.last().unwrap() < 1e-12, "{residual_norms:?}");
+    assert!(m > n, "{residual_norms:?}");
+    let order = convergence_order(&residual_norms[m - 5..m - 2]);
+    assert!(
+        (0.8..1.2).contains(&order),
+        "order {order} for residuals {residual_norms:?}"
+    );
+}
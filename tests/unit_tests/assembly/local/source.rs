@@ -1,15 +1,18 @@
 use crate::unit_tests::assembly::local;
 use crate::unit_tests::assembly::local::density;
+use fenris::assembly::global::VectorAssembler;
 use fenris::assembly::local::ElementVectorAssembler;
 use fenris::assembly::local::{
-    assemble_element_source_vector, ElementSourceAssemblerBuilder, GeneralQuadratureTable, SourceFunction,
+    assemble_element_source_vector, ElementSourceAssemblerBuilder, FunctionSourceAssembler, GeneralQuadratureTable,
+    SourceFunction, TimeDependentSourceAssembler, UniformQuadratureTable,
 };
 use fenris::assembly::operators::Operator;
+use fenris::connectivity::Connectivity;
 use fenris::element::{ElementConnectivity, FiniteElement, ReferenceFiniteElement, Tet10Element, Tet4Element};
 use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
 use fenris::mesh::QuadMesh2d;
 use fenris::nalgebra::base::coordinates::XYZ;
-use fenris::nalgebra::{DVector, DVectorViewMut, OPoint, Point2, Point3, Vector2, U2, U3};
+use fenris::nalgebra::{DVector, DVectorViewMut, OPoint, Point2, Point3, Vector1, Vector2, U1, U2, U3};
 use fenris::quadrature;
 use fenris::quadrature::Quadrature;
 use fenris_nested_vec::NestedVec;
@@ -198,3 +201,76 @@ fn source_vector_assembler_matches_individual_element_assembly() {
         assert_matrix_eq!(element_vector, element_vector_expected);
     }
 }
+
+#[test]
+fn function_source_assembler_constant_source_gives_expected_load_vector() {
+    let n = 4;
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(n);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let c = 3.0;
+    let assembler = FunctionSourceAssembler::new(&mesh, &qtable, U1, |_: &Point2<f64>| Vector1::new(c));
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    // Each element contributes c * h^2 / 4 to each of its nodes
+    let h = 1.0 / n as f64;
+    let mut expected = DVector::zeros(mesh.vertices().len());
+    for conn in mesh.connectivity() {
+        for &node in conn.vertex_indices() {
+            expected[node] += c * h * h / 4.0;
+        }
+    }
+    assert_matrix_eq!(f, expected, comp = abs, tol = 1e-14);
+    assert_scalar_eq!(f.sum(), c, comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn function_source_assembler_evaluates_source_at_physical_points() {
+    // The basis functions form a partition of unity, so the sum of the nodal loads for each
+    // component is the integral of the source, which is exact for a linear source
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let assembler = FunctionSourceAssembler::new(&mesh, &qtable, U2, |x: &Point2<f64>| {
+        Vector2::new(2.0 * x.x + 1.0, x.y - 3.0)
+    });
+    let f = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+
+    // The mesh covers [0, 1]^2, so the integral is the value at the center of the square
+    let f1: f64 = f.iter().step_by(2).sum();
+    let f2: f64 = f.iter().skip(1).step_by(2).sum();
+    let center = Point2::new(0.5, 0.5);
+    assert_scalar_eq!(f1, 2.0 * center.x + 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(f2, center.y - 3.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn time_dependent_source_assembler_uses_current_time() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let source = |x: &Point2<f64>, t: f64| Vector1::new(t * (1.0 + x.x * x.x));
+    let mut assembler = TimeDependentSourceAssembler::new(&mesh, &qtable, U1, source, 1.0);
+    assert_eq!(assembler.time(), 1.0);
+
+    let vector_assembler = VectorAssembler::default();
+    let f_1 = vector_assembler.assemble_vector(&assembler).unwrap();
+
+    assembler.set_time(2.5);
+    assert_eq!(assembler.time(), 2.5);
+    let f_2 = vector_assembler.assemble_vector(&assembler).unwrap();
+    assert_matrix_eq!(f_2, &f_1 * 2.5, comp = abs, tol = 1e-14);
+
+    // At any fixed time, the result must agree with the time-independent assembler
+    let fixed = FunctionSourceAssembler::new(&mesh, &qtable, U1, |x: &Point2<f64>| source(x, 2.5));
+    let f_fixed = vector_assembler.assemble_vector(&fixed).unwrap();
+    assert_matrix_eq!(f_2, f_fixed, comp = abs, tol = 1e-14);
+
+    assembler.set_time(0.0);
+    let f_0 = vector_assembler.assemble_vector(&assembler).unwrap();
+    assert_matrix_eq!(f_0, DVector::zeros(f_0.len()));
+}
@@ -0,0 +1,167 @@
+use fenris::assembly::local::{
+    ElementEllipticAssemblerBuilder, ElementMatrixAssembler, SpdProjectedAssembler, UniformQuadratureTable,
+};
+use fenris::assembly::operators::{EllipticContraction, LaplaceOperator, Operator, SpdProjectedContraction};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix1, OMatrix, OVector, Vector2, U1, U2};
+use fenris::quadrature;
+use fenris::util::small_eig::symmetric_eigenvalues;
+use fenris::Symmetry;
+use matrixcompare::assert_matrix_eq;
+
+/// A scalar operator in 2D whose energy density $\frac{1}{2}(G_1^2 - G_2^2)$ is indefinite.
+struct IndefiniteOperator;
+
+impl Operator<f64, U2> for IndefiniteOperator {
+    type SolutionDim = U1;
+    type Parameters = ();
+}
+
+impl EllipticContraction<f64, U2> for IndefiniteOperator {
+    fn contract(
+        &self,
+        _gradient: &OMatrix<f64, U2, U1>,
+        a: &OVector<f64, U2>,
+        b: &OVector<f64, U2>,
+        _parameters: &(),
+    ) -> OMatrix<f64, U1, U1> {
+        Matrix1::new(a.x * b.x - a.y * b.y)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
+
+fn assert_min_eigenvalue_at_least(matrix: &DMatrix<f64>, min_eigenvalue: f64) {
+    assert_matrix_eq!(matrix, matrix.transpose(), comp = abs, tol = 1e-14);
+    let eigenvalues = symmetric_eigenvalues(matrix);
+    assert!(eigenvalues[0] >= min_eigenvalue - 1e-12, "eigenvalues: {}", eigenvalues);
+}
+
+#[test]
+fn spd_projected_assembler_leaves_psd_element_matrices_unchanged() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let u = DVector::zeros(mesh.vertices().len());
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let projected_assembler = SpdProjectedAssembler::new(&laplace_assembler, 0.0);
+    assert_eq!(projected_assembler.symmetry(), Symmetry::Symmetric);
+
+    for element_index in 0..mesh.connectivity().len() {
+        let original = laplace_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        let projected = projected_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        assert_matrix_eq!(projected, original, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn spd_projected_assembler_clamps_indefinite_element_matrices() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let u = DVector::zeros(mesh.vertices().len());
+    let indefinite_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&IndefiniteOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    for min_eigenvalue in [0.0, 0.1] {
+        let projected_assembler = SpdProjectedAssembler::new(&indefinite_assembler, min_eigenvalue);
+        for element_index in 0..mesh.connectivity().len() {
+            let original = indefinite_assembler
+                .assemble_element_matrix(element_index)
+                .unwrap();
+            assert!(symmetric_eigenvalues(&original)[0] < 0.0);
+
+            let projected = projected_assembler
+                .assemble_element_matrix(element_index)
+                .unwrap();
+            assert_min_eigenvalue_at_least(&projected, min_eigenvalue);
+            let expected_eigenvalues = symmetric_eigenvalues(&original).map(|lambda| lambda.max(min_eigenvalue));
+            assert_matrix_eq!(
+                symmetric_eigenvalues(&projected),
+                expected_eigenvalues,
+                comp = abs,
+                tol = 1e-12
+            );
+        }
+    }
+}
+
+#[test]
+fn spd_projected_contraction_clamps_tangent() {
+    let gradient = Vector2::zeros();
+    let a = Vector2::new(2.0, 3.0);
+    let b = Vector2::new(-1.0, 5.0);
+
+    // The projected tangent is diag(1, 0)
+    let projected = SpdProjectedContraction::new(IndefiniteOperator, 0.0);
+    assert_eq!(
+        EllipticContraction::<f64, U2>::symmetry(&projected),
+        Symmetry::Symmetric
+    );
+    let c = projected.contract(&gradient, &a, &b, &());
+    assert_matrix_eq!(c, Matrix1::new(-2.0), comp = abs, tol = 1e-14);
+
+    // Positive semi-definite tangents are unchanged
+    let laplace = SpdProjectedContraction::new(LaplaceOperator, 0.0);
+    let c = laplace.contract(&gradient, &a, &b, &());
+    assert_matrix_eq!(c, Matrix1::new(a.dot(&b)), comp = abs, tol = 1e-14);
+}
+
+#[test]
+fn spd_projected_contraction_gives_psd_element_matrices() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let qtable =
+        UniformQuadratureTable::from_quadrature_and_uniform_data(quadrature::tensor::quadrilateral_gauss(2), ());
+    let u = DVector::zeros(mesh.vertices().len());
+    let projected_operator = SpdProjectedContraction::new(IndefiniteOperator, 0.0);
+    let projected_laplace = SpdProjectedContraction::new(LaplaceOperator, 0.0);
+    let projected_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&projected_operator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&LaplaceOperator)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let projected_laplace_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_operator(&projected_laplace)
+        .with_finite_element_space(&mesh)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+
+    for element_index in 0..mesh.connectivity().len() {
+        let projected = projected_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        assert_min_eigenvalue_at_least(&projected, 0.0);
+
+        let laplace = laplace_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        let projected_laplace = projected_laplace_assembler
+            .assemble_element_matrix(element_index)
+            .unwrap();
+        assert_matrix_eq!(projected_laplace, laplace, comp = abs, tol = 1e-14);
+    }
+}
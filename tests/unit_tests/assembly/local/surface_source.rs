@@ -0,0 +1,79 @@
+use fenris::assembly::compute_consistent_boundary_loads;
+use fenris::assembly::global::VectorAssembler;
+use fenris::assembly::local::{ElementConnectivityAssembler, SurfaceSourceAssembler, UniformQuadratureTable};
+use fenris::mesh::procedural::{create_rectangular_uniform_hex_mesh, create_unit_square_uniform_quad_mesh_2d};
+use fenris::mesh::{HexMesh, QuadMesh2d};
+use fenris::nalgebra::{Point2, Point3, Vector1, Vector2, Vector3};
+use fenris::quadrature;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+fn is_on_top_face(x: &Point3<f64>) -> bool {
+    (x.z - 1.0).abs() < 1e-12
+}
+
+#[test]
+fn surface_source_constant_pressure_on_hex_face_integrates_to_pressure_times_area() {
+    // The box [0, 2] x [0, 3] x [0, 1], so that the top face has area 6
+    let mesh: HexMesh<f64> = create_rectangular_uniform_hex_mesh(1.0, 2, 3, 1, 2);
+    let patches = mesh.extract_boundary_patches(&[("top", &is_on_top_face)]);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let pressure = 3.0;
+    let assembler =
+        SurfaceSourceAssembler::new(patches.surface_mesh(), &qtable, |_: &Point3<f64>, n: &Vector3<f64>| {
+            n * (-pressure)
+        })
+        .map_element_nodes(mesh.vertices().len(), |node| patches.boundary_to_volume()[node]);
+    let loads = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    assert_eq!(loads.len(), 3 * mesh.vertices().len());
+
+    let total_force: Vector3<f64> = loads
+        .as_slice()
+        .chunks_exact(3)
+        .map(Vector3::from_column_slice)
+        .sum();
+    assert_matrix_eq!(
+        total_force,
+        Vector3::new(0.0, 0.0, -6.0 * pressure),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // The outward normal of the top face is constant, so the loads must coincide with the
+    // consistent loads of the corresponding traction
+    let expected_loads = compute_consistent_boundary_loads(
+        &mesh,
+        is_on_top_face,
+        |_| Vector3::new(0.0, 0.0, -pressure),
+        quadrature::tensor::quadrilateral_gauss(2),
+    );
+    assert_matrix_eq!(loads, expected_loads, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn surface_source_satisfies_divergence_theorem() {
+    // By the divergence theorem, the integral of x . n over the boundary equals d |Ω|,
+    // which requires consistently outward normals and correct area elements
+    let mesh: HexMesh<f64> = create_rectangular_uniform_hex_mesh(1.0, 2, 3, 1, 2);
+    let surface_mesh = mesh.extract_boundary_face_mesh();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let assembler = SurfaceSourceAssembler::new(&surface_mesh, &qtable, |x: &Point3<f64>, n: &Vector3<f64>| {
+        Vector1::new(x.coords.dot(n))
+    });
+    let loads = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    assert_scalar_eq!(loads.sum(), 3.0 * 6.0, comp = abs, tol = 1e-12);
+
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let surface_mesh = mesh.extract_boundary_face_mesh();
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::univariate::gauss(2));
+    let assembler = SurfaceSourceAssembler::new(&surface_mesh, &qtable, |x: &Point2<f64>, n: &Vector2<f64>| {
+        Vector1::new(x.coords.dot(n))
+    });
+    let loads = VectorAssembler::default()
+        .assemble_vector(&assembler)
+        .unwrap();
+    assert_scalar_eq!(loads.sum(), 2.0, comp = abs, tol = 1e-12);
+}
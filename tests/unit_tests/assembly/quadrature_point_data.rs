@@ -0,0 +1,160 @@
+use fenris::assembly::local::{GeneralQuadratureTable, QuadratureTable, UniformQuadratureTable};
+use fenris::assembly::QuadraturePointData;
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{point, Point2, U2};
+use fenris::quadrature;
+use fenris::util::NestedVec;
+use matrixcompare::assert_matrix_eq;
+
+/// A quadrature table with a different number of points in each element.
+fn non_uniform_table() -> GeneralQuadratureTable<f64, U2> {
+    let points = vec![
+        vec![point![0.0, 0.0]],
+        vec![point![-0.5, 0.0], point![0.0, 0.5], point![0.5, 0.0]],
+        vec![],
+        vec![point![0.25, 0.25], point![-0.25, -0.25]],
+    ];
+    let weights = vec![vec![4.0], vec![1.0, 2.0, 1.0], vec![], vec![2.0, 2.0]];
+    GeneralQuadratureTable::from_points_and_weights(NestedVec::from(&points), NestedVec::from(&weights))
+}
+
+#[test]
+fn quadrature_point_data_from_non_uniform_table() {
+    let table = non_uniform_table();
+    let data = QuadraturePointData::from_quadrature_table_and_value(&table, 4, 3);
+
+    assert_eq!(data.num_elements(), 4);
+    assert_eq!(data.len(), 6);
+    assert!(!data.is_empty());
+    assert_eq!(data.offsets(), &[0, 1, 4, 4, 6]);
+    assert_eq!(data.element_data(0), &[3]);
+    assert_eq!(data.element_data(1), &[3, 3, 3]);
+    assert_eq!(data.element_data(2), &[] as &[i32]);
+    assert_eq!(data.element_data(3), &[3, 3]);
+    let element_sizes: Vec<_> = data.iter().map(|element_data| element_data.len()).collect();
+    assert_eq!(element_sizes, vec![1, 3, 0, 2]);
+
+    data.check_consistency_with_table(&table).unwrap();
+
+    // A uniform table with a different number of points is not consistent with the data
+    let uniform_table = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss::<f64>(1));
+    assert_eq!(uniform_table.element_quadrature_size(0), 1);
+    assert!(data.check_consistency_with_table(&uniform_table).is_err());
+    let uniform_data = QuadraturePointData::from_uniform_size_and_value(4, 1, 0.0);
+    uniform_data
+        .check_consistency_with_table(&uniform_table)
+        .unwrap();
+
+    let empty = QuadraturePointData::<f64>::default();
+    assert_eq!(empty.num_elements(), 0);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn quadrature_point_data_mutation_through_element_views() {
+    let mut data = QuadraturePointData::from_element_sizes_and_fn([1, 3, 0, 2], |element_index, point_index| {
+        10 * element_index + point_index
+    });
+    assert_eq!(data.as_slice(), &[0, 10, 11, 12, 30, 31]);
+
+    data.element_data_mut(1)[2] = 100;
+    data.element_data_mut(3).fill(7);
+    assert_eq!(data.as_slice(), &[0, 10, 11, 100, 7, 7]);
+
+    for (element_index, element_data) in data.iter_mut().enumerate() {
+        for value in element_data {
+            *value += element_index;
+        }
+    }
+    assert_eq!(data.element_data(0), &[0]);
+    assert_eq!(data.element_data(1), &[11, 12, 101]);
+    assert_eq!(data.element_data(3), &[10, 10]);
+
+    data.as_mut_slice()[0] = 5;
+    assert_eq!(data.element_data(0), &[5]);
+
+    let doubled = data.map_values(|value| 2 * value);
+    assert_eq!(doubled.offsets(), data.offsets());
+    assert_eq!(doubled.as_slice(), &[10, 22, 24, 202, 20, 20]);
+}
+
+#[test]
+fn quadrature_point_data_map_evaluates_at_physical_points() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let table = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+    let data = QuadraturePointData::map(&mesh, &table, |element_index, x| (element_index, *x));
+    data.check_consistency_with_table(&table).unwrap();
+    assert_eq!(data.num_elements(), 9);
+    assert_eq!(data.len(), 36);
+
+    for (element_index, element_data) in data.iter().enumerate() {
+        let element_vertices: Vec<Point2<f64>> = mesh.connectivity()[element_index]
+            .0
+            .iter()
+            .map(|&v| mesh.vertices()[v])
+            .collect();
+        let element_centroid = element_vertices
+            .iter()
+            .fold(Point2::origin(), |sum, v| sum + v.coords / 4.0);
+
+        // The tensor Gauss points are symmetric about the element centroid
+        let mut centroid = Point2::origin();
+        for (data_element_index, x) in element_data {
+            assert_eq!(*data_element_index, element_index);
+            centroid += x.coords / 4.0;
+        }
+        assert_matrix_eq!(centroid.coords, element_centroid.coords, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn quadrature_point_data_serde_round_trip() {
+    let data = QuadraturePointData::from_element_sizes_and_fn([2, 0, 3], |element_index, point_index| {
+        vec![element_index as f64, point_index as f64]
+    });
+    let json = serde_json::to_string(&data).unwrap();
+    let deserialized: QuadraturePointData<Vec<f64>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, data);
+    assert_eq!(deserialized.element_data(2)[1], vec![2.0, 1.0]);
+}
+
+#[test]
+fn quadrature_point_data_rejects_invalid_offsets() {
+    let valid = QuadraturePointData::from_data_and_offsets(vec![1, 2, 3], vec![0, 2, 2, 3]).unwrap();
+    assert_eq!(valid.element_data(0), &[1, 2]);
+    assert_eq!(valid.element_data(2), &[3]);
+
+    // Empty offsets, offsets not starting at zero, not ending at the number of entries
+    // and decreasing offsets
+    for offsets in [vec![], vec![1, 2, 3], vec![0, 2], vec![0, 2, 4], vec![0, 2, 1, 3]] {
+        assert!(QuadraturePointData::from_data_and_offsets(vec![1, 2, 3], offsets.clone()).is_err());
+        let json = format!(r#"{{"data":[1,2,3],"offsets":{:?}}}"#, offsets);
+        assert!(serde_json::from_str::<QuadraturePointData<i32>>(&json).is_err());
+    }
+    let json = r#"{"data":[1,2,3],"offsets":[0,2,2,3]}"#;
+    assert_eq!(serde_json::from_str::<QuadraturePointData<i32>>(json).unwrap(), valid);
+}
+
+#[test]
+fn general_quadrature_table_with_quadrature_point_data() {
+    let table = non_uniform_table();
+    let data = QuadraturePointData::from_quadrature_table_and_value(&table, 4, 0.0)
+        .map_values(|_| 2.0)
+        .map_values(|&x| x + 1.0);
+    let table = table.with_quadrature_point_data(&data);
+
+    let mut table = table.with_quadrature_point_data(&data);
+    assert_eq!(table.data(), &data);
+
+    let mut element_data = vec![0.0; 3];
+    table.populate_element_data(1, &mut element_data);
+    assert_eq!(element_data, vec![3.0; 3]);
+
+    // Per-point data can be updated in place, with the same layout as the quadrature points
+    table.data_mut()[data.offsets()[1] + 2] = 5.0;
+    table.populate_element_data(1, &mut element_data);
+    assert_eq!(element_data, vec![3.0, 3.0, 5.0]);
+    table.populate_element_data(3, &mut element_data[..2]);
+    assert_eq!(element_data[..2], [3.0, 3.0]);
+}
@@ -1,13 +1,13 @@
 use crate::export_mesh_vtk;
 use fenris::connectivity::Tet4Connectivity;
 use fenris::element::{
-    FixedNodesReferenceFiniteElement, Hex20Element, Hex27Element, Quad4d2Element, Quad9d2Element, Tet4Element,
-    Tri3d2Element, Tri6d2Element,
+    FiniteElement, FixedNodesReferenceFiniteElement, Hex20Element, Hex27Element, Hex8Element, Quad4d2Element,
+    Quad9d2Element, ReferenceShape, Tet4Element, Tri10d2Element, Tri3d2Element, Tri6d2Element,
 };
 use fenris::mesh::Tet4Mesh;
 use fenris_traits::Real;
 use itertools::{izip, Itertools};
-use nalgebra::{Point2, Point3, Vector3};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
 use num::clamp;
 use numeric_literals::replace_float_literals;
 use proptest::array::uniform4;
@@ -16,11 +16,16 @@ use proptest::strategy::ValueTree;
 use proptest::test_runner::TestRunner;
 use util::assert_approx_matrix_eq;
 
+mod closest_point;
 mod hexahedron;
+mod prism;
+mod pyramid;
 mod quadrilateral;
+mod reference_domain;
 mod segment;
 mod tetrahedron;
 mod triangle;
+mod validity;
 
 fn point_in_tri_ref_domain() -> impl Strategy<Value = Point2<f64>> {
     // Generate points x, y in [-1, 1]^2 such that
@@ -42,6 +47,21 @@ fn point_in_hex_ref_domain() -> impl Strategy<Value = Point3<f64>> {
     [r.clone(), r.clone(), r].prop_map(|[x, y, z]| Point3::new(x, y, z))
 }
 
+fn point_in_prism_ref_domain() -> impl Strategy<Value = Point3<f64>> {
+    // Generate points in the reference triangle times [-1, 1]
+    (point_in_tri_ref_domain(), -1.0..=1.0).prop_map(|(p, z)| Point3::new(p.x, p.y, z))
+}
+
+fn point_in_pyramid_ref_domain_away_from_apex() -> impl Strategy<Value = Point3<f64>> {
+    // Generate points in the reference pyramid with zeta <= 0.9 by scaling points in the square
+    // [-1, 1]^2 to the cross section at height zeta
+    let r = -1.0..=1.0;
+    ([r.clone(), r], -1.0..=0.9).prop_map(|([x, y], z)| {
+        let s = (1.0 - z) / 2.0;
+        Point3::new(s * x, s * y, z)
+    })
+}
+
 fn point_in_tet_ref_domain() -> impl Strategy<Value = Point3<f64>> {
     uniform4(0.0..=1.0f64)
         .prop_map(|mut barycentric_coords| {
@@ -81,11 +101,8 @@ fn point_in_tet_ref_domain() -> impl Strategy<Value = Point3<f64>> {
         })
 }
 
-// This is copied from fenris source in order to prevent having this in the public API
-#[replace_float_literals(T::from_f64(literal).unwrap())]
 fn is_likely_in_tet_ref_interior<T: Real>(xi: &Point3<T>) -> bool {
-    let eps = 4.0 * T::default_epsilon();
-    xi.x >= -1.0 - eps && xi.y >= -1.0 - eps && xi.z >= -1.0 - eps && xi.x + xi.y + xi.z <= -1.0 + eps
+    ReferenceShape::Tetrahedron.contains(xi, T::from_f64(4.0).unwrap() * T::default_epsilon())
 }
 
 #[replace_float_literals(T::from_f64(literal).unwrap())]
@@ -173,6 +190,11 @@ partition_of_unity_test!(
     point_in_tri_ref_domain(),
     Tri6d2Element::reference()
 );
+partition_of_unity_test!(
+    tri10d2_partition_of_unity,
+    point_in_tri_ref_domain(),
+    Tri10d2Element::reference()
+);
 partition_of_unity_test!(
     quad4_partition_of_unity,
     point_in_quad_ref_domain(),
@@ -206,6 +228,11 @@ partition_of_unity_gradient_test!(
     point_in_tri_ref_domain(),
     Tri6d2Element::reference()
 );
+partition_of_unity_gradient_test!(
+    tri10d2_partition_of_unity_gradient,
+    point_in_tri_ref_domain(),
+    Tri10d2Element::reference()
+);
 partition_of_unity_gradient_test!(
     quad4_partition_of_unity_gradient,
     point_in_quad_ref_domain(),
@@ -229,10 +256,68 @@ partition_of_unity_gradient_test!(
     Hex20Element::reference()
 );
 
-// TODO: This is copied from fenris code base. Don't want to make it part of public API,
-// but it's unfortunate to duplicate it
-#[replace_float_literals(T::from_f64(literal).unwrap())]
+/// Smooth, non-affine perturbations of the reference elements, so that the Jacobians vary
+/// over the element.
+fn distort_point2(p: &Point2<f64>) -> Point2<f64> {
+    p + 0.1 * Vector2::new(p.x * p.y + 0.5, p.x * p.x - 0.2 * p.y)
+}
+
+fn distort_point3(p: &Point3<f64>) -> Point3<f64> {
+    p + 0.1 * Vector3::new(p.y * p.z + 0.5, p.x * p.x - 0.2 * p.z, p.x * p.y + 0.3 * p.z)
+}
+
+macro_rules! reference_jacobian_from_basis_gradients_test {
+    ($test_name:ident, $ref_domain_strategy:expr, $element:expr) => {
+        proptest! {
+            #[test]
+            fn $test_name(xi in $ref_domain_strategy) {
+                let element = $element;
+                let gradients = element.gradients(&xi);
+                let gradients = gradients.columns(0, gradients.ncols());
+                let j = element.reference_jacobian_from_basis_gradients(&xi, gradients);
+                assert_approx_matrix_eq!(j, element.reference_jacobian(&xi), abstol=1e-12);
+            }
+        }
+    };
+}
+
+reference_jacobian_from_basis_gradients_test!(
+    tri3d2_reference_jacobian_from_basis_gradients,
+    point_in_tri_ref_domain(),
+    Tri3d2Element::from_vertices(
+        Tri3d2Element::<f64>::reference()
+            .vertices()
+            .map(|v| distort_point2(&v))
+    )
+);
+reference_jacobian_from_basis_gradients_test!(
+    quad4_reference_jacobian_from_basis_gradients,
+    point_in_quad_ref_domain(),
+    Quad4d2Element::from_vertices(
+        Quad4d2Element::<f64>::reference()
+            .vertices()
+            .map(|v| distort_point2(&v))
+    )
+);
+reference_jacobian_from_basis_gradients_test!(
+    tet4_reference_jacobian_from_basis_gradients,
+    point_in_tet_ref_domain(),
+    Tet4Element::from_vertices(
+        Tet4Element::<f64>::reference()
+            .vertices()
+            .map(|v| distort_point3(&v))
+    )
+);
+reference_jacobian_from_basis_gradients_test!(
+    hex8_reference_jacobian_from_basis_gradients,
+    point_in_hex_ref_domain(),
+    Hex8Element::from_vertices(
+        Hex8Element::<f64>::reference()
+            .vertices()
+            .map(|v| distort_point3(&v))
+    )
+);
+
 fn is_likely_in_tri_ref_interior<T: Real>(xi: &Point2<T>) -> bool {
-    let eps = 4.0 * T::default_epsilon();
-    xi.x >= -1.0 - eps && xi.y >= -1.0 - eps && xi.x + xi.y <= eps
+    ReferenceShape::Triangle.contains(xi, T::from_f64(4.0).unwrap() * T::default_epsilon())
 }
@@ -0,0 +1,209 @@
+use fenris::element::{
+    closest_point_in_element, ClosestPoint, ClosestPointInElement, ClosestPointSettings, FiniteElement,
+    FixedNodesReferenceFiniteElement, Prism6Element, Pyramid5Element, ReferenceDomain, ReferenceFiniteElement,
+    ReferenceShape, Tet4Element, Tri3d2Element, Tri6d2Element,
+};
+use itertools::Itertools;
+use matrixcompare::assert_matrix_eq;
+use nalgebra::{distance, point, Dyn, Matrix2, Matrix2x6, Matrix3, MatrixViewMut, Point2, Point3, U2};
+use proptest::array::{uniform3, uniform4};
+use proptest::prelude::*;
+
+use crate::unit_tests::element::{point_in_tet_ref_domain, point_in_tri_ref_domain};
+
+/// A quadratic triangle whose geometry is given by its quadratic basis functions,
+/// so that the edges may be curved.
+struct IsoparametricTri6 {
+    vertices: [Point2<f64>; 6],
+}
+
+impl IsoparametricTri6 {
+    fn vertex_matrix(&self) -> Matrix2x6<f64> {
+        Matrix2x6::from_columns(&self.vertices.map(|v| v.coords))
+    }
+}
+
+impl ReferenceFiniteElement<f64> for IsoparametricTri6 {
+    type ReferenceDim = U2;
+
+    fn num_nodes(&self) -> usize {
+        6
+    }
+
+    fn populate_basis(&self, basis_values: &mut [f64], xi: &Point2<f64>) {
+        basis_values.copy_from_slice(Tri6d2Element::reference().evaluate_basis(xi).as_slice());
+    }
+
+    fn populate_basis_gradients(&self, mut basis_gradients: MatrixViewMut<f64, U2, Dyn>, xi: &Point2<f64>) {
+        basis_gradients.copy_from(&Tri6d2Element::reference().gradients(xi));
+    }
+}
+
+impl FiniteElement<f64> for IsoparametricTri6 {
+    type GeometryDim = U2;
+
+    fn reference_jacobian(&self, xi: &Point2<f64>) -> Matrix2<f64> {
+        self.vertex_matrix() * Tri6d2Element::reference().gradients(xi).transpose()
+    }
+
+    fn map_reference_coords(&self, xi: &Point2<f64>) -> Point2<f64> {
+        Point2::from(self.vertex_matrix() * Tri6d2Element::reference().evaluate_basis(xi).transpose())
+    }
+
+    fn diameter(&self) -> f64 {
+        self.vertices
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(0.0, f64::max)
+    }
+}
+
+impl ReferenceDomain<f64> for IsoparametricTri6 {
+    fn reference_shape(&self) -> ReferenceShape {
+        ReferenceShape::Triangle
+    }
+}
+
+/// A Tri6 element on the unit right triangle whose edge opposite to the origin bulges outwards,
+/// and whose bottom edge bulges downwards.
+fn curved_tri6() -> IsoparametricTri6 {
+    IsoparametricTri6 {
+        vertices: [
+            point![0.0, 0.0],
+            point![1.0, 0.0],
+            point![0.0, 1.0],
+            point![0.5, -0.15],
+            point![0.7, 0.7],
+            point![0.0, 0.5],
+        ],
+    }
+}
+
+#[test]
+fn curved_tri6_reference_coords_are_recovered_by_closest_point() {
+    let element = curved_tri6();
+    let settings = ClosestPointSettings::default();
+    let n = 10;
+    for i in 0..=n {
+        for j in 0..=(n - i) {
+            let xi = point![-1.0 + 2.0 * i as f64 / n as f64, -1.0 + 2.0 * j as f64 / n as f64];
+            let x = element.map_reference_coords(&xi);
+            match closest_point_in_element(&element, &x, &settings) {
+                ClosestPoint::InElement(xi_computed) => {
+                    assert_matrix_eq!(xi_computed.coords, xi.coords, comp = abs, tol = 1e-10)
+                }
+                ClosestPoint::ClosestPoint(_) => panic!("Point {} must be inside the element", x),
+            }
+        }
+    }
+}
+
+#[test]
+fn curved_tri6_closest_point_to_exterior_point_is_on_boundary() {
+    let element = curved_tri6();
+    let settings = ClosestPointSettings::default();
+
+    // Densely sample the boundary of the element to obtain (approximate) reference distances
+    let boundary_samples: Vec<_> = (0..=3000)
+        .flat_map(|k| {
+            let t = -1.0 + 2.0 * k as f64 / 3000.0;
+            [point![t, -1.0], point![-1.0, t], point![t, -t]]
+        })
+        .map(|xi| element.map_reference_coords(&xi))
+        .collect();
+
+    for p in [
+        point![1.0, 1.0],
+        point![0.5, -0.5],
+        point![-0.3, 0.4],
+        point![2.0, -0.2],
+    ] {
+        let closest_point = closest_point_in_element(&element, &p, &settings);
+        let xi = match closest_point {
+            ClosestPoint::ClosestPoint(xi) => xi,
+            ClosestPoint::InElement(_) => panic!("Point {} must be outside the element", p),
+        };
+        assert!(element.reference_domain_contains(&xi, 1e-12));
+        let dist = distance(&element.map_reference_coords(&xi), &p);
+        let sampled_dist = boundary_samples
+            .iter()
+            .map(|x| distance(x, &p))
+            .fold(f64::INFINITY, f64::min);
+        assert!(dist <= sampled_dist + 1e-9);
+        assert!(dist >= sampled_dist - 1e-3);
+    }
+}
+
+#[test]
+fn closest_point_in_reference_prism_and_pyramid_is_projection() {
+    let prism = Prism6Element::reference();
+    let pyramid = Pyramid5Element::reference();
+    let points = [
+        point![0.1, -0.2, 0.3],
+        point![-0.9, -0.9, 0.99],
+        point![2.0, 2.0, 0.5],
+        point![-3.0, 0.5, -2.0],
+        point![0.2, 0.1, 3.0],
+    ];
+    for p in points {
+        for (closest_point, shape) in [
+            (prism.closest_point(&p), ReferenceShape::Prism),
+            (pyramid.closest_point(&p), ReferenceShape::Pyramid),
+        ] {
+            // The reference elements map reference coordinates to themselves
+            assert_eq!(
+                matches!(closest_point, ClosestPoint::InElement(_)),
+                shape.contains(&p, 0.0)
+            );
+            assert_matrix_eq!(
+                closest_point.point().coords,
+                shape.project(&p).coords,
+                comp = abs,
+                tol = 1e-10
+            );
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn closest_point_in_tri3_matches_barycentric_solution(
+        vertices in uniform3([-2.0..2.0f64, -2.0..2.0f64]),
+        xi in point_in_tri_ref_domain()
+    ) {
+        let [a, b, c] = vertices.map(|[x, y]| point![x, y]);
+        let jacobian = Matrix2::from_columns(&[b - a, c - a]);
+        prop_assume!(jacobian.determinant().abs() > 0.1);
+        let element = Tri3d2Element::from_vertices([a, b, c]);
+        let x = element.map_reference_coords(&xi);
+
+        // With barycentric coordinates (1 - l1 - l2, l1, l2), we have x = a + J [l1, l2]^T,
+        // and the reference coordinates are given by xi = 2 [l1, l2]^T - 1
+        let l = jacobian.try_inverse().unwrap() * (x - a);
+        let xi_expected = l.map(|l_i| 2.0 * l_i - 1.0);
+
+        let closest_point = closest_point_in_element(&element, &x, &ClosestPointSettings::default());
+        prop_assert!(matches!(closest_point, ClosestPoint::InElement(_)));
+        assert_matrix_eq!(closest_point.point().coords, xi_expected, comp = abs, tol = 1e-10);
+    }
+
+    #[test]
+    fn closest_point_in_tet4_matches_barycentric_solution(
+        vertices in uniform4(uniform3(-2.0..2.0f64)),
+        xi in point_in_tet_ref_domain()
+    ) {
+        let [a, b, c, d] = vertices.map(Point3::from);
+        let jacobian = Matrix3::from_columns(&[b - a, c - a, d - a]);
+        prop_assume!(jacobian.determinant().abs() > 0.1);
+        let element = Tet4Element::from_vertices([a, b, c, d]);
+        let x = element.map_reference_coords(&xi);
+
+        let l = jacobian.try_inverse().unwrap() * (x - a);
+        let xi_expected = l.map(|l_i| 2.0 * l_i - 1.0);
+
+        let closest_point = closest_point_in_element(&element, &x, &ClosestPointSettings::default());
+        prop_assert!(matches!(closest_point, ClosestPoint::InElement(_)));
+        assert_matrix_eq!(closest_point.point().coords, xi_expected, comp = abs, tol = 1e-10);
+    }
+}
@@ -1,15 +1,23 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::assembly::DirichletBcs;
 use fenris::element::{FixedNodesReferenceFiniteElement, Hex20Element, Hex27Element, Hex8Element};
 use fenris::error::estimate_element_L2_error;
 
 use fenris::integrate::IntegrationWorkspace;
+use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::mesh::Hex20Mesh;
 
-use fenris::nalgebra::DVector;
+use fenris::nalgebra::{DMatrix, DVector};
 use fenris::quadrature;
 
 use fenris_optimize::calculus::{approximate_jacobian, VectorFunctionBuilder};
+use fenris_solid::materials::{LameParameters, LinearElasticMaterial};
+use fenris_solid::model::{LoadStepSettings, QuasiStaticModel};
 
 use matrixcompare::assert_scalar_eq;
-use nalgebra::{DVectorView, DimName, Dyn, MatrixView, OMatrix, OPoint, Point3, Vector1, U1, U20, U27, U3, U8};
+use nalgebra::{
+    matrix, DVectorView, DimName, Dyn, MatrixView, OMatrix, OPoint, Point3, Vector1, Vector3, U1, U20, U27, U3, U8,
+};
 
 use crate::unit_tests::element::point_in_hex_ref_domain;
 use proptest::prelude::*;
@@ -137,6 +145,60 @@ fn hex20_quadratic_function_exact_error() {
     assert_scalar_eq!(error, 0.0, comp = abs, tol = 1e-12);
 }
 
+#[test]
+fn hex20_linear_elasticity_passes_patch_test() {
+    // A 2x2x2 patch of Hex20 elements in which the only interior vertex has been moved,
+    // so that none of the elements is a parallelepiped
+    let mut hex_mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    let center = hex_mesh
+        .vertices()
+        .iter()
+        .position(|v| v == &Point3::new(0.5, 0.5, 0.5))
+        .unwrap();
+    hex_mesh.vertices_mut()[center] = Point3::new(0.4, 0.55, 0.6);
+    let mesh = Hex20Mesh::from(&hex_mesh);
+
+    let a = matrix![0.02, -0.01, 0.03;
+                    0.01, 0.04, -0.02;
+                    -0.03, 0.02, 0.01];
+    let b = Vector3::new(0.1, -0.2, 0.3);
+    let u_exact = |x: &Point3<f64>| a * x.coords + b;
+
+    let bcs = DirichletBcs::from_mesh_boundary(&mesh, 3, &[0, 1, 2], |_| true, |x, i| u_exact(x)[i]);
+    let qtable = UniformQuadratureTable::<f64, U3, _>::from_quadrature_and_uniform_data(
+        quadrature::tensor::hexahedron_gauss(3),
+        LameParameters {
+            mu: 4000.0,
+            lambda: 6000.0,
+        },
+    );
+    let model = QuasiStaticModel::new(
+        &mesh,
+        &LinearElasticMaterial,
+        &qtable,
+        bcs,
+        DVector::zeros(3 * mesh.vertices().len()),
+    )
+    .unwrap();
+    let mut u = DVector::zeros(model.num_dofs());
+    model
+        .solve_load_step(&mut u, &LoadStepSettings::default(), |matrix, rhs| {
+            DMatrix::from(matrix)
+                .lu()
+                .solve(rhs)
+                .ok_or_else(|| eyre::eyre!("matrix is singular"))
+        })
+        .unwrap();
+
+    // A linear displacement field must be reproduced exactly, including at the interior nodes
+    let num_interior_nodes = mesh.vertices().len() - mesh.find_boundary_vertices().len();
+    assert_eq!(num_interior_nodes, 7);
+    for (node, x) in mesh.vertices().iter().enumerate() {
+        let u_node = u.fixed_rows::<3>(3 * node);
+        assert_scalar_eq!((u_node - u_exact(x)).amax(), 0.0, comp = abs, tol = 1e-12);
+    }
+}
+
 proptest! {
     #[test]
     fn hex8_partition_of_unity(xi in point_in_hex_ref_domain()) {
@@ -0,0 +1,178 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::ElementEllipticAssemblerBuilder;
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::{Connectivity, MixedFaceConnectivity, Prism15Connectivity};
+use fenris::element::{FiniteElement, FixedNodesReferenceFiniteElement, Prism15Element, Prism6Element};
+use fenris::mesh::Prism15Mesh;
+use fenris::quadrature;
+use fenris::quadrature::CanonicalStiffnessQuadrature;
+use fenris_optimize::calculus::{approximate_jacobian, VectorFunctionBuilder};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use nalgebra::{DMatrix, DVector, DVectorView, DimName, Dyn, OMatrix, OPoint, Point3, Vector3, U1, U15, U3, U6};
+
+use crate::unit_tests::element::point_in_prism_ref_domain;
+use proptest::prelude::*;
+use util::assert_approx_matrix_eq;
+
+#[test]
+fn prism6_lagrange_property() {
+    let element = Prism6Element::reference();
+
+    for (i, xi) in element.vertices().into_iter().enumerate() {
+        let phi = element.evaluate_basis(&xi);
+
+        let mut expected = OMatrix::<f64, U1, U6>::zeros();
+        expected[i] = 1.0;
+
+        assert_approx_matrix_eq!(phi, expected, abstol = 1e-12);
+    }
+}
+
+/// A prism with right-angled triangle base with legs 2 and 3, and height 4
+fn prism6_with_volume_12() -> Prism6Element<f64> {
+    Prism6Element::from_vertices([
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(3.0, 1.0, 1.0),
+        Point3::new(1.0, 4.0, 1.0),
+        Point3::new(1.0, 1.0, 5.0),
+        Point3::new(3.0, 1.0, 5.0),
+        Point3::new(1.0, 4.0, 5.0),
+    ])
+}
+
+#[test]
+fn prism6_volume() {
+    let element = prism6_with_volume_12();
+    let (weights, points) = quadrature::total_order::prism::<f64>(1).unwrap();
+    let volume: f64 = weights
+        .iter()
+        .zip(&points)
+        .map(|(w, xi)| w * element.reference_jacobian(xi).determinant())
+        .sum();
+    assert_scalar_eq!(volume, 12.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn prism15_lagrange_property() {
+    let element = Prism15Element::reference();
+
+    for (i, xi) in element.vertices().into_iter().enumerate() {
+        let phi = element.evaluate_basis(&xi);
+
+        let mut expected = OMatrix::<f64, U1, U15>::zeros();
+        expected[i] = 1.0;
+
+        assert_approx_matrix_eq!(phi, expected, abstol = 1e-12);
+    }
+}
+
+#[test]
+fn prism15_stiffness_of_linear_field_is_exact() {
+    let element = Prism15Element::from(&prism6_with_volume_12());
+    let connectivity = Prism15Connectivity([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+    let mesh = Prism15Mesh::from_vertices_and_connectivity(element.vertices().to_vec(), vec![connectivity]);
+
+    let u = DVector::zeros(15);
+    let qtable = mesh.canonical_stiffness_quadrature();
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let stiffness = DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap());
+
+    // For u(x) = a^T x + b, we have u^T K u = int |grad u|^2 dx = |a|^2 * volume
+    let a = Vector3::new(1.0, -2.0, 3.0);
+    let u_linear = DVector::from_iterator(15, mesh.vertices().iter().map(|x| a.dot(&x.coords) + 4.0));
+    let energy = u_linear.dot(&(&stiffness * &u_linear));
+    assert_scalar_eq!(energy, a.norm_squared() * 12.0, comp = abs, tol = 1e-10);
+
+    // Constant functions are in the null space of the stiffness matrix
+    let constant = DVector::repeat(15, 1.0);
+    assert_matrix_eq!(&stiffness * constant, DVector::zeros(15), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn prism15_boundary_faces_are_tri6_and_quad8() {
+    // Two reference prisms stacked on top of each other, sharing a triangular face
+    let reference = Prism15Element::<f64>::reference();
+    let lifted = |i: usize| reference.vertices()[i] + Vector3::new(0.0, 0.0, 2.0);
+    let mut vertices = reference.vertices().to_vec();
+    vertices.extend([3, 4, 5, 9, 10, 11, 12, 13, 14].iter().map(|&i| lifted(i)));
+    let connectivity = vec![
+        Prism15Connectivity([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]),
+        Prism15Connectivity([3, 4, 5, 15, 16, 17, 9, 10, 11, 18, 19, 20, 21, 22, 23]),
+    ];
+    let mesh = Prism15Mesh::from_vertices_and_connectivity(vertices, connectivity);
+
+    let boundary_faces = mesh.find_boundary_faces();
+    assert_eq!(boundary_faces.len(), 8);
+    let num_triangles = boundary_faces
+        .iter()
+        .filter(|(face, _, _)| matches!(face, MixedFaceConnectivity::Tri(_)))
+        .count();
+    assert_eq!(num_triangles, 2);
+    assert!(boundary_faces
+        .iter()
+        .all(|(face, _, _)| face.vertex_indices().iter().all(|&v| v < 24)));
+    assert_eq!(mesh.find_boundary_vertices(), (0..24).collect::<Vec<_>>());
+}
+
+proptest! {
+    #[test]
+    fn prism6_partition_of_unity(xi in point_in_prism_ref_domain()) {
+        let element = Prism6Element::reference();
+        let phi = element.evaluate_basis(&xi);
+        let phi_sum: f64 = phi.sum();
+        prop_assert!( (phi_sum - 1.0f64).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn prism6_reference_element_gradient_is_derivative_of_transform(
+        xi in point_in_prism_ref_domain()
+    ) {
+        let prism = Prism6Element::reference();
+        // Finite difference parameter
+        let h = 1e-6;
+        // Note: Function values are given as row vectors, so we transpose to get the result,
+        // and we must also transpose the end result
+        let f = VectorFunctionBuilder::with_dimension(6).with_function(move |x, xi| {
+            let xi = OPoint::from(xi.generic_view((0, 0), (U3::name(), U1::name())).clone_owned());
+            x.copy_from(&prism.evaluate_basis(&xi).transpose());
+        });
+
+        let grad = prism.gradients(&xi);
+        let xi = DVectorView::<_, Dyn>::from(&xi.coords).clone_owned();
+        let grad_approx = approximate_jacobian(f, &xi, &h).transpose();
+
+        assert_approx_matrix_eq!(grad, &grad_approx, abstol=1e-5);
+    }
+
+    #[test]
+    fn prism15_partition_of_unity(xi in point_in_prism_ref_domain()) {
+        let element = Prism15Element::reference();
+        let phi = element.evaluate_basis(&xi);
+        let phi_sum: f64 = phi.sum();
+        prop_assert!( (phi_sum - 1.0f64).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn prism15_reference_element_gradient_is_derivative_of_basis(
+        xi in point_in_prism_ref_domain()
+    ) {
+        let prism = Prism15Element::reference();
+        // Finite difference parameter
+        let h = 1e-6;
+        let f = VectorFunctionBuilder::with_dimension(15).with_function(move |x, xi| {
+            let xi = OPoint::from(xi.generic_view((0, 0), (U3::name(), U1::name())).clone_owned());
+            x.copy_from(&prism.evaluate_basis(&xi).transpose());
+        });
+
+        let grad = prism.gradients(&xi);
+        let xi = DVectorView::<_, Dyn>::from(&xi.coords).clone_owned();
+        let grad_approx = approximate_jacobian(f, &xi, &h).transpose();
+
+        assert_approx_matrix_eq!(grad, &grad_approx, abstol=1e-5);
+    }
+}
@@ -0,0 +1,172 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler};
+use fenris::connectivity::{
+    Hex8Connectivity, MixedFaceConnectivity, Pyramid5Connectivity, Quad4d3Connectivity, Tet4Connectivity,
+};
+use fenris::element::{FiniteElement, FixedNodesReferenceFiniteElement, Pyramid5Element};
+use fenris::mesh::{HexMesh, Pyramid5Mesh, Tet4Mesh};
+use fenris::quadrature;
+use fenris::quadrature::CanonicalMassQuadrature;
+use fenris_optimize::calculus::{approximate_jacobian, VectorFunctionBuilder};
+use matrixcompare::assert_scalar_eq;
+use nalgebra::{DVectorView, DimName, Dyn, Matrix3, OMatrix, OPoint, Point3, Vector3, U1, U3, U5};
+
+use crate::unit_tests::element::point_in_pyramid_ref_domain_away_from_apex;
+use proptest::prelude::*;
+use util::assert_approx_matrix_eq;
+
+fn volume(element: &Pyramid5Element<f64>) -> f64 {
+    let (weights, points) = quadrature::total_order::pyramid::<f64>(2).unwrap();
+    weights
+        .iter()
+        .zip(&points)
+        .map(|(w, xi)| w * element.reference_jacobian(xi).determinant())
+        .sum()
+}
+
+#[test]
+fn pyramid5_lagrange_property() {
+    let element = Pyramid5Element::reference();
+
+    for (i, xi) in element.vertices().iter().enumerate() {
+        let phi = element.evaluate_basis(xi);
+
+        let mut expected = OMatrix::<f64, U1, U5>::zeros();
+        expected[i] = 1.0;
+
+        assert_approx_matrix_eq!(phi, expected, abstol = 1e-12);
+    }
+}
+
+#[test]
+fn pyramid5_gradients_at_apex_are_finite() {
+    let element = Pyramid5Element::<f64>::reference();
+    let apex = Point3::new(0.0, 0.0, 1.0);
+    let grad = element.gradients(&apex);
+    assert!(grad.iter().all(|g| g.is_finite()));
+    // The gradients still sum to zero, and the Jacobian of the reference element is the identity
+    assert_approx_matrix_eq!(grad.column_sum(), Vector3::<f64>::zeros(), abstol = 1e-14);
+    assert_approx_matrix_eq!(
+        element.reference_jacobian(&apex),
+        Matrix3::<f64>::identity(),
+        abstol = 1e-14
+    );
+}
+
+#[test]
+fn pyramid5_volume() {
+    assert_scalar_eq!(
+        volume(&Pyramid5Element::reference()),
+        8.0 / 3.0,
+        comp = abs,
+        tol = 1e-12
+    );
+
+    // A pyramid with unit square base and unit height, with the apex not above the center of the base
+    let element = Pyramid5Element::from_vertices([
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.2, 0.7, 1.0),
+    ]);
+    assert_scalar_eq!(volume(&element), 1.0 / 3.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn pyramid5_boundary_faces_are_quad4_and_tri3() {
+    let mesh = Pyramid5Mesh::from_vertices_and_connectivity(
+        Pyramid5Element::<f64>::reference().vertices().to_vec(),
+        vec![Pyramid5Connectivity([0, 1, 2, 3, 4])],
+    );
+    let boundary_faces = mesh.find_boundary_faces();
+    assert_eq!(boundary_faces.len(), 5);
+    let num_quads = boundary_faces
+        .iter()
+        .filter(|(face, _, _)| matches!(face, MixedFaceConnectivity::Quad(_)))
+        .count();
+    assert_eq!(num_quads, 1);
+    let (base, _, _) = boundary_faces
+        .iter()
+        .find(|(_, _, local_index)| *local_index == 0)
+        .unwrap();
+    assert_eq!(base, &MixedFaceConnectivity::Quad(Quad4d3Connectivity([0, 3, 2, 1])));
+    assert_eq!(mesh.find_boundary_vertices(), vec![0, 1, 2, 3, 4]);
+}
+
+/// Assembles the mass matrix with the given density.
+macro_rules! mass_matrix {
+    ($mesh:expr, $density:expr) => {{
+        let mesh = &$mesh;
+        let qtable = mesh
+            .canonical_mass_quadrature()
+            .with_uniform_data(Density($density));
+        let assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(mesh)
+            .with_quadrature_table(&qtable);
+        CsrAssembler::default().assemble(&assembler).unwrap()
+    }};
+}
+
+#[test]
+fn mixed_hex_pyramid_tet_mesh_has_correct_total_mass() {
+    // A unit cube with a pyramid of height 1/2 on its top face, and a tetrahedron attached to
+    // the side face [5, 6, 8] of the pyramid. The cells of each type are stored in separate meshes
+    // that share the same vertices, so that their mass matrices can be added together
+    let vertices = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(0.0, 1.0, 1.0),
+        Point3::new(0.5, 0.5, 1.5),
+        Point3::new(1.5, 0.5, 1.5),
+    ];
+    let hex_mesh =
+        HexMesh::from_vertices_and_connectivity(vertices.clone(), vec![Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7])]);
+    let pyramid_mesh =
+        Pyramid5Mesh::from_vertices_and_connectivity(vertices.clone(), vec![Pyramid5Connectivity([4, 5, 6, 7, 8])]);
+    let tet_mesh = Tet4Mesh::from_vertices_and_connectivity(vertices, vec![Tet4Connectivity([5, 6, 8, 9])]);
+
+    let density = 2.0;
+    let mass =
+        &(&mass_matrix!(hex_mesh, density) + &mass_matrix!(pyramid_mesh, density)) + &mass_matrix!(tet_mesh, density);
+    assert_eq!(mass.nrows(), 10);
+    assert_eq!(mass.ncols(), 10);
+
+    // The volumes of the hex, pyramid and tet are 1, 1/6 and 1/12 respectively
+    let total_mass: f64 = mass.values().iter().sum();
+    assert_scalar_eq!(total_mass, density * 5.0 / 4.0, comp = abs, tol = 1e-12);
+}
+
+proptest! {
+    #[test]
+    fn pyramid5_partition_of_unity(xi in point_in_pyramid_ref_domain_away_from_apex()) {
+        let element = Pyramid5Element::reference();
+        let phi = element.evaluate_basis(&xi);
+        let phi_sum: f64 = phi.sum();
+        prop_assert!( (phi_sum - 1.0f64).abs() <= 1e-12);
+    }
+
+    #[test]
+    fn pyramid5_reference_element_gradient_is_derivative_of_basis(
+        xi in point_in_pyramid_ref_domain_away_from_apex()
+    ) {
+        let pyramid = Pyramid5Element::reference();
+        // Finite difference parameter
+        let h = 1e-6;
+        let f = VectorFunctionBuilder::with_dimension(5).with_function(move |x, xi| {
+            let xi = OPoint::from(xi.generic_view((0, 0), (U3::name(), U1::name())).clone_owned());
+            x.copy_from(&pyramid.evaluate_basis(&xi).transpose());
+        });
+
+        let grad = pyramid.gradients(&xi);
+        let xi = DVectorView::<_, Dyn>::from(&xi.coords).clone_owned();
+        let grad_approx = approximate_jacobian(f, &xi, &h).transpose();
+
+        assert_approx_matrix_eq!(grad, &grad_approx, abstol=1e-5);
+    }
+}
@@ -1,5 +1,6 @@
 use fenris::element::{
-    map_physical_coordinates, FiniteElement, FixedNodesReferenceFiniteElement, Quad4d2Element, Quad9d2Element,
+    map_physical_coordinates, FiniteElement, FixedNodesReferenceFiniteElement, Quad4d2Element, Quad4d3Element,
+    Quad9d2Element, SurfaceFiniteElement,
 };
 use fenris::error::estimate_element_L2_error;
 use fenris::geometry::proptest::nondegenerate_convex_quad2d_strategy_f64;
@@ -9,8 +10,8 @@ use fenris::integrate::IntegrationWorkspace;
 use fenris::nalgebra::DVector;
 use fenris::quadrature;
 
-use matrixcompare::assert_scalar_eq;
-use nalgebra::{MatrixView, OMatrix, Point2, Vector1, Vector2, U1, U9};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use nalgebra::{Matrix3x2, MatrixView, OMatrix, Point2, Point3, Vector1, Vector2, Vector3, U1, U9};
 
 use proptest::prelude::*;
 use util::assert_approx_matrix_eq;
@@ -71,6 +72,32 @@ fn map_physical_coords_quad2d() {
         .relative_eq(&Vector2::new(-1.0, 1.0), 1e-10, 1e-10));
 }
 
+#[test]
+fn quad4d3_element_on_tilted_rectangle() {
+    // The rectangle [0, 2] x [0, 1] in the xy-plane, rotated about the x-axis so that it lies
+    // in the plane z = y
+    let s = 1.0 / 2.0f64.sqrt();
+    let element = Quad4d3Element::from_vertices([
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(2.0, s, s),
+        Point3::new(0.0, s, s),
+    ]);
+
+    let xi = Point2::new(0.5, -0.5);
+    let x = element.map_reference_coords(&xi);
+    assert_matrix_eq!(x.coords, Vector3::new(1.5, 0.25 * s, 0.25 * s), comp = abs, tol = 1e-12);
+
+    let jacobian = element.reference_jacobian(&xi);
+    let expected_jacobian = Matrix3x2::new(1.0, 0.0, 0.0, 0.5 * s, 0.0, 0.5 * s);
+    assert_matrix_eq!(jacobian, expected_jacobian, comp = abs, tol = 1e-12);
+
+    // The vertices are ordered counter-clockwise when viewed from the side of the normal
+    let normal = element.normal(&xi);
+    assert_matrix_eq!(normal, Vector3::new(0.0, -s, s), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(element.diameter(), 5.0f64.sqrt(), comp = abs, tol = 1e-12);
+}
+
 #[test]
 fn quad9_lagrange_property() {
     // We expect that N_i(x_j) = delta_ij
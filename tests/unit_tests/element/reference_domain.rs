@@ -0,0 +1,307 @@
+use fenris::allocators::DimAllocator;
+use fenris::element::{
+    ClosestPoint, ClosestPointInElement, FiniteElement, Quad4d2Element, ReferenceDomain, ReferenceShape, Tet10Element,
+    Tet4Element, Tri3d2Element, Tri3d3Element,
+};
+use itertools::Itertools;
+use matrixcompare::assert_matrix_eq;
+use nalgebra::{point, DefaultAllocator, DimName, OMatrix, OPoint, OVector, Point1, Point2, Point3, U1, U2, U3};
+use proptest::array::{uniform2, uniform3};
+use proptest::prelude::*;
+
+fn points<D: DimName>(coords: &[&[f64]]) -> Vec<OPoint<f64, D>>
+where
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    coords
+        .iter()
+        .map(|x| OPoint::from(OVector::<f64, D>::from_column_slice(x)))
+        .collect()
+}
+
+fn reference_vertices<D: DimName>(shape: ReferenceShape) -> Vec<OPoint<f64, D>>
+where
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    use ReferenceShape::*;
+    match shape {
+        Segment => points(&[&[-1.0], &[1.0]]),
+        Triangle => points(&[&[-1.0, -1.0], &[1.0, -1.0], &[-1.0, 1.0]]),
+        Quadrilateral => points(&[&[-1.0, -1.0], &[1.0, -1.0], &[1.0, 1.0], &[-1.0, 1.0]]),
+        Tetrahedron => points(&[
+            &[-1.0, -1.0, -1.0],
+            &[1.0, -1.0, -1.0],
+            &[-1.0, 1.0, -1.0],
+            &[-1.0, -1.0, 1.0],
+        ]),
+        Hexahedron => points(&[
+            &[-1.0, -1.0, -1.0],
+            &[1.0, -1.0, -1.0],
+            &[1.0, 1.0, -1.0],
+            &[-1.0, 1.0, -1.0],
+            &[-1.0, -1.0, 1.0],
+            &[1.0, -1.0, 1.0],
+            &[1.0, 1.0, 1.0],
+            &[-1.0, 1.0, 1.0],
+        ]),
+        Prism => points(&[
+            &[-1.0, -1.0, -1.0],
+            &[1.0, -1.0, -1.0],
+            &[-1.0, 1.0, -1.0],
+            &[-1.0, -1.0, 1.0],
+            &[1.0, -1.0, 1.0],
+            &[-1.0, 1.0, 1.0],
+        ]),
+        Pyramid => points(&[
+            &[-1.0, -1.0, -1.0],
+            &[1.0, -1.0, -1.0],
+            &[1.0, 1.0, -1.0],
+            &[-1.0, 1.0, -1.0],
+            &[0.0, 0.0, 1.0],
+        ]),
+    }
+}
+
+/// Checks that the projection of `x` is the closest point in the reference domain.
+///
+/// Since the reference domain is the convex hull of its vertices, `p` is the projection of `x`
+/// if and only if `p` is in the domain and `(x - p) . (v - p) <= 0` for all vertices `v`.
+fn assert_is_projection<D: DimName>(shape: ReferenceShape, x: &OPoint<f64, D>)
+where
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let p = shape.project(x);
+    assert!(
+        shape.contains(&p, 1e-12),
+        "projection {} of {} is not in {:?}",
+        p,
+        x,
+        shape
+    );
+    for v in reference_vertices::<D>(shape) {
+        let inner_product = (x - &p).dot(&(v - &p));
+        assert!(
+            inner_product <= 1e-12 * (1.0 + (x - &p).norm()),
+            "{} is not the closest point to {} in {:?}",
+            p,
+            x,
+            shape
+        );
+    }
+
+    if shape.contains(x, 0.0) {
+        assert_eq!(&p, x);
+    }
+    let dist = (x - &p).norm();
+    assert!(shape.contains(x, dist + 1e-14));
+    if dist > 1e-14 {
+        assert!(!shape.contains(x, 0.5 * dist));
+    }
+}
+
+/// Checks the projection for points near every vertex, edge and face of the reference shape.
+///
+/// Features are represented by centroids of subsets of the vertices. We also include some
+/// subsets that are not features (such as diagonals), which does not hurt.
+fn check_points_near_features<D: DimName>(shape: ReferenceShape)
+where
+    DefaultAllocator: DimAllocator<f64, D>,
+{
+    let vertices = reference_vertices::<D>(shape);
+    let centroid = OPoint::from(vertices.iter().map(|v| &v.coords).sum::<OVector<f64, D>>() / vertices.len() as f64);
+
+    let mut directions: Vec<OVector<f64, D>> = Vec::new();
+    for e_i in OMatrix::<f64, D, D>::identity().column_iter() {
+        directions.push(e_i.into_owned());
+        directions.push(-e_i);
+    }
+    directions.push(OVector::<f64, D>::repeat(1.0).normalize());
+    directions.push(OVector::<f64, D>::repeat(-1.0).normalize());
+
+    for subset_size in 1..=usize::min(4, vertices.len()) {
+        for subset in vertices.iter().combinations(subset_size) {
+            let feature_centroid =
+                OPoint::from(subset.iter().map(|v| &v.coords).sum::<OVector<f64, D>>() / subset_size as f64);
+            let mut feature_directions = directions.clone();
+            if let Some(outward) = (&feature_centroid - &centroid).try_normalize(1e-12) {
+                feature_directions.push(-&outward);
+                feature_directions.push(outward);
+            }
+            for direction in &feature_directions {
+                for magnitude in [0.0, 1e-15, 1e-10, 1e-3, 0.5, 3.0] {
+                    let x = &feature_centroid + direction * magnitude;
+                    assert_is_projection(shape, &x);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn reference_shape_projection_near_features() {
+    check_points_near_features::<U1>(ReferenceShape::Segment);
+    check_points_near_features::<U2>(ReferenceShape::Triangle);
+    check_points_near_features::<U2>(ReferenceShape::Quadrilateral);
+    check_points_near_features::<U3>(ReferenceShape::Tetrahedron);
+    check_points_near_features::<U3>(ReferenceShape::Hexahedron);
+    check_points_near_features::<U3>(ReferenceShape::Prism);
+    check_points_near_features::<U3>(ReferenceShape::Pyramid);
+}
+
+#[test]
+fn reference_shape_vertices_are_contained_and_fixed() {
+    macro_rules! check_vertices {
+        ($shape:expr, $dim:ty) => {
+            for v in reference_vertices::<$dim>($shape) {
+                assert!($shape.contains(&v, 0.0));
+                assert_eq!($shape.project(&v), v);
+            }
+        };
+    }
+    check_vertices!(ReferenceShape::Segment, U1);
+    check_vertices!(ReferenceShape::Triangle, U2);
+    check_vertices!(ReferenceShape::Quadrilateral, U2);
+    check_vertices!(ReferenceShape::Tetrahedron, U3);
+    check_vertices!(ReferenceShape::Hexahedron, U3);
+    check_vertices!(ReferenceShape::Prism, U3);
+    check_vertices!(ReferenceShape::Pyramid, U3);
+}
+
+#[test]
+fn simplex_projection_is_not_coordinate_wise_clamping() {
+    // Coordinate-wise clamping would give (1, 1) and (1, 1, 1), which are not even
+    // in the reference domain
+    let triangle = ReferenceShape::Triangle;
+    assert_matrix_eq!(triangle.project(&point![2.0, 2.0]).coords, point![0.0, 0.0].coords);
+    assert_matrix_eq!(triangle.project(&point![3.0, -3.0]).coords, point![1.0, -1.0].coords);
+    assert_matrix_eq!(triangle.project(&point![-3.0, -0.5]).coords, point![-1.0, -0.5].coords);
+
+    let tet = ReferenceShape::Tetrahedron;
+    let face_centroid = point![-1.0 / 3.0, -1.0 / 3.0, -1.0 / 3.0];
+    assert_matrix_eq!(
+        tet.project(&point![1.0, 1.0, 1.0]).coords,
+        face_centroid.coords,
+        comp = abs,
+        tol = 1e-14
+    );
+    assert_matrix_eq!(
+        tet.project(&point![-5.0, -5.0, 3.0]).coords,
+        point![-1.0, -1.0, 1.0].coords
+    );
+}
+
+#[test]
+fn box_and_pyramid_projection_examples() {
+    assert_eq!(ReferenceShape::Segment.project(&Point1::new(-3.0)), Point1::new(-1.0));
+    assert_eq!(
+        ReferenceShape::Quadrilateral.project(&point![2.0, 0.5]),
+        point![1.0, 0.5]
+    );
+    assert_eq!(
+        ReferenceShape::Hexahedron.project(&point![2.0, -2.0, 0.5]),
+        point![1.0, -1.0, 0.5]
+    );
+    assert_eq!(
+        ReferenceShape::Prism.project(&point![2.0, 2.0, 5.0]),
+        point![0.0, 0.0, 1.0]
+    );
+
+    let pyramid = ReferenceShape::Pyramid;
+    let assert_projection = |x: Point3<f64>, expected: Point3<f64>| {
+        assert_matrix_eq!(pyramid.project(&x).coords, expected.coords, comp = abs, tol = 1e-14);
+    };
+    assert_projection(point![0.0, 0.0, 5.0], point![0.0, 0.0, 1.0]);
+    assert_projection(point![0.1, -0.1, 1.5], point![0.0, 0.0, 1.0]);
+    assert_projection(point![0.2, 0.3, -4.0], point![0.2, 0.3, -1.0]);
+    assert_projection(point![5.0, 0.0, -1.0], point![1.0, 0.0, -1.0]);
+    assert_projection(point![5.0, 5.0, -5.0], point![1.0, 1.0, -1.0]);
+    // Projection onto the face 2x + z <= 1
+    assert_projection(point![1.0, 0.0, 0.5], point![0.4, 0.0, 0.2]);
+}
+
+#[test]
+fn reference_domain_tolerance_is_a_distance() {
+    let triangle = ReferenceShape::Triangle;
+    // Distance sqrt(2) * 1e-6 from the hypotenuse
+    let x = point![0.5 + 1e-6, -0.5 + 1e-6];
+    assert!(!triangle.contains(&x, 1e-6));
+    assert!(triangle.contains(&x, 1.5e-6));
+
+    let tet = ReferenceShape::Tetrahedron;
+    let x = point![-1.0 - 1e-6, 0.0, -0.5];
+    assert!(!tet.contains(&x, 0.5e-6));
+    assert!(tet.contains(&x, 1.5e-6));
+}
+
+#[test]
+#[should_panic]
+fn reference_shape_panics_on_dimension_mismatch() {
+    ReferenceShape::Tetrahedron.contains(&Point2::new(0.0, 0.0), 0.0);
+}
+
+#[test]
+fn elements_use_the_shape_of_their_reference_domain() {
+    assert_eq!(
+        Tri3d2Element::<f64>::reference().reference_shape(),
+        ReferenceShape::Triangle
+    );
+    assert_eq!(
+        Quad4d2Element::<f64>::reference().reference_shape(),
+        ReferenceShape::Quadrilateral
+    );
+    assert_eq!(
+        Tet10Element::<f64>::reference().reference_shape(),
+        ReferenceShape::Tetrahedron
+    );
+
+    let element = Tet4Element::<f64>::reference();
+    let x = point![1.0, 1.0, 1.0];
+    assert!(!element.reference_domain_contains(&x, 1e-3));
+    assert_eq!(
+        element.clamp_to_reference_domain(&x),
+        ReferenceShape::Tetrahedron.project(&x)
+    );
+}
+
+proptest! {
+    #[test]
+    fn triangle_projection_is_closest_point(x in uniform2(-3.0..3.0f64)) {
+        assert_is_projection(ReferenceShape::Triangle, &Point2::from(x));
+    }
+
+    #[test]
+    fn tet_projection_is_closest_point(x in uniform3(-3.0..3.0f64)) {
+        assert_is_projection(ReferenceShape::Tetrahedron, &Point3::from(x));
+    }
+
+    #[test]
+    fn prism_projection_is_closest_point(x in uniform3(-3.0..3.0f64)) {
+        assert_is_projection(ReferenceShape::Prism, &Point3::from(x));
+    }
+
+    #[test]
+    fn pyramid_projection_is_closest_point(x in uniform3(-3.0..3.0f64)) {
+        assert_is_projection(ReferenceShape::Pyramid, &Point3::from(x));
+    }
+
+    #[test]
+    fn closest_point_in_element_is_in_reference_domain(
+        vertices in uniform3(uniform3(-2.0..2.0f64)),
+        p in uniform3(-3.0..3.0f64)
+    ) {
+        let tri2 = Tri3d2Element::from_vertices(vertices.map(|v| point![v[0], v[1]]));
+        let xi = tri2.closest_point(&point![p[0], p[1]]).point().clone();
+        prop_assert!(tri2.reference_domain_contains(&xi, 1e-14));
+
+        let tri3 = Tri3d3Element::from_vertices(vertices.map(Point3::from));
+        let xi = tri3.closest_point(&Point3::from(p)).point().clone();
+        prop_assert!(tri3.reference_domain_contains(&xi, 1e-14));
+
+        // Points that map to the interior of the element are found as interior points
+        let xi_interior = ReferenceShape::Triangle.project(&point![0.5 * p[0], 0.5 * p[1]]);
+        let x_interior = tri2.map_reference_coords(&xi_interior);
+        if let ClosestPoint::InElement(xi) = tri2.closest_point(&x_interior) {
+            prop_assert!(tri2.reference_domain_contains(&xi, 1e-14));
+        }
+    }
+}
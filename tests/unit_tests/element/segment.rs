@@ -1,4 +1,6 @@
-use fenris::element::{project_physical_coordinates, FiniteElement, Segment2d2Element};
+use fenris::element::{
+    project_physical_coordinates, FiniteElement, FixedNodesReferenceFiniteElement, Segment2d2Element, Segment3d2Element,
+};
 
 use fenris::geometry::LineSegment2d;
 
@@ -33,6 +35,34 @@ fn map_reference_coords_edge2d() {
         .relative_eq(&(0.25 * a.coords + 0.75 * b.coords), 1e-10, 1e-10));
 }
 
+#[test]
+fn segment3d2_basis_interpolates_nodes() {
+    let a = Point2::new(5.0, 3.0);
+    let b = Point2::new(10.0, 4.0);
+    let element = Segment3d2Element::from(Segment2d2Element::from_vertices([a, b]));
+    assert_eq!(element.vertices()[1], Point2::new(7.5, 3.5));
+
+    // The nodes are located at xi = -1, 0, 1 in reference coordinates
+    for (i, xi) in [-1.0f64, 0.0, 1.0].into_iter().enumerate() {
+        let xi = Point1::new(xi);
+        let phi = element.evaluate_basis(&xi);
+        for j in 0..3 {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!((phi[j] - expected).abs() < 1e-14);
+        }
+        let x = element.map_reference_coords(&xi);
+        assert!(x
+            .coords
+            .relative_eq(&element.vertices()[i].coords, 1e-12, 1e-12));
+    }
+
+    // Gradients sum to zero since the basis functions form a partition of unity
+    for xi in [-0.7f64, 0.1, 0.9] {
+        let gradients = element.gradients(&Point1::new(xi));
+        assert!(gradients.sum().abs() < 1e-14);
+    }
+}
+
 proptest! {
     #[test]
     fn edge2d_element_jacobian_is_derivative_of_transform(
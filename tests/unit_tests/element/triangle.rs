@@ -1,6 +1,6 @@
 use fenris::element::{
     ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement, FixedNodesReferenceFiniteElement,
-    Tri3d2Element, Tri3d3Element, Tri6d2Element,
+    Tri10d2Element, Tri3d2Element, Tri3d3Element, Tri6d2Element,
 };
 use fenris::error::estimate_element_L2_error;
 use fenris::geometry::proptest::clockwise_triangle2d_strategy_f64;
@@ -15,7 +15,7 @@ use fenris_geometry::LineSegment3d;
 use fenris_optimize::calculus::{approximate_jacobian, VectorFunctionBuilder};
 
 use matrixcompare::{assert_matrix_eq, assert_scalar_eq, prop_assert_matrix_eq};
-use nalgebra::{distance, point, DVectorView, DimName, Dyn, OMatrix, OPoint, Point2, Vector1, U1, U2, U3, U6};
+use nalgebra::{distance, point, DVectorView, DimName, Dyn, OMatrix, OPoint, Point2, Vector1, U1, U10, U2, U3, U6};
 
 use crate::unit_tests::element::{is_likely_in_tri_ref_interior, point_in_tri_ref_domain};
 use proptest::prelude::*;
@@ -55,6 +55,23 @@ fn tri6d2_lagrange_property() {
     }
 }
 
+#[test]
+fn tri10d2_lagrange_property() {
+    // We expect that N_i(x_j) = delta_ij
+    // where N_i is the ith basis function, j is the vertex associated with the ith node,
+    // and delta_ij is the Kronecker delta.
+    let element = Tri10d2Element::reference();
+
+    for (i, xi) in element.vertices().into_iter().enumerate() {
+        let phi = element.evaluate_basis(&xi);
+
+        let mut expected = OMatrix::<f64, U1, U10>::zeros();
+        expected[i] = 1.0;
+
+        assert_approx_matrix_eq!(phi, expected, abstol = 1e-12);
+    }
+}
+
 #[test]
 fn tri3d2_closest_point_is_a_vertex() {
     // We test the case where the closest point is a vertex because the proptests don't cover it.
@@ -234,6 +251,31 @@ proptest! {
         assert_scalar_eq!(error, 0.0, comp=abs, tol=element.diameter() * element.diameter() * 1e-12);
     }
 
+    #[test]
+    fn tri10_cubic_function_error_is_zero(tri in clockwise_triangle2d_strategy_f64()) {
+        let element = Tri10d2Element::from(Tri3d2Element::from(tri));
+        // A complete cubic polynomial can be exactly represented with a Tri10 element,
+        // so that the basis weights of u_h are given by u_exact(x_i),
+        // where x_i are the coordinates of each node of the element.
+        let u_exact = |x: &Point2<f64>| {
+            let (x, y) = (x[0], x[1]);
+            0.5 * x * x * x - 1.5 * x * x * y + 2.0 * x * y * y - y * y * y
+                + 2.0 * x * x - 3.0 * x * y + 0.5 * y + 1.5
+        };
+        let u_weights = DVector::from_vec(element.vertices().iter().map(|x| u_exact(x)).collect::<Vec<_>>());
+
+        let (weights, points) = quadrature::total_order::triangle(6).unwrap();
+        let error = estimate_element_L2_error(
+            &element,
+            &|x: &Point2<_>| Vector1::new(u_exact(x)),
+            DVectorView::from(&u_weights),
+            &weights,
+            &points,
+            &mut IntegrationWorkspace::default());
+
+        assert_scalar_eq!(error, 0.0, comp=abs, tol=element.diameter() * u_weights.amax() * 1e-11);
+    }
+
         #[test]
     fn tri3d2_element_gradient_is_derivative_of_transform(
         (tri, xi) in (any::<Triangle2d<f64>>(), point_in_tri_ref_domain())
@@ -278,6 +320,28 @@ proptest! {
         assert_approx_matrix_eq!(grad, &grad_approx, abstol=1e-5);
     }
 
+    #[test]
+    fn tri10d2_element_gradient_is_derivative_of_transform(
+        (tri, xi) in (any::<Triangle2d<f64>>(), point_in_tri_ref_domain())
+    ) {
+
+        let elem = Tri10d2Element::from(Tri3d2Element::from(tri));
+
+        // Finite difference parameter
+        let h = 1e-6;
+        // Note: Function values are given as row vectors, so we transpose to get the result,
+        // and we must also transpose the end result
+        let f = VectorFunctionBuilder::with_dimension(10).with_function(move |x, xi| {
+            let xi = OPoint::from(xi.generic_view((0, 0), (U2::name(), U1::name())).clone_owned());
+            x.copy_from(&elem.evaluate_basis(&xi).transpose());
+        });
+
+        let grad = elem.gradients(&xi);
+        let grad_approx = approximate_jacobian(f, &DVectorView::<_, Dyn>::from(&xi.coords).clone_owned(), &h).transpose();
+
+        assert_approx_matrix_eq!(grad, &grad_approx, abstol=1e-5);
+    }
+
     #[test]
     fn tri3_closest_point_in_interior_is_identity(element: Tri3d2Element<f64>, xi in point_in_tri_ref_domain()) {
         // We cannot compare the reference coordinates directly, because the element
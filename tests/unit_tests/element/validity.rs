@@ -0,0 +1,178 @@
+use fenris::assembly::local::UniformQuadratureTable;
+use fenris::connectivity::Quad4d2Connectivity;
+use fenris::element::{
+    check_element_validity, ElementValidityStatus, FixedNodesReferenceFiniteElement, Quad9d2Element, ReferenceShape,
+};
+use fenris::mesh::QuadMesh2d;
+use fenris::quadrature;
+use fenris::space::{FiniteElementConnectivity, FiniteElementSpace, ReferenceDomainInSpace};
+use itertools::Itertools;
+use nalgebra::{distance, point, Dyn, Matrix2, MatrixViewMut, OMatrix, Point2, Vector2, U2, U9};
+
+/// A space of quadratic quadrilaterals whose geometry is given by their quadratic basis functions.
+///
+/// Since the Quad9 elements in fenris have straight edges, this is used to test curved elements.
+struct IsoparametricQuad9Space {
+    elements: Vec<[Point2<f64>; 9]>,
+}
+
+impl IsoparametricQuad9Space {
+    fn vertex_matrix(&self, element_index: usize) -> OMatrix<f64, U2, U9> {
+        OMatrix::<f64, U2, U9>::from_fn(|i, j| self.elements[element_index][j][i])
+    }
+}
+
+impl FiniteElementConnectivity for IsoparametricQuad9Space {
+    fn num_elements(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn num_nodes(&self) -> usize {
+        9 * self.elements.len()
+    }
+
+    fn element_node_count(&self, _element_index: usize) -> usize {
+        9
+    }
+
+    fn populate_element_nodes(&self, nodes: &mut [usize], element_index: usize) {
+        for (i, node) in nodes.iter_mut().enumerate() {
+            *node = 9 * element_index + i;
+        }
+    }
+}
+
+impl FiniteElementSpace<f64> for IsoparametricQuad9Space {
+    type GeometryDim = U2;
+    type ReferenceDim = U2;
+
+    fn populate_element_basis(&self, _element_index: usize, basis_values: &mut [f64], xi: &Point2<f64>) {
+        basis_values.copy_from_slice(Quad9d2Element::reference().evaluate_basis(xi).as_slice());
+    }
+
+    fn populate_element_gradients(
+        &self,
+        _element_index: usize,
+        mut gradients: MatrixViewMut<f64, U2, Dyn>,
+        xi: &Point2<f64>,
+    ) {
+        gradients.copy_from(&Quad9d2Element::reference().gradients(xi));
+    }
+
+    fn element_reference_jacobian(&self, element_index: usize, xi: &Point2<f64>) -> Matrix2<f64> {
+        self.vertex_matrix(element_index) * Quad9d2Element::reference().gradients(xi).transpose()
+    }
+
+    fn map_element_reference_coords(&self, element_index: usize, xi: &Point2<f64>) -> Point2<f64> {
+        Point2::from(self.vertex_matrix(element_index) * Quad9d2Element::reference().evaluate_basis(xi).transpose())
+    }
+
+    fn diameter(&self, element_index: usize) -> f64 {
+        self.elements[element_index]
+            .iter()
+            .tuple_combinations()
+            .map(|(x, y)| distance(x, y))
+            .fold(0.0, f64::max)
+    }
+}
+
+impl ReferenceDomainInSpace<f64> for IsoparametricQuad9Space {
+    fn element_reference_shape(&self, _element_index: usize) -> ReferenceShape {
+        ReferenceShape::Quadrilateral
+    }
+}
+
+/// The unit square as a Quad9 element, with the given displacements applied to its nodes.
+fn perturbed_unit_square_quad9(displacements: &[(usize, [f64; 2])]) -> [Point2<f64>; 9] {
+    let mut vertices = Quad9d2Element::<f64>::reference()
+        .vertices()
+        .map(|xi| Point2::from((xi.coords + Vector2::new(1.0, 1.0)) / 2.0));
+    for &(i, [dx, dy]) in displacements {
+        vertices[i] += Vector2::new(dx, dy);
+    }
+    vertices
+}
+
+#[test]
+fn check_element_validity_detects_tangled_quad9() {
+    let space = IsoparametricQuad9Space {
+        elements: vec![
+            // The undeformed unit square
+            perturbed_unit_square_quad9(&[]),
+            // A curved, but valid element, with the bottom edge bulging downwards and the right edge outwards
+            perturbed_unit_square_quad9(&[(4, [0.0, -0.1]), (5, [0.15, 0.0])]),
+            // The midpoint of the bottom edge is pulled up beyond the center node, so that the element folds over
+            perturbed_unit_square_quad9(&[(4, [0.0, 0.8])]),
+        ],
+    };
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(3));
+
+    let validity = check_element_validity(&space, &qtable);
+    assert_eq!(validity.len(), 3);
+
+    assert_eq!(validity[0].status, ElementValidityStatus::Valid);
+    // The Jacobian of the map from [-1, 1]^2 to the unit square is I / 2
+    assert!((validity[0].min_jacobian_det - 0.25).abs() <= 1e-12);
+
+    assert_eq!(validity[1].status, ElementValidityStatus::Valid);
+    assert!(validity[1].is_valid());
+    assert!(validity[1].min_jacobian_det > 0.0);
+
+    assert_eq!(validity[2].status, ElementValidityStatus::Inverted);
+    assert!(!validity[2].is_valid());
+    assert!(validity[2].min_jacobian_det < 0.0);
+    // The element is inverted near its bottom edge
+    let xi = &validity[2].min_reference_coords;
+    assert!(xi.y < 0.0);
+    assert_eq!(
+        space.element_reference_jacobian(2, xi).determinant(),
+        validity[2].min_jacobian_det
+    );
+}
+
+#[test]
+fn check_element_validity_detects_degenerate_and_inverted_quad4() {
+    let vertices = vec![
+        point![0.0, 0.0],
+        point![1.0, 0.0],
+        point![1.0, 1.0],
+        point![0.0, 1.0],
+        point![2.0, 0.0],
+        point![2.0, 1.0],
+    ];
+    let connectivity = vec![
+        Quad4d2Connectivity([0, 1, 2, 3]),
+        // The vertices 2 and 5 are swapped, so that the quad is a "bow tie"
+        Quad4d2Connectivity([1, 4, 2, 5]),
+        // A triangle represented as a quad, with vanishing Jacobian at the repeated vertex
+        Quad4d2Connectivity([1, 4, 5, 5]),
+    ];
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(vertices, connectivity);
+    let qtable = UniformQuadratureTable::from_quadrature(quadrature::tensor::quadrilateral_gauss(2));
+
+    let statuses = check_element_validity(&mesh, &qtable)
+        .iter()
+        .map(|validity| validity.status)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        vec![
+            ElementValidityStatus::Valid,
+            ElementValidityStatus::Inverted,
+            ElementValidityStatus::Degenerate
+        ]
+    );
+}
+
+#[test]
+fn reference_shape_vertices_match_reference_elements() {
+    let quad9 = Quad9d2Element::<f64>::reference();
+    let quad_vertices: Vec<Point2<f64>> = ReferenceShape::Quadrilateral.vertices();
+    assert_eq!(quad_vertices, quad9.vertices()[..4].to_vec());
+
+    let tri_vertices: Vec<Point2<f64>> = ReferenceShape::Triangle.vertices();
+    assert_eq!(
+        tri_vertices,
+        vec![point![-1.0, -1.0], point![1.0, -1.0], point![-1.0, 1.0]]
+    );
+}
@@ -5,6 +5,7 @@ use fenris::element::{ElementConnectivity, Tet20Element, Tet4Element, Volumetric
 use fenris::error::{
     estimate_H1_seminorm_error, estimate_L2_error, estimate_element_H1_seminorm_error,
     estimate_element_H1_seminorm_error_squared, estimate_element_L2_error, estimate_element_L2_error_squared,
+    par_estimate_H1_seminorm_error, par_estimate_L2_error,
 };
 use fenris::integrate::IntegrationWorkspace;
 use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
@@ -226,6 +227,9 @@ fn test_estimate_L2_error_on_mesh() {
     };
 
     assert_scalar_eq!(computed_L2_error, expected_L2_error, comp = abs, tol = 1e-12);
+
+    let par_computed_L2_error = par_estimate_L2_error(&mesh, &u_vector, &u_h, &quadrature_table).unwrap();
+    assert_scalar_eq!(par_computed_L2_error, expected_L2_error, comp = abs, tol = 1e-12);
 }
 
 #[test]
@@ -289,6 +293,15 @@ fn test_estimate_H1_seminorm_error_on_mesh() {
         comp = abs,
         tol = 1e-12
     );
+
+    let par_computed_H1_seminorm_error =
+        par_estimate_H1_seminorm_error(&mesh, &u_vector_grad, &u_h, &quadrature_table).unwrap();
+    assert_scalar_eq!(
+        par_computed_H1_seminorm_error,
+        expected_H1_seminorm_error,
+        comp = abs,
+        tol = 1e-12
+    );
 }
 
 /// An arbitrary multi-variate scalar function used in tests.
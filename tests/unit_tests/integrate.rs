@@ -0,0 +1,248 @@
+use fenris::connectivity::Connectivity;
+use fenris::element::ReferenceShape;
+use fenris::integrate::{
+    compute_boundary_normals, compute_face_normal_and_area, integrate_over_boundary, integrate_over_space,
+    integrate_over_subset, par_integrate_over_space, par_integrate_over_subset,
+};
+use fenris::mesh::procedural::{
+    create_rectangular_uniform_hex_mesh, create_rectangular_uniform_tet_mesh, create_unit_box_uniform_hex_mesh_3d,
+    create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+};
+use fenris::mesh::{HexMesh, QuadMesh2d, Tet4Mesh};
+use fenris::nalgebra::{vector, DVector, Matrix2, Matrix2x1, Matrix3x1, Point2, Point3, Vector1, Vector2, Vector3};
+use fenris::quadrature::faces::{reference_face_quadratures_2d, reference_face_quadratures_3d};
+use fenris::quadrature::CanonicalMassQuadrature;
+use fenris::util::global_vector_from_point_fn;
+use matrixcompare::assert_scalar_eq;
+
+#[test]
+fn integrate_one_gives_mesh_volume() {
+    let mesh: Tet4Mesh<f64> = create_rectangular_uniform_tet_mesh(0.5, 2, 3, 1, 2);
+    let qtable = mesh.canonical_mass_quadrature();
+    let u = DVector::zeros(mesh.vertices().len());
+    let one = |_: &Point3<f64>, _: &Vector1<f64>, _: &Matrix3x1<f64>| 1.0;
+
+    let volume = integrate_over_space(&mesh, &qtable, &u, one).unwrap();
+    assert_scalar_eq!(volume, 0.75, comp = abs, tol = 1e-12);
+    let volume = par_integrate_over_space(&mesh, &qtable, &u, one).unwrap();
+    assert_scalar_eq!(volume, 0.75, comp = abs, tol = 1e-12);
+
+    // The subdomains given by the even and odd elements partition the domain
+    let num_elements = mesh.connectivity().len();
+    let even: Vec<_> = (0..num_elements).step_by(2).collect();
+    let odd: Vec<_> = (1..num_elements).step_by(2).collect();
+    let even_volume = integrate_over_subset(&mesh, &qtable, &u, &even, one).unwrap();
+    let odd_volume = par_integrate_over_subset(&mesh, &qtable, &u, &odd, one).unwrap();
+    assert!(even_volume > 0.0 && odd_volume > 0.0);
+    assert_scalar_eq!(even_volume + odd_volume, 0.75, comp = abs, tol = 1e-12);
+    let empty_volume = integrate_over_subset(&mesh, &qtable, &u, &[], one).unwrap();
+    assert_eq!(empty_volume, 0.0);
+}
+
+#[test]
+fn integrate_interpolated_scalar_polynomial_is_exact() {
+    // u is linear and therefore represented exactly by linear tetrahedra
+    let mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    let qtable = mesh.canonical_mass_quadrature();
+    let u = global_vector_from_point_fn(mesh.vertices(), |p| vector![2.0 * p.x - p.y + 3.0 * p.z]);
+
+    // int_[0, 1]^3 u |grad u|^2 dx = 14 * (1 - 1/2 + 3/2)
+    let f = |_: &Point3<f64>, u: &Vector1<f64>, u_grad: &Matrix3x1<f64>| u[0] * u_grad.norm_squared();
+    let integral = integrate_over_space(&mesh, &qtable, &u, f).unwrap();
+    assert_scalar_eq!(integral, 28.0, comp = abs, tol = 1e-12);
+    let integral = par_integrate_over_space(&mesh, &qtable, &u, f).unwrap();
+    assert_scalar_eq!(integral, 28.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn integrate_interpolated_vector_polynomial_is_exact() {
+    // Both components of u are bilinear, and therefore represented exactly by bilinear quads
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(3);
+    let qtable = mesh.canonical_mass_quadrature();
+    let u = global_vector_from_point_fn(mesh.vertices(), |p| vector![p.x * p.y, p.x + 2.0 * p.y]);
+
+    // The columns of the gradient are the gradients of the components, so that
+    // f = xy (x + 2y) + (y, x) . (1, 2), whose integral over the unit square is 1/2 + 3/2
+    let f = |x: &Point2<f64>, u: &Vector2<f64>, u_grad: &Matrix2<f64>| {
+        assert_scalar_eq!(u[0], x.x * x.y, comp = abs, tol = 1e-12);
+        u[0] * u[1] + u_grad.column(0).dot(&u_grad.column(1))
+    };
+    let integral = integrate_over_space(&mesh, &qtable, &u, f).unwrap();
+    assert_scalar_eq!(integral, 2.0, comp = abs, tol = 1e-12);
+    let integral = par_integrate_over_space(&mesh, &qtable, &u, f).unwrap();
+    assert_scalar_eq!(integral, 2.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn boundary_flux_of_linear_field_through_cube_faces() {
+    let mesh: HexMesh<f64> = create_rectangular_uniform_hex_mesh(1.0, 1, 1, 1, 3);
+    let face_quadratures = reference_face_quadratures_3d(ReferenceShape::Hexahedron, 2).unwrap();
+    let gradient = Vector3::new(2.0, -1.0, 3.0);
+    let u = global_vector_from_point_fn(mesh.vertices(), |p| vector![gradient.dot(&p.coords) + 1.0]);
+
+    // Selects the boundary faces whose vertices all lie in the plane x_axis = value
+    let faces_in_plane = |axis: usize, value: f64| -> Vec<(usize, usize)> {
+        mesh.find_boundary_faces()
+            .into_iter()
+            .filter(|(face, _, _)| {
+                face.vertex_indices()
+                    .iter()
+                    .all(|&v| (mesh.vertices()[v][axis] - value).abs() < 1e-12)
+            })
+            .map(|(_, element_index, local_face_index)| (element_index, local_face_index))
+            .collect()
+    };
+    let flux = |faces: &[(usize, usize)]| {
+        let f = |_: &Point3<f64>, n: &Vector3<f64>, _: &Vector1<f64>, u_grad: &Matrix3x1<f64>| u_grad.dot(n);
+        integrate_over_boundary(&mesh, faces, &face_quadratures, &u, f).unwrap()
+    };
+
+    for axis in 0..3 {
+        let lower_faces = faces_in_plane(axis, 0.0);
+        let upper_faces = faces_in_plane(axis, 1.0);
+        assert_eq!(lower_faces.len(), 9);
+        assert_eq!(upper_faces.len(), 9);
+        let lower_flux = flux(&lower_faces);
+        let upper_flux = flux(&upper_faces);
+        assert_scalar_eq!(upper_flux, gradient[axis], comp = abs, tol = 1e-12);
+        assert_scalar_eq!(lower_flux, -upper_flux, comp = abs, tol = 1e-12);
+    }
+
+    let all_faces: Vec<_> = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(_, element_index, local_face_index)| (element_index, local_face_index))
+        .collect();
+    assert_scalar_eq!(flux(&all_faces), 0.0, comp = abs, tol = 1e-12);
+
+    // The mean value of u over the face x = 1 is 2 - 1/2 + 3/2 + 1
+    let f = |_: &Point3<f64>, _: &Vector3<f64>, u: &Vector1<f64>, _: &Matrix3x1<f64>| u[0];
+    let integral = integrate_over_boundary(&mesh, &faces_in_plane(0, 1.0), &face_quadratures, &u, f).unwrap();
+    assert_scalar_eq!(integral, 4.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn boundary_integral_over_square_satisfies_divergence_theorem() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let face_quadratures = reference_face_quadratures_2d(ReferenceShape::Quadrilateral, 2);
+    let u = DVector::zeros(mesh.vertices().len());
+    let faces: Vec<_> = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(_, element_index, local_face_index)| (element_index, local_face_index))
+        .collect();
+
+    // The perimeter, and the integral of (x, 0) . n, which is the area of the square
+    let perimeter = integrate_over_boundary(
+        &mesh,
+        &faces,
+        &face_quadratures,
+        &u,
+        |_: &Point2<f64>, _: &Vector2<f64>, _: &Vector1<f64>, _: &Matrix2x1<f64>| 1.0,
+    )
+    .unwrap();
+    assert_scalar_eq!(perimeter, 4.0, comp = abs, tol = 1e-12);
+    let area = integrate_over_boundary(
+        &mesh,
+        &faces,
+        &face_quadratures,
+        &u,
+        |x: &Point2<f64>, n: &Vector2<f64>, _: &Vector1<f64>, _: &Matrix2x1<f64>| x.x * n.x,
+    )
+    .unwrap();
+    assert_scalar_eq!(area, 1.0, comp = abs, tol = 1e-12);
+
+    // Out of bounds face indices are rejected
+    let f = |_: &Point2<f64>, _: &Vector2<f64>, _: &Vector1<f64>, _: &Matrix2x1<f64>| 1.0;
+    assert!(integrate_over_boundary(&mesh, &[(0, 4)], &face_quadratures, &u, f).is_err());
+    assert!(integrate_over_boundary(&mesh, &[(4, 0)], &face_quadratures, &u, f).is_err());
+}
+
+/// Checks that each normal is an axis-aligned unit vector pointing out of the box with the given
+/// corners, and that the face lies in the corresponding side of the box. Returns the total area
+/// of the faces on each of the six sides, ordered as -x, +x, -y, +y, -z, +z.
+fn box_side_areas(
+    mesh: &HexMesh<f64>,
+    normals: &[((usize, usize), Vector3<f64>, f64)],
+    lower: &Point3<f64>,
+    upper: &Point3<f64>,
+) -> [f64; 6] {
+    let mut areas = [0.0; 6];
+    for &((element_index, local_face_index), normal, area) in normals {
+        let axis = normal.iamax();
+        let sign = normal[axis].signum();
+        let mut expected_normal = Vector3::zeros();
+        expected_normal[axis] = sign;
+        assert_scalar_eq!(normal.dot(&expected_normal), 1.0, comp = abs, tol = 1e-12);
+
+        let side = if sign > 0.0 { upper[axis] } else { lower[axis] };
+        let face = mesh.connectivity()[element_index]
+            .get_face_connectivity(local_face_index)
+            .unwrap();
+        for &v in face.vertex_indices() {
+            assert_scalar_eq!(mesh.vertices()[v][axis], side, comp = abs, tol = 1e-12);
+        }
+        areas[2 * axis + usize::from(sign > 0.0)] += area;
+    }
+    areas
+}
+
+#[test]
+fn boundary_normals_of_unit_cube_are_axis_aligned() {
+    let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(3);
+    let face_quadratures = reference_face_quadratures_3d(ReferenceShape::Hexahedron, 1).unwrap();
+    let normals = compute_boundary_normals(&mesh, &face_quadratures).unwrap();
+    assert_eq!(normals.len(), 6 * 9);
+    for &(_, _, area) in &normals {
+        assert_scalar_eq!(area, 1.0 / 9.0, comp = abs, tol = 1e-12);
+    }
+
+    let areas = box_side_areas(&mesh, &normals, &Point3::origin(), &Point3::new(1.0, 1.0, 1.0));
+    for area in areas {
+        assert_scalar_eq!(area, 1.0, comp = abs, tol = 1e-12);
+    }
+    assert_scalar_eq!(areas.iter().sum::<f64>(), 6.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn boundary_normals_of_inverted_elements_point_outward() {
+    // Mirroring the mesh inverts all elements, which reverses the orientation of their faces
+    let mut mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    mesh.transform_vertices(|p| p.x = -p.x);
+    let face_quadratures = reference_face_quadratures_3d(ReferenceShape::Hexahedron, 1).unwrap();
+    let normals = compute_boundary_normals(&mesh, &face_quadratures).unwrap();
+    assert_eq!(normals.len(), 6 * 4);
+
+    let areas = box_side_areas(
+        &mesh,
+        &normals,
+        &Point3::new(-1.0, 0.0, 0.0),
+        &Point3::new(0.0, 1.0, 1.0),
+    );
+    for area in areas {
+        assert_scalar_eq!(area, 1.0, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn face_normal_and_area_of_quad_edges() {
+    let mut mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+    // Shear the square into a parallelogram with vertices (0, 0), (1, 0), (2, 1), (1, 1)
+    mesh.transform_vertices(|p| p.x += p.y);
+    let face_quadratures = reference_face_quadratures_2d(ReferenceShape::Quadrilateral, 0);
+
+    let expected = [
+        (Vector2::new(0.0, -1.0), 1.0),
+        (Vector2::new(1.0, -1.0) / 2.0f64.sqrt(), 2.0f64.sqrt()),
+        (Vector2::new(0.0, 1.0), 1.0),
+        (Vector2::new(-1.0, 1.0) / 2.0f64.sqrt(), 2.0f64.sqrt()),
+    ];
+    for (local_face_index, (expected_normal, expected_length)) in expected.into_iter().enumerate() {
+        let (normal, length) = compute_face_normal_and_area(&mesh, 0, local_face_index, &face_quadratures).unwrap();
+        assert_scalar_eq!((normal - expected_normal).norm(), 0.0, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(length, expected_length, comp = abs, tol = 1e-12);
+    }
+
+    assert!(compute_face_normal_and_area(&mesh, 0, 4, &face_quadratures).is_err());
+    assert!(compute_face_normal_and_area(&mesh, 1, 0, &face_quadratures).is_err());
+}
@@ -1 +1,5 @@
+mod checkpoint;
+mod inp;
 mod msh;
+mod obj;
+mod vtk;
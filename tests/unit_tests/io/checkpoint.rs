@@ -0,0 +1,81 @@
+use fenris::connectivity::{Hex8Connectivity, Quad4d2Connectivity};
+use fenris::io::checkpoint::{load_checkpoint, save_checkpoint};
+use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::mesh::HexMesh;
+use fenris::nalgebra::{DVector, DVectorView, U2, U3};
+use std::path::PathBuf;
+
+fn output_path(file_name: &str) -> PathBuf {
+    PathBuf::from("data/unit_tests/io_checkpoint").join(file_name)
+}
+
+/// A hex mesh with a displacement and a velocity vector with values that are not exactly
+/// representable in decimal.
+fn hex_mesh_and_state() -> (HexMesh<f64>, DVector<f64>, DVector<f64>) {
+    let mesh = create_unit_box_uniform_hex_mesh_3d(3);
+    let n = 3 * mesh.vertices().len();
+    let u = DVector::from_fn(n, |i, _| (i as f64 / 7.0).sin() / 3.0);
+    let v = DVector::from_fn(n, |i, _| -1e-300 * (i as f64).sqrt() + f64::EPSILON);
+    (mesh, u, v)
+}
+
+#[test]
+fn checkpoint_round_trip_is_bit_exact() -> eyre::Result<()> {
+    let (mesh, u, v) = hex_mesh_and_state();
+    let path = output_path("hex_mesh.bin");
+    save_checkpoint(
+        &path,
+        &mesh,
+        &[("u", DVectorView::from(&u)), ("v", DVectorView::from(&v))],
+    )?;
+
+    let (loaded_mesh, state): (HexMesh<f64>, _) = load_checkpoint(&path)?;
+    assert_eq!(loaded_mesh, mesh);
+    assert_eq!(state.len(), 2);
+    for ((name, loaded), (expected_name, expected)) in state.iter().zip([("u", &u), ("v", &v)]) {
+        assert_eq!(name, expected_name);
+        assert_eq!(loaded.len(), expected.len());
+        assert!(loaded
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, b)| a.to_bits() == b.to_bits()));
+    }
+    Ok(())
+}
+
+#[test]
+fn loading_truncated_checkpoint_fails() -> eyre::Result<()> {
+    let (mesh, u, v) = hex_mesh_and_state();
+    let path = output_path("truncated_source.bin");
+    save_checkpoint(
+        &path,
+        &mesh,
+        &[("u", DVectorView::from(&u)), ("v", DVectorView::from(&v))],
+    )?;
+    let bytes = std::fs::read(&path)?;
+
+    let truncated_path = output_path("truncated.bin");
+    // Truncate within the header, the mesh and the state vectors
+    for len in [0, 5, 13, 20, bytes.len() / 2, bytes.len() - 1] {
+        std::fs::write(&truncated_path, &bytes[..len])?;
+        assert!(load_checkpoint::<f64, U3, Hex8Connectivity>(&truncated_path).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn loading_checkpoint_with_mismatched_types_fails() -> eyre::Result<()> {
+    let (mesh, u, _) = hex_mesh_and_state();
+    let path = output_path("mismatched_types.bin");
+    save_checkpoint(&path, &mesh, &[("u", DVectorView::from(&u))])?;
+
+    let error = load_checkpoint::<f32, U3, Hex8Connectivity>(&path).unwrap_err();
+    assert!(format!("{:?}", error).contains("scalar type"));
+
+    let error = load_checkpoint::<f64, U2, Quad4d2Connectivity>(&path).unwrap_err();
+    assert!(format!("{:?}", error).contains("dimension"));
+
+    std::fs::write(&path, b"not a checkpoint at all")?;
+    assert!(load_checkpoint::<f64, U3, Hex8Connectivity>(&path).is_err());
+    Ok(())
+}
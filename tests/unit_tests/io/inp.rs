@@ -0,0 +1,214 @@
+use fenris::connectivity::{
+    Connectivity, Hex20Connectivity, Hex8Connectivity, Quad4d2Connectivity, Tet10Connectivity, Tet4Connectivity,
+    Tri3d2Connectivity,
+};
+use fenris::io::inp::{load_inp, load_inp_from_str};
+use fenris::mesh::{Hex20Mesh, HexMesh, Mesh3d, Tet10Mesh, Tet4Mesh};
+use nalgebra::{Point2, Point3, U2, U3};
+
+/// Asserts that the nodes of every element are located at the same positions in both meshes.
+fn assert_same_element_node_positions<C: Connectivity>(mesh: &Mesh3d<f64, C>, expected: &Mesh3d<f64, C>) {
+    assert_eq!(mesh.connectivity().len(), expected.connectivity().len());
+    for (cell, expected_cell) in mesh.connectivity().iter().zip(expected.connectivity()) {
+        for (&v, &expected_v) in cell
+            .vertex_indices()
+            .iter()
+            .zip(expected_cell.vertex_indices())
+        {
+            assert_eq!(mesh.vertices()[v], expected.vertices()[expected_v]);
+        }
+    }
+}
+
+#[test]
+fn load_inp_cps3_and_cps4() -> eyre::Result<()> {
+    let inp = "
+*NODE
+10, 0.0, 0.0
+20, 1.0, 0.0
+30, 1.0, 1.0
+40, 0.0, 1.0, 0.0
+*ELEMENT, TYPE=CPS3
+1, 10, 20, 30
+2, 10, 30, 40
+";
+    let tri_mesh = load_inp_from_str::<f64, U2, Tri3d2Connectivity>(inp)?;
+    assert_eq!(
+        tri_mesh.mesh.vertices(),
+        &[
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.0, 1.0)
+        ]
+    );
+    assert_eq!(
+        tri_mesh.mesh.connectivity(),
+        &[Tri3d2Connectivity([0, 1, 2]), Tri3d2Connectivity([0, 2, 3])]
+    );
+    assert!(tri_mesh.warnings.is_empty());
+    assert!(tri_mesh.named_sets.is_empty());
+
+    let inp = "
+*NODE
+1, 0.0, 0.0
+2, 1.0, 0.0
+3, 1.0, 1.0
+4, 0.0, 1.0
+*ELEMENT, TYPE=CPS4R
+7, 1, 2, 3, 4
+";
+    let quad_mesh = load_inp_from_str::<f64, U2, Quad4d2Connectivity>(inp)?;
+    assert_eq!(quad_mesh.mesh.connectivity(), &[Quad4d2Connectivity([0, 1, 2, 3])]);
+    Ok(())
+}
+
+#[test]
+fn load_inp_c3d4_and_c3d10() -> eyre::Result<()> {
+    let inp = "
+*NODE
+1, 0.0, 0.0, 0.0
+2, 1.0, 0.0, 0.0
+3, 0.0, 1.0, 0.0
+4, 0.0, 0.0, 1.0
+5, 0.5, 0.0, 0.0
+6, 0.5, 0.5, 0.0
+7, 0.0, 0.5, 0.0
+8, 0.0, 0.0, 0.5
+9, 0.5, 0.0, 0.5
+10, 0.0, 0.5, 0.5
+*ELEMENT, TYPE=C3D10
+1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10
+";
+    let tet10_mesh = load_inp_from_str::<f64, U3, Tet10Connectivity>(inp)?;
+    let tet4_mesh = Tet4Mesh::from_vertices_and_connectivity(
+        tet10_mesh.mesh.vertices()[..4].to_vec(),
+        vec![Tet4Connectivity([0, 1, 2, 3])],
+    );
+    // The conversion from Tet4 produces the fenris node ordering
+    assert_same_element_node_positions(&tet10_mesh.mesh, &Tet10Mesh::from(&tet4_mesh));
+
+    // The same file does not contain linear tetrahedra
+    assert!(load_inp_from_str::<f64, U3, Tet4Connectivity>(inp).is_err());
+
+    let inp = "
+*NODE
+1, 0.0, 0.0, 0.0
+2, 1.0, 0.0, 0.0
+3, 0.0, 1.0, 0.0
+4, 0.0, 0.0, 1.0
+*ELEMENT, TYPE=C3D4
+1, 1, 2, 3, 4
+";
+    let loaded = load_inp_from_str::<f64, U3, Tet4Connectivity>(inp)?;
+    assert_eq!(loaded.mesh, tet4_mesh);
+    Ok(())
+}
+
+#[test]
+fn load_inp_c3d20() -> eyre::Result<()> {
+    // Element definitions with more than 16 entries continue on the next line
+    let inp = "
+*NODE
+1, 0.0, 0.0, 0.0
+2, 1.0, 0.0, 0.0
+3, 1.0, 1.0, 0.0
+4, 0.0, 1.0, 0.0
+5, 0.0, 0.0, 1.0
+6, 1.0, 0.0, 1.0
+7, 1.0, 1.0, 1.0
+8, 0.0, 1.0, 1.0
+9, 0.5, 0.0, 0.0
+10, 1.0, 0.5, 0.0
+11, 0.5, 1.0, 0.0
+12, 0.0, 0.5, 0.0
+13, 0.5, 0.0, 1.0
+14, 1.0, 0.5, 1.0
+15, 0.5, 1.0, 1.0
+16, 0.0, 0.5, 1.0
+17, 0.0, 0.0, 0.5
+18, 1.0, 0.0, 0.5
+19, 1.0, 1.0, 0.5
+20, 0.0, 1.0, 0.5
+*ELEMENT, TYPE=C3D20R
+1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+16, 17, 18, 19, 20
+";
+    let hex20_mesh = load_inp_from_str::<f64, U3, Hex20Connectivity>(inp)?;
+    let hex8_mesh = HexMesh::from_vertices_and_connectivity(
+        hex20_mesh.mesh.vertices()[..8].to_vec(),
+        vec![Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7])],
+    );
+    assert_same_element_node_positions(&hex20_mesh.mesh, &Hex20Mesh::from(&hex8_mesh));
+    Ok(())
+}
+
+#[test]
+fn load_inp_hex8_file_with_sets() -> eyre::Result<()> {
+    let inp_mesh = load_inp::<f64, U3, Hex8Connectivity>("assets/meshes/beam_hex8_2.inp")?;
+    let mesh = &inp_mesh.mesh;
+    assert_eq!(mesh.vertices().len(), 12);
+    assert_eq!(mesh.vertices()[11], Point3::new(2.0, 1.0, 1.0));
+    assert_eq!(
+        mesh.connectivity(),
+        &[
+            Hex8Connectivity([0, 1, 4, 3, 6, 7, 10, 9]),
+            Hex8Connectivity([1, 2, 5, 4, 7, 8, 11, 10])
+        ]
+    );
+
+    let sets = &inp_mesh.named_sets;
+    assert_eq!(sets.node_set("ALL_NODES"), Some((0..12).collect::<Vec<_>>().as_slice()));
+    assert_eq!(sets.node_set("LEFT"), Some([0, 3, 6, 9].as_slice()));
+    assert_eq!(sets.node_set("BOTTOM"), Some([0, 1, 2, 3, 4, 5].as_slice()));
+    assert_eq!(
+        sets.node_set("LEFT_AND_RIGHT"),
+        Some([0, 2, 3, 5, 6, 8, 9, 11].as_slice())
+    );
+    assert_eq!(sets.element_set("SOLID"), Some([0, 1].as_slice()));
+    // The shell element is not part of the mesh
+    assert_eq!(sets.element_set("EVERYTHING"), Some([0, 1].as_slice()));
+    assert_eq!(sets.element_set("SHELL"), None);
+
+    let expected_warnings = [
+        "line 3: skipping unsupported keyword *HEADING",
+        "line 21: skipping elements of type S4R, which cannot be represented by the requested connectivity",
+        "line 33: skipping unsupported keyword *MATERIAL",
+        "line 34: skipping unsupported keyword *ELASTIC",
+        "line 36: skipping unsupported keyword *SOLID SECTION",
+    ];
+    assert_eq!(inp_mesh.warnings, expected_warnings);
+    Ok(())
+}
+
+#[test]
+fn load_inp_reports_invalid_definitions() {
+    let nodes = "
+*NODE
+1, 0.0, 0.0, 0.0
+2, 1.0, 0.0, 0.0
+3, 0.0, 1.0, 0.0
+4, 0.0, 0.0, 1.0
+";
+    let load = |rest: &str| load_inp_from_str::<f64, U3, Tet4Connectivity>(&format!("{}{}", nodes, rest));
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n").is_ok());
+    // Undefined node
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 5\n").is_err());
+    // Duplicate element id
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n1, 2, 1, 3, 4\n").is_err());
+    // Duplicate node id
+    assert!(load("*NODE\n1, 0.0, 0.0, 0.0\n*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n").is_err());
+    // Incomplete element
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3\n").is_err());
+    // Missing element type
+    assert!(load("*ELEMENT\n1, 1, 2, 3, 4\n").is_err());
+    // Set referring to an undefined node or set
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n*NSET, NSET=A\n1, 7\n").is_err());
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n*NSET, NSET=A\nB\n").is_err());
+    // Invalid range
+    assert!(load("*ELEMENT, TYPE=C3D4\n1, 1, 2, 3, 4\n*NSET, NSET=A, GENERATE\n4, 1\n").is_err());
+
+    // Loading a 3D mesh as 2D fails because of nonzero z-coordinates
+    let inp = format!("{}*ELEMENT, TYPE=CPS3\n1, 1, 2, 3\n", nodes);
+    assert!(load_inp_from_str::<f64, U2, Tri3d2Connectivity>(&inp).is_err());
+}
@@ -1,11 +1,13 @@
 use crate::export_mesh_vtk;
 use fenris::connectivity::{
-    Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity,
-    Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
+    Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri3d3Connectivity, Tri6d2Connectivity,
 };
-use fenris::io::msh::load_msh_from_file;
+use fenris::element::{FiniteElement, Hex20Element};
+use fenris::io::msh::{load_msh_from_bytes, load_msh_from_file, load_msh_with_groups_from_bytes};
 use insta::assert_debug_snapshot;
-use nalgebra::{U2, U3};
+use matrixcompare::assert_matrix_eq;
+use nalgebra::{Point2, U2, U3};
 
 #[test]
 fn load_msh_sphere_tet4_large() -> eyre::Result<()> {
@@ -163,3 +165,203 @@ fn load_msh_cube_hex27() -> eyre::Result<()> {
 
     Ok(())
 }
+
+const SQUARE_TRI3_WITH_GROUPS_MSH: &str = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$PhysicalNames
+2
+1 1 "left"
+2 2 "domain"
+$EndPhysicalNames
+$Entities
+4 4 1 0
+1 0 0 0 0
+2 1 0 0 0
+3 1 1 0 0
+4 0 1 0 0
+1 0 0 0 1 0 0 0 2 1 -2
+2 1 0 0 1 1 0 0 2 2 -3
+3 0 1 0 1 1 0 0 2 3 -4
+4 0 0 0 0 1 0 1 1 2 4 -1
+1 0 0 0 1 1 0 1 2 4 1 2 3 4
+$EndEntities
+$Nodes
+1 4 1 4
+2 1 0 4
+1
+2
+3
+4
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+$EndNodes
+$Elements
+2 3 1 3
+1 4 1 1
+1 4 1
+2 1 2 2
+2 1 2 3
+3 1 3 4
+$EndElements
+"#;
+
+#[test]
+fn load_msh_with_groups_square_tri3d2() -> eyre::Result<()> {
+    let (mesh, groups) =
+        load_msh_with_groups_from_bytes::<f64, U2, Tri3d2Connectivity>(SQUARE_TRI3_WITH_GROUPS_MSH.as_bytes())?;
+
+    assert_eq!(
+        mesh.vertices(),
+        &[
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.0, 1.0)
+        ]
+    );
+    assert_eq!(
+        mesh.connectivity(),
+        &[Tri3d2Connectivity([0, 1, 2]), Tri3d2Connectivity([0, 2, 3])]
+    );
+
+    // The boundary curve only defines a node set, while the surface defines both
+    assert_eq!(groups.node_set("left"), Some([0, 3].as_slice()));
+    assert_eq!(groups.element_set("left"), None);
+    assert_eq!(groups.node_set("domain"), Some([0, 1, 2, 3].as_slice()));
+    assert_eq!(groups.element_set("domain"), Some([0, 1].as_slice()));
+    assert_eq!(groups.node_sets().count(), 2);
+    assert_eq!(groups.element_sets().count(), 1);
+
+    // Loading without groups gives the same mesh
+    let mesh_without_groups =
+        load_msh_from_bytes::<f64, U2, Tri3d2Connectivity>(SQUARE_TRI3_WITH_GROUPS_MSH.as_bytes())?;
+    assert_eq!(mesh_without_groups, mesh);
+
+    Ok(())
+}
+
+const CUBE_HEX20_MSH: &str = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Entities
+0 0 0 1
+1 0 0 0 1 1 1 1 7 0
+$EndEntities
+$Nodes
+1 20 1 20
+3 1 0 20
+1
+2
+3
+4
+5
+6
+7
+8
+9
+10
+11
+12
+13
+14
+15
+16
+17
+18
+19
+20
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+0 0 1
+1 0 1
+1 1 1
+0 1 1
+0.5 0 0
+0 0.5 0
+0 0 0.5
+1 0.5 0
+1 0 0.5
+0.5 1 0
+1 1 0.5
+0 1 0.5
+0.5 0 1
+0 0.5 1
+1 0.5 1
+0.5 1 1
+$EndNodes
+$Elements
+1 1 1 1
+3 1 17 1
+1 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20
+$EndElements
+"#;
+
+#[test]
+fn load_msh_with_groups_cube_hex20() -> eyre::Result<()> {
+    let (mesh, groups) = load_msh_with_groups_from_bytes::<f64, U3, Hex20Connectivity>(CUBE_HEX20_MSH.as_bytes())?;
+
+    assert_eq!(mesh.vertices().len(), 20);
+    assert_eq!(mesh.connectivity(), &[Hex20Connectivity(std::array::from_fn(|i| i))]);
+
+    // The node ordering of the file must agree with the reference element, which is mapped to the unit cube
+    let reference = Hex20Element::<f64>::reference();
+    let element = Hex20Element::from_vertices(mesh.vertices().try_into().unwrap());
+    for (xi, x) in reference.vertices().iter().zip(mesh.vertices()) {
+        assert_matrix_eq!(
+            element.map_reference_coords(xi).coords,
+            x.coords,
+            comp = abs,
+            tol = 1e-14
+        );
+        assert_matrix_eq!(((xi.coords.add_scalar(1.0)) / 2.0), x.coords, comp = abs, tol = 1e-14);
+    }
+
+    // Unnamed groups are named by their tag
+    assert_eq!(groups.element_set("7"), Some([0].as_slice()));
+    assert_eq!(groups.node_set("7"), Some((0..20).collect::<Vec<_>>().as_slice()));
+
+    Ok(())
+}
+
+const SQUARE_MIXED_TRI3_QUAD4_MSH: &str = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Nodes
+1 5 1 5
+2 1 0 5
+1
+2
+3
+4
+5
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+2 0 0
+$EndNodes
+$Elements
+2 2 1 2
+2 1 3 1
+1 1 2 3 4
+2 1 2 1
+2 2 5 3
+$EndElements
+"#;
+
+#[test]
+fn load_msh_mixed_elements_is_rejected() {
+    let result = load_msh_with_groups_from_bytes::<f64, U2, Tri3d2Connectivity>(SQUARE_MIXED_TRI3_QUAD4_MSH.as_bytes());
+    let error = format!("{:?}", result.unwrap_err());
+    assert!(error.contains("mixed-element"));
+    assert!(error.contains("Qua4"));
+
+    let result = load_msh_from_bytes::<f64, U2, Quad4d2Connectivity>(SQUARE_MIXED_TRI3_QUAD4_MSH.as_bytes());
+    let error = format!("{:?}", result.unwrap_err());
+    assert!(error.contains("Tri3"));
+}
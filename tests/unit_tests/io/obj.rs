@@ -0,0 +1,101 @@
+use fenris::connectivity::Tri3d3Connectivity;
+use fenris::io::obj::{load_obj_from_file, load_obj_from_str, save_obj, write_obj};
+use fenris::mesh::TriangleMesh3d;
+use nalgebra::Point3;
+use std::path::PathBuf;
+
+fn output_path(file_name: &str) -> PathBuf {
+    PathBuf::from("data/unit_tests/io_obj").join(file_name)
+}
+
+fn tetrahedron_surface_mesh() -> TriangleMesh3d<f64> {
+    let vertices = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.1, 0.2, 1.0 / 3.0),
+    ];
+    let connectivity = vec![
+        Tri3d3Connectivity([0, 2, 1]),
+        Tri3d3Connectivity([0, 1, 3]),
+        Tri3d3Connectivity([1, 2, 3]),
+        Tri3d3Connectivity([0, 3, 2]),
+    ];
+    TriangleMesh3d::from_vertices_and_connectivity(vertices, connectivity)
+}
+
+#[test]
+fn obj_write_load_round_trip() -> eyre::Result<()> {
+    let mesh = tetrahedron_surface_mesh();
+    let mut output = Vec::new();
+    write_obj(&mesh, &mut output)?;
+    let loaded: TriangleMesh3d<f64> = load_obj_from_str(std::str::from_utf8(&output)?)?;
+    assert_eq!(loaded, mesh);
+    Ok(())
+}
+
+#[test]
+fn obj_save_load_file_round_trip() -> eyre::Result<()> {
+    let mesh = tetrahedron_surface_mesh();
+    let path = output_path("tetrahedron_surface.obj");
+    save_obj(&mesh, &path)?;
+    let loaded: TriangleMesh3d<f64> = load_obj_from_file(&path)?;
+    assert_eq!(loaded, mesh);
+    Ok(())
+}
+
+#[test]
+fn obj_load_handles_polygons_attributes_and_negative_indices() -> eyre::Result<()> {
+    let obj = "# Exported from some tool\r
+mtllib scene.mtl\r
+o square\r
+v 0.0 0.0 0.0\r
+v  1.0\t0.0   0.0 1.0\r
+v 1 1 0 # trailing comment\r
+v 0 1 0\r
+vt 0 0\r
+vn 0 0 1\r
+\r
+g faces\r
+usemtl default\r
+s off\r
+f 1/1/1 2/1/1 3//1 4\r
+v 2 0 0\r
+f -4 -1 -3\r
+l 1 2\r
+";
+    let mesh: TriangleMesh3d<f64> = load_obj_from_str(obj)?;
+    assert_eq!(mesh.vertices().len(), 5);
+    assert_eq!(mesh.vertices()[1], Point3::new(1.0, 0.0, 0.0));
+    assert_eq!(
+        mesh.connectivity(),
+        &[
+            // The quadrilateral is fan-triangulated around its first vertex
+            Tri3d3Connectivity([0, 1, 2]),
+            Tri3d3Connectivity([0, 2, 3]),
+            // Negative indices are relative to the last vertex defined before the face
+            Tri3d3Connectivity([1, 4, 2]),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn obj_load_malformed_file_reports_line_number() {
+    let error_message = |obj: &str| format!("{:?}", load_obj_from_str::<f64>(obj).unwrap_err());
+
+    let message = error_message("v 0 0 0\nv 1 0 0\nv 0 x 0\nf 1 2 3\n");
+    assert!(message.contains("line 3"), "{}", message);
+    assert!(message.contains("\"x\""), "{}", message);
+
+    let message = error_message("v 0 0 0\nv 1 0 0\nf 1 2\n");
+    assert!(message.contains("line 3"), "{}", message);
+    assert!(message.contains("at least 3 vertices"), "{}", message);
+
+    let message = error_message("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 5\n");
+    assert!(message.contains("line 5"), "{}", message);
+    assert!(message.contains("vertex 5"), "{}", message);
+
+    let message = error_message("v 0 0 0\nf 1 -2 0\n");
+    assert!(message.contains("line 2"), "{}", message);
+}
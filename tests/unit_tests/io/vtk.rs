@@ -0,0 +1,618 @@
+use fenris::connectivity::{
+    Hex27Connectivity, Hex8Connectivity, Prism15Connectivity, Pyramid5Connectivity, Segment2d2Connectivity,
+    Tri3d2Connectivity, Tri3d3Connectivity,
+};
+use fenris::element::{Hex27Element, Prism15Element, Pyramid5Element};
+use fenris::io::vtk::{
+    load_vtk_mesh_and_named_sets_from_file, load_vtu, FiniteElementMeshDataSetBuilder, FromVtkCellConnectivity,
+    PvdTimeSeriesWriter, VtkExporter, VtkPrecision,
+};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{
+    Hex20Mesh, Hex27Mesh, HexMesh, Mesh, Mesh2d, NamedSets, Prism15Mesh, Pyramid5Mesh, Quad9Mesh2d, QuadMesh2d,
+    Tet10Mesh, Tet4Mesh, Tri10Mesh2d, Tri6Mesh2d, TriangleMesh2d, TriangleMesh3d,
+};
+use fenris::vtkio::model::{
+    Attribute, Attributes, ByteOrder, CellType, Cells, DataArray, DataSet, ElementType, IOBuffer, Piece,
+    UnstructuredGridPiece, Version, VertexNumbers, Vtk,
+};
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, Point2, Point3, U2, U3};
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+fn output_path(file_name: &str) -> PathBuf {
+    PathBuf::from("data/unit_tests/io_vtk").join(file_name)
+}
+
+fn nodes_where(mesh: &HexMesh<f64>, predicate: impl Fn(&Point3<f64>) -> bool) -> Vec<usize> {
+    mesh.vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| predicate(v))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Tags the faces of the unit box and the elements in the lower half of the box.
+fn tagged_box_mesh() -> (HexMesh<f64>, NamedSets) {
+    let mesh = create_unit_box_uniform_hex_mesh_3d(3);
+    let lower_elements = mesh
+        .connectivity()
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.0.iter().all(|&v| mesh.vertices()[v].z <= 0.5))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    let named_sets = NamedSets::new()
+        .with_node_set("bottom", nodes_where(&mesh, |v| v.z == 0.0))
+        .with_node_set("top", nodes_where(&mesh, |v| v.z == 1.0))
+        .with_node_set("left_edge", nodes_where(&mesh, |v| v.x == 0.0 && v.y == 0.0))
+        .with_node_set("empty", [])
+        .with_element_set("lower", lower_elements)
+        .with_element_set("single", [4])
+        // Element and node sets may share names
+        .with_element_set("bottom", [0, 1, 2]);
+    (mesh, named_sets)
+}
+
+#[test]
+fn named_sets_sort_and_deduplicate_indices() {
+    let mut named_sets = NamedSets::new();
+    assert!(named_sets.is_empty());
+    assert_eq!(named_sets.insert_node_set("a", [3, 1, 3, 2]), None);
+    assert_eq!(named_sets.node_set("a"), Some([1, 2, 3].as_slice()));
+    assert_eq!(named_sets.insert_node_set("a", [0]), Some(vec![1, 2, 3]));
+    assert_eq!(named_sets.node_set("a"), Some([0].as_slice()));
+    assert_eq!(named_sets.element_set("a"), None);
+    assert!(!named_sets.is_empty());
+}
+
+#[test]
+fn vtk_export_import_preserves_named_sets_on_tagged_box_mesh() -> eyre::Result<()> {
+    let (mesh, named_sets) = tagged_box_mesh();
+    assert_eq!(named_sets.node_set("bottom").unwrap().len(), 16);
+    assert!(named_sets.element_set("lower").unwrap().len() > 1);
+
+    let path = output_path("tagged_box_mesh.vtk");
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_named_sets(&named_sets)
+        .try_export(&path)?;
+
+    let (imported_mesh, imported_sets): (HexMesh<f64>, _) = load_vtk_mesh_and_named_sets_from_file(&path)?;
+    assert_eq!(imported_mesh, mesh);
+    assert_eq!(imported_sets, named_sets);
+
+    Ok(())
+}
+
+#[test]
+fn vtk_import_restores_fenris_node_order() -> eyre::Result<()> {
+    // Tet10 and Hex20 are reordered on export, which must be undone on import
+    let hex8_mesh = create_unit_box_uniform_hex_mesh_3d(2);
+    let hex20_mesh = Hex20Mesh::from(&hex8_mesh);
+    let path = output_path("hex20_mesh.vtk");
+    FiniteElementMeshDataSetBuilder::from_mesh(&hex20_mesh).try_export(&path)?;
+    let (imported_mesh, named_sets): (Hex20Mesh<f64>, _) = load_vtk_mesh_and_named_sets_from_file(&path)?;
+    assert_eq!(imported_mesh, hex20_mesh);
+    assert!(named_sets.is_empty());
+
+    let tet10_mesh = Tet10Mesh::from(&Tet4Mesh::from(&hex8_mesh));
+    let path = output_path("tet10_mesh.vtk");
+    FiniteElementMeshDataSetBuilder::from_mesh(&tet10_mesh).try_export(&path)?;
+    let (imported_mesh, _): (Tet10Mesh<f64>, _) = load_vtk_mesh_and_named_sets_from_file(&path)?;
+    assert_eq!(imported_mesh, tet10_mesh);
+
+    Ok(())
+}
+
+#[test]
+fn vtk_import_of_foreign_file_has_no_named_sets() -> eyre::Result<()> {
+    // Attributes that do not follow the naming convention must be ignored
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(4);
+    let num_vertices = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    let path = output_path("foreign_quad_mesh.vtk");
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_point_vector_attributes("displacement", 2, &vec![0.5; 2 * num_vertices])
+        .with_point_scalar_attributes("node_set:bottom", 1, &vec![1; num_vertices])
+        .with_cell_scalar_attributes("material", 1, &vec![2; num_cells])
+        .try_export(&path)?;
+
+    let (imported_mesh, named_sets): (QuadMesh2d<f64>, _) = load_vtk_mesh_and_named_sets_from_file(&path)?;
+    assert_eq!(imported_mesh, mesh);
+    assert!(named_sets.is_empty());
+
+    // The cells are not compatible with a hex mesh
+    assert!(load_vtk_mesh_and_named_sets_from_file::<f64, U3, Hex8Connectivity>(&path).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn vtk_export_rejects_named_sets_with_whitespace() {
+    let (mesh, _) = tagged_box_mesh();
+    let named_sets = NamedSets::new().with_node_set("bottom face", [0]);
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh).with_named_sets(&named_sets);
+}
+
+/// Extracts the `(timestep, file)` attributes of the `DataSet` entries of a ParaView collection.
+fn pvd_entries(pvd: &str) -> Vec<(String, String)> {
+    let attribute = |tag: &str, name: &str| {
+        let start = tag.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+        let end = start + tag[start..].find('"').unwrap();
+        tag[start..end].to_string()
+    };
+    pvd.split("<DataSet")
+        .skip(1)
+        .map(|tag| (attribute(tag, "timestep"), attribute(tag, "file")))
+        .collect()
+}
+
+#[test]
+fn pvd_time_series_writer_writes_collection() -> eyre::Result<()> {
+    let directory = output_path("pvd_time_series");
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory)?;
+    }
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_cells = mesh.connectivity().len();
+
+    let mut writer = PvdTimeSeriesWriter::new(&directory, "series")?;
+    for (step, time) in [0.0, 0.25, 0.5].into_iter().enumerate() {
+        let u: Vec<_> = mesh
+            .vertices()
+            .iter()
+            .flat_map(|v| [time * v.x, time * v.y])
+            .collect();
+        let cell_index: Vec<_> = (0..num_cells).map(|i| i as f64).collect();
+        writer.write_timestep(
+            time,
+            &mesh,
+            &[("u", 2, u.as_slice())],
+            &[("cell_index", 1, cell_index.as_slice())],
+        )?;
+        assert_eq!(writer.num_timesteps(), step + 1);
+    }
+
+    let pvd = std::fs::read_to_string(directory.join("series.pvd"))?;
+    assert!(pvd.contains(r#"<VTKFile type="Collection""#));
+    let expected_entries = [
+        ("0", "series_000000.vtu"),
+        ("0.25", "series_000001.vtu"),
+        ("0.5", "series_000002.vtu"),
+    ];
+    let entries = pvd_entries(&pvd);
+    assert_eq!(entries.len(), expected_entries.len());
+    for ((time, file), (expected_time, expected_file)) in entries.iter().zip(expected_entries) {
+        assert_eq!(time, expected_time);
+        assert_eq!(file, expected_file);
+        let vtu = Vtk::import(directory.join(file))?;
+        assert!(matches!(vtu.data, DataSet::UnstructuredGrid { .. }));
+    }
+    assert!(!directory.join("series.pvd.tmp").exists());
+    Ok(())
+}
+
+#[test]
+fn pvd_time_series_writer_resumes_series_and_rejects_decreasing_times() -> eyre::Result<()> {
+    let directory = output_path("pvd_time_series_resume");
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory)?;
+    }
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+
+    // Resuming without an existing collection starts a new series
+    let mut writer = PvdTimeSeriesWriter::resume(&directory, "series")?;
+    assert_eq!(writer.num_timesteps(), 0);
+    writer.write_timestep(0.0, &mesh, &[], &[])?;
+    writer.write_timestep(1.0, &mesh, &[], &[])?;
+
+    // Restart from the existing collection
+    let mut writer = PvdTimeSeriesWriter::resume(&directory, "series")?;
+    assert_eq!(writer.num_timesteps(), 2);
+    assert!(writer.write_timestep(0.5, &mesh, &[], &[]).is_err());
+    assert!(writer.write_timestep(1.0, &mesh, &[], &[]).is_err());
+    assert!(writer.write_timestep(f64::NAN, &mesh, &[], &[]).is_err());
+    assert_eq!(writer.num_timesteps(), 2);
+    writer.write_timestep(1.5, &mesh, &[], &[])?;
+
+    let pvd = std::fs::read_to_string(writer.pvd_path())?;
+    let entries = pvd_entries(&pvd);
+    let times: Vec<_> = entries.iter().map(|(time, _)| time.as_str()).collect();
+    let files: Vec<_> = entries.iter().map(|(_, file)| file.as_str()).collect();
+    assert_eq!(times, ["0", "1", "1.5"]);
+    assert_eq!(files, ["series_000000.vtu", "series_000001.vtu", "series_000002.vtu"]);
+    assert!(!directory.join("series_000003.vtu").exists());
+
+    // Starting a new series discards the existing collection
+    let mut writer = PvdTimeSeriesWriter::new(&directory, "series")?;
+    writer.write_timestep(0.0, &mesh, &[], &[])?;
+    let pvd = std::fs::read_to_string(writer.pvd_path())?;
+    assert_eq!(pvd_entries(&pvd).len(), 1);
+    Ok(())
+}
+
+#[test]
+fn pvd_time_series_writer_escapes_file_names() -> eyre::Result<()> {
+    let directory = output_path("pvd_time_series_escape");
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory)?;
+    }
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(1);
+    let base_name = r#"run 1 & "a" <'b'>"#;
+
+    let mut writer = PvdTimeSeriesWriter::new(&directory, base_name)?;
+    writer.write_timestep(0.0, &mesh, &[], &[])?;
+    writer.write_timestep(1.0, &mesh, &[], &[])?;
+
+    let pvd = std::fs::read_to_string(writer.pvd_path())?;
+    let entries = pvd_entries(&pvd);
+    assert_eq!(
+        entries[0].1,
+        "run 1 &amp; &quot;a&quot; &lt;&apos;b&apos;&gt;_000000.vtu"
+    );
+
+    let mut writer = PvdTimeSeriesWriter::resume(&directory, base_name)?;
+    assert_eq!(writer.num_timesteps(), 2);
+    writer.write_timestep(2.0, &mesh, &[], &[])?;
+    let writer = PvdTimeSeriesWriter::resume(&directory, base_name)?;
+    assert_eq!(writer.num_timesteps(), 3);
+    assert!(directory.join(format!("{}_000002.vtu", base_name)).exists());
+
+    // Malformed collections are rejected instead of being silently misread
+    std::fs::write(writer.pvd_path(), r#"<DataSet timestep="0" file="unterminated.vtu/>"#)?;
+    assert!(PvdTimeSeriesWriter::resume(&directory, base_name).is_err());
+    std::fs::write(writer.pvd_path(), r#"<DataSet timestep="0" file="a &b.vtu"/>"#)?;
+    assert!(PvdTimeSeriesWriter::resume(&directory, base_name).is_err());
+    Ok(())
+}
+
+fn import_unstructured_grid_piece(path: impl AsRef<std::path::Path>) -> eyre::Result<UnstructuredGridPiece> {
+    let vtk = Vtk::import(path)?;
+    match vtk.data {
+        DataSet::UnstructuredGrid { mut pieces, .. } => match pieces.pop() {
+            Some(Piece::Inline(piece)) => Ok(*piece),
+            _ => eyre::bail!("expected an inline piece"),
+        },
+        _ => eyre::bail!("expected an unstructured grid"),
+    }
+}
+
+fn find_data_array<'a>(attributes: &'a [Attribute], name: &str) -> &'a DataArray {
+    attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::DataArray(array) if array.name == name => Some(array),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no data array with name {}", name))
+}
+
+#[test]
+fn vtk_exporter_writes_named_point_and_cell_fields() -> eyre::Result<()> {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_vertices = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    let u: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .flat_map(|v| [v.x, 2.0 * v.y])
+        .collect();
+    let p: Vec<_> = mesh.vertices().iter().map(|v| v.x + v.y).collect();
+    let sigma: Vec<_> = (0..num_cells).map(|i| 0.5 * i as f64).collect();
+
+    for (precision, file_name) in [
+        (VtkPrecision::F64, "exporter_f64.vtu"),
+        (VtkPrecision::F32, "exporter_f32.vtu"),
+    ] {
+        let path = output_path(file_name);
+        VtkExporter::new(&mesh)
+            .with_point_vector_field("displacement", &u, 2)
+            .with_point_scalar_field("pressure", &p)
+            .with_cell_scalar_field("von_mises", &sigma)
+            .with_precision(precision)
+            .export(&path)?;
+
+        let piece = import_unstructured_grid_piece(&path)?;
+        match precision {
+            VtkPrecision::F32 => assert!(matches!(piece.points, IOBuffer::F32(_))),
+            VtkPrecision::F64 => assert!(matches!(piece.points, IOBuffer::F64(_))),
+        }
+        assert_eq!(piece.data.point.len(), 2);
+        assert_eq!(piece.data.cell.len(), 1);
+
+        // Vectors are padded to three components
+        let displacement = find_data_array(&piece.data.point, "displacement");
+        assert_eq!(displacement.elem, ElementType::Vectors);
+        let expected_displacement: Vec<_> = u.chunks(2).flat_map(|u_i| [u_i[0], u_i[1], 0.0]).collect();
+        assert_eq!(
+            displacement.data.clone().cast_into::<f64>().unwrap(),
+            expected_displacement
+        );
+
+        let pressure = find_data_array(&piece.data.point, "pressure");
+        assert_eq!(pressure.elem.num_comp(), 1);
+        let pressure_values = pressure.data.clone().cast_into::<f64>().unwrap();
+        assert_eq!(pressure_values.len(), num_vertices);
+        // Values are exactly representable in single precision
+        assert_eq!(pressure_values, p);
+
+        let von_mises = find_data_array(&piece.data.cell, "von_mises");
+        assert_eq!(von_mises.elem.num_comp(), 1);
+        assert_eq!(von_mises.data.clone().cast_into::<f64>().unwrap(), sigma);
+    }
+    Ok(())
+}
+
+#[test]
+fn vtk_exporter_rejects_invalid_fields() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let num_vertices = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    let scalars = vec![0.0; num_vertices];
+    let vectors = vec![0.0; 2 * num_vertices];
+    let cell_scalars = vec![0.0; num_cells];
+
+    // Wrong number of values for the number of nodes and components
+    assert!(VtkExporter::new(&mesh)
+        .with_point_scalar_field("p", &scalars[1..])
+        .build()
+        .is_err());
+    assert!(VtkExporter::new(&mesh)
+        .with_point_vector_field("u", &vectors, 3)
+        .build()
+        .is_err());
+    assert!(VtkExporter::new(&mesh)
+        .with_cell_scalar_field("s", &scalars)
+        .build()
+        .is_err());
+    // Unsupported number of vector components
+    assert!(VtkExporter::new(&mesh)
+        .with_point_vector_field("u", &vectors[..0], 0)
+        .build()
+        .is_err());
+    // Invalid and duplicate names
+    assert!(VtkExporter::new(&mesh)
+        .with_point_scalar_field("", &scalars)
+        .build()
+        .is_err());
+    assert!(VtkExporter::new(&mesh)
+        .with_point_scalar_field("my pressure", &scalars)
+        .build()
+        .is_err());
+    assert!(VtkExporter::new(&mesh)
+        .with_point_scalar_field("p", &scalars)
+        .with_point_vector_field("p", &vectors, 2)
+        .build()
+        .is_err());
+
+    // Point and cell fields may share names
+    assert!(VtkExporter::new(&mesh)
+        .with_point_scalar_field("p", &scalars)
+        .with_point_vector_field("u", &vectors, 2)
+        .with_cell_scalar_field("p", &cell_scalars)
+        .build()
+        .is_ok());
+}
+
+/// Exports the mesh with a point and a cell field to a VTU file and checks that both are
+/// loaded unchanged.
+fn assert_vtu_round_trip<D, C>(mesh: &Mesh<f64, D, C>, file_name: &str) -> eyre::Result<()>
+where
+    D: DimName,
+    C: FromVtkCellConnectivity + PartialEq + Debug,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    let point_values: Vec<_> = (0..mesh.vertices().len())
+        .map(|i| 0.25 * i as f64)
+        .collect();
+    let cell_values: Vec<_> = (0..mesh.connectivity().len())
+        .map(|i| -(i as f64))
+        .collect();
+    let path = output_path(file_name);
+    VtkExporter::new(mesh)
+        .with_point_scalar_field("point_index", &point_values)
+        .with_cell_scalar_field("cell_index", &cell_values)
+        .export(&path)?;
+
+    let (imported_mesh, fields): (Mesh<f64, D, C>, _) = load_vtu(&path)?;
+    assert_eq!(&imported_mesh, mesh);
+
+    let point_field = fields.point_field("point_index").unwrap();
+    assert_eq!(point_field.num_components, 1);
+    assert_eq!(point_field.values.as_slice(), point_values.as_slice());
+    let cell_field = fields.cell_field("cell_index").unwrap();
+    assert_eq!(cell_field.num_components, 1);
+    assert_eq!(cell_field.values.as_slice(), cell_values.as_slice());
+    assert_eq!(fields.point_fields().count(), 1);
+    assert_eq!(fields.cell_fields().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn vtu_round_trip_preserves_mesh_and_fields_for_supported_connectivities() -> eyre::Result<()> {
+    let segment_mesh = Mesh2d::from_vertices_and_connectivity(
+        vec![Point2::new(0.0, 0.0), Point2::new(1.0, 0.5), Point2::new(2.0, 0.0)],
+        vec![Segment2d2Connectivity([0, 1]), Segment2d2Connectivity([1, 2])],
+    );
+    assert_vtu_round_trip(&segment_mesh, "round_trip_segment2d2.vtu")?;
+
+    let tri_mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(2);
+    assert_vtu_round_trip(&tri_mesh, "round_trip_tri3d2.vtu")?;
+    assert_vtu_round_trip(&Tri6Mesh2d::from(tri_mesh.clone()), "round_trip_tri6d2.vtu")?;
+    assert_vtu_round_trip(&Tri10Mesh2d::from(&tri_mesh), "round_trip_tri10d2.vtu")?;
+
+    let quad_mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    assert_vtu_round_trip(&quad_mesh, "round_trip_quad4d2.vtu")?;
+    assert_vtu_round_trip(&Quad9Mesh2d::from(quad_mesh.clone()), "round_trip_quad9d2.vtu")?;
+
+    let surface_mesh = TriangleMesh3d::from_vertices_and_connectivity(
+        vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ],
+        vec![Tri3d3Connectivity([0, 1, 2]), Tri3d3Connectivity([0, 3, 1])],
+    );
+    assert_vtu_round_trip(&surface_mesh, "round_trip_tri3d3.vtu")?;
+
+    let tet_mesh: Tet4Mesh<f64> = create_unit_box_uniform_tet_mesh_3d(2);
+    assert_vtu_round_trip(&tet_mesh, "round_trip_tet4.vtu")?;
+    assert_vtu_round_trip(&Tet10Mesh::from(&tet_mesh), "round_trip_tet10.vtu")?;
+
+    let hex_mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    assert_vtu_round_trip(&hex_mesh, "round_trip_hex8.vtu")?;
+    assert_vtu_round_trip(&Hex20Mesh::from(&hex_mesh), "round_trip_hex20.vtu")?;
+    assert_vtu_round_trip(&Hex27Mesh::from(&hex_mesh), "round_trip_hex27.vtu")?;
+
+    // Our node ordering for quadratic prisms coincides with VTK_QUADRATIC_WEDGE
+    let prism15_mesh = Prism15Mesh::from_vertices_and_connectivity(
+        Prism15Element::<f64>::reference().vertices().to_vec(),
+        vec![Prism15Connectivity([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14])],
+    );
+    assert_vtu_round_trip(&prism15_mesh, "round_trip_prism15.vtu")?;
+
+    let pyramid_mesh = Pyramid5Mesh::from_vertices_and_connectivity(
+        Pyramid5Element::<f64>::reference().vertices().to_vec(),
+        vec![Pyramid5Connectivity([0, 1, 2, 3, 4])],
+    );
+    assert_vtu_round_trip(&pyramid_mesh, "round_trip_pyramid5.vtu")?;
+
+    Ok(())
+}
+
+#[test]
+fn hex27_is_exported_with_vtk_triquadratic_hexahedron_node_order() -> eyre::Result<()> {
+    let element = Hex27Element::<f64>::reference();
+    let mesh = Hex27Mesh::from_vertices_and_connectivity(
+        element.vertices().to_vec(),
+        vec![Hex27Connectivity(std::array::from_fn(|i| i))],
+    );
+    let path = output_path("hex27_reference_element.vtu");
+    VtkExporter::new(&mesh).export(&path)?;
+
+    let piece = import_unstructured_grid_piece(&path)?;
+    assert_eq!(piece.cells.types, vec![CellType::TriquadraticHexahedron]);
+    let connectivity = match piece.cells.cell_verts {
+        VertexNumbers::XML { connectivity, .. } => connectivity,
+        VertexNumbers::Legacy { .. } => eyre::bail!("expected XML cell connectivity"),
+    };
+    let points = piece.points.cast_into::<f64>().unwrap();
+
+    // Parametric coordinates of the nodes of VTK_TRIQUADRATIC_HEXAHEDRON, scaled from [0, 1]^3 to [0, 2]^3:
+    // corners, then edges, then the faces x-, x+, y-, y+, z-, z+ and finally the center
+    let expected_vtk_nodes = [
+        [0, 0, 0],
+        [2, 0, 0],
+        [2, 2, 0],
+        [0, 2, 0],
+        [0, 0, 2],
+        [2, 0, 2],
+        [2, 2, 2],
+        [0, 2, 2],
+        [1, 0, 0],
+        [2, 1, 0],
+        [1, 2, 0],
+        [0, 1, 0],
+        [1, 0, 2],
+        [2, 1, 2],
+        [1, 2, 2],
+        [0, 1, 2],
+        [0, 0, 1],
+        [2, 0, 1],
+        [2, 2, 1],
+        [0, 2, 1],
+        [0, 1, 1],
+        [2, 1, 1],
+        [1, 0, 1],
+        [1, 2, 1],
+        [1, 1, 0],
+        [1, 1, 2],
+        [1, 1, 1],
+    ];
+    assert_eq!(connectivity.len(), 27);
+    for (&node, expected) in connectivity.iter().zip(expected_vtk_nodes) {
+        let node = node as usize;
+        let expected: Vec<f64> = expected.iter().map(|&c| c as f64 - 1.0).collect();
+        assert_eq!(&points[3 * node..3 * node + 3], expected.as_slice());
+    }
+
+    let (imported_mesh, _): (Hex27Mesh<f64>, _) = load_vtu(&path)?;
+    assert_eq!(imported_mesh, mesh);
+    Ok(())
+}
+
+#[test]
+fn load_vtu_returns_padded_vectors_and_skips_named_sets() -> eyre::Result<()> {
+    let (mesh, named_sets) = tagged_box_mesh();
+    let u: Vec<_> = mesh.vertices().iter().flat_map(|v| [v.x, v.y]).collect();
+    let path = output_path("load_vtu_vectors_and_sets.vtu");
+    FiniteElementMeshDataSetBuilder::from_mesh(&mesh)
+        .with_named_sets(&named_sets)
+        .with_point_vector_attributes("u", 2, &u)
+        .try_export(&path)?;
+
+    let (imported_mesh, fields): (HexMesh<f64>, _) = load_vtu(&path)?;
+    assert_eq!(imported_mesh, mesh);
+    assert_eq!(
+        fields
+            .point_fields()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["u"]
+    );
+    assert_eq!(fields.cell_fields().count(), 0);
+
+    let u_field = fields.point_field("u").unwrap();
+    assert_eq!(u_field.num_components, 3);
+    let expected_u: Vec<_> = u.chunks(2).flat_map(|u_i| [u_i[0], u_i[1], 0.0]).collect();
+    assert_eq!(u_field.values.as_slice(), expected_u.as_slice());
+    Ok(())
+}
+
+#[test]
+fn load_vtu_rejects_mixed_cell_types() -> eyre::Result<()> {
+    // A triangle and a quad sharing an edge
+    let points: Vec<f64> = vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, //
+        2.0, 0.0, 0.0, //
+        2.0, 1.0, 0.0,
+    ];
+    let piece = UnstructuredGridPiece {
+        points: points.into(),
+        cells: Cells {
+            cell_verts: VertexNumbers::Legacy {
+                num_cells: 2,
+                vertices: vec![3, 0, 1, 2, 4, 1, 3, 4, 2],
+            },
+            types: vec![CellType::Triangle, CellType::Quad],
+        },
+        data: Attributes::new(),
+    };
+    let path = output_path("mixed_cell_types.vtu");
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    Vtk {
+        version: Version { major: 1, minor: 0 },
+        title: "mixed".to_string(),
+        byte_order: ByteOrder::BigEndian,
+        data: DataSet::UnstructuredGrid {
+            meta: None,
+            pieces: vec![Piece::Inline(Box::new(piece))],
+        },
+        file_path: None,
+    }
+    .export(&path)?;
+
+    let error = load_vtu::<f64, U2, Tri3d2Connectivity>(&path).unwrap_err();
+    assert!(format!("{:?}", error).contains("mixed cell types"));
+    Ok(())
+}
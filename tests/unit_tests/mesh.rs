@@ -2,21 +2,26 @@ use fenris::connectivity::{CellConnectivity, Connectivity, Quad9d2Connectivity,
 use fenris::geometry::polymesh::PolyMesh;
 use fenris::geometry::{Orientation, Triangle};
 use fenris::mesh::procedural::{
-    create_rectangular_uniform_hex_mesh, create_rectangular_uniform_quad_mesh_2d,
+    create_rectangular_uniform_hex_mesh, create_rectangular_uniform_quad_mesh_2d, create_unit_box_uniform_hex_mesh_3d,
     create_unit_square_uniform_quad_mesh_2d,
 };
 use fenris::mesh::{Mesh, Mesh2d};
 use fenris::proptest::rectangular_uniform_mesh_strategy;
 use itertools::{equal, sorted, Itertools};
 use nalgebra::allocator::Allocator;
-use nalgebra::{DefaultAllocator, DimName, Point2, Scalar, Vector2};
+use nalgebra::{DefaultAllocator, DimName, Point2, Scalar, Vector2, Vector3};
 use proptest::collection::vec;
 use proptest::prelude::*;
 use std::cmp::max;
 
+mod distance;
+mod extrude;
 mod mesh_convert;
 mod procedural;
+mod quality;
 mod refinement;
+mod topology;
+mod validation;
 
 #[test]
 fn quad4_find_boundary_faces() {
@@ -296,3 +301,41 @@ proptest! {
         prop_assert_eq!(kept_quads_from_old_mesh, kept_quads_from_new_mesh);
     }
 }
+
+#[test]
+fn merge_hex_meshes_welds_shared_interface() {
+    let left = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    let right = left.clone().translated(&Vector3::new(1.0, 0.0, 0.0));
+
+    let (merged, vertex_maps) = Mesh::merge(&[left.clone(), right.clone()], 1e-12);
+    // The 3x3 vertices on the interface x = 1 are shared
+    assert_eq!(merged.vertices().len(), 27 + 27 - 9);
+    assert_eq!(merged.connectivity().len(), 16);
+    assert_eq!(vertex_maps.len(), 2);
+
+    for (mesh, vertex_map) in [&left, &right].into_iter().zip(&vertex_maps) {
+        assert_eq!(vertex_map.len(), mesh.vertices().len());
+        for (v, &new_index) in mesh.vertices().iter().zip(vertex_map) {
+            assert_eq!(merged.vertices()[new_index], *v);
+        }
+    }
+
+    // The merged mesh is conforming: the faces on the interface are interior faces, so the boundary
+    // consists of the 6 * 4 faces of each cube minus the 4 faces on each side of the interface
+    assert_eq!(merged.find_boundary_faces().len(), 2 * 24 - 2 * 4);
+    let interface_vertices: Vec<_> = merged.vertices().iter().filter(|v| v.x == 1.0).collect();
+    assert_eq!(interface_vertices.len(), 9);
+}
+
+#[test]
+fn merge_hex_meshes_with_zero_tolerance_concatenates() {
+    let left = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    let right = left.clone().translated(&Vector3::new(1.0, 0.0, 0.0));
+
+    let (merged, vertex_maps) = Mesh::merge(&[left.clone(), right.clone()], 0.0);
+    assert_eq!(merged.vertices().len(), 27 + 27);
+    assert_eq!(merged.connectivity().len(), 16);
+    assert_eq!(vertex_maps[0], (0..27).collect::<Vec<_>>());
+    assert_eq!(vertex_maps[1], (27..54).collect::<Vec<_>>());
+    assert_eq!(&merged.connectivity()[..8], left.connectivity());
+}
@@ -0,0 +1,153 @@
+use fenris::connectivity::Tet4Connectivity;
+use fenris::geometry::SignedDistance;
+use fenris::mesh::distance::SurfaceMeshDistanceQuery;
+use fenris::mesh::procedural::create_unit_box_uniform_tet_mesh_3d;
+use fenris::mesh::{fix_orientation, Tet4Mesh, TriangleMesh3d};
+use fenris::nalgebra::{Point3, Vector3};
+use itertools::iproduct;
+use matrixcompare::assert_scalar_eq;
+
+/// The surface mesh of the unit box, triangulated by a uniform tetrahedral mesh.
+fn unit_box_surface_mesh(cells_per_dim: usize) -> TriangleMesh3d<f64> {
+    let mut mesh = create_unit_box_uniform_tet_mesh_3d(cells_per_dim);
+    fix_orientation(&mut mesh);
+    mesh.extract_surface_mesh()
+}
+
+/// The exact signed distance to the unit box.
+fn unit_box_signed_distance(p: &Point3<f64>) -> f64 {
+    let q = p.coords.map(|x| (x - 0.5).abs() - 0.5);
+    q.map(|q_i| q_i.max(0.0)).norm() + q.max().min(0.0)
+}
+
+/// Points on a regular grid with the given number of points along each axis in the box
+/// $[a, b]^3$.
+fn grid_points(a: f64, b: f64, n: usize) -> Vec<Point3<f64>> {
+    let x = |i: usize| a + (b - a) * i as f64 / (n - 1) as f64;
+    iproduct!(0..n, 0..n, 0..n)
+        .map(|(i, j, k)| Point3::new(x(i), x(j), x(k)))
+        .collect()
+}
+
+#[test]
+fn signed_distance_to_unit_box() {
+    let query = SurfaceMeshDistanceQuery::from_mesh(&unit_box_surface_mesh(3)).unwrap();
+    assert_eq!(query.num_triangles(), 6 * 9 * 2);
+
+    // The points include points near and on the edges and corners of the box, and points that
+    // are equally far from several faces
+    let points = grid_points(-0.5, 1.5, 17);
+    for p in &points {
+        let expected = unit_box_signed_distance(p);
+        let result = query.signed_distance(p);
+        assert_scalar_eq!(result.signed_distance, expected, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(unit_box_signed_distance(&result.point), 0.0, comp = abs, tol = 1e-12);
+
+        let (closest_point, triangle_index, distance) = query.closest_point(p);
+        assert_scalar_eq!(distance, expected.abs(), comp = abs, tol = 1e-12);
+        assert_eq!(closest_point, result.point);
+        assert_eq!(triangle_index, result.feature_id);
+    }
+
+    // Outside points whose closest points are a corner and an edge of the box
+    let corner_result = query.signed_distance(&Point3::new(1.1, -0.2, 1.2));
+    assert_scalar_eq!(
+        (corner_result.point - Point3::new(1.0, 0.0, 1.0)).norm(),
+        0.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(corner_result.signed_distance, 0.09f64.sqrt(), comp = abs, tol = 1e-12);
+    let edge_result = query.signed_distance(&Point3::new(0.3, -0.1, -0.1));
+    assert_scalar_eq!(
+        (edge_result.point - Point3::new(0.3, 0.0, 0.0)).norm(),
+        0.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(edge_result.signed_distance, 0.02f64.sqrt(), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn signed_distance_near_sharp_edges_and_vertices_of_tetrahedron() {
+    let vertices = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.0, 1.0),
+    ];
+    let mesh = Tet4Mesh::from_vertices_and_connectivity(vertices, vec![Tet4Connectivity([0, 1, 2, 3])]);
+    let query = SurfaceMeshDistanceQuery::from_mesh(&mesh.extract_surface_mesh()).unwrap();
+
+    // The outward normals of the faces z = 0, y = 0 and x + y + z = 1
+    let n_z = -Vector3::<f64>::z();
+    let n_y = -Vector3::<f64>::y();
+    let n_slanted = Vector3::new(1.0, 1.0, 1.0).normalize();
+
+    // The faces z = 0 and x + y + z = 1 meet at an angle smaller than 90 degrees, so that the
+    // normal of one of the faces gives the wrong sign for points outside the edge between them
+    let edge_point = Point3::new(0.5, 0.5, 0.0);
+    let offset = n_slanted * 0.05 + n_z * 0.2;
+    assert!(offset.dot(&n_slanted) < 0.0);
+    let result = query.signed_distance(&(edge_point + offset));
+    assert_scalar_eq!((result.point - edge_point).norm(), 0.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(result.signed_distance, offset.norm(), comp = abs, tol = 1e-12);
+    let result = query.signed_distance(&(edge_point + Vector3::new(-0.01, -0.01, 0.005)));
+    assert!(result.signed_distance < 0.0);
+
+    // The same holds for points outside the vertex (1, 0, 0)
+    let vertex = Point3::new(1.0, 0.0, 0.0);
+    let offset = n_slanted * 0.05 + n_y * 0.1 + n_z * 0.1;
+    assert!(offset.dot(&n_slanted) < 0.0);
+    let result = query.signed_distance(&(vertex + offset));
+    assert_scalar_eq!((result.point - vertex).norm(), 0.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(result.signed_distance, offset.norm(), comp = abs, tol = 1e-12);
+
+    // Inside points are closest to one of the faces
+    let result = query.signed_distance(&Point3::new(0.1, 0.2, 0.3));
+    assert_scalar_eq!(result.signed_distance, -0.1, comp = abs, tol = 1e-12);
+    let result = query
+        .query_signed_distance(&Point3::new(0.2, 0.3, 0.4))
+        .unwrap();
+    assert_scalar_eq!(result.signed_distance, -0.1 / 3.0f64.sqrt(), comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn signed_distance_to_sphere() {
+    // Project the surface of the unit box radially onto the unit sphere centered in the box
+    let center = Point3::new(0.5, 0.5, 0.5);
+    let mut mesh = unit_box_surface_mesh(8);
+    mesh.transform_vertices(|p| *p = center + (*p - center).normalize());
+    let query = SurfaceMeshDistanceQuery::from_mesh(&mesh).unwrap();
+
+    // The triangles are chords of the sphere, so the distance to the mesh only approximates
+    // the distance to the sphere. Points very close to the sphere are therefore excluded.
+    let points: Vec<_> = grid_points(-1.5, 2.5, 21)
+        .into_iter()
+        .filter(|p| ((p - center).norm() - 1.0).abs() > 0.05)
+        .collect();
+    let results = query.signed_distances_par(&points);
+    let closest_points = query.closest_points_par(&points);
+    assert_eq!(results.len(), points.len());
+    assert_eq!(closest_points.len(), points.len());
+
+    for ((p, result), closest) in points.iter().zip(&results).zip(&closest_points) {
+        let expected = (p - center).norm() - 1.0;
+        assert_scalar_eq!(result.signed_distance, expected, comp = abs, tol = 0.03);
+        assert_eq!(result.signed_distance.signum(), expected.signum());
+        assert_eq!(result, &query.signed_distance(p));
+        assert_eq!(closest, &query.closest_point(p));
+        assert_scalar_eq!(closest.2, result.signed_distance.abs(), comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn distance_query_rejects_invalid_meshes() {
+    let empty = TriangleMesh3d::<f64>::from_vertices_and_connectivity(vec![], vec![]);
+    assert!(SurfaceMeshDistanceQuery::from_mesh(&empty).is_err());
+
+    let mesh = unit_box_surface_mesh(1);
+    let out_of_bounds =
+        TriangleMesh3d::from_vertices_and_connectivity(mesh.vertices()[..3].to_vec(), mesh.connectivity().to_vec());
+    assert!(SurfaceMeshDistanceQuery::from_mesh(&out_of_bounds).is_err());
+}
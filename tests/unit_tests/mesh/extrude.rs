@@ -0,0 +1,72 @@
+use fenris::element::{ElementConnectivity, FiniteElement};
+use fenris::mesh::procedural::{create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d};
+use fenris::mesh::{extrude, Mesh3d};
+use fenris::nalgebra::U3;
+use fenris::quadrature;
+use fenris::quadrature::QuadraturePair3d;
+use matrixcompare::assert_scalar_eq;
+
+/// Checks that all elements have positive Jacobian determinants at the quadrature points
+/// and returns the total volume of the mesh.
+fn check_orientation_and_compute_volume<C>(mesh: &Mesh3d<f64, C>, quadrature: &QuadraturePair3d<f64>) -> f64
+where
+    C: ElementConnectivity<f64, GeometryDim = U3, ReferenceDim = U3>,
+{
+    let (weights, points) = quadrature;
+    let mut volume = 0.0;
+    for connectivity in mesh.connectivity() {
+        let element = connectivity.element(mesh.vertices()).unwrap();
+        for (w, xi) in weights.iter().zip(points) {
+            let det = element.reference_jacobian(xi).determinant();
+            assert!(det > 0.0);
+            volume += w * det;
+        }
+    }
+    volume
+}
+
+#[test]
+fn extrude_quad_mesh_to_hex_mesh() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let layers = [0.0, 0.3, 1.0];
+    let (extruded, bottom, top) = extrude(&mesh, &layers);
+
+    let n = mesh.vertices().len();
+    assert_eq!(extruded.vertices().len(), 3 * n);
+    assert_eq!(extruded.connectivity().len(), 2 * mesh.connectivity().len());
+    assert_eq!(bottom, (0..n).collect::<Vec<_>>());
+    assert_eq!(top, (2 * n..3 * n).collect::<Vec<_>>());
+    for (&b, &t) in bottom.iter().zip(&top) {
+        assert_eq!(extruded.vertices()[b].z, 0.0);
+        assert_eq!(extruded.vertices()[t].z, 1.0);
+        assert_eq!(extruded.vertices()[b].xy(), extruded.vertices()[t].xy());
+    }
+
+    let volume = check_orientation_and_compute_volume(&extruded, &quadrature::tensor::hexahedron_gauss(2));
+    assert_scalar_eq!(volume, 1.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn extrude_triangle_mesh_to_prism_mesh() {
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let layers = [-1.0, 0.5, 1.0, 2.0];
+    let (extruded, bottom, top) = extrude(&mesh, &layers);
+
+    let n = mesh.vertices().len();
+    assert_eq!(extruded.vertices().len(), 4 * n);
+    assert_eq!(extruded.connectivity().len(), 3 * mesh.connectivity().len());
+    assert_eq!(bottom.len(), n);
+    assert_eq!(top.len(), n);
+    assert!(bottom.iter().all(|&i| extruded.vertices()[i].z == -1.0));
+    assert!(top.iter().all(|&i| extruded.vertices()[i].z == 2.0));
+
+    let volume = check_orientation_and_compute_volume(&extruded, &quadrature::total_order::prism(2).unwrap());
+    assert_scalar_eq!(volume, 3.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "strictly increasing")]
+fn extrude_panics_for_non_increasing_layers() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(1);
+    let _ = extrude(&mesh, &[0.0, 1.0, 1.0]);
+}
@@ -1,15 +1,23 @@
-use fenris::assembly::global::assemble_scalar;
-use fenris::assembly::local::UniformQuadratureTable;
+use fenris::assembly::global::{assemble_scalar, CsrAssembler};
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::connectivity::{
+    Hex20Connectivity, Hex27Connectivity, Hex8Connectivity, Quad4d2Connectivity, Quad9d2Connectivity,
+    Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri6d2Connectivity,
+};
 use fenris::error::estimate_L2_error;
 use fenris::integrate::dependency::NoDeps;
 use fenris::integrate::{ElementIntegralAssemblerBuilder, FnFunction};
-use fenris::mesh::procedural::create_rectangular_uniform_tet_mesh;
-use fenris::mesh::Tet20Mesh;
-use fenris::quadrature::total_order;
+use fenris::mesh::procedural::{
+    create_rectangular_uniform_tet_mesh, create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d,
+    create_unit_square_uniform_quad_mesh_2d, create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{p_coarsen_mesh, p_refine_mesh, NodeOrigin, Tet10Mesh, Tet20Mesh, Tri10Mesh2d};
+use fenris::quadrature::{total_order, CanonicalMassQuadrature};
 use fenris::util::global_vector_from_point_fn;
-use matrixcompare::assert_scalar_eq;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use nalgebra::allocator::Allocator;
 use nalgebra::coordinates::XYZ;
-use nalgebra::{Point3, Vector1, U1};
+use nalgebra::{DefaultAllocator, DimName, OPoint, OVector, Point2, Point3, Vector1, U1};
 
 #[test]
 fn tet20_from_tet4_can_represent_cubic_polynomial() {
@@ -64,3 +72,173 @@ fn tet20_from_tet4_can_represent_cubic_polynomial() {
     assert_scalar_eq!(tet20_integral, exact_integral, comp = abs, tol = tol);
     assert_scalar_eq!(error, 0.0, comp = abs, tol = tol);
 }
+
+/// Computes the total mass of the mesh with unit density, i.e. the sum of all entries of the mass matrix.
+macro_rules! total_mass {
+    ($mesh:expr) => {{
+        let mesh = &$mesh;
+        let qtable = mesh
+            .canonical_mass_quadrature()
+            .with_uniform_data(Density(1.0));
+        let assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(mesh)
+            .with_quadrature_table(&qtable);
+        let mass_matrix = CsrAssembler::default().assemble(&assembler).unwrap();
+        mass_matrix.values().iter().sum::<f64>()
+    }};
+}
+
+/// Checks that every node is the average of its parent vertices in the original mesh.
+fn assert_nodes_match_origins<D>(
+    vertices: &[OPoint<f64, D>],
+    original_vertices: &[OPoint<f64, D>],
+    origins: &[NodeOrigin],
+) where
+    D: DimName,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    assert_eq!(vertices.len(), origins.len());
+    for (v, origin) in vertices.iter().zip(origins) {
+        let parents = origin.parent_vertices();
+        let average = parents
+            .iter()
+            .map(|&parent| &original_vertices[parent].coords)
+            .sum::<OVector<f64, D>>()
+            / parents.len() as f64;
+        assert_matrix_eq!(v.coords, average, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn p_refine_tri3_to_tri6() {
+    let n = 3;
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(n);
+    let (tri6_mesh, origins) = p_refine_mesh::<_, _, _, Tri6d2Connectivity>(&mesh);
+
+    // Euler's formula V - E + F = 1 for a triangulated disk
+    let (num_vertices, num_faces) = (mesh.vertices().len(), mesh.connectivity().len());
+    let num_edges = num_vertices + num_faces - 1;
+    assert_eq!(tri6_mesh.vertices().len(), num_vertices + num_edges);
+    assert_eq!(&tri6_mesh.vertices()[..num_vertices], mesh.vertices());
+    assert!(origins[..num_vertices]
+        .iter()
+        .enumerate()
+        .all(|(i, origin)| origin == &NodeOrigin::Vertex(i)));
+    assert!(origins[num_vertices..]
+        .iter()
+        .all(|origin| matches!(origin, NodeOrigin::EdgeMidpoint(_))));
+    assert_nodes_match_origins(tri6_mesh.vertices(), mesh.vertices(), &origins);
+    assert_scalar_eq!(total_mass!(tri6_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+
+    let (coarsened_mesh, original_indices) = p_coarsen_mesh::<_, _, _, Tri3d2Connectivity>(&tri6_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+    assert_eq!(original_indices, (0..num_vertices).collect::<Vec<_>>());
+}
+
+#[test]
+fn tri10_from_tri3_can_represent_cubic_polynomial() {
+    let n = 3;
+    let mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(n);
+    let tri10_mesh = Tri10Mesh2d::from(&mesh);
+
+    // Two nodes are created for every edge and one for every element, and the original
+    // vertices come first
+    let (num_vertices, num_faces) = (mesh.vertices().len(), mesh.connectivity().len());
+    let num_edges = num_vertices + num_faces - 1;
+    assert_eq!(tri10_mesh.vertices().len(), num_vertices + 2 * num_edges + num_faces);
+    assert_eq!(&tri10_mesh.vertices()[..num_vertices], mesh.vertices());
+    assert_scalar_eq!(total_mass!(tri10_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+
+    let polynomial = |p: &Point2<f64>| -> f64 {
+        let (x, y) = (p.x, p.y);
+        2.0 * x.powi(3) - 3.0 * y.powi(3) + x * x * y - 4.0 * x * y * y + 3.0 * x * x - y * y + 2.0 * x * y + x
+            - 2.0 * y
+            + 1.0
+    };
+    let f = |x: &Point2<f64>| -> Vector1<f64> { Vector1::new(polynomial(x)) };
+    let u_tri10 = global_vector_from_point_fn(tri10_mesh.vertices(), f);
+
+    // Need order 6 since we're computing squared error of cubic polynomial
+    let qtable = UniformQuadratureTable::from_quadrature(total_order::triangle(6).unwrap());
+    let error = estimate_L2_error(&tri10_mesh, &f, &u_tri10, &qtable).unwrap();
+    assert_scalar_eq!(error, 0.0, comp = abs, tol = 1e-12);
+
+    let (coarsened_mesh, _) = p_coarsen_mesh::<_, _, _, Tri3d2Connectivity>(&tri10_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+}
+
+#[test]
+fn p_refine_quad4_to_quad9() {
+    let n = 3;
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(n);
+    let (quad9_mesh, origins) = p_refine_mesh::<_, _, _, Quad9d2Connectivity>(&mesh);
+
+    assert_eq!(quad9_mesh.vertices().len(), (2 * n + 1).pow(2));
+    assert_eq!(
+        origins
+            .iter()
+            .filter(|origin| matches!(origin, NodeOrigin::Centroid(_)))
+            .count(),
+        n * n
+    );
+    assert_nodes_match_origins(quad9_mesh.vertices(), mesh.vertices(), &origins);
+    assert_scalar_eq!(total_mass!(quad9_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+
+    let (coarsened_mesh, _) = p_coarsen_mesh::<_, _, _, Quad4d2Connectivity>(&quad9_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+}
+
+#[test]
+fn p_refine_tet4_to_tet10() {
+    let mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    let (tet10_mesh, origins) = p_refine_mesh::<_, _, _, Tet10Connectivity>(&mesh);
+
+    // Euler's formula V - E + F - C = 1 for a tetrahedralized ball, where each interior face
+    // is shared by two tetrahedra
+    let num_vertices = mesh.vertices().len();
+    let num_cells = mesh.connectivity().len();
+    let num_boundary_faces = mesh.find_boundary_faces().len();
+    let num_faces = (4 * num_cells + num_boundary_faces) / 2;
+    let num_edges = num_vertices + num_faces - num_cells - 1;
+    assert_eq!(tet10_mesh.vertices().len(), num_vertices + num_edges);
+    assert_nodes_match_origins(tet10_mesh.vertices(), mesh.vertices(), &origins);
+    assert_scalar_eq!(total_mass!(tet10_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+
+    let (coarsened_mesh, _) = p_coarsen_mesh::<_, _, _, Tet4Connectivity>(&tet10_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+}
+
+#[test]
+fn p_refine_hex8_to_hex20_and_hex27() {
+    let n = 2;
+    let mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(n);
+    let num_vertices = (n + 1).pow(3);
+    let num_edges = 3 * n * (n + 1).pow(2);
+
+    let (hex20_mesh, origins) = p_refine_mesh::<_, _, _, Hex20Connectivity>(&mesh);
+    assert_eq!(hex20_mesh.vertices().len(), num_vertices + num_edges);
+    assert_nodes_match_origins(hex20_mesh.vertices(), mesh.vertices(), &origins);
+    assert_scalar_eq!(total_mass!(hex20_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+    let (coarsened_mesh, _) = p_coarsen_mesh::<_, _, _, Hex8Connectivity>(&hex20_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+
+    let (hex27_mesh, origins) = p_refine_mesh::<_, _, _, Hex27Connectivity>(&mesh);
+    assert_eq!(hex27_mesh.vertices().len(), (2 * n + 1).pow(3));
+    assert_nodes_match_origins(hex27_mesh.vertices(), mesh.vertices(), &origins);
+    assert_scalar_eq!(total_mass!(hex27_mesh), total_mass!(mesh), comp = abs, tol = 1e-12);
+    let (coarsened_mesh, _) = p_coarsen_mesh::<_, _, _, Hex8Connectivity>(&hex27_mesh);
+    assert_eq!(coarsened_mesh, mesh);
+}
+
+#[test]
+fn p_coarsen_drops_unreferenced_nodes() {
+    // The Tet10 mesh refers to all vertices but 0, so that vertex 0 is dropped and the remaining
+    // vertices are relabeled in order
+    let vertices = (0..11).map(|i| Point3::new(i as f64, 0.0, 0.0)).collect();
+    let tet10_mesh =
+        Tet10Mesh::from_vertices_and_connectivity(vertices, vec![Tet10Connectivity([4, 2, 3, 1, 5, 6, 7, 8, 9, 10])]);
+    let (tet4_mesh, original_indices) = p_coarsen_mesh::<_, _, _, Tet4Connectivity>(&tet10_mesh);
+    assert_eq!(original_indices, vec![1, 2, 3, 4]);
+    assert_eq!(tet4_mesh.connectivity(), &[Tet4Connectivity([3, 1, 2, 0])]);
+    assert_eq!(tet4_mesh.vertices()[0], Point3::new(1.0, 0.0, 0.0));
+}
@@ -4,14 +4,19 @@ use fenris::element::{ElementConnectivity, FiniteElement, SurfaceFiniteElement};
 use fenris::integrate::{dependency::NoDeps, FnFunction};
 use fenris::integrate::{integrate_over_element, volume_form, ElementIntegralAssemblerBuilder};
 use fenris::io::vtk::FiniteElementMeshDataSetBuilder;
-use fenris::mesh::procedural::{create_rectangular_uniform_hex_mesh, create_rectangular_uniform_tet_mesh};
+use fenris::mesh::procedural::{
+    create_box_uniform_hex_mesh_3d, create_box_uniform_quad_mesh_2d, create_graded_hex_mesh_3d,
+    create_graded_quad_mesh_2d, create_rectangular_uniform_hex_mesh, create_rectangular_uniform_tet_mesh,
+    StructuredGrid,
+};
+use fenris::mesh::HexMesh;
 use fenris::quadrature::CanonicalMassQuadrature;
-use fenris::quadrature::Quadrature;
+use fenris::quadrature::{Quadrature, QuadraturePair3d};
 use fenris::util::global_vector_from_point_fn;
 use fenris_geometry::AxisAlignedBoundingBox3d;
-use matrixcompare::prop_assert_scalar_eq;
+use matrixcompare::{assert_scalar_eq, prop_assert_scalar_eq};
 use nalgebra::coordinates::XYZ;
-use nalgebra::{vector, Point3, Vector1, Vector4, U1};
+use nalgebra::{vector, Point2, Point3, Vector1, Vector2, Vector3, Vector4, U1};
 use proptest::prelude::*;
 use std::path::PathBuf;
 
@@ -189,3 +194,103 @@ proptest! {
         }
     }
 }
+
+#[test]
+fn box_uniform_quad_mesh_2d_basics() {
+    let mesh = create_box_uniform_quad_mesh_2d(&Vector2::new(3.0, 0.5), [6, 2]);
+    assert_eq!(mesh.vertices().len(), 7 * 3);
+    assert_eq!(mesh.connectivity().len(), 6 * 2);
+
+    let area: f64 = mesh.cell_iter().map(|quad| quad.area()).sum();
+    assert_scalar_eq!(area, 1.5, comp = abs, tol = 1e-12);
+
+    let grid = StructuredGrid::from_num_cells([6, 2]);
+    assert_eq!(grid.num_vertices(), mesh.vertices().len());
+    assert_eq!(mesh.vertices()[grid.vertex_index([6, 2])], Point2::new(3.0, 0.5));
+    assert_eq!(grid.min_side_vertices(0), vec![0, 7, 14]);
+    assert_eq!(grid.max_side_vertices(0), vec![6, 13, 20]);
+    assert_eq!(grid.min_side_vertices(1), (0..7).collect::<Vec<_>>());
+    assert_eq!(grid.max_side_vertices(1), (14..21).collect::<Vec<_>>());
+    assert!(grid
+        .max_side_vertices(0)
+        .iter()
+        .all(|&i| mesh.vertices()[i].x == 3.0));
+}
+
+#[test]
+fn box_uniform_hex_mesh_3d_basics() {
+    let extents = Vector3::new(2.0, 1.0, 0.5);
+    let mesh = create_box_uniform_hex_mesh_3d(&extents, [4, 3, 2]);
+    assert_eq!(mesh.vertices().len(), 5 * 4 * 3);
+    assert_eq!(mesh.connectivity().len(), 4 * 3 * 2);
+
+    let quadrature = fenris::quadrature::tensor::hexahedron_gauss(2);
+    let volume = total_volume(&mesh, &quadrature);
+    assert_scalar_eq!(volume, 1.0, comp = abs, tol = 1e-12);
+
+    let grid = StructuredGrid::from_num_cells([4, 3, 2]);
+    assert_eq!(grid.num_vertices_per_axis(), [5, 4, 3]);
+    for axis in 0..3 {
+        let min_side = grid.min_side_vertices(axis);
+        let max_side = grid.max_side_vertices(axis);
+        assert_eq!(min_side.len() * grid.num_vertices_per_axis()[axis], grid.num_vertices());
+        assert!(min_side.iter().all(|&i| mesh.vertices()[i][axis] == 0.0));
+        assert!(max_side
+            .iter()
+            .all(|&i| mesh.vertices()[i][axis] == extents[axis]));
+    }
+}
+
+#[test]
+fn graded_meshes_honor_coordinates_exactly() {
+    // Cells are clustered near x = 0 and y = 1
+    let x = [0.0, 0.01, 0.03, 0.1, 0.4, 1.0];
+    let y = [0.0, 0.7, 0.95, 0.99, 1.0];
+    let z = [-1.0, 0.0, 2.0];
+
+    let quad_mesh = create_graded_quad_mesh_2d(&x, &y);
+    assert_eq!(quad_mesh.connectivity().len(), 5 * 4);
+    let grid = StructuredGrid::from_num_cells([5, 4]);
+    for (j, &y_j) in y.iter().enumerate() {
+        for (i, &x_i) in x.iter().enumerate() {
+            assert_eq!(quad_mesh.vertices()[grid.vertex_index([i, j])], Point2::new(x_i, y_j));
+        }
+    }
+    let area: f64 = quad_mesh.cell_iter().map(|quad| quad.area()).sum();
+    assert_scalar_eq!(area, 1.0, comp = abs, tol = 1e-12);
+
+    let hex_mesh = create_graded_hex_mesh_3d(&x, &y, &z);
+    assert_eq!(hex_mesh.connectivity().len(), 5 * 4 * 2);
+    let grid = StructuredGrid::from_num_cells([5, 4, 2]);
+    for (k, &z_k) in z.iter().enumerate() {
+        for (j, &y_j) in y.iter().enumerate() {
+            for (i, &x_i) in x.iter().enumerate() {
+                let v = hex_mesh.vertices()[grid.vertex_index([i, j, k])];
+                assert_eq!(v, Point3::new(x_i, y_j, z_k));
+            }
+        }
+    }
+    let quadrature = fenris::quadrature::tensor::hexahedron_gauss(2);
+    assert_scalar_eq!(total_volume(&hex_mesh, &quadrature), 3.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "strictly increasing")]
+fn graded_mesh_panics_for_non_monotone_coordinates() {
+    let _ = create_graded_quad_mesh_2d(&[0.0, 0.5, 0.4, 1.0], &[0.0, 1.0]);
+}
+
+fn total_volume(mesh: &HexMesh<f64>, quadrature: &QuadraturePair3d<f64>) -> f64 {
+    let (weights, points) = quadrature;
+    let mut volume = 0.0;
+    for connectivity in mesh.connectivity() {
+        let element = connectivity.element(mesh.vertices()).unwrap();
+        for (w, xi) in weights.iter().zip(points) {
+            let det = element.reference_jacobian(xi).determinant();
+            // Elements must be positively oriented
+            assert!(det > 0.0);
+            volume += w * det;
+        }
+    }
+    volume
+}
@@ -0,0 +1,193 @@
+use fenris::element::{Hex8Element, Quad4d2Element, Tet4Element, Tri3d2Element};
+use fenris::mesh::procedural::create_unit_box_uniform_hex_mesh_3d;
+use fenris::mesh::quality::{
+    compute_element_metric, tetrahedron_dihedral_angles, tetrahedron_min_max_dihedral_angles, ElementQuality,
+    QualitySummary, SimplexQuality,
+};
+use fenris::nalgebra::{Point2, Point3};
+use matrixcompare::assert_scalar_eq;
+use std::f64::consts::PI;
+
+fn regular_tetrahedron() -> Tet4Element<f64> {
+    Tet4Element::from_vertices([
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(-1.0, 1.0, -1.0),
+        Point3::new(1.0, -1.0, -1.0),
+        Point3::new(-1.0, -1.0, 1.0),
+    ])
+}
+
+fn unit_cube_vertices() -> [Point3<f64>; 8] {
+    [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(0.0, 1.0, 1.0),
+    ]
+}
+
+#[test]
+fn regular_tetrahedron_quality() {
+    let tet = regular_tetrahedron();
+    assert_scalar_eq!(tet.aspect_ratio(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(tet.scaled_jacobian(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(tet.radius_ratio(), 1.0, comp = abs, tol = 1e-12);
+    for angle in tetrahedron_dihedral_angles(&tet) {
+        assert_scalar_eq!(angle, (1.0f64 / 3.0).acos(), comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn reference_tetrahedron_quality() {
+    // The reference tetrahedron has three right angles at its origin vertex, and the remaining corners
+    // are spanned by edges of lengths 2, 2 sqrt(2) and 2 sqrt(2), so that the minimum corner
+    // determinant ratio is 8 / 16 = 1 / 2
+    let tet = Tet4Element::<f64>::reference();
+    assert_scalar_eq!(tet.scaled_jacobian(), 0.5 * 2.0f64.sqrt(), comp = abs, tol = 1e-12);
+    // V = 4/3, S = 6 + 2 sqrt(3) and the circumradius is sqrt(3), so that r = 4 / (6 + 2 sqrt(3))
+    let r = 4.0 / (6.0 + 2.0 * 3.0f64.sqrt());
+    assert_scalar_eq!(tet.radius_ratio(), 3.0 * r / 3.0f64.sqrt(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(
+        tet.aspect_ratio(),
+        2.0 * 2.0f64.sqrt() / (2.0 * 6.0f64.sqrt() * r),
+        comp = abs,
+        tol = 1e-12
+    );
+    let (min_angle, max_angle) = tetrahedron_min_max_dihedral_angles(&tet);
+    assert_scalar_eq!(min_angle, (1.0f64 / 3.0f64.sqrt()).acos(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(max_angle, PI / 2.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn degenerate_sliver_tetrahedron_quality() {
+    // All four vertices lie on the same circle in the plane z = 0, so the edges are all
+    // of comparable length, but the tetrahedron has zero volume
+    let tet = Tet4Element::from_vertices([
+        Point3::new(1.0f64, 0.0, 0.0),
+        Point3::new(-1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, -1.0, 0.0),
+    ]);
+    assert_eq!(tet.scaled_jacobian(), 0.0);
+    assert_eq!(tet.radius_ratio(), 0.0);
+    assert!(tet.aspect_ratio().is_infinite());
+    assert_eq!(tetrahedron_dihedral_angles(&tet), [PI, 0.0, 0.0, 0.0, 0.0, PI]);
+    assert_eq!(tetrahedron_min_max_dihedral_angles(&tet), (0.0, PI));
+}
+
+#[test]
+fn nearly_degenerate_sliver_tetrahedron_quality() {
+    let tet = Tet4Element::from_vertices([
+        Point3::new(-1.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.01),
+        Point3::new(0.0, -1.0, 0.01),
+    ]);
+    assert!(tet.scaled_jacobian() > 0.0);
+    assert!(tet.scaled_jacobian() < 0.02);
+    assert!(tet.radius_ratio() < 0.02);
+    assert!(tet.aspect_ratio() > 50.0);
+    let (min_angle, max_angle) = tetrahedron_min_max_dihedral_angles(&tet);
+    assert!(min_angle < 1.0f64.to_radians());
+    assert!(max_angle > 178.0f64.to_radians());
+}
+
+#[test]
+fn skewed_hexahedron_quality() {
+    let cube = Hex8Element::from_vertices(unit_cube_vertices());
+    assert_scalar_eq!(cube.aspect_ratio(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(cube.scaled_jacobian(), 1.0, comp = abs, tol = 1e-12);
+
+    // Shear the top face by a unit distance in the x-direction. The vertical edges then have length sqrt(2),
+    // and at every corner the vertical edge makes an angle of 45 degrees with the bottom face
+    let mut vertices = unit_cube_vertices();
+    for v in &mut vertices[4..] {
+        v.x += 1.0;
+    }
+    let skewed = Hex8Element::from_vertices(vertices);
+    assert_scalar_eq!(skewed.aspect_ratio(), 2.0f64.sqrt(), comp = abs, tol = 1e-12);
+    assert_scalar_eq!(skewed.scaled_jacobian(), 1.0 / 2.0f64.sqrt(), comp = abs, tol = 1e-12);
+
+    // Swapping the top and bottom faces inverts the element
+    let mut vertices = unit_cube_vertices();
+    vertices.rotate_left(4);
+    let inverted = Hex8Element::from_vertices(vertices);
+    assert_scalar_eq!(inverted.scaled_jacobian(), -1.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn planar_element_quality() {
+    let equilateral = Tri3d2Element::from_vertices([
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(1.0, 3.0f64.sqrt()),
+    ]);
+    assert_scalar_eq!(equilateral.aspect_ratio(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(equilateral.scaled_jacobian(), 1.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(equilateral.radius_ratio(), 1.0, comp = abs, tol = 1e-12);
+
+    // The right isosceles triangle has 45 degree corners, and r = 2 - sqrt(2), R = sqrt(2)
+    let right = Tri3d2Element::<f64>::reference();
+    assert_scalar_eq!(
+        right.scaled_jacobian(),
+        2.0 / 3.0f64.sqrt() / 2.0f64.sqrt(),
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        right.radius_ratio(),
+        2.0 * (2.0 - 2.0f64.sqrt()) / 2.0f64.sqrt(),
+        comp = abs,
+        tol = 1e-12
+    );
+
+    let rectangle = Quad4d2Element::from_vertices([
+        Point2::new(0.0, 0.0),
+        Point2::new(3.0, 0.0),
+        Point2::new(3.0, 1.0),
+        Point2::new(0.0, 1.0),
+    ]);
+    assert_scalar_eq!(rectangle.aspect_ratio(), 3.0, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(rectangle.scaled_jacobian(), 1.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn quality_summary_over_mesh() {
+    let mut mesh = create_unit_box_uniform_hex_mesh_3d::<f64>(2);
+    // Move the center vertex, which distorts all eight elements
+    let center = mesh
+        .vertices()
+        .iter()
+        .position(|v| *v == Point3::new(0.5, 0.5, 0.5))
+        .unwrap();
+    mesh.vertices_mut()[center].x += 0.25;
+
+    let scaled_jacobians = compute_element_metric(&mesh, |element| element.scaled_jacobian());
+    assert_eq!(scaled_jacobians.len(), 8);
+    let summary = QualitySummary::from_values(&scaled_jacobians, 4).unwrap();
+    assert!(summary.min < 1.0);
+    assert!(summary.min > 0.0);
+    assert_eq!(summary.min, scaled_jacobians[summary.min_index]);
+    assert_eq!(summary.max, scaled_jacobians[summary.max_index]);
+    assert_eq!(summary.histogram.iter().sum::<usize>(), 8);
+    assert_eq!(summary.bin_edges().len(), 5);
+}
+
+#[test]
+fn quality_summary_statistics() {
+    let values = [0.5, 1.0, 0.0, 0.25, 0.75, 1.0];
+    let summary = QualitySummary::from_values(&values, 4).unwrap();
+    assert_eq!(summary.min, 0.0);
+    assert_eq!(summary.max, 1.0);
+    assert_eq!(summary.min_index, 2);
+    assert_eq!(summary.max_index, 1);
+    assert_scalar_eq!(summary.mean, 3.5 / 6.0, comp = abs, tol = 1e-14);
+    assert_eq!(summary.histogram, vec![1, 1, 1, 3]);
+    assert_eq!(summary.bin_edges(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+
+    assert!(QualitySummary::<f64>::from_values(&[], 4).is_none());
+}
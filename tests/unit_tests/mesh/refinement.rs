@@ -1,9 +1,31 @@
 use crate::export_mesh_vtk;
-use fenris::connectivity::Tri3d2Connectivity;
-use fenris::mesh::refinement::{refine_uniformly, refine_uniformly_repeat};
-use fenris::mesh::Mesh;
+use fenris::allocators::BiDimAllocator;
+use fenris::assembly::global::{apply_homogeneous_dirichlet_bc_csr, CsrAssembler};
+use fenris::assembly::local::{Density, ElementEllipticAssemblerBuilder, ElementMassAssembler};
+use fenris::assembly::operators::LaplaceOperator;
+use fenris::connectivity::{
+    Hex27Connectivity, Quad9d2Connectivity, Tet10Connectivity, Tet4Connectivity, Tri3d2Connectivity, Tri6d2Connectivity,
+};
+use fenris::element::{ElementConnectivity, FiniteElement};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::refinement::{
+    refine_uniformly, refine_uniformly_repeat, refine_uniformly_with_parents, refine_uniformly_with_prolongation,
+    RefineConnectivity, UniformRefinement,
+};
+use fenris::mesh::{Mesh, Mesh2d, Mesh3d};
+use fenris::nalgebra::{
+    point, DMatrix, DVector, DefaultAllocator, Matrix2, Matrix3, OPoint, Point2, Point3, Vector2, Vector3, U3,
+};
+use fenris::quadrature;
+use fenris::quadrature::{CanonicalMassQuadrature, CanonicalStiffnessQuadrature, QuadraturePair3d};
+use fenris::SmallDim;
 use insta::assert_debug_snapshot;
-use nalgebra::point;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use std::collections::HashSet;
+use std::hash::Hash;
 
 #[test]
 fn uniform_refinement_tri3d2() {
@@ -38,3 +60,329 @@ fn uniform_refinement_tri3d2() {
     assert_debug_snapshot!(refined1);
     assert_debug_snapshot!(refined2);
 }
+
+/// Applies an arbitrary affine transformation to the vertices of a mesh in 2D.
+fn transform_2d<C>(mut mesh: Mesh2d<f64, C>) -> Mesh2d<f64, C> {
+    let a = Matrix2::new(1.5, 0.4, -0.2, 0.8);
+    mesh.transform_vertices(|v| *v = Point2::from(a * v.coords + Vector2::new(0.3, -0.1)));
+    mesh
+}
+
+/// Applies an arbitrary affine transformation to the vertices of a mesh in 3D.
+fn transform_3d<C>(mut mesh: Mesh3d<f64, C>) -> Mesh3d<f64, C> {
+    let a = Matrix3::new(1.5, 0.4, 0.1, -0.2, 0.8, 0.3, 0.1, -0.3, 1.2);
+    mesh.transform_vertices(|v| *v = Point3::from(a * v.coords + Vector3::new(0.3, -0.1, 0.2)));
+    mesh
+}
+
+/// Checks that the refined mesh is conforming and consistently oriented, and that the
+/// prolongation operator exactly reproduces the given field, which must be contained in the
+/// finite element space on the original mesh.
+fn assert_prolongation_reproduces_field<D, C>(
+    mesh: &Mesh<f64, D, C>,
+    field: impl Fn(&OPoint<f64, D>) -> f64,
+    reference_interior_point: &OPoint<f64, D>,
+) where
+    D: SmallDim,
+    C: ElementConnectivity<f64, GeometryDim = D, ReferenceDim = D>,
+    UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+    <UniformRefinement as RefineConnectivity<C>>::VertexLabel: Eq + Hash,
+    DefaultAllocator: BiDimAllocator<f64, D, D>,
+{
+    let (refined_mesh, prolongation) = refine_uniformly_with_prolongation(mesh, 1).unwrap();
+    assert_eq!(refined_mesh.vertices(), refine_uniformly(mesh).vertices());
+    assert_eq!(prolongation.nrows(), refined_mesh.vertices().len());
+    assert_eq!(prolongation.ncols(), mesh.vertices().len());
+
+    // Shared nodes must not be duplicated
+    let vertices = refined_mesh.vertices();
+    for (i, v_i) in vertices.iter().enumerate() {
+        for v_j in &vertices[i + 1..] {
+            assert!((v_i - v_j).norm() > 1e-12);
+        }
+    }
+
+    // Children are stored contiguously for each parent, and must have the same orientation
+    let jacobian_determinant = |connectivity: &C, vertices: &[OPoint<f64, D>]| {
+        let element = connectivity.element(vertices).unwrap();
+        element
+            .reference_jacobian(reference_interior_point)
+            .determinant()
+    };
+    let num_children = refined_mesh.connectivity().len() / mesh.connectivity().len();
+    for (i, child) in refined_mesh.connectivity().iter().enumerate() {
+        let parent = &mesh.connectivity()[i / num_children];
+        let child_det = jacobian_determinant(child, vertices);
+        let parent_det = jacobian_determinant(parent, mesh.vertices());
+        assert!(child_det * parent_det > 0.0);
+    }
+
+    let u_coarse = DVector::from_iterator(mesh.vertices().len(), mesh.vertices().iter().map(&field));
+    let u_fine = DVector::from_iterator(vertices.len(), vertices.iter().map(&field));
+    assert_matrix_eq!(&prolongation * &u_coarse, u_fine, comp = abs, tol = 1e-12);
+
+    // With multiple components per node, the prolongation acts on each component separately
+    let (_, block_prolongation) = refine_uniformly_with_prolongation(mesh, 3).unwrap();
+    let interleave = |u: &DVector<f64>| DVector::from_fn(3 * u.len(), |i, _| (i % 3) as f64 * u[i / 3] - 1.0);
+    let u_coarse_block = interleave(&u_coarse);
+    let u_fine_block = interleave(&u_fine);
+    assert_matrix_eq!(
+        &block_prolongation * &u_coarse_block,
+        u_fine_block,
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+fn linear_2d(x: &Point2<f64>) -> f64 {
+    2.0 + 3.0 * x.x - 1.5 * x.y
+}
+
+fn quadratic_2d(x: &Point2<f64>) -> f64 {
+    linear_2d(x) + x.x * x.x - 2.0 * x.x * x.y + 0.5 * x.y * x.y
+}
+
+fn linear_3d(x: &Point3<f64>) -> f64 {
+    2.0 + 3.0 * x.x - 1.5 * x.y + 0.5 * x.z
+}
+
+fn quadratic_3d(x: &Point3<f64>) -> f64 {
+    linear_3d(x) + x.x * x.x - 2.0 * x.x * x.y + 0.5 * x.y * x.z - x.z * x.z
+}
+
+#[test]
+fn uniform_refinement_prolongation_reproduces_linear_fields() {
+    let tri_mesh = transform_2d(create_unit_square_uniform_tri_mesh_2d(2));
+    assert_prolongation_reproduces_field(&tri_mesh, linear_2d, &point![-1.0 / 3.0, -1.0 / 3.0]);
+
+    let quad_mesh = transform_2d(create_unit_square_uniform_quad_mesh_2d(2));
+    assert_prolongation_reproduces_field(&quad_mesh, linear_2d, &point![0.0, 0.0]);
+    assert_eq!(refine_uniformly(&quad_mesh).vertices().len(), 25);
+    assert_eq!(refine_uniformly(&quad_mesh).connectivity().len(), 16);
+
+    let tet_mesh = transform_3d(create_unit_box_uniform_tet_mesh_3d(1));
+    assert_prolongation_reproduces_field(&tet_mesh, linear_3d, &point![-0.5, -0.5, -0.5]);
+    assert_eq!(
+        refine_uniformly(&tet_mesh).connectivity().len(),
+        8 * tet_mesh.connectivity().len()
+    );
+
+    let hex_mesh = transform_3d(create_unit_box_uniform_hex_mesh_3d(2));
+    assert_prolongation_reproduces_field(&hex_mesh, linear_3d, &point![0.0, 0.0, 0.0]);
+    assert_eq!(refine_uniformly(&hex_mesh).vertices().len(), 125);
+    assert_eq!(refine_uniformly(&hex_mesh).connectivity().len(), 64);
+}
+
+#[test]
+fn uniform_refinement_prolongation_reproduces_quadratic_fields() {
+    let tri6_mesh = transform_2d(Mesh2d::<f64, Tri6d2Connectivity>::from(
+        create_unit_square_uniform_tri_mesh_2d(2),
+    ));
+    assert_prolongation_reproduces_field(&tri6_mesh, quadratic_2d, &point![-1.0 / 3.0, -1.0 / 3.0]);
+
+    let quad9_mesh = transform_2d(Mesh2d::<f64, Quad9d2Connectivity>::from(
+        create_unit_square_uniform_quad_mesh_2d(2),
+    ));
+    assert_prolongation_reproduces_field(&quad9_mesh, quadratic_2d, &point![0.0, 0.0]);
+    assert_eq!(refine_uniformly(&quad9_mesh).vertices().len(), 81);
+
+    let tet10_mesh = transform_3d(Mesh3d::<f64, Tet10Connectivity>::from(
+        &create_unit_box_uniform_tet_mesh_3d(1),
+    ));
+    assert_prolongation_reproduces_field(&tet10_mesh, quadratic_3d, &point![-0.5, -0.5, -0.5]);
+
+    let hex27_mesh = transform_3d(Mesh3d::<f64, Hex27Connectivity>::from(
+        &create_unit_box_uniform_hex_mesh_3d(1),
+    ));
+    assert_prolongation_reproduces_field(&hex27_mesh, quadratic_3d, &point![0.0, 0.0, 0.0]);
+    assert_eq!(refine_uniformly(&hex27_mesh).vertices().len(), 125);
+}
+
+#[test]
+fn galerkin_coarse_operator_matches_coarse_assembly() {
+    // Laplace operator on quadratic quadrilaterals
+    {
+        let coarse_mesh = transform_2d(Mesh2d::<f64, Quad9d2Connectivity>::from(
+            create_unit_square_uniform_quad_mesh_2d(2),
+        ));
+        let (fine_mesh, prolongation) = refine_uniformly_with_prolongation(&coarse_mesh, 1).unwrap();
+        let assemble_laplace = |mesh: &Mesh2d<f64, Quad9d2Connectivity>| {
+            let u = DVector::zeros(mesh.vertices().len());
+            let qtable = mesh.canonical_stiffness_quadrature();
+            let assembler = ElementEllipticAssemblerBuilder::new()
+                .with_finite_element_space(mesh)
+                .with_operator(&LaplaceOperator)
+                .with_quadrature_table(&qtable)
+                .with_u(&u)
+                .build();
+            DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap())
+        };
+        let p = DMatrix::from(&prolongation);
+        let galerkin = p.transpose() * assemble_laplace(&fine_mesh) * &p;
+        assert_matrix_eq!(galerkin, assemble_laplace(&coarse_mesh), comp = abs, tol = 1e-12);
+    }
+
+    // Mass matrix with vector-valued unknowns on linear tetrahedra
+    {
+        let coarse_mesh = transform_3d(create_unit_box_uniform_tet_mesh_3d(2));
+        let (fine_mesh, prolongation) = refine_uniformly_with_prolongation(&coarse_mesh, 3).unwrap();
+        let assemble_mass = |mesh: &Mesh3d<f64, Tet4Connectivity>| {
+            let qtable = mesh
+                .canonical_mass_quadrature()
+                .with_uniform_data(Density(2.0));
+            let assembler = ElementMassAssembler::with_solution_dim(3)
+                .with_space(mesh)
+                .with_quadrature_table(&qtable);
+            DMatrix::from(&CsrAssembler::default().assemble(&assembler).unwrap())
+        };
+        let p = DMatrix::from(&prolongation);
+        let galerkin = p.transpose() * assemble_mass(&fine_mesh) * &p;
+        assert_matrix_eq!(galerkin, assemble_mass(&coarse_mesh), comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn two_grid_correction_reduces_poisson_residual() {
+    let coarse_mesh: Mesh2d<f64, Tri3d2Connectivity> = create_unit_square_uniform_tri_mesh_2d(8);
+    let (fine_mesh, prolongation) = refine_uniformly_with_prolongation(&coarse_mesh, 1).unwrap();
+    let boundary_nodes: Vec<_> = fine_mesh
+        .vertices()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.x.min(v.y) == 0.0 || v.x.max(v.y) == 1.0)
+        .map(|(i, _)| i)
+        .collect();
+
+    let u = DVector::zeros(fine_mesh.vertices().len());
+    let qtable = fine_mesh.canonical_stiffness_quadrature();
+    let assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&fine_mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u)
+        .build();
+    let mut a_fine = CsrAssembler::default().assemble(&assembler).unwrap();
+    apply_homogeneous_dirichlet_bc_csr(&mut a_fine, &boundary_nodes, 1);
+    let restriction = prolongation.transpose();
+    let a_coarse = DMatrix::from(&(&restriction * &(&a_fine * &prolongation)));
+    let coarse_solver = a_coarse.cholesky().unwrap();
+
+    let n = fine_mesh.vertices().len();
+    let b = DVector::from_fn(n, |i, _| if boundary_nodes.contains(&i) { 0.0 } else { 1.0 });
+    let diagonal = a_fine.diagonal_as_csr();
+    let inv_diagonal = DVector::from_iterator(n, diagonal.values().iter().map(|d| 1.0 / d));
+    let smooth = |u: &mut DVector<f64>| {
+        for _ in 0..2 {
+            let r = &b - &a_fine * &*u;
+            *u += (2.0 / 3.0) * r.component_mul(&inv_diagonal);
+        }
+    };
+
+    let mut u = DVector::zeros(n);
+    let mut residual_norm = b.norm();
+    let initial_residual_norm = residual_norm;
+    for _ in 0..20 {
+        smooth(&mut u);
+        let r = &b - &a_fine * &u;
+        u += &prolongation * &coarse_solver.solve(&(&restriction * &r));
+        smooth(&mut u);
+
+        let new_residual_norm = (&b - &a_fine * &u).norm();
+        assert!(new_residual_norm <= 0.5 * residual_norm);
+        residual_norm = new_residual_norm;
+    }
+    assert!(residual_norm <= 1e-6 * initial_residual_norm);
+}
+
+/// Computes the volume of each element in the mesh with the given quadrature rule.
+fn element_volumes<C>(mesh: &Mesh3d<f64, C>, quadrature: &QuadraturePair3d<f64>) -> Vec<f64>
+where
+    C: ElementConnectivity<f64, GeometryDim = U3, ReferenceDim = U3>,
+{
+    let (weights, points) = quadrature;
+    mesh.connectivity()
+        .iter()
+        .map(|connectivity| {
+            let element = connectivity.element(mesh.vertices()).unwrap();
+            weights
+                .iter()
+                .zip(points)
+                .map(|(w, xi)| w * element.reference_jacobian(xi).determinant().abs())
+                .sum()
+        })
+        .collect()
+}
+
+/// Repeatedly refines the mesh and checks that the parent map is consistent with the element volumes.
+fn assert_repeated_refinement_preserves_volume<C>(
+    mesh: Mesh3d<f64, C>,
+    quadrature: &QuadraturePair3d<f64>,
+    expected_vertex_counts: &[usize],
+) where
+    C: ElementConnectivity<f64, GeometryDim = U3, ReferenceDim = U3>,
+    UniformRefinement: RefineConnectivity<C, OutputConnectivity = C>,
+    <UniformRefinement as RefineConnectivity<C>>::VertexLabel: Eq + Hash,
+{
+    let mut mesh = mesh;
+    for &expected_vertex_count in expected_vertex_counts {
+        let (refined, parents) = refine_uniformly_with_parents(&mesh);
+        let num_parents = mesh.connectivity().len();
+        assert_eq!(refined.connectivity().len(), 8 * num_parents);
+        assert_eq!(refined.vertices().len(), expected_vertex_count);
+        assert_eq!(parents.len(), refined.connectivity().len());
+
+        let parent_volumes = element_volumes(&mesh, quadrature);
+        let mut children_volumes = vec![0.0; num_parents];
+        let mut children_counts = vec![0; num_parents];
+        for (&parent, volume) in parents.iter().zip(element_volumes(&refined, quadrature)) {
+            children_volumes[parent] += volume;
+            children_counts[parent] += 1;
+        }
+        assert!(children_counts.iter().all(|&count| count == 8));
+        assert_matrix_eq!(
+            DVector::from(children_volumes),
+            DVector::from(parent_volumes),
+            comp = abs,
+            tol = 1e-14
+        );
+
+        let total_volume: f64 = element_volumes(&refined, quadrature).iter().sum();
+        assert_scalar_eq!(total_volume, 1.0, comp = abs, tol = 1e-13);
+        mesh = refined;
+    }
+}
+
+#[test]
+fn repeated_uniform_refinement_with_parents_hex8() {
+    // A cube subdivided into 2^k cells per dimension has (2^k + 1)^3 vertices
+    let mesh = create_unit_box_uniform_hex_mesh_3d(1);
+    let quadrature = quadrature::tensor::hexahedron_gauss(2);
+    assert_repeated_refinement_preserves_volume(mesh, &quadrature, &[27, 125, 729]);
+}
+
+#[test]
+fn repeated_uniform_refinement_with_parents_tet4() {
+    // Uniform refinement adds a single vertex on every edge of the mesh
+    fn count_edges(mesh: &Mesh3d<f64, Tet4Connectivity>) -> usize {
+        let mut edges = HashSet::new();
+        for connectivity in mesh.connectivity() {
+            let v = connectivity.0;
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.insert((v[i].min(v[j]), v[i].max(v[j])));
+                }
+            }
+        }
+        edges.len()
+    }
+
+    let mesh = create_unit_box_uniform_tet_mesh_3d(1);
+    let mut expected_vertex_counts = Vec::new();
+    let mut current = mesh.clone();
+    for _ in 0..3 {
+        expected_vertex_counts.push(current.vertices().len() + count_edges(&current));
+        current = refine_uniformly(&current);
+    }
+    let quadrature = quadrature::total_order::tetrahedron(1).unwrap();
+    assert_repeated_refinement_preserves_volume(mesh, &quadrature, &expected_vertex_counts);
+}
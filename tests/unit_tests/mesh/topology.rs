@@ -0,0 +1,160 @@
+use fenris::connectivity::Connectivity;
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::topology::MeshTopology;
+use fenris::mesh::{HexMesh, Mesh};
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, DimName, Point3, Scalar, Vector3};
+
+/// Checks that the topology is consistent with the local faces of the mesh and with
+/// [`Mesh::find_boundary_faces`].
+fn assert_topology_consistent_with_mesh<T, D, C>(mesh: &Mesh<T, D, C>, topology: &MeshTopology)
+where
+    T: Scalar,
+    D: DimName,
+    C: Connectivity,
+    DefaultAllocator: Allocator<T, D>,
+{
+    assert_eq!(topology.num_elements(), mesh.connectivity().len());
+    for (element_index, element) in mesh.connectivity().iter().enumerate() {
+        let faces = topology.element_faces(element_index);
+        let neighbors = topology.element_neighbors(element_index);
+        assert_eq!(faces.len(), element.num_faces());
+        assert_eq!(neighbors.len(), element.num_faces());
+        for (local_index, (&face_index, &neighbor)) in faces.iter().zip(neighbors).enumerate() {
+            let mut face_vertices = element
+                .get_face_connectivity(local_index)
+                .unwrap()
+                .vertex_indices()
+                .to_vec();
+            face_vertices.sort_unstable();
+            assert_eq!(topology.face_vertices(face_index), face_vertices.as_slice());
+
+            let face_elements = topology.face_elements(face_index);
+            assert!(face_elements.contains(&(element_index, local_index)));
+            match neighbor {
+                Some(neighbor) => {
+                    assert_eq!(face_elements.len(), 2);
+                    assert_ne!(neighbor, element_index);
+                    assert!(topology
+                        .element_neighbors(neighbor)
+                        .contains(&Some(element_index)));
+                }
+                None => assert!(topology.is_boundary_face(face_index)),
+            }
+        }
+    }
+
+    let mut expected_boundary_faces: Vec<_> = mesh
+        .find_boundary_faces()
+        .into_iter()
+        .map(|(_, element_index, local_index)| (element_index, local_index))
+        .collect();
+    expected_boundary_faces.sort_unstable();
+    assert_eq!(topology.boundary_faces(), expected_boundary_faces.as_slice());
+}
+
+#[test]
+fn mesh_topology_of_2x2x2_hex_mesh() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(2);
+    let topology = MeshTopology::from_mesh(&mesh);
+    assert_topology_consistent_with_mesh(&mesh, &topology);
+
+    // Each of the 3 interior planes is split into 4 faces, and each of the 6 sides of the box
+    // is split into 4 faces
+    let num_interior_faces = (0..topology.num_faces())
+        .filter(|&face| topology.face_elements(face).len() == 2)
+        .count();
+    assert_eq!(num_interior_faces, 12);
+    assert_eq!(topology.boundary_faces().len(), 24);
+    assert_eq!(topology.num_faces(), 36);
+
+    let centroid = |element_index: usize| {
+        let vertices = mesh.connectivity()[element_index].0;
+        Point3::from(
+            vertices
+                .iter()
+                .map(|&v| mesh.vertices()[v].coords)
+                .sum::<Vector3<f64>>()
+                / 8.0,
+        )
+    };
+    let find_element = |x: [f64; 3]| {
+        (0..mesh.connectivity().len())
+            .find(|&i| (centroid(i) - Point3::from(x)).norm() < 1e-12)
+            .unwrap()
+    };
+
+    // Every element is in a corner of the box, with three neighbors and three boundary faces
+    for element_index in 0..mesh.connectivity().len() {
+        let neighbors = topology.element_neighbors(element_index);
+        assert_eq!(neighbors.iter().flatten().count(), 3);
+        for neighbor in neighbors.iter().flatten() {
+            // Neighbors are translated by half the box along a single axis
+            let d = centroid(*neighbor) - centroid(element_index);
+            assert_eq!(d.iter().filter(|d_i| d_i.abs() > 1e-12).count(), 1);
+            assert!((d.norm() - 0.5).abs() < 1e-12);
+        }
+    }
+
+    let origin_element = find_element([0.25, 0.25, 0.25]);
+    let mut neighbors: Vec<_> = topology
+        .element_neighbors(origin_element)
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+    neighbors.sort_unstable();
+    let mut expected_neighbors = vec![
+        find_element([0.75, 0.25, 0.25]),
+        find_element([0.25, 0.75, 0.25]),
+        find_element([0.25, 0.25, 0.75]),
+    ];
+    expected_neighbors.sort_unstable();
+    assert_eq!(neighbors, expected_neighbors);
+    assert!(!topology
+        .element_neighbors(origin_element)
+        .contains(&Some(find_element([0.75, 0.75, 0.75]))));
+}
+
+#[test]
+fn mesh_topology_of_2d_meshes() {
+    let quad_mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let topology = MeshTopology::from_mesh(&quad_mesh);
+    assert_topology_consistent_with_mesh(&quad_mesh, &topology);
+    assert_eq!(topology.num_faces(), 12);
+    assert_eq!(topology.boundary_faces().len(), 8);
+
+    // Each quad is split into two triangles, which adds a diagonal face per quad
+    let tri_mesh = create_unit_square_uniform_tri_mesh_2d::<f64>(2);
+    let topology = MeshTopology::from_mesh(&tri_mesh);
+    assert_topology_consistent_with_mesh(&tri_mesh, &topology);
+    assert_eq!(topology.num_faces(), 16);
+    assert_eq!(topology.boundary_faces().len(), 8);
+}
+
+#[test]
+fn mesh_topology_of_tet_mesh() {
+    let mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    let topology = MeshTopology::from_mesh(&mesh);
+    assert_topology_consistent_with_mesh(&mesh, &topology);
+
+    // Every local face is either a boundary face or one of the two sides of an interior face
+    let num_local_faces = 4 * mesh.connectivity().len();
+    let num_interior_faces = topology.num_faces() - topology.boundary_faces().len();
+    assert_eq!(
+        topology.boundary_faces().len() + 2 * num_interior_faces,
+        num_local_faces
+    );
+}
+
+#[test]
+fn mesh_topology_of_empty_mesh() {
+    let mesh: HexMesh<f64> = Mesh::from_vertices_and_connectivity(Vec::new(), Vec::new());
+    let topology = MeshTopology::from_mesh(&mesh);
+    assert_eq!(topology.num_elements(), 0);
+    assert_eq!(topology.num_faces(), 0);
+    assert!(topology.boundary_faces().is_empty());
+}
@@ -0,0 +1,247 @@
+use fenris::connectivity::{Hex8Connectivity, Quad4d2Connectivity, Tet4Connectivity, Tri3d2Connectivity};
+use fenris::element::{Hex8Element, Quad4d2Element, Tet4Element, Tri3d2Element};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_box_uniform_tet_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{
+    fix_orientation, validate, HexMesh, MeshValidationReport, QuadMesh2d, Tet4Mesh, TriangleMesh2d, ValidateElement,
+};
+use fenris::nalgebra::{Point2, Point3};
+use matrixcompare::assert_scalar_eq;
+
+fn unit_square_vertices() -> Vec<Point2<f64>> {
+    vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(0.0, 1.0),
+    ]
+}
+
+fn unit_cube_vertices() -> Vec<Point3<f64>> {
+    vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.0, 1.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(0.0, 1.0, 1.0),
+    ]
+}
+
+#[test]
+fn signed_volumes_of_reference_elements() {
+    assert_scalar_eq!(
+        Tri3d2Element::<f64>::reference().signed_volume(),
+        2.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        Quad4d2Element::<f64>::reference().signed_volume(),
+        4.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        Tet4Element::<f64>::reference().signed_volume(),
+        4.0 / 3.0,
+        comp = abs,
+        tol = 1e-12
+    );
+    assert_scalar_eq!(
+        Hex8Element::<f64>::reference().signed_volume(),
+        8.0,
+        comp = abs,
+        tol = 1e-12
+    );
+}
+
+#[test]
+fn trilinear_hex_volume_is_exact() {
+    // Move a single vertex of the unit cube, so that the hexahedron is no longer a parallelepiped.
+    // The volume then follows from splitting the unit cube into the part below the bilinear
+    // top face z = 1 + xy / 2
+    let mut vertices = unit_cube_vertices();
+    vertices[6].z = 1.5;
+    let hex = Hex8Element::from_vertices(vertices.try_into().unwrap());
+    assert_scalar_eq!(hex.signed_volume(), 1.0 + 1.0 / 8.0, comp = abs, tol = 1e-12);
+    assert!(hex.is_positively_oriented());
+}
+
+#[test]
+fn procedural_meshes_are_valid() {
+    assert_eq!(validate(&create_unit_square_uniform_tri_mesh_2d::<f64>(3)), Ok(()));
+    assert_eq!(validate(&create_unit_square_uniform_quad_mesh_2d::<f64>(3)), Ok(()));
+    assert_eq!(validate(&create_unit_box_uniform_tet_mesh_3d::<f64>(2)), Ok(()));
+    assert_eq!(validate(&create_unit_box_uniform_hex_mesh_3d::<f64>(2)), Ok(()));
+
+    let report = MeshValidationReport::from_mesh(&create_unit_box_uniform_hex_mesh_3d::<f64>(2));
+    assert!(report.is_empty());
+    assert_eq!(report.to_string(), "mesh validation found no problems");
+}
+
+#[test]
+fn out_of_bounds_vertex_indices_are_reported() {
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(
+        unit_square_vertices(),
+        vec![Tri3d2Connectivity([0, 1, 2]), Tri3d2Connectivity([0, 2, 4])],
+    );
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.out_of_bounds_elements, vec![1]);
+    assert!(report.duplicate_elements.is_empty());
+    assert!(report.inverted_elements.is_empty());
+    assert!(report.degenerate_elements.is_empty());
+    // Vertex 3 is only referenced by the invalid element
+    assert_eq!(report.unreferenced_vertices, vec![3]);
+}
+
+#[test]
+fn duplicate_elements_are_reported() {
+    let mesh = Tet4Mesh::from_vertices_and_connectivity(
+        unit_cube_vertices(),
+        vec![
+            Tet4Connectivity([0, 1, 3, 4]),
+            Tet4Connectivity([1, 2, 3, 6]),
+            Tet4Connectivity([0, 1, 3, 4]),
+            // A duplicate with permuted vertices must also be detected
+            Tet4Connectivity([1, 3, 4, 0]),
+        ],
+    );
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.duplicate_elements, vec![2, 3]);
+    assert!(report.out_of_bounds_elements.is_empty());
+    assert!(report.degenerate_elements.is_empty());
+}
+
+#[test]
+fn unreferenced_vertices_are_not_errors() {
+    let mut vertices = unit_square_vertices();
+    vertices.push(Point2::new(2.0, 2.0));
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(vertices, vec![Quad4d2Connectivity([0, 1, 2, 3])]);
+    assert_eq!(validate(&mesh), Ok(()));
+
+    let report = MeshValidationReport::from_mesh(&mesh);
+    assert!(report.is_valid());
+    assert!(!report.is_empty());
+    assert_eq!(report.unreferenced_vertices, vec![4]);
+}
+
+#[test]
+fn inverted_simplices_are_reported() {
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(
+        unit_square_vertices(),
+        vec![Tri3d2Connectivity([0, 1, 2]), Tri3d2Connectivity([0, 3, 2])],
+    );
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.inverted_elements, vec![1]);
+    assert!(report.degenerate_elements.is_empty());
+
+    let mesh = Tet4Mesh::from_vertices_and_connectivity(
+        unit_cube_vertices(),
+        vec![Tet4Connectivity([0, 1, 3, 4]), Tet4Connectivity([0, 3, 1, 4])],
+    );
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.inverted_elements, vec![1]);
+}
+
+#[test]
+fn inverted_tensor_product_elements_are_reported() {
+    // Clockwise quadrilateral
+    let mesh =
+        QuadMesh2d::from_vertices_and_connectivity(unit_square_vertices(), vec![Quad4d2Connectivity([0, 3, 2, 1])]);
+    assert_eq!(validate(&mesh).unwrap_err().inverted_elements, vec![0]);
+
+    // A non-convex quadrilateral has positive area, but a negative Jacobian determinant at the
+    // reflex corner
+    let vertices = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(0.5, 0.5),
+        Point2::new(0.0, 2.0),
+    ];
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(vertices, vec![Quad4d2Connectivity([0, 1, 2, 3])]);
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.inverted_elements, vec![0]);
+    assert!(report.degenerate_elements.is_empty());
+
+    // Hexahedron with top and bottom faces swapped
+    let mesh =
+        HexMesh::from_vertices_and_connectivity(unit_cube_vertices(), vec![Hex8Connectivity([4, 5, 6, 7, 0, 1, 2, 3])]);
+    assert_eq!(validate(&mesh).unwrap_err().inverted_elements, vec![0]);
+}
+
+#[test]
+fn degenerate_elements_are_reported() {
+    // Collinear triangle
+    let vertices = vec![Point2::new(0.0, 0.0), Point2::new(1.0, 0.0), Point2::new(2.0, 1e-12)];
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(vertices, vec![Tri3d2Connectivity([0, 1, 2])]);
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.degenerate_elements, vec![0]);
+    assert!(report.inverted_elements.is_empty());
+
+    // Flattened hexahedron
+    let mut vertices = unit_cube_vertices();
+    for v in &mut vertices[4..] {
+        v.z = 0.0;
+    }
+    let mesh = HexMesh::from_vertices_and_connectivity(vertices, vec![Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7])]);
+    let report = validate(&mesh).unwrap_err();
+    assert_eq!(report.degenerate_elements, vec![0]);
+    assert!(report.inverted_elements.is_empty());
+}
+
+#[test]
+fn report_display_lists_problems_by_type() {
+    let mut vertices = unit_square_vertices();
+    vertices.push(Point2::new(2.0, 2.0));
+    let mesh = TriangleMesh2d::from_vertices_and_connectivity(
+        vertices,
+        vec![
+            Tri3d2Connectivity([0, 1, 2]),
+            Tri3d2Connectivity([0, 3, 2]),
+            Tri3d2Connectivity([0, 2, 7]),
+            Tri3d2Connectivity([2, 0, 1]),
+        ],
+    );
+    let report = validate(&mesh).unwrap_err();
+    let expected = "mesh validation found problems:\n  \
+                    elements with out-of-bounds vertex indices (1): [2]\n  \
+                    duplicate elements (1): [3]\n  \
+                    inverted elements (1): [1]\n  \
+                    unreferenced vertices, not an error (1): [4]";
+    assert_eq!(report.to_string(), expected);
+}
+
+#[test]
+fn fix_orientation_flips_inverted_simplices() {
+    let mut mesh = TriangleMesh2d::from_vertices_and_connectivity(
+        unit_square_vertices(),
+        vec![Tri3d2Connectivity([0, 1, 2]), Tri3d2Connectivity([0, 3, 2])],
+    );
+    assert_eq!(fix_orientation(&mut mesh), vec![1]);
+    assert_eq!(mesh.connectivity()[0], Tri3d2Connectivity([0, 1, 2]));
+    assert_eq!(mesh.connectivity()[1], Tri3d2Connectivity([0, 2, 3]));
+    assert_eq!(validate(&mesh), Ok(()));
+
+    let mut mesh = create_unit_box_uniform_tet_mesh_3d::<f64>(2);
+    let num_cells = mesh.connectivity().len();
+    let inverted_mesh = Tet4Mesh::from_vertices_and_connectivity(
+        mesh.vertices().to_vec(),
+        mesh.connectivity()
+            .iter()
+            .map(|&Tet4Connectivity([a, b, c, d])| Tet4Connectivity([a, c, b, d]))
+            .collect(),
+    );
+    assert_eq!(
+        validate(&inverted_mesh).unwrap_err().inverted_elements,
+        (0..num_cells).collect::<Vec<_>>()
+    );
+    let mut fixed_mesh = inverted_mesh;
+    assert_eq!(fix_orientation(&mut fixed_mesh), (0..num_cells).collect::<Vec<_>>());
+    assert_eq!(fixed_mesh, mesh);
+    assert!(fix_orientation(&mut mesh).is_empty());
+}
@@ -3,8 +3,11 @@ mod basis;
 mod element;
 mod error;
 mod fe_mesh;
+mod integrate;
 mod io;
 mod mesh;
 mod quadrature;
 mod reorder;
+mod space;
 mod spatially_indexed;
+mod util;
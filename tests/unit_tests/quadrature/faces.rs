@@ -0,0 +1,117 @@
+use fenris::connectivity::{Connectivity, Hex8Connectivity};
+use fenris::element::ReferenceShape;
+use fenris::nalgebra::{Point2, Point3, Vector2};
+use fenris::quadrature::faces::{
+    reference_face_embeddings_3d, reference_face_quadratures_2d, reference_face_quadratures_3d,
+};
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+
+#[test]
+fn hexahedron_face_quadratures_integrate_linear_function() {
+    let f = |x: &Point3<f64>| 1.0 + 2.0 * x.x - 3.0 * x.y + 5.0 * x.z;
+    // Faces are ordered as in Hex8Connectivity: z = -1, y = -1, x = 1, y = 1, x = -1, z = 1.
+    // Each face has area 4, so the integral of a linear function is 4 times its value at the face center
+    let expected = [-16.0, 16.0, 12.0, -8.0, -4.0, 24.0];
+
+    let faces = reference_face_quadratures_3d::<f64>(ReferenceShape::Hexahedron, 1).unwrap();
+    assert_eq!(faces.len(), 6);
+    for ((embedding, quadrature), expected) in faces.iter().zip(expected) {
+        assert_eq!(embedding.shape(), ReferenceShape::Quadrilateral);
+        let (weights, points) = embedding.map_quadrature(quadrature);
+        let integral: f64 = weights.iter().zip(&points).map(|(w, x)| w * f(x)).sum();
+        assert_scalar_eq!(integral, expected, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn hexahedron_face_embeddings_are_consistent_with_face_connectivity() {
+    let connectivity = Hex8Connectivity([0, 1, 2, 3, 4, 5, 6, 7]);
+    let vertices = [
+        Point3::new(-1.0, -1.0, -1.0),
+        Point3::new(1.0, -1.0, -1.0),
+        Point3::new(1.0, 1.0, -1.0),
+        Point3::new(-1.0, 1.0, -1.0),
+        Point3::new(-1.0, -1.0, 1.0),
+        Point3::new(1.0, -1.0, 1.0),
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(-1.0, 1.0, 1.0),
+    ];
+    let face_reference_vertices = [
+        Point2::new(-1.0, -1.0),
+        Point2::new(1.0, -1.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(-1.0, 1.0),
+    ];
+
+    let embeddings = reference_face_embeddings_3d::<f64>(ReferenceShape::Hexahedron);
+    for (i, embedding) in embeddings.iter().enumerate() {
+        let face = connectivity.get_face_connectivity(i).unwrap();
+        for (eta, &vertex_index) in face_reference_vertices.iter().zip(face.vertex_indices()) {
+            let xi = embedding.map_reference_coords(eta);
+            assert_matrix_eq!(xi.coords, vertices[vertex_index].coords, comp = abs, tol = 1e-14);
+        }
+    }
+}
+
+#[test]
+fn face_quadratures_satisfy_divergence_theorem_with_outward_normals() {
+    // By the divergence theorem, the integral of x . n over the boundary is 3 times the volume
+    let shapes_and_volumes = [
+        (ReferenceShape::Tetrahedron, 4.0 / 3.0),
+        (ReferenceShape::Hexahedron, 8.0),
+        (ReferenceShape::Prism, 4.0),
+        (ReferenceShape::Pyramid, 8.0 / 3.0),
+    ];
+
+    for (shape, volume) in shapes_and_volumes {
+        let mut integral = 0.0;
+        for (embedding, quadrature) in reference_face_quadratures_3d::<f64>(shape, 1).unwrap() {
+            let jacobian = embedding.jacobian();
+            let n = jacobian.column(0).cross(&jacobian.column(1)).normalize();
+            let (weights, points) = embedding.map_quadrature(&quadrature);
+            integral += weights
+                .iter()
+                .zip(&points)
+                .map(|(w, x)| w * x.coords.dot(&n))
+                .sum::<f64>();
+        }
+        assert_scalar_eq!(integral, 3.0 * volume, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn edge_quadratures_satisfy_divergence_theorem_with_outward_normals() {
+    // The integral of x . n over the boundary is 2 times the area
+    let shapes_and_areas = [(ReferenceShape::Triangle, 2.0), (ReferenceShape::Quadrilateral, 4.0)];
+
+    for (shape, area) in shapes_and_areas {
+        let mut integral = 0.0;
+        for (embedding, quadrature) in reference_face_quadratures_2d::<f64>(shape, 1) {
+            let t = embedding.jacobian().column(0).normalize();
+            let n = Vector2::new(t.y, -t.x);
+            let (weights, points) = embedding.map_quadrature(&quadrature);
+            integral += weights
+                .iter()
+                .zip(&points)
+                .map(|(w, x)| w * x.coords.dot(&n))
+                .sum::<f64>();
+        }
+        assert_scalar_eq!(integral, 2.0 * area, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn edge_quadratures_integrate_polynomials_of_given_strength() {
+    // Integrate x^3 y^2 along the edges of the reference quadrilateral, oriented counter-clockwise
+    let f = |x: &Point2<f64>| x.x.powi(3) * x.y.powi(2);
+    // Edges y = -1 and y = 1 contribute the integral of x^3, which vanishes, and
+    // the edges x = 1 and x = -1 each contribute 2/3 and -2/3, respectively
+    let expected = [0.0, 2.0 / 3.0, 0.0, -2.0 / 3.0];
+    let edges = reference_face_quadratures_2d::<f64>(ReferenceShape::Quadrilateral, 5);
+    for ((embedding, quadrature), expected) in edges.iter().zip(expected) {
+        assert_eq!(embedding.shape(), ReferenceShape::Segment);
+        let (weights, points) = embedding.map_quadrature(quadrature);
+        let integral: f64 = weights.iter().zip(&points).map(|(w, x)| w * f(x)).sum();
+        assert_scalar_eq!(integral, expected, comp = abs, tol = 1e-12);
+    }
+}
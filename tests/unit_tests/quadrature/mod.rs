@@ -4,6 +4,7 @@ use itertools::izip;
 use nalgebra::Point1;
 
 mod canonical;
+mod faces;
 mod subdivide;
 
 #[test]
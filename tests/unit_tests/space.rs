@@ -0,0 +1,130 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::ElementEllipticAssemblerBuilder;
+use fenris::assembly::operators::{EllipticContraction, LaplaceOperator, Operator};
+use fenris::assembly::{find_boundary_dofs, DirichletBcs};
+use fenris::mesh::procedural::create_unit_square_uniform_quad_mesh_2d;
+use fenris::nalgebra::{DMatrix, DVector, Matrix2, Vector2, U2};
+use fenris::quadrature::CanonicalStiffnessQuadrature;
+use fenris::space::{DofLayout, VectorSpace};
+use fenris::Symmetry;
+use matrixcompare::assert_matrix_eq;
+
+/// The vector Laplacian in 2D, which applies the scalar Laplacian to each component.
+struct VectorLaplaceOperator;
+
+impl Operator<f64, U2> for VectorLaplaceOperator {
+    type SolutionDim = U2;
+    type Parameters = ();
+}
+
+impl EllipticContraction<f64, U2> for VectorLaplaceOperator {
+    fn contract(&self, _gradient: &Matrix2<f64>, a: &Vector2<f64>, b: &Vector2<f64>, _data: &()) -> Matrix2<f64> {
+        Matrix2::identity() * a.dot(b)
+    }
+
+    fn symmetry(&self) -> Symmetry {
+        Symmetry::Symmetric
+    }
+}
+
+#[test]
+fn vector_space_dof_index_for_both_layouts() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(2);
+    let n = mesh.vertices().len();
+
+    let interleaved = VectorSpace::<_, U2>::new(mesh.clone());
+    assert_eq!(interleaved.layout(), DofLayout::Interleaved);
+    assert_eq!(interleaved.num_nodes(), n);
+    assert_eq!(interleaved.num_dofs(), 2 * n);
+    assert_eq!(interleaved.dof_index(0, 0), 0);
+    assert_eq!(interleaved.dof_index(0, 1), 1);
+    assert_eq!(interleaved.dof_index(3, 1), 7);
+
+    let blocked = VectorSpace::<_, U2>::with_layout(mesh.clone(), DofLayout::Blocked);
+    assert_eq!(blocked.num_dofs(), 2 * n);
+    assert_eq!(blocked.dof_index(0, 0), 0);
+    assert_eq!(blocked.dof_index(0, 1), n);
+    assert_eq!(blocked.dof_index(3, 1), n + 3);
+
+    for space in [&interleaved, &blocked] {
+        let mut dofs: Vec<_> = (0..n)
+            .flat_map(|node| (0..2).map(move |component| (node, component)))
+            .map(|(node, component)| {
+                let dof = space.dof_index(node, component);
+                assert_eq!(space.node_and_component(dof), (node, component));
+                dof
+            })
+            .collect();
+        dofs.sort_unstable();
+        assert_eq!(dofs, (0..2 * n).collect::<Vec<_>>());
+    }
+
+    let u_blocked = DVector::from_fn(2 * n, |i, _| i as f64);
+    let u_interleaved = blocked.vector_to_interleaved(&u_blocked);
+    assert_eq!(u_interleaved[1], n as f64);
+    assert_eq!(blocked.vector_from_interleaved(&u_interleaved), u_blocked);
+    assert_eq!(interleaved.vector_from_interleaved(&u_interleaved), u_interleaved);
+}
+
+#[test]
+fn vector_laplacian_in_blocked_layout_is_permutation_of_interleaved_layout() {
+    let mesh = create_unit_square_uniform_quad_mesh_2d::<f64>(3);
+    let n = mesh.vertices().len();
+    let qtable = mesh.canonical_stiffness_quadrature();
+
+    let u_scalar = DVector::zeros(n);
+    let scalar_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&LaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u_scalar)
+        .build();
+    let a_scalar = DMatrix::from(&CsrAssembler::default().assemble(&scalar_assembler).unwrap());
+
+    let u_vector = DVector::zeros(2 * n);
+    let vector_assembler = ElementEllipticAssemblerBuilder::new()
+        .with_finite_element_space(&mesh)
+        .with_operator(&VectorLaplaceOperator)
+        .with_quadrature_table(&qtable)
+        .with_u(&u_vector)
+        .build();
+    let a_interleaved = CsrAssembler::default().assemble(&vector_assembler).unwrap();
+
+    // The assembled matrix uses the interleaved layout
+    let interleaved = VectorSpace::<_, U2>::new(mesh.clone());
+    for node_i in 0..n {
+        for node_j in 0..n {
+            for c in 0..2 {
+                let i = interleaved.dof_index(node_i, c);
+                let j = interleaved.dof_index(node_j, c);
+                assert_eq!(DMatrix::from(&a_interleaved)[(i, j)], a_scalar[(node_i, node_j)]);
+            }
+        }
+    }
+    assert_eq!(interleaved.csr_from_interleaved(&a_interleaved), a_interleaved);
+
+    // In the blocked layout, the vector Laplacian is block diagonal with the scalar Laplacian in each block
+    let blocked = VectorSpace::<_, U2>::with_layout(mesh.clone(), DofLayout::Blocked);
+    let a_blocked = DMatrix::from(&blocked.csr_from_interleaved(&a_interleaved));
+    assert_matrix_eq!(a_blocked.view((0, 0), (n, n)), a_scalar, comp = float);
+    assert_matrix_eq!(a_blocked.view((n, n), (n, n)), a_scalar, comp = float);
+    assert!(a_blocked.view((0, n), (n, n)).iter().all(|&x| x == 0.0));
+    assert!(a_blocked.view((n, 0), (n, n)).iter().all(|&x| x == 0.0));
+
+    // Imposing boundary conditions before or after changing the layout gives the same system
+    let boundary_dofs = find_boundary_dofs(&mesh, 2, &[0, 1], |x| x.x == 0.0);
+    let b_interleaved = DVector::from_fn(2 * n, |i, _| i as f64);
+    let (mut a_constrained, mut b_constrained) = (a_interleaved.clone(), b_interleaved.clone());
+    DirichletBcs::homogeneous(boundary_dofs.clone())
+        .apply_to_system(&mut a_constrained, &mut b_constrained)
+        .unwrap();
+
+    let mut a_blocked_constrained = blocked.csr_from_interleaved(&a_interleaved);
+    let mut b_blocked_constrained = blocked.vector_from_interleaved(&b_interleaved);
+    DirichletBcs::homogeneous(blocked.dofs_from_interleaved(&boundary_dofs))
+        .apply_to_system(&mut a_blocked_constrained, &mut b_blocked_constrained)
+        .unwrap();
+
+    assert_eq!(a_blocked_constrained, blocked.csr_from_interleaved(&a_constrained));
+    assert_eq!(b_blocked_constrained, blocked.vector_from_interleaved(&b_constrained));
+}
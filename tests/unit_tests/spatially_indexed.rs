@@ -1,9 +1,18 @@
-use fenris::element::{ElementConnectivity, FiniteElement};
-use fenris::mesh::procedural::create_unit_square_uniform_tri_mesh_2d;
-use fenris::mesh::TriangleMesh2d;
-use fenris::space::{FindClosestElement, FiniteElementSpace, SpatiallyIndexed};
-use matrixcompare::assert_matrix_eq;
-use nalgebra::Point2;
+use fenris::connectivity::{Segment2d1Connectivity, Segment3d1Connectivity};
+use fenris::element::{ClosestPoint, ClosestPointInElement, ElementConnectivity, FiniteElement, Quad4d2Element};
+use fenris::mesh::procedural::{
+    create_unit_box_uniform_hex_mesh_3d, create_unit_square_uniform_quad_mesh_2d,
+    create_unit_square_uniform_tri_mesh_2d,
+};
+use fenris::mesh::{HexMesh, Mesh, QuadMesh2d, TriangleMesh2d};
+use fenris::space::{
+    classify_point, classify_points, interpolate_at_points, interpolate_at_points_par, interpolate_gradient_at_points,
+    interpolate_gradient_at_points_par, ClosestPointInElementInSpace, FindClosestElement, FindContainingElement,
+    FiniteElementSpace, InterpolateGradientInSpace, InterpolateInSpace, PointClassification, SpatiallyIndexed,
+};
+use fenris::util::global_vector_from_point_fn;
+use matrixcompare::{assert_matrix_eq, assert_scalar_eq};
+use nalgebra::{Matrix3x2, Point1, Point2, Point3, Vector1, Vector2, U1};
 
 #[test]
 fn spatially_indexed_closest_element_at_interfaces() {
@@ -31,3 +40,443 @@ fn spatially_indexed_closest_element_at_interfaces() {
         }
     }
 }
+
+/// Returns the closest point on the unit square/cube to the given point,
+/// and the multi-index of the cell of a uniform grid on the unit square/cube that contains it.
+///
+/// The point must not project onto an interface between cells.
+fn expected_closest_point_and_cell<const D: usize>(p: [f64; D], cells_per_dim: usize) -> ([f64; D], [usize; D]) {
+    let x = p.map(|p_i| p_i.clamp(0.0, 1.0));
+    let cell = x.map(|x_i| ((x_i * cells_per_dim as f64).floor() as usize).min(cells_per_dim - 1));
+    (x, cell)
+}
+
+/// Returns the multi-index of the cell in a uniform grid on the unit square/cube given its vertices.
+fn cell_multi_index<const D: usize>(vertices: impl IntoIterator<Item = [f64; D]>, cells_per_dim: usize) -> [usize; D] {
+    let min_corner = vertices
+        .into_iter()
+        .fold([f64::INFINITY; D], |min, v| std::array::from_fn(|i| min[i].min(v[i])));
+    min_corner.map(|x_i| (x_i * cells_per_dim as f64).round() as usize)
+}
+
+#[test]
+fn spatially_indexed_closest_element_in_structured_quad_mesh() {
+    let cells_per_dim = 4;
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(cells_per_dim);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+
+    let interior_points = [[0.1, 0.1], [0.3, 0.6], [0.9, 0.45], [0.55, 0.85]];
+    let boundary_points = [[0.0, 0.3], [0.6, 1.0], [1.0, 0.1], [0.0, 0.0], [1.0, 1.0]];
+    let exterior_points = [
+        [-0.5, 0.3],
+        [0.6, 1.2],
+        [1.3, -0.4],
+        [-1.0, -1.0],
+        [2.0, 0.9],
+        [0.4, -0.01],
+    ];
+    let points = interior_points
+        .iter()
+        .chain(&boundary_points)
+        .map(|p| (p, false))
+        .chain(exterior_points.iter().map(|p| (p, true)));
+
+    for (&p, is_exterior) in points {
+        let (x_expected, cell_expected) = expected_closest_point_and_cell(p, cells_per_dim);
+        let p = Point2::from(p);
+        let (element_idx, xi) = space.find_closest_element_and_reference_coords(&p).unwrap();
+
+        let vertices = mesh.connectivity()[element_idx]
+            .0
+            .map(|v| <[f64; 2]>::from(mesh.vertices()[v].coords));
+        assert_eq!(cell_multi_index(vertices, cells_per_dim), cell_expected);
+        assert!(xi.iter().all(|xi_i| xi_i.abs() <= 1.0));
+        let x = space.map_element_reference_coords(element_idx, &xi);
+        assert_matrix_eq!(x.coords, Point2::from(x_expected).coords, comp = abs, tol = 1e-12);
+
+        let closest_point = space.closest_point_in_element(element_idx, &p);
+        assert_eq!(matches!(closest_point, ClosestPoint::ClosestPoint(_)), is_exterior);
+    }
+}
+
+#[test]
+fn spatially_indexed_closest_element_in_structured_hex_mesh() {
+    let cells_per_dim = 3;
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(cells_per_dim);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+
+    let interior_points = [[0.1, 0.1, 0.1], [0.5, 0.2, 0.8], [0.9, 0.5, 0.4]];
+    let boundary_points = [[0.0, 0.5, 0.5], [0.2, 1.0, 0.9], [1.0, 1.0, 1.0], [0.5, 0.1, 0.0]];
+    let exterior_points = [
+        [-0.5, 0.5, 0.5],
+        [0.2, 1.3, 0.9],
+        [2.0, 2.0, 2.0],
+        [0.5, -0.2, 1.5],
+        [-1.0, 0.8, -0.1],
+    ];
+    let points = interior_points
+        .iter()
+        .chain(&boundary_points)
+        .map(|p| (p, false))
+        .chain(exterior_points.iter().map(|p| (p, true)));
+
+    for (&p, is_exterior) in points {
+        let (x_expected, cell_expected) = expected_closest_point_and_cell(p, cells_per_dim);
+        let p = Point3::from(p);
+        let (element_idx, xi) = space.find_closest_element_and_reference_coords(&p).unwrap();
+
+        let vertices = mesh.connectivity()[element_idx]
+            .0
+            .map(|v| <[f64; 3]>::from(mesh.vertices()[v].coords));
+        assert_eq!(cell_multi_index(vertices, cells_per_dim), cell_expected);
+        assert!(xi.iter().all(|xi_i| xi_i.abs() <= 1.0));
+        let x = space.map_element_reference_coords(element_idx, &xi);
+        assert_matrix_eq!(x.coords, Point3::from(x_expected).coords, comp = abs, tol = 1e-12);
+
+        let closest_point = space.closest_point_in_element(element_idx, &p);
+        assert_eq!(matches!(closest_point, ClosestPoint::ClosestPoint(_)), is_exterior);
+    }
+}
+
+#[test]
+fn quad4_closest_point_in_distorted_element() {
+    // The bilinear map of a trapezoid is not affine, so that Newton iterations are necessary
+    let element = Quad4d2Element::from_vertices([
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(1.5, 1.0),
+        Point2::new(0.5, 1.0),
+    ]);
+
+    let xi_interior = Point2::new(0.3, -0.6);
+    let x_interior = element.map_reference_coords(&xi_interior);
+    match element.closest_point(&x_interior) {
+        ClosestPoint::InElement(xi) => assert_matrix_eq!(xi.coords, xi_interior.coords, comp = abs, tol = 1e-12),
+        ClosestPoint::ClosestPoint(_) => panic!("Point is inside element"),
+    }
+
+    // The closest point to a point above the top edge is on the top edge
+    let closest_point = element.closest_point(&Point2::new(0.8, 3.0));
+    assert!(matches!(closest_point, ClosestPoint::ClosestPoint(_)));
+    let x = element.map_reference_coords(closest_point.point());
+    assert_scalar_eq!(x.x, 0.8, comp = abs, tol = 1e-12);
+    assert_scalar_eq!(x.y, 1.0, comp = abs, tol = 1e-12);
+}
+
+#[test]
+fn spatially_indexed_interpolation_in_non_uniform_1d_quadratic_mesh() {
+    // Element end points, deliberately non-uniform. The nodes are numbered such that
+    // the elements are not in order of increasing coordinates.
+    let end_points = [0.0, 0.1, 0.35, 0.45, 1.0, 1.6];
+    let element_order = [3, 0, 4, 2, 1];
+    let mut vertices: Vec<_> = end_points.iter().map(|&x| Point1::new(x)).collect();
+    let connectivity = element_order
+        .iter()
+        .map(|&i| {
+            let midpoint_index = vertices.len();
+            vertices.push(Point1::new(0.5 * (end_points[i] + end_points[i + 1])));
+            Segment3d1Connectivity([i, midpoint_index, i + 1])
+        })
+        .collect();
+    let mesh = Mesh::<f64, U1, Segment3d1Connectivity>::from_vertices_and_connectivity(vertices, connectivity);
+
+    // Quadratic elements reproduce quadratic functions exactly
+    let u_exact = |x: f64| 2.0 * x * x - x + 0.5;
+    let u_grad_exact = |x: f64| 4.0 * x - 1.0;
+    let u = global_vector_from_point_fn(mesh.vertices(), |p| Vector1::new(u_exact(p.x)));
+    let space = SpatiallyIndexed::from_space(mesh);
+
+    let interior_points = [0.0, 0.03, 0.1, 0.2, 0.35, 0.4, 0.45, 0.7, 0.99, 1.3, 1.6];
+    for x in interior_points {
+        let p = Point1::new(x);
+        let (element_idx, xi) = space.find_closest_element_and_reference_coords(&p).unwrap();
+        let x_closest = space.map_element_reference_coords(element_idx, &xi);
+        assert_scalar_eq!(x_closest.x, x, comp = abs, tol = 1e-14);
+
+        let u_h: Vector1<f64> = space.interpolate_at_point(&p, u.as_view());
+        let u_grad_h: Vector1<f64> = space.interpolate_gradient_at_point(&p, u.as_view());
+        assert_scalar_eq!(u_h[0], u_exact(x), comp = abs, tol = 1e-12);
+        assert_scalar_eq!(u_grad_h[0], u_grad_exact(x), comp = abs, tol = 1e-12);
+    }
+
+    // Points outside the domain are clamped to the first or last element
+    for (x, x_clamped, expected_element) in [(-0.3, 0.0, 1), (-1e-3, 0.0, 1), (1.7, 1.6, 2), (25.0, 1.6, 2)] {
+        let p = Point1::new(x);
+        let (element_idx, xi) = space.find_closest_element_and_reference_coords(&p).unwrap();
+        assert_eq!(element_idx, expected_element);
+        let x_closest = space.map_element_reference_coords(element_idx, &xi);
+        assert_scalar_eq!(x_closest.x, x_clamped, comp = abs, tol = 1e-14);
+
+        let u_h: Vector1<f64> = space.interpolate_at_point(&p, u.as_view());
+        let u_grad_h: Vector1<f64> = space.interpolate_gradient_at_point(&p, u.as_view());
+        assert_scalar_eq!(u_h[0], u_exact(x_clamped), comp = abs, tol = 1e-12);
+        assert_scalar_eq!(u_grad_h[0], u_grad_exact(x_clamped), comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn spatially_indexed_containing_element_in_triangle_mesh() {
+    let mesh: TriangleMesh2d<f64> = create_unit_square_uniform_tri_mesh_2d(4);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+    let tol = 1e-12;
+
+    // Interior points of each element are only contained in that element
+    for (element_idx, conn) in mesh.connectivity().iter().enumerate() {
+        let element = conn.element(mesh.vertices()).unwrap();
+        let xi = Point2::new(-0.5, -0.5);
+        let x = element.map_reference_coords(&xi);
+        let (found_idx, found_xi) = space.find_containing_element(&x, tol).unwrap();
+        assert_eq!(found_idx, element_idx);
+        assert_matrix_eq!(found_xi.coords, xi.coords, comp = abs, tol = 1e-12);
+    }
+
+    // Vertices and edge midpoints are assigned to the element with the lowest index among
+    // the elements sharing them
+    let lowest_element_containing = |vertex_indices: &[usize]| {
+        mesh.connectivity()
+            .iter()
+            .position(|conn| vertex_indices.iter().all(|v| conn.0.contains(v)))
+            .unwrap()
+    };
+    for (v, x) in mesh.vertices().iter().enumerate() {
+        let (found_idx, found_xi) = space.find_containing_element(x, tol).unwrap();
+        assert_eq!(found_idx, lowest_element_containing(&[v]));
+        let x_found = space.map_element_reference_coords(found_idx, &found_xi);
+        assert_matrix_eq!(x_found.coords, x.coords, comp = abs, tol = 1e-12);
+    }
+    for conn in mesh.connectivity() {
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            let (a, b) = (conn.0[a], conn.0[b]);
+            let x = Point2::from((mesh.vertices()[a].coords + mesh.vertices()[b].coords) / 2.0);
+            let (found_idx, _) = space.find_containing_element(&x, tol).unwrap();
+            assert_eq!(found_idx, lowest_element_containing(&[a, b]));
+        }
+    }
+
+    // Points outside the domain are not contained in any element, unless they are within the tolerance
+    let exterior_points = [[-0.1, 0.5], [0.5, 1.0 + 1e-6], [1.5, 1.5], [-1e-3, -1e-3]];
+    for p in exterior_points {
+        assert!(space
+            .find_containing_element(&Point2::from(p), tol)
+            .is_none());
+    }
+    let (_, xi) = space
+        .find_containing_element(&Point2::new(0.5, 1.0 + 1e-6), 1e-4)
+        .unwrap();
+    assert!(xi.iter().all(|xi_i| xi_i.abs() <= 1.0));
+
+    // The tolerance also applies to points beyond the bounding boxes of the elements
+    let p = Point2::new(0.5, 1.02);
+    let (element_idx, xi) = space.find_containing_element(&p, 0.1).unwrap();
+    let x = space.map_element_reference_coords(element_idx, &xi);
+    assert_matrix_eq!(x.coords, Point2::new(0.5, 1.0).coords, comp = abs, tol = 1e-12);
+    assert!(space.find_containing_element(&p, 0.01).is_none());
+}
+
+#[test]
+fn spatially_indexed_containing_element_in_hex_mesh() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(3);
+    let space = SpatiallyIndexed::from_space(mesh.clone());
+
+    for p in [[0.1, 0.1, 0.1], [0.5, 0.2, 0.8], [1.0, 1.0, 1.0], [0.5, 0.1, 0.0]] {
+        let p = Point3::from(p);
+        let (element_idx, xi) = space.find_containing_element(&p, 1e-12).unwrap();
+        let x = space.map_element_reference_coords(element_idx, &xi);
+        assert_matrix_eq!(x.coords, p.coords, comp = abs, tol = 1e-12);
+    }
+
+    for p in [[-0.5, 0.5, 0.5], [0.2, 1.3, 0.9], [2.0, 2.0, 2.0], [0.5, 0.5, -1e-6]] {
+        assert!(space
+            .find_containing_element(&Point3::from(p), 1e-12)
+            .is_none());
+    }
+}
+
+#[test]
+fn spatially_indexed_containing_element_in_1d_mesh() {
+    // The elements are not in order of increasing coordinates
+    let end_points = [0.0, 0.1, 0.35, 0.45, 1.0, 1.6];
+    let element_order = [3, 0, 4, 2, 1];
+    let vertices = end_points.iter().map(|&x| Point1::new(x)).collect();
+    let connectivity = element_order
+        .iter()
+        .map(|&i| Segment2d1Connectivity([i, i + 1]))
+        .collect();
+    let mesh = Mesh::<f64, U1, Segment2d1Connectivity>::from_vertices_and_connectivity(vertices, connectivity);
+    let space = SpatiallyIndexed::from_space(mesh);
+
+    for (x, expected_element) in [(0.05, 1), (0.1, 1), (0.2, 4), (0.45, 0), (0.0, 1), (1.6, 2), (1.2, 2)] {
+        let p = Point1::new(x);
+        let (element_idx, xi) = space.find_containing_element(&p, 1e-12).unwrap();
+        assert_eq!(element_idx, expected_element);
+        let x_found = space.map_element_reference_coords(element_idx, &xi);
+        assert_scalar_eq!(x_found.x, x, comp = abs, tol = 1e-14);
+    }
+
+    for x in [-0.3, -1e-3, 1.7, 25.0] {
+        assert!(space
+            .find_containing_element(&Point1::new(x), 1e-12)
+            .is_none());
+    }
+
+    let (element_idx, _) = space
+        .find_containing_element(&Point1::new(-0.02), 0.5)
+        .unwrap();
+    assert_eq!(element_idx, 1);
+    assert!(space
+        .find_containing_element(&Point1::new(-0.02), 0.1)
+        .is_none());
+}
+
+#[test]
+fn spatially_indexed_queries_reflect_updated_geometry() {
+    let mesh: QuadMesh2d<f64> = create_unit_square_uniform_quad_mesh_2d(2);
+    let mut space = SpatiallyIndexed::from_space(mesh);
+    let stale_space = space.clone();
+    let tol = 1e-12;
+
+    // Stretch and translate the mesh so that it covers [10, 12] x [0, 1]
+    space.update_space(|mesh| {
+        for v in mesh.vertices_mut() {
+            v.x = 2.0 * v.x + 10.0;
+        }
+    });
+
+    let x_new = Point2::new(11.5, 0.25);
+    let (element_idx, xi) = space.find_containing_element(&x_new, tol).unwrap();
+    let x_found = space.map_element_reference_coords(element_idx, &xi);
+    assert_matrix_eq!(x_found.coords, x_new.coords, comp = abs, tol = 1e-12);
+    let (closest_idx, _) = space
+        .find_closest_element_and_reference_coords(&x_new)
+        .unwrap();
+    assert_eq!(closest_idx, element_idx);
+    assert!(stale_space.find_containing_element(&x_new, tol).is_none());
+
+    let x_old = Point2::new(0.75, 0.25);
+    assert!(stale_space.find_containing_element(&x_old, tol).is_some());
+    assert!(space.find_containing_element(&x_old, tol).is_none());
+
+    // Rebuilding without changes to the geometry gives the same results
+    space.rebuild();
+    assert_eq!(
+        space
+            .find_containing_element(&x_new, tol)
+            .map(|(idx, _)| idx),
+        Some(element_idx)
+    );
+    assert!(space
+        .into_space()
+        .vertices()
+        .iter()
+        .all(|v| v.x >= 10.0 && v.x <= 12.0));
+}
+
+#[test]
+fn spatially_indexed_parallel_interpolation_matches_serial() {
+    let space = SpatiallyIndexed::from_space(create_unit_box_uniform_hex_mesh_3d::<f64>(4));
+    let u = global_vector_from_point_fn(space.space().vertices(), |p| {
+        Vector2::new(p.x * p.y + p.z, (p.x - p.z).sin())
+    });
+
+    // Enough points to span several parallel chunks, including points outside the mesh
+    let n = 12;
+    let coord = |i: usize| -0.2 + 1.4 * i as f64 / (n - 1) as f64;
+    let points: Vec<_> = (0..n * n * n)
+        .map(|idx| Point3::new(coord(idx % n), coord((idx / n) % n), coord(idx / (n * n))))
+        .collect();
+
+    let mut values_serial = vec![Vector2::zeros(); points.len()];
+    let mut values_par = vec![Vector2::repeat(f64::NAN); points.len()];
+    interpolate_at_points(&space, &points, u.as_view(), &mut values_serial);
+    interpolate_at_points_par(&space, &points, u.as_view(), &mut values_par);
+    assert_eq!(values_par, values_serial);
+
+    let mut gradients_serial = vec![Matrix3x2::zeros(); points.len()];
+    let mut gradients_par = vec![Matrix3x2::repeat(f64::NAN); points.len()];
+    interpolate_gradient_at_points(&space, &points, u.as_view(), &mut gradients_serial);
+    interpolate_gradient_at_points_par(&space, &points, u.as_view(), &mut gradients_par);
+    assert_eq!(gradients_par, gradients_serial);
+}
+
+#[test]
+fn spatially_indexed_classify_points_straddling_boundary_of_hex_mesh() {
+    let mesh: HexMesh<f64> = create_unit_box_uniform_hex_mesh_3d(3);
+    let space = SpatiallyIndexed::from_space(mesh);
+    let tolerance = 1e-3;
+
+    let inside_points = [[0.5, 0.5, 0.5], [1.0, 0.5, 0.5], [0.9995, 0.2, 0.3], [0.0, 0.0, 0.0]];
+    let on_boundary_points = [[1.0005, 0.2, 0.3], [0.4, -1e-4, 0.6], [0.3, 0.7, 1.0 + 0.9e-3]];
+    let outside_points = [[1.01, 0.2, 0.3], [0.4, -0.5, 0.6], [1.1, 1.1, -0.1]];
+
+    let classify = |p: [f64; 3]| {
+        let p = Point3::from(p);
+        let classification = classify_point(&space, &p, tolerance).unwrap();
+        let (element_index, xi, closest_point) = space.find_closest_point(&p).unwrap();
+        assert_eq!(classification.element_index(), element_index);
+        assert_eq!(classification.reference_coords(), &xi);
+        assert_matrix_eq!(
+            space
+                .map_element_reference_coords(element_index, &xi)
+                .coords,
+            closest_point.coords
+        );
+
+        // The closest point is the projection of the point onto the unit box
+        let projection = p.map(|x_i| x_i.clamp(0.0, 1.0));
+        assert_matrix_eq!(closest_point.coords, projection.coords, comp = abs, tol = 1e-12);
+        assert_scalar_eq!(
+            classification.distance(),
+            (projection - p).norm(),
+            comp = abs,
+            tol = 1e-12
+        );
+        classification
+    };
+
+    for p in inside_points {
+        let classification = classify(p);
+        assert!(matches!(classification, PointClassification::Inside { .. }));
+        assert!(classification.is_inside());
+        assert_eq!(classification.distance(), 0.0);
+    }
+
+    for p in on_boundary_points {
+        let classification = classify(p);
+        assert!(matches!(classification, PointClassification::OnBoundary { .. }));
+        assert!(!classification.is_inside());
+        assert!(classification.distance() > 0.0);
+    }
+
+    for p in outside_points {
+        let classification = classify(p);
+        assert!(matches!(classification, PointClassification::Outside { .. }));
+        assert!(classification.distance() > tolerance);
+    }
+
+    let points: Vec<_> = inside_points
+        .iter()
+        .chain(&on_boundary_points)
+        .chain(&outside_points)
+        .map(|&p| Point3::from(p))
+        .collect();
+    let classifications = classify_points(&space, &points, tolerance).unwrap();
+    let expected: Vec<_> = points
+        .iter()
+        .map(|p| classify_point(&space, p, tolerance).unwrap())
+        .collect();
+    assert_eq!(classifications, expected);
+
+    // With zero tolerance, every point outside the domain is classified as outside
+    for p in &points[inside_points.len()..] {
+        let classification = classify_point(&space, p, 0.0).unwrap();
+        assert!(matches!(classification, PointClassification::Outside { .. }));
+    }
+}
+
+#[test]
+fn classify_points_in_empty_space() {
+    let mesh: QuadMesh2d<f64> = Mesh::from_vertices_and_connectivity(Vec::new(), Vec::new());
+    let space = SpatiallyIndexed::from_space(mesh);
+    assert!(classify_point(&space, &Point2::new(0.5, 0.5), 1e-3).is_none());
+    assert!(space.find_closest_point(&Point2::new(0.5, 0.5)).is_none());
+}
@@ -0,0 +1,3 @@
+mod const_dim;
+mod csr_blocks;
+mod small_eig;
@@ -0,0 +1,52 @@
+use fenris::nalgebra::{Matrix1, Matrix2, Matrix3, OMatrix, Vector2, Vector3, U1, U2, U3, U4};
+use fenris::util::{with_const_dim, ConstDim};
+
+#[test]
+fn with_const_dim_dispatches_on_dimension() {
+    assert_eq!(with_const_dim::<U1, _>(|_| 1, |_| 2, |_| 3), Ok(1));
+    assert_eq!(with_const_dim::<U2, _>(|_| 1, |_| 2, |_| 3), Ok(2));
+    assert_eq!(with_const_dim::<U3, _>(|_| 1, |_| 2, |_| 3), Ok(3));
+}
+
+#[test]
+fn with_const_dim_views_generic_values_with_fixed_dimension() {
+    let m1 = OMatrix::<f64, U1, U1>::new(5.0);
+    let m1_fixed: Matrix1<f64> =
+        with_const_dim::<U1, _>(|dim| *dim.matrix(&m1), |_| unreachable!(), |_| unreachable!()).unwrap();
+    assert_eq!(m1_fixed, Matrix1::new(5.0));
+
+    let m2 = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+    let v2 = Vector2::new(5.0, 6.0);
+    let product = with_const_dim::<U2, _>(
+        |_| unreachable!(),
+        |dim| dim.generic_vector(dim.matrix(&m2) * dim.vector(&v2)),
+        |_| unreachable!(),
+    )
+    .unwrap();
+    assert_eq!(product, m2 * v2);
+
+    let m3 = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0);
+    let v3 = Vector3::new(1.0, -1.0, 2.0);
+    let outer = with_const_dim::<U3, _>(
+        |_| unreachable!(),
+        |_| unreachable!(),
+        |dim| dim.generic_matrix(dim.matrix(&m3) * dim.vector(&v3) * dim.vector(&v3).transpose()),
+    )
+    .unwrap();
+    assert_eq!(outer, m3 * v3 * v3.transpose());
+}
+
+#[test]
+fn with_const_dim_fails_for_unsupported_dimension() {
+    let error = with_const_dim::<U4, ()>(|_| unreachable!(), |_| unreachable!(), |_| unreachable!()).unwrap_err();
+    assert_eq!(error.dim(), 4);
+    assert_eq!(error.supported(), &(1..=3));
+}
+
+#[test]
+fn const_dim_try_new_checks_dimension() {
+    assert!(ConstDim::<U2, 2>::try_new().is_ok());
+    let error = ConstDim::<U2, 3>::try_new().unwrap_err();
+    assert_eq!(error.dim(), 2);
+    assert_eq!(error.supported(), &(3..=3));
+}
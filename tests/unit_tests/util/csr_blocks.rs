@@ -0,0 +1,97 @@
+use fenris::nalgebra::DVector;
+use fenris::nalgebra_sparse::{CooMatrix, CsrMatrix};
+use fenris::util::{csr_row_blocks, par_row_blocks, par_spmv_into, spmv_into};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use rayon::iter::ParallelIterator;
+
+/// Sparse matrices with short rows (at most 9 entries per row before duplicates are summed),
+/// including empty rows.
+fn fem_like_matrix() -> impl Strategy<Value = CsrMatrix<f64>> {
+    (0..600usize, 1..600usize).prop_flat_map(|(nrows, ncols)| {
+        let row_entries = vec((0..ncols, -10.0..10.0), 0..=9);
+        vec(row_entries, nrows).prop_map(move |rows| {
+            let mut coo = CooMatrix::new(nrows, ncols);
+            for (i, row) in rows.into_iter().enumerate() {
+                for (j, v) in row {
+                    coo.push(i, j, v);
+                }
+            }
+            CsrMatrix::from(&coo)
+        })
+    })
+}
+
+fn matrix_and_vector() -> impl Strategy<Value = (CsrMatrix<f64>, DVector<f64>)> {
+    fem_like_matrix().prop_flat_map(|matrix| {
+        let x = vec(-10.0..10.0, matrix.ncols()).prop_map(DVector::from_vec);
+        (Just(matrix), x)
+    })
+}
+
+#[test]
+fn csr_row_blocks_handles_empty_matrix() {
+    let matrix = CsrMatrix::<f64>::zeros(0, 3);
+    assert_eq!(csr_row_blocks(&matrix, 4).count(), 0);
+    assert_eq!(par_row_blocks(&matrix, 4).count(), 0);
+
+    let mut y = DVector::zeros(0);
+    spmv_into(&matrix, &DVector::zeros(3), &mut y);
+}
+
+#[test]
+#[should_panic]
+fn csr_row_blocks_panics_on_zero_block_size() {
+    let matrix = CsrMatrix::<f64>::identity(3);
+    csr_row_blocks(&matrix, 0);
+}
+
+proptest! {
+    #[test]
+    fn csr_row_blocks_cover_all_rows(matrix in fem_like_matrix(), block_size in 1..100usize) {
+        let blocks: Vec<_> = csr_row_blocks(&matrix, block_size).collect();
+        prop_assert_eq!(blocks.len(), matrix.nrows().div_ceil(block_size));
+
+        let mut next_row = 0;
+        for block in &blocks {
+            let range = block.row_range();
+            prop_assert_eq!(range.start, next_row);
+            prop_assert!(block.nrows() == block_size || range.end == matrix.nrows());
+            prop_assert_eq!(block.nnz(), block.values().len());
+            prop_assert_eq!(block.nnz(), block.col_indices().len());
+            for (i, (col_indices, values)) in range.clone().zip(block.rows()) {
+                let row = matrix.row(i);
+                prop_assert_eq!(col_indices, row.col_indices());
+                prop_assert_eq!(values, row.values());
+            }
+            next_row = range.end;
+        }
+        prop_assert_eq!(next_row, matrix.nrows());
+
+        // The parallel blocks are identical to the serial blocks
+        let par_blocks: Vec<_> = par_row_blocks(&matrix, block_size).collect();
+        prop_assert_eq!(par_blocks.len(), blocks.len());
+        for (par_block, block) in par_blocks.iter().zip(&blocks) {
+            prop_assert_eq!(par_block.row_range(), block.row_range());
+            prop_assert_eq!(par_block.col_indices(), block.col_indices());
+            prop_assert_eq!(par_block.values(), block.values());
+        }
+    }
+
+    #[test]
+    fn spmv_into_matches_nalgebra_sparse((matrix, x) in matrix_and_vector()) {
+        let expected = &matrix * &x;
+
+        // Fill with garbage to make sure that y is overwritten
+        let mut y = DVector::repeat(matrix.nrows(), f64::NAN);
+        spmv_into(&matrix, &x, &mut y);
+        prop_assert_eq!(&y, &expected);
+
+        // The parallel version is deterministic and identical to the serial version
+        for _ in 0..3 {
+            let mut y_par = DVector::repeat(matrix.nrows(), f64::NAN);
+            par_spmv_into(&matrix, &x, &mut y_par);
+            prop_assert_eq!(&y_par, &y);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use fenris::assembly::global::CsrAssembler;
+use fenris::assembly::local::{Density, ElementMassAssembler, UniformQuadratureTable};
+use fenris::connectivity::Quad4d2Connectivity;
+use fenris::mesh::QuadMesh2d;
+use fenris::nalgebra::{DMatrix, DMatrixViewMut, DVector, Point2};
+use fenris::quadrature;
+use fenris::util::small_eig::{
+    generalized_symmetric_eigen, generalized_symmetric_eigenvalues, project_symmetric_onto_min_eigenvalue,
+    symmetric_eigen, symmetric_eigen_in_place, symmetric_eigenvalues,
+};
+use matrixcompare::assert_matrix_eq;
+use std::f64::consts::PI;
+
+/// The tridiagonal matrix with stencil `[off_diag, diag, off_diag]`.
+fn tridiagonal(n: usize, diag: f64, off_diag: f64) -> DMatrix<f64> {
+    DMatrix::from_fn(n, n, |i, j| {
+        if i == j {
+            diag
+        } else if i.abs_diff(j) == 1 {
+            off_diag
+        } else {
+            0.0
+        }
+    })
+}
+
+/// Some arbitrary symmetric matrix.
+fn arbitrary_symmetric_matrix(n: usize) -> DMatrix<f64> {
+    let a = DMatrix::from_fn(n, n, |i, j| f64::sin(1.3 * i as f64 + 0.7 * (j * j) as f64));
+    &a + a.transpose()
+}
+
+fn assert_relative_eq(eigenvalues: &DVector<f64>, expected: &DVector<f64>, tol: f64) {
+    assert_eq!(eigenvalues.len(), expected.len());
+    for (lambda, lambda_expected) in eigenvalues.iter().zip(expected.iter()) {
+        assert!(
+            (lambda - lambda_expected).abs() <= tol * lambda_expected.abs(),
+            "Eigenvalue {lambda} differs from expected eigenvalue {lambda_expected}"
+        );
+    }
+}
+
+#[test]
+fn symmetric_eigenvalues_of_discrete_laplacian() {
+    for n in [1, 2, 5, 10, 50, 100] {
+        // The eigenvalues of the 1D discrete Laplacian tridiag(-1, 2, -1) are
+        // 4 sin^2(k pi / (2 (n + 1))), k = 1, ..., n
+        let laplacian = tridiagonal(n, 2.0, -1.0);
+        let expected = DVector::from_fn(n, |k, _| {
+            4.0 * f64::sin((k + 1) as f64 * PI / (2.0 * (n + 1) as f64)).powi(2)
+        });
+
+        let eigenvalues = symmetric_eigenvalues(&laplacian);
+        assert_matrix_eq!(eigenvalues, expected, comp = abs, tol = 1e-12);
+
+        // The smallest eigenvalues are tiny for large n, but must still be accurate
+        // in a relative sense
+        assert_relative_eq(&eigenvalues, &expected, 1e-10);
+    }
+}
+
+#[test]
+fn symmetric_eigen_decomposition_is_orthonormal_and_recomposes_matrix() {
+    for n in [1, 3, 8, 30] {
+        let a = arbitrary_symmetric_matrix(n);
+        let eigen = symmetric_eigen(&a);
+
+        // Eigenvalues are sorted in ascending order
+        assert!(eigen
+            .eigenvalues
+            .as_slice()
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+
+        let v = &eigen.eigenvectors;
+        assert_matrix_eq!(v.transpose() * v, DMatrix::identity(n, n), comp = abs, tol = 1e-12);
+        assert_matrix_eq!(eigen.recompose(), a, comp = abs, tol = 1e-12);
+        for (lambda, v_i) in eigen.eigenvalues.iter().zip(v.column_iter()) {
+            assert_matrix_eq!(&a * v_i, *lambda * v_i, comp = abs, tol = 1e-12);
+        }
+    }
+}
+
+#[test]
+fn symmetric_eigen_in_place_on_matrix_view() {
+    // Only the upper triangle of the sub-block is accessed, so the lower triangle is
+    // filled with garbage
+    let n = 6;
+    let a = arbitrary_symmetric_matrix(n);
+    let mut storage = DMatrix::repeat(n + 2, n + 2, f64::NAN);
+    for j in 0..n {
+        for i in 0..=j {
+            storage[(i + 1, j + 2)] = a[(i, j)];
+        }
+    }
+
+    let mut eigenvectors = DMatrix::zeros(n, n);
+    symmetric_eigen_in_place(
+        storage.view_mut((1, 2), (n, n)),
+        Some(DMatrixViewMut::from(&mut eigenvectors)),
+    );
+
+    let expected = symmetric_eigen(&a);
+    assert_matrix_eq!(
+        storage.view((1, 2), (n, n)).diagonal(),
+        expected.eigenvalues,
+        comp = abs,
+        tol = 1e-12
+    );
+    for (lambda, v_i) in expected.eigenvalues.iter().zip(eigenvectors.column_iter()) {
+        assert_matrix_eq!(&a * v_i, *lambda * v_i, comp = abs, tol = 1e-12);
+    }
+}
+
+#[test]
+fn symmetric_eigenvalues_of_reference_quad_mass_matrix() {
+    // The mass matrix of the bilinear reference element [-1, 1]^2 is the Kronecker product
+    // M_1 ⊗ M_1 of the 1D mass matrices M_1 = [2, 1; 1, 2] / 3, whose eigenvalues are 1/3 and 1
+    let vertices = vec![
+        Point2::new(-1.0, -1.0),
+        Point2::new(1.0, -1.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(-1.0, 1.0),
+    ];
+    let mesh = QuadMesh2d::from_vertices_and_connectivity(vertices, vec![Quad4d2Connectivity([0, 1, 2, 3])]);
+    let qtable = UniformQuadratureTable::from_quadrature_and_uniform_data(
+        quadrature::tensor::quadrilateral_gauss(2),
+        Density(1.0),
+    );
+
+    {
+        let mass_assembler = ElementMassAssembler::with_solution_dim(1)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        let mass = DMatrix::from(&CsrAssembler::default().assemble(&mass_assembler).unwrap());
+        let expected = DVector::from_column_slice(&[1.0 / 9.0, 1.0 / 3.0, 1.0 / 3.0, 1.0]);
+        assert_matrix_eq!(symmetric_eigenvalues(&mass), expected, comp = abs, tol = 1e-14);
+    }
+
+    {
+        // For vector-valued problems, each eigenvalue has multiplicity 2
+        let mass_assembler = ElementMassAssembler::with_solution_dim(2)
+            .with_space(&mesh)
+            .with_quadrature_table(&qtable);
+        let mass = DMatrix::from(&CsrAssembler::default().assemble(&mass_assembler).unwrap());
+        let expected = DVector::from_column_slice(&[
+            1.0 / 9.0,
+            1.0 / 9.0,
+            1.0 / 3.0,
+            1.0 / 3.0,
+            1.0 / 3.0,
+            1.0 / 3.0,
+            1.0,
+            1.0,
+        ]);
+        let eigen = symmetric_eigen(&mass);
+        assert_matrix_eq!(eigen.eigenvalues, expected, comp = abs, tol = 1e-14);
+        assert_matrix_eq!(eigen.recompose(), mass, comp = abs, tol = 1e-14);
+    }
+}
+
+#[test]
+fn generalized_symmetric_eigenvalues_of_1d_finite_element_laplacian() {
+    for n in [1, 4, 20, 60] {
+        // Linear finite elements on a uniform grid of [0, 1] with homogeneous Dirichlet conditions.
+        // The eigenvalues of K x = lambda M x are (6 / h^2) (1 - cos(theta_k)) / (2 + cos(theta_k)),
+        // with theta_k = k pi / (n + 1)
+        let h = 1.0 / (n + 1) as f64;
+        let stiffness = tridiagonal(n, 2.0 / h, -1.0 / h);
+        let mass = tridiagonal(n, 4.0 * h / 6.0, h / 6.0);
+        let expected = DVector::from_fn(n, |k, _| {
+            let theta = (k + 1) as f64 * PI / (n + 1) as f64;
+            (6.0 / (h * h)) * (1.0 - theta.cos()) / (2.0 + theta.cos())
+        });
+
+        let eigenvalues = generalized_symmetric_eigenvalues(&stiffness, &mass).unwrap();
+        assert_relative_eq(&eigenvalues, &expected, 1e-10);
+
+        let eigen = generalized_symmetric_eigen(&stiffness, &mass).unwrap();
+        assert_relative_eq(&eigen.eigenvalues, &expected, 1e-10);
+        // Eigenvectors are M-orthonormal
+        let x = &eigen.eigenvectors;
+        assert_matrix_eq!(
+            x.transpose() * &mass * x,
+            DMatrix::identity(n, n),
+            comp = abs,
+            tol = 1e-10
+        );
+        for (lambda, x_i) in eigen.eigenvalues.iter().zip(x.column_iter()) {
+            let residual = &stiffness * x_i - *lambda * (&mass * x_i);
+            assert!(residual.norm() <= 1e-10 * lambda);
+        }
+    }
+}
+
+#[test]
+fn generalized_symmetric_eigen_fails_for_indefinite_mass() {
+    let stiffness = tridiagonal(3, 2.0, -1.0);
+    let mass = tridiagonal(3, 1.0, 2.0);
+    assert!(generalized_symmetric_eigenvalues(&stiffness, &mass).is_err());
+    assert!(generalized_symmetric_eigen(&stiffness, &mass).is_err());
+}
+
+#[test]
+fn project_symmetric_onto_min_eigenvalue_clamps_spectrum() {
+    let n = 10;
+    let a = arbitrary_symmetric_matrix(n);
+    let eigen = symmetric_eigen(&a);
+    assert!(eigen.eigenvalues[0] < 0.0);
+
+    let mut projected = a.clone();
+    project_symmetric_onto_min_eigenvalue(&mut projected, 0.0);
+    let projected_eigen = symmetric_eigen(&projected);
+    let expected = eigen.eigenvalues.map(|lambda| lambda.max(0.0));
+    assert_matrix_eq!(projected_eigen.eigenvalues, expected, comp = abs, tol = 1e-12);
+    assert_matrix_eq!(projected, projected.transpose(), comp = abs, tol = 1e-14);
+
+    // Matrices that already satisfy the bound are left untouched
+    let laplacian = tridiagonal(n, 2.0, -1.0);
+    let mut projected_laplacian = laplacian.clone();
+    project_symmetric_onto_min_eigenvalue(&mut projected_laplacian, 0.0);
+    assert_eq!(projected_laplacian, laplacian);
+}